@@ -0,0 +1,53 @@
+use bevy::math::Vec3;
+use bevy_prototype_animation::curve::{compressed::CompressedFloat32x3Curve, Curve, CurveFixed};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::prelude::*;
+
+criterion_group!(benches, batch_sampling);
+criterion_main!(benches);
+
+/// The number of characters in the crowd, each sampling the same curve at its
+/// own time.
+const CROWD_SIZE: usize = 256;
+
+fn scalar_sampling(curve: &impl Curve<Vec3>, times: &[f32], out: &mut [Vec3]) {
+    for (time, out) in times.iter().zip(out.iter_mut()) {
+        *out = curve.sample(*time);
+    }
+}
+
+fn batch_sampling(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("batch_sampling");
+    group.warm_up_time(std::time::Duration::from_millis(500));
+    group.measurement_time(std::time::Duration::from_secs(3));
+
+    let mut rand = rand::thread_rng();
+    let fixed = CurveFixed::from_keyframes(
+        30.0,
+        (0..90)
+            .map(|_| Vec3::new(rand.gen(), rand.gen(), rand.gen()))
+            .collect(),
+    );
+    let compressed = CompressedFloat32x3Curve::quantize(fixed.clone());
+
+    let duration = fixed.duration();
+    let times = (0..CROWD_SIZE)
+        .map(|_| duration * rand.gen::<f32>())
+        .collect::<Vec<_>>();
+    let mut out = vec![Vec3::ZERO; CROWD_SIZE];
+
+    group.bench_function("fixed_scalar", |bencher| {
+        bencher.iter(|| scalar_sampling(&fixed, black_box(&times), &mut out));
+    });
+    group.bench_function("fixed_batch", |bencher| {
+        bencher.iter(|| fixed.sample_batch(black_box(&times), &mut out));
+    });
+    group.bench_function("compressed_scalar", |bencher| {
+        bencher.iter(|| scalar_sampling(&compressed, black_box(&times), &mut out));
+    });
+    group.bench_function("compressed_batch", |bencher| {
+        bencher.iter(|| compressed.sample_batch(black_box(&times), &mut out));
+    });
+
+    group.finish()
+}
@@ -0,0 +1,102 @@
+use bevy::{
+    asset::AssetPlugin,
+    prelude::*,
+    tasks::{ComputeTaskPool, IoTaskPool, TaskPool},
+};
+use bevy_prototype_animation::{
+    curve::CurveFixed, path::PropertyPath, prelude::*, AnimationPlugin,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+criterion_group!(benches, bind_hierarchy);
+criterion_main!(benches);
+
+/// The number of chains of bones beneath the root, like the limbs of a rig.
+const CHAINS: usize = 5;
+/// The number of bones in each chain.
+const CHAIN_LENGTH: usize = 30;
+/// The number of named, but unanimated children of each bone.
+const EXTRA_CHILDREN: usize = 16;
+
+fn spawn_chain(world: &mut World, chain: usize, depth: usize) -> Entity {
+    let mut children: Vec<_> = (0..EXTRA_CHILDREN)
+        .map(|idx| {
+            world
+                .spawn()
+                .insert(Name::new(format!("extra{}", idx)))
+                .id()
+        })
+        .collect();
+    if depth + 1 < CHAIN_LENGTH {
+        children.push(spawn_chain(world, chain, depth + 1));
+    }
+    world
+        .spawn()
+        .insert(Name::new(format!("chain{}_{}", chain, depth)))
+        .insert(Transform::default())
+        .push_children(&children)
+        .id()
+}
+
+fn bone_paths() -> Vec<String> {
+    let mut paths = Vec::new();
+    for chain in 0..CHAINS {
+        let mut path = String::new();
+        for depth in 0..CHAIN_LENGTH {
+            if depth > 0 {
+                path.push('/');
+            }
+            path.push_str(&format!("chain{}_{}", chain, depth));
+            paths.push(path.clone());
+        }
+    }
+    paths
+}
+
+fn bind_hierarchy(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("bind_hierarchy");
+    group.warm_up_time(std::time::Duration::from_millis(500));
+    group.measurement_time(std::time::Duration::from_secs(3));
+
+    let mut app = App::new();
+    app.insert_resource(IoTaskPool(TaskPool::new()))
+        .insert_resource(ComputeTaskPool(TaskPool::new()))
+        .add_plugin(AssetPlugin)
+        .add_plugin(AnimationPlugin::default().without_application())
+        .register_type::<Transform>();
+
+    let mut builder = AnimationClip::builder();
+    {
+        let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+        let registry = registry.read();
+        for path in bone_paths() {
+            let path = format!(
+                "{}@bevy_transform::components::transform::Transform.translation",
+                path
+            );
+            let path = PropertyPath::parse(&registry, &path).unwrap();
+            builder = builder.add_curve(path, CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO]));
+        }
+    }
+    let clip = builder.build();
+    let mut graph = AnimationGraph::new();
+    graph.add_clip(&clip).unwrap();
+
+    let chains: Vec<_> = (0..CHAINS)
+        .map(|chain| spawn_chain(&mut app.world, chain, 0))
+        .collect();
+    app.world.spawn().insert(graph).push_children(&chains);
+    app.update();
+
+    group.bench_function("150_bones", |bencher| {
+        bencher.iter(|| {
+            // Renaming an entity in the hierarchy forces the graph to rebind.
+            app.world
+                .entity_mut(chains[0])
+                .insert(Name::new("chain0_0"));
+            app.update();
+        });
+    });
+
+    group.finish()
+}
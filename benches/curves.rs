@@ -1,5 +1,5 @@
 use bevy::math::Vec4;
-use bevy_prototype_animation::curves::{Curve, CurveVariable, KeyframeIndex};
+use bevy_prototype_animation::curve::{Curve, CurveVariableLinear, KeyframeIndex};
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use rand::prelude::*;
 
@@ -8,7 +8,7 @@ criterion_main!(benches);
 
 const SAMPLES_COUNT: usize = 100;
 
-fn curve_sampling<T>(samples: &[f32], curve: &impl Curve<Output = T>) {
+fn curve_sampling<T>(samples: &[f32], curve: &impl Curve<T>) {
     let mut c: KeyframeIndex = 0;
     for t in samples {
         let (nc, v) = curve.sample_with_cursor(c, *t);
@@ -22,7 +22,7 @@ fn curve_variable(criterion: &mut Criterion) {
     group.warm_up_time(std::time::Duration::from_millis(500));
     group.measurement_time(std::time::Duration::from_secs(3));
 
-    let curve = CurveVariable::with_auto_tangents(
+    let curve = CurveVariableLinear::with_keyframes(
         vec![0.0, 1.0, 1.3, 1.6, 1.7, 1.8, 1.9, 2.0],
         vec![3.0, 0.0, 1.0, 0.0, 0.5, 0.0, 0.25, 0.0]
             .iter()
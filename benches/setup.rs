@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use bevy_prototype_animation::{curve::CurveFixed, path::PropertyPath, prelude::*};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+criterion_group!(benches, add_clips);
+criterion_main!(benches);
+
+/// The number of chains of bones beneath the root, like the limbs of a rig.
+const CHAINS: usize = 5;
+/// The number of bones in each chain.
+const CHAIN_LENGTH: usize = 30;
+/// The number of clips added to each graph, like the moveset of a character.
+const CLIPS: usize = 30;
+
+/// Builds clips animating the translation and rotation of every bone.
+fn rig_clips() -> Vec<AnimationClip> {
+    let mut bones = Vec::with_capacity(CHAINS * CHAIN_LENGTH);
+    for chain in 0..CHAINS {
+        let mut path = String::new();
+        for depth in 0..CHAIN_LENGTH {
+            if depth > 0 {
+                path.push('/');
+            }
+            path.push_str(&format!("c{}_{}", chain, depth));
+            bones.push(path.clone());
+        }
+    }
+
+    (0..CLIPS)
+        .map(|clip| {
+            let mut builder = AnimationClip::builder();
+            for (idx, bone) in bones.iter().enumerate() {
+                let offset = (clip + idx) as f32;
+                builder = builder
+                    .add_curve(
+                        PropertyPath::new::<Transform>(bone, "translation").unwrap(),
+                        CurveFixed::from_keyframes(
+                            30.0,
+                            (0..30).map(|x| Vec3::splat(offset + x as f32)).collect(),
+                        ),
+                    )
+                    .add_curve(
+                        PropertyPath::new::<Transform>(bone, "rotation").unwrap(),
+                        CurveFixed::from_keyframes(
+                            30.0,
+                            (0..30)
+                                .map(|x| Quat::from_rotation_y(offset + x as f32))
+                                .collect(),
+                        ),
+                    );
+            }
+            builder.build()
+        })
+        .collect()
+}
+
+fn add_clips(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("graph_setup");
+    group.warm_up_time(std::time::Duration::from_millis(500));
+    group.measurement_time(std::time::Duration::from_secs(3));
+
+    let clips = rig_clips();
+    group.bench_function("add_clip_loop", |bencher| {
+        bencher.iter(|| {
+            let mut graph = AnimationGraph::new();
+            for clip in clips.iter() {
+                graph.add_clip(clip).unwrap();
+            }
+            black_box(graph)
+        })
+    });
+    group.bench_function("add_clips", |bencher| {
+        bencher.iter(|| {
+            let mut graph = AnimationGraph::new();
+            graph.add_clips(&clips).unwrap();
+            black_box(graph)
+        })
+    });
+
+    group.finish();
+}
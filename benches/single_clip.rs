@@ -0,0 +1,81 @@
+use bevy::{
+    math::{Quat, Vec3},
+    transform::prelude::Transform,
+};
+use bevy_prototype_animation::{
+    curve::CurveFixed,
+    graph::{pose::PoseBuffer, NodeId, PlaybackMode},
+    path::PropertyPath,
+    prelude::*,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+criterion_group!(benches, single_clip);
+criterion_main!(benches);
+
+/// The number of bones animated by the clip.
+const BONES: usize = 100;
+
+/// Builds a graph playing a single clip, animating the whole transform of
+/// every bone, with the given weight.
+fn skeleton_graph(weight: f32) -> AnimationGraph {
+    let mut builder = AnimationClip::builder();
+    for bone in 0..BONES {
+        let entity = format!("root/bone_{}", bone);
+        let offset = bone as f32 * 0.1;
+        builder = builder
+            .add_curve(
+                PropertyPath::new::<Transform>(&entity, "translation").unwrap(),
+                CurveFixed::from_keyframes(
+                    30.0,
+                    (0..30)
+                        .map(|frame| Vec3::new((frame as f32 * 0.1 + offset).sin(), 0.0, 1.0))
+                        .collect(),
+                ),
+            )
+            .add_curve(
+                PropertyPath::new::<Transform>(&entity, "rotation").unwrap(),
+                CurveFixed::from_keyframes(
+                    30.0,
+                    (0..30)
+                        .map(|frame| Quat::from_rotation_y(frame as f32 * 0.1 + offset))
+                        .collect(),
+                ),
+            )
+            .add_curve(
+                PropertyPath::new::<Transform>(&entity, "scale").unwrap(),
+                CurveFixed::from_keyframes(30.0, vec![Vec3::ONE; 30]),
+            );
+    }
+
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(&builder.build()).unwrap();
+    graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+    graph
+        .add_input(NodeId::ROOT, node)
+        .unwrap()
+        .set_weight(weight);
+    graph
+}
+
+fn single_clip(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("single_clip");
+    group.warm_up_time(std::time::Duration::from_millis(500));
+    group.measurement_time(std::time::Duration::from_secs(3));
+
+    // A weight just short of 1 is blended like any other, as the rest of the
+    // weight goes to the default values.
+    for (name, weight) in [("blended", 0.999), ("sampled_directly", 1.0)] {
+        let mut graph = skeleton_graph(weight);
+        let mut pose = PoseBuffer::new();
+        group.bench_function(name, |bencher| {
+            bencher.iter(|| {
+                graph.advance_time(0.01);
+                graph.evaluate();
+                graph.sample_pose(&mut pose);
+            })
+        });
+    }
+
+    group.finish();
+}
@@ -0,0 +1,111 @@
+use bevy::{
+    asset::AssetPlugin,
+    core::CorePlugin,
+    prelude::*,
+    tasks::{ComputeTaskPool, IoTaskPool, TaskPool},
+    transform::TransformPlugin,
+};
+use bevy_prototype_animation::{
+    curve::CurveFixed,
+    graph::{NodeId, PlaybackMode},
+    path::PropertyPath,
+    prelude::*,
+    AnimationPlugin,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+criterion_group!(benches, split_bone);
+criterion_main!(benches);
+
+/// The number of tracks animating the single bone, like the blend shapes of
+/// a face.
+const TRACKS: usize = 2000;
+
+#[derive(Component, Reflect, Clone)]
+#[reflect(Component)]
+struct Blendshapes {
+    weights: Vec<f32>,
+}
+
+impl Default for Blendshapes {
+    fn default() -> Self {
+        Self {
+            weights: vec![0.0; TRACKS],
+        }
+    }
+}
+
+/// Builds an app with a single graph blending several clips, each animating
+/// every weight of a single bone.
+fn face_app(threshold: Option<usize>) -> App {
+    let mut app = App::new();
+    app.insert_resource(IoTaskPool(TaskPool::new()))
+        .insert_resource(ComputeTaskPool(TaskPool::new()))
+        .add_plugin(CorePlugin)
+        .add_plugin(TransformPlugin)
+        .add_plugin(AssetPlugin)
+        .add_plugin(AnimationPlugin::default())
+        .register_type::<Blendshapes>();
+
+    let mut graph = AnimationGraph::new();
+    graph.set_track_split_threshold(threshold);
+    for clip in 0..4 {
+        let mut builder = AnimationClip::builder();
+        {
+            let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+            let registry = registry.read();
+            for idx in 0..TRACKS {
+                let path = format!("face@split::Blendshapes.weights[{}]", idx);
+                let keyframes = (0..30)
+                    .map(|frame| ((frame * (clip + 1) + idx) as f32 * 0.1).sin())
+                    .collect();
+                builder = builder.add_curve(
+                    PropertyPath::parse(&registry, &path).unwrap(),
+                    CurveFixed::from_keyframes(30.0, keyframes),
+                );
+            }
+        }
+        let node = graph.add_clip(&builder.build()).unwrap();
+        graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+        graph
+            .add_input(NodeId::ROOT, node)
+            .unwrap()
+            .set_weight(0.25);
+    }
+
+    let face = app
+        .world
+        .spawn()
+        .insert(Name::new("face"))
+        .insert(Blendshapes::default())
+        .id();
+    app.world.spawn().insert(graph).push_children(&[face]);
+    // Binds the graph.
+    app.update();
+    app
+}
+
+fn step(app: &mut App) {
+    let mut graphs = app.world.query::<&mut AnimationGraph>();
+    for mut graph in graphs.iter_mut(&mut app.world) {
+        graph.advance_time(0.01);
+    }
+    app.update();
+}
+
+fn split_bone(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("split_bone");
+    group.warm_up_time(std::time::Duration::from_millis(500));
+    group.measurement_time(std::time::Duration::from_secs(3));
+
+    for (name, threshold) in [
+        ("unsplit", None),
+        ("batches_of_500", Some(500)),
+        ("batches_of_125", Some(125)),
+    ] {
+        let mut app = face_app(threshold);
+        group.bench_function(name, |bencher| bencher.iter(|| step(&mut app)));
+    }
+
+    group.finish();
+}
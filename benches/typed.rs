@@ -0,0 +1,124 @@
+use bevy::{
+    asset::AssetPlugin,
+    core::CorePlugin,
+    prelude::*,
+    tasks::{ComputeTaskPool, IoTaskPool, TaskPool},
+    transform::TransformPlugin,
+};
+use bevy_prototype_animation::{
+    animate_component,
+    curve::CurveFixed,
+    graph::{
+        application::animate_entities_system, typed::TypedAnimationPlugin, NodeId, PlaybackMode,
+    },
+    path::PropertyPath,
+    prelude::*,
+    AnimationPlugin,
+};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+criterion_group!(benches, application);
+criterion_main!(benches);
+
+/// The number of animated lamps beneath the graph.
+const LAMPS: usize = 512;
+
+#[derive(Component, Reflect, Clone, Default)]
+#[reflect(Component)]
+struct ReflectedGlow {
+    intensity: f32,
+    offset: Vec3,
+}
+
+#[derive(Component, Reflect, Clone, Default)]
+#[reflect(Component)]
+struct TypedGlow {
+    intensity: f32,
+    offset: Vec3,
+}
+
+animate_component!(TypedGlow {
+    intensity: f32,
+    offset: Vec3
+});
+
+/// Builds an app animating the `intensity` and `offset` of a `C` on each lamp.
+/// Only the application system added by `configure` runs, so the apps only
+/// differ in how the values are applied.
+fn glow_app<C: Component + Default>(component: &str, configure: impl FnOnce(&mut App)) -> App {
+    let mut app = App::new();
+    app.insert_resource(IoTaskPool(TaskPool::new()))
+        .insert_resource(ComputeTaskPool(TaskPool::new()))
+        .add_plugin(CorePlugin)
+        .add_plugin(TransformPlugin)
+        .add_plugin(AssetPlugin)
+        .add_plugin(AnimationPlugin::default().without_application());
+    configure(&mut app);
+
+    let mut builder = AnimationClip::builder();
+    {
+        let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+        let registry = registry.read();
+        for idx in 0..LAMPS {
+            let path = |field| {
+                let path = format!("lamp{}@typed::{}.{}", idx, component, field);
+                PropertyPath::parse(&registry, &path).unwrap()
+            };
+            builder = builder
+                .add_curve(
+                    path("intensity"),
+                    CurveFixed::from_keyframes(1.0, vec![0.0f32, idx as f32]),
+                )
+                .add_curve(
+                    path("offset"),
+                    CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::ONE * idx as f32]),
+                );
+        }
+    }
+    let clip = builder.build();
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(&clip).unwrap();
+    graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+
+    let lamps: Vec<_> = (0..LAMPS)
+        .map(|idx| {
+            app.world
+                .spawn()
+                .insert(Name::new(format!("lamp{}", idx)))
+                .insert(C::default())
+                .id()
+        })
+        .collect();
+    app.world.spawn().insert(graph).push_children(&lamps);
+    // Binds the graph.
+    app.update();
+    app
+}
+
+fn step(app: &mut App) {
+    let mut graphs = app.world.query::<&mut AnimationGraph>();
+    for mut graph in graphs.iter_mut(&mut app.world) {
+        graph.advance_time(0.01);
+    }
+    app.update();
+}
+
+fn application(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("application");
+    group.warm_up_time(std::time::Duration::from_millis(500));
+    group.measurement_time(std::time::Duration::from_secs(3));
+
+    let mut reflected = glow_app::<ReflectedGlow>("ReflectedGlow", |app| {
+        app.register_type::<ReflectedGlow>()
+            .add_system(animate_entities_system.exclusive_system().at_end());
+    });
+    group.bench_function("reflected", |bencher| bencher.iter(|| step(&mut reflected)));
+
+    let mut typed = glow_app::<TypedGlow>("TypedGlow", |app| {
+        app.add_plugin(TypedAnimationPlugin::<TypedGlow>::default());
+    });
+    group.bench_function("typed", |bencher| bencher.iter(|| step(&mut typed)));
+
+    group.finish();
+}
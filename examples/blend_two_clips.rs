@@ -0,0 +1,101 @@
+//! Blends a clip moving a cube side to side with one moving it up and down,
+//! shifting the weights between them over time with a graph parameter.
+//!
+//! Run with `cargo run --example blend_two_clips`.
+
+use bevy::{prelude::*, reflect::TypeRegistryArc};
+use bevy_prototype_animation::{
+    clip::AnimationClip,
+    curve::CurveFixed,
+    graph::{AnimationGraph, NodeId, PlaybackMode, WeightBinding},
+    path::PropertyPath,
+    AnimationPlugin,
+};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(AnimationPlugin::default())
+        .add_startup_system(setup)
+        .add_system(update_graphs)
+        .run();
+}
+
+/// Builds a looping clip moving the cube between `from` and `to`.
+fn back_and_forth(path: &PropertyPath, from: Vec3, to: Vec3) -> AnimationClip {
+    AnimationClip::builder()
+        .add_curve(
+            path.clone(),
+            CurveFixed::from_keyframes(1.0, vec![from, to, from]),
+        )
+        .build()
+}
+
+/// Binds an input's weight to the "blend" parameter, fading in as the
+/// parameter goes from `start` to `end`.
+fn blend_binding(start: f32, end: f32) -> Option<WeightBinding> {
+    Some(WeightBinding {
+        param: "blend".into(),
+        in_range: (start, end),
+        out_range: (0.0, 1.0),
+        clamp: true,
+    })
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    type_registry: Res<TypeRegistryArc>,
+) {
+    let path = PropertyPath::parse(
+        &type_registry.read(),
+        "cube@bevy_transform::components::transform::Transform.translation",
+    )
+    .unwrap();
+    let side_to_side = back_and_forth(&path, Vec3::X * -2.0, Vec3::X * 2.0);
+    let up_and_down = back_and_forth(&path, Vec3::Y * -2.0, Vec3::Y * 2.0);
+
+    let mut graph = AnimationGraph::new();
+    for (clip, binding) in [
+        (side_to_side, blend_binding(1.0, 0.0)),
+        (up_and_down, blend_binding(0.0, 1.0)),
+    ] {
+        let node = graph.add_clip(&clip).unwrap();
+        graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+        graph
+            .add_input(NodeId::ROOT, node)
+            .unwrap()
+            .set_weight_binding(binding);
+    }
+
+    commands.spawn_bundle(PerspectiveCameraBundle {
+        transform: Transform::from_xyz(0.0, 3.0, 8.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..Default::default()
+    });
+    commands.spawn_bundle(PointLightBundle {
+        transform: Transform::from_xyz(4.0, 8.0, 4.0),
+        ..Default::default()
+    });
+    commands
+        .spawn_bundle((Transform::identity(), GlobalTransform::identity()))
+        .insert(graph)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+                    material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+                    ..Default::default()
+                })
+                .insert(Name::new("cube"));
+        });
+}
+
+fn update_graphs(time: Res<Time>, mut graphs: Query<&mut AnimationGraph>) {
+    // Slowly shift between the two clips.
+    let blend = (time.seconds_since_startup() as f32 * 0.5).sin() * 0.5 + 0.5;
+    for mut graph in graphs.iter_mut() {
+        graph.set_param("blend", blend);
+        graph.advance_time(time.delta_seconds());
+    }
+}
@@ -0,0 +1,97 @@
+//! Animates a grid of cubes, each with its own graph, and logs the animation
+//! diagnostics alongside the frame time every second.
+//!
+//! Run with `cargo run --example diagnostics --features diagnostics`.
+
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+    reflect::TypeRegistryArc,
+};
+use bevy_prototype_animation::{
+    clip::AnimationClip,
+    curve::CurveFixed,
+    graph::{AnimationGraph, NodeId, PlaybackMode},
+    path::PropertyPath,
+    AnimationPlugin,
+};
+
+/// The number of cubes along each side of the grid.
+const GRID_SIZE: i32 = 10;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(AnimationPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(LogDiagnosticsPlugin::default())
+        .add_startup_system(setup)
+        .add_system(advance_graphs)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    type_registry: Res<TypeRegistryArc>,
+) {
+    let path = PropertyPath::parse(
+        &type_registry.read(),
+        "cube@bevy_transform::components::transform::Transform.translation",
+    )
+    .unwrap();
+    let clip = AnimationClip::builder()
+        .add_curve(
+            path,
+            CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::new(0.0, 1.0, 0.0), Vec3::ZERO]),
+        )
+        .build();
+
+    let mesh = meshes.add(Mesh::from(shape::Cube { size: 0.5 }));
+    let material = materials.add(Color::rgb(0.8, 0.7, 0.6).into());
+    for x in 0..GRID_SIZE {
+        for z in 0..GRID_SIZE {
+            let mut graph = AnimationGraph::new();
+            let node = graph.add_clip(&clip).unwrap();
+            graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+            graph.add_input(NodeId::ROOT, node).unwrap();
+            // Offset each cube's clip so the grid ripples.
+            graph
+                .set_clip_phase_offset(node, (x + z) as f32 * 0.05)
+                .unwrap();
+
+            let offset = (GRID_SIZE - 1) as f32 * 0.5;
+            commands
+                .spawn_bundle((
+                    Transform::from_xyz(x as f32 - offset, 0.0, z as f32 - offset),
+                    GlobalTransform::identity(),
+                ))
+                .insert(graph)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(PbrBundle {
+                            mesh: mesh.clone(),
+                            material: material.clone(),
+                            ..Default::default()
+                        })
+                        .insert(Name::new("cube"));
+                });
+        }
+    }
+
+    commands.spawn_bundle(PerspectiveCameraBundle {
+        transform: Transform::from_xyz(0.0, 10.0, 14.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..Default::default()
+    });
+    commands.spawn_bundle(PointLightBundle {
+        transform: Transform::from_xyz(4.0, 8.0, 4.0),
+        ..Default::default()
+    });
+}
+
+fn advance_graphs(time: Res<Time>, mut graphs: Query<&mut AnimationGraph>) {
+    for mut graph in graphs.iter_mut() {
+        graph.advance_time(time.delta_seconds());
+    }
+}
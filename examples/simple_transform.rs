@@ -0,0 +1,80 @@
+//! Moves a cube back and forth by animating its translation through a
+//! property path.
+//!
+//! Run with `cargo run --example simple_transform`.
+
+use bevy::{prelude::*, reflect::TypeRegistryArc};
+use bevy_prototype_animation::{
+    clip::AnimationClip,
+    curve::CurveFixed,
+    graph::{AnimationGraph, NodeId, PlaybackMode},
+    path::PropertyPath,
+    AnimationPlugin,
+};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(AnimationPlugin::default())
+        .add_startup_system(setup)
+        .add_system(advance_graphs)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    type_registry: Res<TypeRegistryArc>,
+) {
+    // Paths are relative to the entity with the graph, so this animates its
+    // child named "cube".
+    let path = PropertyPath::parse(
+        &type_registry.read(),
+        "cube@bevy_transform::components::transform::Transform.translation",
+    )
+    .unwrap();
+    let translations = CurveFixed::from_keyframes(
+        1.0,
+        vec![
+            Vec3::new(-2.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(-2.0, 0.0, 0.0),
+        ],
+    );
+    let clip = AnimationClip::builder()
+        .add_curve(path, translations)
+        .build();
+
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(&clip).unwrap();
+    graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+
+    commands.spawn_bundle(PerspectiveCameraBundle {
+        transform: Transform::from_xyz(0.0, 3.0, 8.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..Default::default()
+    });
+    commands.spawn_bundle(PointLightBundle {
+        transform: Transform::from_xyz(4.0, 8.0, 4.0),
+        ..Default::default()
+    });
+    commands
+        .spawn_bundle((Transform::identity(), GlobalTransform::identity()))
+        .insert(graph)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+                    material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+                    ..Default::default()
+                })
+                .insert(Name::new("cube"));
+        });
+}
+
+fn advance_graphs(time: Res<Time>, mut graphs: Query<&mut AnimationGraph>) {
+    for mut graph in graphs.iter_mut() {
+        graph.advance_time(time.delta_seconds());
+    }
+}
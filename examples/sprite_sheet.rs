@@ -0,0 +1,78 @@
+//! Plays a looping flipbook animation on a sprite sheet through an
+//! [`AnimationGraph`].
+//!
+//! Run with `cargo run --example sprite_sheet --features sprite`.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_prototype_animation::{
+    graph::{AnimationGraph, NodeId, PlaybackMode},
+    sprite::SpriteSheetClip,
+    AnimationPlugin,
+};
+
+const FRAMES: usize = 8;
+const FRAME_SIZE: u32 = 16;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(AnimationPlugin::default())
+        .add_startup_system(setup)
+        .add_system(advance_graphs)
+        .run();
+}
+
+/// Builds a sprite sheet with a different shade of gray in each frame.
+fn sprite_sheet() -> Image {
+    let width = FRAME_SIZE * FRAMES as u32;
+    let mut image = Image::new_fill(
+        Extent3d {
+            width,
+            height: FRAME_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    for (idx, pixel) in image.data.chunks_exact_mut(4).enumerate() {
+        let frame = (idx as u32 % width) / FRAME_SIZE;
+        let shade = (255 * (frame + 1) / FRAMES as u32) as u8;
+        pixel[..3].copy_from_slice(&[shade, shade, shade]);
+    }
+    image
+}
+
+fn setup(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let texture = images.add(sprite_sheet());
+    let atlas = TextureAtlas::from_grid(texture, Vec2::splat(FRAME_SIZE as f32), FRAMES, 1);
+
+    let mut graph = AnimationGraph::new();
+    let node = graph
+        .add_clip(&SpriteSheetClip::new(0..FRAMES, 10.0))
+        .unwrap();
+    graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(SpriteSheetBundle {
+            texture_atlas: atlases.add(atlas),
+            transform: Transform::from_scale(Vec3::splat(8.0)),
+            ..Default::default()
+        })
+        .insert(graph);
+}
+
+fn advance_graphs(time: Res<Time>, mut graphs: Query<&mut AnimationGraph>) {
+    for mut graph in graphs.iter_mut() {
+        graph.advance_time(time.delta_seconds());
+    }
+}
@@ -0,0 +1,110 @@
+//! Pulses a light by animating a custom component through a typed track,
+//! which is applied without reflection.
+//!
+//! Run with `cargo run --example typed_component`.
+
+use bevy::{prelude::*, reflect::TypeRegistryArc};
+use bevy_prototype_animation::{
+    animate_component,
+    clip::AnimationClip,
+    curve::CurveFixed,
+    graph::{typed::TypedAnimationPlugin, AnimationGraph, NodeId, PlaybackMode},
+    path::PropertyPath,
+    AnimationPlugin,
+};
+
+/// Drives the light and transform of a lamp. Only this component is animated.
+#[derive(Component, Reflect, Clone, Default)]
+#[reflect(Component)]
+struct Pulse {
+    intensity: f32,
+    offset: Vec3,
+}
+
+animate_component!(Pulse {
+    intensity: f32,
+    offset: Vec3
+});
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(AnimationPlugin::default())
+        .add_plugin(TypedAnimationPlugin::<Pulse>::default())
+        .add_startup_system(setup)
+        .add_system(advance_graphs)
+        .add_system(apply_pulses)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    type_registry: Res<TypeRegistryArc>,
+) {
+    let (intensity, offset) = {
+        let type_registry = type_registry.read();
+        let path = |field| {
+            PropertyPath::parse(
+                &type_registry,
+                &format!("lamp@typed_component::Pulse.{}", field),
+            )
+            .unwrap()
+        };
+        (path("intensity"), path("offset"))
+    };
+    let clip = AnimationClip::builder()
+        .add_curve(
+            intensity,
+            CurveFixed::from_keyframes(2.0, vec![200.0f32, 1600.0, 200.0]),
+        )
+        .add_curve(
+            offset,
+            CurveFixed::from_keyframes(
+                2.0,
+                vec![
+                    Vec3::new(0.0, 1.0, 0.0),
+                    Vec3::new(0.0, 2.5, 0.0),
+                    Vec3::new(0.0, 1.0, 0.0),
+                ],
+            ),
+        )
+        .build();
+
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(&clip).unwrap();
+    graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+
+    commands.spawn_bundle(PerspectiveCameraBundle {
+        transform: Transform::from_xyz(0.0, 3.0, 8.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..Default::default()
+    });
+    commands.spawn_bundle(PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Plane { size: 8.0 })),
+        material: materials.add(Color::rgb(0.4, 0.5, 0.4).into()),
+        ..Default::default()
+    });
+    commands
+        .spawn_bundle((Transform::default(), GlobalTransform::default(), graph))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(PointLightBundle::default())
+                .insert(Name::new("lamp"))
+                .insert(Pulse::default());
+        });
+}
+
+fn advance_graphs(time: Res<Time>, mut graphs: Query<&mut AnimationGraph>) {
+    for mut graph in graphs.iter_mut() {
+        graph.advance_time(time.delta_seconds());
+    }
+}
+
+fn apply_pulses(mut lamps: Query<(&Pulse, &mut PointLight, &mut Transform), Changed<Pulse>>) {
+    for (pulse, mut light, mut transform) in lamps.iter_mut() {
+        light.intensity = pulse.intensity;
+        transform.translation = pulse.offset;
+    }
+}
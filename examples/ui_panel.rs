@@ -0,0 +1,79 @@
+//! Fades in a UI panel while sliding it in from the left, by animating its
+//! `Style` and `UiColor`.
+//!
+//! Run with `cargo run --example ui_panel --features ui`.
+
+use bevy::prelude::*;
+use bevy_prototype_animation::{
+    clip::AnimationClip,
+    curve::CurveFixed,
+    graph::{AnimationGraph, NodeId},
+    path::EntityPath,
+    ui::UiProperty,
+    AnimationPlugin,
+};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(AnimationPlugin::default())
+        .add_startup_system(setup)
+        .add_system(advance_graphs)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    // An empty entity path animates the graph's own entity.
+    let panel = EntityPath::from_parts(Vec::new());
+    let position = CurveFixed::from_keyframes(
+        1.0,
+        vec![
+            Rect {
+                left: Val::Percent(-30.0),
+                top: Val::Percent(20.0),
+                ..Default::default()
+            },
+            Rect {
+                left: Val::Percent(10.0),
+                top: Val::Percent(20.0),
+                ..Default::default()
+            },
+        ],
+    );
+    let width = CurveFixed::from_keyframes(1.0, vec![Val::Percent(20.0), Val::Percent(30.0)]);
+    let color = CurveFixed::from_keyframes(
+        1.0,
+        vec![
+            UiColor(Color::rgba(0.2, 0.3, 0.8, 0.0)),
+            UiColor(Color::rgba(0.2, 0.3, 0.8, 1.0)),
+        ],
+    );
+    let clip = AnimationClip::builder()
+        .add_curve(UiProperty::position(panel.clone()), position)
+        .add_curve(UiProperty::width(panel.clone()), width)
+        .add_curve(UiProperty::color(panel), color)
+        .build();
+
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(&clip).unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+
+    commands.spawn_bundle(UiCameraBundle::default());
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(20.0), Val::Percent(60.0)),
+                ..Default::default()
+            },
+            color: UiColor(Color::NONE),
+            ..Default::default()
+        })
+        .insert(graph);
+}
+
+fn advance_graphs(time: Res<Time>, mut graphs: Query<&mut AnimationGraph>) {
+    for mut graph in graphs.iter_mut() {
+        graph.advance_time(time.delta_seconds());
+    }
+}
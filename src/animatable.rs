@@ -1,34 +1,137 @@
 use crate::util;
 use bevy_asset::{Asset, Assets, Handle, HandleId};
 use bevy_core::FloatOrd;
-use bevy_ecs::world::World;
+use bevy_ecs::{system::Resource, world::World};
 use bevy_math::*;
 use bevy_reflect::Reflect;
 use bevy_transform::prelude::Transform;
+#[cfg(feature = "ui")]
+use smallvec::SmallVec;
 
+/// Read-only access to the resources of a [`World`], passed to
+/// [`Animatable::post_process`].
+///
+/// Components and non-send resources can't be read through it, so it can be
+/// shared with threads that are writing components at the same time.
+#[derive(Clone, Copy)]
+pub struct WorldResources<'w> {
+    world: &'w World,
+}
+
+impl<'w> WorldResources<'w> {
+    pub fn new(world: &'w World) -> Self {
+        Self { world }
+    }
+
+    /// Gets a resource, if it exists.
+    pub fn get<R: Resource>(&self) -> Option<&'w R> {
+        self.world.get_resource::<R>()
+    }
+}
+
+/// A weighted value passed to [`Animatable::blend`].
+///
+/// Additive inputs are offsets applied on top of the blend of the other
+/// inputs, such as the output of [`Animatable::difference`]. Additive
+/// rotations are scaled by interpolating from the identity by their weight,
+/// and are applied after the blended rotation, in its local space.
 pub struct BlendInput<T> {
     pub weight: f32,
     pub value: T,
     pub additive: bool,
 }
 
-pub trait Animatable: Reflect + Sized + Send + Sync + 'static {
+/// A value that can be sampled from curves and blended by the tracks of an
+/// [`AnimationGraph`](crate::graph::AnimationGraph).
+///
+/// Animatable types must be [`Clone`], so that tracks can hold constant
+/// values such as captured poses.
+pub trait Animatable: Reflect + Clone + Sized + Send + Sync + 'static {
     fn interpolate(a: &Self, b: &Self, time: f32) -> Self;
+
+    /// Blends a set of weighted values.
+    ///
+    /// Types with a meaningful weighted average take the average of the
+    /// non-additive inputs. If their weights sum to less than 1, the remaining
+    /// weight is given to the type's rest value: zero for numbers and vectors,
+    /// and the identity for rotations and [`Transform`]s, which have a scale
+    /// of 1. If they sum to more than 1, the result is renormalized. Additive
+    /// inputs are then applied on top, scaled by their weights. Additive
+    /// rotations are applied in the local space of the blended rotation, so
+    /// blending `Animatable::difference(a, b)` on top of `b` with a weight of
+    /// 1 produces `a`.
+    ///
+    /// Discrete types instead pick the input with the highest weight.
     fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self;
 
+    /// Blends a set of weighted values with the graph's
+    /// [`TransformBlendMode`]. Only [`Transform`] blends differently depending
+    /// on the mode; the default implementation ignores it and calls
+    /// [`blend`](Self::blend).
+    fn blend_with_mode(
+        inputs: impl Iterator<Item = BlendInput<Self>>,
+        _mode: TransformBlendMode,
+    ) -> Self {
+        Self::blend(inputs)
+    }
+
+    /// Computes the offset from `b` to `a`, such that additively blending the
+    /// result on top of `b` with a weight of `1.0` produces `a`. Used to
+    /// convert clips into additive clips.
+    ///
+    /// The default implementation returns `a` unchanged, for types without a
+    /// meaningful difference.
+    fn difference(a: &Self, _b: &Self) -> Self {
+        a.clone()
+    }
+
+    /// Measures how far apart two values are. Used as the error metric when
+    /// comparing and simplifying curves.
+    ///
+    /// The default implementation returns `0.0` if both values are equal and
+    /// [`f32::INFINITY`] otherwise, which is suitable for discrete types.
+    fn distance(a: &Self, b: &Self) -> f32 {
+        if matches!(a.reflect_partial_eq(b), Some(true)) {
+            0.0
+        } else {
+            f32::INFINITY
+        }
+    }
+
+    /// Checks that the value doesn't contain any NaN or infinite components.
+    /// Used to validate keyframes, as blending any non-finite value produces
+    /// a non-finite result.
+    ///
+    /// The default implementation returns `true`, which is suitable for types
+    /// without floating point components.
+    fn is_finite(&self) -> bool {
+        true
+    }
+
+    /// Rewrites a sequence of keyframes into an equivalent form that
+    /// interpolates and compresses consistently. Used before resampling or
+    /// compressing curves.
+    ///
+    /// Rotations are flipped into the same hemisphere as the keyframe before
+    /// them, so that keyframes storing the same rotation as `q` and `-q` become
+    /// identical. The default implementation leaves the keyframes as is.
+    fn canonicalize_keyframes(_keyframes: &mut [Self]) {}
+
     /// Post-processes the value using resources in the [`World`].
     /// Most animatable types do not need to implement this.
     ///
-    /// # Safety
-    /// All concrete implementors of this function can only read
-    /// into the resources stored in the World. Mutation of any state,
-    /// or reading any component or NonSend resource data may cause
-    /// undefined behavior or data races.
-    unsafe fn post_process(&mut self, world: &World) {}
+    /// This is only called when applying the blended value to an animated
+    /// entity, after the value has changed. It is not called when sampling
+    /// curves or blending values. The motivating case is upgrading the weak
+    /// [`Handle<T>`]s produced by sampling into strong ones via [`Assets<T>`].
+    ///
+    /// This may be called from multiple threads while components are being
+    /// written, so only resources are accessible.
+    fn post_process(&mut self, _resources: WorldResources<'_>) {}
 }
 
 macro_rules! impl_float_animatable_32 {
-    ($ty: ty) => {
+    ($ty: ty, $length: ident) => {
         impl Animatable for $ty {
             #[inline(always)]
             fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
@@ -37,22 +140,44 @@ macro_rules! impl_float_animatable_32 {
 
             #[inline(always)]
             fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
-                let mut value = Default::default();
+                let mut value: Self = Default::default();
+                let mut additive: Self = Default::default();
+                let mut total_weight = 0.0;
                 for input in inputs {
                     if input.additive {
-                        value += input.weight * input.value;
+                        additive += input.weight * input.value;
                     } else {
-                        value = Self::interpolate(&value, &input.value, input.weight);
+                        value += input.weight * input.value;
+                        total_weight += input.weight;
                     }
                 }
-                value
+                // Any remaining weight is given to zero, which adds nothing.
+                if total_weight > 1.0 {
+                    value /= total_weight;
+                }
+                value + additive
+            }
+
+            #[inline(always)]
+            fn difference(a: &Self, b: &Self) -> Self {
+                *a - *b
+            }
+
+            #[inline(always)]
+            fn distance(a: &Self, b: &Self) -> f32 {
+                (*a - *b).$length() as f32
+            }
+
+            #[inline(always)]
+            fn is_finite(&self) -> bool {
+                <$ty>::is_finite(*self)
             }
         }
     };
 }
 
 macro_rules! impl_float_animatable_64 {
-    ($ty: ty) => {
+    ($ty: ty, $length: ident) => {
         impl Animatable for $ty {
             #[inline(always)]
             fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
@@ -62,29 +187,52 @@ macro_rules! impl_float_animatable_64 {
 
             #[inline(always)]
             fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
-                let mut value = Default::default();
+                let mut value: Self = Default::default();
+                let mut additive: Self = Default::default();
+                let mut total_weight = 0.0;
                 for input in inputs {
+                    let weight = f64::from(input.weight);
                     if input.additive {
-                        value += f64::from(input.weight) * input.value;
+                        additive += weight * input.value;
                     } else {
-                        value = Self::interpolate(&value, &input.value, input.weight);
+                        value += weight * input.value;
+                        total_weight += weight;
                     }
                 }
-                value
+                // Any remaining weight is given to zero, which adds nothing.
+                if total_weight > 1.0 {
+                    value /= total_weight;
+                }
+                value + additive
+            }
+
+            #[inline(always)]
+            fn difference(a: &Self, b: &Self) -> Self {
+                *a - *b
+            }
+
+            #[inline(always)]
+            fn distance(a: &Self, b: &Self) -> f32 {
+                (*a - *b).$length() as f32
+            }
+
+            #[inline(always)]
+            fn is_finite(&self) -> bool {
+                <$ty>::is_finite(*self)
             }
         }
     };
 }
 
-impl_float_animatable_32!(f32);
-impl_float_animatable_32!(Vec2);
-impl_float_animatable_32!(Vec3A);
-impl_float_animatable_32!(Vec4);
+impl_float_animatable_32!(f32, abs);
+impl_float_animatable_32!(Vec2, length);
+impl_float_animatable_32!(Vec3A, length);
+impl_float_animatable_32!(Vec4, length);
 
-impl_float_animatable_64!(f64);
-impl_float_animatable_64!(DVec2);
-impl_float_animatable_64!(DVec3);
-impl_float_animatable_64!(DVec4);
+impl_float_animatable_64!(f64, abs);
+impl_float_animatable_64!(DVec2, length);
+impl_float_animatable_64!(DVec3, length);
+impl_float_animatable_64!(DVec4, length);
 
 /// Vec3 is special cased to use Vec3A internally for blending
 impl Animatable for Vec3 {
@@ -95,15 +243,26 @@ impl Animatable for Vec3 {
 
     #[inline(always)]
     fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
-        let mut value = Vec3A::ZERO;
-        for input in inputs {
-            if input.additive {
-                value += input.weight * Vec3A::from(input.value);
-            } else {
-                value = Vec3A::interpolate(&value, &Vec3A::from(input.value), input.weight);
-            }
-        }
-        Self::from(value)
+        Self::from(Vec3A::blend(inputs.map(|input| BlendInput {
+            weight: input.weight,
+            value: Vec3A::from(input.value),
+            additive: input.additive,
+        })))
+    }
+
+    #[inline(always)]
+    fn difference(a: &Self, b: &Self) -> Self {
+        *a - *b
+    }
+
+    #[inline(always)]
+    fn distance(a: &Self, b: &Self) -> f32 {
+        a.distance(*b)
+    }
+
+    #[inline(always)]
+    fn is_finite(&self) -> bool {
+        Vec3::is_finite(*self)
     }
 }
 
@@ -122,6 +281,258 @@ impl Animatable for bool {
     }
 }
 
+/// Integers are discrete, so they are stepped between keyframes and blended by
+/// picking the input with the highest weight.
+macro_rules! impl_int_animatable {
+    ($($ty: ty),*) => {
+        $(
+            impl Animatable for $ty {
+                #[inline]
+                fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
+                    util::step_unclamped(*a, *b, t)
+                }
+
+                #[inline]
+                fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
+                    inputs
+                        .max_by(|a, b| FloatOrd(a.weight).cmp(&FloatOrd(b.weight)))
+                        .map(|input| input.value)
+                        .unwrap_or_default()
+                }
+
+                #[inline]
+                fn distance(a: &Self, b: &Self) -> f32 {
+                    (*a as f64 - *b as f64).abs() as f32
+                }
+            }
+        )*
+    };
+}
+
+impl_int_animatable!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+#[cfg(feature = "bevy_render")]
+impl Animatable for bevy_render::color::Color {
+    /// Interpolates in linear RGBA space.
+    #[inline]
+    fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
+        let a = Vec4::from(a.as_linear_rgba_f32());
+        let b = Vec4::from(b.as_linear_rgba_f32());
+        let [r, g, b, a] = Vec4::interpolate(&a, &b, t).to_array();
+        Self::rgba_linear(r, g, b, a)
+    }
+
+    #[inline]
+    fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
+        let [r, g, b, a] = Vec4::blend(inputs.map(|input| BlendInput {
+            weight: input.weight,
+            value: Vec4::from(input.value.as_linear_rgba_f32()),
+            additive: input.additive,
+        }))
+        .to_array();
+        Self::rgba_linear(r, g, b, a)
+    }
+
+    #[inline]
+    fn difference(a: &Self, b: &Self) -> Self {
+        let [r, g, b, a] =
+            (Vec4::from(a.as_linear_rgba_f32()) - Vec4::from(b.as_linear_rgba_f32())).to_array();
+        Self::rgba_linear(r, g, b, a)
+    }
+
+    #[inline]
+    fn distance(a: &Self, b: &Self) -> f32 {
+        Vec4::from(a.as_linear_rgba_f32()).distance(Vec4::from(b.as_linear_rgba_f32()))
+    }
+
+    #[inline]
+    fn is_finite(&self) -> bool {
+        Vec4::from(self.as_linear_rgba_f32()).is_finite()
+    }
+}
+
+/// Visibility is stepped between keyframes and blended by picking the input
+/// with the highest weight, like `bool`.
+#[cfg(feature = "bevy_render")]
+impl Animatable for bevy_render::view::Visibility {
+    #[inline]
+    fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
+        Self {
+            is_visible: bool::interpolate(&a.is_visible, &b.is_visible, t),
+        }
+    }
+
+    #[inline]
+    fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
+        Self {
+            is_visible: bool::blend(inputs.map(|input| BlendInput {
+                weight: input.weight,
+                value: input.value.is_visible,
+                additive: input.additive,
+            })),
+        }
+    }
+}
+
+/// [`Val`]s are interpolated and blended numerically when all of the values
+/// use the same unit. Otherwise they are stepped between keyframes and blended
+/// by picking the input with the highest weight, as there is no meaningful
+/// value between pixels and percentages, or between `Auto` and a length.
+///
+/// [`Val`]: bevy_ui::Val
+#[cfg(feature = "ui")]
+impl Animatable for bevy_ui::Val {
+    #[inline]
+    fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
+        use bevy_ui::Val;
+        match (a, b) {
+            (Val::Px(a), Val::Px(b)) => Val::Px(f32::interpolate(a, b, t)),
+            (Val::Percent(a), Val::Percent(b)) => Val::Percent(f32::interpolate(a, b, t)),
+            _ => util::step_unclamped(*a, *b, t),
+        }
+    }
+
+    fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
+        use bevy_ui::Val;
+        // The units of every input must be known before blending any of them.
+        let inputs: SmallVec<[BlendInput<Self>; 4]> = inputs.collect();
+        let same_unit = inputs.windows(2).all(|pair| {
+            std::mem::discriminant(&pair[0].value) == std::mem::discriminant(&pair[1].value)
+        });
+        let lengths = || {
+            inputs.iter().map(|input| BlendInput {
+                weight: input.weight,
+                value: match input.value {
+                    Val::Px(value) | Val::Percent(value) => value,
+                    _ => 0.0,
+                },
+                additive: input.additive,
+            })
+        };
+        match inputs.first().map(|input| input.value) {
+            Some(Val::Px(_)) if same_unit => Val::Px(f32::blend(lengths())),
+            Some(Val::Percent(_)) if same_unit => Val::Percent(f32::blend(lengths())),
+            _ => inputs
+                .iter()
+                .max_by(|a, b| FloatOrd(a.weight).cmp(&FloatOrd(b.weight)))
+                .map(|input| input.value)
+                .unwrap_or_default(),
+        }
+    }
+
+    #[inline]
+    fn difference(a: &Self, b: &Self) -> Self {
+        use bevy_ui::Val;
+        match (a, b) {
+            (Val::Px(a), Val::Px(b)) => Val::Px(a - b),
+            (Val::Percent(a), Val::Percent(b)) => Val::Percent(a - b),
+            _ => *a,
+        }
+    }
+
+    #[inline]
+    fn distance(a: &Self, b: &Self) -> f32 {
+        use bevy_ui::Val;
+        match (a, b) {
+            (Val::Px(a), Val::Px(b)) | (Val::Percent(a), Val::Percent(b)) => (a - b).abs(),
+            _ if a == b => 0.0,
+            _ => f32::INFINITY,
+        }
+    }
+
+    #[inline]
+    fn is_finite(&self) -> bool {
+        use bevy_ui::Val;
+        match self {
+            Val::Px(value) | Val::Percent(value) => value.is_finite(),
+            _ => true,
+        }
+    }
+}
+
+/// Implements [`Animatable`] for a struct of [`Val`]s by animating each of its
+/// fields independently.
+///
+/// [`Val`]: bevy_ui::Val
+#[cfg(feature = "ui")]
+macro_rules! impl_val_struct_animatable {
+    ($ty: ty, $($field: ident),+) => {
+        impl Animatable for $ty {
+            #[inline]
+            fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
+                Self {
+                    $($field: Animatable::interpolate(&a.$field, &b.$field, t),)+
+                }
+            }
+
+            fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
+                let inputs: SmallVec<[BlendInput<Self>; 4]> = inputs.collect();
+                Self {
+                    $($field: Animatable::blend(inputs.iter().map(|input| BlendInput {
+                        weight: input.weight,
+                        value: input.value.$field,
+                        additive: input.additive,
+                    })),)+
+                }
+            }
+
+            #[inline]
+            fn difference(a: &Self, b: &Self) -> Self {
+                Self {
+                    $($field: Animatable::difference(&a.$field, &b.$field),)+
+                }
+            }
+
+            #[inline]
+            fn distance(a: &Self, b: &Self) -> f32 {
+                0.0f32 $(.max(Animatable::distance(&a.$field, &b.$field)))+
+            }
+
+            #[inline]
+            fn is_finite(&self) -> bool {
+                true $(&& Animatable::is_finite(&self.$field))+
+            }
+        }
+    };
+}
+
+#[cfg(feature = "ui")]
+impl_val_struct_animatable!(Rect<bevy_ui::Val>, left, right, top, bottom);
+#[cfg(feature = "ui")]
+impl_val_struct_animatable!(Size<bevy_ui::Val>, width, height);
+
+#[cfg(feature = "ui")]
+impl Animatable for bevy_ui::UiColor {
+    #[inline]
+    fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
+        Self(Animatable::interpolate(&a.0, &b.0, t))
+    }
+
+    #[inline]
+    fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
+        Self(Animatable::blend(inputs.map(|input| BlendInput {
+            weight: input.weight,
+            value: input.value.0,
+            additive: input.additive,
+        })))
+    }
+
+    #[inline]
+    fn difference(a: &Self, b: &Self) -> Self {
+        Self(Animatable::difference(&a.0, &b.0))
+    }
+
+    #[inline]
+    fn distance(a: &Self, b: &Self) -> f32 {
+        Animatable::distance(&a.0, &b.0)
+    }
+
+    #[inline]
+    fn is_finite(&self) -> bool {
+        Animatable::is_finite(&self.0)
+    }
+}
+
 impl Animatable for HandleId {
     #[inline]
     fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
@@ -137,6 +548,9 @@ impl Animatable for HandleId {
     }
 }
 
+/// Handles are stepped between keyframes and blended by picking the input with
+/// the highest weight. Sampled handles are weak, and are upgraded to strong
+/// handles via the [`Assets<T>`] resource in [`Animatable::post_process`].
 impl<T: Asset> Animatable for Handle<T> {
     #[inline]
     fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
@@ -151,15 +565,13 @@ impl<T: Asset> Animatable for Handle<T> {
             .expect("Attempted to blend Handle with zero inputs.")
     }
 
-    // SAFE: This implementation only reads resources from the provided
-    // World.
-    unsafe fn post_process(&mut self, world: &World) {
+    fn post_process(&mut self, resources: WorldResources<'_>) {
         // Upgrade weak handles into strong ones.
         if self.is_strong() {
             return;
         }
-        *self = world
-            .get_resource::<Assets<T>>()
+        *self = resources
+            .get::<Assets<T>>()
             .expect(
                 "Attempted to animate a Handle<T> without the corresponding Assets<T> resource.",
             )
@@ -178,33 +590,176 @@ impl Animatable for Transform {
 
     fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
         let mut translation = Vec3A::ZERO;
+        let mut rotation = Vec4::ZERO;
         let mut scale = Vec3A::ZERO;
-        let mut rotation = Quat::IDENTITY;
+        let mut total_weight = 0.0;
+        let mut additive_translation = Vec3A::ZERO;
+        let mut additive_rotation = Quat::IDENTITY;
+        let mut additive_scale = Vec3A::ZERO;
 
         for input in inputs {
+            let value = input.value;
             if input.additive {
-                translation += input.weight * Vec3A::from(input.value.translation);
-                scale += input.weight * Vec3A::from(input.value.scale);
-                rotation = (input.value.rotation * input.weight) * rotation;
+                additive_translation += input.weight * Vec3A::from(value.translation);
+                additive_rotation *=
+                    Quat::interpolate(&Quat::IDENTITY, &value.rotation, input.weight);
+                additive_scale += input.weight * Vec3A::from(value.scale);
             } else {
-                translation = Vec3A::interpolate(
-                    &translation,
-                    &Vec3A::from(input.value.translation),
-                    input.weight,
-                );
-                scale = Vec3A::interpolate(&scale, &Vec3A::from(input.value.scale), input.weight);
-                rotation = Quat::interpolate(&rotation, &input.value.rotation, input.weight);
+                translation += input.weight * Vec3A::from(value.translation);
+                add_rotation(&mut rotation, value.rotation, input.weight);
+                scale += input.weight * Vec3A::from(value.scale);
+                total_weight += input.weight;
             }
         }
 
+        // Give any remaining weight to the identity. Its translation is zero,
+        // so only the scale needs to account for it.
+        if total_weight < 1.0 {
+            scale += (1.0 - total_weight) * Vec3A::ONE;
+        } else {
+            translation /= total_weight;
+            scale /= total_weight;
+        }
+
+        Self {
+            translation: Vec3::from(translation + additive_translation),
+            rotation: finish_rotation(rotation, total_weight) * additive_rotation,
+            scale: Vec3::from(scale + additive_scale),
+        }
+    }
+
+    fn blend_with_mode(
+        inputs: impl Iterator<Item = BlendInput<Self>>,
+        mode: TransformBlendMode,
+    ) -> Self {
+        mode.blend(inputs)
+    }
+
+    fn difference(a: &Self, b: &Self) -> Self {
         Self {
-            translation: Vec3::from(translation),
-            rotation,
-            scale: Vec3::from(scale),
+            translation: a.translation - b.translation,
+            rotation: Quat::difference(&a.rotation, &b.rotation),
+            scale: a.scale - b.scale,
+        }
+    }
+
+    /// The largest of the translation, rotation, and scale distances.
+    fn distance(a: &Self, b: &Self) -> f32 {
+        <Vec3 as Animatable>::distance(&a.translation, &b.translation)
+            .max(<Quat as Animatable>::distance(&a.rotation, &b.rotation))
+            .max(<Vec3 as Animatable>::distance(&a.scale, &b.scale))
+    }
+
+    fn is_finite(&self) -> bool {
+        self.translation.is_finite() && self.rotation.is_finite() && self.scale.is_finite()
+    }
+
+    fn canonicalize_keyframes(keyframes: &mut [Self]) {
+        for idx in 1..keyframes.len() {
+            if keyframes[idx - 1].rotation.dot(keyframes[idx].rotation) < 0.0 {
+                keyframes[idx].rotation = -keyframes[idx].rotation;
+            }
+        }
+    }
+}
+
+/// Selects how multiple [`Transform`]s are blended together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransformBlendMode {
+    /// Blends the translation, rotation, and scale of each [`Transform`]
+    /// independently. This is cheap, but translations are blended linearly and
+    /// cut through the pivot when the rotations differ significantly.
+    Componentwise,
+    /// Blends rotation and translation together as dual quaternions, keeping
+    /// translations on the arc traced by the rotation. Scale is blended
+    /// separately.
+    DualQuaternion,
+}
+
+impl Default for TransformBlendMode {
+    fn default() -> Self {
+        Self::Componentwise
+    }
+}
+
+impl TransformBlendMode {
+    /// Blends a set of [`Transform`]s using this mode.
+    pub fn blend(self, inputs: impl Iterator<Item = BlendInput<Transform>>) -> Transform {
+        match self {
+            Self::Componentwise => Transform::blend(inputs),
+            Self::DualQuaternion => blend_dual_quaternion(inputs),
         }
     }
 }
 
+/// Converts a rotation and translation into the real and dual parts of a
+/// unit dual quaternion.
+#[inline]
+fn to_dual_quaternion(rotation: Quat, translation: Vec3) -> (Vec4, Vec4) {
+    let translation = Quat::from_xyzw(translation.x, translation.y, translation.z, 0.0);
+    let dual = (translation * rotation) * 0.5;
+    (rotation.into(), dual.into())
+}
+
+fn blend_dual_quaternion(inputs: impl Iterator<Item = BlendInput<Transform>>) -> Transform {
+    let mut real = Vec4::ZERO;
+    let mut dual = Vec4::ZERO;
+    let mut scale = Vec3A::ZERO;
+    let mut total_weight = 0.0;
+    // Additive inputs are applied on top of the blended result, the same
+    // way as with componentwise blending.
+    let mut additive_translation = Vec3A::ZERO;
+    let mut additive_scale = Vec3A::ZERO;
+    let mut additive_rotation = Quat::IDENTITY;
+
+    for input in inputs {
+        let value = input.value;
+        if input.additive {
+            additive_translation += input.weight * Vec3A::from(value.translation);
+            additive_scale += input.weight * Vec3A::from(value.scale);
+            additive_rotation *= Quat::interpolate(&Quat::IDENTITY, &value.rotation, input.weight);
+            continue;
+        }
+        let (input_real, input_dual) = to_dual_quaternion(value.rotation, value.translation);
+        // q and -q are the same rotation. Keep every input in the same
+        // hemisphere as the running sum so they don't cancel out.
+        let sign = if real.dot(input_real) < 0.0 {
+            -1.0
+        } else {
+            1.0
+        };
+        real += input_real * (sign * input.weight);
+        dual += input_dual * (sign * input.weight);
+        scale += input.weight * Vec3A::from(value.scale);
+        total_weight += input.weight;
+    }
+
+    // Give any remaining weight to the identity, whose dual part is zero.
+    if total_weight < 1.0 {
+        add_rotation(&mut real, Quat::IDENTITY, 1.0 - total_weight);
+        scale += (1.0 - total_weight) * Vec3A::ONE;
+    }
+
+    let length = real.length();
+    let mut result = if length > 0.0 {
+        let real = Quat::from_vec4(real / length);
+        let dual = Quat::from_vec4(dual / length);
+        let translation = (dual * real.conjugate()) * 2.0;
+        Transform {
+            translation: Vec3::new(translation.x, translation.y, translation.z),
+            rotation: real,
+            scale: Vec3::from(scale / total_weight.max(1.0)),
+        }
+    } else {
+        Transform::identity()
+    };
+
+    result.translation += Vec3::from(additive_translation);
+    result.rotation *= additive_rotation;
+    result.scale += Vec3::from(additive_scale);
+    result
+}
+
 impl Animatable for Quat {
     /// Performs an nlerp, because it's cheaper and easier to combine with other animations,
     /// reference: http://number-none.com/product/Understanding%20Slerp,%20Then%20Not%20Using%20It/
@@ -216,17 +771,79 @@ impl Animatable for Quat {
         let a: Vec4 = (*a).into();
         let b: Vec4 = b.into();
         let rot = Vec4::interpolate(&a, &b, t);
-        let inv_mag = util::approx_rsqrt(rot.dot(rot));
-        Quat::from_vec4(rot * inv_mag)
+        // Normalized exactly, as single clips write sampled rotations as is.
+        Quat::from_vec4(rot.normalize())
     }
 
     #[inline]
     fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
-        let mut value = Self::IDENTITY;
+        let mut value = Vec4::ZERO;
+        let mut total_weight = 0.0;
+        let mut additive = Self::IDENTITY;
         for input in inputs {
-            value = Self::interpolate(&value, &input.value, input.weight);
+            if input.additive {
+                additive *= Self::interpolate(&Self::IDENTITY, &input.value, input.weight);
+            } else {
+                add_rotation(&mut value, input.value, input.weight);
+                total_weight += input.weight;
+            }
         }
-        value
+        finish_rotation(value, total_weight) * additive
+    }
+
+    /// The rotation that, applied after `b`, produces `a`.
+    #[inline]
+    fn difference(a: &Self, b: &Self) -> Self {
+        b.inverse() * *a
+    }
+
+    /// The angle between the two rotations in radians.
+    #[inline]
+    fn distance(a: &Self, b: &Self) -> f32 {
+        // Interpolated rotations are only approximately normalized, and acos is
+        // very imprecise near 1.0, so use the atan2 form on normalized values.
+        let a: Vec4 = a.normalize().into();
+        let b: Vec4 = b.normalize().into();
+        let b = if a.dot(b) < 0.0 { -b } else { b };
+        4.0 * (a - b).length().atan2((a + b).length())
+    }
+
+    #[inline]
+    fn is_finite(&self) -> bool {
+        Quat::is_finite(*self)
+    }
+
+    fn canonicalize_keyframes(keyframes: &mut [Self]) {
+        for idx in 1..keyframes.len() {
+            if keyframes[idx - 1].dot(keyframes[idx]) < 0.0 {
+                keyframes[idx] = -keyframes[idx];
+            }
+        }
+    }
+}
+
+/// Adds a weighted rotation to a sum of rotations. q and -q are the same
+/// rotation, so it's kept in the same hemisphere as the sum to avoid
+/// cancelling it out.
+#[inline]
+fn add_rotation(sum: &mut Vec4, rotation: Quat, weight: f32) {
+    let rotation = Vec4::from(rotation);
+    let sign = if sum.dot(rotation) < 0.0 { -1.0 } else { 1.0 };
+    *sum += rotation * (sign * weight);
+}
+
+/// Normalizes a sum of rotations built with [`add_rotation`], giving any
+/// weight below `1.0` to the identity.
+#[inline]
+fn finish_rotation(mut sum: Vec4, total_weight: f32) -> Quat {
+    if total_weight < 1.0 {
+        add_rotation(&mut sum, Quat::IDENTITY, 1.0 - total_weight);
+    }
+    let length = sum.length();
+    if length > 0.0 {
+        Quat::from_vec4(sum / length)
+    } else {
+        Quat::IDENTITY
     }
 }
 
@@ -289,3 +906,125 @@ impl Animatable for Quat {
 //         )
 //     }
 // }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn input<T>(weight: f32, value: T) -> BlendInput<T> {
+        BlendInput {
+            weight,
+            value,
+            additive: false,
+        }
+    }
+
+    fn same_rotation(a: Quat, b: Quat) -> bool {
+        a.normalize().dot(b.normalize()).abs() > 1.0 - 1e-5
+    }
+
+    #[cfg(feature = "ui")]
+    #[test]
+    pub fn test_val_interpolates_matching_units() {
+        use bevy_ui::Val;
+        let lerp = |a, b| Val::interpolate(&a, &b, 0.25);
+        assert_eq!(lerp(Val::Px(0.0), Val::Px(100.0)), Val::Px(25.0));
+        assert_eq!(
+            lerp(Val::Percent(0.0), Val::Percent(100.0)),
+            Val::Percent(25.0)
+        );
+        assert_eq!(lerp(Val::Px(0.0), Val::Percent(100.0)), Val::Px(0.0));
+        assert_eq!(lerp(Val::Auto, Val::Px(100.0)), Val::Auto);
+        assert_eq!(
+            Val::interpolate(&Val::Auto, &Val::Px(100.0), 1.0),
+            Val::Px(100.0)
+        );
+    }
+
+    #[cfg(feature = "ui")]
+    #[test]
+    pub fn test_val_blends_matching_units() {
+        use bevy_ui::Val;
+        let blended =
+            Val::blend([input(0.25, Val::Px(100.0)), input(0.75, Val::Px(20.0))].into_iter());
+        assert_eq!(blended, Val::Px(40.0));
+        let blended =
+            Val::blend([input(0.25, Val::Px(100.0)), input(0.75, Val::Percent(20.0))].into_iter());
+        assert_eq!(blended, Val::Percent(20.0));
+
+        let rect = |val| Rect::all(val);
+        let blended = Rect::<Val>::blend(
+            [
+                input(0.5, rect(Val::Px(10.0))),
+                input(0.5, rect(Val::Px(30.0))),
+            ]
+            .into_iter(),
+        );
+        assert_eq!(blended, rect(Val::Px(20.0)));
+    }
+
+    #[test]
+    pub fn test_partial_weight_blends_toward_rest_pose() {
+        let clip = Transform {
+            translation: Vec3::new(2.0, 0.0, 0.0),
+            rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            scale: Vec3::splat(3.0),
+        };
+        let halfway = Transform {
+            translation: Vec3::new(1.0, 0.0, 0.0),
+            rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_4),
+            scale: Vec3::splat(2.0),
+        };
+
+        let blended = Transform::blend(std::iter::once(input(0.5, clip)));
+        assert!((blended.translation - halfway.translation).length() < 1e-5);
+        assert!(same_rotation(blended.rotation, halfway.rotation));
+        assert!((blended.scale - halfway.scale).length() < 1e-5);
+
+        // The translation follows the rotation's arc when blending dual
+        // quaternions, so only the rotation and scale are comparable.
+        let blended = TransformBlendMode::DualQuaternion.blend(std::iter::once(input(0.5, clip)));
+        assert!(same_rotation(blended.rotation, halfway.rotation));
+        assert!((blended.scale - halfway.scale).length() < 1e-5);
+
+        let blended = Quat::blend(std::iter::once(input(0.5, clip.rotation)));
+        assert!(same_rotation(blended, halfway.rotation));
+        assert_eq!(f32::blend(std::iter::once(input(0.5, 4.0))), 2.0);
+        assert_eq!(
+            Vec3::blend(std::iter::once(input(0.25, Vec3::splat(4.0)))),
+            Vec3::ONE
+        );
+    }
+
+    #[test]
+    pub fn test_blend_is_weighted_average() {
+        // Crossfades are independent of the order of the inputs.
+        let inputs = [input(0.5, 2.0f32), input(0.5, 4.0)];
+        assert_eq!(f32::blend(inputs.into_iter()), 3.0);
+        let inputs = [input(0.25, 4.0f32), input(0.75, 2.0)];
+        assert_eq!(f32::blend(inputs.into_iter()), 2.5);
+
+        // Weights summing to more than 1 are renormalized.
+        let inputs = [input(1.0, Vec3::ONE), input(3.0, Vec3::splat(5.0))];
+        assert_eq!(Vec3::blend(inputs.into_iter()), Vec3::splat(4.0));
+        let inputs = [
+            input(2.0, Transform::from_scale(Vec3::splat(2.0))),
+            input(2.0, Transform::from_scale(Vec3::splat(4.0))),
+        ];
+        assert_eq!(Transform::blend(inputs.into_iter()).scale, Vec3::splat(3.0));
+
+        // Additive inputs are applied on top of the average.
+        let inputs = [
+            BlendInput {
+                weight: 0.5,
+                value: 2.0f32,
+                additive: true,
+            },
+            input(1.0, 4.0),
+        ];
+        assert_eq!(f32::blend(inputs.into_iter()), 5.0);
+
+        assert_eq!(f32::blend(std::iter::empty()), 0.0);
+        assert_eq!(Transform::blend(std::iter::empty()), Transform::identity());
+    }
+}
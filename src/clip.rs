@@ -1,35 +1,158 @@
+#[cfg(feature = "bevy_render")]
 use crate::{
-    curve::Curve,
-    graph::{ClipId, CurveTrack, Track},
-    path::PropertyPath,
+    curve::CurveVariableLinear,
+    path::{EntityPath, FieldPath},
+};
+use crate::{
+    curve::{mapped_curve_methods, simplify_curve, Curve, CurveError, SmoothLoop, Tween},
+    graph::{ClipId, CurveTrack, Easing, MorphCurves, Track},
+    path::{AccessPath, AccessTarget, PathInterner, PropertyPath},
+    target::ReflectResource,
     Animatable,
 };
-use bevy_reflect::TypeUuid;
-use bevy_utils::{Hashed, PreHashMap};
+use bevy_ecs::reflect::ReflectComponent;
+use bevy_log::warn;
+use bevy_reflect::{
+    impl_reflect_value, FromType, Reflect, TypeRegistration, TypeRegistry, TypeUuid,
+};
+use bevy_utils::{HashMap, Hashed, PreHashMap};
 use std::{
     any::{Any, TypeId},
+    borrow::Cow,
+    fmt::Display,
     sync::Arc,
 };
+use thiserror::Error;
 
 #[derive(Clone)]
 pub(crate) struct CurveWrapper<T>(pub Arc<dyn Curve<T>>);
 
-pub(crate) trait ClipCurve: Send + Sync + 'static {
+/// A non-generic interface for the curve of a single property in an
+/// [`AnimationClip`].
+///
+/// Curves added with [`AnimationClipBuilder::add_curve`] are blended with
+/// [`Animatable::blend`]. Implement this with a custom [`Track`] to animate a
+/// property with a different blend, and add it with
+/// [`AnimationClipBuilder::add_custom_curve`].
+pub trait ClipCurve: Send + Sync + 'static {
     fn value_type_id(&self) -> TypeId;
+    fn value_type_name(&self) -> &'static str;
+    fn duration(&self) -> f32;
+    fn keyframe_count(&self) -> usize;
     fn as_any(&self) -> &dyn Any;
+    /// Creates a new track for the curve, for graphs where the clip is the
+    /// first to animate the curve's property. Curves from clips added
+    /// afterwards are passed to the track's
+    /// [`add_generic_curve`](Track::add_generic_curve), which should accept
+    /// curves of the same type as this one.
     fn into_track(&self, clip_id: ClipId) -> Box<dyn Track>;
+    /// A pointer to the underlying curve, used to detect shared curves.
+    fn curve_ptr(&self) -> *const ();
+    /// Samples the curve at `time`. The value should be of the type
+    /// identified by [`value_type_id`](Self::value_type_id).
+    fn sample_reflect(&self, time: f32) -> Box<dyn Reflect>;
+    /// See [`Curve::find_non_finite`].
+    fn find_non_finite(&self) -> Option<usize> {
+        None
+    }
+    /// Creates a new handle to the same underlying curve.
+    fn clone_curve(&self) -> Box<dyn ClipCurve>;
+    /// Creates a copy of the curve with fewer keyframes. See
+    /// [`AnimationClip::simplified`]. Curves that can't be simplified are
+    /// cloned.
+    fn simplified(&self, _tolerance: f32) -> Box<dyn ClipCurve> {
+        self.clone_curve()
+    }
+    /// Creates a curve blending its end towards its start. See
+    /// [`AnimationClip::smooth_loop`]. Curves that can't be smoothed are
+    /// cloned.
+    fn smooth_loop(&self, _blend_window: f32) -> Box<dyn ClipCurve> {
+        self.clone_curve()
+    }
+    /// Creates a curve sampling the difference from `reference`'s value at
+    /// `reference_time`. Returns `None` if the value types don't match, or if
+    /// the curve can't be made additive.
+    fn make_additive(
+        &self,
+        _reference: &dyn ClipCurve,
+        _reference_time: f32,
+    ) -> Option<Box<dyn ClipCurve>> {
+        None
+    }
 }
 
 impl<T: Animatable> ClipCurve for CurveWrapper<T> {
     fn value_type_id(&self) -> TypeId {
         TypeId::of::<T>()
     }
+    fn value_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+    fn duration(&self) -> f32 {
+        self.0.duration()
+    }
+    fn keyframe_count(&self) -> usize {
+        self.0.keyframe_count()
+    }
     fn as_any(&self) -> &dyn Any {
         self as &_
     }
     fn into_track(&self, clip_id: ClipId) -> Box<dyn Track> {
         Box::new(CurveTrack::new(self.0.clone(), clip_id))
     }
+    fn curve_ptr(&self) -> *const () {
+        Arc::as_ptr(&self.0) as *const ()
+    }
+    fn sample_reflect(&self, time: f32) -> Box<dyn Reflect> {
+        // Boxed directly rather than with clone_value, which would turn
+        // structs into dynamic structs that can't be downcast.
+        Box::new(self.0.sample(time))
+    }
+    fn find_non_finite(&self) -> Option<usize> {
+        self.0.find_non_finite()
+    }
+    fn clone_curve(&self) -> Box<dyn ClipCurve> {
+        Box::new(self.clone())
+    }
+    fn simplified(&self, tolerance: f32) -> Box<dyn ClipCurve> {
+        match simplify_curve(self.0.as_ref(), tolerance) {
+            Ok(curve) => Box::new(CurveWrapper::<T>(Arc::new(curve))),
+            Err(_) => Box::new(self.clone()),
+        }
+    }
+    fn smooth_loop(&self, blend_window: f32) -> Box<dyn ClipCurve> {
+        if self.0.keyframe_count() == 0 {
+            return Box::new(self.clone());
+        }
+        let curve = SmoothLoop::new(self.0.clone(), blend_window);
+        Box::new(CurveWrapper::<T>(Arc::new(curve)))
+    }
+    fn make_additive(
+        &self,
+        reference: &dyn ClipCurve,
+        reference_time: f32,
+    ) -> Option<Box<dyn ClipCurve>> {
+        let reference = reference.as_any().downcast_ref::<CurveWrapper<T>>()?;
+        let curve = AdditiveCurve {
+            curve: self.0.clone(),
+            reference: reference.0.sample(reference_time),
+        };
+        Some(Box::new(CurveWrapper::<T>(Arc::new(curve))))
+    }
+}
+
+/// A curve sampling the difference between another curve and a constant
+/// reference value.
+struct AdditiveCurve<T> {
+    curve: Arc<dyn Curve<T>>,
+    reference: T,
+}
+
+impl<T: Animatable> Curve<T> for AdditiveCurve<T> {
+    mapped_curve_methods!(T, curve, |this, _, value| T::difference(
+        &value,
+        &this.reference
+    ));
 }
 
 /// An immutable container of curves.
@@ -38,71 +161,1388 @@ impl<T: Animatable> ClipCurve for CurveWrapper<T> {
 pub struct AnimationClip {
     // TODO: See if we can remove this extra layer of indirection
     pub(crate) curves: PreHashMap<PropertyPath, Box<dyn ClipCurve>>,
+    // Curves for parameters of the graphs the clip is added to.
+    pub(crate) params: HashMap<Cow<'static, str>, Arc<dyn Curve<f32>>>,
+    pub(crate) additive: bool,
+    // Metadata for tools and gameplay code, not used to sample the clip.
+    pub(crate) name: Option<Cow<'static, str>>,
+    pub(crate) tags: Vec<Cow<'static, str>>,
 }
 
+/// Clones share their curves with the original clip.
+impl Clone for AnimationClip {
+    fn clone(&self) -> Self {
+        Self {
+            curves: self
+                .curves
+                .iter()
+                .map(|(path, curve)| (path.clone(), curve.clone_curve()))
+                .collect(),
+            params: self.params.clone(),
+            additive: self.additive,
+            name: self.name.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+}
+
+// Registered as an opaque value, so that tools can find clips in the type
+// registry.
+impl_reflect_value!(AnimationClip);
+
 impl AnimationClip {
     pub fn builder() -> AnimationClipBuilder {
         AnimationClipBuilder::new()
     }
 
+    /// The duration of the clip in seconds. This is the duration of the
+    /// longest curve in the clip, including parameter curves.
+    pub fn duration(&self) -> f32 {
+        self.curves
+            .values()
+            .map(|curve| curve.duration())
+            .chain(self.params.values().map(|curve| curve.duration()))
+            .fold(0.0, f32::max)
+    }
+
     pub fn properties(&self) -> impl Iterator<Item = &Hashed<PropertyPath>> {
         self.curves.keys()
     }
 
+    /// Interns the paths of the clip's curves, replacing them with copies
+    /// sharing the storage of the interned paths. Sharing one interner
+    /// between the clips of a skeleton stores each of its paths once, rather
+    /// than once per clip.
+    pub fn share_paths(&mut self, interner: &mut PathInterner) {
+        self.curves = std::mem::take(&mut self.curves)
+            .into_iter()
+            .map(|(path, curve)| (Hashed::new(interner.share_property(&path)), curve))
+            .collect();
+    }
+
+    /// The names of the graph parameters the clip has curves for. See
+    /// [`AnimationClipBuilder::add_param_curve`].
+    pub fn params(&self) -> impl Iterator<Item = &str> {
+        self.params.keys().map(AsRef::as_ref)
+    }
+
+    /// Gets the curve for a graph parameter, if the clip has one.
+    pub fn get_param_curve(&self, param: &str) -> Option<Arc<dyn Curve<f32>>> {
+        self.params.get(param).cloned()
+    }
+
+    /// Iterates over the curves in the clip, yielding the property each one
+    /// animates, the [`TypeId`] of its values, its keyframe count, and its
+    /// duration in seconds.
+    pub fn iter_curves(&self) -> impl Iterator<Item = (&PropertyPath, TypeId, usize, f32)> {
+        self.curves.iter().map(|(path, curve)| {
+            (
+                &**path,
+                curve.value_type_id(),
+                curve.keyframe_count(),
+                curve.duration(),
+            )
+        })
+    }
+
+    /// Combines the curves of two clips into a new clip, such as a face clip
+    /// and a body clip exported separately. The curves are shared with both
+    /// clips.
+    ///
+    /// Fails if both clips animate the same property, or if only one of them
+    /// is additive. See [`AnimationClip::merge_with`] to replace curves of
+    /// the same type instead.
+    pub fn merge(&self, other: &AnimationClip) -> Result<AnimationClip, MergeError> {
+        self.merge_with(other, MergePolicy::Reject)
+    }
+
+    /// Combines the curves of two clips into a new clip, resolving
+    /// properties animated by both clips with `policy`. Properties animated
+    /// with different types by each clip are always rejected.
+    pub fn merge_with(
+        &self,
+        other: &AnimationClip,
+        policy: MergePolicy,
+    ) -> Result<AnimationClip, MergeError> {
+        if self.additive != other.additive {
+            return Err(MergeError::MismatchedAdditive);
+        }
+        let mut curves: PreHashMap<PropertyPath, Box<dyn ClipCurve>> = self
+            .curves
+            .iter()
+            .map(|(path, curve)| (path.clone(), curve.clone_curve()))
+            .collect();
+        for (path, curve) in other.curves.iter() {
+            if let Some(existing) = curves.get(path) {
+                if existing.value_type_id() != curve.value_type_id() {
+                    return Err(MergeError::ConflictingType {
+                        path: (**path).clone(),
+                        existing: existing.value_type_name(),
+                        new: curve.value_type_name(),
+                    });
+                } else if policy == MergePolicy::Reject {
+                    return Err(MergeError::DuplicateProperty((**path).clone()));
+                }
+            }
+            curves.insert(path.clone(), curve.clone_curve());
+        }
+        let mut params = self.params.clone();
+        for (param, curve) in other.params.iter() {
+            if policy == MergePolicy::Reject && params.contains_key(param) {
+                return Err(MergeError::DuplicateParam(param.clone()));
+            }
+            params.insert(param.clone(), curve.clone());
+        }
+        let mut tags = self.tags.clone();
+        for tag in other.tags.iter() {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        Ok(AnimationClip {
+            curves,
+            params,
+            additive: self.additive,
+            name: self.name.clone(),
+            tags,
+        })
+    }
+
+    /// Creates a copy of the clip where every curve, including parameter
+    /// curves, is blended towards its value at time 0 over its last
+    /// `blend_window` seconds, with [`SmoothLoop`]. This hides the pop of
+    /// looping clips whose last keyframes don't quite match their first.
+    ///
+    /// The seam is closed at the end of each curve, so curves shorter than
+    /// the clip still hold a different value through the rest of the clip.
+    pub fn smooth_loop(&self, blend_window: f32) -> AnimationClip {
+        AnimationClip {
+            curves: self
+                .curves
+                .iter()
+                .map(|(path, curve)| (path.clone(), curve.smooth_loop(blend_window)))
+                .collect(),
+            params: self
+                .params
+                .iter()
+                .map(|(param, curve)| {
+                    let smoothed: Arc<dyn Curve<f32>> = if curve.keyframe_count() > 0 {
+                        Arc::new(SmoothLoop::new(curve.clone(), blend_window))
+                    } else {
+                        curve.clone()
+                    };
+                    (param.clone(), smoothed)
+                })
+                .collect(),
+            additive: self.additive,
+            name: self.name.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+
+    /// Gets the name of the clip, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Names the clip. Graphs the clip is added to afterwards can find its
+    /// node by name with [`AnimationGraph::find_clip_by_name`].
+    ///
+    /// [`AnimationGraph::find_clip_by_name`]: crate::graph::AnimationGraph::find_clip_by_name
+    pub fn set_name(&mut self, name: impl Into<Cow<'static, str>>) {
+        self.name = Some(name.into());
+    }
+
+    /// Iterates over the tags of the clip, in the order they were added.
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.tags.iter().map(AsRef::as_ref)
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|existing| existing == tag)
+    }
+
+    /// Tags the clip, such as with `"combat"` or `"upper_body"`. Graphs the
+    /// clip is added to afterwards can find its nodes by tag with
+    /// [`AnimationGraph::clips_with_tag`]. Returns `false` if the clip
+    /// already had the tag.
+    ///
+    /// [`AnimationGraph::clips_with_tag`]: crate::graph::AnimationGraph::clips_with_tag
+    pub fn add_tag(&mut self, tag: impl Into<Cow<'static, str>>) -> bool {
+        let tag = tag.into();
+        if self.tags.contains(&tag) {
+            return false;
+        }
+        self.tags.push(tag);
+        true
+    }
+
+    /// Removes a tag from the clip. Returns `false` if the clip didn't have
+    /// the tag.
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let len = self.tags.len();
+        self.tags.retain(|existing| existing != tag);
+        self.tags.len() != len
+    }
+
+    /// Whether the clip is meant to be blended additively on top of other
+    /// clips. See [`AnimationClipBuilder::make_additive`].
+    pub fn is_additive(&self) -> bool {
+        self.additive
+    }
+
+    /// Creates a copy of the clip with every curve simplified with
+    /// [`simplify_curve`]. Curves that cannot be simplified are kept as is.
+    pub fn simplified(&self, tolerance: f32) -> AnimationClip {
+        AnimationClip {
+            curves: self
+                .curves
+                .iter()
+                .map(|(path, curve)| (path.clone(), curve.simplified(tolerance)))
+                .collect(),
+            params: self
+                .params
+                .iter()
+                .map(|(param, curve)| {
+                    let simplified = simplify_curve(curve.as_ref(), tolerance)
+                        .map(|curve| Arc::new(curve) as Arc<dyn Curve<f32>>)
+                        .unwrap_or_else(|_| curve.clone());
+                    (param.clone(), simplified)
+                })
+                .collect(),
+            additive: self.additive,
+            name: self.name.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+
+    /// Checks that every curve in the clip can be applied to the property it
+    /// targets. This checks that each component type is registered as a
+    /// component, and for types registered with [`ReflectDefault`], that the
+    /// field exists and matches the value type of the curve. A warning is
+    /// logged for each field that can't be checked, such as the fields of
+    /// other types and of assets.
+    ///
+    /// Entity paths are not checked. See [`AnimationGraph::validate_against`]
+    /// for validating against a concrete hierarchy.
+    ///
+    /// [`AnimationGraph::validate_against`]: crate::graph::AnimationGraph::validate_against
+    pub fn validate(&self, registry: &TypeRegistry) -> Vec<ClipValidationError> {
+        let mut errors = Vec::new();
+        for (path, curve) in self.curves.iter() {
+            let result = validate_component(registry, path.access()).and_then(|registration| {
+                // The fields of assets are in the asset, not its handle's component.
+                if let AccessTarget::Asset(_) = path.access().target() {
+                    warn!(
+                        "Cannot validate '{}', as the fields of assets can't be checked.",
+                        **path
+                    );
+                    return Ok(());
+                }
+                match registration.data::<ReflectDefault>() {
+                    Some(default) => validate_field(
+                        path.access(),
+                        default.default().as_ref(),
+                        curve.value_type_id(),
+                        curve.value_type_name(),
+                    ),
+                    None => {
+                        warn!(
+                            "Cannot validate '{}', as '{}' isn't registered with ReflectDefault.",
+                            **path,
+                            path.access().component_name()
+                        );
+                        Ok(())
+                    }
+                }
+            });
+            if let Err(kind) = result {
+                errors.push(ClipValidationError {
+                    path: (**path).clone(),
+                    kind,
+                });
+            }
+        }
+        errors
+    }
+
+    /// Samples the curve of a property at `time`, without a graph. Returns
+    /// `None` if the clip doesn't animate the property, or animates it with
+    /// another type or a custom curve. Useful for previews, like thumbnails
+    /// of a clip at evenly spaced times.
+    pub fn sample_property<T: Animatable>(&self, path: &PropertyPath, time: f32) -> Option<T> {
+        let curve = self.get_curve::<T>(&Hashed::new(path.clone())).ok()?;
+        Some(curve.sample(time))
+    }
+
+    /// Samples the curve of every property at `time`, in no particular
+    /// order. See [`ClipCurve::sample_reflect`].
+    pub fn sample_all_at(&self, time: f32) -> Vec<(PropertyPath, Box<dyn Reflect>)> {
+        self.curves
+            .iter()
+            .map(|(path, curve)| ((**path).clone(), curve.sample_reflect(time)))
+            .collect()
+    }
+
     pub fn get_curve<T: Animatable + 'static>(
         &self,
         key: &Hashed<PropertyPath>,
     ) -> Result<Arc<dyn Curve<T>>, GetCurveError> {
-        self.curves
+        let curve = self
+            .curves
             .get(key)
-            .ok_or(GetCurveError::MissingKey)
-            .and_then(|curve| {
-                curve
-                    .as_any()
-                    .downcast_ref::<CurveWrapper<T>>()
-                    .map(|wrapper| wrapper.0.clone())
-                    .ok_or(GetCurveError::WrongType)
+            .ok_or_else(|| GetCurveError::MissingKey((**key).clone()))?;
+        curve
+            .as_any()
+            .downcast_ref::<CurveWrapper<T>>()
+            .map(|wrapper| wrapper.0.clone())
+            .ok_or_else(|| GetCurveError::WrongType {
+                path: (**key).clone(),
+                expected: std::any::type_name::<T>(),
+                found: curve.value_type_name(),
             })
     }
 }
 
+/// How [`AnimationClip::merge_with`] resolves properties animated with the
+/// same type by both clips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Fail with [`MergeError::DuplicateProperty`].
+    Reject,
+    /// Use the curve from the other clip.
+    PreferOther,
+}
+
+impl Default for MergePolicy {
+    fn default() -> Self {
+        Self::Reject
+    }
+}
+
 pub struct AnimationClipBuilder {
     curves: PreHashMap<PropertyPath, Box<dyn ClipCurve>>,
+    params: HashMap<Cow<'static, str>, Arc<dyn Curve<f32>>>,
+    additive: bool,
+    name: Option<Cow<'static, str>>,
+    tags: Vec<Cow<'static, str>>,
+    sanitize: bool,
 }
 
 impl AnimationClipBuilder {
     pub fn new() -> AnimationClipBuilder {
         Self {
             curves: PreHashMap::default(),
+            params: HashMap::default(),
+            additive: false,
+            name: None,
+            tags: Vec::new(),
+            sanitize: false,
+        }
+    }
+
+    /// Names the clip. See [`AnimationClip::set_name`].
+    pub fn name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Tags the clip. See [`AnimationClip::add_tag`].
+    pub fn tag(mut self, tag: impl Into<Cow<'static, str>>) -> Self {
+        let tag = tag.into();
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
         }
+        self
+    }
+
+    /// If enabled, keyframes that aren't finite are replaced with their
+    /// nearest finite neighbor with [`Curve::sanitize`] as curves are added,
+    /// and a warning is logged. Only applies to curves added afterwards.
+    ///
+    /// Curves that are shared with other clips cannot be sanitized.
+    pub fn sanitize(mut self, sanitize: bool) -> Self {
+        self.sanitize = sanitize;
+        self
     }
 
+    /// Adds a curve for a property.
+    ///
+    /// # Panics
+    /// In debug builds, this will panic if the property already has a curve.
+    /// Release builds replace the existing curve. See
+    /// [`try_add_curve`](Self::try_add_curve) to handle duplicates, or
+    /// [`replace_curve`](Self::replace_curve) to replace curves on purpose.
     pub fn add_curve<T: Animatable + 'static>(
         self,
         key: impl Into<PropertyPath>,
         curve: impl Curve<T> + Send + Sync + 'static,
     ) -> Self {
-        self.add_dynamic_curve(key, Arc::new(curve))
+        self.add_dynamic_curve(key, into_dynamic_curve(curve))
     }
 
+    /// Like [`add_curve`](Self::add_curve), for curves that are already
+    /// shared.
     pub fn add_dynamic_curve<T: Animatable + 'static>(
-        mut self,
+        self,
         key: impl Into<PropertyPath>,
         curve: Arc<dyn Curve<T>>,
     ) -> Self {
-        self.curves
-            .insert(Hashed::new(key.into()), Box::new(CurveWrapper(curve)));
+        let key = Hashed::new(key.into());
+        let curve = self.wrap_curve(&key, curve);
+        self.insert_new_curve(key, curve)
+    }
+
+    /// Adds a curve for a property, or fails with
+    /// [`ClipBuildError::DuplicateProperty`] if the property already has a
+    /// curve.
+    pub fn try_add_curve<T: Animatable + 'static>(
+        mut self,
+        key: impl Into<PropertyPath>,
+        curve: impl Curve<T> + Send + Sync + 'static,
+    ) -> Result<Self, ClipBuildError> {
+        let key = Hashed::new(key.into());
+        if self.curves.contains_key(&key) {
+            return Err(ClipBuildError::DuplicateProperty((*key).clone()));
+        }
+        let curve = self.wrap_curve(&key, into_dynamic_curve(curve));
+        self.curves.insert(key, curve);
+        Ok(self)
+    }
+
+    /// Adds a curve for a property, replacing its existing curve if it has
+    /// one, whatever its type.
+    pub fn replace_curve<T: Animatable + 'static>(
+        mut self,
+        key: impl Into<PropertyPath>,
+        curve: impl Curve<T> + Send + Sync + 'static,
+    ) -> Self {
+        let key = Hashed::new(key.into());
+        let curve = self.wrap_curve(&key, into_dynamic_curve(curve));
+        self.curves.insert(key, curve);
         self
     }
 
-    pub fn build(self) -> AnimationClip {
-        AnimationClip {
+    /// Adds a curve for a property, replacing its existing curve if it has
+    /// one of the same type, like [`AnimationClip::merge_with`] with
+    /// [`MergePolicy::PreferOther`]. Fails with
+    /// [`ClipBuildError::ConflictingType`] if the existing curve is of a
+    /// different type.
+    pub fn merge_curve<T: Animatable + 'static>(
+        mut self,
+        key: impl Into<PropertyPath>,
+        curve: impl Curve<T> + Send + Sync + 'static,
+    ) -> Result<Self, ClipBuildError> {
+        let key = Hashed::new(key.into());
+        if let Some(existing) = self.curves.get(&key) {
+            if existing.value_type_id() != TypeId::of::<T>() {
+                return Err(ClipBuildError::ConflictingType {
+                    path: (*key).clone(),
+                    existing: existing.value_type_name(),
+                    new: std::any::type_name::<T>(),
+                });
+            }
+        }
+        let curve = self.wrap_curve(&key, into_dynamic_curve(curve));
+        self.curves.insert(key, curve);
+        Ok(self)
+    }
+
+    fn wrap_curve<T: Animatable + 'static>(
+        &self,
+        key: &PropertyPath,
+        mut curve: Arc<dyn Curve<T>>,
+    ) -> Box<dyn ClipCurve> {
+        if self.sanitize {
+            sanitize_curve(key, &mut curve);
+        }
+        Box::new(CurveWrapper(curve))
+    }
+
+    /// Inserts the curve of a property that shouldn't have one yet. See
+    /// [`add_curve`](Self::add_curve).
+    fn insert_new_curve(mut self, key: Hashed<PropertyPath>, curve: Box<dyn ClipCurve>) -> Self {
+        debug_assert!(
+            !self.curves.contains_key(&key),
+            "'{}' already has a curve. Use `try_add_curve` to detect duplicate \
+            properties, or `replace_curve` to replace the existing curve.",
+            *key
+        );
+        self.curves.insert(key, curve);
+        self
+    }
+
+    /// Adds a curve that creates its own [`Track`] when the clip is added to
+    /// a graph, to blend a property differently from [`Animatable::blend`].
+    /// See [`ClipCurve`].
+    ///
+    /// Custom curves are not sanitized, but non-finite keyframes reported by
+    /// [`ClipCurve::find_non_finite`] are still rejected by [`try_build`].
+    ///
+    /// [`try_build`]: Self::try_build
+    ///
+    /// # Panics
+    /// Like [`add_curve`](Self::add_curve), this will panic in debug builds if
+    /// the property already has a curve.
+    pub fn add_custom_curve(self, key: impl Into<PropertyPath>, curve: Box<dyn ClipCurve>) -> Self {
+        self.insert_new_curve(Hashed::new(key.into()), curve)
+    }
+
+    /// Adds curves for the weights of a list of morph targets, or blend
+    /// shapes, such as `Blendshapes.weights`. Each curve animates the weight
+    /// at its index in the list, which must be a list of `f32`s.
+    ///
+    /// The curves are blended as a single track for the whole list: each
+    /// index is blended across clips on its own, and the list is fetched
+    /// once and every animated index written in a single pass, rather than
+    /// once per index as with a curve for each `weights[i]`. Indices the
+    /// clips don't animate are left unchanged. Indices beyond the end of the
+    /// list are skipped, with a warning the first time.
+    ///
+    /// ```rust,ignore
+    /// let blink = AnimationClip::builder()
+    ///     .add_morph_curves(weights_path, vec![(EYE_BLINK_LEFT, left), (EYE_BLINK_RIGHT, right)])
+    ///     .build();
+    /// ```
+    ///
+    /// # Panics
+    /// Like [`add_curve`](Self::add_curve), this will panic in debug builds if
+    /// the list already has curves.
+    pub fn add_morph_curves(
+        self,
+        key: impl Into<PropertyPath>,
+        curves: Vec<(usize, Arc<dyn Curve<f32>>)>,
+    ) -> Self {
+        self.add_custom_curve(key, Box::new(MorphCurves::new(curves)))
+    }
+
+    /// Adds a [`Tween`] from `start` to `end` over `duration` seconds for a
+    /// property.
+    ///
+    /// ```rust,ignore
+    /// let fade_in = AnimationClip::builder()
+    ///     .add_tween(alpha_path, 0.0f32, 1.0, 0.3, Easing::QuadOut)
+    ///     .build();
+    /// ```
+    pub fn add_tween<T: Animatable + 'static>(
+        self,
+        key: impl Into<PropertyPath>,
+        start: T,
+        end: T,
+        duration: f32,
+        easing: Easing,
+    ) -> Self {
+        self.add_curve(key, Tween::new(start, end, duration, easing))
+    }
+
+    /// Adds the same curve for multiple properties. The curve is shared
+    /// between all of the properties, both in the clip and in the
+    /// [`AnimationGraph`]s the clip is added to.
+    ///
+    /// [`AnimationGraph`]: crate::graph::AnimationGraph
+    pub fn add_curve_multi<T: Animatable + 'static>(
+        mut self,
+        keys: impl IntoIterator<Item = PropertyPath>,
+        curve: impl Curve<T> + Send + Sync + 'static,
+    ) -> Self {
+        let mut curve = into_dynamic_curve(curve);
+        for key in keys {
+            // Sanitize before the curve is shared.
+            if self.sanitize {
+                sanitize_curve(&key, &mut curve);
+            }
+            self = self.add_dynamic_curve(key, curve.clone());
+        }
+        self
+    }
+
+    /// Adds a curve for a parameter of the [`AnimationGraph`]s the clip is
+    /// added to, instead of a property of an entity. Whenever a graph is
+    /// evaluated, the curve is sampled at the clip's time and the parameter
+    /// is set to the result, driving any inputs bound to it with a
+    /// [`WeightBinding`].
+    ///
+    /// ```rust,ignore
+    /// let cutscene = AnimationClip::builder()
+    ///     .add_param_curve("worried", CurveVariableLinear::from_line(0.0, 2.0, 0.0, 1.0))
+    ///     .build();
+    /// ```
+    ///
+    /// Curves are sampled whether or not the clip is blended. If multiple
+    /// clips in a graph have a curve for the same parameter, the clip added
+    /// last wins.
+    ///
+    /// [`AnimationGraph`]: crate::graph::AnimationGraph
+    /// [`WeightBinding`]: crate::graph::WeightBinding
+    pub fn add_param_curve(
+        mut self,
+        param: impl Into<Cow<'static, str>>,
+        curve: impl Curve<f32> + Send + Sync + 'static,
+    ) -> Self {
+        let param = param.into();
+        let mut curve = into_dynamic_curve(curve);
+        if self.sanitize {
+            sanitize_curve(&param, &mut curve);
+        }
+        self.params.insert(param, curve);
+        self
+    }
+
+    /// Adds a curve that toggles the [`Visibility`] of the entity at
+    /// `entity_path`. Each keyframe is a time in seconds, and whether the
+    /// entity is visible from then until the next keyframe.
+    ///
+    /// # Panics
+    /// This will panic if `keyframes` is empty or isn't sorted by time.
+    ///
+    /// [`Visibility`]: bevy_render::view::Visibility
+    #[cfg(feature = "bevy_render")]
+    pub fn add_visibility_track(
+        self,
+        entity_path: EntityPath,
+        keyframes: Vec<(f32, bool)>,
+    ) -> Self {
+        use bevy_render::view::Visibility;
+
+        assert!(
+            !keyframes.is_empty(),
+            "A visibility track needs at least one keyframe."
+        );
+        let (times, values) = keyframes
+            .into_iter()
+            .map(|(time, is_visible)| (time, Visibility { is_visible }))
+            .unzip();
+        let curve = CurveVariableLinear::with_keyframes(times, values)
+            .expect("Visibility keyframes must be sorted by time.");
+        let access = AccessPath::from_parts(
+            TypeId::of::<Visibility>(),
+            std::any::type_name::<Visibility>(),
+            FieldPath::root(),
+        );
+        self.add_curve(PropertyPath::from_parts(entity_path, access), curve)
+    }
+
+    /// Converts the curves added so far into offsets from `reference`'s pose
+    /// at `reference_time`, using [`Animatable::difference`], and marks the
+    /// clip as additive. Clip nodes for additive clips blend additively by
+    /// default.
+    ///
+    /// Curves for properties that `reference` doesn't animate, or animates
+    /// with a different type, are left unchanged.
+    pub fn make_additive(mut self, reference: &AnimationClip, reference_time: f32) -> Self {
+        for (path, curve) in self.curves.iter_mut() {
+            let additive = reference
+                .curves
+                .get(path)
+                .and_then(|reference| curve.make_additive(reference.as_ref(), reference_time));
+            if let Some(additive) = additive {
+                *curve = additive;
+            }
+        }
+        self.additive = true;
+        self
+    }
+
+    /// Builds the clip, checking that every keyframe is finite with
+    /// [`Animatable::is_finite`].
+    ///
+    /// A single NaN keyframe makes every blend involving it NaN, so clips with
+    /// non-finite keyframes are rejected. See [`AnimationClipBuilder::sanitize`]
+    /// to repair them instead.
+    pub fn try_build(self) -> Result<AnimationClip, CurveError> {
+        for (path, curve) in self.curves.iter() {
+            if let Some(index) = curve.find_non_finite() {
+                return Err(CurveError::NonFiniteKeyframe {
+                    path: (**path).clone(),
+                    index,
+                });
+            }
+        }
+        for (param, curve) in self.params.iter() {
+            if let Some(index) = curve.find_non_finite() {
+                return Err(CurveError::NonFiniteParamKeyframe {
+                    param: param.clone(),
+                    index,
+                });
+            }
+        }
+        Ok(AnimationClip {
             curves: self.curves,
+            params: self.params,
+            additive: self.additive,
+            name: self.name,
+            tags: self.tags,
+        })
+    }
+
+    /// Builds the clip. See [`AnimationClipBuilder::try_build`].
+    ///
+    /// # Panics
+    /// This will panic if any of the curves have a keyframe that isn't finite.
+    pub fn build(self) -> AnimationClip {
+        match self.try_build() {
+            Ok(clip) => clip,
+            Err(err) => panic!("Failed to build AnimationClip: {}", err),
         }
     }
 }
 
+fn sanitize_curve<T: 'static>(path: &dyn Display, curve: &mut Arc<dyn Curve<T>>) {
+    let replaced = Arc::get_mut(curve).map_or(0, |curve| curve.sanitize());
+    if replaced > 0 {
+        warn!(
+            "Replaced {} non-finite keyframe(s) of '{}' with their nearest finite neighbors.",
+            replaced, path
+        );
+    }
+}
+
+/// Converts a curve into a shared curve. Curves that are already shared are
+/// used as is, instead of being wrapped again.
+pub(crate) fn into_dynamic_curve<T, C>(curve: C) -> Arc<dyn Curve<T>>
+where
+    T: 'static,
+    C: Curve<T> + Send + Sync + 'static,
+{
+    let mut curve = Some(curve);
+    if let Some(shared) = (&mut curve as &mut dyn Any).downcast_mut::<Option<Arc<dyn Curve<T>>>>() {
+        return shared.take().unwrap();
+    }
+    Arc::new(curve.unwrap())
+}
+
+#[derive(Error, Debug)]
 pub enum GetCurveError {
-    MissingKey,
-    WrongType,
+    #[error("the clip does not have a curve for '{0}'")]
+    MissingKey(PropertyPath),
+    #[error("the curve for '{path}' is of type '{found}', not '{expected}'")]
+    WrongType {
+        path: PropertyPath,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum ClipBuildError {
+    #[error("'{0}' already has a curve")]
+    DuplicateProperty(PropertyPath),
+    #[error("'{path}' is animated as '{existing}' and cannot be merged with '{new}'")]
+    ConflictingType {
+        path: PropertyPath,
+        existing: &'static str,
+        new: &'static str,
+    },
+}
+
+#[derive(Error, Debug)]
+pub enum MergeError {
+    #[error("'{path}' is animated as '{existing}' and cannot be merged with '{new}'")]
+    ConflictingType {
+        path: PropertyPath,
+        existing: &'static str,
+        new: &'static str,
+    },
+    #[error("both clips animate '{0}'")]
+    DuplicateProperty(PropertyPath),
+    #[error("both clips have a curve for the graph parameter '{0}'")]
+    DuplicateParam(Cow<'static, str>),
+    #[error("additive clips cannot be merged with non-additive clips")]
+    MismatchedAdditive,
+}
+
+/// Type data for creating default instances of a reflected type. Registering
+/// a component with `#[reflect(Default)]` allows [`AnimationClip::validate`] to
+/// check the fields targeted by a clip.
+#[derive(Clone)]
+pub struct ReflectDefault {
+    default: fn() -> Box<dyn Reflect>,
+}
+
+impl ReflectDefault {
+    /// Creates a default instance of the type.
+    pub fn default(&self) -> Box<dyn Reflect> {
+        (self.default)()
+    }
+}
+
+impl<T: Reflect + Default> FromType<T> for ReflectDefault {
+    fn from_type() -> Self {
+        Self {
+            default: || Box::new(T::default()),
+        }
+    }
+}
+
+/// An error found when validating a clip. See [`AnimationClip::validate`].
+#[derive(Error, Debug)]
+#[error("cannot animate '{path}': {kind}")]
+pub struct ClipValidationError {
+    pub path: PropertyPath,
+    pub kind: ClipValidationErrorKind,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ClipValidationErrorKind {
+    #[error("component type '{0}' is not registered")]
+    UnregisteredComponent(String),
+    #[error("type '{0}' is not registered as a component")]
+    NotAComponent(String),
+    #[error("type '{0}' is not registered as a resource")]
+    NotAResource(String),
+    #[error("invalid field: {0}")]
+    InvalidField(String),
+    #[error("field is of type '{field}' but the curve is of type '{curve}'")]
+    MismatchedType { field: String, curve: &'static str },
+    #[error("no entity was found at the path")]
+    MissingEntity,
+    #[error("the entity does not have the component")]
+    MissingComponent,
+    #[error("the resource does not exist")]
+    MissingResource,
+    #[error("the asset is not loaded, or its handle is not registered")]
+    MissingAsset,
+}
+
+pub(crate) fn validate_component<'a>(
+    registry: &'a TypeRegistry,
+    access: &AccessPath,
+) -> Result<&'a TypeRegistration, ClipValidationErrorKind> {
+    let registration = registry.get(access.component_type_id()).ok_or_else(|| {
+        ClipValidationErrorKind::UnregisteredComponent(access.component_name().to_string())
+    })?;
+    if *access.target() == AccessTarget::Resource {
+        if registration.data::<ReflectResource>().is_none() {
+            return Err(ClipValidationErrorKind::NotAResource(
+                access.component_name().to_string(),
+            ));
+        }
+    } else if registration.data::<ReflectComponent>().is_none() {
+        return Err(ClipValidationErrorKind::NotAComponent(
+            access.component_name().to_string(),
+        ));
+    }
+    Ok(registration)
+}
+
+pub(crate) fn validate_field(
+    access: &AccessPath,
+    component: &dyn Reflect,
+    value_type_id: TypeId,
+    value_type_name: &'static str,
+) -> Result<(), ClipValidationErrorKind> {
+    let field = access
+        .field_path()
+        .field(component)
+        .map_err(|err| ClipValidationErrorKind::InvalidField(err.to_string()))?;
+    if field.any().type_id() != value_type_id {
+        return Err(ClipValidationErrorKind::MismatchedType {
+            field: field.type_name().to_string(),
+            curve: value_type_name,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        curve::{resample_preserving_loop, CurveFixed, CurveVariableLinear},
+        graph::{AnimationGraph, NodeId},
+    };
+    use bevy_ecs::prelude::*;
+    use bevy_math::Vec3;
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component, Default)]
+    struct Test {
+        a: f32,
+        b: bool,
+    }
+
+    fn registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        registry
+    }
+
+    fn validate(path: &str, curve: impl ClipCurve) -> Vec<ClipValidationError> {
+        let registry = registry();
+        let path = PropertyPath::parse(&registry, path).unwrap();
+        let mut clip = AnimationClip::builder().build();
+        clip.curves.insert(Hashed::new(path), Box::new(curve));
+        clip.validate(&registry)
+    }
+
+    fn f32_curve() -> CurveWrapper<f32> {
+        CurveWrapper(Arc::new(CurveFixed::from_keyframes(1.0, vec![0.0, 1.0])))
+    }
+
+    #[test]
+    pub fn test_validate_valid_clip() {
+        let errors = validate(
+            "a@bevy_prototype_animation::clip::test::Test.a",
+            f32_curve(),
+        );
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    pub fn test_validate_invalid_field() {
+        let errors = validate(
+            "a@bevy_prototype_animation::clip::test::Test.c",
+            f32_curve(),
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].path.to_string(),
+            "a@bevy_prototype_animation::clip::test::Test.c"
+        );
+        assert!(matches!(
+            errors[0].kind,
+            ClipValidationErrorKind::InvalidField(_)
+        ));
+    }
+
+    #[test]
+    pub fn test_validate_mismatched_type() {
+        let errors = validate(
+            "a@bevy_prototype_animation::clip::test::Test.b",
+            f32_curve(),
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].kind,
+            ClipValidationErrorKind::MismatchedType {
+                field: "bool".to_string(),
+                curve: "f32",
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_validate_unregistered_component() {
+        let path = PropertyPath::parse(
+            &registry(),
+            "a@bevy_prototype_animation::clip::test::Test.a",
+        )
+        .unwrap();
+        let clip = AnimationClip::builder()
+            .add_curve(path, CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]))
+            .build();
+        let errors = clip.validate(&TypeRegistry::default());
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            ClipValidationErrorKind::UnregisteredComponent(_)
+        ));
+    }
+
+    #[test]
+    pub fn test_shared_curves_are_not_rewrapped() {
+        let registry = registry();
+        let path = PropertyPath::parse(&registry, "a@bevy_prototype_animation::clip::test::Test.c")
+            .unwrap();
+        let curve: Arc<dyn Curve<Vec3>> = Arc::new(CurveFixed::from_keyframes(
+            2.0,
+            vec![Vec3::ZERO, Vec3::X, Vec3::Y],
+        ));
+
+        let resampled = resample_preserving_loop(&curve, 4.0);
+        assert_eq!(resampled.keyframe_count(), 5);
+        assert_eq!(resampled.iter().next(), Some(&Vec3::ZERO));
+        assert_eq!(resampled.iter().last(), Some(&Vec3::Y));
+
+        let clip = AnimationClip::builder()
+            .add_curve(path.clone(), curve.clone())
+            .build();
+        let stored = clip.get_curve::<Vec3>(&Hashed::new(path)).unwrap();
+        assert_eq!(
+            Arc::as_ptr(&stored) as *const (),
+            Arc::as_ptr(&curve) as *const ()
+        );
+    }
+
+    #[test]
+    pub fn test_non_finite_keyframes_are_rejected() {
+        let registry = registry();
+        let path = PropertyPath::parse(
+            &registry,
+            "a@bevy_prototype_animation::clip::test::Test.position",
+        )
+        .unwrap();
+        let keyframes = vec![Vec3::ZERO, Vec3::new(0.0, f32::NAN, 0.0), Vec3::X];
+        let result = AnimationClip::builder()
+            .add_curve(path.clone(), CurveFixed::from_keyframes(1.0, keyframes))
+            .try_build();
+        match result {
+            Err(CurveError::NonFiniteKeyframe {
+                path: error_path,
+                index,
+            }) => {
+                assert_eq!(error_path, path);
+                assert_eq!(index, 1);
+            }
+            _ => panic!("expected the clip to be rejected"),
+        }
+
+        let infinite =
+            CurveVariableLinear::with_keyframes(vec![0.0, 1.0], vec![0.0f32, f32::INFINITY])
+                .unwrap();
+        let a = PropertyPath::parse(&registry, "a@bevy_prototype_animation::clip::test::Test.a")
+            .unwrap();
+        assert!(AnimationClip::builder()
+            .add_curve(a, infinite)
+            .try_build()
+            .is_err());
+    }
+
+    #[test]
+    pub fn test_non_finite_keyframes_are_sanitized() {
+        let registry = registry();
+        let path = PropertyPath::parse(
+            &registry,
+            "a@bevy_prototype_animation::clip::test::Test.position",
+        )
+        .unwrap();
+        let nan = Vec3::splat(f32::NAN);
+        let keyframes = vec![nan, Vec3::X, nan, nan, nan, Vec3::Y, Vec3::Z, nan];
+        let clip = AnimationClip::builder()
+            .sanitize(true)
+            .add_curve(path.clone(), CurveFixed::from_keyframes(1.0, keyframes))
+            .try_build()
+            .unwrap();
+        let curve = clip.get_curve::<Vec3>(&Hashed::new(path)).unwrap();
+        let sanitized: Vec<Vec3> = (0..8).map(|frame| curve.sample(frame as f32)).collect();
+        assert_eq!(
+            sanitized,
+            vec![
+                Vec3::X,
+                Vec3::X,
+                Vec3::X,
+                Vec3::X,
+                Vec3::Y,
+                Vec3::Y,
+                Vec3::Z,
+                Vec3::Z
+            ]
+        );
+    }
+
+    fn test_path(path: &str) -> PropertyPath {
+        PropertyPath::parse(&registry(), path).unwrap()
+    }
+
+    #[test]
+    pub fn test_merge_disjoint_clips() {
+        let face = test_path("face@bevy_prototype_animation::clip::test::Test.a");
+        let body = test_path("body@bevy_prototype_animation::clip::test::Test.position");
+        let face_clip = AnimationClip::builder()
+            .add_curve(
+                face.clone(),
+                CurveFixed::from_keyframes(2.0, vec![0.0f32, 1.0]),
+            )
+            .build();
+        let body_clip = AnimationClip::builder()
+            .add_curve(
+                body.clone(),
+                CurveFixed::from_keyframes(4.0, vec![Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::Z]),
+            )
+            .build();
+        let merged = face_clip.merge(&body_clip).unwrap();
+
+        let mut curves: Vec<_> = merged.iter_curves().collect();
+        curves.sort_by(|a, b| a.0.to_string().cmp(&b.0.to_string()));
+        assert_eq!(
+            curves,
+            vec![
+                (&body, TypeId::of::<Vec3>(), 4, 0.75),
+                (&face, TypeId::of::<f32>(), 2, 0.5),
+            ]
+        );
+        assert_eq!(merged.duration(), 0.75);
+
+        // The merged clip animates both sets of properties through a graph.
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&merged).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+        for time in [0.0, 0.25, 0.5, 0.75] {
+            let face_curve = face_clip
+                .get_curve::<f32>(&Hashed::new(face.clone()))
+                .unwrap();
+            let body_curve = body_clip
+                .get_curve::<Vec3>(&Hashed::new(body.clone()))
+                .unwrap();
+            assert_eq!(
+                graph.sample_clip_property::<f32>(node, &face, time),
+                Some(face_curve.sample(time))
+            );
+            assert_eq!(
+                graph.sample_clip_property::<Vec3>(node, &body, time),
+                Some(body_curve.sample(time))
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_smooth_loop_closes_every_curve() {
+        let position = test_path("body@bevy_prototype_animation::clip::test::Test.position");
+        let a = test_path("body@bevy_prototype_animation::clip::test::Test.a");
+        // Both curves end close to, but not at, their first keyframe.
+        let keyframes = (0..=8)
+            .map(|f| Vec3::new(f as f32 * 0.1, 1.0, 0.0))
+            .collect();
+        let clip = AnimationClip::builder()
+            .add_curve(position.clone(), CurveFixed::from_keyframes(8.0, keyframes))
+            .add_curve(
+                a.clone(),
+                CurveFixed::from_keyframes(2.0, vec![0.0f32, 2.0, 0.2]),
+            )
+            .add_param_curve("lean", CurveFixed::from_keyframes(2.0, vec![0.5, 1.0, 0.6]))
+            .build();
+        let smoothed = clip.smooth_loop(0.25);
+
+        let original = clip
+            .get_curve::<Vec3>(&Hashed::new(position.clone()))
+            .unwrap();
+        let curve = smoothed.get_curve::<Vec3>(&Hashed::new(position)).unwrap();
+        assert!(curve.sample(1.0).abs_diff_eq(curve.sample(0.0), 1e-5));
+        for time in [0.0, 0.25, 0.5, 0.75] {
+            assert_eq!(curve.sample(time), original.sample(time));
+        }
+        let curve = smoothed.get_curve::<f32>(&Hashed::new(a)).unwrap();
+        assert!((curve.sample(1.0) - curve.sample(0.0)).abs() < 1e-5);
+        assert_eq!(curve.sample(0.5), 2.0);
+        let param = smoothed.get_param_curve("lean").unwrap();
+        assert!((param.sample(1.0) - 0.5).abs() < 1e-5);
+        assert_eq!(smoothed.duration(), clip.duration());
+    }
+
+    #[test]
+    pub fn test_clips_are_sampled_without_a_graph() {
+        let a = test_path("a@bevy_prototype_animation::clip::test::Test.a");
+        let b = test_path("a@bevy_prototype_animation::clip::test::Test.b");
+        let position = test_path("a@bevy_prototype_animation::clip::test::Test.position");
+        let clip = AnimationClip::builder()
+            .add_curve(
+                a.clone(),
+                CurveFixed::from_keyframes(2.0, vec![0.0f32, 1.0, 3.0]),
+            )
+            .add_curve(
+                b.clone(),
+                CurveFixed::from_keyframes(2.0, vec![false, true]),
+            )
+            .add_curve(
+                position.clone(),
+                CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::new(2.0, 4.0, 6.0)]),
+            )
+            .build();
+
+        assert_eq!(clip.sample_property::<f32>(&a, 0.5), Some(1.0));
+        assert_eq!(clip.sample_property::<Vec3>(&a, 0.5), None);
+        let missing = test_path("b@bevy_prototype_animation::clip::test::Test.a");
+        assert_eq!(clip.sample_property::<f32>(&missing, 0.5), None);
+
+        let samples = clip.sample_all_at(0.5);
+        assert_eq!(samples.len(), 3);
+        for (path, value) in samples {
+            if path == a {
+                assert_eq!(value.downcast_ref::<f32>(), Some(&1.0));
+            } else if path == b {
+                assert_eq!(value.downcast_ref::<bool>(), Some(&true));
+            } else {
+                assert_eq!(path, position);
+                assert_eq!(
+                    value.downcast_ref::<Vec3>(),
+                    Some(&Vec3::new(1.0, 2.0, 3.0))
+                );
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_clip_names_and_tags() {
+        let mut clip = AnimationClip::builder()
+            .name("punch")
+            .tag("combat")
+            .tag("combat")
+            .tag("upper_body")
+            .build();
+        assert_eq!(clip.name(), Some("punch"));
+        assert_eq!(
+            clip.tags().collect::<Vec<_>>(),
+            vec!["combat", "upper_body"]
+        );
+        assert!(!clip.add_tag("combat"));
+        assert!(clip.remove_tag("upper_body"));
+        assert!(!clip.has_tag("upper_body"));
+        clip.set_name(String::from("jab"));
+
+        // Metadata is kept by copies of the clip, and merged clips keep the
+        // name of the first clip and the tags of both.
+        let other = AnimationClip::builder()
+            .name("kick")
+            .tag("lower_body")
+            .tag("combat")
+            .build();
+        let merged = clip.simplified(1e-3).merge(&other).unwrap();
+        assert_eq!(merged.name(), Some("jab"));
+        assert_eq!(
+            merged.tags().collect::<Vec<_>>(),
+            vec!["combat", "lower_body"]
+        );
+    }
+
+    #[test]
+    pub fn test_merge_conflicting_clips() {
+        let path = test_path("a@bevy_prototype_animation::clip::test::Test.a");
+        let clip = |value: f32| {
+            AnimationClip::builder()
+                .add_curve(path.clone(), CurveFixed::from_keyframes(1.0, vec![value]))
+                .build()
+        };
+        let (first, second) = (clip(1.0), clip(2.0));
+        assert!(matches!(
+            first.merge(&second),
+            Err(MergeError::DuplicateProperty(_))
+        ));
+        let merged = first.merge_with(&second, MergePolicy::PreferOther).unwrap();
+        let curve = merged.get_curve::<f32>(&Hashed::new(path.clone())).unwrap();
+        assert_eq!(curve.sample(0.0), 2.0);
+
+        let other_type = AnimationClip::builder()
+            .add_curve(path, CurveFixed::from_keyframes(1.0, vec![Vec3::X]))
+            .build();
+        for policy in [MergePolicy::Reject, MergePolicy::PreferOther] {
+            assert!(matches!(
+                first.merge_with(&other_type, policy),
+                Err(MergeError::ConflictingType { .. })
+            ));
+        }
+
+        let additive = AnimationClip::builder().make_additive(&first, 0.0).build();
+        assert!(matches!(
+            first.merge(&additive),
+            Err(MergeError::MismatchedAdditive)
+        ));
+    }
+
+    #[test]
+    pub fn test_duplicate_curves_are_detected() {
+        let path = test_path("a@bevy_prototype_animation::clip::test::Test.a");
+        let other = test_path("b@bevy_prototype_animation::clip::test::Test.a");
+        let curve = |value: f32| CurveFixed::from_keyframes(1.0, vec![value]);
+        let sample = |clip: &AnimationClip| {
+            let curve = clip.get_curve::<f32>(&Hashed::new(path.clone())).unwrap();
+            curve.sample(0.0)
+        };
+        let builder = AnimationClip::builder()
+            .try_add_curve(path.clone(), curve(1.0))
+            .unwrap()
+            .try_add_curve(other, curve(2.0))
+            .unwrap();
+        let builder = match builder.try_add_curve(path.clone(), curve(3.0)) {
+            Err(ClipBuildError::DuplicateProperty(duplicate)) => {
+                assert_eq!(duplicate, path);
+                AnimationClip::builder().add_curve(path.clone(), curve(1.0))
+            }
+            _ => panic!("expected a duplicate property error"),
+        };
+
+        let replaced = builder.replace_curve(path.clone(), curve(4.0));
+        assert_eq!(sample(&replaced.build()), 4.0);
+        // Replacing doesn't care about the type of the existing curve.
+        let replaced = AnimationClip::builder()
+            .add_curve(path.clone(), CurveFixed::from_keyframes(1.0, vec![Vec3::X]))
+            .replace_curve(path.clone(), curve(5.0));
+        assert_eq!(sample(&replaced.build()), 5.0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "already has a curve")]
+    pub fn test_duplicate_curves_panic_in_debug() {
+        let path = test_path("a@bevy_prototype_animation::clip::test::Test.a");
+        AnimationClip::builder()
+            .add_curve(path.clone(), CurveFixed::from_keyframes(1.0, vec![1.0f32]))
+            .add_curve(path, CurveFixed::from_keyframes(1.0, vec![2.0f32]));
+    }
+
+    #[test]
+    pub fn test_merge_curve_requires_the_same_type() {
+        let path = test_path("a@bevy_prototype_animation::clip::test::Test.a");
+        let merged = AnimationClip::builder()
+            .merge_curve(path.clone(), CurveFixed::from_keyframes(1.0, vec![1.0f32]))
+            .unwrap()
+            .merge_curve(path.clone(), CurveFixed::from_keyframes(1.0, vec![2.0f32]))
+            .unwrap()
+            .build();
+        let curve = merged.get_curve::<f32>(&Hashed::new(path.clone())).unwrap();
+        assert_eq!(curve.sample(0.0), 2.0);
+
+        let result = AnimationClip::builder()
+            .add_curve(path.clone(), CurveFixed::from_keyframes(1.0, vec![1.0f32]))
+            .merge_curve(path, CurveFixed::from_keyframes(1.0, vec![Vec3::X]));
+        assert!(matches!(
+            result,
+            Err(ClipBuildError::ConflictingType { existing, new, .. })
+                if existing == std::any::type_name::<f32>()
+                    && new == std::any::type_name::<Vec3>()
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "bevy_render")]
+    pub fn test_visibility_track_hides_child() {
+        use crate::AnimationPlugin;
+        use bevy_app::App;
+        use bevy_asset::AssetPlugin;
+        use bevy_core::Name;
+        use bevy_render::view::Visibility;
+        use bevy_tasks::{ComputeTaskPool, IoTaskPool, TaskPool};
+        use bevy_transform::prelude::Children;
+        use std::str::FromStr;
+
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .insert_resource(ComputeTaskPool(TaskPool::new()))
+            .add_plugin(AssetPlugin)
+            .add_plugin(AnimationPlugin::default())
+            .register_type::<Visibility>();
+
+        let clip = AnimationClip::builder()
+            .add_visibility_track(
+                EntityPath::from_str("child").unwrap(),
+                vec![(0.0, true), (0.5, false)],
+            )
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+
+        let child = app
+            .world
+            .spawn()
+            .insert(Name::new("child"))
+            .insert(Visibility::default())
+            .id();
+        let parent = app
+            .world
+            .spawn()
+            .insert(graph)
+            .insert(Children::with(&[child]))
+            .id();
+
+        for frame in 0..=10 {
+            let time = frame as f32 / 10.0;
+            app.world
+                .get_mut::<AnimationGraph>(parent)
+                .unwrap()
+                .sample_at(time);
+            app.update();
+            let visibility = app.world.get::<Visibility>(child).unwrap();
+            assert_eq!(visibility.is_visible, time < 0.5, "t = {}", time);
+        }
+    }
 }
@@ -6,6 +6,122 @@ use bevy_core::FloatOrd;
 use bevy_math::*;
 use bevy_transform::prelude::Transform;
 
+/// Settings for compressing curves.
+///
+/// Channels are quantized to 16 bits across the range of their values. The
+/// quantization error of channels with outlying keyframes can exceed the
+/// allowed error, in which case they are stored uncompressed instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompressionSettings {
+    /// The largest error allowed in any channel other than a rotation's.
+    pub max_error: f32,
+    /// The largest error allowed in the angle of a rotation, in degrees.
+    pub rotation_error_degrees: f32,
+    /// Whether channels that stay within the allowed error of a single value
+    /// are stored as that value. Exactly constant channels are always stored
+    /// as a single value.
+    pub allow_channel_removal: bool,
+}
+
+impl Default for CompressionSettings {
+    fn default() -> Self {
+        Self {
+            max_error: 1e-4,
+            rotation_error_degrees: 0.01,
+            allow_channel_removal: true,
+        }
+    }
+}
+
+impl CompressionSettings {
+    /// The largest error allowed in each component of a rotation.
+    ///
+    /// For small differences, the angle between two unit quaternions is about
+    /// twice the length of their difference, so an error of `e` in each of
+    /// the four components is at most an angle of `4e`.
+    fn rotation_component_error(&self) -> f32 {
+        self.rotation_error_degrees.to_radians() * 0.25
+    }
+}
+
+/// How a channel of a compressed curve is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelCompression {
+    /// A single value for every keyframe.
+    Static,
+    /// 16 bits per keyframe.
+    Quantized,
+    /// An uncompressed `f32` per keyframe.
+    Raw,
+}
+
+/// How a single channel of a curve was compressed. See [`CompressionReport`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelReport {
+    /// The name of the channel, like `"x"` or `"rotation.w"`.
+    pub name: &'static str,
+    pub mode: ChannelCompression,
+    /// The largest difference between a keyframe and its compressed value.
+    pub max_error: f32,
+    /// The root mean square of the differences between the keyframes and
+    /// their compressed values.
+    pub rms_error: f32,
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+}
+
+/// A summary of how a curve was compressed, for build pipelines to log.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompressionReport {
+    pub channels: Vec<ChannelReport>,
+}
+
+impl CompressionReport {
+    /// The size of the curve's keyframes before compression, in bytes.
+    pub fn bytes_before(&self) -> usize {
+        self.channels
+            .iter()
+            .map(|channel| channel.bytes_before)
+            .sum()
+    }
+
+    /// The size of the curve's keyframes after compression, in bytes.
+    pub fn bytes_after(&self) -> usize {
+        self.channels
+            .iter()
+            .map(|channel| channel.bytes_after)
+            .sum()
+    }
+
+    /// The largest error of any channel.
+    pub fn max_error(&self) -> f32 {
+        self.channels
+            .iter()
+            .map(|channel| channel.max_error)
+            .fold(0.0, f32::max)
+    }
+}
+
+impl std::fmt::Display for CompressionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> {} bytes, max error {}",
+            self.bytes_before(),
+            self.bytes_after(),
+            self.max_error()
+        )?;
+        for channel in self.channels.iter() {
+            write!(
+                f,
+                "\n  {}: {:?}, max error {}, rms error {}",
+                channel.name, channel.mode, channel.max_error, channel.rms_error
+            )?;
+        }
+        Ok(())
+    }
+}
+
 enum CompressedFloat32Storage {
     Static {
         frames: usize,
@@ -16,10 +132,34 @@ enum CompressedFloat32Storage {
         min_value: f32,
         increment: f32,
     },
+    Raw {
+        frames: Box<[f32]>,
+    },
+}
+
+/// Measures the error of compressed values.
+fn channel_error(values: &[f32], decoded: impl Iterator<Item = f32>) -> (f32, f32) {
+    let mut max_error = 0.0f32;
+    let mut squared_error = 0.0;
+    for (value, decoded) in values.iter().zip(decoded) {
+        let error = (value - decoded).abs();
+        max_error = max_error.max(error);
+        squared_error += error * error;
+    }
+    (max_error, (squared_error / values.len() as f32).sqrt())
 }
 
 impl CompressedFloat32Storage {
-    pub fn quantize(values: impl Iterator<Item = f32>) -> Self {
+    /// Compresses a channel, storing it as a single value if it stays within
+    /// `max_error` of one (or exactly, if channel removal isn't allowed), and
+    /// quantizing it otherwise, unless that would exceed `max_error`.
+    fn quantize(
+        name: &'static str,
+        values: impl Iterator<Item = f32>,
+        max_error: f32,
+        settings: &CompressionSettings,
+        report: &mut CompressionReport,
+    ) -> Self {
         let values: Vec<f32> = values.collect();
         assert!(!values.is_empty());
         let mut min_value = FloatOrd(f32::INFINITY);
@@ -30,24 +170,74 @@ impl CompressedFloat32Storage {
             min_value = std::cmp::min(min_value, value);
             max_value = std::cmp::max(max_value, value);
         }
+        let (min_value, max_value) = (min_value.0, max_value.0);
+        let bytes_before = values.len() * std::mem::size_of::<f32>();
 
-        if min_value == max_value {
-            Self::Static {
+        let removable =
+            settings.allow_channel_removal && (max_value - min_value) * 0.5 <= max_error;
+        let (storage, (error, rms_error)) = if min_value == max_value || removable {
+            let value = if min_value == max_value {
+                min_value
+            } else {
+                min_value + (max_value - min_value) * 0.5
+            };
+            let errors = channel_error(&values, std::iter::repeat(value));
+            let storage = Self::Static {
                 frames: values.len(),
-                value: min_value.0,
-            }
+                value,
+            };
+            (storage, errors)
         } else {
-            let increment = (max_value.0 - min_value.0) / f32::from(u16::MAX);
-            let frames = values
-                .into_iter()
-                .map(|value| ((value - min_value.0) / increment) as u16)
+            let increment = (max_value - min_value) / f32::from(u16::MAX);
+            let frames: Box<[u16]> = values
+                .iter()
+                .map(|value| ((value - min_value) / increment).round() as u16)
                 .collect();
+            let decoded = frames
+                .iter()
+                .map(|frame| min_value + f32::from(*frame) * increment);
+            let errors = channel_error(&values, decoded);
+            if errors.0 <= max_error {
+                let storage = Self::Quantized {
+                    frames,
+                    min_value,
+                    increment,
+                };
+                (storage, errors)
+            } else {
+                let storage = Self::Raw {
+                    frames: values.into_boxed_slice(),
+                };
+                (storage, (0.0, 0.0))
+            }
+        };
+        report.channels.push(ChannelReport {
+            name,
+            mode: storage.mode(),
+            max_error: error,
+            rms_error,
+            bytes_before,
+            bytes_after: storage.byte_size(),
+        });
+        storage
+    }
 
-            Self::Quantized {
-                frames,
-                min_value: min_value.0,
-                increment,
+    fn mode(&self) -> ChannelCompression {
+        match self {
+            Self::Static { .. } => ChannelCompression::Static,
+            Self::Quantized { .. } => ChannelCompression::Quantized,
+            Self::Raw { .. } => ChannelCompression::Raw,
+        }
+    }
+
+    /// The size of the stored keyframes, in bytes.
+    fn byte_size(&self) -> usize {
+        match self {
+            Self::Static { .. } => std::mem::size_of::<f32>(),
+            Self::Quantized { frames, .. } => {
+                frames.len() * std::mem::size_of::<u16>() + 2 * std::mem::size_of::<f32>()
             }
+            Self::Raw { frames } => frames.len() * std::mem::size_of::<f32>(),
         }
     }
 
@@ -56,6 +246,7 @@ impl CompressedFloat32Storage {
         match self {
             Self::Static { frames, .. } => *frames,
             Self::Quantized { frames, .. } => frames.len(),
+            Self::Raw { frames } => frames.len(),
         }
     }
 
@@ -68,26 +259,146 @@ impl CompressedFloat32Storage {
     pub fn sample(&self, frame_rate: f32, time: f32, time_offset: f32) -> f32 {
         match self {
             Self::Static { value, .. } => *value,
+            Self::Quantized {
+                frames,
+                min_value,
+                increment,
+            } => sample_frames(frames.len(), frame_rate, time, time_offset, |idx| {
+                *min_value + f32::from(frames[idx]) * *increment
+            }),
+            Self::Raw { frames } => {
+                sample_frames(frames.len(), frame_rate, time, time_offset, |idx| {
+                    frames[idx]
+                })
+            }
+        }
+    }
+
+    /// Samples every time in `times` into `out`, producing the same values as
+    /// [`sample`](Self::sample). Four keyframe pairs are decoded at a time.
+    pub fn sample_batch(&self, frame_rate: f32, time_offset: f32, times: &[f32], out: &mut [f32]) {
+        match self {
+            Self::Static { value, .. } => out.fill(*value),
             Self::Quantized {
                 frames,
                 min_value,
                 increment,
             } => {
-                let frame_time = time * frame_rate - time_offset;
-                let frame_time = frame_time.clamp(0.0, (frames.len() - 1) as f32);
-                let frame = frame_time.trunc();
-                let time = frame_time - frame;
-                let frame_idx = frame as usize;
-
-                if frame_idx >= frames.len() - 1 {
-                    *min_value + f32::from(frames[frames.len() - 1]) * *increment
-                } else {
-                    let start = *min_value + f32::from(frames[frame_idx]) * *increment;
-                    let end = *min_value + f32::from(frames[frame_idx + 1]) * *increment;
-                    // Interpolate the value
-                    f32::interpolate(&start, &end, time)
-                }
+                let decode = |idx: usize| *min_value + f32::from(frames[idx]) * *increment;
+                sample_frames_batch(frames.len(), frame_rate, time_offset, times, out, decode);
             }
+            Self::Raw { frames } => {
+                sample_frames_batch(frames.len(), frame_rate, time_offset, times, out, |idx| {
+                    frames[idx]
+                });
+            }
+        }
+    }
+}
+
+/// Samples `len` keyframes decoded with `decode`, interpolating between them.
+#[inline(always)]
+fn sample_frames(
+    len: usize,
+    frame_rate: f32,
+    time: f32,
+    time_offset: f32,
+    decode: impl Fn(usize) -> f32,
+) -> f32 {
+    let frame_time = (time - time_offset) * frame_rate;
+    let frame_time = frame_time.clamp(0.0, (len - 1) as f32);
+    let frame = frame_time.trunc();
+    let time = frame_time - frame;
+    let frame_idx = frame as usize;
+
+    if frame_idx >= len - 1 {
+        decode(len - 1)
+    } else {
+        let start = decode(frame_idx);
+        let end = decode(frame_idx + 1);
+        // Interpolate the value
+        f32::interpolate(&start, &end, time)
+    }
+}
+
+/// Samples every time in `times` into `out`, producing the same values as
+/// [`sample_frames`]. Four keyframe pairs are decoded at a time.
+fn sample_frames_batch(
+    len: usize,
+    frame_rate: f32,
+    time_offset: f32,
+    times: &[f32],
+    out: &mut [f32],
+    decode: impl Fn(usize) -> f32,
+) {
+    let last = len - 1;
+    let mut times = times.chunks_exact(4);
+    let mut outs = out.chunks_exact_mut(4);
+    for (times, out) in (&mut times).zip(&mut outs) {
+        let mut frame_idx = [0; 4];
+        let mut frame_time = [0.0; 4];
+        for lane in 0..4 {
+            let time = ((times[lane] - time_offset) * frame_rate).clamp(0.0, last as f32);
+            let frame = time.trunc();
+            frame_idx[lane] = frame as usize;
+            frame_time[lane] = time - frame;
+        }
+        for lane in 0..4 {
+            let idx = frame_idx[lane];
+            out[lane] = if idx >= last {
+                decode(last)
+            } else {
+                f32::interpolate(&decode(idx), &decode(idx + 1), frame_time[lane])
+            };
+        }
+    }
+    for (time, out) in times.remainder().iter().zip(outs.into_remainder()) {
+        *out = sample_frames(len, frame_rate, *time, time_offset, &decode);
+    }
+}
+
+/// The duration of `len` keyframes starting at `time_offset`, matching
+/// [`CurveFixed`]'s duration for the same keyframes.
+#[inline]
+fn frames_duration(len: usize, frame_rate: f32, time_offset: f32) -> f32 {
+    ((len - 1) as f32 / frame_rate + time_offset).max(0.0)
+}
+
+/// The index of the last keyframe at or before `time`, returned as the
+/// cursor when sampling with a cursor.
+#[inline]
+fn frame_cursor(len: usize, frame_rate: f32, time_offset: f32, time: f32) -> KeyframeIndex {
+    let frame = ((time - time_offset) * frame_rate).clamp(0.0, (len - 1) as f32);
+    (frame as usize).min(KeyframeIndex::MAX as usize) as KeyframeIndex
+}
+
+/// How many times multi-channel compressed curves sample at once when
+/// sampling a batch.
+const BATCH_SIZE: usize = 16;
+
+/// Samples a batch of times from several channels, `BATCH_SIZE` times at a
+/// time, and assembles the values of each channel for every time with
+/// `assemble`.
+fn sample_channels_batch<T, const N: usize>(
+    channels: [&CompressedFloat32Storage; N],
+    frame_rate: f32,
+    time_offset: f32,
+    times: &[f32],
+    out: &mut [T],
+    assemble: impl Fn([f32; N]) -> T,
+) {
+    assert_eq!(times.len(), out.len(), "mismatched batch lengths");
+    let mut lanes = [[0.0; BATCH_SIZE]; N];
+    for (times, out) in times.chunks(BATCH_SIZE).zip(out.chunks_mut(BATCH_SIZE)) {
+        for (channel, lane) in channels.iter().zip(lanes.iter_mut()) {
+            channel.sample_batch(frame_rate, time_offset, times, &mut lane[..times.len()]);
+        }
+        for (idx, out) in out.iter_mut().enumerate() {
+            let mut values = [0.0; N];
+            for (value, lane) in values.iter_mut().zip(lanes.iter()) {
+                *value = lane[idx];
+            }
+            *out = assemble(values);
         }
     }
 }
@@ -99,18 +410,34 @@ pub struct CompressedFloat32Curve {
 }
 
 impl CompressedFloat32Curve {
+    /// Compresses a curve with the default [`CompressionSettings`].
     pub fn quantize(src: CurveFixed<f32>) -> Self {
-        Self {
+        Self::compress(src, &CompressionSettings::default()).0
+    }
+
+    pub fn compress(
+        src: CurveFixed<f32>,
+        settings: &CompressionSettings,
+    ) -> (Self, CompressionReport) {
+        let mut report = CompressionReport::default();
+        let curve = Self {
             frame_rate: src.frame_rate(),
             time_offset: src.time_offset(),
-            values: CompressedFloat32Storage::quantize(src.keyframes.into_iter()),
-        }
+            values: CompressedFloat32Storage::quantize(
+                "value",
+                src.keyframes.into_iter(),
+                settings.max_error,
+                settings,
+                &mut report,
+            ),
+        };
+        (curve, report)
     }
 }
 
 impl Curve<f32> for CompressedFloat32Curve {
     fn duration(&self) -> f32 {
-        self.values.len() as f32 * self.frame_rate
+        frames_duration(self.values.len(), self.frame_rate, self.time_offset)
     }
 
     fn time_offset(&self) -> f32 {
@@ -125,8 +452,15 @@ impl Curve<f32> for CompressedFloat32Curve {
         self.values.sample(self.frame_rate, time, self.time_offset)
     }
 
+    fn sample_batch(&self, times: &[f32], out: &mut [f32]) {
+        assert_eq!(times.len(), out.len(), "mismatched batch lengths");
+        self.values
+            .sample_batch(self.frame_rate, self.time_offset, times, out);
+    }
+
     fn sample_with_cursor(&self, _: KeyframeIndex, time: f32) -> (KeyframeIndex, f32) {
-        (0, self.sample(time))
+        let cursor = frame_cursor(self.values.len(), self.frame_rate, self.time_offset, time);
+        (cursor, self.sample(time))
     }
 }
 
@@ -138,21 +472,40 @@ pub struct CompressedFloat32x2Curve {
 }
 
 impl CompressedFloat32x2Curve {
+    /// Compresses a curve with the default [`CompressionSettings`].
     pub fn quantize(src: CurveFixed<Vec2>) -> Self {
-        let x = src.keyframes.iter().map(|vec| vec.x);
-        let y = src.keyframes.iter().map(|vec| vec.y);
-        Self {
+        Self::compress(src, &CompressionSettings::default()).0
+    }
+
+    pub fn compress(
+        src: CurveFixed<Vec2>,
+        settings: &CompressionSettings,
+    ) -> (Self, CompressionReport) {
+        let mut report = CompressionReport::default();
+        let keyframes = &src.keyframes;
+        let mut channel = |name, f: fn(&Vec2) -> f32| {
+            let values = keyframes.iter().map(f);
+            CompressedFloat32Storage::quantize(
+                name,
+                values,
+                settings.max_error,
+                settings,
+                &mut report,
+            )
+        };
+        let curve = Self {
             frame_rate: src.frame_rate(),
             time_offset: src.time_offset(),
-            x: CompressedFloat32Storage::quantize(x),
-            y: CompressedFloat32Storage::quantize(y),
-        }
+            x: channel("x", |vec| vec.x),
+            y: channel("y", |vec| vec.y),
+        };
+        (curve, report)
     }
 }
 
 impl Curve<Vec2> for CompressedFloat32x2Curve {
     fn duration(&self) -> f32 {
-        self.x.len() as f32 * self.frame_rate
+        frames_duration(self.x.len(), self.frame_rate, self.time_offset)
     }
 
     fn time_offset(&self) -> f32 {
@@ -169,8 +522,21 @@ impl Curve<Vec2> for CompressedFloat32x2Curve {
         Vec2::new(x, y)
     }
 
+    fn sample_batch(&self, times: &[f32], out: &mut [Vec2]) {
+        let channels = [&self.x, &self.y];
+        sample_channels_batch(
+            channels,
+            self.frame_rate,
+            self.time_offset,
+            times,
+            out,
+            |[x, y]| Vec2::new(x, y),
+        );
+    }
+
     fn sample_with_cursor(&self, _: KeyframeIndex, time: f32) -> (KeyframeIndex, Vec2) {
-        (0, self.sample(time))
+        let cursor = frame_cursor(self.x.len(), self.frame_rate, self.time_offset, time);
+        (cursor, self.sample(time))
     }
 }
 
@@ -183,23 +549,41 @@ pub struct CompressedFloat32x3Curve {
 }
 
 impl CompressedFloat32x3Curve {
+    /// Compresses a curve with the default [`CompressionSettings`].
     pub fn quantize(src: CurveFixed<Vec3>) -> Self {
-        let x = src.keyframes.iter().map(|vec| vec.x);
-        let y = src.keyframes.iter().map(|vec| vec.y);
-        let z = src.keyframes.iter().map(|vec| vec.z);
-        Self {
+        Self::compress(src, &CompressionSettings::default()).0
+    }
+
+    pub fn compress(
+        src: CurveFixed<Vec3>,
+        settings: &CompressionSettings,
+    ) -> (Self, CompressionReport) {
+        let mut report = CompressionReport::default();
+        let keyframes = &src.keyframes;
+        let mut channel = |name, f: fn(&Vec3) -> f32| {
+            let values = keyframes.iter().map(f);
+            CompressedFloat32Storage::quantize(
+                name,
+                values,
+                settings.max_error,
+                settings,
+                &mut report,
+            )
+        };
+        let curve = Self {
             frame_rate: src.frame_rate(),
             time_offset: src.time_offset(),
-            x: CompressedFloat32Storage::quantize(x),
-            y: CompressedFloat32Storage::quantize(y),
-            z: CompressedFloat32Storage::quantize(z),
-        }
+            x: channel("x", |vec| vec.x),
+            y: channel("y", |vec| vec.y),
+            z: channel("z", |vec| vec.z),
+        };
+        (curve, report)
     }
 }
 
 impl Curve<Vec3> for CompressedFloat32x3Curve {
     fn duration(&self) -> f32 {
-        self.x.len() as f32 * self.frame_rate
+        frames_duration(self.x.len(), self.frame_rate, self.time_offset)
     }
 
     fn time_offset(&self) -> f32 {
@@ -217,14 +601,27 @@ impl Curve<Vec3> for CompressedFloat32x3Curve {
         Vec3::new(x, y, z)
     }
 
+    fn sample_batch(&self, times: &[f32], out: &mut [Vec3]) {
+        let channels = [&self.x, &self.y, &self.z];
+        sample_channels_batch(
+            channels,
+            self.frame_rate,
+            self.time_offset,
+            times,
+            out,
+            |[x, y, z]| Vec3::new(x, y, z),
+        );
+    }
+
     fn sample_with_cursor(&self, _: KeyframeIndex, time: f32) -> (KeyframeIndex, Vec3) {
-        (0, self.sample(time))
+        let cursor = frame_cursor(self.x.len(), self.frame_rate, self.time_offset, time);
+        (cursor, self.sample(time))
     }
 }
 
 impl Curve<Vec3A> for CompressedFloat32x3Curve {
     fn duration(&self) -> f32 {
-        self.x.len() as f32 * self.frame_rate
+        frames_duration(self.x.len(), self.frame_rate, self.time_offset)
     }
 
     fn time_offset(&self) -> f32 {
@@ -242,8 +639,21 @@ impl Curve<Vec3A> for CompressedFloat32x3Curve {
         Vec3A::new(x, y, z)
     }
 
+    fn sample_batch(&self, times: &[f32], out: &mut [Vec3A]) {
+        let channels = [&self.x, &self.y, &self.z];
+        sample_channels_batch(
+            channels,
+            self.frame_rate,
+            self.time_offset,
+            times,
+            out,
+            |[x, y, z]| Vec3A::new(x, y, z),
+        );
+    }
+
     fn sample_with_cursor(&self, _: KeyframeIndex, time: f32) -> (KeyframeIndex, Vec3A) {
-        (0, self.sample(time))
+        let cursor = frame_cursor(self.x.len(), self.frame_rate, self.time_offset, time);
+        (cursor, self.sample(time))
     }
 }
 
@@ -258,7 +668,7 @@ pub struct CompressedFloat32x4Curve {
 
 impl Curve<Vec4> for CompressedFloat32x4Curve {
     fn duration(&self) -> f32 {
-        self.x.len() as f32 * self.frame_rate
+        frames_duration(self.x.len(), self.frame_rate, self.time_offset)
     }
 
     fn time_offset(&self) -> f32 {
@@ -277,25 +687,139 @@ impl Curve<Vec4> for CompressedFloat32x4Curve {
         Vec4::new(x, y, z, w)
     }
 
+    fn sample_batch(&self, times: &[f32], out: &mut [Vec4]) {
+        let channels = [&self.x, &self.y, &self.z, &self.w];
+        sample_channels_batch(
+            channels,
+            self.frame_rate,
+            self.time_offset,
+            times,
+            out,
+            |[x, y, z, w]| Vec4::new(x, y, z, w),
+        );
+    }
+
     fn sample_with_cursor(&self, _: KeyframeIndex, time: f32) -> (KeyframeIndex, Vec4) {
-        (0, self.sample(time))
+        let cursor = frame_cursor(self.x.len(), self.frame_rate, self.time_offset, time);
+        (cursor, self.sample(time))
     }
 }
 
 impl CompressedFloat32x4Curve {
+    /// Compresses a curve with the default [`CompressionSettings`].
     pub fn quantize(src: CurveFixed<Vec3>) -> Self {
-        let x = src.keyframes.iter().map(|vec| vec.x);
-        let y = src.keyframes.iter().map(|vec| vec.y);
-        let z = src.keyframes.iter().map(|vec| vec.z);
-        let w = src.keyframes.iter().map(|vec| vec.z);
-        Self {
+        Self::compress(src, &CompressionSettings::default()).0
+    }
+
+    pub fn compress(
+        src: CurveFixed<Vec3>,
+        settings: &CompressionSettings,
+    ) -> (Self, CompressionReport) {
+        let mut report = CompressionReport::default();
+        let keyframes = &src.keyframes;
+        let mut channel = |name, f: fn(&Vec3) -> f32| {
+            let values = keyframes.iter().map(f);
+            CompressedFloat32Storage::quantize(
+                name,
+                values,
+                settings.max_error,
+                settings,
+                &mut report,
+            )
+        };
+        let curve = Self {
             frame_rate: src.frame_rate(),
             time_offset: src.time_offset(),
-            x: CompressedFloat32Storage::quantize(x),
-            y: CompressedFloat32Storage::quantize(y),
-            z: CompressedFloat32Storage::quantize(z),
-            w: CompressedFloat32Storage::quantize(w),
-        }
+            x: channel("x", |vec| vec.x),
+            y: channel("y", |vec| vec.y),
+            z: channel("z", |vec| vec.z),
+            w: channel("w", |vec| vec.z),
+        };
+        (curve, report)
+    }
+}
+
+/// A compressed rotation curve. Each component is quantized separately, and
+/// sampled rotations are renormalized.
+pub struct CompressedQuatCurve {
+    frame_rate: f32,
+    time_offset: f32,
+    x: CompressedFloat32Storage,
+    y: CompressedFloat32Storage,
+    z: CompressedFloat32Storage,
+    w: CompressedFloat32Storage,
+}
+
+impl CompressedQuatCurve {
+    /// Compresses a rotation curve. The keyframes are first flipped into the
+    /// same hemisphere as the keyframe before them, so that components of
+    /// constant rotations stored as both `q` and `-q` are stored as constants.
+    pub fn quantize(src: CurveFixed<Quat>) -> Self {
+        Self::compress(src, &CompressionSettings::default()).0
+    }
+
+    /// Compresses a rotation curve like [`quantize`](Self::quantize), within
+    /// the rotation error of `settings`.
+    pub fn compress(
+        mut src: CurveFixed<Quat>,
+        settings: &CompressionSettings,
+    ) -> (Self, CompressionReport) {
+        Quat::canonicalize_keyframes(&mut src.keyframes);
+        let mut report = CompressionReport::default();
+        let keyframes = &src.keyframes;
+        let max_error = settings.rotation_component_error();
+        let mut channel = |name, f: fn(&Quat) -> f32| {
+            let values = keyframes.iter().map(f);
+            CompressedFloat32Storage::quantize(name, values, max_error, settings, &mut report)
+        };
+        let curve = Self {
+            frame_rate: src.frame_rate(),
+            time_offset: src.time_offset(),
+            x: channel("x", |quat| quat.x),
+            y: channel("y", |quat| quat.y),
+            z: channel("z", |quat| quat.z),
+            w: channel("w", |quat| quat.w),
+        };
+        (curve, report)
+    }
+}
+
+impl Curve<Quat> for CompressedQuatCurve {
+    fn duration(&self) -> f32 {
+        frames_duration(self.x.len(), self.frame_rate, self.time_offset)
+    }
+
+    fn time_offset(&self) -> f32 {
+        self.time_offset
+    }
+
+    fn keyframe_count(&self) -> usize {
+        self.x.len()
+    }
+
+    fn sample(&self, time: f32) -> Quat {
+        let x = self.x.sample(self.frame_rate, time, self.time_offset);
+        let y = self.y.sample(self.frame_rate, time, self.time_offset);
+        let z = self.z.sample(self.frame_rate, time, self.time_offset);
+        let w = self.w.sample(self.frame_rate, time, self.time_offset);
+        Quat::from_xyzw(x, y, z, w).normalize()
+    }
+
+    fn sample_batch(&self, times: &[f32], out: &mut [Quat]) {
+        let channels = [&self.x, &self.y, &self.z, &self.w];
+        sample_channels_batch(
+            channels,
+            self.frame_rate,
+            self.time_offset,
+            times,
+            out,
+            |[x, y, z, w]| Quat::from_xyzw(x, y, z, w).normalize(),
+        );
+    }
+
+    fn sample_with_cursor(&self, _: KeyframeIndex, time: f32) -> (KeyframeIndex, Quat) {
+        let cursor = frame_cursor(self.x.len(), self.frame_rate, self.time_offset, time);
+        (cursor, self.sample(time))
     }
 }
 
@@ -316,3 +840,369 @@ pub struct CompressedTransformCurve {
     rotation_z: CompressedFloat32Storage,
     rotation_w: CompressedFloat32Storage,
 }
+
+impl CompressedTransformCurve {
+    /// Compresses a transform curve. The rotations are first flipped into the
+    /// same hemisphere as the rotation before them, as with
+    /// [`CompressedQuatCurve::quantize`].
+    pub fn quantize(src: CurveFixed<Transform>) -> Self {
+        Self::compress(src, &CompressionSettings::default()).0
+    }
+
+    /// Compresses a transform curve like [`quantize`](Self::quantize). The
+    /// rotation channels are compressed within the rotation error of
+    /// `settings`, and the others within its max error.
+    pub fn compress(
+        mut src: CurveFixed<Transform>,
+        settings: &CompressionSettings,
+    ) -> (Self, CompressionReport) {
+        Transform::canonicalize_keyframes(&mut src.keyframes);
+        let mut report = CompressionReport::default();
+        let keyframes = &src.keyframes;
+        let rotation_error = settings.rotation_component_error();
+        let mut channel = |name, max_error, f: fn(&Transform) -> f32| {
+            let values = keyframes.iter().map(f);
+            CompressedFloat32Storage::quantize(name, values, max_error, settings, &mut report)
+        };
+        let curve = Self {
+            frame_rate: src.frame_rate(),
+            time_offset: src.time_offset(),
+            translation_x: channel("translation.x", settings.max_error, |transform| {
+                transform.translation.x
+            }),
+            translation_y: channel("translation.y", settings.max_error, |transform| {
+                transform.translation.y
+            }),
+            translation_z: channel("translation.z", settings.max_error, |transform| {
+                transform.translation.z
+            }),
+            scale_x: channel("scale.x", settings.max_error, |transform| transform.scale.x),
+            scale_y: channel("scale.y", settings.max_error, |transform| transform.scale.y),
+            scale_z: channel("scale.z", settings.max_error, |transform| transform.scale.z),
+            rotation_x: channel("rotation.x", rotation_error, |transform| {
+                transform.rotation.x
+            }),
+            rotation_y: channel("rotation.y", rotation_error, |transform| {
+                transform.rotation.y
+            }),
+            rotation_z: channel("rotation.z", rotation_error, |transform| {
+                transform.rotation.z
+            }),
+            rotation_w: channel("rotation.w", rotation_error, |transform| {
+                transform.rotation.w
+            }),
+        };
+        (curve, report)
+    }
+}
+
+impl Curve<Transform> for CompressedTransformCurve {
+    fn duration(&self) -> f32 {
+        frames_duration(self.translation_x.len(), self.frame_rate, self.time_offset)
+    }
+
+    fn time_offset(&self) -> f32 {
+        self.time_offset
+    }
+
+    fn keyframe_count(&self) -> usize {
+        self.translation_x.len()
+    }
+
+    fn sample(&self, time: f32) -> Transform {
+        let sample = |channel: &CompressedFloat32Storage| {
+            channel.sample(self.frame_rate, time, self.time_offset)
+        };
+        Transform {
+            translation: Vec3::new(
+                sample(&self.translation_x),
+                sample(&self.translation_y),
+                sample(&self.translation_z),
+            ),
+            rotation: Quat::from_xyzw(
+                sample(&self.rotation_x),
+                sample(&self.rotation_y),
+                sample(&self.rotation_z),
+                sample(&self.rotation_w),
+            )
+            .normalize(),
+            scale: Vec3::new(
+                sample(&self.scale_x),
+                sample(&self.scale_y),
+                sample(&self.scale_z),
+            ),
+        }
+    }
+
+    fn sample_batch(&self, times: &[f32], out: &mut [Transform]) {
+        let channels = [
+            &self.translation_x,
+            &self.translation_y,
+            &self.translation_z,
+            &self.rotation_x,
+            &self.rotation_y,
+            &self.rotation_z,
+            &self.rotation_w,
+            &self.scale_x,
+            &self.scale_y,
+            &self.scale_z,
+        ];
+        sample_channels_batch(
+            channels,
+            self.frame_rate,
+            self.time_offset,
+            times,
+            out,
+            |[tx, ty, tz, rx, ry, rz, rw, sx, sy, sz]| Transform {
+                translation: Vec3::new(tx, ty, tz),
+                rotation: Quat::from_xyzw(rx, ry, rz, rw).normalize(),
+                scale: Vec3::new(sx, sy, sz),
+            },
+        );
+    }
+
+    fn sample_with_cursor(&self, _: KeyframeIndex, time: f32) -> (KeyframeIndex, Transform) {
+        let cursor = frame_cursor(
+            self.translation_x.len(),
+            self.frame_rate,
+            self.time_offset,
+            time,
+        );
+        (cursor, self.sample(time))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn is_static(storage: &CompressedFloat32Storage) -> bool {
+        matches!(storage, CompressedFloat32Storage::Static { .. })
+    }
+
+    /// A constant rotation, alternately stored as `q` and `-q`.
+    fn flipped_rotations() -> (Quat, Vec<Quat>) {
+        let rotation = Quat::from_euler(EulerRot::YXZ, 1.0, 0.5, -0.25);
+        let keyframes = (0..8)
+            .map(|idx| if idx % 2 == 0 { rotation } else { -rotation })
+            .collect();
+        (rotation, keyframes)
+    }
+
+    #[test]
+    pub fn test_constant_flipped_rotations_compress_to_static() {
+        let (rotation, keyframes) = flipped_rotations();
+        let curve = CompressedQuatCurve::quantize(CurveFixed::from_keyframes(30.0, keyframes));
+        assert!([&curve.x, &curve.y, &curve.z, &curve.w]
+            .into_iter()
+            .all(is_static));
+        for time in [0.0, 0.01, 0.05, 0.1, 0.2] {
+            let sampled = curve.sample(time);
+            assert!(sampled.abs_diff_eq(rotation, 1e-6), "{:?}", sampled);
+        }
+    }
+
+    #[test]
+    pub fn test_constant_flipped_transform_rotations_compress_to_static() {
+        let (rotation, keyframes) = flipped_rotations();
+        let keyframes = keyframes
+            .into_iter()
+            .enumerate()
+            .map(|(idx, rotation)| Transform {
+                translation: Vec3::X * idx as f32,
+                rotation,
+                scale: Vec3::ONE,
+            })
+            .collect();
+        let curve = CompressedTransformCurve::quantize(CurveFixed::from_keyframes(30.0, keyframes));
+        assert!(!is_static(&curve.translation_x));
+        assert!([
+            &curve.rotation_x,
+            &curve.rotation_y,
+            &curve.rotation_z,
+            &curve.rotation_w,
+            &curve.scale_x,
+        ]
+        .into_iter()
+        .all(is_static));
+        for time in [0.0, 0.01, 0.05, 0.1, 0.2] {
+            let sampled = curve.sample(time).rotation;
+            assert!(sampled.abs_diff_eq(rotation, 1e-6), "{:?}", sampled);
+        }
+    }
+
+    #[test]
+    pub fn test_offset_curves_match_fixed_curves() {
+        let keyframes: Vec<Vec3> = (0..10)
+            .map(|idx| Vec3::new(idx as f32, (idx * idx) as f32, 1.0))
+            .collect();
+        let fixed = CurveFixed::from_keyframes_with_offset(10.0, 5, keyframes);
+        let curve = CompressedFloat32x3Curve::quantize(fixed.clone());
+        assert!((Curve::<Vec3>::duration(&curve) - 1.4).abs() < 1e-6);
+        assert_eq!(Curve::<Vec3>::duration(&curve), fixed.duration());
+        assert_eq!(Curve::<Vec3>::time_offset(&curve), fixed.time_offset());
+        for time in [0.0, 0.5, 0.55, 0.8, 1.0, 1.4, 2.0] {
+            let sampled: Vec3 = curve.sample(time);
+            assert!(sampled.abs_diff_eq(fixed.sample(time), 0.01), "{}", time);
+        }
+
+        // The cursor is the keyframe at or before the sampled time.
+        let sample = |time| Curve::<Vec3>::sample_with_cursor(&curve, 0, time).0;
+        assert_eq!(sample(0.0), 0);
+        assert_eq!(sample(0.55), 0);
+        assert_eq!(sample(0.75), 2);
+        assert_eq!(sample(2.0), 9);
+    }
+
+    fn outlier_curve() -> CurveFixed<f32> {
+        let mut keyframes: Vec<f32> = (0..32).map(|idx| (idx as f32 * 0.2).sin()).collect();
+        keyframes[20] = 1000.0;
+        CurveFixed::from_keyframes(30.0, keyframes)
+    }
+
+    #[test]
+    pub fn test_outliers_fall_back_to_raw_storage() {
+        let settings = CompressionSettings {
+            max_error: 1e-3,
+            ..Default::default()
+        };
+        let fixed = outlier_curve();
+        let (curve, report) = CompressedFloat32Curve::compress(fixed.clone(), &settings);
+        assert!(matches!(curve.values, CompressedFloat32Storage::Raw { .. }));
+        assert_eq!(report.channels.len(), 1);
+        assert_eq!(report.channels[0].mode, ChannelCompression::Raw);
+        assert_eq!(report.max_error(), 0.0);
+        assert_eq!(report.bytes_before(), 32 * 4);
+        assert_eq!(report.bytes_after(), 32 * 4);
+        for idx in 0..=60 {
+            let time = idx as f32 / 60.0;
+            assert!((curve.sample(time) - fixed.sample(time)).abs() <= settings.max_error);
+        }
+
+        // Without the outlier, quantizing stays within the error bound.
+        let mut keyframes = fixed.keyframes.clone();
+        keyframes[20] = 0.0;
+        let fixed = CurveFixed::from_keyframes(30.0, keyframes);
+        let (curve, report) = CompressedFloat32Curve::compress(fixed.clone(), &settings);
+        assert_eq!(report.channels[0].mode, ChannelCompression::Quantized);
+        assert!(report.max_error() <= settings.max_error);
+        assert!(report.channels[0].rms_error <= report.max_error());
+        assert!(report.bytes_after() < report.bytes_before());
+        for idx in 0..=60 {
+            let time = idx as f32 / 60.0;
+            assert!((curve.sample(time) - fixed.sample(time)).abs() <= settings.max_error);
+        }
+    }
+
+    #[test]
+    pub fn test_compression_keeps_the_duration() {
+        let settings = CompressionSettings {
+            max_error: 1e-3,
+            ..Default::default()
+        };
+        let constant = CurveFixed::from_keyframes(24.0, vec![2.0; 12]);
+        let smooth =
+            CurveFixed::from_keyframes(30.0, (0..32).map(|idx| idx as f32 * 0.1).collect());
+        let mut outlier = outlier_curve();
+        outlier.set_frame_offset(3);
+        // Stored as static, quantized and raw channels respectively.
+        for (fixed, mode) in [
+            (constant, ChannelCompression::Static),
+            (smooth, ChannelCompression::Quantized),
+            (outlier, ChannelCompression::Raw),
+        ] {
+            let (curve, report) = CompressedFloat32Curve::compress(fixed.clone(), &settings);
+            assert_eq!(report.channels[0].mode, mode);
+            assert_eq!(curve.duration(), fixed.duration());
+            assert_eq!(curve.keyframe_count(), fixed.keyframe_count());
+        }
+    }
+
+    #[test]
+    pub fn test_nearly_constant_channels_are_removed() {
+        let vectors: Vec<Vec3> = (0..16)
+            .map(|idx| Vec3::new(idx as f32, 1.0 + (idx % 2) as f32 * 1e-5, 2.0))
+            .collect();
+        let settings = CompressionSettings::default();
+        let (curve, report) = CompressedFloat32x3Curve::compress(
+            CurveFixed::from_keyframes(30.0, vectors.clone()),
+            &settings,
+        );
+        let modes: Vec<_> = report.channels.iter().map(|channel| channel.mode).collect();
+        assert_eq!(
+            modes,
+            vec![
+                ChannelCompression::Quantized,
+                ChannelCompression::Static,
+                ChannelCompression::Static
+            ]
+        );
+        assert!(is_static(&curve.y));
+        assert!(report.channels[1].max_error <= settings.max_error);
+
+        let settings = CompressionSettings {
+            allow_channel_removal: false,
+            ..settings
+        };
+        let (_, report) = CompressedFloat32x3Curve::compress(
+            CurveFixed::from_keyframes(30.0, vectors),
+            &settings,
+        );
+        assert_eq!(report.channels[1].mode, ChannelCompression::Quantized);
+        assert_eq!(report.channels[2].mode, ChannelCompression::Static);
+    }
+
+    /// Sample times covering both ends, the keyframes themselves, and a
+    /// count which isn't a multiple of the batch or lane sizes.
+    fn batch_times() -> Vec<f32> {
+        (0..61).map(|idx| idx as f32 * 0.0125 - 0.1).collect()
+    }
+
+    fn assert_batch_matches<T: PartialEq + std::fmt::Debug + Default + Clone>(
+        curve: &impl Curve<T>,
+    ) {
+        let times = batch_times();
+        let mut batch = vec![T::default(); times.len()];
+        curve.sample_batch(&times, &mut batch);
+        for (time, value) in times.iter().zip(batch) {
+            assert_eq!(value, curve.sample(*time), "t = {}", time);
+        }
+    }
+
+    #[test]
+    pub fn test_batch_sampling_matches_scalar_sampling() {
+        let vectors: Vec<Vec3> = (0..9)
+            .map(|idx| Vec3::new(idx as f32, (idx as f32).sin(), -0.5 * idx as f32))
+            .collect();
+        let fixed = CurveFixed::from_keyframes(30.0, vectors.clone());
+        assert_batch_matches(&CompressedFloat32Curve::quantize(
+            CurveFixed::from_keyframes(30.0, vectors.iter().map(|vec| vec.y).collect()),
+        ));
+        assert_batch_matches(&CompressedFloat32x2Curve::quantize(
+            CurveFixed::from_keyframes(30.0, vectors.iter().map(|vec| vec.truncate()).collect()),
+        ));
+        assert_batch_matches::<Vec3>(&CompressedFloat32x3Curve::quantize(fixed.clone()));
+        assert_batch_matches::<Vec3A>(&CompressedFloat32x3Curve::quantize(fixed.clone()));
+        assert_batch_matches(&CompressedFloat32x4Curve::quantize(fixed));
+
+        let rotations: Vec<Quat> = (0..9)
+            .map(|idx| Quat::from_euler(EulerRot::YXZ, idx as f32 * 0.3, 0.5, -0.25))
+            .collect();
+        assert_batch_matches(&CompressedQuatCurve::quantize(CurveFixed::from_keyframes(
+            30.0,
+            rotations.clone(),
+        )));
+        let transforms = vectors
+            .into_iter()
+            .zip(rotations)
+            .map(|(translation, rotation)| Transform {
+                translation,
+                rotation,
+                scale: Vec3::ONE,
+            })
+            .collect();
+        let mut fixed = CurveFixed::from_keyframes(30.0, transforms);
+        fixed.set_frame_offset(3);
+        assert_batch_matches(&CompressedTransformCurve::quantize(fixed));
+    }
+}
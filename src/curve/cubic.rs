@@ -0,0 +1,407 @@
+use crate::{
+    curve::{
+        check_time_stamps, find_segment, sanitize_keyframes, Curve, CurveError, Extrapolation,
+        KeyframeIndex,
+    },
+    Animatable,
+};
+use bevy_math::*;
+use serde::{Deserialize, Serialize};
+
+/// Values that can be added together and scaled, as needed to interpolate
+/// them with tangents.
+pub trait VectorSpace: Animatable + Copy {
+    fn zero() -> Self;
+
+    fn add(self, other: Self) -> Self;
+
+    fn sub(self, other: Self) -> Self;
+
+    fn scale(self, factor: f32) -> Self;
+
+    /// Maps a value interpolated componentwise back to a valid value, like
+    /// normalizing a rotation. The default implementation returns `self`.
+    #[inline]
+    fn project(self) -> Self {
+        self
+    }
+}
+
+macro_rules! impl_vector_space {
+    ($ty:ty, $zero:expr) => {
+        impl VectorSpace for $ty {
+            #[inline]
+            fn zero() -> Self {
+                $zero
+            }
+
+            #[inline]
+            fn add(self, other: Self) -> Self {
+                self + other
+            }
+
+            #[inline]
+            fn sub(self, other: Self) -> Self {
+                self - other
+            }
+
+            #[inline]
+            fn scale(self, factor: f32) -> Self {
+                self * factor
+            }
+        }
+    };
+}
+
+impl_vector_space!(f32, 0.0);
+impl_vector_space!(Vec2, Vec2::ZERO);
+impl_vector_space!(Vec3, Vec3::ZERO);
+impl_vector_space!(Vec3A, Vec3A::ZERO);
+impl_vector_space!(Vec4, Vec4::ZERO);
+
+/// Rotations are interpolated as 4D vectors and renormalized, so their
+/// tangents are quaternion derivatives rather than angular velocities.
+impl VectorSpace for Quat {
+    #[inline]
+    fn zero() -> Self {
+        Quat::from_xyzw(0.0, 0.0, 0.0, 0.0)
+    }
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        self + other
+    }
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        self - other
+    }
+
+    #[inline]
+    fn scale(self, factor: f32) -> Self {
+        self * factor
+    }
+
+    #[inline]
+    fn project(self) -> Self {
+        self.normalize()
+    }
+}
+
+/// Curve with sparse keyframes, each with an incoming and an outgoing tangent.
+///
+/// Values between keyframes are interpolated with cubic Hermite splines. The
+/// tangents are the rate of change of the value per second, entering and
+/// leaving each keyframe. The curve is smooth wherever they match.
+///
+/// **NOTE**: The maximum number of keyframes is limited by the capacity of [`KeyframeIndex`] (a `u16`)
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct CurveCubic<T> {
+    time_stamps: Vec<f32>,
+    keyframes: Vec<T>,
+    in_tangents: Vec<T>,
+    out_tangents: Vec<T>,
+    #[serde(default)]
+    extrapolation: Extrapolation,
+}
+
+impl<T> CurveCubic<T>
+where
+    T: VectorSpace,
+{
+    /// Creates a curve with automatic tangents. See
+    /// [`auto_tangents`](Self::auto_tangents).
+    pub fn from_keyframes(samples: Vec<f32>, values: Vec<T>) -> Result<Self, CurveError> {
+        let tangents = vec![T::zero(); values.len()];
+        let mut curve =
+            Self::from_keyframes_with_tangents(samples, values, tangents.clone(), tangents)?;
+        curve.auto_tangents();
+        Ok(curve)
+    }
+
+    pub fn from_keyframes_with_tangents(
+        samples: Vec<f32>,
+        values: Vec<T>,
+        in_tangents: Vec<T>,
+        out_tangents: Vec<T>,
+    ) -> Result<Self, CurveError> {
+        check_time_stamps(&samples, values.len())?;
+        if in_tangents.len() != values.len() || out_tangents.len() != values.len() {
+            return Err(CurveError::MismatchedLength);
+        }
+
+        Ok(Self {
+            time_stamps: samples,
+            keyframes: values,
+            in_tangents,
+            out_tangents,
+            extrapolation: Extrapolation::Clamp,
+        })
+    }
+
+    /// Sets how the curve is sampled outside of its keyframes. With
+    /// [`Extrapolation::Linear`], the curve continues along the incoming
+    /// tangent of its first keyframe and the outgoing tangent of its last.
+    pub fn with_extrapolation(mut self, extrapolation: Extrapolation) -> Self {
+        self.extrapolation = extrapolation;
+        self
+    }
+
+    #[inline]
+    pub fn extrapolation(&self) -> Extrapolation {
+        self.extrapolation
+    }
+
+    #[inline]
+    pub fn set_extrapolation(&mut self, extrapolation: Extrapolation) {
+        self.extrapolation = extrapolation;
+    }
+
+    /// Replaces the tangents of every keyframe with Catmull-Rom tangents,
+    /// which makes the curve smooth.
+    ///
+    /// The tangent of each keyframe points from the previous keyframe to the
+    /// next one. The first and last keyframes point to their only neighbor.
+    /// Rotations are canonicalized first, so that the curve takes the
+    /// shortest path between them.
+    pub fn auto_tangents(&mut self) {
+        T::canonicalize_keyframes(&mut self.keyframes);
+        let len = self.keyframes.len();
+        for idx in 0..len {
+            let prev = idx.saturating_sub(1);
+            let next = (idx + 1).min(len - 1);
+            let tangent = if prev == next {
+                T::zero()
+            } else {
+                let dt = self.time_stamps[next] - self.time_stamps[prev];
+                self.keyframes[next]
+                    .sub(self.keyframes[prev])
+                    .scale(dt.recip())
+            };
+            self.in_tangents[idx] = tangent;
+            self.out_tangents[idx] = tangent;
+        }
+    }
+
+    /// Gets keyframe value at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is out of bounds.
+    #[inline]
+    pub fn get_value(&self, at: KeyframeIndex) -> &T {
+        &self.keyframes[at as usize]
+    }
+
+    /// Gets keyframe time at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is out of bounds.
+    #[inline]
+    pub fn get_time(&self, at: KeyframeIndex) -> f32 {
+        self.time_stamps[at as usize]
+    }
+
+    /// Gets the incoming and outgoing tangents of the keyframe at the given
+    /// index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is out of bounds.
+    #[inline]
+    pub fn get_tangents(&self, at: KeyframeIndex) -> (T, T) {
+        (
+            self.in_tangents[at as usize],
+            self.out_tangents[at as usize],
+        )
+    }
+
+    /// Sets the incoming and outgoing tangents of the keyframe at the given
+    /// index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is out of bounds.
+    pub fn set_tangents(&mut self, at: KeyframeIndex, in_tangent: T, out_tangent: T) {
+        self.in_tangents[at as usize] = in_tangent;
+        self.out_tangents[at as usize] = out_tangent;
+    }
+
+    /// `true` when this `CurveCubic` doesn't have any keyframe.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    pub fn set_time_offset(&mut self, mut time_offset: f32) {
+        time_offset -= self.time_offset(); // Removes current offset
+        self.time_stamps.iter_mut().for_each(|t| *t += time_offset);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (f32, &T)> {
+        self.time_stamps.iter().copied().zip(self.keyframes.iter())
+    }
+}
+
+impl<T> Curve<T> for CurveCubic<T>
+where
+    T: VectorSpace,
+{
+    fn duration(&self) -> f32 {
+        self.time_stamps.last().copied().unwrap_or(0.0)
+    }
+
+    fn time_offset(&self) -> f32 {
+        self.time_stamps.first().copied().unwrap_or(0.0)
+    }
+
+    #[inline]
+    fn keyframe_count(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    fn sample(&self, time: f32) -> T {
+        // Index guessing gives a small search optimization
+        let index = if time < self.duration() * 0.5 {
+            0
+        } else {
+            self.time_stamps.len() - 1
+        };
+
+        self.sample_with_cursor(index as KeyframeIndex, time).1
+    }
+
+    fn sample_with_cursor(&self, cursor: KeyframeIndex, time: f32) -> (KeyframeIndex, T) {
+        let time = self
+            .extrapolation
+            .wrap_time(time, self.time_offset(), self.duration());
+        let cursor = match find_segment(&self.time_stamps, cursor, time) {
+            Ok(cursor) => cursor,
+            Err(index) if self.extrapolation == Extrapolation::Linear => {
+                let idx = index as usize;
+                let tangent = if idx == 0 {
+                    self.in_tangents[idx]
+                } else {
+                    self.out_tangents[idx]
+                };
+                let dt = time - self.time_stamps[idx];
+                let value = self.keyframes[idx].add(tangent.scale(dt));
+                return (index, value.project());
+            }
+            Err(index) => return (index, self.keyframes[index as usize]),
+        };
+
+        let (i, j) = (cursor as usize - 1, cursor as usize);
+        let dt = self.time_stamps[j] - self.time_stamps[i];
+        let t = (time - self.time_stamps[i]) / dt;
+        debug_assert!(
+            (0.0..=1.0).contains(&t),
+            "t = {} but should be normalized",
+            t
+        );
+
+        // Hermite basis functions, with the tangents scaled from per second to
+        // per segment
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let value = self.keyframes[i]
+            .scale(2.0 * t3 - 3.0 * t2 + 1.0)
+            .add(self.out_tangents[i].scale((t3 - 2.0 * t2 + t) * dt))
+            .add(self.keyframes[j].scale(3.0 * t2 - 2.0 * t3))
+            .add(self.in_tangents[j].scale((t3 - t2) * dt));
+
+        (cursor, value.project())
+    }
+
+    fn find_non_finite(&self) -> Option<usize> {
+        (0..self.keyframes.len()).find(|idx| {
+            !self.keyframes[*idx].is_finite()
+                || !self.in_tangents[*idx].is_finite()
+                || !self.out_tangents[*idx].is_finite()
+        })
+    }
+
+    /// Replaces keyframes that aren't finite like other curves, and tangents
+    /// that aren't finite with zero. Returns the number of values replaced.
+    fn sanitize(&mut self) -> usize {
+        let mut replaced = sanitize_keyframes(&mut self.keyframes);
+        for tangent in self
+            .in_tangents
+            .iter_mut()
+            .chain(self.out_tangents.iter_mut())
+        {
+            if !tangent.is_finite() {
+                *tangent = T::zero();
+                replaced += 1;
+            }
+        }
+        replaced
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::CurveVariableLinear;
+
+    #[test]
+    pub fn test_auto_tangents_are_continuous() {
+        let times = vec![0.0, 0.5, 1.75, 2.0, 3.0];
+        let curve =
+            CurveCubic::from_keyframes(times.clone(), vec![0.0, 2.0, -1.0, 0.5, 0.25]).unwrap();
+        let h = 1e-4;
+        for (idx, time) in times.iter().enumerate().take(4).skip(1) {
+            let value = curve.sample(*time);
+            assert!((value - curve.get_value(idx as KeyframeIndex)).abs() < 1e-6);
+            let before = (value - curve.sample(time - h)) / h;
+            let after = (curve.sample(time + h) - value) / h;
+            let (in_tangent, out_tangent) = curve.get_tangents(idx as KeyframeIndex);
+            assert_eq!(in_tangent, out_tangent);
+            assert!((before - after).abs() < 0.05, "{} != {}", before, after);
+            assert!((before - in_tangent).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    pub fn test_straight_lines_match_linear_curves() {
+        let times = vec![-1.0, 0.0, 0.25, 2.0, 2.5];
+        let values: Vec<_> = times
+            .iter()
+            .map(|t| Vec3::new(1.0, -2.0, 0.5) * *t + Vec3::Y)
+            .collect();
+        let cubic = CurveCubic::from_keyframes(times.clone(), values.clone()).unwrap();
+        let linear = CurveVariableLinear::with_keyframes(times, values).unwrap();
+        let mut cursor = 0;
+        for idx in -20..=60 {
+            let time = idx as f32 * 0.05;
+            let (next_cursor, value) = cubic.sample_with_cursor(cursor, time);
+            cursor = next_cursor;
+            assert!(value.abs_diff_eq(linear.sample(time), 1e-5), "t = {}", time);
+            assert!(cubic.sample(time).abs_diff_eq(value, 1e-6));
+        }
+    }
+
+    #[test]
+    pub fn test_rotations_take_the_shortest_path() {
+        let a = Quat::from_rotation_y(0.0);
+        let b = -Quat::from_rotation_y(1.0);
+        let curve = CurveCubic::from_keyframes(vec![0.0, 1.0], vec![a, b]).unwrap();
+        let value = curve.sample(0.5);
+        assert!(value.is_normalized());
+        assert!(value.angle_between(Quat::from_rotation_y(0.5)) < 1e-3);
+    }
+
+    #[test]
+    pub fn test_mismatched_tangents_are_rejected() {
+        assert!(matches!(
+            CurveCubic::from_keyframes_with_tangents(
+                vec![0.0, 1.0],
+                vec![0.0, 1.0],
+                vec![0.0],
+                vec![0.0, 0.0]
+            ),
+            Err(CurveError::MismatchedLength)
+        ));
+    }
+}
@@ -1,9 +1,12 @@
 use crate::{
-    curve::{Curve, KeyframeIndex},
+    curve::{sanitize_keyframes, Curve, Extrapolation, KeyframeIndex},
     Animatable,
 };
 use serde::{Deserialize, Serialize};
 
+/// How many frames [`CurveFixed`] locates at once when sampling a batch.
+const BATCH_SIZE: usize = 16;
+
 /// Curve with evenly spaced keyframes, in another words a curve with a fixed frame rate.
 ///
 /// This curve maintains the faster sampling rate over a wide range of frame rates, because
@@ -20,6 +23,8 @@ where
     /// negated to use [`std::f32::mul_add`]
     negative_frame_offset: f32,
     pub keyframes: Vec<T>,
+    #[serde(default)]
+    extrapolation: Extrapolation,
 }
 
 impl<T> CurveFixed<T>
@@ -39,6 +44,7 @@ where
             frame_rate,
             negative_frame_offset: -(frame_offset as f32),
             keyframes,
+            extrapolation: Extrapolation::Clamp,
         }
     }
 
@@ -47,9 +53,26 @@ where
             frame_rate: 30.0,
             negative_frame_offset: 0.0,
             keyframes: vec![v],
+            extrapolation: Extrapolation::Clamp,
         }
     }
 
+    /// Sets how the curve is sampled outside of its keyframes.
+    pub fn with_extrapolation(mut self, extrapolation: Extrapolation) -> Self {
+        self.extrapolation = extrapolation;
+        self
+    }
+
+    #[inline]
+    pub fn extrapolation(&self) -> Extrapolation {
+        self.extrapolation
+    }
+
+    #[inline]
+    pub fn set_extrapolation(&mut self, extrapolation: Extrapolation) {
+        self.extrapolation = extrapolation;
+    }
+
     #[inline]
     pub fn frame_rate(&self) -> f32 {
         self.frame_rate
@@ -74,6 +97,51 @@ where
         -self.negative_frame_offset as i32
     }
 
+    /// Samples the curve at a fractional keyframe index, extrapolating
+    /// outside of the keyframes of the curve. The curve must not be empty.
+    fn sample_frame(&self, frame_time: f32) -> T {
+        let (frame_idx, time) = self.locate_frame(frame_time);
+        self.sample_located(frame_idx, time)
+    }
+
+    /// Finds the keyframe to interpolate from for a fractional keyframe index,
+    /// and how far to interpolate towards the next one.
+    #[inline]
+    fn locate_frame(&self, frame_time: f32) -> (usize, f32) {
+        let last = (self.keyframe_count() - 1) as f32;
+        let frame_time = match self.extrapolation {
+            Extrapolation::Linear if last > 0.0 && !(0.0..=last).contains(&frame_time) => {
+                // Extend the first or last pair of keyframes.
+                let frame = if frame_time < 0.0 { 0.0 } else { last - 1.0 };
+                return (frame as usize, frame_time - frame);
+            }
+            extrapolation => extrapolation
+                .wrap_time(frame_time, 0.0, last)
+                .clamp(0.0, last),
+        };
+        let frame = frame_time.trunc();
+        (frame as usize, frame_time - frame)
+    }
+
+    /// Interpolates from a keyframe towards the next, as located by
+    /// [`locate_frame`](Self::locate_frame).
+    #[inline]
+    fn sample_located(&self, frame_idx: usize, time: f32) -> T {
+        match self.keyframes.get(frame_idx + 1) {
+            Some(next) => <T as Animatable>::interpolate(&self.keyframes[frame_idx], next, time),
+            None => self.keyframes.last().unwrap().clone(),
+        }
+    }
+
+    /// Overwrites the last keyframe with the first, so a looping curve ends
+    /// exactly where it starts. See [`SmoothLoop`](crate::curve::SmoothLoop)
+    /// to blend into the start over several keyframes instead.
+    pub fn force_loop_exact(&mut self) {
+        if let Some(first) = self.keyframes.first().cloned() {
+            *self.keyframes.last_mut().unwrap() = first;
+        }
+    }
+
     /// `true` when this `CurveFixed` doesn't have any keyframe
     #[inline]
     pub fn is_empty(&self) -> bool {
@@ -113,26 +181,47 @@ where
     fn sample(&self, time: f32) -> T {
         // Make sure to have at least one sample
         assert!(!self.keyframes.is_empty(), "track is empty");
+        self.sample_frame(time * self.frame_rate + self.negative_frame_offset)
+    }
 
-        let frame_time = time * self.frame_rate + self.negative_frame_offset;
-        let frame_time = frame_time.clamp(0.0, (self.keyframe_count() - 1) as f32);
-        let frame = frame_time.trunc();
-        let time = frame_time - frame;
-        let frame_idx = frame as usize;
-        if frame_idx >= self.keyframe_count() - 1 {
-            self.keyframes.last().unwrap().clone()
-        } else {
-            // Interpolate the value
-            <T as Animatable>::interpolate(
-                &self.keyframes[frame_idx],
-                &self.keyframes[frame_idx + 1],
-                time,
-            )
+    fn sample_batch(&self, times: &[f32], out: &mut [T]) {
+        assert!(!self.keyframes.is_empty(), "track is empty");
+        assert_eq!(times.len(), out.len(), "mismatched batch lengths");
+        if self.keyframes.len() == 1 {
+            out.fill(self.keyframes[0].clone());
+            return;
         }
+        let mut frames = [(0, 0.0); BATCH_SIZE];
+        for (times, out) in times.chunks(BATCH_SIZE).zip(out.chunks_mut(BATCH_SIZE)) {
+            // Locate the frames of the whole chunk before touching any
+            // keyframes.
+            for (frame, time) in frames.iter_mut().zip(times) {
+                *frame = self.locate_frame(time * self.frame_rate + self.negative_frame_offset);
+            }
+            for (out, (frame_idx, time)) in out.iter_mut().zip(frames) {
+                *out = self.sample_located(frame_idx, time);
+            }
+        }
+    }
+
+    fn sample_normalized(&self, t: f32) -> T {
+        assert!(!self.keyframes.is_empty(), "track is empty");
+        // Index the keyframes directly, without converting to and from seconds.
+        self.sample_frame(t.clamp(0.0, 1.0) * (self.keyframe_count() - 1) as f32)
     }
 
     #[inline]
     fn sample_with_cursor(&self, _: KeyframeIndex, time: f32) -> (KeyframeIndex, T) {
         (0, self.sample(time))
     }
+
+    fn find_non_finite(&self) -> Option<usize> {
+        self.keyframes
+            .iter()
+            .position(|keyframe| !keyframe.is_finite())
+    }
+
+    fn sanitize(&mut self) -> usize {
+        sanitize_keyframes(&mut self.keyframes)
+    }
 }
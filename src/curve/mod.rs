@@ -1,32 +1,153 @@
-use crate::Animatable;
+use crate::{path::PropertyPath, Animatable};
 use bevy_asset::{Asset, Handle, HandleId};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, sync::Arc};
 use thiserror::Error;
 
+/// Implements the methods of [`Curve`] for a curve that samples another curve
+/// stored in its `$curve` field and maps each sampled value, keeping the
+/// duration, time offset, and keyframes of the inner curve.
+///
+/// The mapping is written like a closure taking the curve, the time it was
+/// sampled at, and the sampled value:
+///
+/// ```ignore
+/// impl<T: Animatable> Curve<T> for Offset<T> {
+///     mapped_curve_methods!(T, curve, |this, _, value| T::difference(&value, &this.offset));
+/// }
+/// ```
+macro_rules! mapped_curve_methods {
+    ($value_ty: ty, $curve: ident, |$this: pat_param, $time: pat_param, $value: pat_param| $map: expr) => {
+        #[inline]
+        fn duration(&self) -> f32 {
+            self.$curve.duration()
+        }
+
+        #[inline]
+        fn time_offset(&self) -> f32 {
+            self.$curve.time_offset()
+        }
+
+        #[inline]
+        fn keyframe_count(&self) -> usize {
+            self.$curve.keyframe_count()
+        }
+
+        fn sample(&self, time: f32) -> $value_ty {
+            let value = self.$curve.sample(time);
+            let ($this, $time, $value) = (self, time, value);
+            $map
+        }
+
+        fn sample_with_cursor(
+            &self,
+            cursor: $crate::curve::KeyframeIndex,
+            time: f32,
+        ) -> ($crate::curve::KeyframeIndex, $value_ty) {
+            let (cursor, value) = self.$curve.sample_with_cursor(cursor, time);
+            let ($this, $time, $value) = (self, time, value);
+            (cursor, $map)
+        }
+
+        #[inline]
+        fn find_non_finite(&self) -> Option<usize> {
+            self.$curve.find_non_finite()
+        }
+    };
+}
+
+pub(crate) use mapped_curve_methods;
+
 pub mod compressed;
+mod cubic;
 mod fixed;
-// mod variable;
-//mod variable_linear;
+mod smooth_loop;
+mod tween;
+mod variable_linear;
 
+pub use cubic::*;
 pub use fixed::*;
-// pub use variable::*;
-//pub use variable_linear::*;
+pub use smooth_loop::*;
+pub use tween::*;
+pub use variable_linear::*;
 
-// use crate::math::interpolation::Lerp;
 use bevy_math::*;
 
 /// Points to a keyframe inside a given curve.
 ///
-/// When sampling curves with variable framerate like [`CurveVariable`] and [`CurveVariableLinear`]
-/// is useful to keep track of a particular keyframe near the last sampling time, this keyframe index
+/// When sampling curves with variable framerate like [`CurveVariableLinear`] it is useful to keep
+/// track of a particular keyframe near the last sampling time, this keyframe index
 /// is referred as cursor and speeds up sampling when the next time is close to the previous on, that
 /// happens very often when playing a animation for instance.
 ///
 /// **NOTE** By default each keyframe is indexed using a `u16` to reduce memory usage for the curve cursor cache when implemented
 pub type KeyframeIndex = u16;
 
+/// How a curve is sampled at times before its first keyframe or after its
+/// last keyframe. It doesn't change the [`duration`](Curve::duration) of the
+/// curve.
+///
+/// Unlike the looping of clip nodes in a graph, this applies to a single
+/// curve, such as one that is shorter than the trim range of its clip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Extrapolation {
+    /// Holds the first or last keyframe.
+    Clamp,
+    /// Repeats the keyframes.
+    Loop,
+    /// Repeats the keyframes, alternating between forwards and backwards.
+    PingPong,
+    /// Continues along the first or last segment of the curve. Values are
+    /// extrapolated with [`Animatable::interpolate`] beyond `0..=1`, which
+    /// holds the nearest keyframe for discrete types.
+    Linear,
+}
+
+impl Default for Extrapolation {
+    fn default() -> Self {
+        Self::Clamp
+    }
+}
+
+impl Extrapolation {
+    /// Maps a time outside of `start..=end` back into it, for
+    /// [`Loop`](Self::Loop) and [`PingPong`](Self::PingPong). Other modes, and
+    /// times within the range, return the time unchanged.
+    pub fn wrap_time(self, time: f32, start: f32, end: f32) -> f32 {
+        let length = end - start;
+        if (start..=end).contains(&time) || length <= 0.0 {
+            return time;
+        }
+        match self {
+            Self::Loop => start + (time - start).rem_euclid(length),
+            Self::PingPong => {
+                let time = (time - start).rem_euclid(2.0 * length);
+                start
+                    + if time > length {
+                        2.0 * length - time
+                    } else {
+                        time
+                    }
+            }
+            Self::Clamp | Self::Linear => time,
+        }
+    }
+}
+
 /// Defines a curve function that can be sampled.
 /// Typically composed made of keyframes
-pub trait Curve<T>: Send + Sync + 'static {
+///
+/// Shared and borrowed curves, including `Arc<dyn Curve<T>>` and
+/// `Box<dyn Curve<T>>`, are also curves and can be used anywhere a curve is
+/// expected.
+///
+/// The trait doesn't require `'static`, so that borrowed curves (`&C`) can
+/// implement it and be passed to functions like [`resample_preserving_loop`].
+/// Curves stored in clips and graphs must still be `'static`, which
+/// [`AnimationClipBuilder::add_curve`] and `Arc<dyn Curve<T>>` require.
+///
+/// [`AnimationClipBuilder::add_curve`]: crate::clip::AnimationClipBuilder::add_curve
+pub trait Curve<T>: Send + Sync {
     /// The total duration of the curve in seconds.
     fn duration(&self) -> f32;
 
@@ -36,6 +157,16 @@ pub trait Curve<T>: Send + Sync + 'static {
     /// The number of keyframes within the curve.
     fn keyframe_count(&self) -> usize;
 
+    /// Samples the curve at a given time.
+    ///
+    /// Any finite time is valid, including negative times and times past the
+    /// end of the curve. Times outside of the keyframe range sample the first
+    /// or last keyframe respectively, unless the curve is configured with
+    /// another [`Extrapolation`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when the curve is empty, e.i. has no keyframes
     fn sample(&self, time: f32) -> T;
 
     /// Samples the curve starting from some keyframe cursor, this make the common case `O(1)`
@@ -57,68 +188,366 @@ pub trait Curve<T>: Send + Sync + 'static {
     ///
     /// Panics when the curve is empty, e.i. has no keyframes
     fn sample_with_cursor(&self, cursor: KeyframeIndex, time: f32) -> (KeyframeIndex, T);
+
+    /// Samples the curve at a normalized time, where `0.0` is the first
+    /// keyframe at [`time_offset`](Self::time_offset) and `1.0` is the last
+    /// keyframe at [`duration`](Self::duration). Times outside of `0..=1` are
+    /// clamped.
+    ///
+    /// Useful for driving curves with an external progress value, regardless
+    /// of their duration.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the curve is empty, e.i. has no keyframes
+    fn sample_normalized(&self, t: f32) -> T {
+        let start = self.time_offset();
+        let end = self.duration();
+        self.sample(start + t.clamp(0.0, 1.0) * (end - start))
+    }
+
+    /// Samples the curve at every time in `times`, writing the values into the
+    /// matching elements of `out`. Produces the same values as calling
+    /// [`sample`](Self::sample) for each time.
+    ///
+    /// Sampling many times at once lets curves amortize their per-sample
+    /// overhead, which adds up when animating large numbers of entities. The
+    /// default implementation samples each time individually.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the curve is empty, or if `times` and `out` have different
+    /// lengths.
+    fn sample_batch(&self, times: &[f32], out: &mut [T]) {
+        assert_eq!(times.len(), out.len(), "mismatched batch lengths");
+        for (time, out) in times.iter().zip(out.iter_mut()) {
+            *out = self.sample(*time);
+        }
+    }
+
+    /// Finds the index of the first keyframe that isn't finite, as checked by
+    /// [`Animatable::is_finite`].
+    ///
+    /// The default implementation returns `None`, for curves whose keyframes
+    /// cannot be inspected.
+    fn find_non_finite(&self) -> Option<usize> {
+        None
+    }
+
+    /// Replaces every keyframe that isn't finite with the nearest finite
+    /// keyframe, and returns the number of keyframes replaced. Keyframes are
+    /// left as is if none of them are finite.
+    ///
+    /// The default implementation does nothing, for curves whose keyframes
+    /// cannot be modified.
+    fn sanitize(&mut self) -> usize {
+        0
+    }
+}
+
+/// Checks that there are as many time stamps as keyframes, within the
+/// [`KeyframeIndex`] limit, and that the time stamps are sorted.
+pub(crate) fn check_time_stamps(time_stamps: &[f32], len: usize) -> Result<(), CurveError> {
+    // Make sure both have the same length
+    if time_stamps.len() != len {
+        return Err(CurveError::MismatchedLength);
+    }
+
+    if len > KeyframeIndex::MAX as usize {
+        return Err(CurveError::KeyframeLimitReached(
+            KeyframeIndex::MAX as usize,
+        ));
+    }
+
+    // Make sure time stamps are ordered
+    if !time_stamps
+        .iter()
+        .zip(time_stamps.iter().skip(1))
+        .all(|(a, b)| a < b)
+    {
+        return Err(CurveError::NotSorted);
+    }
+    Ok(())
+}
+
+/// Finds the keyframes around `time` in a sorted, non-empty list of time
+/// stamps, searching from a keyframe cursor.
+///
+/// Returns `Ok(cursor)` if `time` falls between the keyframes at `cursor - 1`
+/// and `cursor`, or `Err(index)` if it falls before the first or after the
+/// last keyframe, at `index`.
+pub(crate) fn find_segment(
+    time_stamps: &[f32],
+    mut cursor: KeyframeIndex,
+    time: f32,
+) -> Result<KeyframeIndex, KeyframeIndex> {
+    // Adjust for the current keyframe cursor
+    let last_cursor = (time_stamps.len() - 1) as KeyframeIndex;
+
+    cursor = cursor.min(last_cursor);
+    if time_stamps[cursor as usize] < time {
+        // Forward search
+        loop {
+            if cursor == last_cursor {
+                return Err(last_cursor);
+            }
+            cursor += 1;
+
+            if time_stamps[cursor as usize] >= time {
+                break;
+            }
+        }
+    } else {
+        // Backward search
+        loop {
+            if cursor == 0 {
+                return Err(0);
+            }
+
+            let i = cursor - 1;
+            if time_stamps[i as usize] <= time {
+                break;
+            }
+
+            cursor = i;
+        }
+    }
+    Ok(cursor)
+}
+
+/// Replaces every value that isn't finite with the nearest finite value,
+/// preferring earlier values on ties. See [`Curve::sanitize`].
+pub(crate) fn sanitize_keyframes<T: Animatable>(keyframes: &mut [T]) -> usize {
+    let finite: Vec<usize> = (0..keyframes.len())
+        .filter(|idx| keyframes[*idx].is_finite())
+        .collect();
+    if finite.is_empty() || finite.len() == keyframes.len() {
+        return 0;
+    }
+    for idx in 0..keyframes.len() {
+        if let Err(next) = finite.binary_search(&idx) {
+            let nearest = match (
+                next.checked_sub(1).map(|prev| finite[prev]),
+                finite.get(next),
+            ) {
+                (Some(prev), Some(next)) if next - idx < idx - prev => *next,
+                (Some(prev), _) => prev,
+                (None, Some(next)) => *next,
+                (None, None) => unreachable!(),
+            };
+            keyframes[idx] = keyframes[nearest].clone();
+        }
+    }
+    keyframes.len() - finite.len()
 }
 
 /// Resamples the curve preserving the loop cycle.
 ///
-/// [`CurveFixed`] only supports evenly spaced keyframes, because of that the curve duration
-/// is always a multiple of the frame rate. So resampling a curve will always round up their duration
-/// but it's still possible to preserve the loop cycle, i.e. both start and end keyframes will be remain the same,
+/// [`CurveFixed`] only supports evenly spaced keyframes, so keyframes are sampled every
+/// `1 / frame_rate` seconds from the start of the curve, and the duration of the resampled
+/// curve is rounded up to a whole number of frames. The loop cycle is still preserved:
+/// the first and last keyframes are sampled at the exact start and end of the curve,
 /// which is a very desired property.
-pub fn resample_preserving_loop<T: Animatable + Clone>(
-    curve: &impl Curve<T>,
-    frame_rate: f32,
-) -> CurveFixed<T> {
+///
+/// The resampled curve always has at least two keyframes. Curves with a single keyframe,
+/// or shorter than a frame, are resampled into a single frame from their start to their
+/// end value, so a single keyframe becomes a constant curve one frame long.
+///
+/// The resampled keyframes are canonicalized with [`Animatable::canonicalize_keyframes`].
+///
+/// # Panics
+///
+/// Panics when the curve is empty, e.i. has no keyframes
+pub fn resample_preserving_loop<T, C>(curve: &C, frame_rate: f32) -> CurveFixed<T>
+where
+    T: Animatable + Clone,
+    C: Curve<T> + ?Sized,
+{
+    /// How far past a whole number of frames a duration can be before it's rounded up to
+    /// another frame, to allow for f32 imprecision.
+    const FRAME_EPSILON: f32 = 1e-3;
+
     // get properties
-    let offset = curve.time_offset();
-    let duration = curve.duration();
+    let start = curve.time_offset();
+    let end = curve.duration().max(start);
 
-    let frame_count = (duration * frame_rate).round() as usize;
-    let frame_offset = (offset * frame_rate).round() as i32;
+    let frames = ((end - start) * frame_rate - FRAME_EPSILON).ceil().max(1.0) as usize;
+    let frame_offset = (start * frame_rate).round() as i32;
 
-    let normalize = 1.0 / (frame_count - 1) as f32;
     let mut cursor0 = 0;
-    let keyframes = (0..frame_count)
+    let mut keyframes = (0..=frames)
         .into_iter()
         .map(|f| {
-            let time = duration * (f as f32 * normalize) + offset;
+            // Sample the last frame at the exact end of the curve, as the rounded up
+            // frame might lie past it and f32 precision might not be enough to land on
+            // it and preserve the loop.
+            let time = if f == frames {
+                end
+            } else {
+                start + f as f32 / frame_rate
+            };
             let (cursor1, value) = curve.sample_with_cursor(cursor0, time);
             cursor0 = cursor1;
             value
         })
         .collect::<Vec<_>>();
-
-    // TODO: copy the start and end keyframes, because f32 precision might not be enough to preserve the loop
-    // keyframes[0] = self.value_at(0);
-    // keyframes[frame_count - 1] = self.value_at((self.len() - 1) as KeyframeIndex);
+    T::canonicalize_keyframes(&mut keyframes);
 
     CurveFixed::from_keyframes_with_offset(frame_rate, frame_offset, keyframes)
 }
 
-impl<C: Curve<HandleId>, T: Asset> Curve<Handle<T>> for C {
-    fn duration(&self) -> f32 {
-        <Self as Curve<HandleId>>::duration(self)
+/// Simplifies a curve by removing keyframes that can be reconstructed by linearly
+/// interpolating their neighbors.
+///
+/// The curve is first sampled at [`Curve::keyframe_count`] evenly spaced times between
+/// [`Curve::time_offset`] and [`Curve::duration`], which exactly reproduces the keyframes
+/// of curves with a fixed frame rate like [`CurveFixed`]. A keyframe is removed if every
+/// removed keyframe between its retained neighbors stays within `tolerance` of their
+/// interpolation, as measured by [`Animatable::distance`]. The first and last keyframes
+/// are always kept, and step discontinuities are kept as sharp as they are in the source.
+pub fn simplify_curve<T, C>(curve: &C, tolerance: f32) -> Result<CurveVariableLinear<T>, CurveError>
+where
+    T: Animatable + Clone,
+    C: Curve<T> + ?Sized,
+{
+    let count = curve.keyframe_count();
+    let start = curve.time_offset();
+    let end = curve.duration();
+    if count == 0 {
+        return CurveVariableLinear::with_keyframes(Vec::new(), Vec::new());
+    } else if count == 1 || end <= start {
+        return CurveVariableLinear::with_keyframes(vec![start], vec![curve.sample(start)]);
     }
 
-    fn time_offset(&self) -> f32 {
-        <Self as Curve<HandleId>>::time_offset(self)
-    }
+    let step = (end - start) / (count - 1) as f32;
+    let mut cursor = 0;
+    let times: Vec<f32> = (0..count).map(|f| start + f as f32 * step).collect();
+    let values: Vec<T> = times
+        .iter()
+        .map(|time| {
+            let (next_cursor, value) = curve.sample_with_cursor(cursor, *time);
+            cursor = next_cursor;
+            value
+        })
+        .collect();
 
-    fn keyframe_count(&self) -> usize {
-        <Self as Curve<HandleId>>::keyframe_count(self)
+    let mut keep = vec![0];
+    let mut anchor = 0;
+    for current in 1..count - 1 {
+        // Check if the current keyframe can be skipped by linking the last
+        // retained keyframe directly to the next one.
+        let next = current + 1;
+        let fits = (anchor + 1..next).all(|skipped| {
+            let t = (times[skipped] - times[anchor]) / (times[next] - times[anchor]);
+            let value = T::interpolate(&values[anchor], &values[next], t);
+            T::distance(&value, &values[skipped]) <= tolerance
+        });
+        if !fits {
+            keep.push(current);
+            anchor = current;
+        }
     }
+    keep.push(count - 1);
 
-    fn sample(&self, time: f32) -> Handle<T> {
-        let id = <Self as Curve<HandleId>>::sample(self, time);
-        Handle::<T>::weak(id)
-    }
+    CurveVariableLinear::with_keyframes(
+        keep.iter().map(|idx| times[*idx]).collect(),
+        keep.iter().map(|idx| values[*idx].clone()).collect(),
+    )
+}
 
-    fn sample_with_cursor(&self, cursor: KeyframeIndex, time: f32) -> (KeyframeIndex, Handle<T>) {
-        let (cursor, id) = <Self as Curve<HandleId>>::sample_with_cursor(self, cursor, time);
-        (cursor, Handle::<T>::weak(id))
-    }
+macro_rules! impl_curve_for_pointer {
+    ($([$($generics: tt)*] $ty: ty),* $(,)?) => {
+        $(
+            impl<$($generics)*> Curve<T> for $ty {
+                #[inline]
+                fn duration(&self) -> f32 {
+                    (**self).duration()
+                }
+
+                #[inline]
+                fn time_offset(&self) -> f32 {
+                    (**self).time_offset()
+                }
+
+                #[inline]
+                fn keyframe_count(&self) -> usize {
+                    (**self).keyframe_count()
+                }
+
+                #[inline]
+                fn sample(&self, time: f32) -> T {
+                    (**self).sample(time)
+                }
+
+                #[inline]
+                fn sample_with_cursor(&self, cursor: KeyframeIndex, time: f32) -> (KeyframeIndex, T) {
+                    (**self).sample_with_cursor(cursor, time)
+                }
+
+                #[inline]
+                fn sample_normalized(&self, t: f32) -> T {
+                    (**self).sample_normalized(t)
+                }
+
+                #[inline]
+                fn sample_batch(&self, times: &[f32], out: &mut [T]) {
+                    (**self).sample_batch(times, out)
+                }
+
+                #[inline]
+                fn find_non_finite(&self) -> Option<usize> {
+                    (**self).find_non_finite()
+                }
+            }
+        )*
+    };
 }
 
+// Forward `Curve` through pointer types.
+impl_curve_for_pointer!(
+    [T: 'static] Arc<dyn Curve<T>>,
+    [T: 'static] Box<dyn Curve<T>>,
+    [T, C: Curve<T>] Arc<C>,
+    [T, C: Curve<T> + ?Sized] &C,
+);
+
+/// Curves of [`HandleId`]s can also be sampled as weak [`Handle`]s.
+macro_rules! impl_handle_curve {
+    ($($ty: ty),* $(,)?) => {
+        $(
+            impl<T: Asset> Curve<Handle<T>> for $ty {
+                fn duration(&self) -> f32 {
+                    <Self as Curve<HandleId>>::duration(self)
+                }
+
+                fn time_offset(&self) -> f32 {
+                    <Self as Curve<HandleId>>::time_offset(self)
+                }
+
+                fn keyframe_count(&self) -> usize {
+                    <Self as Curve<HandleId>>::keyframe_count(self)
+                }
+
+                fn sample(&self, time: f32) -> Handle<T> {
+                    let id = <Self as Curve<HandleId>>::sample(self, time);
+                    Handle::<T>::weak(id)
+                }
+
+                fn sample_with_cursor(
+                    &self,
+                    cursor: KeyframeIndex,
+                    time: f32,
+                ) -> (KeyframeIndex, Handle<T>) {
+                    let (cursor, id) = <Self as Curve<HandleId>>::sample_with_cursor(self, cursor, time);
+                    (cursor, Handle::<T>::weak(id))
+                }
+            }
+        )*
+    };
+}
+
+impl_handle_curve!(CurveFixed<HandleId>, CurveVariableLinear<HandleId>);
+
 #[derive(Error, Debug)]
 pub enum CurveError {
     #[error("number of keyframes time stamps and values doesn't match")]
@@ -127,4 +556,289 @@ pub enum CurveError {
     KeyframeLimitReached(usize),
     #[error("keyframes aren't sorted by time")]
     NotSorted,
+    #[error("keyframe {index} of '{path}' is not finite")]
+    NonFiniteKeyframe { path: PropertyPath, index: usize },
+    #[error("keyframe {index} of the graph parameter '{param}' is not finite")]
+    NonFiniteParamKeyframe {
+        param: Cow<'static, str>,
+        index: usize,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    assert_obj_safe!(Curve<f32>);
+    assert_impl_all!(dyn Curve<f32>: Send, Sync);
+    assert_impl_all!(Arc<dyn Curve<Vec3>>: Curve<Vec3>);
+    assert_impl_all!(Box<dyn Curve<Vec3>>: Curve<Vec3>);
+    assert_impl_all!(&'static dyn Curve<Vec3>: Curve<Vec3>);
+    assert_impl_all!(Arc<CurveFixed<HandleId>>: Curve<Handle<crate::clip::AnimationClip>>);
+
+    fn assert_sampling_bounded(curve: &impl Curve<f32>, keyframes: &[f32]) {
+        let min = keyframes.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = keyframes.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        let mut cursor = 0;
+        for _ in 0..10_000 {
+            let time = rng.gen_range(-1000.0..=1000.0);
+            let value = curve.sample(time);
+            let (next_cursor, cursor_value) = curve.sample_with_cursor(cursor, time);
+            cursor = next_cursor;
+            assert!((cursor as usize) < keyframes.len());
+            for value in [value, cursor_value] {
+                assert!(!value.is_nan(), "sampled NaN at t = {}", time);
+                assert!(
+                    value >= min && value <= max,
+                    "t = {}, value = {}",
+                    time,
+                    value
+                );
+            }
+        }
+        let (_, first) = curve.sample_with_cursor(0, -1000.0);
+        let (_, last) = curve.sample_with_cursor(0, 1000.0);
+        assert_eq!(first, keyframes[0]);
+        assert_eq!(last, keyframes[keyframes.len() - 1]);
+    }
+
+    #[test]
+    pub fn test_sampling_any_finite_time() {
+        let mut rng = StdRng::seed_from_u64(0xcafe);
+        let many: Vec<f32> = (0..100).map(|_| rng.gen_range(-10.0..10.0)).collect();
+        for keyframes in [vec![3.0], vec![-1.0, 2.0], many] {
+            let fixed = CurveFixed::from_keyframes(30.0, keyframes.clone());
+            assert_sampling_bounded(&fixed, &keyframes);
+
+            let mut offset = CurveFixed::from_keyframes(24.0, keyframes.clone());
+            offset.set_frame_offset(-12);
+            assert_sampling_bounded(&offset, &keyframes);
+
+            let times = (0..keyframes.len()).map(|f| f as f32 * 0.1 - 1.0).collect();
+            let variable = CurveVariableLinear::with_keyframes(times, keyframes.clone()).unwrap();
+            assert_sampling_bounded(&variable, &keyframes);
+        }
+    }
+
+    #[test]
+    pub fn test_simplify_mostly_linear_curve() {
+        let tolerance = 1e-3;
+        let keyframes = (0..31)
+            .map(|f| f as f32 / 30.0 + if f % 2 == 0 { 1e-4 } else { -1e-4 })
+            .collect();
+        let curve = CurveFixed::from_keyframes(30.0, keyframes);
+        let simplified = simplify_curve(&curve, tolerance).unwrap();
+
+        assert!(simplified.keyframe_count() < curve.keyframe_count());
+        assert_eq!(simplified.time_offset(), curve.time_offset());
+        assert_eq!(simplified.duration(), curve.duration());
+        for step in 0..=200 {
+            let time = step as f32 / 200.0;
+            assert!((simplified.sample(time) - curve.sample(time)).abs() <= tolerance);
+        }
+    }
+
+    #[test]
+    pub fn test_simplify_preserves_step() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![0.0f32, 0.0, 0.0, 1.0, 1.0, 1.0]);
+        let simplified = simplify_curve(&curve, 1e-3).unwrap();
+
+        assert_eq!(simplified.keyframe_count(), 4);
+        for step in 0..=50 {
+            let time = step as f32 / 10.0;
+            assert!((simplified.sample(time) - curve.sample(time)).abs() <= 1e-5);
+        }
+    }
+
+    #[test]
+    pub fn test_simplify_constant_rotation() {
+        let rotation = Quat::from_rotation_y(1.0);
+        let curve = CurveFixed::from_keyframes(30.0, vec![rotation; 10]);
+        let simplified = simplify_curve(&curve, 1e-3).unwrap();
+
+        assert_eq!(simplified.keyframe_count(), 2);
+        assert!(Quat::distance(&simplified.sample(0.1), &rotation) <= 1e-3);
+    }
+
+    #[test]
+    pub fn test_resample_preserves_end_keyframes() {
+        let keyframes: Vec<f32> = (0..7).map(|f| (f as f32 * 0.37).sin()).collect();
+        let times = (0..7).map(|f| f as f32 * 0.3).collect();
+        let curve = CurveVariableLinear::with_keyframes(times, keyframes.clone()).unwrap();
+        let resampled = resample_preserving_loop(&curve, 30.0);
+
+        assert_eq!(resampled.keyframes[0], keyframes[0]);
+        assert_eq!(resampled.keyframes.last(), keyframes.last());
+    }
+
+    #[test]
+    pub fn test_resample_short_curves() {
+        let curve =
+            CurveVariableLinear::with_keyframes(vec![0.0, 0.01], vec![1.0f32, 2.0]).unwrap();
+        let resampled = resample_preserving_loop(&curve, 30.0);
+        assert_eq!(resampled.keyframes, vec![1.0, 2.0]);
+        assert!(resampled.iter().all(|value| value.is_finite()));
+
+        let constant = resample_preserving_loop(&CurveFixed::from_constant(Vec3::X), 30.0);
+        assert_eq!(constant.keyframes, vec![Vec3::X, Vec3::X]);
+        assert_eq!(constant.sample(0.5), Vec3::X);
+    }
+
+    #[test]
+    pub fn test_resample_keeps_frame_spacing() {
+        let keyframes: Vec<f32> = (0..31).map(|f| (f as f32 * 0.37).sin()).collect();
+        let curve = CurveFixed::from_keyframes(30.0, keyframes.clone());
+        let resampled = resample_preserving_loop(&curve, 24.0);
+
+        assert_eq!(resampled.keyframe_count(), 25);
+        assert_eq!(resampled.duration(), curve.duration());
+        assert_eq!(resampled.keyframes[0], keyframes[0]);
+        assert_eq!(resampled.keyframes.last(), keyframes.last());
+        for f in 0..25 {
+            let time = f as f32 / 24.0;
+            assert!((resampled.sample(time) - curve.sample(time)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    pub fn test_force_loop_exact() {
+        let mut fixed = CurveFixed::from_keyframes(4.0, vec![1.0f32, 3.0, 2.0, 1.1]);
+        fixed.force_loop_exact();
+        assert_eq!(fixed.sample(fixed.duration()), fixed.sample(0.0));
+        assert_eq!(fixed.sample(0.5), 2.0);
+
+        let mut variable =
+            CurveVariableLinear::with_keyframes(vec![0.0, 0.5, 2.0], vec![1.0f32, 3.0, 1.1])
+                .unwrap();
+        variable.force_loop_exact();
+        assert_eq!(variable.sample(2.0), 1.0);
+        assert_eq!(variable.sample(0.5), 3.0);
+    }
+
+    #[test]
+    pub fn test_resampled_rotations_are_canonicalized() {
+        let rotation = Quat::from_rotation_y(1.0);
+        let keyframes = (0..8)
+            .map(|idx| if idx % 2 == 0 { rotation } else { -rotation })
+            .collect();
+        let curve = CurveFixed::from_keyframes(4.0, keyframes);
+        let resampled = resample_preserving_loop(&curve, 30.0);
+
+        assert!(resampled.keyframe_count() > curve.keyframe_count());
+        // Interpolated rotations are only approximately normalized.
+        let first = resampled.keyframes[0].normalize();
+        for keyframe in resampled.iter() {
+            assert!(
+                keyframe.normalize().dot(first) > 1.0 - 1e-5,
+                "{:?}",
+                keyframe
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_sample_normalized_with_time_offset() {
+        let keyframes = vec![0.0f32, 4.0, 1.0, 3.0, 2.0, 5.0];
+        let mut fixed = CurveFixed::from_keyframes(24.0, keyframes.clone());
+        fixed.set_frame_offset(12);
+        let times = (0..keyframes.len()).map(|f| f as f32 * 0.3 - 1.0).collect();
+        let variable = CurveVariableLinear::with_keyframes(times, keyframes.clone()).unwrap();
+        let curves: [Arc<dyn Curve<f32>>; 2] = [Arc::new(fixed), Arc::new(variable)];
+
+        for curve in curves {
+            let start = curve.time_offset();
+            let end = curve.duration();
+            assert_ne!(start, 0.0);
+            assert!(
+                (curve.sample_normalized(0.5) - curve.sample((start + end) / 2.0)).abs() < 1e-5
+            );
+            for step in 0..=20 {
+                let t = step as f32 / 20.0;
+                let expected = curve.sample(start + t * (end - start));
+                assert!((curve.sample_normalized(t) - expected).abs() < 1e-5);
+            }
+            assert_eq!(curve.sample_normalized(-1.0), keyframes[0]);
+            assert_eq!(curve.sample_normalized(2.0), keyframes[keyframes.len() - 1]);
+        }
+    }
+
+    #[test]
+    pub fn test_extrapolation_modes() {
+        // Keyframes at 1, 2 and 3 seconds.
+        let keyframes = vec![0.0f32, 1.0, 3.0];
+        let fixed = CurveFixed::from_keyframes_with_offset(1.0, 1, keyframes.clone());
+        let variable = CurveVariableLinear::with_keyframes(vec![1.0, 2.0, 3.0], keyframes).unwrap();
+        let cases = [
+            (Extrapolation::Clamp, 0.0, 3.0),
+            (Extrapolation::Loop, 2.0, 1.5),
+            (Extrapolation::PingPong, 0.5, 0.75),
+            (Extrapolation::Linear, -0.5, 5.5),
+        ];
+        for (extrapolation, before, after) in cases {
+            let curves: [Arc<dyn Curve<f32>>; 2] = [
+                Arc::new(fixed.clone().with_extrapolation(extrapolation)),
+                Arc::new(variable.clone().with_extrapolation(extrapolation)),
+            ];
+            for curve in curves {
+                assert_eq!(curve.time_offset(), 1.0);
+                assert_eq!(curve.duration(), 3.0);
+                assert!(
+                    (curve.sample(0.5) - before).abs() < 1e-5,
+                    "{:?}",
+                    extrapolation
+                );
+                assert!(
+                    (curve.sample(4.25) - after).abs() < 1e-5,
+                    "{:?}",
+                    extrapolation
+                );
+                assert!((curve.sample_with_cursor(2, 4.25).1 - after).abs() < 1e-5);
+                assert_eq!(curve.sample(2.5), 2.0);
+
+                let times = [0.5, 1.5, 4.25, -3.0, 7.75];
+                let mut batch = [0.0; 5];
+                curve.sample_batch(&times, &mut batch);
+                for (time, value) in times.iter().zip(batch) {
+                    assert_eq!(value, curve.sample(*time), "t = {}", time);
+                }
+            }
+        }
+
+        let cubic = CurveCubic::from_keyframes_with_tangents(
+            vec![0.0, 1.0],
+            vec![0.0, 1.0],
+            vec![2.0, 0.0],
+            vec![0.0, 3.0],
+        )
+        .unwrap();
+        assert_eq!(cubic.sample(-1.0), 0.0);
+        let cubic = cubic.with_extrapolation(Extrapolation::Linear);
+        assert_eq!(cubic.sample(-1.0), -2.0);
+        assert_eq!(cubic.sample(2.0), 4.0);
+    }
+
+    #[test]
+    pub fn test_batch_sampling_matches_scalar_sampling() {
+        let keyframes: Vec<Vec3> = (0..10)
+            .map(|idx| Vec3::new(idx as f32, (idx as f32).cos(), 2.0 - idx as f32))
+            .collect();
+        let mut fixed = CurveFixed::from_keyframes(24.0, keyframes.clone());
+        fixed.set_frame_offset(-6);
+        let times = (0..keyframes.len()).map(|f| f as f32 * 0.1).collect();
+        let variable = CurveVariableLinear::with_keyframes(times, keyframes).unwrap();
+        let curves: [Arc<dyn Curve<Vec3>>; 2] = [Arc::new(fixed), Arc::new(variable)];
+
+        // Not a multiple of the batch size, and out of order.
+        let mut rng = StdRng::seed_from_u64(0xba7c);
+        let times: Vec<f32> = (0..37).map(|_| rng.gen_range(-1.0..2.0)).collect();
+        for curve in curves {
+            let mut batch = vec![Vec3::ZERO; times.len()];
+            curve.sample_batch(&times, &mut batch);
+            for (time, value) in times.iter().zip(batch) {
+                assert_eq!(value, curve.sample(*time), "t = {}", time);
+            }
+        }
+    }
 }
@@ -0,0 +1,106 @@
+use crate::{curve::Curve, Animatable};
+use std::sync::Arc;
+
+/// A curve whose end is blended towards its value at time 0, closing the seam
+/// of a looping curve whose last keyframe doesn't quite match its first.
+///
+/// Over the final `blend_window` seconds before [`Curve::duration`], the curve
+/// is interpolated towards its start value with a linear ramp, using
+/// [`Animatable::interpolate`], so it samples exactly the start value at its
+/// duration. Rotations are interpolated in the same hemisphere. Times before
+/// the window sample the underlying curve unchanged.
+#[derive(Clone)]
+pub struct SmoothLoop<T> {
+    curve: Arc<dyn Curve<T>>,
+    start: T,
+    blend_window: f32,
+}
+
+impl<T> SmoothLoop<T>
+where
+    T: Animatable + Clone,
+{
+    /// Smooths the loop of `curve` over the last `blend_window` seconds. The
+    /// window is clamped to the duration of the curve.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the curve is empty, e.i. has no keyframes
+    pub fn new(curve: Arc<dyn Curve<T>>, blend_window: f32) -> Self {
+        let start = curve.sample(0.0);
+        let blend_window = blend_window.clamp(0.0, curve.duration().max(0.0));
+        Self {
+            curve,
+            start,
+            blend_window,
+        }
+    }
+
+    #[inline]
+    pub fn blend_window(&self) -> f32 {
+        self.blend_window
+    }
+
+    /// Blends a sample of the underlying curve towards the start value.
+    fn close_seam(&self, time: f32, value: T) -> T {
+        let window_start = self.curve.duration() - self.blend_window;
+        if self.blend_window <= 0.0 || time <= window_start {
+            return value;
+        }
+        let t = (time - window_start) / self.blend_window;
+        if t >= 1.0 {
+            // Interpolation isn't always exact at its ends, such as for
+            // rotations, which are only approximately normalized.
+            return self.start.clone();
+        }
+        T::interpolate(&value, &self.start, t)
+    }
+}
+
+impl<T> Curve<T> for SmoothLoop<T>
+where
+    T: Animatable + Clone,
+{
+    mapped_curve_methods!(T, curve, |this, time, value| this.close_seam(time, value));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curve::CurveFixed;
+    use bevy_math::{Quat, Vec3};
+
+    #[test]
+    pub fn test_smooth_loop_closes_seam() {
+        let keyframes = (0..11).map(|f| Vec3::new(f as f32, 1.0, 0.5)).collect();
+        let curve: Arc<dyn Curve<Vec3>> = Arc::new(CurveFixed::from_keyframes(10.0, keyframes));
+        let smoothed = SmoothLoop::new(curve.clone(), 0.25);
+        assert_eq!(smoothed.duration(), 1.0);
+        assert!(smoothed.sample(1.0).abs_diff_eq(smoothed.sample(0.0), 1e-5));
+        // Values before the window are untouched.
+        for step in 0..=7 {
+            let time = step as f32 * 0.1;
+            assert_eq!(smoothed.sample(time), curve.sample(time));
+        }
+        let (_, value) = smoothed.sample_with_cursor(0, 0.9);
+        assert_eq!(value, smoothed.sample(0.9));
+        assert!(value.x < curve.sample(0.9).x);
+    }
+
+    #[test]
+    pub fn test_smooth_loop_rotations_stay_in_hemisphere() {
+        let start = Quat::from_rotation_y(0.1);
+        // Ends close to the start, but in the opposite hemisphere.
+        let end = -Quat::from_rotation_y(0.2);
+        let curve: Arc<dyn Curve<Quat>> = Arc::new(CurveFixed::from_keyframes(
+            2.0,
+            vec![start, Quat::IDENTITY, end],
+        ));
+        let smoothed = SmoothLoop::new(curve, 0.5);
+
+        assert!(smoothed.sample(1.0).abs_diff_eq(smoothed.sample(0.0), 1e-5));
+        // The blend takes the short way around, staying near both ends.
+        let mid = smoothed.sample(0.75);
+        assert!(mid.dot(start).abs() > Quat::from_rotation_y(0.3).dot(Quat::IDENTITY));
+    }
+}
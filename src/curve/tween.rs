@@ -0,0 +1,160 @@
+use crate::{
+    curve::{Curve, KeyframeIndex},
+    graph::Easing,
+    Animatable,
+};
+
+/// A curve between exactly two values, like a fade in or a move from one
+/// point to another.
+///
+/// The values are stored inline, without the keyframe buffer of
+/// [`CurveFixed`](crate::curve::CurveFixed), and the curve is eased between
+/// them with an [`Easing`]. Times outside of `0..=duration` hold the start or
+/// end value.
+#[derive(Debug, Clone)]
+pub struct Tween<T> {
+    start: T,
+    end: T,
+    duration: f32,
+    easing: Easing,
+}
+
+impl<T> Tween<T>
+where
+    T: Animatable + Clone,
+{
+    /// Creates a tween from `start` to `end` over `duration` seconds. Tweens
+    /// without a positive duration always sample `end`.
+    pub fn new(start: T, end: T, duration: f32, easing: Easing) -> Self {
+        Self {
+            start,
+            end,
+            duration,
+            easing,
+        }
+    }
+
+    #[inline]
+    pub fn start(&self) -> &T {
+        &self.start
+    }
+
+    #[inline]
+    pub fn end(&self) -> &T {
+        &self.end
+    }
+
+    #[inline]
+    pub fn easing(&self) -> &Easing {
+        &self.easing
+    }
+}
+
+impl<T> Curve<T> for Tween<T>
+where
+    T: Animatable + Clone,
+{
+    fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    #[inline]
+    fn time_offset(&self) -> f32 {
+        0.0
+    }
+
+    #[inline]
+    fn keyframe_count(&self) -> usize {
+        2
+    }
+
+    fn sample(&self, time: f32) -> T {
+        let t = if self.duration > 0.0 {
+            (time / self.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        T::interpolate(&self.start, &self.end, self.easing.ease(t))
+    }
+
+    #[inline]
+    fn sample_with_cursor(&self, _: KeyframeIndex, time: f32) -> (KeyframeIndex, T) {
+        (0, self.sample(time))
+    }
+
+    fn find_non_finite(&self) -> Option<usize> {
+        [&self.start, &self.end]
+            .iter()
+            .position(|value| !value.is_finite())
+    }
+
+    fn sanitize(&mut self) -> usize {
+        match (self.start.is_finite(), self.end.is_finite()) {
+            (false, true) => {
+                self.start = self.end.clone();
+                1
+            }
+            (true, false) => {
+                self.end = self.start.clone();
+                1
+            }
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy_math::Vec3;
+    use std::sync::Arc;
+
+    #[test]
+    pub fn test_tween_easings() {
+        // The expected eased weights at t = 0, 0.5 and 1.
+        let easings = [
+            (Easing::Linear, 0.5),
+            (Easing::QuadIn, 0.25),
+            (Easing::QuadOut, 0.75),
+            (Easing::QuadInOut, 0.5),
+            (Easing::CubicIn, 0.125),
+            (Easing::CubicOut, 0.875),
+            (Easing::CubicInOut, 0.5),
+            (Easing::Custom(Arc::new(|t| t.sqrt())), 0.5f32.sqrt()),
+        ];
+        let (start, end) = (Vec3::new(1.0, 2.0, 3.0), Vec3::new(5.0, -2.0, 3.0));
+        for (easing, half) in easings {
+            let tween = Tween::new(start, end, 2.0, easing.clone());
+            assert_eq!(tween.sample(0.0), start, "{:?}", easing);
+            assert!(
+                tween.sample(1.0).abs_diff_eq(start.lerp(end, half), 1e-5),
+                "{:?}",
+                easing
+            );
+            assert!(tween.sample(2.0).abs_diff_eq(end, 1e-5), "{:?}", easing);
+            // Times outside of the tween hold its ends.
+            assert_eq!(tween.sample(-1.0), tween.sample(0.0));
+            assert_eq!(tween.sample(3.0), tween.sample(2.0));
+        }
+    }
+
+    #[test]
+    pub fn test_tween_matches_line() {
+        let tween = Tween::new(0.0f32, 4.0, 0.5, Easing::Linear);
+        assert_eq!(tween.duration(), 0.5);
+        assert_eq!(tween.keyframe_count(), 2);
+        assert_eq!(tween.sample_normalized(0.25), 1.0);
+        let mut out = [0.0; 3];
+        tween.sample_batch(&[0.0, 0.25, 1.0], &mut out);
+        assert_eq!(out, [0.0, 2.0, 4.0]);
+        assert_eq!(
+            Tween::new(0.0f32, 4.0, 0.0, Easing::Linear).sample(0.0),
+            4.0
+        );
+
+        let mut tween = Tween::new(f32::NAN, 4.0, 0.5, Easing::Linear);
+        assert_eq!(tween.find_non_finite(), Some(0));
+        assert_eq!(tween.sanitize(), 1);
+        assert_eq!(tween.sample(0.25), 4.0);
+    }
+}
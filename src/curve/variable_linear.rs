@@ -0,0 +1,196 @@
+use crate::{
+    curve::{
+        check_time_stamps, find_segment, sanitize_keyframes, Curve, CurveError, Extrapolation,
+        KeyframeIndex,
+    },
+    Animatable,
+};
+use serde::{Deserialize, Serialize};
+
+/// Curve with sparse keyframes frames, in another words a curve with variable frame rate.
+///
+/// Values between keyframes are linearly interpolated using [`Animatable::interpolate`].
+/// It can't handle discontinuities, as in two keyframes with the same timestamp.
+///
+/// **NOTE**: The maximum number of keyframes is limited by the capacity of [`KeyframeIndex`] (a `u16`)
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct CurveVariableLinear<T> {
+    time_stamps: Vec<f32>,
+    keyframes: Vec<T>,
+    #[serde(default)]
+    extrapolation: Extrapolation,
+}
+
+impl<T> CurveVariableLinear<T>
+where
+    T: Animatable + Clone,
+{
+    pub fn with_keyframes(samples: Vec<f32>, values: Vec<T>) -> Result<Self, CurveError> {
+        check_time_stamps(&samples, values.len())?;
+        Ok(Self {
+            time_stamps: samples,
+            keyframes: values,
+            extrapolation: Extrapolation::Clamp,
+        })
+    }
+
+    pub fn from_line(time0: f32, time1: f32, value0: T, value1: T) -> Self {
+        if time0 < time1 {
+            Self {
+                time_stamps: vec![time0, time1],
+                keyframes: vec![value0, value1],
+                extrapolation: Extrapolation::Clamp,
+            }
+        } else {
+            Self {
+                time_stamps: vec![time1, time0],
+                keyframes: vec![value1, value0],
+                extrapolation: Extrapolation::Clamp,
+            }
+        }
+    }
+
+    pub fn from_constant(value: T) -> Self {
+        Self {
+            time_stamps: vec![0.0],
+            keyframes: vec![value],
+            extrapolation: Extrapolation::Clamp,
+        }
+    }
+
+    /// Sets how the curve is sampled outside of its keyframes.
+    pub fn with_extrapolation(mut self, extrapolation: Extrapolation) -> Self {
+        self.extrapolation = extrapolation;
+        self
+    }
+
+    #[inline]
+    pub fn extrapolation(&self) -> Extrapolation {
+        self.extrapolation
+    }
+
+    #[inline]
+    pub fn set_extrapolation(&mut self, extrapolation: Extrapolation) {
+        self.extrapolation = extrapolation;
+    }
+
+    /// Gets keyframe value at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is out of bounds.
+    #[inline]
+    pub fn get_value(&self, at: KeyframeIndex) -> &T {
+        &self.keyframes[at as usize]
+    }
+
+    /// Gets keyframe time at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is out of bounds.
+    #[inline]
+    pub fn get_time(&self, at: KeyframeIndex) -> f32 {
+        self.time_stamps[at as usize]
+    }
+
+    /// Overwrites the last keyframe with the first, so a looping curve ends
+    /// exactly where it starts. See [`SmoothLoop`](crate::curve::SmoothLoop)
+    /// to blend into the start over several keyframes instead.
+    pub fn force_loop_exact(&mut self) {
+        if let Some(first) = self.keyframes.first().cloned() {
+            *self.keyframes.last_mut().unwrap() = first;
+        }
+    }
+
+    /// `true` when this `CurveVariableLinear` doesn't have any keyframe.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    pub fn set_time_offset(&mut self, mut time_offset: f32) {
+        time_offset -= self.time_offset(); // Removes current offset
+        self.time_stamps.iter_mut().for_each(|t| *t += time_offset);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (f32, &T)> {
+        self.time_stamps.iter().copied().zip(self.keyframes.iter())
+    }
+}
+
+impl<T> Curve<T> for CurveVariableLinear<T>
+where
+    T: Animatable + Clone,
+{
+    fn duration(&self) -> f32 {
+        self.time_stamps.last().copied().unwrap_or(0.0)
+    }
+
+    fn time_offset(&self) -> f32 {
+        self.time_stamps.first().copied().unwrap_or(0.0)
+    }
+
+    #[inline]
+    fn keyframe_count(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    fn sample(&self, time: f32) -> T {
+        // Index guessing gives a small search optimization
+        let index = if time < self.duration() * 0.5 {
+            0
+        } else {
+            self.time_stamps.len() - 1
+        };
+
+        self.sample_with_cursor(index as KeyframeIndex, time).1
+    }
+
+    fn sample_with_cursor(&self, cursor: KeyframeIndex, time: f32) -> (KeyframeIndex, T) {
+        let time = self
+            .extrapolation
+            .wrap_time(time, self.time_offset(), self.duration());
+        let cursor = match find_segment(&self.time_stamps, cursor, time) {
+            Ok(cursor) => cursor,
+            Err(index)
+                if self.extrapolation == Extrapolation::Linear && self.keyframes.len() > 1 =>
+            {
+                // Extend the first or last segment.
+                let i = (index as usize).max(1) - 1;
+                let previous_time = self.time_stamps[i];
+                let t = (time - previous_time) / (self.time_stamps[i + 1] - previous_time);
+                let value = T::interpolate(&self.keyframes[i], &self.keyframes[i + 1], t);
+                return (index, value);
+            }
+            Err(index) => return (index, self.keyframes[index as usize].clone()),
+        };
+
+        // Lerp the value
+        let i = cursor - 1;
+        let previous_time = self.time_stamps[i as usize];
+        let t = (time - previous_time) / (self.time_stamps[cursor as usize] - previous_time);
+        debug_assert!(
+            (0.0..=1.0).contains(&t),
+            "t = {} but should be normalized",
+            t
+        ); // Checks if it's required to normalize t
+        let value = T::interpolate(
+            &self.keyframes[i as usize],
+            &self.keyframes[cursor as usize],
+            t,
+        );
+
+        (cursor, value)
+    }
+
+    fn find_non_finite(&self) -> Option<usize> {
+        self.keyframes
+            .iter()
+            .position(|keyframe| !keyframe.is_finite())
+    }
+
+    fn sanitize(&mut self) -> usize {
+        sanitize_keyframes(&mut self.keyframes)
+    }
+}
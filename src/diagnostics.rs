@@ -0,0 +1,128 @@
+//! Counters for profiling the animation systems.
+//!
+//! The [`AnimationPlugin`](crate::AnimationPlugin) keeps an
+//! [`AnimationDiagnostics`] resource up to date. With the `diagnostics`
+//! feature, the counters are also recorded as `bevy_diagnostic` measurements,
+//! so they show up in the output of `LogDiagnosticsPlugin`.
+
+use bevy_ecs::prelude::*;
+use std::time::Duration;
+
+#[cfg(feature = "diagnostics")]
+use bevy_diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+
+/// Counters collected by the animation systems over the current frame.
+///
+/// The counters are reset at the start of every frame, so they only cover the
+/// graphs that were evaluated and applied this frame. Values written outside of
+/// [`evaluate_graph_system`] and [`animate_entities_system`], such as graphs
+/// applied as soon as they are bound, are not counted.
+///
+/// [`evaluate_graph_system`]: crate::evaluate_graph_system
+/// [`animate_entities_system`]: crate::graph::application::animate_entities_system
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AnimationDiagnostics {
+    /// The number of graphs evaluated.
+    pub graphs_evaluated: u32,
+    /// The number of graph nodes visited while evaluating. See
+    /// [`GraphStats`](crate::graph::GraphStats).
+    pub nodes_visited: u32,
+    /// The number of clips blended with a nonzero weight.
+    pub active_clips: u32,
+    /// The number of bound entities written to.
+    pub bones_applied: u32,
+    /// The number of tracks blended and written to an entity.
+    pub tracks_sampled: u32,
+    /// The number of blended tracks that weren't written, as their value was
+    /// unchanged. Also counted in `tracks_sampled`.
+    pub tracks_skipped: u32,
+    /// The time spent evaluating graphs.
+    pub evaluation_time: Duration,
+    /// The time spent writing graphs to their bound entities.
+    pub application_time: Duration,
+}
+
+#[cfg(feature = "diagnostics")]
+impl AnimationDiagnostics {
+    pub const GRAPHS_EVALUATED: DiagnosticId =
+        DiagnosticId::from_u128(18978600833262232939008914078720294081);
+    pub const NODES_VISITED: DiagnosticId =
+        DiagnosticId::from_u128(281075966608346359477102960642315777053);
+    pub const ACTIVE_CLIPS: DiagnosticId =
+        DiagnosticId::from_u128(229667835560525184816927695284116119512);
+    pub const BONES_APPLIED: DiagnosticId =
+        DiagnosticId::from_u128(307330712849517190635081241119917416284);
+    pub const TRACKS_SAMPLED: DiagnosticId =
+        DiagnosticId::from_u128(118066317904084684258403173499315270273);
+    pub const TRACKS_SKIPPED: DiagnosticId =
+        DiagnosticId::from_u128(288436050859683524108302164148193701636);
+    pub const EVALUATION_TIME: DiagnosticId =
+        DiagnosticId::from_u128(86523739758280606332577516196959159286);
+    pub const APPLICATION_TIME: DiagnosticId =
+        DiagnosticId::from_u128(312390492710320789523379693169662592656);
+
+    /// Registers a [`Diagnostic`] for each of the counters.
+    pub fn register(diagnostics: &mut Diagnostics) {
+        let counters = [
+            (Self::GRAPHS_EVALUATED, "animation_graphs_evaluated"),
+            (Self::NODES_VISITED, "animation_nodes_visited"),
+            (Self::ACTIVE_CLIPS, "animation_active_clips"),
+            (Self::BONES_APPLIED, "animation_bones_applied"),
+            (Self::TRACKS_SAMPLED, "animation_tracks_sampled"),
+            (Self::TRACKS_SKIPPED, "animation_tracks_skipped"),
+        ];
+        for (id, name) in counters {
+            diagnostics.add(Diagnostic::new(id, name, 20));
+        }
+        diagnostics.add(
+            Diagnostic::new(Self::EVALUATION_TIME, "animation_evaluation_time", 20)
+                .with_suffix("s"),
+        );
+        diagnostics.add(
+            Diagnostic::new(Self::APPLICATION_TIME, "animation_application_time", 20)
+                .with_suffix("s"),
+        );
+    }
+}
+
+/// Resets the [`AnimationDiagnostics`] at the start of the frame.
+pub fn reset_animation_diagnostics_system(mut diagnostics: ResMut<AnimationDiagnostics>) {
+    *diagnostics = AnimationDiagnostics::default();
+}
+
+/// Records the [`AnimationDiagnostics`] of the frame as measurements of the
+/// diagnostics registered by [`AnimationDiagnostics::register`].
+#[cfg(feature = "diagnostics")]
+pub fn record_animation_diagnostics_system(
+    counters: Res<AnimationDiagnostics>,
+    mut diagnostics: ResMut<Diagnostics>,
+) {
+    let measurements = [
+        (
+            AnimationDiagnostics::GRAPHS_EVALUATED,
+            counters.graphs_evaluated,
+        ),
+        (AnimationDiagnostics::NODES_VISITED, counters.nodes_visited),
+        (AnimationDiagnostics::ACTIVE_CLIPS, counters.active_clips),
+        (AnimationDiagnostics::BONES_APPLIED, counters.bones_applied),
+        (
+            AnimationDiagnostics::TRACKS_SAMPLED,
+            counters.tracks_sampled,
+        ),
+        (
+            AnimationDiagnostics::TRACKS_SKIPPED,
+            counters.tracks_skipped,
+        ),
+    ];
+    for (id, value) in measurements {
+        diagnostics.add_measurement(id, value as f64);
+    }
+    diagnostics.add_measurement(
+        AnimationDiagnostics::EVALUATION_TIME,
+        counters.evaluation_time.as_secs_f64(),
+    );
+    diagnostics.add_measurement(
+        AnimationDiagnostics::APPLICATION_TIME,
+        counters.application_time.as_secs_f64(),
+    );
+}
@@ -1,59 +1,679 @@
-use crate::graph::{track::BoneId, AnimationGraph};
-use bevy_ecs::prelude::*;
-use bevy_log::warn;
-use bevy_reflect::{TypeRegistry, TypeRegistryArc};
+use crate::{
+    clip::{validate_component, AnimationClip, ClipValidationError, ClipValidationErrorKind},
+    diagnostics::AnimationDiagnostics,
+    graph::{
+        hierarchy,
+        track::{Bone, BoneId, Track},
+        AnimationGraph, GraphState, OutputMode, TrackError, WriteMask,
+    },
+    path::{AccessPath, AccessTarget},
+    target, WorldResources,
+};
+use bevy_ecs::{prelude::*, system::Command};
+use bevy_log::{info_span, warn};
+use bevy_reflect::{Reflect, TypeRegistry, TypeRegistryArc};
 use bevy_tasks::ComputeTaskPool;
-use dashmap::DashSet;
-use std::ops::Deref;
+use bevy_utils::HashSet;
+use smallvec::{smallvec, SmallVec};
+use std::{
+    cell::Cell,
+    marker::PhantomData,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-const BINDING_BATCCH_SIZE: usize = 8;
+const BINDING_BATCH_SIZE: usize = 8;
 
+/// Marks an entity as being animated by a bone in an [`AnimationGraph`].
+///
+/// Malformed hierarchies can lead several of a graph's bones to the same
+/// entity. They then share a single binding, and each of their tracks is
+/// written once.
 #[derive(Component)]
-pub(crate) struct BoneBinding {
+pub struct BoneBinding {
     pub(super) graph: Entity,
-    pub(super) bone_id: BoneId,
+    pub(super) graph_nonce: u32,
+    pub(super) bone_ids: SmallVec<[BoneId; 1]>,
 }
 
-// This MUST be used as an exclusive system for aliasing safety.
-// The immutable reference to the a World is used mutably in an unsafe
-// manner if simultaneous World access is allowed.
-pub(crate) fn animate_entities_system(
+impl BoneBinding {
+    /// The entity of the [`AnimationGraph`] animating the bound entity.
+    pub fn graph(&self) -> Entity {
+        self.graph
+    }
+}
+
+/// The bones a bound entity is animated by.
+pub(super) type BoundBones<'a> = SmallVec<[&'a Bone; 1]>;
+
+/// Caps the time [`animate_entities_system`] spends writing bound entities
+/// each frame. Without this resource, every binding is written every frame.
+///
+/// Bindings are written in batches, in a stable round-robin order, and once
+/// the budget is spent the remaining batches are carried over to the next
+/// frame. Skipped entities keep the pose they were last written, and their
+/// graphs keep advancing, so they catch up with the current pose when they're
+/// next written. At least one batch is written every frame, however small the
+/// budget.
+///
+/// Graphs with an [`AnimationPriority`] are written every frame, and don't
+/// count against the budget. Typed components, such as [`Transform`]s applied
+/// by a [`TypedAnimationPlugin`], are always written in full.
+///
+/// Budgeted bindings are written whole, ignoring the graph's
+/// [`track_split_threshold`]. The first batch is written in parallel with the
+/// unbudgeted bindings, and the rest one batch at a time afterwards, so the
+/// budget can be checked between them.
+///
+/// [`Transform`]: bevy_transform::prelude::Transform
+/// [`TypedAnimationPlugin`]: crate::graph::typed::TypedAnimationPlugin
+/// [`track_split_threshold`]: AnimationGraph::set_track_split_threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationBudget {
+    /// The time in microseconds that may be spent writing bindings, measured
+    /// from the start of [`animate_entities_system`].
+    pub max_micros_per_frame: u64,
+}
+
+/// Marks an [`AnimationGraph`] whose bound entities are written every frame,
+/// regardless of the [`AnimationBudget`].
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct AnimationPriority;
+
+/// How far [`animate_entities_system`] got through the bindings under an
+/// [`AnimationBudget`].
+#[derive(Default)]
+pub struct BudgetCursor {
+    /// The first binding to write next frame. Bindings before it are written
+    /// after the ones from it onwards.
+    next: Option<Entity>,
+    /// The entities skipped over budget, which are written next frame even
+    /// if their graph doesn't change again.
+    carried: HashSet<Entity>,
+}
+
+/// Removes the [`BoneBinding`]s of [`AnimationGraph`]s that were removed or
+/// despawned.
+///
+/// Removals are only visible until the end of the frame, so this should run
+/// late in the frame. Any bindings that are missed are removed by
+/// [`animate_entities_system`] when they fail to apply.
+pub fn cleanup_bindings_system(
+    removed: RemovedComponents<AnimationGraph>,
+    bindings: Query<(Entity, &BoneBinding)>,
+    mut removed_graphs: Local<HashSet<Entity>>,
+    mut commands: Commands,
+) {
+    removed_graphs.extend(removed.iter());
+    if removed_graphs.is_empty() {
+        return;
+    }
+    for (entity, binding) in bindings.iter() {
+        if removed_graphs.contains(&binding.graph) {
+            commands.entity(entity).remove::<BoneBinding>();
+        }
+    }
+    removed_graphs.clear();
+}
+
+/// Applies the evaluated values of all changed [`AnimationGraph`]s to their bound
+/// entities. Graphs that skipped their update or have a total weight of 0 are
+/// not applied.
+///
+/// Tracks targeting resources and assets are blended along with the rest,
+/// and written once the components are, when the system's commands are
+/// applied.
+///
+/// This MUST be added as an exclusive system, and should run after the graphs
+/// have been evaluated and bound.
+//
+// This MUST be used as an exclusive system for aliasing safety: the shared
+// World reference is used to mutate components from multiple threads. The
+// bindings are first resolved into a list of entities to write, and only that
+// list is processed in parallel. See the safety comment below for why this
+// doesn't alias.
+#[allow(clippy::too_many_arguments)]
+pub fn animate_entities_system(
     world: &World,
     entities: Query<(Entity, &BoneBinding)>,
     graphs: Query<(&AnimationGraph, ChangeTrackers<AnimationGraph>)>,
+    priorities: Query<&AnimationPriority>,
+    budget: Option<Res<AnimationBudget>>,
+    mut cursor: Local<BudgetCursor>,
     type_registry: Res<TypeRegistryArc>,
     task_pool: Res<ComputeTaskPool>,
-    dead: Local<DashSet<Entity>>,
     mut commands: Commands,
 ) {
-    debug_assert!(dead.is_empty());
-
+    let _span = info_span!("animate_entities").entered();
+    let start = Instant::now();
     if graphs.is_empty() {
         for (entity, _) in entities.iter() {
             commands.entity(entity).remove::<BoneBinding>();
         }
         return;
     }
+    let deadline = match &budget {
+        Some(budget) => start + Duration::from_micros(budget.max_micros_per_frame),
+        None => {
+            *cursor = BudgetCursor::default();
+            // Nothing is budgeted, so the deadline is never checked.
+            start
+        }
+    };
+
+    let mut dead = Vec::new();
+    // Each bound entity, and whether it counts against the budget.
+    let mut items = Vec::new();
+    for (entity, binding) in entities.iter() {
+        let carried = cursor.carried.contains(&entity);
+        match bound_bones(entity, binding, &graphs, carried) {
+            Ok(Some((graph, bones))) => {
+                let item = BoundEntity {
+                    entity,
+                    graph,
+                    bones,
+                };
+                let budgeted = budget.is_some() && priorities.get(binding.graph).is_err();
+                items.push((item, budgeted));
+            }
+            Ok(None) => {}
+            Err(_) => dead.push(entity),
+        }
+    }
+    // SAFE: This system is exclusive, so nothing else accesses the World
+    // while it runs, and the writers are only used by this system. The only
+    // components read while they exist are the AnimationGraphs and
+    // BoneBindings in the queries. Neither is registered with
+    // ReflectComponent, so the writers can't write them.
+    let writers = unsafe { EntityWriter::disjoint(world, items, |(item, _)| item.entity) };
+    let mut work = Vec::new();
+    let mut budgeted = Vec::new();
+    let mut split = Vec::new();
+    for ((item, is_budgeted), writer) in writers {
+        if is_budgeted {
+            budgeted.push((item, writer));
+        } else if matches!(
+            item.graph.track_split_threshold,
+            Some(threshold) if item.track_count() > threshold
+        ) {
+            split.push((item, writer));
+        } else {
+            work.push((item, writer));
+        }
+    }
+    // Continue from where the budget ran out last frame.
+    if let Some(next) = cursor.next {
+        let skipped = budgeted.partition_point(|(item, _)| item.entity < next);
+        budgeted.rotate_left(skipped);
+    }
+
+    // Everything the writes read besides the graphs is fetched up front.
+    let type_registry = type_registry.read();
+    let type_registry = &*type_registry;
+    let resources = WorldResources::new(world);
+    // The first budgeted batch is always written, so every binding is
+    // eventually written however small the budget.
+    let batch_size = even_batch_size(budgeted.len());
+    let first_len = batch_size.min(budgeted.len());
+    let (first, deferred) = budgeted.split_at_mut(first_len);
+    let results = task_pool.scope(|scope| {
+        let batches = work
+            .chunks_mut(BINDING_BATCH_SIZE)
+            .chain(Some(first).filter(|batch| !batch.is_empty()));
+        for batch in batches {
+            scope.spawn(async move {
+                let mut failed = Vec::new();
+                let mut stats = ApplyStats::default();
+                let mut targets = Vec::new();
+                write_batch(
+                    batch,
+                    type_registry,
+                    resources,
+                    &mut failed,
+                    &mut stats,
+                    &mut targets,
+                );
+                (failed, stats, targets)
+            });
+        }
+    });
+    let mut stats = ApplyStats::default();
+    let mut failed = Vec::new();
+    let mut targets = Vec::new();
+    for (batch_failed, batch_stats, batch_targets) in results {
+        failed.extend(batch_failed);
+        stats.add(batch_stats);
+        targets.extend(batch_targets);
+    }
+
+    // The other budgeted batches are written one at a time, checking the
+    // deadline before each, so the budget is overrun by at most one batch.
+    // The bindings left once it passes are written first next frame.
+    let mut written = 0;
+    for batch in deferred.chunks_mut(batch_size) {
+        if Instant::now() >= deadline {
+            break;
+        }
+        write_batch(
+            batch,
+            type_registry,
+            resources,
+            &mut failed,
+            &mut stats,
+            &mut targets,
+        );
+        written += batch.len();
+    }
+    let skipped = deferred[written..].iter().map(|(item, _)| item.entity);
+    cursor.next = skipped.clone().next();
+    cursor.carried.clear();
+    cursor.carried.extend(skipped);
+
+    // Bones with too many tracks for a single task are blended in parallel
+    // batches, and then written one entity at a time.
+    let mut failed_split = Vec::new();
+    for (item, writer) in split.iter_mut() {
+        let batch_size = item.graph.track_split_threshold.unwrap_or(usize::MAX);
+        let staged = stage_tracks(item, batch_size, &task_pool);
+        let result = animate_entity(
+            item,
+            writer,
+            type_registry,
+            resources,
+            Some(&staged),
+            &mut stats,
+            &mut targets,
+        );
+        if result.is_err() {
+            failed_split.push(item.entity);
+        }
+    }
+
+    for entity in dead.into_iter().chain(failed).chain(failed_split) {
+        commands.entity(entity).remove::<BoneBinding>();
+    }
+    // The World can't be borrowed mutably while the entities are written, so
+    // resources and assets are written, and the counters recorded, when the
+    // commands are applied.
+    commands.add(RecordApplication {
+        stats,
+        time: start.elapsed(),
+        targets,
+    });
+}
+
+/// Writes a batch of bound entities, collecting the entities that failed to
+/// apply in `failed`. See [`animate_entity`].
+fn write_batch(
+    batch: &mut [(BoundEntity, EntityWriter)],
+    type_registry: &TypeRegistry,
+    resources: WorldResources,
+    failed: &mut Vec<Entity>,
+    stats: &mut ApplyStats,
+    targets: &mut Vec<TargetWrite>,
+) {
+    for (item, writer) in batch {
+        let result = animate_entity(item, writer, type_registry, resources, None, stats, targets);
+        if result.is_err() {
+            failed.push(item.entity);
+        }
+    }
+}
+
+/// The size of the batches to split `len` budgeted bindings into. The batches
+/// are at most [`BINDING_BATCH_SIZE`], and evenly sized so the budget is spent
+/// in equal steps.
+fn even_batch_size(len: usize) -> usize {
+    let batches = len.saturating_sub(1) / BINDING_BATCH_SIZE + 1;
+    len.saturating_sub(1) / batches + 1
+}
+
+/// Counters collected while writing bound entities. See
+/// [`AnimationDiagnostics`].
+#[derive(Debug, Default, Clone, Copy)]
+struct ApplyStats {
+    bones_applied: u32,
+    tracks_sampled: u32,
+    tracks_skipped: u32,
+}
+
+impl ApplyStats {
+    fn add(&mut self, other: ApplyStats) {
+        self.bones_applied += other.bones_applied;
+        self.tracks_sampled += other.tracks_sampled;
+        self.tracks_skipped += other.tracks_skipped;
+    }
+}
+
+/// Writes the resources and assets animated by [`animate_entities_system`],
+/// and adds its counters to the [`AnimationDiagnostics`].
+struct RecordApplication {
+    stats: ApplyStats,
+    time: Duration,
+    targets: Vec<TargetWrite>,
+}
+
+impl Command for RecordApplication {
+    fn write(mut self, world: &mut World) {
+        let start = Instant::now();
+        self.stats.add(write_targets(world, self.targets));
+        self.time += start.elapsed();
+        if let Some(mut diagnostics) = world.get_resource_mut::<AnimationDiagnostics>() {
+            diagnostics.bones_applied += self.stats.bones_applied;
+            diagnostics.tracks_sampled += self.stats.tracks_sampled;
+            diagnostics.tracks_skipped += self.stats.tracks_skipped;
+            diagnostics.application_time += self.time;
+        }
+    }
+}
+
+/// The blended value of a track targeting a resource or an asset, to be
+/// written once the components are. See [`AccessTarget`].
+///
+/// [`AccessTarget`]: crate::path::AccessTarget
+struct TargetWrite {
+    entity: Entity,
+    property: AccessPath,
+    track: Arc<dyn Track>,
+    value: Box<dyn Reflect>,
+}
+
+/// Writes the blended values of tracks targeting resources and assets, one at
+/// a time, and counts the written tracks.
+fn write_targets(world: &mut World, targets: Vec<TargetWrite>) -> ApplyStats {
+    let mut stats = ApplyStats::default();
+    if targets.is_empty() {
+        return stats;
+    }
+    let type_registry = match world.get_resource::<TypeRegistryArc>() {
+        Some(type_registry) => type_registry.clone(),
+        None => return stats,
+    };
+    let type_registry = type_registry.read();
+    for TargetWrite {
+        entity,
+        property,
+        track,
+        value,
+    } in targets
+    {
+        let mut result = None;
+        let found = target::write_target(
+            &type_registry,
+            world,
+            entity,
+            &property,
+            &mut |output, resources| {
+                result = property
+                    .field_path()
+                    .field_mut(output)
+                    .ok()
+                    .map(|field| track.write_blended(value.as_ref(), field, resources));
+            },
+        );
+        match result {
+            Some(Ok(written)) => {
+                stats.tracks_sampled += 1;
+                stats.tracks_skipped += !written as u32;
+            }
+            Some(Err(_)) => {}
+            None if !found => warn!(
+                "Failed to animate '{}'. The resource or asset does not exist.",
+                property
+            ),
+            None => warn!(
+                "Failed to animate '{}'. '{}' has no field {}.",
+                property,
+                property.component_name(),
+                property.field_path(),
+            ),
+        }
+    }
+    stats
+}
 
+/// Writes the evaluated values of a graph to its bound entities immediately,
+/// including its typed tracks. Follows the same rules as
+/// [`animate_entities_system`], except that the graph doesn't need to have
+/// changed, or to be in the world.
+pub(crate) fn apply_graph(world: &mut World, graph: &AnimationGraph) {
+    if graph.output_mode == OutputMode::Buffer || graph.update_skipped || graph.total_weight == 0.0
+    {
+        return;
+    }
+    let type_registry = match world.get_resource::<TypeRegistryArc>() {
+        Some(type_registry) => type_registry.clone(),
+        None => return,
+    };
     let type_registry = type_registry.read();
-    entities.par_for_each(&*task_pool, BINDING_BATCCH_SIZE, |(entity, binding)| {
-        if animate_entity(entity, binding, &graphs, &type_registry, world).is_ok() {
-            dead.insert(entity);
+    let mut targets = Vec::new();
+    for bone in graph.clips.bones() {
+        let entity = match bone.entity() {
+            Some(entity) if bone.write_mask != WriteMask::None => entity,
+            _ => continue,
+        };
+        let item = BoundEntity {
+            entity,
+            graph,
+            bones: smallvec![bone],
+        };
+        // Entities that fail to apply keep their binding until
+        // animate_entities_system removes it.
+        let (mut writer, resources) = EntityWriter::exclusive(world, entity);
+        let _ = animate_entity(
+            &item,
+            &mut writer,
+            &type_registry,
+            resources,
+            None,
+            &mut ApplyStats::default(),
+            &mut targets,
+        );
+        for track in bone.tracks().filter(|track| track.track.is_typed()) {
+            track
+                .track
+                .apply_typed(world, entity, &graph.state, &bone.write_mask);
+        }
+    }
+    write_targets(world, targets);
+}
+
+/// Samples every curve of `clip` at `time`, in seconds, and writes the values
+/// to the entities beneath `root` immediately, as if the clip were played on
+/// its own by a graph on `root`.
+///
+/// No graph, bindings or systems are involved: entity paths are resolved by
+/// walking the hierarchy beneath `root`, and each property is written through
+/// reflection one at a time. Useful for previews in worlds that don't run the
+/// animation systems, such as an editor's preview panel, and as a reference
+/// to test the full pipeline against.
+///
+/// Returns the properties that couldn't be written, and why. The other
+/// properties are still written.
+pub fn apply_clip_to_world(
+    world: &mut World,
+    root: Entity,
+    clip: &AnimationClip,
+    time: f32,
+    registry: &TypeRegistry,
+) -> Vec<ClipValidationError> {
+    // Sample the clip at full weight, with the tracks the graph would build.
+    let mut state = GraphState::default();
+    let clip_id = state.add_clip(clip.duration()).unwrap();
+    state.set_additive(clip_id, clip.is_additive());
+    state.set_time(clip_id, time);
+    state.add_weight(clip_id, 1.0);
+    state.update_single_clip();
+
+    let mut errors = Vec::new();
+    for (path, curve) in clip.curves.iter() {
+        let track = curve.into_track(clip_id);
+        let result = hierarchy::find_bone_in_world(world, root, path.entity())
+            .ok_or(ClipValidationErrorKind::MissingEntity)
+            .and_then(|entity| {
+                validate_component(registry, path.access())?;
+                write_clip_track(world, entity, registry, path.access(), &*track, &state)
+            });
+        if let Err(kind) = result {
+            errors.push(ClipValidationError {
+                path: (**path).clone(),
+                kind,
+            });
         }
+    }
+    errors
+}
+
+/// Writes a track sampled from a single clip to the property at `access`.
+fn write_clip_track(
+    world: &mut World,
+    entity: Entity,
+    registry: &TypeRegistry,
+    access: &AccessPath,
+    track: &dyn Track,
+    state: &GraphState,
+) -> Result<(), ClipValidationErrorKind> {
+    let write = |output: &mut dyn Reflect, resources: WorldResources<'_>| {
+        let field = access
+            .field_path()
+            .field_mut(output)
+            .map_err(|err| ClipValidationErrorKind::InvalidField(err.to_string()))?;
+        match track.blend_via_reflect(state, field, resources) {
+            Ok(_) => Ok(()),
+            Err(TrackError::IncorrectType { found, .. }) => {
+                Err(ClipValidationErrorKind::MismatchedType {
+                    field: found,
+                    curve: track.value_type_name(),
+                })
+            }
+            Err(err) => Err(ClipValidationErrorKind::InvalidField(err.to_string())),
+        }
+    };
+    let kind = match access.target() {
+        AccessTarget::Component => {
+            let reflect = registry
+                .get(access.component_type_id())
+                .and_then(|registration| registration.data::<ReflectComponent>())
+                .ok_or(ClipValidationErrorKind::MissingComponent)?;
+            let (mut writer, resources) = EntityWriter::exclusive(world, entity);
+            return writer
+                .reflect_mut(reflect, |component| write(component, resources))
+                .ok_or(ClipValidationErrorKind::MissingComponent)?;
+        }
+        AccessTarget::Resource => ClipValidationErrorKind::MissingResource,
+        AccessTarget::Asset(_) => ClipValidationErrorKind::MissingAsset,
+    };
+    let mut result = Err(kind);
+    target::write_target(registry, world, entity, access, &mut |output, resources| {
+        result = write(output, resources);
     });
+    result
+}
+
+/// A bound entity to write this frame, along with the bones it's bound to.
+struct BoundEntity<'a> {
+    entity: Entity,
+    graph: &'a AnimationGraph,
+    bones: BoundBones<'a>,
+}
+
+impl<'a> BoundEntity<'a> {
+    fn track_count(&self) -> usize {
+        self.bones.iter().map(|bone| bone.track_count()).sum()
+    }
+}
+
+/// Mutable access to the components of a single entity, through a [`World`]
+/// that is only borrowed immutably. This lets several entities be written
+/// from parallel tasks while resources are read through [`WorldResources`].
+///
+/// Writers are only created for distinct entities, and can't be shared
+/// between threads, so each component has at most one mutable reference at
+/// a time.
+pub(super) struct EntityWriter<'w> {
+    world: &'w World,
+    entity: Entity,
+    // Not Sync, so that a writer is only used by one thread at a time.
+    marker: PhantomData<Cell<()>>,
+}
 
-    if !dead.is_empty() {
-        for entity in dead.iter() {
-            commands.entity(*entity).remove::<BoneBinding>();
+impl<'w> EntityWriter<'w> {
+    /// Creates a writer for an entity of a World borrowed mutably, along with
+    /// read access to its resources.
+    pub fn exclusive(world: &'w mut World, entity: Entity) -> (Self, WorldResources<'w>) {
+        let world = &*world;
+        let writer = Self {
+            world,
+            entity,
+            marker: PhantomData,
+        };
+        (writer, WorldResources::new(world))
+    }
+
+    /// Pairs each item with a writer for its entity. Items are sorted by
+    /// entity, and only the first item for each entity is kept, so no two
+    /// writers share an entity.
+    ///
+    /// # Safety
+    /// While the writers exist, nothing else may mutate the World, or access
+    /// any component the writers write. Components that are never written
+    /// through the writers may still be read elsewhere.
+    pub unsafe fn disjoint<T>(
+        world: &'w World,
+        items: impl IntoIterator<Item = T>,
+        entity: impl Fn(&T) -> Entity,
+    ) -> Vec<(T, Self)> {
+        let mut items: Vec<_> = items.into_iter().collect();
+        // Stable, so the first of several items for an entity is kept.
+        items.sort_by_key(|item| entity(item));
+        items.dedup_by_key(|item| entity(item));
+        items
+            .into_iter()
+            .map(|item| {
+                let writer = Self {
+                    world,
+                    entity: entity(&item),
+                    marker: PhantomData,
+                };
+                (item, writer)
+            })
+            .collect()
+    }
+
+    pub fn get<C: Component>(&self) -> Option<&C> {
+        self.world.get::<C>(self.entity)
+    }
+
+    pub fn get_mut<C: Component>(&mut self) -> Option<Mut<'_, C>> {
+        // SAFE: No other writer has this entity, and the mutable borrow of
+        // the writer ensures this is the only reference it hands out.
+        unsafe {
+            self.world.get_entity(self.entity)?.get_unchecked_mut::<C>(
+                self.world.last_change_tick(),
+                self.world.read_change_tick(),
+            )
         }
+    }
 
-        dead.clear();
+    /// Calls `write` with the component `reflect` is for, if the entity has
+    /// it.
+    pub fn reflect_mut<R>(
+        &mut self,
+        reflect: &ReflectComponent,
+        write: impl FnOnce(&mut dyn Reflect) -> R,
+    ) -> Option<R> {
+        // SAFE: As in `get_mut`.
+        let mut component =
+            unsafe { reflect.reflect_component_unchecked_mut(self.world, self.entity) }?;
+        Some(write(component.as_mut()))
     }
 }
 
-enum AnimatePropertyError {
+pub(super) enum AnimatePropertyError {
     /// The graph entity no longer has a AnimationGraph or was despawned.
     InvalidAnimationGraph,
+    /// The graph that created the binding was replaced by another graph.
+    StaleBinding,
     /// The animation graph no longer has corresponding bone.
     InvalidBoundBone,
     /// The binding is no longer valid as the graph has bound to another bone.
@@ -62,57 +682,171 @@ enum AnimatePropertyError {
     NoValidProperties,
 }
 
-fn animate_entity(
+/// Gets the graph and bones a bound entity is animated by. Returns `None` if
+/// nothing should be written to the entity this frame, or an error if the
+/// binding is no longer valid. Bones that are masked out are left out.
+pub(super) fn applicable_bones<'a>(
     entity: Entity,
     binding: &BoneBinding,
-    graphs: &Query<(&AnimationGraph, ChangeTrackers<AnimationGraph>)>,
-    type_registry: &TypeRegistry,
-    world: &World,
-) -> Result<(), AnimatePropertyError> {
+    graphs: &'a Query<(&AnimationGraph, ChangeTrackers<AnimationGraph>)>,
+) -> Result<Option<(&'a AnimationGraph, BoundBones<'a>)>, AnimatePropertyError> {
+    bound_bones(entity, binding, graphs, false)
+}
+
+/// Like [`applicable_bones`], but graphs that haven't changed are also written
+/// if `unchanged` is set, such as for entities skipped over budget.
+fn bound_bones<'a>(
+    entity: Entity,
+    binding: &BoneBinding,
+    graphs: &'a Query<(&AnimationGraph, ChangeTrackers<AnimationGraph>)>,
+    unchanged: bool,
+) -> Result<Option<(&'a AnimationGraph, BoundBones<'a>)>, AnimatePropertyError> {
     let (graph, tracker) = graphs
         .get(binding.graph)
         .map_err(|_| AnimatePropertyError::InvalidAnimationGraph)?;
-    let bone = graph
-        .get_bone(binding.bone_id)
-        .ok_or(AnimatePropertyError::InvalidBoundBone)?;
-    if bone.entity() != Some(entity) {
-        return Err(AnimatePropertyError::BoneNoLongerBound);
-    } else if !tracker.is_changed() {
+    if graph.nonce != binding.graph_nonce {
+        return Err(AnimatePropertyError::StaleBinding);
+    } else if graph.output_mode == OutputMode::Buffer {
+        // The graph's values are written to its AnimatedPose instead.
+        return Ok(None);
+    }
+    let mut bones = BoundBones::new();
+    for bone_id in binding.bone_ids.iter() {
+        let bone = graph
+            .get_bone(*bone_id)
+            .ok_or(AnimatePropertyError::InvalidBoundBone)?;
+        if bone.entity() != Some(entity) {
+            return Err(AnimatePropertyError::BoneNoLongerBound);
+        } else if bone.write_mask != WriteMask::None {
+            bones.push(bone);
+        }
+    }
+    if !tracker.is_changed() && !unchanged {
         // No need to update the components if the upstream graph hasn't changed.
-        return Ok(());
+        return Ok(None);
+    } else if graph.update_skipped || graph.total_weight == 0.0 {
+        // The graph is waiting on its update interval, or has nothing to blend.
+        return Ok(None);
+    } else if bones.is_empty() {
+        // The entity is owned by something else for now, but stays bound.
+        return Ok(None);
     }
+    Ok(Some((graph, bones)))
+}
+
+/// Blends the tracks of the bones of an entity that are written through
+/// reflection in parallel, in batches of `batch_size` tracks. The values are in
+/// the order of each bone's [`Bone::tracks`], and the other tracks are left
+/// empty.
+fn stage_tracks(
+    item: &BoundEntity,
+    batch_size: usize,
+    task_pool: &ComputeTaskPool,
+) -> Vec<Option<Box<dyn Reflect>>> {
+    let tracks: Vec<_> = item
+        .bones
+        .iter()
+        .flat_map(|bone| bone.tracks().map(move |track| (*bone, track)))
+        .collect();
+    let graph = item.graph;
+    task_pool
+        .scope(|scope| {
+            for batch in tracks.chunks(batch_size) {
+                scope.spawn(async move {
+                    batch
+                        .iter()
+                        .map(|(bone, track)| {
+                            let written =
+                                !track.track.is_typed() && graph.writes(bone, track.property);
+                            written.then(|| track.track.blend_boxed(&graph.state))
+                        })
+                        .collect::<Vec<_>>()
+                });
+            }
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Writes the blended values of the bones of an entity to it, or the values in
+/// `staged` when they were blended ahead of time by [`stage_tracks`]. The
+/// written bones and tracks are counted in `stats`. The values of tracks
+/// targeting resources and assets are added to `targets` instead.
+fn animate_entity(
+    item: &BoundEntity,
+    writer: &mut EntityWriter,
+    type_registry: &TypeRegistry,
+    resources: WorldResources,
+    staged: Option<&[Option<Box<dyn Reflect>>]>,
+    stats: &mut ApplyStats,
+    targets: &mut Vec<TargetWrite>,
+) -> Result<(), AnimatePropertyError> {
+    let BoundEntity {
+        entity,
+        graph,
+        ref bones,
+    } = *item;
 
     let mut success = false;
-    for track in bone.tracks() {
+    let mut track_stats = graph.track_stats.as_ref().map(|stats| stats.lock());
+    let tracks = bones
+        .iter()
+        .flat_map(|bone| bone.tracks().map(move |track| (*bone, track)));
+    for (idx, (bone, track)) in tracks.enumerate() {
         let property = track.property;
-        let component = type_registry
+        // Typed tracks are applied by their own system, and are masked per field.
+        if track.track.is_typed() || !graph.writes(bone, property) {
+            success = true;
+            continue;
+        }
+        // Resources and assets may be shared with other entities, so they
+        // can't be written from here.
+        if !property.is_component() {
+            let value = match staged.and_then(|staged| staged[idx].as_deref()) {
+                Some(value) => value.clone_value(),
+                None => track.track.blend_boxed(&graph.state),
+            };
+            if let Some(track) = bone.shared_track(property) {
+                targets.push(TargetWrite {
+                    entity,
+                    property: property.clone(),
+                    track,
+                    value,
+                });
+            }
+            success = true;
+            continue;
+        }
+        let reflect = type_registry
             .get(property.component_type_id())
-            .and_then(|registration| registration.data::<ReflectComponent>())
-            // SAFE: Each entity is only accessed by one thread at a given time in
-            // an exclusive system. Only one component on every is accessed at a
-            // given time.
-            //
-            // The blend_via_reflect call below will cause simultaneous read-only
-            // access of Resources in a read-only fashion. There are no aliasing
-            // issues as this mutation only affects components.
-            .and_then(|reflect| unsafe { reflect.reflect_component_unchecked_mut(world, entity) });
-
-        if let Some(mut comp) = component {
-            if let Ok(field) = property.field_path().field_mut(comp.as_mut()) {
-                // SAFE: This access is read-only and is required to only access
-                // resources. This cannot cause race conditions as only non-Resource
-                // components are mutated.
-                success |= unsafe {
-                    track
+            .and_then(|registration| registration.data::<ReflectComponent>());
+        let written = reflect.and_then(|reflect| {
+            writer.reflect_mut(reflect, |comp| {
+                let field = match property.field_path().field_mut(comp) {
+                    Ok(field) => field,
+                    Err(_) => return,
+                };
+                let result = match staged.and_then(|staged| staged[idx].as_deref()) {
+                    Some(value) => track.track.write_blended(value, field, resources),
+                    None => track
                         .track
-                        .blend_via_reflect(&graph.state, field, world)
-                        .is_ok()
+                        .blend_via_reflect(&graph.state, field, resources),
                 };
-            }
-        } else {
+                if let Ok(written) = result {
+                    success = true;
+                    stats.tracks_sampled += 1;
+                    stats.tracks_skipped += !written as u32;
+                    if let Some(track_stats) = &mut track_stats {
+                        track_stats.record(&bone.path, property, track.track, field, written);
+                    }
+                }
+            })
+        });
+        if written.is_none() {
             warn!(
                 "Failed to animate '{}'. Struct '{}' has no field {}.",
-                property.deref(),
+                property,
                 property.component_name(),
                 property.field_path(),
             );
@@ -120,6 +854,7 @@ fn animate_entity(
     }
 
     if success {
+        stats.bones_applied += bones.len() as u32;
         Ok(())
     } else {
         Err(AnimatePropertyError::NoValidProperties)
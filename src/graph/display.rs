@@ -0,0 +1,181 @@
+use crate::graph::{AnimationGraph, Node, NodeId};
+use std::fmt::{self, Write};
+
+impl AnimationGraph {
+    /// Renders the graph in the Graphviz DOT format. Disconnected inputs are
+    /// drawn with dashed edges.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        for (node_id, _) in self.nodes.iter() {
+            let name = self.node_name(node_id);
+            // Writing to a String cannot fail.
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\"];",
+                node_id,
+                escape_dot(&name)
+            );
+        }
+        for (node_id, node) in self.nodes.iter() {
+            for input in node.inputs() {
+                let style = if input.is_connected() {
+                    ""
+                } else {
+                    ", style=dashed"
+                };
+                let _ = writeln!(
+                    dot,
+                    "    \"{}\" -> \"{}\" [label=\"{}\"{}];",
+                    node_id,
+                    input.node_id(),
+                    input.weight(),
+                    style
+                );
+            }
+        }
+        dot.push('}');
+        dot
+    }
+
+    fn node_name(&self, node_id: NodeId) -> String {
+        let kind = match self.nodes.get(node_id) {
+            Some(Node::Blend { .. }) => " (blend)",
+            Some(Node::Snapshot { .. }) => " (snapshot)",
+            Some(Node::Random { .. }) => " (random)",
+            _ => "",
+        };
+        match self.node_label(node_id) {
+            Some(label) => format!("{}{}", label, kind),
+            None => format!("{}{}", node_id, kind),
+        }
+    }
+
+    fn fmt_inputs(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        node_id: NodeId,
+        prefix: &mut String,
+        ancestors: &mut Vec<NodeId>,
+    ) -> fmt::Result {
+        let inputs: Vec<_> = match self.nodes.get(node_id) {
+            Some(node) => node.inputs().collect(),
+            None => return Ok(()),
+        };
+        ancestors.push(node_id);
+        for (idx, input) in inputs.iter().enumerate() {
+            let last = idx + 1 == inputs.len();
+            let branch = if last { "└─" } else { "├─" };
+            write!(
+                f,
+                "\n{}{} {} [w={}",
+                prefix,
+                branch,
+                self.node_name(input.node_id()),
+                input.weight()
+            )?;
+            if !input.is_connected() {
+                f.write_str(", disconnected")?;
+            }
+            f.write_str("]")?;
+            // Guard against cycles, which would otherwise recurse forever.
+            if ancestors.contains(&input.node_id()) {
+                f.write_str(" (cycle)")?;
+                continue;
+            }
+            let len = prefix.len();
+            prefix.push_str(if last { "   " } else { "│  " });
+            self.fmt_inputs(f, input.node_id(), prefix, ancestors)?;
+            prefix.truncate(len);
+        }
+        ancestors.pop();
+        Ok(())
+    }
+}
+
+/// Prints the graph as a tree, starting from the root, with the weight and
+/// connection state of every input. Nodes are printed with their label if
+/// they have one, and their numeric ID otherwise.
+///
+/// ```text
+/// ROOT (blend)
+/// └─ locomotion (blend) [w=1]
+///    ├─ walk [w=0.3]
+///    └─ run [w=0.7]
+/// ```
+impl fmt::Display for AnimationGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.node_name(NodeId::ROOT))?;
+        self.fmt_inputs(f, NodeId::ROOT, &mut String::new(), &mut Vec::new())
+    }
+}
+
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::clip::AnimationClip;
+
+    fn graph() -> (AnimationGraph, NodeId, NodeId, NodeId) {
+        let clip = AnimationClip::builder().build();
+        let mut graph = AnimationGraph::new();
+        let walk = graph.add_clip(&clip).unwrap();
+        let run = graph.add_clip(&clip).unwrap();
+        let idle = graph.add_clip(&clip).unwrap();
+        let locomotion = graph
+            .nodes
+            .add(Node::Blend {
+                inputs: Vec::new(),
+                propogate_time: true,
+            })
+            .unwrap();
+        graph.add_input(NodeId::ROOT, locomotion).unwrap();
+        graph.add_input(locomotion, walk).unwrap().set_weight(0.3);
+        graph.add_input(locomotion, run).unwrap().set_weight(0.7);
+        graph.add_input(NodeId::ROOT, idle).unwrap().set_weight(0.0);
+        (graph, locomotion, walk, run)
+    }
+
+    #[test]
+    pub fn test_display_labeled_graph() {
+        let (mut graph, locomotion, walk, run) = graph();
+        graph.set_node_label(locomotion, "locomotion").unwrap();
+        graph.set_node_label(walk, "walk").unwrap();
+        graph.set_node_label(run, "run").unwrap();
+        assert_eq!(
+            graph.to_string(),
+            "ROOT (blend)\n\
+             ├─ locomotion (blend) [w=1]\n\
+             │  ├─ walk [w=0.3]\n\
+             │  └─ run [w=0.7]\n\
+             └─ 3 [w=0]"
+        );
+    }
+
+    #[test]
+    pub fn test_display_unlabeled_graph() {
+        let (graph, ..) = graph();
+        assert_eq!(
+            graph.to_string(),
+            "ROOT (blend)\n\
+             ├─ 4 (blend) [w=1]\n\
+             │  ├─ 1 [w=0.3]\n\
+             │  └─ 2 [w=0.7]\n\
+             └─ 3 [w=0]"
+        );
+    }
+
+    #[test]
+    pub fn test_to_dot() {
+        let (mut graph, locomotion, ..) = graph();
+        graph.set_node_label(locomotion, "\"locomotion\"").unwrap();
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.contains("    \"4\" [label=\"\\\"locomotion\\\" (blend)\"];\n"));
+        assert!(dot.contains("    \"ROOT\" -> \"4\" [label=\"1\"];\n"));
+        assert!(dot.contains("    \"4\" -> \"1\" [label=\"0.3\"];\n"));
+        assert!(dot.ends_with('}'));
+    }
+}
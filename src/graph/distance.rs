@@ -0,0 +1,278 @@
+//! Distance matching: finding when the root of a clip has travelled a given
+//! distance, so that stops and turns can be timed to land exactly where the
+//! gameplay needs them. See [`AnimationGraph::time_at_root_distance`].
+
+use crate::{
+    graph::{AnimationGraph, ClipId, NodeId},
+    path::{AccessPath, EntityPath, PropertyPath},
+};
+use bevy_math::{Vec2, Vec3};
+use bevy_transform::prelude::Transform;
+use bevy_utils::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// The default number of times per second root translation curves are
+/// sampled at when building distance tables.
+pub(super) const DEFAULT_RESOLUTION: f32 = 60.0;
+
+/// The horizontal distance travelled by the root of a clip, sampled at
+/// uniform intervals over the full clip. Distances never decrease, so the
+/// table can be inverted with a binary search.
+#[derive(Debug)]
+struct DistanceTable {
+    interval: f32,
+    distances: Vec<f32>,
+}
+
+impl DistanceTable {
+    fn build(duration: f32, resolution: f32, sample: impl Fn(f32) -> Option<Vec3>) -> Option<Self> {
+        let count = (duration * resolution).ceil().max(1.0) as usize;
+        let interval = duration / count as f32;
+        let mut distances = Vec::with_capacity(count + 1);
+        let mut previous = horizontal(sample(0.0)?);
+        let mut distance = 0.0;
+        distances.push(distance);
+        for idx in 1..=count {
+            let position = horizontal(sample(idx as f32 * interval)?);
+            distance += position.distance(previous);
+            distances.push(distance);
+            previous = position;
+        }
+        Some(Self {
+            interval,
+            distances,
+        })
+    }
+
+    /// The distance travelled by `time`, in seconds along the clip.
+    fn distance_at(&self, time: f32) -> f32 {
+        if self.interval <= 0.0 {
+            return 0.0;
+        }
+        let last = self.distances.len() - 1;
+        let position = (time / self.interval).clamp(0.0, last as f32);
+        let idx = (position as usize).min(last.saturating_sub(1));
+        let next = (idx + 1).min(last);
+        let t = position - idx as f32;
+        self.distances[idx] + (self.distances[next] - self.distances[idx]) * t
+    }
+
+    /// The earliest time, in seconds along the clip, by which `distance` has
+    /// been travelled. Returns `None` if the clip never travels that far.
+    fn time_at(&self, distance: f32) -> Option<f32> {
+        if distance > *self.distances.last()? {
+            return None;
+        }
+        let idx = self.distances.partition_point(|&d| d < distance);
+        if idx == 0 {
+            return Some(0.0);
+        }
+        let (from, to) = (self.distances[idx - 1], self.distances[idx]);
+        let t = (distance - from) / (to - from);
+        Some((idx as f32 - 1.0 + t) * self.interval)
+    }
+}
+
+/// Projects a translation onto the ground plane.
+fn horizontal(translation: Vec3) -> Vec2 {
+    Vec2::new(translation.x, translation.z)
+}
+
+/// The distance tables of the clips of a graph, built the first time they
+/// are requested.
+#[derive(Debug, Default)]
+pub(super) struct RootDistances {
+    // Locked as the tables are built while the graph is only borrowed.
+    tables: Mutex<HashMap<ClipId, Arc<DistanceTable>>>,
+}
+
+impl Clone for RootDistances {
+    fn clone(&self) -> Self {
+        Self {
+            tables: Mutex::new(self.tables.lock().unwrap().clone()),
+        }
+    }
+}
+
+impl RootDistances {
+    /// Drops the table of a clip whose curves changed.
+    pub fn invalidate(&mut self, clip: ClipId) {
+        self.tables.get_mut().unwrap().remove(&clip);
+    }
+
+    pub fn clear(&mut self) {
+        self.tables.get_mut().unwrap().clear();
+    }
+}
+
+impl AnimationGraph {
+    /// Finds the time at which the root of a clip node has travelled
+    /// `distance` along the ground, to play distance-matched stops and turns.
+    /// Setting the node's [time](Self::set_time) to the result, and advancing
+    /// it as the character moves, makes the stop land exactly where it needs
+    /// to.
+    ///
+    /// The distance is measured along the horizontal path of the
+    /// translation of the graph's root, ignoring its height, from the start
+    /// of the node's trim range. The result is in seconds relative to the
+    /// start of the trim range, like the node's time. Time warps and
+    /// [normalized](Self::set_normalized_time) times are not accounted for.
+    ///
+    /// The root translation curve is sampled at the graph's
+    /// [resolution](Self::set_root_distance_resolution) the first time the
+    /// clip is queried, and the table is reused until the clip is reloaded.
+    ///
+    /// Returns `None` if `node_id` isn't a clip node, its clip doesn't
+    /// animate the root's translation or is evicted, or its root never
+    /// travels that far within the trim range.
+    pub fn time_at_root_distance(&self, node_id: NodeId, distance: f32) -> Option<f32> {
+        let table = self.root_distance_table(node_id)?;
+        let state = &self.state.clips[self.clip_id(node_id).ok()?.0 as usize];
+        let start = table.distance_at(state.start);
+        let time = table.time_at(start + distance.max(0.0))? - state.start;
+        (time <= state.duration).then(|| time.max(0.0))
+    }
+
+    /// Gets the horizontal distance the root of a clip node has travelled by
+    /// `time`, in seconds relative to the start of the node's trim range. The
+    /// inverse of [`time_at_root_distance`](Self::time_at_root_distance).
+    pub fn distance_at_time(&self, node_id: NodeId, time: f32) -> Option<f32> {
+        let table = self.root_distance_table(node_id)?;
+        let state = &self.state.clips[self.clip_id(node_id).ok()?.0 as usize];
+        let time = state.start + time.clamp(0.0, state.duration);
+        Some(table.distance_at(time) - table.distance_at(state.start))
+    }
+
+    /// Gets how many times per second root translation curves are sampled at
+    /// for distance matching. Defaults to 60.
+    pub fn root_distance_resolution(&self) -> f32 {
+        self.root_distance_resolution
+    }
+
+    /// Sets how many times per second root translation curves are sampled at
+    /// for [`time_at_root_distance`](Self::time_at_root_distance). Tables
+    /// already built are rebuilt at the new resolution when next requested.
+    pub fn set_root_distance_resolution(&mut self, samples_per_second: f32) {
+        self.root_distance_resolution = samples_per_second.max(1.0);
+        self.root_distances.clear();
+    }
+
+    fn root_distance_table(&self, node_id: NodeId) -> Option<Arc<DistanceTable>> {
+        let clip = self.clip_id(node_id).ok()?;
+        if let Some(table) = self.root_distances.tables.lock().unwrap().get(&clip) {
+            return Some(table.clone());
+        }
+        if self.is_clip_evicted(node_id).ok()? {
+            return None;
+        }
+        let path = PropertyPath::from_parts(
+            EntityPath::root(),
+            AccessPath::of::<Transform>("translation").ok()?,
+        );
+        let duration = self.state.clips[clip.0 as usize].clip_duration;
+        let table = DistanceTable::build(duration, self.root_distance_resolution, |time| {
+            self.sample_clip_property::<Vec3>(node_id, &path, time)
+        })?;
+        let table = Arc::new(table);
+        let mut tables = self.root_distances.tables.lock().unwrap();
+        tables.insert(clip, table.clone());
+        Some(table)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        clip::AnimationClip,
+        curve::{CurveFixed, CurveVariableLinear},
+    };
+
+    fn root_translation() -> PropertyPath {
+        PropertyPath::from_parts(
+            EntityPath::root(),
+            AccessPath::of::<Transform>("translation").unwrap(),
+        )
+    }
+
+    #[test]
+    pub fn test_linear_root_motion_is_matched() {
+        // The root bobs up and down, which isn't counted.
+        let curve = CurveFixed::from_keyframes(
+            2.0,
+            vec![
+                Vec3::ZERO,
+                Vec3::new(1.0, 0.5, 0.0),
+                Vec3::new(2.0, 0.0, 0.0),
+            ],
+        );
+        let clip = AnimationClip::builder()
+            .add_curve(root_translation(), curve)
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+
+        let time = graph.time_at_root_distance(node, 0.8).unwrap();
+        assert!((time - 0.4).abs() < 1e-4);
+        let distance = graph.distance_at_time(node, 0.4).unwrap();
+        assert!((distance - 0.8).abs() < 1e-4);
+        assert_eq!(graph.time_at_root_distance(node, 2.5), None);
+        assert_eq!(graph.time_at_root_distance(NodeId::ROOT, 0.8), None);
+
+        // Trimmed clips are matched from the start of their range.
+        graph.set_clip_range(node, 0.25..1.0).unwrap();
+        let time = graph.time_at_root_distance(node, 0.8).unwrap();
+        assert!((time - 0.4).abs() < 1e-4);
+        assert_eq!(graph.time_at_root_distance(node, 1.9), None);
+
+        graph.set_root_distance_resolution(10.0);
+        let time = graph.time_at_root_distance(node, 0.8).unwrap();
+        assert!((time - 0.4).abs() < 1e-4);
+    }
+
+    #[test]
+    pub fn test_varying_root_motion_is_monotonic() {
+        let curve = CurveVariableLinear::with_keyframes(
+            vec![0.0, 0.25, 0.5, 1.0],
+            vec![
+                Vec3::ZERO,
+                Vec3::new(0.1, 0.0, 0.1),
+                Vec3::new(0.2, 0.0, 0.2),
+                Vec3::new(1.5, 0.0, 1.5),
+            ],
+        )
+        .unwrap();
+        let clip = AnimationClip::builder()
+            .add_curve(root_translation(), curve)
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+
+        let mut previous = -1.0;
+        for step in 0..=20 {
+            let distance = step as f32 * 0.1;
+            let time = graph.time_at_root_distance(node, distance).unwrap();
+            assert!(time > previous);
+            let matched = graph.distance_at_time(node, time).unwrap();
+            assert!((matched - distance).abs() < 1e-4);
+            previous = time;
+        }
+    }
+
+    #[test]
+    pub fn test_reloaded_clips_rebuild_their_tables() {
+        let clip = |distance: f32| {
+            AnimationClip::builder()
+                .add_curve(
+                    root_translation(),
+                    CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::X * distance]),
+                )
+                .build()
+        };
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip(1.0)).unwrap();
+        assert!((graph.distance_at_time(node, 1.0).unwrap() - 1.0).abs() < 1e-4);
+        graph.reload_clip(node, &clip(3.0)).unwrap();
+        assert!((graph.distance_at_time(node, 1.0).unwrap() - 3.0).abs() < 1e-4);
+    }
+}
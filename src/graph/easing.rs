@@ -0,0 +1,102 @@
+use std::{fmt, sync::Arc};
+
+/// Shapes how the weight of a [`NodeInput`] maps to its influence on the
+/// blend. Useful for making crossfades and other weight ramps less
+/// mechanical.
+///
+/// The predefined curves map weights in `0..=1` onto `0..=1`, and clamp
+/// weights outside of that range.
+///
+/// [`NodeInput`]: crate::graph::NodeInput
+#[derive(Clone)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    /// A user provided easing function. It is called with the unclamped
+    /// weight.
+    Custom(Arc<dyn Fn(f32) -> f32 + Send + Sync>),
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+impl Easing {
+    /// Applies the easing function to a weight.
+    pub fn ease(&self, weight: f32) -> f32 {
+        let t = weight.clamp(0.0, 1.0);
+        match self {
+            // Linear weights are left unclamped so that un-eased inputs keep
+            // their exact weights.
+            Self::Linear => weight,
+            Self::QuadIn => t * t,
+            Self::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Self::CubicIn => t * t * t,
+            Self::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Self::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Self::Custom(ease) => ease(weight),
+        }
+    }
+}
+
+impl fmt::Debug for Easing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Linear => f.write_str("Linear"),
+            Self::QuadIn => f.write_str("QuadIn"),
+            Self::QuadOut => f.write_str("QuadOut"),
+            Self::QuadInOut => f.write_str("QuadInOut"),
+            Self::CubicIn => f.write_str("CubicIn"),
+            Self::CubicOut => f.write_str("CubicOut"),
+            Self::CubicInOut => f.write_str("CubicInOut"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    assert_impl_all!(Easing: Send, Sync);
+
+    #[test]
+    pub fn test_easing_endpoints() {
+        let easings = [
+            Easing::Linear,
+            Easing::QuadIn,
+            Easing::QuadOut,
+            Easing::QuadInOut,
+            Easing::CubicIn,
+            Easing::CubicOut,
+            Easing::CubicInOut,
+        ];
+        for easing in easings {
+            assert!(easing.ease(0.0).abs() < 1e-6, "{:?}", easing);
+            assert!((easing.ease(1.0) - 1.0).abs() < 1e-6, "{:?}", easing);
+        }
+        assert_eq!(Easing::CubicIn.ease(2.0), 1.0);
+        assert_eq!(Easing::Linear.ease(2.0), 2.0);
+        assert_eq!(Easing::Custom(Arc::new(|w| w * 3.0)).ease(2.0), 6.0);
+    }
+}
@@ -0,0 +1,39 @@
+//! Fixed-point weights, used to evaluate graphs deterministically. See
+//! [`AnimationGraph::set_deterministic`].
+//!
+//! [`AnimationGraph::set_deterministic`]: crate::graph::AnimationGraph::set_deterministic
+
+use std::ops::AddAssign;
+
+const FRACTION_BITS: u32 = 16;
+const ONE: i64 = 1 << FRACTION_BITS;
+
+/// A weight in Q16.16 fixed-point. Every operation is on integers, so the
+/// results are identical on every platform.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FixedWeight(i64);
+
+impl FixedWeight {
+    /// Rounds a weight to the nearest multiple of 2^-16. Scaling by a power
+    /// of two and rounding are both exact, so this is deterministic as well.
+    pub fn from_f32(weight: f32) -> Self {
+        Self((weight * ONE as f32).round() as i64)
+    }
+
+    /// Converts back to a float. Exact for weights below 256.
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / ONE as f32
+    }
+
+    /// Multiplies two weights, rounding the result to the nearest multiple
+    /// of 2^-16.
+    pub fn mul(self, other: Self) -> Self {
+        Self((self.0 * other.0 + ONE / 2) >> FRACTION_BITS)
+    }
+}
+
+impl AddAssign for FixedWeight {
+    fn add_assign(&mut self, other: Self) {
+        self.0 += other.0;
+    }
+}
@@ -1,16 +1,161 @@
 use crate::{
-    graph::{application::BoneBinding, AnimationGraph},
+    graph::{
+        application::{apply_graph, BoneBinding},
+        pose::AnimatedPose,
+        track::BoneId,
+        AnimationGraph, OutputMode,
+    },
     path::EntityPath,
 };
 use bevy_core::Name;
-use bevy_ecs::prelude::*;
+use bevy_ecs::{
+    prelude::*,
+    system::{Command, EntityCommands},
+};
+use bevy_log::{info_span, warn};
 use bevy_transform::prelude::{Children, Parent, PreviousParent};
-use bevy_utils::HashSet;
+use bevy_utils::{HashMap, HashSet};
+use smallvec::{smallvec, SmallVec};
+
+/// How many times [`bind_hierarchy_system`] retries binding a graph that left
+/// some of its bones unbound, without its hierarchy changing. Each retry waits
+/// up to twice as many frames as the last.
+const MAX_BIND_RETRIES: u8 = 8;
+
+/// Marks an [`AnimationGraph`] to be bound and applied at the end of the frame
+/// it's added in, if the animation systems haven't bound it yet. See
+/// [`bind_auto_bound_graphs_system`].
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct AutoBind;
+
+/// An [`AnimationGraph`] that is bound and applied in the same frame it's
+/// spawned, so that its hierarchy is never shown in the unanimated pose.
+///
+/// ```rust,ignore
+/// commands
+///     .spawn_bundle(AnimationGraphBundle::new(graph))
+///     .push_children(&[skeleton]);
+/// ```
+#[derive(Bundle, Default)]
+pub struct AnimationGraphBundle {
+    pub graph: AnimationGraph,
+    pub auto_bind: AutoBind,
+}
+
+impl AnimationGraphBundle {
+    pub fn new(graph: AnimationGraph) -> Self {
+        Self {
+            graph,
+            auto_bind: AutoBind,
+        }
+    }
+}
+
+impl AnimationGraph {
+    /// Binds the graph to the hierarchy beneath `root`, then evaluates it and
+    /// writes its values to the bound entities immediately, instead of waiting
+    /// for the animation systems. Graphs with an [`OutputMode::Buffer`] write
+    /// their [`AnimatedPose`] to `root` instead.
+    ///
+    /// The graph doesn't need to be in the world, and should be inserted on
+    /// `root` afterwards. Useful when spawning hierarchies from startup
+    /// systems or when post-processing scenes. Graphs already on `root` can be
+    /// bound with [`BindAnimationGraphExt::bind_animation_graph`].
+    pub fn bind(&mut self, root: Entity, world: &mut World) {
+        bind_graph(world, root, self);
+        self.evaluate();
+        if self.output_mode != OutputMode::Buffer {
+            apply_graph(world, self);
+        } else if !self.update_skipped && self.total_weight != 0.0 {
+            match world.get_mut::<AnimatedPose>(root) {
+                Some(mut pose) => pose.write(self),
+                None => {
+                    let mut pose = AnimatedPose::default();
+                    pose.write(self);
+                    world.entity_mut(root).insert(pose);
+                }
+            }
+        }
+    }
+
+    /// Rebinds the graph's bones the next time [`bind_hierarchy_system`]
+    /// runs, as if the hierarchy beneath it changed.
+    ///
+    /// Bones left unbound are already retried for a while, so this is only
+    /// needed for hierarchies changed in ways the animation systems can't
+    /// detect, or that finish spawning long after the graph. See
+    /// [`bind`](Self::bind) to bind the graph immediately instead.
+    pub fn rebind(&mut self) {
+        self.clips.set_dirty(true);
+    }
+}
+
+/// A [`Command`] that binds and applies the [`AnimationGraph`] on an entity
+/// immediately. See [`AnimationGraph::bind`].
+pub struct BindAnimationGraph {
+    pub root: Entity,
+}
+
+impl Command for BindAnimationGraph {
+    fn write(self, world: &mut World) {
+        bind_graph_in_world(world, self.root);
+    }
+}
+
+pub trait BindAnimationGraphExt {
+    /// Binds and applies the [`AnimationGraph`] on the entity once the
+    /// commands are applied. See [`AnimationGraph::bind`].
+    fn bind_animation_graph(&mut self) -> &mut Self;
+}
+
+impl<'w, 's, 'a> BindAnimationGraphExt for EntityCommands<'w, 's, 'a> {
+    fn bind_animation_graph(&mut self) -> &mut Self {
+        let root = self.id();
+        self.commands().add(BindAnimationGraph { root });
+        self
+    }
+}
+
+/// Binds and applies the graph on `root` in place. Returns false if `root`
+/// doesn't have a graph.
+fn bind_graph_in_world(world: &mut World, root: Entity) -> bool {
+    // The graph is swapped out rather than removed, which would remove its
+    // bindings in cleanup_bindings_system.
+    let mut graph = match world.get_mut::<AnimationGraph>(root) {
+        Some(mut graph) => std::mem::take(&mut *graph),
+        None => return false,
+    };
+    graph.bind(root, world);
+    *world.get_mut::<AnimationGraph>(root).unwrap() = graph;
+    true
+}
 
+/// Binds and applies the [`AutoBind`] graphs that still need to be bound.
+///
+/// Graphs added after the animation systems have run, such as graphs spawned
+/// with [`Commands`] from the same stage, would otherwise be bound and applied
+/// in the next frame, leaving their hierarchy in its unanimated pose for a
+/// frame. This MUST be added as an exclusive system, and should run before
+/// transforms are propagated.
+pub fn bind_auto_bound_graphs_system(world: &mut World) {
+    let mut graphs = world.query_filtered::<(Entity, &AnimationGraph), With<AutoBind>>();
+    let pending: SmallVec<[Entity; 4]> = graphs
+        .iter(world)
+        .filter(|(_, graph)| graph.clips.is_dirty())
+        .map(|(root, _)| root)
+        .collect();
+    for root in pending {
+        bind_graph_in_world(world, root);
+    }
+}
+
+/// Marks [`AnimationGraph`]s as needing to be rebound when the entity hierarchy
+/// beneath them changes.
+//
 // This system bubbles up changes in transform hierarchies, dirtying all
 // affected animation graphs. This does `O(d)` parent lookupps, where `d`
 // is the depth of the changed
-pub(crate) fn dirty_hierarchy_system(
+pub fn dirty_hierarchy_system(
     mut graphs: Query<&mut AnimationGraph>,
     changed: Query<(Entity, Option<&PreviousParent>), Or<(Changed<Parent>, Changed<Name>)>>,
     //     removed: Query<&PreviousParent, Without<Parent>>,
@@ -44,60 +189,527 @@ pub(crate) fn dirty_hierarchy_system(
     visited.clear();
 }
 
-// This runs a `O(n*b*d)` operation for every animation graph in the World.
-// Here, n is the number of bones the graph has, b is the upper bound branching
-// factor of the hierarchy, and d is the deepest bone in the hierarchy.
+/// Binds the bones of dirty [`AnimationGraph`]s to the entities in their hierarchy.
+/// Should run after [`dirty_hierarchy_system`], and before the graphs are
+/// evaluated so that binding doesn't cause them to be evaluated again in the
+/// next frame.
+///
+/// Graphs with bones that couldn't be bound are retried on later frames, less
+/// and less often, in case the rest of their hierarchy is still being spawned.
+/// See [`AnimationGraph::rebind`] to force a graph to be rebound.
+//
+// This builds a trie of the graph's bone paths, then binds every bone in a
+// single traversal of the hierarchy beneath the graph, making this `O(n + b)`
+// per graph. Here, n is the number of children of the entities that lie on a
+// bone's path, and b is the total length of the bones' paths.
 //
 // This will run on a given graph any time a descendant entity's Parent or Name
 // components are changed/added, despawned, or when new clips added to a graph
 // that creates new bones. Ideally graphs should only have this done once during
 // initialization.
-pub(crate) fn bind_hierarchy_system(
-    mut graphs: Query<(Entity, &mut AnimationGraph), Changed<AnimationGraph>>,
+//
+// Hierarchies spawned over several frames, like scenes, may not have every
+// change picked up by dirty_hierarchy_system. Graphs left with unbound bones
+// are bound again every 2^n frames after the nth failed attempt, until every
+// bone is bound or MAX_BIND_RETRIES is reached. Graphs with bones that are
+// legitimately missing only pay for a handful of extra traversals. The
+// attempts are counted here rather than on the graph, so that retries which
+// don't find any new entities leave the graph unchanged.
+pub fn bind_hierarchy_system(
+    mut graphs: Query<(Entity, &mut AnimationGraph)>,
     children: Query<&Children>,
     names: Query<&Name>,
+    removed: RemovedComponents<AnimationGraph>,
     mut commands: Commands,
+    mut frame: Local<u32>,
+    mut failed_binds: Local<HashMap<Entity, u8>>,
 ) {
+    let _span = info_span!("bind_animation_graphs").entered();
+    *frame = frame.wrapping_add(1);
+    for entity in removed.iter() {
+        failed_binds.remove(&entity);
+    }
     for (root, mut graph) in graphs.iter_mut() {
-        if !graph.clips.is_dirty() {
+        // Graphs are changed every frame their time is advanced. Only read
+        // them through a shared reference until their bindings change, so
+        // that graphs which don't need rebinding aren't marked as changed
+        // again.
+        let dirty = graph.clips.is_dirty();
+        let failed = failed_binds.get(&root).copied().unwrap_or(0);
+        let retry = (1..=MAX_BIND_RETRIES).contains(&failed) && *frame & ((1 << failed) - 1) == 0;
+        if !dirty && !retry {
             continue;
         }
-        for bone in graph.clips.bones_mut() {
-            if let Some(entity) = find_bone(root, &bone.path, &children, &names) {
+        let entities = find_bones(
+            root,
+            graph.clips.bones().map(|bone| &bone.path),
+            |entity| children.get(entity).ok().map(|children| &children[..]),
+            |entity| names.get(entity).ok(),
+        );
+        let rebound = graph
+            .clips
+            .bones()
+            .zip(entities.iter())
+            .any(|(bone, entity)| bone.entity() != *entity);
+        if dirty || rebound {
+            let graph_nonce = graph.nonce;
+            // Bones that fail to bind are left without a BoneBinding.
+            for (entity, bone_ids) in assign_bones(&mut graph, entities) {
                 commands.entity(entity).insert(BoneBinding {
                     graph: root,
-                    bone_id: bone.id,
+                    graph_nonce,
+                    bone_ids,
                 });
-                bone.set_entity(Some(entity));
-            } else {
-                bone.set_entity(None);
+            }
+            graph.clips.set_dirty(false);
+        }
+        // Attempts that aren't retries restart the count.
+        if graph.clips.bones().all(|bone| bone.entity().is_some()) {
+            failed_binds.remove(&root);
+        } else if dirty {
+            failed_binds.insert(root, 1);
+        } else {
+            failed_binds.insert(root, failed.saturating_add(1));
+        }
+    }
+}
+
+/// Binds the bones of a graph to the entities in the hierarchy beneath `root`
+/// immediately, instead of waiting for [`bind_hierarchy_system`]. The graph
+/// doesn't need to be in the world yet.
+pub(crate) fn bind_graph(world: &mut World, root: Entity, graph: &mut AnimationGraph) {
+    let entities = {
+        let world: &World = world;
+        find_bones(
+            root,
+            graph.clips.bones().map(|bone| &bone.path),
+            |entity| world.get::<Children>(entity).map(|children| &children[..]),
+            |entity| world.get::<Name>(entity),
+        )
+    };
+    let graph_nonce = graph.nonce;
+    for (entity, bone_ids) in assign_bones(graph, entities) {
+        world.entity_mut(entity).insert(BoneBinding {
+            graph: root,
+            graph_nonce,
+            bone_ids,
+        });
+    }
+    graph.clips.set_dirty(false);
+}
+
+/// Sets the entity of each of the graph's bones, in the order returned by
+/// [`find_bones`], and groups the bones by the entity they're bound to.
+///
+/// Bones only reach the same entity through malformed hierarchies, such as an
+/// entity listed in the [`Children`] of several parents. They share a single
+/// [`BoneBinding`] rather than overwriting each other's.
+fn assign_bones(
+    graph: &mut AnimationGraph,
+    entities: Vec<Option<Entity>>,
+) -> Vec<(Entity, SmallVec<[BoneId; 1]>)> {
+    let mut bound: Vec<(Entity, SmallVec<[BoneId; 1]>)> = Vec::with_capacity(entities.len());
+    let mut indices: HashMap<Entity, usize> = HashMap::default();
+    let mut shared = false;
+    for (bone, entity) in graph.clips.bones_mut().zip(entities) {
+        bone.set_entity(entity);
+        let entity = match entity {
+            Some(entity) => entity,
+            None => continue,
+        };
+        match indices.get(&entity) {
+            Some(&idx) => {
+                bound[idx].1.push(bone.id);
+                shared = true;
+            }
+            None => {
+                indices.insert(entity, bound.len());
+                bound.push((entity, smallvec![bone.id]));
             }
         }
-        graph.clips.set_dirty(false);
+    }
+    if shared {
+        for (entity, bone_ids) in bound.iter().filter(|(_, bone_ids)| bone_ids.len() > 1) {
+            let paths: Vec<_> = bone_ids
+                .iter()
+                .filter_map(|bone_id| graph.clips.get_bone(*bone_id))
+                .map(|bone| format!("'{}'", bone.path))
+                .collect();
+            warn!(
+                "Bones {} are all bound to {:?}. The tracks of each are applied to it in turn.",
+                paths.join(", "),
+                entity
+            );
+        }
+    }
+    bound
+}
+
+/// A trie of entity paths, keyed by the names of each path segment.
+#[derive(Default)]
+struct PathTrie<'a> {
+    /// The index of the path ending at this node, if any.
+    path: Option<usize>,
+    // Bones rarely have more than a few animated children, and comparing
+    // Names only compares their precomputed hashes, so this is searched
+    // linearly instead of hashing every name in the hierarchy.
+    children: Vec<(&'a Name, PathTrie<'a>)>,
+}
+
+impl<'a> PathTrie<'a> {
+    fn child(&self, name: &Name) -> Option<&PathTrie<'a>> {
+        self.children
+            .iter()
+            .find(|(child, _)| *child == name)
+            .map(|(_, child)| child)
+    }
+
+    fn child_mut(&mut self, name: &'a Name) -> &mut PathTrie<'a> {
+        let idx = match self.children.iter().position(|(child, _)| *child == name) {
+            Some(idx) => idx,
+            None => {
+                self.children.push((name, PathTrie::default()));
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[idx].1
     }
 }
 
-fn find_bone<'a>(
+/// Finds the entities for a set of paths through the hierarchy beneath `root`
+/// in a single traversal. Returns the entity for each path in order, or `None`
+/// if it was not found.
+///
+/// Like [`find_bone_in_world`], an entity with multiple children of the same
+/// name only has its first matching child searched. The empty path is the root
+/// itself, and is found without visiting any children.
+fn find_bones<'a, 'w>(
     root: Entity,
-    path: &EntityPath,
-    children: &Query<&Children>,
-    names: &Query<&Name>,
-) -> Option<Entity> {
+    paths: impl Iterator<Item = &'a EntityPath>,
+    children: impl Fn(Entity) -> Option<&'w [Entity]>,
+    names: impl Fn(Entity) -> Option<&'w Name>,
+) -> Vec<Option<Entity>> {
+    let mut trie = PathTrie::default();
+    let mut count = 0;
+    for (idx, path) in paths.enumerate() {
+        let mut node = &mut trie;
+        for name in path.iter() {
+            node = node.child_mut(name);
+        }
+        node.path = Some(idx);
+        count += 1;
+    }
+
+    let mut entities = vec![None; count];
+    let mut open_set = vec![(root, &trie)];
+    let mut matched = Vec::new();
+    while let Some((entity, node)) = open_set.pop() {
+        if let Some(idx) = node.path {
+            entities[idx] = Some(entity);
+        }
+        if node.children.is_empty() {
+            continue;
+        }
+        matched.clear();
+        for child in children(entity).into_iter().flatten() {
+            let name = match names(*child) {
+                Some(name) => name,
+                None => continue,
+            };
+            if let Some(next) = node.child(name) {
+                if !matched.contains(&name) {
+                    matched.push(name);
+                    open_set.push((*child, next));
+                }
+            }
+        }
+    }
+    entities
+}
+
+/// Finds an entity by following a path through the hierarchy beneath `root`,
+/// reading directly from a [`World`]. The empty path is `root` itself.
+pub(crate) fn find_bone_in_world(world: &World, root: Entity, path: &EntityPath) -> Option<Entity> {
     let mut current = root;
     for fragment in path.iter() {
-        let mut found = false;
-        for child in children.get(current).ok()?.iter() {
-            if let Ok(name) = names.get(*child) {
-                if name == fragment {
-                    found = true;
-                    current = *child;
-                    break;
-                }
+        current = world
+            .get::<Children>(current)?
+            .iter()
+            .copied()
+            .find(|child| world.get::<Name>(*child) == Some(fragment))?;
+    }
+    Some(current)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        clip::AnimationClip, curve::CurveFixed, graph::NodeId, path::PropertyPath, AnimationPlugin,
+    };
+    use bevy_app::{App, CoreStage};
+    use bevy_asset::AssetPlugin;
+    use bevy_math::Vec3;
+    use bevy_reflect::TypeRegistryArc;
+    use bevy_tasks::{ComputeTaskPool, IoTaskPool, TaskPool};
+    use bevy_transform::prelude::Transform;
+
+    const DEPTH: usize = 20;
+
+    fn spawn_named(world: &mut World, name: &str, children: &[Entity]) -> Entity {
+        world
+            .spawn()
+            .insert(Name::new(name.to_string()))
+            .insert(Transform::default())
+            .insert(Children::with(children))
+            .id()
+    }
+
+    /// Spawns a chain of entities named "l{depth}", where every level also has
+    /// a "side" child, an unnamed child, and a second child with the same name
+    /// as the next level that only contains a "shadowed" child.
+    fn spawn_level(world: &mut World, depth: usize) -> Entity {
+        let mut children = Vec::new();
+        if depth < DEPTH {
+            let name = format!("l{}", depth + 1);
+            children.push(spawn_level(world, depth + 1));
+            children.push(spawn_named(world, "side", &[]));
+            children.push(world.spawn().id());
+            let shadowed = spawn_named(world, "shadowed", &[]);
+            children.push(spawn_named(world, &name, &[shadowed]));
+        }
+        spawn_named(world, &format!("l{}", depth), &children)
+    }
+
+    #[test]
+    pub fn test_bindings_match_path_walks() {
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .insert_resource(ComputeTaskPool(TaskPool::new()))
+            .add_plugin(AssetPlugin)
+            .add_plugin(AnimationPlugin::default())
+            .register_type::<Transform>();
+
+        let mut paths = Vec::new();
+        let mut prefix = String::new();
+        for depth in 1..=DEPTH {
+            prefix.push_str(&format!("l{}/", depth));
+            for leaf in ["", "side", "shadowed", "missing"] {
+                paths.push(format!("{}{}", prefix, leaf));
+            }
+        }
+        let mut builder = AnimationClip::builder();
+        {
+            let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+            let registry = registry.read();
+            for path in paths.iter() {
+                let path = format!(
+                    "{}@bevy_transform::components::transform::Transform.translation",
+                    path.trim_end_matches('/')
+                );
+                let path = PropertyPath::parse(&registry, &path).unwrap();
+                builder =
+                    builder.add_curve(path, CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO]));
+            }
+        }
+        let clip = builder.build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+
+        let top = spawn_level(&mut app.world, 1);
+        let root = app
+            .world
+            .spawn()
+            .insert(graph)
+            .insert(Children::with(&[top]))
+            .id();
+        app.update();
+
+        let graph = app.world.get::<AnimationGraph>(root).unwrap();
+        let mut bound = 0;
+        for bone in graph.bones() {
+            let expected = find_bone_in_world(&app.world, root, &bone.path);
+            assert_eq!(bone.entity(), expected, "{}", bone.path);
+            if let Some(entity) = expected {
+                let binding = app.world.get::<BoneBinding>(entity).unwrap();
+                assert_eq!(binding.graph(), root);
+                bound += 1;
             }
         }
-        if !found {
-            return None;
+        assert_eq!(graph.bones().count(), DEPTH * 4);
+        // Every level and all but the deepest's "side" child are bound. The
+        // shadowed and missing entities are not.
+        assert_eq!(bound, DEPTH * 2 - 1);
+    }
+
+    #[derive(Default)]
+    struct ChangeCounts {
+        bindings: usize,
+        graphs: usize,
+    }
+
+    fn count_changes_system(
+        bindings: Query<(), Changed<BoneBinding>>,
+        graphs: Query<(), Changed<AnimationGraph>>,
+        mut counts: ResMut<ChangeCounts>,
+    ) {
+        if bindings.iter().next().is_some() {
+            counts.bindings += 1;
+        }
+        if graphs.iter().next().is_some() {
+            counts.graphs += 1;
         }
     }
-    Some(current)
+
+    #[test]
+    pub fn test_advancing_time_does_not_rebind() {
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .insert_resource(ComputeTaskPool(TaskPool::new()))
+            .init_resource::<ChangeCounts>()
+            .add_plugin(AssetPlugin)
+            .add_plugin(AnimationPlugin::default())
+            .add_system_to_stage(CoreStage::PostUpdate, count_changes_system)
+            .register_type::<Transform>();
+
+        let path = {
+            let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+            PropertyPath::parse(
+                &registry.read(),
+                "l1@bevy_transform::components::transform::Transform.translation",
+            )
+            .unwrap()
+        };
+        let clip = AnimationClip::builder()
+            .add_curve(
+                path,
+                CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::X]),
+            )
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+        let bone = spawn_named(&mut app.world, "l1", &[]);
+        let root = app
+            .world
+            .spawn()
+            .insert(graph)
+            .insert(Children::with(&[bone]))
+            .id();
+
+        // Binding and evaluating the new graph doesn't change it again in the
+        // following frames.
+        for _ in 0..5 {
+            app.update();
+        }
+        let counts = app.world.get_resource::<ChangeCounts>().unwrap();
+        assert_eq!((counts.bindings, counts.graphs), (1, 1));
+
+        for _ in 0..100 {
+            app.world
+                .get_mut::<AnimationGraph>(root)
+                .unwrap()
+                .advance_time(1.0 / 60.0);
+            app.update();
+        }
+        let counts = app.world.get_resource::<ChangeCounts>().unwrap();
+        assert_eq!((counts.bindings, counts.graphs), (1, 101));
+        assert_eq!(
+            app.world.get::<BoneBinding>(bone).map(BoneBinding::graph),
+            Some(root)
+        );
+    }
+
+    fn retry_app() -> App {
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .insert_resource(ComputeTaskPool(TaskPool::new()))
+            .init_resource::<ChangeCounts>()
+            .add_plugin(AssetPlugin)
+            .add_plugin(AnimationPlugin::default())
+            .add_system_to_stage(CoreStage::PostUpdate, count_changes_system)
+            .register_type::<Transform>();
+        app
+    }
+
+    fn translation_graph(app: &App, path: &str) -> AnimationGraph {
+        let path = {
+            let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+            let path = format!(
+                "{}@bevy_transform::components::transform::Transform.translation",
+                path
+            );
+            PropertyPath::parse(&registry.read(), &path).unwrap()
+        };
+        let clip = AnimationClip::builder()
+            .add_curve(path, CurveFixed::from_keyframes(1.0, vec![Vec3::X]))
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+        graph
+    }
+
+    #[test]
+    pub fn test_unbound_bones_are_retried() {
+        let mut app = retry_app();
+        let graph = translation_graph(&app, "l1/l2");
+        let l1 = spawn_named(&mut app.world, "l1", &[]);
+        let root = app
+            .world
+            .spawn()
+            .insert(graph)
+            .insert(Children::with(&[l1]))
+            .id();
+        app.update();
+        let bone: EntityPath = "l1/l2".parse().unwrap();
+        let graph = app.world.get::<AnimationGraph>(root).unwrap();
+        assert_eq!(graph.find_bone(&bone).unwrap().entity(), None);
+
+        // Replacing the children directly doesn't change any Parent, so the
+        // hierarchy isn't marked as changed.
+        let l2 = spawn_named(&mut app.world, "l2", &[]);
+        app.world.entity_mut(l1).insert(Children::with(&[l2]));
+        for _ in 0..2 {
+            app.update();
+        }
+        let graph = app.world.get::<AnimationGraph>(root).unwrap();
+        assert_eq!(graph.find_bone(&bone).unwrap().entity(), Some(l2));
+        assert_eq!(app.world.get::<Transform>(l2).unwrap().translation, Vec3::X);
+    }
+
+    #[test]
+    pub fn test_bind_retries_are_limited() {
+        let mut app = retry_app();
+        let graph = translation_graph(&app, "l1/missing");
+        let l1 = spawn_named(&mut app.world, "l1", &[]);
+        let root = app
+            .world
+            .spawn()
+            .insert(graph)
+            .insert(Children::with(&[l1]))
+            .id();
+        for _ in 0..2000 {
+            app.update();
+        }
+        // The graph is only changed when it's added. Retries that don't find
+        // the missing bone leave it unchanged.
+        let counts = app.world.get_resource::<ChangeCounts>().unwrap();
+        assert_eq!(counts.graphs, 1);
+
+        // The retries have run out, so the new bone isn't found until the
+        // graph is rebound, which restarts them.
+        let missing = spawn_named(&mut app.world, "missing", &[]);
+        app.world.entity_mut(l1).insert(Children::with(&[missing]));
+        for _ in 0..1000 {
+            app.update();
+        }
+        let graph = app.world.get::<AnimationGraph>(root).unwrap();
+        assert!(graph.bones().all(|bone| bone.entity().is_none()));
+        app.world.get_mut::<AnimationGraph>(root).unwrap().rebind();
+        app.update();
+        let graph = app.world.get::<AnimationGraph>(root).unwrap();
+        assert!(graph.bones().all(|bone| bone.entity() == Some(missing)));
+    }
 }
@@ -0,0 +1,172 @@
+use crate::graph::AnimationGraph;
+use bevy_ecs::prelude::*;
+
+/// Controls how often the [`AnimationGraph`] on the same entity is updated.
+///
+/// The crate never changes this on its own. It's meant to be written by a
+/// user provided system, for example one that increases the interval with the
+/// distance to the camera:
+///
+/// ```rust,ignore
+/// fn distance_lod_system(
+///     camera: Query<&GlobalTransform, With<Camera>>,
+///     mut lods: Query<(&GlobalTransform, &mut AnimationLod)>,
+/// ) {
+///     let camera = camera.single().translation;
+///     for (transform, mut lod) in lods.iter_mut() {
+///         let far = transform.translation.distance(camera) > 50.0;
+///         let interval = if far { 0.1 } else { 0.0 };
+///         if lod.interval != interval {
+///             lod.interval = interval;
+///         }
+///     }
+/// }
+/// ```
+///
+/// See [`AnimationGraph::set_update_interval`].
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct AnimationLod {
+    /// The minimum time between updates of the graph, in seconds. 0 updates
+    /// the graph every frame.
+    pub interval: f32,
+}
+
+/// Copies the intervals of changed [`AnimationLod`]s into their
+/// [`AnimationGraph`]s.
+pub fn apply_animation_lod_system(
+    mut graphs: Query<(&AnimationLod, &mut AnimationGraph), Changed<AnimationLod>>,
+) {
+    for (lod, mut graph) in graphs.iter_mut() {
+        if graph.update_interval() != lod.interval {
+            graph.set_update_interval(lod.interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        clip::AnimationClip, curve::CurveFixed, graph::NodeId, path::PropertyPath, AnimationPlugin,
+    };
+    use bevy_app::App;
+    use bevy_asset::AssetPlugin;
+    use bevy_core::Name;
+    use bevy_math::Vec3;
+    use bevy_reflect::TypeRegistryArc;
+    use bevy_tasks::{ComputeTaskPool, IoTaskPool, TaskPool};
+    use bevy_transform::prelude::Children;
+    use bevy_transform::prelude::Transform;
+
+    const FRAME: f32 = 1.0 / 60.0;
+
+    fn advance_graphs_system(mut graphs: Query<&mut AnimationGraph>) {
+        for mut graph in graphs.iter_mut() {
+            graph.advance_time(FRAME);
+        }
+    }
+
+    fn app() -> (App, PropertyPath) {
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .insert_resource(ComputeTaskPool(TaskPool::new()))
+            .add_plugin(AssetPlugin)
+            .add_plugin(AnimationPlugin::default())
+            .add_system(advance_graphs_system.before(crate::AnimationSystem::GraphEvaluation))
+            .register_type::<Transform>();
+        let path = {
+            let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+            PropertyPath::parse(
+                &registry.read(),
+                "a@bevy_transform::components::transform::Transform.translation",
+            )
+            .unwrap()
+        };
+        (app, path)
+    }
+
+    #[test]
+    pub fn test_update_interval_reduces_sampling() {
+        let (mut app, path) = app();
+        // Samples to the clip's time.
+        let clip = AnimationClip::builder()
+            .add_curve(
+                path,
+                CurveFixed::from_keyframes(0.1, vec![Vec3::ZERO, Vec3::X * 10.0]),
+            )
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+
+        let bone = app
+            .world
+            .spawn()
+            .insert(Name::new("a"))
+            .insert(Transform::default())
+            .id();
+        let root = app
+            .world
+            .spawn()
+            .insert(graph)
+            .insert(AnimationLod { interval: 0.1 })
+            .insert(Children::with(&[bone]))
+            .id();
+
+        let mut samples = 0;
+        let mut last = 0.0;
+        for frame in 1..=60 {
+            app.update();
+            let x = app.world.get::<Transform>(bone).unwrap().translation.x;
+            if x != last {
+                samples += 1;
+                last = x;
+                // Skipped frames are applied in full on the next update.
+                assert!((x - frame as f32 * FRAME).abs() < 1e-4, "{} {}", frame, x);
+            }
+        }
+        assert!((8..=10).contains(&samples), "{}", samples);
+
+        let graph = app.world.get::<AnimationGraph>(root).unwrap();
+        let pending = if graph.is_update_skipped() { 0.1 } else { 0.0 };
+        let time = graph.clip_time(node).unwrap();
+        assert!(time <= 1.0 + 1e-4 && time >= 1.0 - pending, "{}", time);
+    }
+
+    #[test]
+    pub fn test_zero_weight_graphs_are_not_applied() {
+        let (mut app, path) = app();
+        let clip = AnimationClip::builder()
+            .add_curve(
+                path,
+                CurveFixed::from_keyframes(1.0, vec![Vec3::X * 3.0; 2]),
+            )
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap().set_weight(0.0);
+
+        let bone = app
+            .world
+            .spawn()
+            .insert(Name::new("a"))
+            .insert(Transform::from_xyz(1.0, 0.0, 0.0))
+            .id();
+        let root = app
+            .world
+            .spawn()
+            .insert(graph)
+            .insert(Children::with(&[bone]))
+            .id();
+
+        app.update();
+        assert_eq!(app.world.get::<Transform>(bone).unwrap().translation.x, 1.0);
+
+        let mut graph = app.world.get_mut::<AnimationGraph>(root).unwrap();
+        let root_node = graph.nodes.get_mut(NodeId::ROOT).unwrap();
+        root_node.get_input_mut(node).unwrap().set_weight(1.0);
+        app.update();
+        let x = app.world.get::<Transform>(bone).unwrap().translation.x;
+        assert!((x - 3.0).abs() < 1e-5);
+    }
+}
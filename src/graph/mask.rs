@@ -0,0 +1,43 @@
+use crate::path::AccessPath;
+
+/// Controls which of a bone's animated properties an [`AnimationGraph`]
+/// writes to its bound entity, so gameplay code can take ownership of them.
+/// Masked properties are still evaluated, and are written again as soon as
+/// they are unmasked, without rebinding.
+///
+/// ```rust,ignore
+/// // Let IK drive the hand, and hand the ragdoll's arm over to physics.
+/// graph.set_bone_write_mask(&hand, WriteMask::Exclude(vec![transform_access]))?;
+/// graph.disable_bone(&arm)?;
+/// ```
+///
+/// See [`AnimationGraph::set_bone_write_mask`].
+///
+/// [`AnimationGraph`]: crate::graph::AnimationGraph
+/// [`AnimationGraph::set_bone_write_mask`]: crate::graph::AnimationGraph::set_bone_write_mask
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteMask {
+    /// Every animated property is written.
+    All,
+    /// No properties are written.
+    None,
+    /// Every animated property except the listed ones is written.
+    Exclude(Vec<AccessPath>),
+}
+
+impl Default for WriteMask {
+    fn default() -> Self {
+        Self::All
+    }
+}
+
+impl WriteMask {
+    /// Checks if the mask lets a property be written.
+    pub fn allows(&self, property: &AccessPath) -> bool {
+        match self {
+            Self::All => true,
+            Self::None => false,
+            Self::Exclude(excluded) => !excluded.contains(property),
+        }
+    }
+}
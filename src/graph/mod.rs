@@ -1,55 +1,677 @@
-pub(crate) mod application;
-pub(crate) mod hierarchy;
+pub mod application;
+mod display;
+mod distance;
+mod easing;
+mod fixed;
+pub mod hierarchy;
+pub mod lod;
+mod mask;
+mod morph;
 mod node;
+mod params;
+pub mod pose;
+mod random;
+pub mod recorder;
+mod runtime;
+mod stats;
 mod track;
+pub mod transition;
+pub mod typed;
 
+pub use easing::Easing;
+pub use mask::WriteMask;
+pub(crate) use morph::MorphCurves;
 pub(crate) use node::*;
+pub use node::{NodeId, NodeInput};
+pub use params::WeightBinding;
+pub use runtime::{GraphRuntimeState, GraphRuntimeStateError};
+pub use stats::TrackStats;
 pub(crate) use track::*;
+pub use track::{BatchBuffers, ClipId, Track, TrackError, TypeConflict};
 
-use crate::{clip::AnimationClip, path::EntityPath};
-use bevy_ecs::{component::Component, prelude::Entity};
-use std::collections::VecDeque;
+use distance::RootDistances;
+use fixed::FixedWeight;
+use params::{GraphParams, ParamCurve};
+use random::GraphRng;
+use stats::TrackStatsStorage;
+use transition::PoseFade;
 
-#[derive(Default, Debug)]
-struct ClipState {
+use crate::{
+    clip::{
+        into_dynamic_curve, validate_component, validate_field, AnimationClip, ClipValidationError,
+        ClipValidationErrorKind,
+    },
+    curve::Curve,
+    path::{AccessPath, AccessTarget, EntityPath, FieldPath, PathInterner, PropertyPath},
+    target, Animatable, TransformBlendMode,
+};
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{
+    component::Component,
+    prelude::{Entity, World},
+};
+use bevy_log::info;
+use bevy_reflect::{impl_reflect_value, Reflect, TypeRegistry, TypeRegistryArc};
+use bevy_transform::prelude::Transform;
+use bevy_utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use std::{
+    any::TypeId,
+    borrow::Cow,
+    ops::Range,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+use thiserror::Error;
+
+/// Used to distinguish graphs created on the same entity.
+static NEXT_GRAPH_NONCE: AtomicU32 = AtomicU32::new(0);
+
+/// How a clip's time behaves when it reaches either end of the clip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlaybackMode {
+    /// Plays the clip once. Time is clamped to the clip's bounds, and the clip
+    /// is marked as finished when it reaches the end, or finished in reverse
+    /// when reaching the start with negative time deltas.
+    Once,
+    /// Loops the clip. Time wraps around both ends of the clip.
+    Loop,
+}
+
+impl Default for PlaybackMode {
+    fn default() -> Self {
+        Self::Once
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub(crate) struct ClipState {
     weight: f32,
+    /// The local time of the clip, relative to the start of its trim range.
+    /// If `normalized` is set, this is instead the phase of the clip, from 0
+    /// to 1.
     time: f32,
+    /// The start of the trim range in the clip's time.
+    start: f32,
+    /// The length of the trim range.
+    duration: f32,
+    /// The duration of the full, untrimmed clip.
+    clip_duration: f32,
+    mode: PlaybackMode,
+    normalized: bool,
+    additive: bool,
+    sync: Option<ClipSync>,
+    finished: bool,
+    finished_reverse: bool,
+    /// Whether a looping clip wrapped around either end since the graph was
+    /// last evaluated.
+    looped: bool,
+    warp: Option<TimeWarp>,
+    /// Shifts the time the clip is sampled at, in seconds. See
+    /// [`AnimationGraph::set_clip_phase_offset`].
+    phase_offset: f32,
+    /// Freezes the clip's time while the rest of the graph advances. See
+    /// [`AnimationGraph::pause_clip`].
+    paused: bool,
+    /// Scales the time deltas the clip is advanced by. See
+    /// [`AnimationGraph::set_clip_speed`].
+    speed: f32,
+}
+
+/// A curve mapping the local time of a clip node to the time its clip is
+/// sampled at. See [`AnimationGraph::set_time_warp`].
+#[derive(Clone)]
+struct TimeWarp(Arc<dyn Curve<f32>>);
+
+impl std::fmt::Debug for TimeWarp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimeWarp")
+            .field("duration", &self.0.duration())
+            .finish()
+    }
+}
+
+/// The sync group a clip belongs to.
+#[derive(Debug, Clone, Copy)]
+struct ClipSync {
+    /// Index into [`GraphState::sync_groups`].
+    group: usize,
+    /// Whether the clip follows the normalized phase of the group's leader,
+    /// or its absolute time.
+    normalized: bool,
+}
+
+/// A group of clips whose times are kept in sync. See
+/// [`AnimationGraph::set_sync_group`].
+#[derive(Debug, Clone)]
+struct SyncGroup {
+    name: Cow<'static, str>,
+    /// The clip with the highest weight as of the last evaluation.
+    leader: Option<ClipId>,
+}
+
+impl ClipState {
+    fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            clip_duration: duration,
+            speed: 1.0,
+            ..Default::default()
+        }
+    }
+
+    /// The time at which the clip's curves are sampled.
+    #[inline]
+    fn sample_time(&self) -> f32 {
+        let seconds = self.offset_seconds();
+        match &self.warp {
+            Some(warp) => self.start + warp.0.sample(seconds).clamp(0.0, self.duration),
+            None => self.start + seconds,
+        }
+    }
+
+    /// The local time of the clip in seconds, shifted by its phase offset and
+    /// wrapped or clamped to its local duration.
+    #[inline]
+    fn offset_seconds(&self) -> f32 {
+        let seconds = self.seconds();
+        if self.phase_offset == 0.0 {
+            return seconds;
+        }
+        let duration = self.local_duration();
+        match self.mode {
+            PlaybackMode::Loop if duration > 0.0 => {
+                (seconds + self.phase_offset).rem_euclid(duration)
+            }
+            PlaybackMode::Loop => 0.0,
+            PlaybackMode::Once => (seconds + self.phase_offset).clamp(0.0, duration),
+        }
+    }
+
+    /// The local time of the clip in seconds, relative to the start of its
+    /// trim range.
+    #[inline]
+    fn seconds(&self) -> f32 {
+        if self.normalized {
+            self.time * self.local_duration()
+        } else {
+            self.time
+        }
+    }
+
+    /// The duration of the clip's local time in seconds. This is the length of
+    /// its trim range, or the duration of its time warp if it has one.
+    #[inline]
+    fn local_duration(&self) -> f32 {
+        match &self.warp {
+            Some(warp) => warp.0.duration(),
+            None => self.duration,
+        }
+    }
+
+    /// How long the clip takes to play through once in seconds, at its speed.
+    /// Clips with a speed of 0 never reach their end.
+    fn playback_duration(&self) -> Option<f32> {
+        (self.speed != 0.0).then(|| self.local_duration() / self.speed.abs())
+    }
+
+    /// How long a [`PlaybackMode::Once`] clip has left to play in seconds, at
+    /// its speed. Looping clips never finish.
+    fn remaining_time(&self) -> Option<f32> {
+        if self.mode == PlaybackMode::Loop || self.speed == 0.0 {
+            return None;
+        }
+        let remaining = if self.speed > 0.0 {
+            self.local_duration() - self.seconds()
+        } else {
+            self.seconds()
+        };
+        Some(remaining.max(0.0) / self.speed.abs())
+    }
+
+    /// How far through the clip the current time is, from 0 to 1.
+    #[inline]
+    fn phase(&self) -> f32 {
+        let duration = self.local_duration();
+        if self.normalized {
+            self.time
+        } else if duration > 0.0 {
+            self.time / duration
+        } else {
+            0.0
+        }
+    }
+
+    /// The length of the clip in the units of its time: 1 if the time is
+    /// normalized, and its duration otherwise.
+    #[inline]
+    fn length(&self) -> f32 {
+        if self.normalized {
+            1.0
+        } else {
+            self.local_duration()
+        }
+    }
+
+    /// Converts a time in seconds into the units of the clip's time.
+    #[inline]
+    fn local_time(&self, seconds: f32) -> f32 {
+        let duration = self.local_duration();
+        if !self.normalized {
+            seconds
+        } else if duration > 0.0 {
+            seconds / duration
+        } else {
+            0.0
+        }
+    }
+
+    /// Switches between normalized and absolute time, preserving the current
+    /// position in the clip.
+    fn set_normalized(&mut self, normalized: bool) {
+        let phase = self.phase();
+        self.normalized = normalized;
+        self.time = self.bound_time(phase * self.length());
+    }
+
+    /// Sets or removes the clip's time warp, preserving the current position
+    /// in the clip.
+    fn set_warp(&mut self, warp: Option<TimeWarp>) {
+        let phase = self.phase();
+        self.warp = warp;
+        self.time = self.bound_time(phase * self.length());
+    }
+
+    /// Matches the time of the leader of the clip's sync group.
+    fn follow(&mut self, leader: &ClipState, normalized: bool) {
+        let time = if normalized {
+            leader.phase() * self.length()
+        } else {
+            self.local_time(leader.seconds())
+        };
+        self.time = self.bound_time(time);
+        self.finished = leader.finished;
+        self.finished_reverse = leader.finished_reverse;
+        self.looped |= leader.looped;
+    }
+
+    /// Restricts playback to a sub-range of the clip. The range is clamped to
+    /// the bounds of the full clip.
+    fn set_range(&mut self, range: Range<f32>) {
+        let start = range.start.clamp(0.0, self.clip_duration);
+        let end = range.end.clamp(start, self.clip_duration);
+        self.start = start;
+        self.duration = end - start;
+        self.time = self.bound_time(self.time);
+    }
+
+    /// Changes the duration of the full clip, such as when it is reloaded.
+    /// Untrimmed clips play the full new clip, and trim ranges are clamped
+    /// to its bounds.
+    fn set_clip_duration(&mut self, clip_duration: f32) {
+        let trimmed = self.start > 0.0 || self.duration < self.clip_duration;
+        let end = if trimmed {
+            self.start + self.duration
+        } else {
+            clip_duration
+        };
+        self.clip_duration = clip_duration;
+        self.set_range(self.start..end);
+    }
+
+    /// Wraps or clamps a time to the bounds of the clip.
+    fn bound_time(&self, time: f32) -> f32 {
+        let length = self.length();
+        match self.mode {
+            PlaybackMode::Loop if length > 0.0 => time.rem_euclid(length),
+            PlaybackMode::Loop => 0.0,
+            PlaybackMode::Once => time.clamp(0.0, length),
+        }
+    }
+
+    fn set_time(&mut self, time: f32) {
+        self.time = self.bound_time(time);
+        self.finished = false;
+        self.finished_reverse = false;
+    }
+
+    fn advance_time(&mut self, delta_time: f32) {
+        let delta_time = delta_time * self.speed;
+        if delta_time == 0.0 {
+            return;
+        }
+        let time = self.time + self.local_time(delta_time);
+        self.finished = false;
+        self.finished_reverse = false;
+        if self.mode == PlaybackMode::Once {
+            self.finished = delta_time > 0.0 && time >= self.length();
+            self.finished_reverse = delta_time < 0.0 && time <= 0.0;
+        } else {
+            self.looped |= self.length() > 0.0 && (time < 0.0 || time >= self.length());
+        }
+        self.time = self.bound_time(time);
+    }
 }
 
-#[derive(Default, Debug)]
-pub(crate) struct GraphState {
+/// The evaluated state of the clips in an [`AnimationGraph`], which
+/// [`Track`]s blend their curves with.
+///
+/// Only the current clip states can be read. With
+/// [`UpdateMode::FixedInterpolated`], the built-in tracks also blend towards
+/// the states of the previous fixed step, which custom tracks don't see.
+#[derive(Default, Debug, Clone)]
+pub struct GraphState {
     clips: Vec<ClipState>,
+    /// The clip states as of the previous fixed step, and how far between
+    /// them and the current clip states the graph should be sampled. Only
+    /// used with [`UpdateMode::FixedInterpolated`].
+    previous: Option<(Vec<ClipState>, f32)>,
+    transform_blend_mode: TransformBlendMode,
+    sync_groups: Vec<SyncGroup>,
+    /// The only clip with a weight, if it's blended normally with a weight
+    /// of 1. Set when the graph is evaluated.
+    single_clip: Option<ClipId>,
 }
 
 impl GraphState {
+    /// How far from 1 the weight of a clip played on its own may be for the
+    /// clip to be sampled directly. See [`single_clip`](Self::single_clip).
+    const SINGLE_CLIP_TOLERANCE: f32 = 1e-5;
+
+    /// Gets the clip the graph is playing on its own, as of the last time the
+    /// graph was evaluated: the only clip with a weight, if that weight is 1
+    /// and the clip isn't additive.
+    ///
+    /// The built-in tracks sample the curve of that clip directly instead of
+    /// blending it, so the applied values are exactly the curve's.
+    pub fn single_clip(&self) -> Option<ClipId> {
+        self.single_clip
+    }
+
+    /// Gets the weight a clip is blended with, as of the last time the graph
+    /// was evaluated. Clips that aren't in the graph have no weight.
+    pub fn clip_weight(&self, clip: ClipId) -> f32 {
+        self.clips
+            .get(clip.0 as usize)
+            .map_or(0.0, |clip| clip.weight)
+    }
+
+    /// Gets the time a clip's curves should be sampled at, including its trim
+    /// range, phase offset and time warp.
+    pub fn clip_sample_time(&self, clip: ClipId) -> f32 {
+        self.clips
+            .get(clip.0 as usize)
+            .map_or(0.0, ClipState::sample_time)
+    }
+
+    /// Whether a clip is blended additively. See
+    /// [`AnimationGraph::set_additive`].
+    pub fn is_clip_additive(&self, clip: ClipId) -> bool {
+        self.clips
+            .get(clip.0 as usize)
+            .map_or(false, |clip| clip.additive)
+    }
+
     /// Creates a new state for a clip. Returns the corresponding
-    /// internal ID for the clip.
-    pub fn add_clip(&mut self) -> ClipId {
-        assert!(self.clips.len() < u16::MAX as usize);
+    /// internal ID for the clip, or an error if the graph already has
+    /// [`AnimationGraph::MAX_CLIPS`] clips.
+    pub(crate) fn add_clip(&mut self, duration: f32) -> Result<ClipId, AnimationGraphError> {
+        if self.is_full() {
+            return Err(AnimationGraphError::GraphFull);
+        }
         let clip_id = ClipId(self.clips.len() as u16);
-        self.clips.push(Default::default());
-        clip_id
+        self.clips.push(ClipState::new(duration));
+        if let Some((previous, _)) = self.previous.as_mut() {
+            previous.push(ClipState::new(duration));
+        }
+        Ok(clip_id)
+    }
+
+    fn is_full(&self) -> bool {
+        self.clips.len() >= AnimationGraph::MAX_CLIPS
     }
 
     /// Sets the time for a given clip in the current state of the
-    /// graph.
+    /// graph. The time is wrapped or clamped according to the clip's
+    /// [`PlaybackMode`], and the clip is no longer considered finished.
     ///
     /// # Panics
     /// This will panic if `clip` isn't a valid `ClipId`.
-    pub fn set_time(&mut self, clip: ClipId, time: f32) {
-        self.clips[clip.0 as usize].time = time;
+    pub(crate) fn set_time(&mut self, clip: ClipId, time: f32) {
+        self.clips[clip.0 as usize].set_time(time);
     }
 
     /// Advances time by a specific delta for all clips in the
-    /// graph.
-    pub fn advance_time(&mut self, delta_time: f32) {
-        for clip in self.clips.iter_mut() {
-            clip.time += delta_time;
+    /// graph. Negative deltas play the clips in reverse.
+    ///
+    /// Clips following the leader of a sync group are not advanced directly,
+    /// and instead match the leader's phase or time after it is advanced.
+    pub(crate) fn advance_time(&mut self, delta_time: f32) {
+        for idx in 0..self.clips.len() {
+            if self.sync_leader(idx).is_none() && !self.clips[idx].paused {
+                self.clips[idx].advance_time(delta_time);
+            }
+        }
+        for idx in 0..self.clips.len() {
+            if self.clips[idx].paused {
+                continue;
+            }
+            if let Some(leader) = self.sync_leader(idx) {
+                let leader = self.clips[leader].clone();
+                let normalized = self.clips[idx].sync.map_or(false, |sync| sync.normalized);
+                self.clips[idx].follow(&leader, normalized);
+            }
+        }
+    }
+
+    /// Gets the index of the clip a clip follows, if it is in a sync group
+    /// and isn't the group's leader.
+    fn sync_leader(&self, clip: usize) -> Option<usize> {
+        let sync = self.clips[clip].sync?;
+        let leader = self.sync_groups[sync.group].leader?.0 as usize;
+        (leader != clip).then(|| leader)
+    }
+
+    /// Adds a clip to a sync group, creating the group if it doesn't exist.
+    ///
+    /// # Panics
+    /// This will panic if `clip` isn't a valid `ClipId`.
+    pub(crate) fn set_sync_group(
+        &mut self,
+        clip: ClipId,
+        name: Cow<'static, str>,
+        normalized: bool,
+    ) {
+        self.clear_sync_group(clip);
+        let group = match self.sync_groups.iter().position(|group| group.name == name) {
+            Some(group) => group,
+            None => {
+                self.sync_groups.push(SyncGroup { name, leader: None });
+                self.sync_groups.len() - 1
+            }
+        };
+        let sync = Some(ClipSync { group, normalized });
+        self.clips[clip.0 as usize].sync = sync;
+        if let Some((previous, _)) = self.previous.as_mut() {
+            previous[clip.0 as usize].sync = sync;
+        }
+    }
+
+    /// Removes a clip from its sync group.
+    ///
+    /// # Panics
+    /// This will panic if `clip` isn't a valid `ClipId`.
+    pub(crate) fn clear_sync_group(&mut self, clip: ClipId) {
+        if let Some(sync) = self.clips[clip.0 as usize].sync.take() {
+            let group = &mut self.sync_groups[sync.group];
+            if group.leader == Some(clip) {
+                group.leader = None;
+            }
+        }
+        if let Some((previous, _)) = self.previous.as_mut() {
+            previous[clip.0 as usize].sync = None;
+        }
+    }
+
+    /// Picks the clip with the highest weight in each sync group as its
+    /// leader. Groups without any weighted clips have no leader, and their
+    /// clips advance independently.
+    fn elect_sync_leaders(&mut self) {
+        for group in self.sync_groups.iter_mut() {
+            group.leader = None;
+        }
+        for (idx, clip) in self.clips.iter().enumerate() {
+            let sync = match clip.sync {
+                Some(sync) if clip.weight > 0.0 => sync,
+                _ => continue,
+            };
+            let group = &mut self.sync_groups[sync.group];
+            let is_leader = group.leader.map_or(true, |leader| {
+                clip.weight > self.clips[leader.0 as usize].weight
+            });
+            if is_leader {
+                group.leader = Some(ClipId(idx as u16));
+            }
+        }
+    }
+
+    /// Freezes or resumes the time of a clip.
+    ///
+    /// # Panics
+    /// This will panic if `clip` isn't a valid `ClipId`.
+    pub(crate) fn set_paused(&mut self, clip: ClipId, paused: bool) {
+        self.clips[clip.0 as usize].paused = paused;
+        if let Some((previous, _)) = self.previous.as_mut() {
+            previous[clip.0 as usize].paused = paused;
+        }
+    }
+
+    /// Sets the speed a clip is advanced at.
+    ///
+    /// # Panics
+    /// This will panic if `clip` isn't a valid `ClipId`.
+    pub(crate) fn set_speed(&mut self, clip: ClipId, speed: f32) {
+        self.clips[clip.0 as usize].speed = speed;
+        if let Some((previous, _)) = self.previous.as_mut() {
+            previous[clip.0 as usize].speed = speed;
+        }
+    }
+
+    /// Sets the [`PlaybackMode`] of a clip.
+    ///
+    /// # Panics
+    /// This will panic if `clip` isn't a valid `ClipId`.
+    pub(crate) fn set_playback_mode(&mut self, clip: ClipId, mode: PlaybackMode) {
+        let state = &mut self.clips[clip.0 as usize];
+        state.mode = mode;
+        state.time = state.bound_time(state.time);
+        if let Some((previous, _)) = self.previous.as_mut() {
+            let previous = &mut previous[clip.0 as usize];
+            previous.mode = mode;
+            previous.time = previous.bound_time(previous.time);
+        }
+    }
+
+    /// Sets whether the time of a clip is a normalized phase.
+    ///
+    /// # Panics
+    /// This will panic if `clip` isn't a valid `ClipId`.
+    pub(crate) fn set_normalized(&mut self, clip: ClipId, normalized: bool) {
+        self.clips[clip.0 as usize].set_normalized(normalized);
+        if let Some((previous, _)) = self.previous.as_mut() {
+            previous[clip.0 as usize].set_normalized(normalized);
+        }
+    }
+
+    /// Sets or removes the time warp of a clip.
+    ///
+    /// # Panics
+    /// This will panic if `clip` isn't a valid `ClipId`.
+    fn set_time_warp(&mut self, clip: ClipId, warp: Option<TimeWarp>) {
+        if let Some((previous, _)) = self.previous.as_mut() {
+            previous[clip.0 as usize].set_warp(warp.clone());
+        }
+        self.clips[clip.0 as usize].set_warp(warp);
+    }
+
+    /// Sets the offset added to a clip's time when it's sampled.
+    ///
+    /// # Panics
+    /// This will panic if `clip` isn't a valid `ClipId`.
+    fn set_phase_offset(&mut self, clip: ClipId, offset: f32) {
+        self.clips[clip.0 as usize].phase_offset = offset;
+        if let Some((previous, _)) = self.previous.as_mut() {
+            previous[clip.0 as usize].phase_offset = offset;
+        }
+    }
+
+    /// Sets whether a clip is blended additively.
+    ///
+    /// # Panics
+    /// This will panic if `clip` isn't a valid `ClipId`.
+    pub(crate) fn set_additive(&mut self, clip: ClipId, additive: bool) {
+        self.clips[clip.0 as usize].additive = additive;
+        if let Some((previous, _)) = self.previous.as_mut() {
+            previous[clip.0 as usize].additive = additive;
+        }
+    }
+
+    /// Changes the duration of a clip, keeping its time and weight.
+    ///
+    /// # Panics
+    /// This will panic if `clip` isn't a valid `ClipId`.
+    fn set_clip_duration(&mut self, clip: ClipId, duration: f32) {
+        self.clips[clip.0 as usize].set_clip_duration(duration);
+        if let Some((previous, _)) = self.previous.as_mut() {
+            previous[clip.0 as usize].set_clip_duration(duration);
+        }
+    }
+
+    /// Saves the current clip states as the previous fixed step, reusing the
+    /// buffer of the last saved step.
+    fn save_previous(&mut self, alpha: f32) {
+        match self.previous.as_mut() {
+            Some((previous, previous_alpha)) => {
+                previous.clone_from(&self.clips);
+                *previous_alpha = alpha;
+            }
+            None => self.previous = Some((self.clips.clone(), alpha)),
+        }
+    }
+
+    /// Blends a captured pose over the other clips, as if it was faded out
+    /// on top of them. The non-additive clips are normalized like
+    /// [`Animatable::blend`] normalizes them, and share the weight the pose
+    /// leaves. Additive clips are still applied on top. Returns whether any
+    /// weight was changed.
+    fn overlay_pose(&mut self, pose_id: ClipId) -> bool {
+        let pose_weight = self.clips[pose_id.0 as usize].weight.min(1.0);
+        if pose_weight <= 0.0 {
+            return false;
         }
+        let others: f32 = self
+            .clips
+            .iter()
+            .enumerate()
+            .filter(|(idx, clip)| *idx != pose_id.0 as usize && !clip.additive)
+            .map(|(_, clip)| clip.weight)
+            .sum();
+        let scale = (1.0 - pose_weight) / others.max(1.0);
+        for (idx, clip) in self.clips.iter_mut().enumerate() {
+            if idx == pose_id.0 as usize {
+                clip.weight = pose_weight;
+            } else if !clip.additive {
+                clip.weight *= scale;
+            }
+        }
+        true
     }
 
     /// Resets weights for all clips in the graph to 0.
-    pub fn clear_weights(&mut self) {
+    pub(crate) fn clear_weights(&mut self) {
         for clip in self.clips.iter_mut() {
             clip.weight = 0.0;
         }
@@ -60,27 +682,26 @@ impl GraphState {
     ///
     /// # Panics
     /// This will panic if `clip` isn't a valid `ClipId`.
-    pub fn add_weight(&mut self, clip: ClipId, delta_weight: f32) {
+    pub(crate) fn add_weight(&mut self, clip: ClipId, delta_weight: f32) {
         self.clips[clip.0 as usize].weight += delta_weight;
     }
 
-    /// Normalize all of the weights.
-    pub fn normalize_weights(&mut self) {
-        // Get the length of the N-dimensional weight vector.
-        let weight_sum = self
+    /// Finds the clip the graph is playing on its own, if any. See
+    /// [`single_clip`](Self::single_clip).
+    pub(crate) fn update_single_clip(&mut self) {
+        let mut weighted = self
             .clips
             .iter()
-            .map(|clip| clip.weight * clip.weight)
-            .sum::<f32>()
-            .sqrt();
-
-        if weight_sum != 0.0 {
-            return;
-        }
-
-        for clip in self.clips.iter_mut() {
-            clip.weight /= weight_sum;
-        }
+            .enumerate()
+            .filter(|(_, clip)| clip.weight != 0.0);
+        self.single_clip = match (weighted.next(), weighted.next()) {
+            (Some((idx, clip)), None)
+                if !clip.additive && (clip.weight - 1.0).abs() <= Self::SINGLE_CLIP_TOLERANCE =>
+            {
+                Some(ClipId(idx as u16))
+            }
+            _ => None,
+        };
     }
 }
 
@@ -91,20 +712,347 @@ struct GraphTraversalNode {
     cumulative_weight: f32,
 }
 
+#[derive(Error, Debug, Clone)]
 pub enum AnimationGraphError {
+    #[error("node {0:?} does not exist in the graph")]
     NodeNotFound(NodeId),
+    #[error("node {0:?} is already an input of the target node")]
     InputAlreadyExists(NodeId),
+    #[error("node {0:?} is not an input of the target node")]
+    InputNotFound(NodeId),
+    #[error("node {0:?} is not a blend node")]
     NotBlendNode(NodeId),
+    #[error("node {0:?} is not a clip node")]
+    NotClipNode(NodeId),
+    #[error("node {0:?} is not a random node")]
+    NotRandomNode(NodeId),
+    #[error("node {0:?} is not a snapshot node")]
+    NotSnapshotNode(NodeId),
+    #[error("the graph has reached its maximum number of nodes or clips")]
+    GraphFull,
+    #[error("'{0}' is not animated by the graph")]
+    BoneNotFound(EntityPath),
+    #[error("fixed update rate of {0} hz is not finite and positive")]
+    InvalidUpdateRate(f32),
+    #[error("the clip asset is not loaded")]
+    ClipNotLoaded,
+    #[error(transparent)]
+    Track(#[from] TrackError),
+}
+
+/// Controls how the clip times of an [`AnimationGraph`] are driven.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeMode {
+    /// Clip times are driven by [`AnimationGraph::advance_time`].
+    Playing,
+    /// Clip times are pinned to the last time passed to
+    /// [`AnimationGraph::sample_at`]. Calls to [`AnimationGraph::advance_time`]
+    /// are ignored in this mode, and scrubbing never produces events or
+    /// marks clips as finished.
+    Scrubbing,
+}
+
+impl Default for TimeMode {
+    fn default() -> Self {
+        Self::Playing
+    }
+}
+
+/// Controls how often the clip times of an [`AnimationGraph`] are advanced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdateMode {
+    /// Time is advanced by the full delta every time
+    /// [`AnimationGraph::advance_time`] is called.
+    PerFrame,
+    /// Time is accumulated and advanced in fixed steps of `1 / hz` seconds.
+    /// The graph is sampled by interpolating between the two most recent
+    /// steps based on the leftover accumulated time. This smooths out
+    /// fluctuating frame rates at the cost of up to one step of latency.
+    ///
+    /// `hz` must be finite and positive. At most
+    /// [`MAX_FIXED_STEPS_PER_UPDATE`] steps are taken per update, and any
+    /// further whole steps are dropped.
+    FixedInterpolated { hz: f32 },
+}
+
+/// The maximum number of fixed steps an [`AnimationGraph`] takes in one call
+/// to [`AnimationGraph::advance_time`] with [`UpdateMode::FixedInterpolated`].
+pub const MAX_FIXED_STEPS_PER_UPDATE: u32 = 8;
+
+impl Default for UpdateMode {
+    fn default() -> Self {
+        Self::PerFrame
+    }
+}
+
+/// Controls where the blended values of an [`AnimationGraph`] are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Values are applied directly to the components of the bound entities.
+    Entities,
+    /// Values are written into an [`AnimatedPose`] on the graph's entity, and
+    /// the bound entities are left untouched.
+    ///
+    /// [`AnimatedPose`]: crate::graph::pose::AnimatedPose
+    Buffer,
+}
+
+impl Default for OutputMode {
+    fn default() -> Self {
+        Self::Entities
+    }
+}
+
+/// Controls whether an [`AnimationGraph`] writes the [`Transform`] of its own
+/// entity, the root of the hierarchy it animates, when clips animate it.
+///
+/// [`Transform`]: bevy_transform::prelude::Transform
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootTransformPolicy {
+    /// The root's [`Transform`] is written like any other bone's.
+    ///
+    /// [`Transform`]: bevy_transform::prelude::Transform
+    Apply,
+    /// The root's [`Transform`] is left to other systems, such as a
+    /// character controller. Its tracks are still evaluated, and are written
+    /// again as soon as the policy is set back to [`RootTransformPolicy::Apply`].
+    ///
+    /// [`Transform`]: bevy_transform::prelude::Transform
+    Ignore,
+}
+
+impl Default for RootTransformPolicy {
+    fn default() -> Self {
+        Self::Apply
+    }
+}
+
+/// Counters collected by the last evaluation of an [`AnimationGraph`]. See
+/// [`AnimationGraph::stats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GraphStats {
+    /// The number of nodes visited while traversing the graph. Inputs with a
+    /// weight of 0 are not visited.
+    pub nodes_visited: u32,
+    /// The number of clips and snapshots blended with a nonzero weight.
+    pub active_clips: u32,
+}
+
+/// A clip added from an asset. See [`AnimationGraph::add_clip_asset`].
+#[derive(Clone)]
+struct ClipAsset {
+    handle: Handle<AnimationClip>,
+    clip_id: ClipId,
+    // Only set for clips added with add_lazy_clip_asset.
+    eviction: Option<ClipEviction>,
+}
+
+impl ClipAsset {
+    fn is_evicted(&self) -> bool {
+        matches!(&self.eviction, Some(eviction) if eviction.evicted)
+    }
+
+    fn is_requested(&self) -> bool {
+        matches!(&self.eviction, Some(eviction) if eviction.requested)
+    }
+}
+
+#[derive(Clone)]
+struct ClipEviction {
+    timeout: f32,
+    // How long the clip's weight has been zero.
+    idle_time: f32,
+    evicted: bool,
+    // Set when an evicted clip is given a weight, until it's restored.
+    requested: bool,
 }
 
 #[derive(Component)]
 pub struct AnimationGraph {
+    // Stored in the graph's bindings so that they are not adopted by other
+    // graphs later added to the same entity.
+    nonce: u32,
     nodes: GraphNodes,
+    // Only used for debug output.
+    labels: HashMap<NodeId, Cow<'static, str>>,
+    // Clip nodes by the names and tags of the clips they were added from.
+    clip_names: HashMap<Cow<'static, str>, NodeId>,
+    clip_tags: HashMap<Cow<'static, str>, Vec<NodeId>>,
+    params: GraphParams,
+    // Input weights used instead of the weights stored in the nodes, keyed by
+    // (target, input). Kept apart from the nodes so that graphs built from the
+    // same template can blend differently without editing their structure.
+    weight_overrides: HashMap<(NodeId, NodeId), f32>,
+    // Curves from clips driving the parameters, in the order the clips were
+    // added.
+    param_curves: Vec<ParamCurve>,
     state: GraphState,
     clips: GraphClips,
+    // The assets that clips were added from, so they can be reloaded when
+    // the assets are modified.
+    clip_assets: Vec<ClipAsset>,
+    time_mode: TimeMode,
+    update_mode: UpdateMode,
+    output_mode: OutputMode,
+    root_transform_policy: RootTransformPolicy,
+    // Bones with more tracks than this are blended across multiple tasks when
+    // the graph is applied.
+    track_split_threshold: Option<usize>,
+    accumulated_time: f32,
+    update_interval: f32,
+    // Time passed to advance_time that hasn't been applied yet because the
+    // update interval hasn't elapsed.
+    interval_time: f32,
+    update_skipped: bool,
+    // The sum of the clip weights, as of the last evaluation.
+    total_weight: f32,
+    stats: GraphStats,
+    // Only collected while profiling, as it's updated by every applied track.
+    track_stats: Option<Box<TrackStatsStorage>>,
+    // Picks the active inputs of random nodes.
+    rng: GraphRng,
+    // Set while crossfading from a captured pose into the graph.
+    pose_fade: Option<PoseFade>,
+    // The snapshot node every captured pose is stored in, once a pose has
+    // been captured.
+    snapshot: Option<NodeId>,
+    // Accumulates weights in fixed-point while evaluating deterministically.
+    deterministic: bool,
+    // Distance matching tables for the clips, built when first requested.
+    root_distances: RootDistances,
+    root_distance_resolution: f32,
+    // Debugging overrides applied while evaluating, kept apart from the
+    // authored weights so that clearing them restores the blend exactly.
+    muted: HashSet<NodeId>,
+    soloed: Vec<ClipId>,
+    // Scratch buffers reused between traversals to avoid allocations.
+    traversal: SmallVec<[GraphTraversalNode; 16]>,
+    pending: SmallVec<[NodeId; 16]>,
+    fixed_weights: Vec<FixedWeight>,
+}
+
+impl Default for AnimationGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Registered as an opaque value, so that tools can find graphs in the type
+// registry. Graphs are deliberately not registered with ReflectComponent, so
+// that clips can't animate them. See animate_entities_system.
+impl_reflect_value!(AnimationGraph);
+
+/// Clones share their tracks with the original graph until either is modified.
+/// A clone is a new graph: it has its own nonce and random number generator
+/// state, and must be bound to its own hierarchy.
+impl Clone for AnimationGraph {
+    fn clone(&self) -> Self {
+        let nonce = NEXT_GRAPH_NONCE.fetch_add(1, Ordering::Relaxed);
+        let mut clips = self.clips.clone();
+        clips.set_dirty(true);
+        Self {
+            nonce,
+            nodes: self.nodes.clone(),
+            labels: self.labels.clone(),
+            clip_names: self.clip_names.clone(),
+            clip_tags: self.clip_tags.clone(),
+            params: self.params.clone(),
+            weight_overrides: self.weight_overrides.clone(),
+            param_curves: self.param_curves.clone(),
+            state: self.state.clone(),
+            clips,
+            clip_assets: self.clip_assets.clone(),
+            time_mode: self.time_mode,
+            update_mode: self.update_mode,
+            output_mode: self.output_mode,
+            root_transform_policy: self.root_transform_policy,
+            track_split_threshold: self.track_split_threshold,
+            accumulated_time: self.accumulated_time,
+            update_interval: self.update_interval,
+            interval_time: self.interval_time,
+            update_skipped: self.update_skipped,
+            total_weight: self.total_weight,
+            stats: self.stats,
+            track_stats: self.track_stats.as_ref().map(|_| Box::default()),
+            rng: GraphRng::new(nonce as u64),
+            pose_fade: self.pose_fade.clone(),
+            snapshot: self.snapshot,
+            deterministic: self.deterministic,
+            root_distances: self.root_distances.clone(),
+            root_distance_resolution: self.root_distance_resolution,
+            muted: self.muted.clone(),
+            soloed: self.soloed.clone(),
+            traversal: SmallVec::new(),
+            pending: SmallVec::new(),
+            fixed_weights: Vec::new(),
+        }
+    }
 }
 
 impl AnimationGraph {
+    /// The maximum number of nodes in a graph, including the root.
+    pub const MAX_NODES: usize = u16::MAX as usize + 1;
+    /// The maximum number of clips in a graph. Every clip node and
+    /// snapshot node has its own clip.
+    pub const MAX_CLIPS: usize = u16::MAX as usize;
+
+    /// Creates an empty graph with only a root blend node.
+    pub fn new() -> Self {
+        Self::with_capacity(0, 0)
+    }
+
+    /// Creates an empty graph with only a root blend node, with space
+    /// preallocated for `nodes` nodes and `clips` clips.
+    pub fn with_capacity(nodes: usize, clips: usize) -> Self {
+        let mut nodes = GraphNodes::with_capacity(nodes);
+        // An empty graph can't be full.
+        let _ = nodes.add(Node::Blend {
+            inputs: Vec::new(),
+            propogate_time: true,
+        });
+        let state = GraphState {
+            clips: Vec::with_capacity(clips),
+            ..Default::default()
+        };
+        let nonce = NEXT_GRAPH_NONCE.fetch_add(1, Ordering::Relaxed);
+        Self {
+            nonce,
+            nodes,
+            labels: HashMap::default(),
+            clip_names: HashMap::default(),
+            clip_tags: HashMap::default(),
+            params: GraphParams::default(),
+            weight_overrides: HashMap::default(),
+            param_curves: Vec::new(),
+            state,
+            clips: GraphClips::default(),
+            clip_assets: Vec::new(),
+            time_mode: TimeMode::default(),
+            update_mode: UpdateMode::default(),
+            output_mode: OutputMode::default(),
+            root_transform_policy: RootTransformPolicy::default(),
+            track_split_threshold: None,
+            accumulated_time: 0.0,
+            update_interval: 0.0,
+            interval_time: 0.0,
+            update_skipped: false,
+            total_weight: 0.0,
+            stats: GraphStats::default(),
+            track_stats: None,
+            rng: GraphRng::new(nonce as u64),
+            pose_fade: None,
+            snapshot: None,
+            deterministic: false,
+            root_distances: RootDistances::default(),
+            root_distance_resolution: distance::DEFAULT_RESOLUTION,
+            muted: HashSet::default(),
+            soloed: Vec::new(),
+            traversal: SmallVec::new(),
+            pending: SmallVec::new(),
+            fixed_weights: Vec::new(),
+        }
+    }
+
     pub fn add_input(
         &mut self,
         target: NodeId,
@@ -116,6 +1064,7 @@ impl AnimationGraph {
             .get(input)
             .ok_or(AnimationGraphError::NodeNotFound(input))?;
 
+        let target_id = target;
         let target = self
             .nodes
             .get_mut(target)
@@ -126,131 +1075,3610 @@ impl AnimationGraph {
         } else if let Node::Blend { inputs, .. } = target {
             inputs.push(NodeInput::new(input));
             Ok(inputs.last_mut().unwrap())
+        } else if let Node::Random { inputs, .. } = target {
+            inputs.push((1.0, NodeInput::new(input)));
+            Ok(&mut inputs.last_mut().unwrap().1)
         } else {
-            Err(AnimationGraphError::NotBlendNode(input))
+            Err(AnimationGraphError::NotBlendNode(target_id))
         }
     }
 
-    /// Adds an [`AnimationClip`] as a node in the graph.
+    /// Overrides the weight of the input from `input` into the blend node
+    /// `target`, without modifying the input itself.
     ///
-    /// Returns the corresponding node ID.
-    pub fn add_clip(&mut self, clip: &AnimationClip) -> NodeId {
-        let clip_id = self.state.add_clip();
-        // TODO: Handle the error from this call.
-        self.clips.add_clip(clip_id, clip);
-        self.nodes.add(Node::Clip { clip: clip_id })
+    /// While set, the override is used instead of the input's weight whenever
+    /// the graph is evaluated, including weights driven by a
+    /// [`WeightBinding`]. The input's easing still applies. This lets many
+    /// graphs built from the same template share its structure and defaults
+    /// while each blends differently.
+    pub fn override_input_weight(
+        &mut self,
+        target: NodeId,
+        input: NodeId,
+        weight: f32,
+    ) -> Result<(), AnimationGraphError> {
+        match self.nodes.get(target) {
+            Some(Node::Blend { inputs, .. }) => {
+                if !inputs
+                    .iter()
+                    .any(|node_input| node_input.node_id() == input)
+                {
+                    return Err(AnimationGraphError::InputNotFound(input));
+                }
+            }
+            Some(_) => return Err(AnimationGraphError::NotBlendNode(target)),
+            None => return Err(AnimationGraphError::NodeNotFound(target)),
+        }
+        self.weight_overrides.insert((target, input), weight);
+        Ok(())
     }
 
-    /// Advances the time for all clips in the graph by a set delta.
-    /// This function allows for negative time deltas.
-    pub fn advance_time(&mut self, delta_time: f32) {
-        self.state.advance_time(delta_time);
+    /// Removes the override of the weight of an input set with
+    /// [`override_input_weight`](Self::override_input_weight), returning the
+    /// overridden weight. The input's own weight is used again.
+    pub fn clear_override(&mut self, target: NodeId, input: NodeId) -> Option<f32> {
+        self.weight_overrides.remove(&(target, input))
     }
 
-    pub fn bones(&self) -> impl Iterator<Item = &Bone> {
-        self.clips.bones()
+    /// Gets the weight an input is overridden with, if any. See
+    /// [`override_input_weight`](Self::override_input_weight).
+    pub fn input_weight_override(&self, target: NodeId, input: NodeId) -> Option<f32> {
+        self.weight_overrides.get(&(target, input)).copied()
     }
 
-    pub(crate) fn bones_mut(&mut self) -> impl Iterator<Item = &mut Bone> {
-        self.clips.bones_mut()
+    /// Iterates over the inputs of the node `target`. Only blend and random
+    /// nodes have inputs.
+    pub fn inputs(
+        &self,
+        target: NodeId,
+    ) -> Result<impl Iterator<Item = &NodeInput>, AnimationGraphError> {
+        self.nodes
+            .get(target)
+            .map(Node::inputs)
+            .ok_or(AnimationGraphError::NodeNotFound(target))
     }
 
-    pub fn find_bone(&self, path: &EntityPath) -> Option<&Bone> {
-        self.clips.find_bone(path)
+    /// Gets the input from `input` into the blend or random node `target`.
+    pub fn input(&self, target: NodeId, input: NodeId) -> Result<&NodeInput, AnimationGraphError> {
+        self.nodes
+            .get(target)
+            .ok_or(AnimationGraphError::NodeNotFound(target))?
+            .inputs()
+            .find(|node_input| node_input.node_id() == input)
+            .ok_or(AnimationGraphError::InputNotFound(input))
     }
 
-    pub fn find_bone_mut(&mut self, path: &EntityPath) -> Option<&mut Bone> {
-        self.clips.find_bone_mut(path)
+    /// Gets the input from `input` into the blend or random node `target`
+    /// to modify it. Use [`input`](Self::input) to only read it, which
+    /// doesn't mark the graph as changed.
+    pub fn input_mut(
+        &mut self,
+        target: NodeId,
+        input: NodeId,
+    ) -> Result<&mut NodeInput, AnimationGraphError> {
+        self.nodes
+            .get_mut(target)
+            .ok_or(AnimationGraphError::NodeNotFound(target))?
+            .get_input_mut(input)
+            .ok_or(AnimationGraphError::InputNotFound(input))
     }
 
-    pub(crate) fn get_bone(&self, id: BoneId) -> Option<&Bone> {
-        self.clips.get_bone(id)
+    /// Adds an [`AnimationClip`] as a node in the graph.
+    ///
+    /// Returns the corresponding node ID, or an error if the clip animates a
+    /// property already animated by the graph with a different type, or if the
+    /// graph is full. The graph is left unchanged on failure.
+    ///
+    /// The node blends additively if the clip is additive. The clip's name
+    /// and tags are recorded for [`find_clip_by_name`](Self::find_clip_by_name)
+    /// and [`clips_with_tag`](Self::clips_with_tag).
+    pub fn add_clip(&mut self, clip: &AnimationClip) -> Result<NodeId, AnimationGraphError> {
+        if self.nodes.is_full() || self.state.is_full() {
+            return Err(AnimationGraphError::GraphFull);
+        }
+        self.clips.check_clip(clip)?;
+        let clip_id = self.state.add_clip(clip.duration())?;
+        self.state.set_additive(clip_id, clip.is_additive());
+        self.clips.add_clip(clip_id, clip)?;
+        self.add_param_curves(clip_id, clip);
+        let node_id = self.nodes.add(Node::Clip { clip: clip_id })?;
+        self.add_clip_metadata(node_id, clip);
+        self.log_ignored_root_transform(clip);
+        Ok(node_id)
     }
 
-    pub(crate) fn update_bone(&mut self, path: &EntityPath, entity: Option<Entity>) {
-        if let Some(bone) = self.find_bone_mut(path) {
-            bone.set_entity(entity);
+    /// Adds several [`AnimationClip`]s as nodes in the graph, returning their
+    /// node IDs in order. Faster than adding them one at a time with
+    /// [`add_clip`](Self::add_clip), as space for all of their bones is
+    /// reserved up front.
+    ///
+    /// The whole batch is checked before any of it is added: if any clip
+    /// animates a property with a different type than the graph or another
+    /// clip in the batch, or the batch doesn't fit in the graph, an error is
+    /// returned and the graph is left unchanged.
+    pub fn add_clips<'a>(
+        &mut self,
+        clips: impl IntoIterator<Item = &'a AnimationClip>,
+    ) -> Result<Vec<NodeId>, AnimationGraphError> {
+        let clips: Vec<_> = clips.into_iter().collect();
+        if self.nodes.count() + clips.len() > Self::MAX_NODES
+            || self.state.clips.len() + clips.len() > Self::MAX_CLIPS
+        {
+            return Err(AnimationGraphError::GraphFull);
+        }
+        self.clips.check_clips(&clips)?;
+
+        self.nodes.reserve(clips.len());
+        self.state.clips.reserve(clips.len());
+        let mut added = Vec::with_capacity(clips.len());
+        for clip in clips.iter() {
+            let clip_id = self.state.add_clip(clip.duration())?;
+            self.state.set_additive(clip_id, clip.is_additive());
+            added.push((clip_id, *clip));
         }
+        self.clips.add_checked_clips(&added)?;
+        added
+            .into_iter()
+            .map(|(clip_id, clip)| {
+                self.add_param_curves(clip_id, clip);
+                let node_id = self.nodes.add(Node::Clip { clip: clip_id })?;
+                self.add_clip_metadata(node_id, clip);
+                self.log_ignored_root_transform(clip);
+                Ok(node_id)
+            })
+            .collect()
     }
 
-    /// Sets the time for a given node. If the node is set to propagate its
-    /// time, all of it's currently connected inputs will also have the time
-    /// propagated to them as well.
-    pub fn set_time(&mut self, node_id: NodeId, time: f32) -> Result<(), AnimationGraphError> {
-        self.nodes
-            .get_mut(node_id)
-            .ok_or(AnimationGraphError::NodeNotFound(node_id))?;
+    /// Reserves space for at least `bones` more bones and `clips` more clips,
+    /// so that adding them doesn't reallocate. See
+    /// [`add_clips`](Self::add_clips), which reserves space for its clips
+    /// itself.
+    pub fn reserve(&mut self, bones: usize, clips: usize) {
+        self.clips.reserve(bones);
+        self.nodes.reserve(clips);
+        self.state.clips.reserve(clips);
+    }
 
-        // TODO: Cache this to avoid allocations in the future.
-        let mut pending = VecDeque::new();
-        pending.push_back(node_id);
-        while let Some(node_id) = pending.pop_front() {
-            let node = if let Some(node) = self.nodes.get(node_id) {
-                node
-            } else {
+    /// Adds a loaded [`AnimationClip`] asset as a node in the graph, like
+    /// [`add_clip`](Self::add_clip).
+    ///
+    /// The graph keeps the handle, and the clip's curves are reloaded when
+    /// the asset is modified. See [`reload_clip`](Self::reload_clip).
+    pub fn add_clip_asset(
+        &mut self,
+        handle: &Handle<AnimationClip>,
+        clips: &Assets<AnimationClip>,
+    ) -> Result<NodeId, AnimationGraphError> {
+        let clip = clips
+            .get(handle)
+            .ok_or(AnimationGraphError::ClipNotLoaded)?;
+        let node_id = self.add_clip(clip)?;
+        let clip_id = self.clip_id(node_id)?;
+        self.clip_assets.push(ClipAsset {
+            handle: handle.clone(),
+            clip_id,
+            eviction: None,
+        });
+        Ok(node_id)
+    }
+
+    /// Adds a loaded [`AnimationClip`] asset as a node in the graph, like
+    /// [`add_clip_asset`](Self::add_clip_asset), whose curves are evicted once
+    /// its weight has been zero for `timeout` seconds of graph time.
+    ///
+    /// Evicting a clip keeps its bones and tracks, so the entities it animates
+    /// stay bound, but replaces each of its curves with a constant, releasing
+    /// the graph's references to their keyframes. When an evicted clip is
+    /// given a weight again, it contributes nothing for that frame, and its
+    /// curves are restored from the asset by [`restore_evicted_clips`]
+    /// before the graph is evaluated again.
+    ///
+    /// Useful for graphs holding many situational clips, such as emotes, that
+    /// go unused for long periods. See
+    /// [`resident_curve_bytes`](Self::resident_curve_bytes).
+    ///
+    /// [`restore_evicted_clips`]: Self::restore_evicted_clips
+    pub fn add_lazy_clip_asset(
+        &mut self,
+        handle: &Handle<AnimationClip>,
+        clips: &Assets<AnimationClip>,
+        timeout: f32,
+    ) -> Result<NodeId, AnimationGraphError> {
+        let node_id = self.add_clip_asset(handle, clips)?;
+        self.clip_assets.last_mut().unwrap().eviction = Some(ClipEviction {
+            timeout,
+            idle_time: 0.0,
+            evicted: false,
+            requested: false,
+        });
+        Ok(node_id)
+    }
+
+    /// Whether the curves of a clip node added with
+    /// [`add_lazy_clip_asset`](Self::add_lazy_clip_asset) are evicted.
+    pub fn is_clip_evicted(&self, node_id: NodeId) -> Result<bool, AnimationGraphError> {
+        let clip_id = self.clip_id(node_id)?;
+        Ok(self
+            .clip_assets
+            .iter()
+            .any(|asset| asset.clip_id == clip_id && asset.is_evicted()))
+    }
+
+    /// Restores the curves of every evicted clip that was given a weight
+    /// since it was evicted, from its asset. Clips whose assets aren't loaded
+    /// stay evicted. Called by [`restore_evicted_clips_system`] before graphs
+    /// are evaluated.
+    ///
+    /// [`restore_evicted_clips_system`]: crate::restore_evicted_clips_system
+    pub fn restore_evicted_clips(
+        &mut self,
+        clips: &Assets<AnimationClip>,
+    ) -> Result<(), AnimationGraphError> {
+        for idx in 0..self.clip_assets.len() {
+            let asset = &self.clip_assets[idx];
+            if !asset.is_requested() {
                 continue;
+            }
+            let clip_id = asset.clip_id;
+            let clip = match clips.get(&asset.handle) {
+                Some(clip) => clip,
+                None => continue,
             };
+            self.reload_clip_id(clip_id, clip)?;
+            if let Some(eviction) = self.clip_assets[idx].eviction.as_mut() {
+                eviction.idle_time = 0.0;
+                eviction.evicted = false;
+                eviction.requested = false;
+            }
+        }
+        Ok(())
+    }
 
-            match node {
-                Node::Clip { clip } => {
-                    self.state.set_time(*clip, time);
-                }
-                Node::Blend {
-                    inputs,
-                    propogate_time,
-                } => {
-                    if *propogate_time {
-                        pending.extend(
-                            inputs
-                                .iter()
-                                .filter(|input| input.is_connected())
-                                .map(|input| input.node_id()),
-                        );
-                    }
-                }
+    /// Whether any evicted clip is waiting to be restored by
+    /// [`restore_evicted_clips`](Self::restore_evicted_clips).
+    pub fn has_requested_clips(&self) -> bool {
+        self.clip_assets.iter().any(ClipAsset::is_requested)
+    }
+
+    /// An estimate of the memory used by the curves the graph references, in
+    /// bytes, counting the keyframes of each curve. Curves shared between
+    /// tracks, or with the clips the graph was built from, are counted once.
+    pub fn resident_curve_bytes(&self) -> usize {
+        self.clips.resident_curve_bytes()
+    }
+
+    /// Gets the handle of the asset a clip node was added from, if it was
+    /// added with [`add_clip_asset`](Self::add_clip_asset).
+    pub fn clip_asset(&self, node_id: NodeId) -> Option<&Handle<AnimationClip>> {
+        let clip_id = self.clip_id(node_id).ok()?;
+        self.clip_assets
+            .iter()
+            .find(|asset| asset.clip_id == clip_id)
+            .map(|asset| &asset.handle)
+    }
+
+    /// Replaces the curves of a clip node with the curves of a new version of
+    /// its clip. The clip's time and weight are kept.
+    ///
+    /// Curves that change the type of an already animated property are
+    /// skipped with a warning, and the property keeps its old curve. Bones
+    /// only animated by the new version are added, and properties it no
+    /// longer animates are removed. As with [`add_clip`](Self::add_clip), the
+    /// reloaded curves are not [optimized](Self::optimize).
+    pub fn reload_clip(
+        &mut self,
+        node_id: NodeId,
+        clip: &AnimationClip,
+    ) -> Result<(), AnimationGraphError> {
+        let clip_id = self.clip_id(node_id)?;
+        self.reload_clip_id(clip_id, clip)
+    }
+
+    fn reload_clip_id(
+        &mut self,
+        clip_id: ClipId,
+        clip: &AnimationClip,
+    ) -> Result<(), AnimationGraphError> {
+        self.clips.reload_clip(clip_id, clip)?;
+        self.root_distances.invalidate(clip_id);
+        self.state.set_clip_duration(clip_id, clip.duration());
+        self.state.set_additive(clip_id, clip.is_additive());
+        self.param_curves
+            .retain(|param_curve| param_curve.clip_id != clip_id);
+        self.add_param_curves(clip_id, clip);
+        let node_id = self.nodes.iter().find_map(|(node_id, node)| match node {
+            Node::Clip { clip } if *clip == clip_id => Some(node_id),
+            _ => None,
+        });
+        if let Some(node_id) = node_id {
+            self.clip_names.retain(|_, named| *named != node_id);
+            for nodes in self.clip_tags.values_mut() {
+                nodes.retain(|tagged| *tagged != node_id);
             }
+            self.clip_tags.retain(|_, nodes| !nodes.is_empty());
+            self.add_clip_metadata(node_id, clip);
+        }
+        Ok(())
+    }
+
+    /// Records the name and tags of the clip a node was added from.
+    fn add_clip_metadata(&mut self, node_id: NodeId, clip: &AnimationClip) {
+        if let Some(name) = &clip.name {
+            self.clip_names.insert(name.clone(), node_id);
+        }
+        for tag in clip.tags.iter() {
+            self.clip_tags.entry(tag.clone()).or_default().push(node_id);
+        }
+    }
+
+    /// Logs that a clip's curves for the root's [`Transform`] won't be written,
+    /// as they would otherwise be silently dropped.
+    fn log_ignored_root_transform(&self, clip: &AnimationClip) {
+        if self.root_transform_policy != RootTransformPolicy::Ignore {
+            return;
+        }
+        let animates_root = clip.properties().any(|path| {
+            path.entity().is_root()
+                && path.access().component_type_id() == TypeId::of::<Transform>()
+        });
+        if animates_root {
+            info!(
+                "AnimationClip '{}' animates the Transform of the graph's root, which is ignored by the graph's RootTransformPolicy.",
+                clip.name().unwrap_or("<unnamed>")
+            );
+        }
+    }
+
+    /// Finds the clip node added from a clip with the given name. See
+    /// [`AnimationClip::set_name`].
+    ///
+    /// Names aren't required to be unique: if several clips with the same
+    /// name were added, the last one added wins.
+    pub fn find_clip_by_name(&self, name: &str) -> Option<NodeId> {
+        self.clip_names.get(name).copied()
+    }
+
+    /// Iterates over the clip nodes added from clips with the given tag. See
+    /// [`AnimationClip::add_tag`].
+    pub fn clips_with_tag(&self, tag: &str) -> impl Iterator<Item = NodeId> + '_ {
+        self.clip_tags.get(tag).into_iter().flatten().copied()
+    }
+
+    fn add_param_curves(&mut self, clip_id: ClipId, clip: &AnimationClip) {
+        let mut params: SmallVec<[_; 4]> = clip.params.iter().collect();
+        // Keep the order parameters are set in deterministic.
+        params.sort_unstable_by_key(|(param, _)| *param);
+        for (param, curve) in params {
+            self.param_curves.push(ParamCurve {
+                clip_id,
+                param: param.clone(),
+                curve: curve.clone(),
+            });
         }
+    }
 
+    /// Reloads every clip added from the asset with the given handle.
+    pub(crate) fn reload_clip_asset(
+        &mut self,
+        handle: &Handle<AnimationClip>,
+        clip: &AnimationClip,
+    ) -> Result<(), AnimationGraphError> {
+        for idx in 0..self.clip_assets.len() {
+            let asset = &self.clip_assets[idx];
+            // Evicted clips pick up the new version when they're restored.
+            if asset.handle == *handle && !asset.is_evicted() {
+                self.reload_clip_id(asset.clip_id, clip)?;
+            }
+        }
         Ok(())
     }
 
-    /// Evaluates the graph, computing the influences individual results.
-    pub fn evaluate(&mut self) {
-        self.state.clear_weights();
+    /// Whether any clip of the graph was added from the asset with the given
+    /// handle.
+    pub(crate) fn references_clip_asset(&self, handle: &Handle<AnimationClip>) -> bool {
+        self.clip_assets.iter().any(|asset| asset.handle == *handle)
+    }
 
-        // TODO: Use smallvec to avoid allocation here.
-        let mut stack = vec![GraphTraversalNode {
-            node_id: NodeId::ROOT,
-            cumulative_weight: 1.0,
-        }];
+    /// The number of nodes in the graph, including the root.
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.nodes.count()
+    }
 
-        // Conduct a depth-first traversal of the graph multiplying the weights
-        // as it gets deeper into the tree.
-        while let Some(current) = stack.pop() {
-            let current_node = if let Some(node) = self.nodes.get(current.node_id) {
-                node
-            } else {
-                continue;
-            };
+    /// Whether the graph has a node with the given ID. IDs are only
+    /// meaningful for the graph that created them, or an identically built
+    /// one, so IDs loaded from saved data should be checked before use.
+    #[inline]
+    pub fn contains_node(&self, node_id: NodeId) -> bool {
+        self.nodes.get(node_id).is_some()
+    }
 
-            match &current_node {
-                Node::Clip { clip } => {
-                    self.state.add_weight(*clip, current.cumulative_weight);
+    /// The number of clips in the graph, including the poses of snapshot
+    /// nodes.
+    #[inline]
+    pub fn clip_count(&self) -> usize {
+        self.state.clips.len()
+    }
+
+    /// Advances the time for all clips in the graph by a set delta.
+    /// This function allows for negative time deltas.
+    ///
+    /// This does nothing while the graph is in [`TimeMode::Scrubbing`]. With
+    /// [`UpdateMode::FixedInterpolated`], the time is only advanced in fixed
+    /// steps, and any leftover time is carried over to the next call.
+    ///
+    /// If the graph has an [update interval](Self::set_update_interval), the
+    /// delta is accumulated until the interval has elapsed, and the clips are
+    /// then advanced by the full accumulated delta at once.
+    pub fn advance_time(&mut self, delta_time: f32) {
+        if self.time_mode != TimeMode::Playing {
+            return;
+        }
+        self.interval_time += delta_time;
+        self.update_skipped = self.interval_time.abs() < self.update_interval;
+        if self.update_skipped {
+            return;
+        }
+        let delta_time = std::mem::take(&mut self.interval_time);
+        self.advance_pose_fade(delta_time);
+        self.evict_idle_clips(delta_time);
+        match self.update_mode {
+            UpdateMode::PerFrame => self.state.advance_time(delta_time),
+            UpdateMode::FixedInterpolated { hz } => {
+                let step = hz.recip();
+                self.accumulated_time += delta_time;
+                let mut steps = 0;
+                while self.accumulated_time.abs() >= step && steps < MAX_FIXED_STEPS_PER_UPDATE {
+                    let delta = step.copysign(self.accumulated_time);
+                    self.state.save_previous(0.0);
+                    self.state.advance_time(delta);
+                    self.accumulated_time -= delta;
+                    steps += 1;
                 }
-                Node::Blend { inputs, .. } => {
-                    for input in inputs.iter().filter(|input| input.is_connected()) {
-                        let cumulative_weight = input.weight() * current.cumulative_weight;
-                        if cumulative_weight != 0.0 {
-                            stack.push(GraphTraversalNode {
-                                node_id: input.node_id(),
-                                cumulative_weight,
-                            });
-                        }
-                    }
+                // Drop the whole steps that didn't fit, so that a long stall
+                // doesn't make every following update catch up as well.
+                self.accumulated_time %= step;
+                if let Some((_, alpha)) = self.state.previous.as_mut() {
+                    *alpha = self.accumulated_time.abs() * hz;
                 }
             }
         }
+    }
+
+    /// Gets how [`Transform`](bevy_transform::prelude::Transform)s animated
+    /// by the graph are blended.
+    #[inline]
+    pub fn transform_blend_mode(&self) -> TransformBlendMode {
+        self.state.transform_blend_mode
+    }
+
+    /// Sets how [`Transform`](bevy_transform::prelude::Transform)s animated
+    /// by the graph are blended.
+    ///
+    /// This only applies to tracks animating entire transforms. See
+    /// [`AnimationGraph::optimize`] for fusing per-field tracks.
+    pub fn set_transform_blend_mode(&mut self, mode: TransformBlendMode) {
+        self.state.transform_blend_mode = mode;
+    }
+
+    /// Gets how often the clip times of the graph are advanced.
+    #[inline]
+    pub fn update_mode(&self) -> UpdateMode {
+        self.update_mode
+    }
+
+    /// Sets how often the clip times of the graph are advanced. Any time
+    /// accumulated towards the next fixed step is discarded.
+    ///
+    /// Returns an error, leaving the mode unchanged, if the rate of an
+    /// [`UpdateMode::FixedInterpolated`] mode isn't finite and positive.
+    pub fn set_update_mode(&mut self, update_mode: UpdateMode) -> Result<(), AnimationGraphError> {
+        if let UpdateMode::FixedInterpolated { hz } = update_mode {
+            if !hz.is_finite() || hz <= 0.0 {
+                return Err(AnimationGraphError::InvalidUpdateRate(hz));
+            }
+        }
+        self.update_mode = update_mode;
+        self.accumulated_time = 0.0;
+        match update_mode {
+            UpdateMode::PerFrame => self.state.previous = None,
+            UpdateMode::FixedInterpolated { .. } => self.state.save_previous(0.0),
+        }
+        Ok(())
+    }
+
+    /// Gets the minimum time between updates of the graph, in seconds.
+    #[inline]
+    pub fn update_interval(&self) -> f32 {
+        self.update_interval
+    }
+
+    /// Sets the minimum time between updates of the graph, in seconds. An
+    /// interval of 0 updates the graph every time
+    /// [`advance_time`](Self::advance_time) is called.
+    ///
+    /// This is intended for reducing the cost of distant or otherwise
+    /// unimportant graphs. See [`AnimationLod`] for driving it from a
+    /// component. Time accumulated towards the next update is kept.
+    ///
+    /// [`AnimationLod`]: crate::graph::lod::AnimationLod
+    pub fn set_update_interval(&mut self, seconds: f32) {
+        self.update_interval = seconds.max(0.0);
+    }
+
+    /// Whether the last call to [`advance_time`](Self::advance_time) was
+    /// deferred because the update interval hasn't elapsed yet. Skipped
+    /// graphs are neither evaluated nor applied.
+    #[inline]
+    pub fn is_update_skipped(&self) -> bool {
+        self.update_skipped
+    }
+
+    /// The sum of the weights of every clip as of the last evaluation. Graphs
+    /// with a total weight of 0 are not applied.
+    #[inline]
+    pub fn total_weight(&self) -> f32 {
+        self.total_weight
+    }
+
+    /// Counters collected by the last evaluation of the graph.
+    #[inline]
+    pub fn stats(&self) -> GraphStats {
+        self.stats
+    }
+
+    /// Starts or stops collecting [`TrackStats`] for every track written to
+    /// the bound entities. Stopping discards the collected stats.
+    ///
+    /// Meant for profiling: collecting stats clones every written value.
+    /// Typed components are written by their own systems, and aren't
+    /// counted.
+    pub fn enable_track_stats(&mut self, enabled: bool) {
+        if !enabled {
+            self.track_stats = None;
+        } else if self.track_stats.is_none() {
+            self.track_stats = Some(Box::default());
+        }
+    }
+
+    #[inline]
+    pub fn is_track_stats_enabled(&self) -> bool {
+        self.track_stats.is_some()
+    }
+
+    /// Iterates over the [`TrackStats`] collected for every track written
+    /// since they were enabled or last reset. See
+    /// [`enable_track_stats`](Self::enable_track_stats).
+    pub fn track_stats(&self) -> impl Iterator<Item = (PropertyPath, TrackStats)> {
+        self.track_stats
+            .as_ref()
+            .map(|stats| stats.collect())
+            .unwrap_or_default()
+            .into_iter()
+    }
+
+    /// Clears the collected [`TrackStats`], if they're enabled.
+    pub fn reset_track_stats(&mut self) {
+        if let Some(stats) = &mut self.track_stats {
+            stats.clear();
+        }
+    }
+
+    #[inline]
+    pub fn output_mode(&self) -> OutputMode {
+        self.output_mode
+    }
+
+    /// Sets where the blended values of the graph are written. See
+    /// [`OutputMode`].
+    pub fn set_output_mode(&mut self, output_mode: OutputMode) {
+        self.output_mode = output_mode;
+    }
+
+    #[inline]
+    pub fn root_transform_policy(&self) -> RootTransformPolicy {
+        self.root_transform_policy
+    }
+
+    /// Sets whether the [`Transform`] of the graph's own entity is written
+    /// when clips animate it, through an empty entity path. Takes effect the
+    /// next time the graph is applied. See [`RootTransformPolicy`].
+    ///
+    /// Only affects values applied to entities: [`AnimatedPose`]s and
+    /// [`sample_pose`](Self::sample_pose) still include the root's
+    /// [`Transform`].
+    ///
+    /// [`Transform`]: bevy_transform::prelude::Transform
+    /// [`AnimatedPose`]: crate::graph::pose::AnimatedPose
+    pub fn set_root_transform_policy(&mut self, policy: RootTransformPolicy) {
+        self.root_transform_policy = policy;
+    }
+
+    /// Whether a property of a bone is written to its bound entity, according
+    /// to the bone's [`WriteMask`] and the graph's [`RootTransformPolicy`].
+    pub(crate) fn writes(&self, bone: &Bone, property: &AccessPath) -> bool {
+        bone.write_mask.allows(property)
+            && !(self.root_transform_policy == RootTransformPolicy::Ignore
+                && bone.path.is_root()
+                && property.component_type_id() == TypeId::of::<Transform>())
+    }
+
+    /// The number of tracks a bone needs to exceed to be split across tasks
+    /// when the graph is applied. See
+    /// [`set_track_split_threshold`](Self::set_track_split_threshold).
+    #[inline]
+    pub fn track_split_threshold(&self) -> Option<usize> {
+        self.track_split_threshold
+    }
+
+    /// Splits the tracks of bones animating more than `threshold` properties
+    /// across tasks when the graph is applied. `None` disables splitting,
+    /// which is the default.
+    ///
+    /// Bound entities are normally written by one task each, which makes a
+    /// single heavy bone, like the face of a rig with hundreds of blend
+    /// shapes, a bottleneck. The tracks of split bones are blended into a
+    /// staging buffer in parallel, in batches of `threshold` tracks, and then
+    /// written to their entity by a single task. Staging allocates a value
+    /// per track, so this only pays off for bones with many tracks.
+    pub fn set_track_split_threshold(&mut self, threshold: Option<usize>) {
+        self.track_split_threshold = threshold.map(|threshold| threshold.max(1));
+    }
+
+    /// Whether clip weights are accumulated deterministically. See
+    /// [`set_deterministic`](Self::set_deterministic).
+    #[inline]
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Accumulates clip weights in Q16.16 fixed-point while evaluating, so
+    /// that graphs evaluated from the same inputs have bit-identical weights
+    /// on every platform, such as for lockstep networking. Disabled by
+    /// default.
+    ///
+    /// The eased weight of each input is rounded to the nearest multiple of
+    /// 2^-16, and the weights are multiplied down the graph and summed for
+    /// each clip with integer arithmetic. Nodes are always visited in the
+    /// same order. Clip weights aren't normalized afterwards, so the weights
+    /// the clips are blended with, and the [total](Self::total_weight), are
+    /// exactly the fixed-point sums. The exception is while a
+    /// [captured pose](Self::capture_pose) is blended over the graph, which
+    /// rescales the other weights with floats.
+    ///
+    /// Only the weights are fixed-point. Easing curves, clip times, curve
+    /// sampling and [`Animatable`] blends are still computed with floats. The
+    /// basic float operations are exact to IEEE 754 everywhere, but easings
+    /// using transcendental functions may round differently across platforms,
+    /// which can change a rounded weight in rare cases.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    /// Gets the [`ClipId`] of a clip node. Clips are identified by their ID
+    /// when they are sampled and blended.
+    pub fn clip_id(&self, node_id: NodeId) -> Result<ClipId, AnimationGraphError> {
+        match self.nodes.get(node_id) {
+            Some(Node::Clip { clip }) => {
+                debug_assert!(
+                    (clip.0 as usize) < self.state.clips.len(),
+                    "clip node {node_id:?} refers to missing clip {clip:?}"
+                );
+                Ok(*clip)
+            }
+            Some(_) => Err(AnimationGraphError::NotClipNode(node_id)),
+            None => Err(AnimationGraphError::NodeNotFound(node_id)),
+        }
+    }
+
+    /// Sets the [`PlaybackMode`] of a clip node. The clip's current time is
+    /// immediately wrapped or clamped to match the new mode.
+    pub fn set_playback_mode(
+        &mut self,
+        node_id: NodeId,
+        mode: PlaybackMode,
+    ) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        self.state.set_playback_mode(clip, mode);
+        Ok(())
+    }
+
+    /// Checks if the time of a clip node is a normalized phase. See
+    /// [`set_normalized_time`](Self::set_normalized_time).
+    pub fn is_normalized_time(&self, node_id: NodeId) -> Result<bool, AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        Ok(self.state.clips[clip.0 as usize].normalized)
+    }
+
+    /// Sets whether the time of a clip node is a normalized phase, where `0.0`
+    /// is the start of the clip's trim range and `1.0` is its end, regardless
+    /// of its duration. Useful for driving clips with an external progress
+    /// value, or keeping clips of different lengths in step.
+    ///
+    /// Times passed to [`set_time`](Self::set_time) and returned by
+    /// [`clip_time`](Self::clip_time) are phases for such clips, and are
+    /// wrapped or clamped to `0..=1` by the clip's [`PlaybackMode`].
+    /// [`advance_time`](Self::advance_time) still advances them in seconds.
+    /// The clip's current position is preserved when switching.
+    pub fn set_normalized_time(
+        &mut self,
+        node_id: NodeId,
+        normalized: bool,
+    ) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        self.state.set_normalized(clip, normalized);
+        Ok(())
+    }
+
+    /// Checks if a clip node is blended additively on top of the other
+    /// clips in the graph.
+    pub fn is_additive(&self, node_id: NodeId) -> Result<bool, AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        Ok(self.state.clips[clip.0 as usize].additive)
+    }
+
+    /// Sets whether a clip node is blended additively on top of the other
+    /// clips in the graph. Additive clips should be made with
+    /// [`AnimationClipBuilder::make_additive`].
+    ///
+    /// [`AnimationClipBuilder::make_additive`]: crate::clip::AnimationClipBuilder::make_additive
+    pub fn set_additive(
+        &mut self,
+        node_id: NodeId,
+        additive: bool,
+    ) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        self.state.set_additive(clip, additive);
+        Ok(())
+    }
+
+    /// Adds a clip node to a sync group, creating the group if it doesn't
+    /// exist. Clips in the same group play in lockstep, which keeps clips
+    /// with matching cycles, like walks and runs, aligned while blending.
+    ///
+    /// Whenever the graph is evaluated, the clip in each group with the highest
+    /// weight is elected the group's leader. The leader advances in real time,
+    /// and the others follow it. If `normalized` is true, the clip follows the
+    /// leader's phase, its time relative to its duration. Otherwise, it
+    /// follows the leader's time directly. As every clip in a group stays
+    /// aligned, the leader can change mid-blend without clips jumping.
+    pub fn set_sync_group(
+        &mut self,
+        node_id: NodeId,
+        group: impl Into<Cow<'static, str>>,
+        normalized: bool,
+    ) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        self.state.set_sync_group(clip, group.into(), normalized);
+        Ok(())
+    }
+
+    /// Gives a node a label to identify it by when the graph is printed.
+    /// Unlabeled nodes are printed as their numeric ID.
+    pub fn set_node_label(
+        &mut self,
+        node_id: NodeId,
+        label: impl Into<Cow<'static, str>>,
+    ) -> Result<(), AnimationGraphError> {
+        self.nodes
+            .get(node_id)
+            .ok_or(AnimationGraphError::NodeNotFound(node_id))?;
+        self.labels.insert(node_id, label.into());
+        Ok(())
+    }
+
+    /// Gets the label of a node, if it has one.
+    pub fn node_label(&self, node_id: NodeId) -> Option<&str> {
+        self.labels.get(&node_id).map(AsRef::as_ref)
+    }
+
+    /// Sets a parameter of the graph. Inputs with a [`WeightBinding`] to the
+    /// parameter are updated the next time the graph is evaluated.
+    ///
+    /// Parameters driven by a clip's parameter curve are overwritten whenever
+    /// the graph is evaluated. See [`AnimationClipBuilder::add_param_curve`].
+    ///
+    /// [`AnimationClipBuilder::add_param_curve`]: crate::clip::AnimationClipBuilder::add_param_curve
+    pub fn set_param(&mut self, name: impl Into<Cow<'static, str>>, value: f32) {
+        self.params.set(name.into(), value);
+    }
+
+    /// Gets the value of a parameter, if it has been set.
+    #[inline]
+    pub fn get_param(&self, name: &str) -> Option<f32> {
+        self.params.get(name)
+    }
+
+    /// Iterates over the parameters that have been set, and their values.
+    pub fn params(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.params
+            .iter()
+            .map(|(name, value)| (name.as_ref(), value))
+    }
+
+    /// Sets the value used for parameters that [`WeightBinding`]s refer to,
+    /// but that haven't been set. A warning is logged the first time each
+    /// missing parameter is used. Defaults to 0.
+    pub fn set_missing_param_value(&mut self, value: f32) {
+        self.params.missing_value = value;
+    }
+
+    /// Removes a clip node from its sync group, if any. It will advance
+    /// independently from then on.
+    pub fn clear_sync_group(&mut self, node_id: NodeId) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        self.state.clear_sync_group(clip);
+        Ok(())
+    }
+
+    /// Restricts a clip node to only play a sub-range of its clip. The node's
+    /// time, duration, and finished state are all relative to the trimmed range,
+    /// so a time of 0 samples the clip at `range.start`. Looping clips wrap
+    /// within the range, and one-shot clips clamp to it.
+    ///
+    /// The range is clamped to the bounds of the clip. Clip nodes sharing the
+    /// same clip share its curves, regardless of their ranges.
+    pub fn set_clip_range(
+        &mut self,
+        node_id: NodeId,
+        range: Range<f32>,
+    ) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        self.state.clips[clip.0 as usize].set_range(range);
+        Ok(())
+    }
+
+    /// Skips the first `offset` seconds of a clip node's clip. This is
+    /// equivalent to trimming the clip to the range `offset..duration`.
+    pub fn set_clip_start_offset(
+        &mut self,
+        node_id: NodeId,
+        offset: f32,
+    ) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        let state = &mut self.state.clips[clip.0 as usize];
+        state.set_range(offset..state.clip_duration);
+        Ok(())
+    }
+
+    /// Removes any trimming from a clip node, playing the full clip.
+    pub fn clear_clip_range(&mut self, node_id: NodeId) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        let state = &mut self.state.clips[clip.0 as usize];
+        state.set_range(0.0..state.clip_duration);
+        Ok(())
+    }
+
+    /// Gets the duration of a clip node, respecting its trim range. For nodes
+    /// with a [time warp](Self::set_time_warp), this is the duration of the
+    /// warp curve instead.
+    pub fn clip_duration(&self, node_id: NodeId) -> Result<f32, AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        Ok(self.state.clips[clip.0 as usize].local_duration())
+    }
+
+    /// Remaps the time of a clip node through a curve, to speed up or slow
+    /// down parts of its clip without re-authoring it, such as holding the
+    /// wind-up of an attack.
+    ///
+    /// The node's time still advances linearly, and is mapped through the
+    /// curve whenever its clip is sampled. The curve's values are times
+    /// relative to the start of the node's trim range, and are clamped to it.
+    /// The node's duration becomes the curve's duration, so looping, finishing
+    /// and normalized times all follow the node's time. For loops to line up,
+    /// the curve should map 0 to 0 and its duration to the end of the clip.
+    ///
+    /// ```rust,ignore
+    /// // Hold the first frame of the wind-up for half a second.
+    /// let warp = CurveVariableLinear::with_keyframes(
+    ///     vec![0.0, 0.5, 1.5],
+    ///     vec![0.0, 0.0, 1.0],
+    /// )?;
+    /// graph.set_time_warp(attack, warp)?;
+    /// ```
+    pub fn set_time_warp(
+        &mut self,
+        node_id: NodeId,
+        curve: impl Curve<f32> + 'static,
+    ) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        let warp = TimeWarp(into_dynamic_curve(curve));
+        self.state.set_time_warp(clip, Some(warp));
+        Ok(())
+    }
+
+    /// Removes the time warp of a clip node, if any. See
+    /// [`set_time_warp`](Self::set_time_warp).
+    pub fn clear_time_warp(&mut self, node_id: NodeId) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        self.state.set_time_warp(clip, None);
+        Ok(())
+    }
+
+    /// Shifts the time a clip node's clip is sampled at by `offset` seconds,
+    /// so that graphs playing the same clips in lockstep don't animate in
+    /// unison. Looping clips wrap the shifted time around, and others clamp
+    /// it to their duration.
+    ///
+    /// The offset only applies when sampling. The node's time, and the times
+    /// of clips following it in a sync group, are unaffected, so clips in a
+    /// sync group are aligned first and then shifted by their own offsets.
+    /// See [`randomize_phases`](Self::randomize_phases) to offset every
+    /// looping clip at once.
+    pub fn set_clip_phase_offset(
+        &mut self,
+        node_id: NodeId,
+        offset: f32,
+    ) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        self.state.set_phase_offset(clip, offset);
+        Ok(())
+    }
+
+    /// Gets the phase offset of a clip node, in seconds. See
+    /// [`set_clip_phase_offset`](Self::set_clip_phase_offset).
+    pub fn clip_phase_offset(&self, node_id: NodeId) -> Result<f32, AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        Ok(self.state.clips[clip.0 as usize].phase_offset)
+    }
+
+    /// Scales how fast the time of a clip node advances. The default speed is
+    /// 1, and negative speeds play the clip in reverse. Clips following the
+    /// leader of a sync group match the leader's time, whatever their speed.
+    pub fn set_clip_speed(
+        &mut self,
+        node_id: NodeId,
+        speed: f32,
+    ) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        self.state.set_speed(clip, speed);
+        Ok(())
+    }
+
+    /// Gets the speed of a clip node. See
+    /// [`set_clip_speed`](Self::set_clip_speed).
+    pub fn clip_speed(&self, node_id: NodeId) -> Result<f32, AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        Ok(self.state.clips[clip.0 as usize].speed)
+    }
+
+    /// Gets how long a node takes to play through once in seconds.
+    ///
+    /// For clip nodes, this is the [duration](Self::clip_duration) of the
+    /// clip's trim range or time warp, scaled by the node's
+    /// [speed](Self::set_clip_speed). Blend nodes last as long as the longest
+    /// of their connected inputs, whatever their weights, and random nodes as
+    /// long as their active input. Returns `None` for snapshots, clips with a
+    /// speed of 0, nodes that don't exist, and nodes without any inputs that
+    /// have a duration.
+    pub fn node_duration(&self, node_id: NodeId) -> Option<f32> {
+        self.max_over_clips(node_id, ClipState::playback_duration)
+    }
+
+    /// Gets how long a node has left to play in seconds, as of its clips'
+    /// current times, assuming time moves forwards. Looping clips never
+    /// finish, so they have no remaining time.
+    ///
+    /// Blend and random nodes have as long left as the longest of their
+    /// inputs that finish, like [`node_duration`](Self::node_duration), and
+    /// `None` if none of their inputs finish.
+    pub fn remaining_time(&self, node_id: NodeId) -> Option<f32> {
+        self.max_over_clips(node_id, ClipState::remaining_time)
+    }
+
+    /// Gets the maximum of `f` over the clips of the nodes beneath a node,
+    /// through connected inputs. Each node is only visited once, so cycles
+    /// are safe to traverse.
+    fn max_over_clips(
+        &self,
+        node_id: NodeId,
+        f: impl Fn(&ClipState) -> Option<f32>,
+    ) -> Option<f32> {
+        let mut visited = HashSet::default();
+        let mut stack = vec![node_id];
+        let mut max: Option<f32> = None;
+        while let Some(node_id) = stack.pop() {
+            if !visited.insert(node_id) {
+                continue;
+            }
+            match self.nodes.get(node_id) {
+                Some(Node::Clip { clip }) => {
+                    if let Some(value) = f(&self.state.clips[clip.0 as usize]) {
+                        max = Some(max.map_or(value, |max| max.max(value)));
+                    }
+                }
+                Some(Node::Blend { inputs, .. }) => stack.extend(
+                    inputs
+                        .iter()
+                        .filter(|input| input.is_connected())
+                        .map(NodeInput::node_id),
+                ),
+                Some(Node::Random { inputs, active, .. }) => {
+                    let active = active.and_then(|active| inputs.get(active));
+                    if let Some((_, input)) = active.filter(|(_, input)| input.is_connected()) {
+                        stack.push(input.node_id());
+                    }
+                }
+                Some(Node::Snapshot { .. }) | None => {}
+            }
+        }
+        max
+    }
+
+    /// Freezes the time of a clip node while the rest of the graph advances.
+    /// The clip is still blended, at the time it was paused at. Clips in a
+    /// sync group stop following their leader while paused.
+    pub fn pause_clip(&mut self, node_id: NodeId) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        self.state.set_paused(clip, true);
+        Ok(())
+    }
+
+    /// Lets the time of a paused clip node advance again. See
+    /// [`pause_clip`](Self::pause_clip).
+    pub fn resume_clip(&mut self, node_id: NodeId) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        self.state.set_paused(clip, false);
+        Ok(())
+    }
+
+    /// Checks if the time of a clip node is frozen. See
+    /// [`pause_clip`](Self::pause_clip).
+    pub fn is_clip_paused(&self, node_id: NodeId) -> Result<bool, AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        Ok(self.state.clips[clip.0 as usize].paused)
+    }
+
+    /// Treats a node as if its weight were 0 while evaluating, whatever its
+    /// authored weight, muting every clip beneath it. Meant for debugging
+    /// blends. See [`clear_solo_mute`](Self::clear_solo_mute).
+    pub fn mute(&mut self, node_id: NodeId) -> Result<(), AnimationGraphError> {
+        if self.nodes.get(node_id).is_none() {
+            return Err(AnimationGraphError::NodeNotFound(node_id));
+        }
+        self.muted.insert(node_id);
+        Ok(())
+    }
+
+    /// Checks if a node is muted. See [`mute`](Self::mute).
+    pub fn is_muted(&self, node_id: NodeId) -> bool {
+        self.muted.contains(&node_id)
+    }
+
+    /// Blends only the soloed clip nodes while evaluating, giving them the
+    /// weight of every other clip. Clips are soloed in proportion to the
+    /// weights they'd otherwise have, or evenly if none of them would be
+    /// blended. Multiple clips can be soloed at once. Meant for debugging
+    /// blends. See [`clear_solo_mute`](Self::clear_solo_mute).
+    pub fn solo(&mut self, node_id: NodeId) -> Result<(), AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        if !self.soloed.contains(&clip) {
+            self.soloed.push(clip);
+        }
+        Ok(())
+    }
+
+    /// Checks if a clip node is soloed. See [`solo`](Self::solo).
+    pub fn is_soloed(&self, node_id: NodeId) -> bool {
+        self.clip_id(node_id)
+            .map_or(false, |clip| self.soloed.contains(&clip))
+    }
+
+    /// Unmutes and unsolos every node, restoring the authored blend the next
+    /// time the graph is evaluated.
+    pub fn clear_solo_mute(&mut self) {
+        self.muted.clear();
+        self.soloed.clear();
+    }
+
+    /// Gets the current time of a clip node, relative to the start of its
+    /// trim range. This is the clip's phase if its time is
+    /// [normalized](Self::set_normalized_time).
+    pub fn clip_time(&self, node_id: NodeId) -> Result<f32, AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        Ok(self.state.clips[clip.0 as usize].time)
+    }
+
+    /// Gets the weight a clip node was blended with as of the last
+    /// evaluation. Clips that weren't reached by the evaluation have a weight
+    /// of 0.
+    pub fn clip_weight(&self, node_id: NodeId) -> Result<f32, AnimationGraphError> {
+        Ok(self.state.clip_weight(self.clip_id(node_id)?))
+    }
+
+    /// Checks if a [`PlaybackMode::Once`] clip node has reached its end.
+    pub fn is_finished(&self, node_id: NodeId) -> Result<bool, AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        Ok(self.state.clips[clip.0 as usize].finished)
+    }
+
+    /// Checks if a [`PlaybackMode::Once`] clip node has reached its start
+    /// while playing in reverse.
+    pub fn is_finished_reverse(&self, node_id: NodeId) -> Result<bool, AnimationGraphError> {
+        let clip = self.clip_id(node_id)?;
+        Ok(self.state.clips[clip.0 as usize].finished_reverse)
+    }
+
+    /// Gets how the clip times of the graph are currently driven.
+    #[inline]
+    pub fn time_mode(&self) -> TimeMode {
+        self.time_mode
+    }
+
+    /// Sets how the clip times of the graph are driven. Use this with
+    /// [`TimeMode::Playing`] to resume normal playback after scrubbing.
+    pub fn set_time_mode(&mut self, time_mode: TimeMode) {
+        self.time_mode = time_mode;
+    }
+
+    /// Samples the entire graph at an exact time without advancing it.
+    ///
+    /// This sets the time of every clip reachable from the root, following the
+    /// same propagation rules as [`set_time`](Self::set_time), then evaluates the
+    /// graph. The graph is switched into [`TimeMode::Scrubbing`] so that
+    /// [`advance_time`](Self::advance_time) cannot move it away from the scrubbed
+    /// time. If called multiple times before the graph is applied, the last call
+    /// wins.
+    pub fn sample_at(&mut self, time: f32) {
+        self.time_mode = TimeMode::Scrubbing;
+        self.update_skipped = false;
+        // The root node always exists, so this cannot fail.
+        let _ = self.set_time(NodeId::ROOT, time);
+        self.evaluate();
+        // Scrubbing samples the exact time, so skip any interpolation.
+        if self.state.previous.is_some() {
+            self.state.save_previous(0.0);
+        }
+    }
+
+    /// Captures the current values of every bound property into a pose
+    /// sampled by the graph's [`Node::Snapshot`] node, adding the node the
+    /// first time a pose is captured.
+    ///
+    /// The snapshot samples to the captured values regardless of time, and is
+    /// blended over the rest of the graph: with a weight of `w`, the other
+    /// clips only get `1 - w` of their weight. Fading it out on top of a newly
+    /// started clip smoothly transitions from the current pose into the clip.
+    /// Properties on unbound bones, or that could not be read from their
+    /// entity, are not captured and do not contribute to the blend.
+    ///
+    /// A graph only has one snapshot node. Capturing another pose replaces
+    /// the previous one and returns the same node, so capturing a pose at
+    /// the start of every transition doesn't grow the graph.
+    ///
+    /// Returns the node ID of the snapshot node, or an error if the graph is
+    /// full.
+    pub fn capture_pose(&mut self, world: &World) -> Result<NodeId, AnimationGraphError> {
+        let (node, pose_id) = self.reset_snapshot()?;
+        self.for_each_bound_value(world, |track, value| {
+            // A type mismatch here means the property is not animatable as the
+            // track's type, so it's skipped.
+            let _ = make_track_mut(track).add_snapshot(pose_id, value);
+        });
+        Ok(node)
+    }
+
+    /// The ID of the pose sampled by the graph's snapshot node, if it has
+    /// one.
+    fn snapshot_pose(&self) -> Option<ClipId> {
+        match self.nodes.get(self.snapshot?) {
+            Some(Node::Snapshot { pose_id }) => Some(*pose_id),
+            _ => None,
+        }
+    }
+
+    /// Gets the graph's snapshot node and the ID of its pose, with the
+    /// previously captured pose removed from the tracks. The node is added if
+    /// the graph doesn't have one yet.
+    fn reset_snapshot(&mut self) -> Result<(NodeId, ClipId), AnimationGraphError> {
+        if let Some(node) = self.snapshot {
+            if let Some(Node::Snapshot { pose_id }) = self.nodes.get(node) {
+                let pose_id = *pose_id;
+                self.clips.remove_clip(pose_id);
+                return Ok((node, pose_id));
+            }
+        }
+        if self.nodes.is_full() {
+            return Err(AnimationGraphError::GraphFull);
+        }
+        let pose_id = self.state.add_clip(0.0)?;
+        let node = self.nodes.add(Node::Snapshot { pose_id })?;
+        self.snapshot = Some(node);
+        Ok((node, pose_id))
+    }
+
+    /// Captures the current values of every bound property as the rest values
+    /// of their tracks.
+    ///
+    /// When the clips animating a property have a total weight below 1, the
+    /// remaining weight is given to its rest value. Without one, it is given to
+    /// the value type's default rest value instead, i.e. zero or the identity.
+    /// See [`Animatable::blend`].
+    ///
+    /// This is typically called once after the graph has been bound, while
+    /// the hierarchy is still in its bind pose. Properties on unbound bones, or
+    /// that could not be read from their entity, are left unchanged.
+    ///
+    /// [`Animatable::blend`]: crate::Animatable::blend
+    pub fn set_rest_pose_from_current(&mut self, world: &World) {
+        self.for_each_bound_value(world, |track, value| {
+            let _ = make_track_mut(track).set_rest_value(value);
+        });
+    }
+
+    /// Sets the rest value of the property at `path`. See
+    /// [`set_rest_pose_from_current`](Self::set_rest_pose_from_current).
+    ///
+    /// Returns an error if the property isn't animated by the graph, or is
+    /// animated as a different type.
+    pub fn set_rest_value(
+        &mut self,
+        path: &PropertyPath,
+        value: &dyn Reflect,
+    ) -> Result<(), AnimationGraphError> {
+        let track = self
+            .clips
+            .find_bone_mut(path.entity())
+            .and_then(|bone| bone.tracks.get_mut(path.access()))
+            .ok_or(TrackError::MissingTrack)?;
+        make_track_mut(track).set_rest_value(value)?;
+        Ok(())
+    }
+
+    /// Removes the rest values of every property animated by the graph.
+    pub fn clear_rest_pose(&mut self) {
+        for bone in self.clips.bones_mut() {
+            for track in bone.tracks.values_mut() {
+                make_track_mut(track).clear_rest_value();
+            }
+        }
+    }
+
+    /// Samples the value a single clip node would produce for a property at
+    /// `time`, in seconds along the clip, as if it were the only clip in the
+    /// graph. The graph's weights and clip times are ignored and left
+    /// unchanged. Useful for debugging tools.
+    ///
+    /// Returns `None` if `node_id` isn't a clip node, the clip doesn't animate
+    /// the property, or the property isn't animated as a `T`.
+    pub fn sample_clip_property<T: Animatable>(
+        &self,
+        node_id: NodeId,
+        path: &PropertyPath,
+        time: f32,
+    ) -> Option<T> {
+        let clip = self.clip_id(node_id).ok()?;
+        match self.find_track(path) {
+            Some(track) => track
+                .as_any()
+                .downcast_ref::<CurveTrack<T>>()?
+                .clip_curve(clip)
+                .map(|curve| curve.sample(time)),
+            None => self
+                .sample_clip_property_reflect(node_id, path, time)?
+                .downcast_ref::<T>()
+                .cloned(),
+        }
+    }
+
+    /// A type-erased version of
+    /// [`sample_clip_property`](Self::sample_clip_property).
+    pub fn sample_clip_property_reflect(
+        &self,
+        node_id: NodeId,
+        path: &PropertyPath,
+        time: f32,
+    ) -> Option<Box<dyn Reflect>> {
+        let clip = self.clip_id(node_id).ok()?;
+        if let Some(track) = self.find_track(path) {
+            return track.sample_clip_boxed(clip, time);
+        }
+        // The property may have been fused into a track for its whole
+        // component by `optimize`.
+        let access = path.access();
+        let component = AccessPath::from_parts(
+            access.component_type_id(),
+            access.component_name(),
+            FieldPath::root(),
+        );
+        let value = self
+            .find_bone(path.entity())?
+            .tracks
+            .get(&component)?
+            .sample_clip_boxed(clip, time)?;
+        let field = access.field_path().field(value.as_ref()).ok()?;
+        Some(field.clone_value())
+    }
+
+    /// Samples the current value of a property in each of `graphs`, writing
+    /// it to the matching entry of `out`. This produces the same values as
+    /// evaluating each graph on its own, but the curves of consecutive graphs
+    /// built from the same clips are sampled for all of them at once, which
+    /// is considerably faster for large crowds.
+    ///
+    /// # Panics
+    /// This will panic if `graphs` and `out` have different lengths.
+    pub fn sample_property_batch<T: Animatable>(
+        graphs: &[&AnimationGraph],
+        path: &PropertyPath,
+        out: &mut [T],
+    ) -> Result<(), AnimationGraphError> {
+        Self::sample_property_batch_with(graphs, path, out, &mut BatchBuffers::new())
+    }
+
+    /// Like [`sample_property_batch`](Self::sample_property_batch), but
+    /// reuses scratch buffers between calls instead of allocating them.
+    ///
+    /// # Panics
+    /// This will panic if `graphs` and `out` have different lengths.
+    pub fn sample_property_batch_with<T: Animatable>(
+        graphs: &[&AnimationGraph],
+        path: &PropertyPath,
+        out: &mut [T],
+        buffers: &mut BatchBuffers<T>,
+    ) -> Result<(), AnimationGraphError> {
+        assert_eq!(graphs.len(), out.len(), "mismatched batch lengths");
+        let mut batch = Vec::with_capacity(graphs.len());
+        for graph in graphs {
+            let track = graph.find_track(path).ok_or(TrackError::MissingTrack)?;
+            let track = track
+                .as_any()
+                .downcast_ref::<CurveTrack<T>>()
+                .ok_or_else(|| TrackError::IncorrectType {
+                    expected: std::any::type_name::<T>(),
+                    found: track.value_type_name().to_owned(),
+                })?;
+            batch.push((track, &graph.state));
+        }
+
+        let mut start = 0;
+        while start < batch.len() {
+            let first = batch[start].0;
+            let end = start
+                + batch[start..]
+                    .iter()
+                    .take_while(|(track, _)| first.shares_curves(track))
+                    .count();
+            CurveTrack::sample_and_blend_batch(&batch[start..end], &mut out[start..end], buffers);
+            start = end;
+        }
+        Ok(())
+    }
+
+    fn find_track(&self, path: &PropertyPath) -> Option<&dyn Track> {
+        self.find_bone(path.entity())?
+            .tracks
+            .get(path.access())
+            .map(|track| track.as_ref())
+    }
+
+    /// Reads the current value of every bound property from the world.
+    fn for_each_bound_value(
+        &mut self,
+        world: &World,
+        mut f: impl FnMut(&mut Arc<dyn Track>, &dyn Reflect),
+    ) {
+        let type_registry = match world.get_resource::<TypeRegistryArc>() {
+            Some(type_registry) => type_registry.read(),
+            None => return,
+        };
+        for bone in self.clips.bones_mut() {
+            let entity = if let Some(entity) = bone.entity() {
+                entity
+            } else {
+                continue;
+            };
+
+            for (property, track) in bone.tracks.iter_mut() {
+                let value = target::reflect_target(&type_registry, world, entity, property)
+                    .and_then(|target| property.field_path().field(target).ok());
+                if let Some(value) = value {
+                    f(track, value);
+                }
+            }
+        }
+    }
+
+    /// Checks that every property animated by the graph can be applied to the
+    /// hierarchy beneath `root`: each entity path must resolve to an entity,
+    /// the component, resource or asset targeted by each property must exist,
+    /// and each field must exist and match the value type of its curves.
+    pub fn validate_against(
+        &self,
+        registry: &TypeRegistry,
+        world: &World,
+        root: Entity,
+    ) -> Vec<ClipValidationError> {
+        let mut errors = Vec::new();
+        for bone in self.clips.bones() {
+            let entity = hierarchy::find_bone_in_world(world, root, &bone.path);
+            for (property, track) in bone.tracks.iter() {
+                let result = entity
+                    .ok_or(ClipValidationErrorKind::MissingEntity)
+                    .and_then(|entity| {
+                        validate_component(registry, property)?;
+                        target::reflect_target(registry, world, entity, property).ok_or(
+                            match property.target() {
+                                AccessTarget::Component => {
+                                    ClipValidationErrorKind::MissingComponent
+                                }
+                                AccessTarget::Resource => ClipValidationErrorKind::MissingResource,
+                                AccessTarget::Asset(_) => ClipValidationErrorKind::MissingAsset,
+                            },
+                        )
+                    })
+                    .and_then(|target| {
+                        validate_field(
+                            property,
+                            target,
+                            track.value_type_id(),
+                            track.value_type_name(),
+                        )
+                    });
+                if let Err(kind) = result {
+                    errors.push(ClipValidationError {
+                        path: PropertyPath::from_parts(bone.path.clone(), property.clone()),
+                        kind,
+                    });
+                }
+            }
+        }
+        errors
+    }
+
+    #[inline]
+    pub fn bones(&self) -> impl Iterator<Item = &Bone> {
+        self.clips.bones()
+    }
+
+    /// Samples and blends every property the graph animates at its current
+    /// time and weights, writing the values into `out`.
+    ///
+    /// Nothing is applied to entities, so this can be used to drive custom
+    /// consumers of the pose, such as skinning done outside of the ECS. Unlike
+    /// values applied to entities, sampled [`Handle`]s are weak. See
+    /// [`PoseBuffer`] for how values are stored.
+    ///
+    /// [`PoseBuffer`]: crate::graph::pose::PoseBuffer
+    pub fn sample_pose(&self, out: &mut pose::PoseBuffer) {
+        out.write(self);
+    }
+
+    pub(crate) fn bones_mut(&mut self) -> impl Iterator<Item = &mut Bone> {
+        self.clips.bones_mut()
+    }
+
+    /// Iterates over the path of every bone the graph animates, the entity
+    /// it is bound to, if any, and the number of properties it animates.
+    pub fn animated_entities(
+        &self,
+    ) -> impl Iterator<Item = (&EntityPath, Option<Entity>, usize)> + '_ {
+        self.bones()
+            .map(|bone| (bone.path(), bone.entity(), bone.track_count()))
+    }
+
+    /// The sum of the weights of the clips that animate any property of the
+    /// bone at `path`, as of the last evaluation. Clips that don't have a
+    /// curve for the bone don't move it, regardless of their weight.
+    ///
+    /// Returns 0 if the graph doesn't animate the bone.
+    pub fn total_influence(&self, path: &EntityPath) -> f32 {
+        let bone = match self.find_bone(path) {
+            Some(bone) => bone,
+            None => return 0.0,
+        };
+        self.state
+            .clips
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| bone.animates_clip(ClipId(*idx as u16)))
+            .map(|(_, clip)| clip.weight)
+            .sum()
+    }
+
+    /// Optimizes the graph's tracks for faster sampling and application.
+    ///
+    /// Currently this fuses bones with separately animated [`Transform`]
+    /// translation, rotation, and scale into a single track that blends and
+    /// applies the whole [`Transform`] at once. This does not change the
+    /// sampled results. This should be called after all clips have been
+    /// added, as clips added afterwards are not fused.
+    ///
+    /// [`Transform`]: bevy_transform::prelude::Transform
+    pub fn optimize(&mut self) {
+        self.clips.fuse_transforms();
+    }
+
+    /// Sets which of a bone's properties are written to its bound entity.
+    /// Masks take effect the next time the graph is applied, and can be
+    /// changed every frame without rebinding. See [`WriteMask`].
+    pub fn set_bone_write_mask(
+        &mut self,
+        path: &EntityPath,
+        mask: WriteMask,
+    ) -> Result<(), AnimationGraphError> {
+        let bone = self
+            .clips
+            .find_bone_mut(path)
+            .ok_or_else(|| AnimationGraphError::BoneNotFound(path.clone()))?;
+        bone.write_mask = mask;
+        Ok(())
+    }
+
+    /// Gets the [`WriteMask`] of a bone, if it's animated by the graph.
+    pub fn bone_write_mask(&self, path: &EntityPath) -> Option<&WriteMask> {
+        self.find_bone(path).map(|bone| &bone.write_mask)
+    }
+
+    /// Stops writing any of a bone's properties to its bound entity. Shorthand
+    /// for setting its mask to [`WriteMask::None`].
+    pub fn disable_bone(&mut self, path: &EntityPath) -> Result<(), AnimationGraphError> {
+        self.set_bone_write_mask(path, WriteMask::None)
+    }
+
+    /// Resumes writing all of a bone's properties to its bound entity.
+    /// Shorthand for setting its mask to [`WriteMask::All`].
+    pub fn enable_bone(&mut self, path: &EntityPath) -> Result<(), AnimationGraphError> {
+        self.set_bone_write_mask(path, WriteMask::All)
+    }
+
+    /// Gets the entity currently bound to the bone at a given path, if any.
+    ///
+    /// This may not be a valid entity ID even if available.
+    #[inline]
+    pub fn bound_entity(&self, path: &EntityPath) -> Option<Entity> {
+        self.find_bone(path).and_then(|bone| bone.entity())
+    }
+
+    #[inline]
+    pub fn find_bone(&self, path: &EntityPath) -> Option<&Bone> {
+        self.clips.find_bone(path)
+    }
+
+    pub fn find_bone_mut(&mut self, path: &EntityPath) -> Option<&mut Bone> {
+        self.clips.find_bone_mut(path)
+    }
+
+    /// Gets the id of the bone at a path, which can be used to look the bone
+    /// up again with [`get_bone`](Self::get_bone) without hashing its path.
+    #[inline]
+    pub fn bone_id(&self, path: &EntityPath) -> Option<BoneId> {
+        self.clips.bone_id(path)
+    }
+
+    #[inline]
+    pub fn get_bone(&self, id: BoneId) -> Option<&Bone> {
+        self.clips.get_bone(id)
+    }
+
+    /// The interned paths of the graph's bones. The id of each bone's path is
+    /// the index of its [`BoneId`].
+    pub fn paths(&self) -> &PathInterner {
+        self.clips.paths()
+    }
+
+    pub(crate) fn update_bone(&mut self, path: &EntityPath, entity: Option<Entity>) {
+        if let Some(bone) = self.find_bone_mut(path) {
+            bone.set_entity(entity);
+        }
+    }
+
+    /// Sets the parameters driven by clips to their curves' values at the
+    /// clips' current times.
+    fn update_param_curves(&mut self) {
+        for param_curve in self.param_curves.iter() {
+            let time = self.state.clips[param_curve.clip_id.0 as usize].sample_time();
+            let value = param_curve.curve.sample(time);
+            if !self.params.set_existing(&param_curve.param, value) {
+                self.params.set(param_curve.param.clone(), value);
+            }
+        }
+    }
+
+    /// Updates the weights of inputs bound to a parameter.
+    fn update_weight_bindings(&mut self) {
+        for (_, node) in self.nodes.iter_mut() {
+            for input in node.inputs_mut() {
+                if let Some(binding) = input.weight_binding() {
+                    let weight = self.params.evaluate(binding);
+                    input.set_weight(weight);
+                }
+            }
+        }
+    }
+
+    /// Sets the time for a given node. If the node is set to propagate its
+    /// time, all of it's currently connected inputs will also have the time
+    /// propagated to them as well.
+    pub fn set_time(&mut self, node_id: NodeId, time: f32) -> Result<(), AnimationGraphError> {
+        self.nodes
+            .get_mut(node_id)
+            .ok_or(AnimationGraphError::NodeNotFound(node_id))?;
+
+        let pending = &mut self.pending;
+        pending.clear();
+        pending.push(node_id);
+        while let Some(node_id) = pending.pop() {
+            let node = if let Some(node) = self.nodes.get(node_id) {
+                node
+            } else {
+                continue;
+            };
+
+            match node {
+                Node::Clip { clip } => {
+                    self.state.set_time(*clip, time);
+                }
+                Node::Snapshot { .. } => {}
+                Node::Blend {
+                    inputs,
+                    propogate_time,
+                } => {
+                    if *propogate_time {
+                        pending.extend(
+                            inputs
+                                .iter()
+                                .rev()
+                                .filter(|input| input.is_connected())
+                                .map(|input| input.node_id()),
+                        );
+                    }
+                }
+                Node::Random { inputs, .. } => {
+                    pending.extend(
+                        inputs
+                            .iter()
+                            .rev()
+                            .filter(|(_, input)| input.is_connected())
+                            .map(|(_, input)| input.node_id()),
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates the graph, computing the influences individual results.
+    pub fn evaluate(&mut self) {
+        self.update_param_curves();
+        self.update_weight_bindings();
+        self.update_random_nodes();
+        self.state.clear_weights();
+        let deterministic = self.deterministic;
+        self.fixed_weights.clear();
+        if deterministic {
+            let clips = self.state.clips.len();
+            self.fixed_weights.resize(clips, FixedWeight::default());
+        }
+        let fixed_weights = &mut self.fixed_weights;
+        let state = &mut self.state;
+        let mut add_weight = |clip: ClipId, weight: f32| {
+            if deterministic {
+                fixed_weights[clip.0 as usize] += FixedWeight::from_f32(weight);
+            } else {
+                state.add_weight(clip, weight);
+            }
+        };
+
+        // While fading from a pose, the pose is blended over the root with
+        // the weight the fade has left.
+        if let Some(fade) = &self.pose_fade {
+            add_weight(fade.pose_id(), 1.0 - fade.progress());
+        }
+        let stack = &mut self.traversal;
+        stack.clear();
+        stack.push(GraphTraversalNode {
+            node_id: NodeId::ROOT,
+            cumulative_weight: 1.0,
+        });
+
+        // Conduct a depth-first traversal of the graph multiplying the weights
+        // as it gets deeper into the tree. Inputs are pushed in reverse so
+        // that they are visited in order, keeping the order weights are
+        // accumulated in deterministic.
+        let mut nodes_visited = 0;
+        while let Some(current) = stack.pop() {
+            let current_node = if let Some(node) = self.nodes.get(current.node_id) {
+                node
+            } else {
+                continue;
+            };
+            nodes_visited += 1;
+            if !self.muted.is_empty() && self.muted.contains(&current.node_id) {
+                continue;
+            }
+
+            match &current_node {
+                Node::Clip { clip } | Node::Snapshot { pose_id: clip } => {
+                    add_weight(*clip, current.cumulative_weight);
+                }
+                Node::Blend { inputs, .. } => {
+                    for input in inputs.iter().rev().filter(|input| input.is_connected()) {
+                        let key = (current.node_id, input.node_id());
+                        let weight = match self.weight_overrides.get(&key) {
+                            Some(weight) => input.easing().ease(*weight),
+                            None => input.eased_weight(),
+                        };
+                        let cumulative_weight = if deterministic {
+                            FixedWeight::from_f32(weight)
+                                .mul(FixedWeight::from_f32(current.cumulative_weight))
+                                .to_f32()
+                        } else {
+                            weight * current.cumulative_weight
+                        };
+                        if cumulative_weight != 0.0 {
+                            stack.push(GraphTraversalNode {
+                                node_id: input.node_id(),
+                                cumulative_weight,
+                            });
+                        }
+                    }
+                }
+                Node::Random { inputs, active, .. } => {
+                    // Only the active input is blended, at full weight.
+                    let active = active.and_then(|active| inputs.get(active));
+                    if let Some((_, input)) = active.filter(|(_, input)| input.is_connected()) {
+                        stack.push(GraphTraversalNode {
+                            node_id: input.node_id(),
+                            cumulative_weight: current.cumulative_weight,
+                        });
+                    }
+                }
+            }
+        }
+
+        if deterministic {
+            let mut total_weight = FixedWeight::default();
+            for (clip, weight) in self.state.clips.iter_mut().zip(&self.fixed_weights) {
+                clip.weight = weight.to_f32();
+                total_weight += *weight;
+            }
+            self.total_weight = total_weight.to_f32();
+        } else {
+            self.total_weight = self.state.clips.iter().map(|clip| clip.weight).sum();
+        }
+        let overlaid = self
+            .snapshot_pose()
+            .map_or(false, |pose_id| self.state.overlay_pose(pose_id));
+        if overlaid {
+            self.total_weight = self.state.clips.iter().map(|clip| clip.weight).sum();
+        }
+        if !self.soloed.is_empty() {
+            self.apply_solo();
+        }
+        self.hold_evicted_clips();
+        self.stats = GraphStats {
+            nodes_visited,
+            active_clips: self
+                .state
+                .clips
+                .iter()
+                .filter(|clip| clip.weight != 0.0)
+                .count() as u32,
+        };
+        self.state.update_single_clip();
+        self.state.elect_sync_leaders();
+    }
+
+    /// Evicts the curves of lazy clips whose weight has been zero for longer
+    /// than their timeout, as of the last evaluation.
+    fn evict_idle_clips(&mut self, delta_time: f32) {
+        for asset in self.clip_assets.iter_mut() {
+            let eviction = match asset.eviction.as_mut() {
+                Some(eviction) if !eviction.evicted => eviction,
+                _ => continue,
+            };
+            if self.state.clip_weight(asset.clip_id) != 0.0 {
+                eviction.idle_time = 0.0;
+                continue;
+            }
+            eviction.idle_time += delta_time.abs();
+            if eviction.idle_time >= eviction.timeout {
+                self.clips.evict_clip(asset.clip_id);
+                eviction.evicted = true;
+            }
+        }
+    }
+
+    /// Takes the weights of evicted clips, whose curves are placeholders, out
+    /// of the blend, and requests that the ones given a weight are restored.
+    fn hold_evicted_clips(&mut self) {
+        for asset in self.clip_assets.iter_mut() {
+            let eviction = match asset.eviction.as_mut() {
+                Some(eviction) if eviction.evicted => eviction,
+                _ => continue,
+            };
+            let clip = &mut self.state.clips[asset.clip_id.0 as usize];
+            if clip.weight != 0.0 {
+                eviction.requested = true;
+                self.total_weight -= std::mem::take(&mut clip.weight);
+            }
+        }
+    }
+
+    /// Gives the weight of every clip that isn't soloed to the soloed clips,
+    /// in proportion to their own weights, or evenly if they have none. The
+    /// total weight of the graph is unchanged.
+    fn apply_solo(&mut self) {
+        let clips = &mut self.state.clips;
+        let soloed_weight: f32 = self
+            .soloed
+            .iter()
+            .map(|clip| clips[clip.0 as usize].weight)
+            .sum();
+        let soloed_count = self.soloed.len() as f32;
+        for (idx, clip) in clips.iter_mut().enumerate() {
+            clip.weight = if !self.soloed.contains(&ClipId(idx as u16)) {
+                0.0
+            } else if soloed_weight != 0.0 {
+                clip.weight * self.total_weight / soloed_weight
+            } else {
+                self.total_weight / soloed_count
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        curve::{Curve, CurveFixed, CurveVariableLinear},
+        path::PropertyPath,
+        WorldResources,
+    };
+    use bevy_app::App;
+    use bevy_asset::{AddAsset, AssetPlugin, Assets, Handle};
+    use bevy_core::Name;
+    use bevy_ecs::reflect::ReflectComponent;
+    use bevy_math::{EulerRot, Quat, Vec3};
+    use bevy_reflect::{TypeRegistry, TypeUuid};
+    use bevy_tasks::{IoTaskPool, TaskPool};
+    use bevy_transform::prelude::Children;
+    use bevy_transform::prelude::Transform;
+    use bevy_utils::Hashed;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::any::TypeId;
+
+    assert_impl_all!(AnimationGraph: Send, Sync);
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Test {
+        a: f32,
+    }
+
+    #[derive(TypeUuid)]
+    #[uuid = "5a4c6f7e-1d2b-4e8a-9c3f-0b7d6e5a4c31"]
+    struct TestAsset;
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct TestHandle {
+        handle: Handle<TestAsset>,
+    }
+
+    fn test_path() -> PropertyPath {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        PropertyPath::parse(&registry, "a@bevy_prototype_animation::graph::test::Test.a").unwrap()
+    }
+
+    fn sample_f32(graph: &AnimationGraph, path: &PropertyPath) -> f32 {
+        graph
+            .find_bone(path.entity())
+            .and_then(|bone| bone.tracks.get(path.access()))
+            .and_then(|track| track.as_any().downcast_ref::<CurveTrack<f32>>())
+            .unwrap()
+            .sample_and_blend(&graph.state)
+    }
+
+    fn single_clip_graph(curve: CurveFixed<f32>) -> (AnimationGraph, PropertyPath, NodeId) {
+        let path = test_path();
+        let clip = AnimationClip::builder()
+            .add_curve(path.clone(), curve)
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        assert!(graph.add_input(NodeId::ROOT, node).is_ok());
+        (graph, path, node)
+    }
+
+    #[test]
+    pub fn test_sample_at_matches_curve() {
+        let curve = CurveFixed::from_keyframes(2.0, vec![0.0, 1.0, 4.0, 2.0, 3.0]);
+        let (mut graph, path, _) = single_clip_graph(curve.clone());
+
+        for time in [1.75, 0.25, 1.0, 0.6, 2.0, 0.0] {
+            graph.sample_at(time);
+            assert_eq!(graph.time_mode(), TimeMode::Scrubbing);
+            assert_eq!(sample_f32(&graph, &path), curve.sample(time));
+        }
+    }
+
+    #[test]
+    pub fn test_sample_at_last_call_wins() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![0.0, 1.0, 2.0]);
+        let (mut graph, path, _) = single_clip_graph(curve.clone());
+
+        graph.sample_at(0.5);
+        graph.sample_at(1.5);
+        graph.sample_at(1.25);
+        assert_eq!(sample_f32(&graph, &path), curve.sample(1.25));
+    }
+
+    #[test]
+    pub fn test_advance_time_ignored_while_scrubbing() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![0.0, 1.0, 2.0]);
+        let (mut graph, path, _) = single_clip_graph(curve.clone());
+
+        graph.sample_at(0.5);
+        graph.advance_time(1.0);
+        graph.evaluate();
+        assert_eq!(sample_f32(&graph, &path), curve.sample(0.5));
+
+        graph.set_time_mode(TimeMode::Playing);
+        graph.advance_time(1.0);
+        graph.evaluate();
+        assert_eq!(sample_f32(&graph, &path), curve.sample(1.5));
+    }
+
+    #[test]
+    pub fn test_snapshot_crossfade_into_clip() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![1.0, 1.0]);
+        let (mut graph, path, _) = single_clip_graph(curve);
+
+        let type_registry = TypeRegistryArc::default();
+        type_registry.write().register::<Test>();
+        let mut world = World::new();
+        world.insert_resource(type_registry);
+        let entity = world.spawn().insert(Test { a: 5.0 }).id();
+        graph
+            .find_bone_mut(path.entity())
+            .unwrap()
+            .set_entity(Some(entity));
+
+        let snapshot = graph.capture_pose(&world).unwrap();
+        assert!(graph.add_input(NodeId::ROOT, snapshot).is_ok());
+
+        // Fade the snapshot out on top of the clip.
+        for step in 0..=4 {
+            let t = step as f32 / 4.0;
+            graph
+                .nodes
+                .get_mut(NodeId::ROOT)
+                .and_then(|root| root.get_input_mut(snapshot))
+                .unwrap()
+                .set_weight(1.0 - t);
+            graph.evaluate();
+            let expected = 5.0 * (1.0 - t) + 1.0 * t;
+            assert!((sample_f32(&graph, &path) - expected).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    pub fn test_capturing_a_pose_reuses_the_snapshot() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![1.0, 1.0]);
+        let (mut graph, path, clip) = single_clip_graph(curve);
+        graph
+            .nodes
+            .get_mut(NodeId::ROOT)
+            .and_then(|root| root.get_input_mut(clip))
+            .unwrap()
+            .set_weight(0.0);
+
+        let type_registry = TypeRegistryArc::default();
+        type_registry.write().register::<Test>();
+        let mut world = World::new();
+        world.insert_resource(type_registry);
+        let entity = world.spawn().insert(Test { a: 5.0 }).id();
+        graph
+            .find_bone_mut(path.entity())
+            .unwrap()
+            .set_entity(Some(entity));
+
+        let snapshot = graph.capture_pose(&world).unwrap();
+        assert!(graph.add_input(NodeId::ROOT, snapshot).is_ok());
+        let clips = graph.clip_count();
+        let nodes = graph.node_count();
+        for value in [2.0, 3.0, 4.0] {
+            world.get_mut::<Test>(entity).unwrap().a = value;
+            assert_eq!(graph.capture_pose(&world).unwrap(), snapshot);
+            graph.evaluate();
+            assert_eq!(sample_f32(&graph, &path), value);
+        }
+        assert_eq!(graph.clip_count(), clips);
+        assert_eq!(graph.node_count(), nodes);
+    }
+
+    #[test]
+    pub fn test_crossfade_returns_unanimated_property_to_rest() {
+        let (mut graph, path, from) =
+            single_clip_graph(CurveFixed::from_keyframes(1.0, vec![1.0, 1.0]));
+        // The second clip only animates another entity.
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        let other =
+            PropertyPath::parse(&registry, "b@bevy_prototype_animation::graph::test::Test.a")
+                .unwrap();
+        let clip = AnimationClip::builder()
+            .add_curve(other, CurveFixed::from_keyframes(1.0, vec![2.0, 2.0]))
+            .build();
+        let to = graph.add_clip(&clip).unwrap();
+        graph.add_input(NodeId::ROOT, to).unwrap().set_weight(0.0);
+
+        let type_registry = TypeRegistryArc::default();
+        type_registry.write().register::<Test>();
+        let mut world = World::new();
+        world.insert_resource(type_registry);
+        let entity = world.spawn().insert(Test { a: 5.0 }).id();
+        graph
+            .find_bone_mut(path.entity())
+            .unwrap()
+            .set_entity(Some(entity));
+        graph.set_rest_pose_from_current(&world);
+
+        for step in 0..=4 {
+            let t = step as f32 / 4.0;
+            let root = graph.nodes.get_mut(NodeId::ROOT).unwrap();
+            root.get_input_mut(from).unwrap().set_weight(1.0 - t);
+            root.get_input_mut(to).unwrap().set_weight(t);
+            graph.evaluate();
+            let expected = 1.0 * (1.0 - t) + 5.0 * t;
+            assert!((sample_f32(&graph, &path) - expected).abs() < 1e-5);
+        }
+
+        // Without a rest value, the remaining weight goes to zero.
+        graph.clear_rest_pose();
+        graph.evaluate();
+        assert_eq!(sample_f32(&graph, &path), 0.0);
+        graph.set_rest_value(&path, &3.0f32).unwrap();
+        graph.evaluate();
+        assert_eq!(sample_f32(&graph, &path), 3.0);
+        assert!(graph.set_rest_value(&path, &3.0f64).is_err());
+    }
+
+    #[test]
+    pub fn test_sample_clip_property_matches_single_clip_graph() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![0.0, 4.0, 2.0]);
+        let (mut graph, path, node) = single_clip_graph(curve.clone());
+        let (mut single, _, _) = single_clip_graph(curve);
+        let clip = AnimationClip::builder()
+            .add_curve(
+                path.clone(),
+                CurveFixed::from_keyframes(1.0, vec![10.0f32, 10.0]),
+            )
+            .build();
+        let other = graph.add_clip(&clip).unwrap();
+        graph.add_input(NodeId::ROOT, other).unwrap();
+        graph.sample_at(0.25);
+        graph.evaluate();
+        let blended = sample_f32(&graph, &path);
+
+        for time in [0.0, 0.5, 1.25, 1.75, 2.5] {
+            single.sample_at(time);
+            single.evaluate();
+            let expected = sample_f32(&single, &path);
+            assert_eq!(
+                graph.sample_clip_property(node, &path, time),
+                Some(expected)
+            );
+            let value = graph
+                .sample_clip_property_reflect(node, &path, time)
+                .unwrap();
+            assert_eq!(value.downcast_ref::<f32>(), Some(&expected));
+        }
+        // Sampling a single clip leaves the graph's state untouched.
+        assert_eq!(graph.clip_time(node).unwrap(), 0.25);
+        assert_eq!(sample_f32(&graph, &path), blended);
+
+        assert_eq!(graph.sample_clip_property::<f64>(node, &path, 0.0), None);
+        assert_eq!(
+            graph.sample_clip_property::<f32>(NodeId::ROOT, &path, 0.0),
+            None
+        );
+    }
+
+    #[test]
+    pub fn test_sample_clip_property_of_fused_transforms() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Transform>();
+        let path = |field: &str| {
+            let path = format!(
+                "a@bevy_transform::components::transform::Transform.{}",
+                field
+            );
+            PropertyPath::parse(&registry, &path).unwrap()
+        };
+        let translation = CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::X, Vec3::Y]);
+        let clip = AnimationClip::builder()
+            .add_curve(path("translation"), translation.clone())
+            .add_curve(
+                path("rotation"),
+                CurveFixed::from_constant(Quat::from_rotation_y(1.0)),
+            )
+            .add_curve(path("scale"), CurveFixed::from_constant(Vec3::ONE))
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        graph.optimize();
+        assert!(graph.find_track(&path("translation")).is_none());
+
+        let translation_path = path("translation");
+        for time in [0.0, 0.5, 1.5] {
+            let value = graph.sample_clip_property::<Vec3>(node, &translation_path, time);
+            assert_eq!(value, Some(translation.sample(time)));
+        }
+    }
+
+    #[test]
+    pub fn test_sample_property_batch_matches_evaluation() {
+        let path = test_path();
+        let walk = AnimationClip::builder()
+            .add_curve(
+                path.clone(),
+                CurveFixed::from_keyframes(8.0, vec![0.0f32, 3.0, 1.0, 4.0, 1.0, 5.0]),
+            )
+            .build();
+        let run = AnimationClip::builder()
+            .add_curve(
+                path.clone(),
+                CurveFixed::from_keyframes(8.0, vec![2.0f32, 7.0, 1.0, 8.0]),
+            )
+            .build();
+        let build = |clips: &[&AnimationClip], time: f32, weight: f32| {
+            let mut graph = AnimationGraph::new();
+            for (idx, clip) in clips.iter().enumerate() {
+                let node = graph.add_clip(clip).unwrap();
+                let weight = if idx == 0 { 1.0 - weight } else { weight };
+                graph
+                    .add_input(NodeId::ROOT, node)
+                    .unwrap()
+                    .set_weight(weight);
+            }
+            graph.sample_at(time);
+            graph.evaluate();
+            graph
+        };
+
+        // The third graph doesn't share its curves with the others, and the
+        // last one has a rest value.
+        let mut graphs: Vec<_> = (0..20)
+            .map(|idx| build(&[&walk, &run], idx as f32 * 0.07, idx as f32 / 19.0))
+            .collect();
+        graphs[2] = build(&[&run, &walk], 0.3, 0.5);
+        graphs[19] = build(&[&walk], 0.2, 0.5);
+        graphs[19].set_rest_value(&path, &1.5f32).unwrap();
+        graphs[19].evaluate();
+
+        let graphs: Vec<_> = graphs.iter().collect();
+        let mut batch = vec![0.0; graphs.len()];
+        AnimationGraph::sample_property_batch(&graphs, &path, &mut batch).unwrap();
+        for (graph, value) in graphs.iter().zip(batch) {
+            assert_eq!(value, sample_f32(graph, &path));
+        }
+
+        // Buffers grown by a larger batch are reused for smaller ones.
+        let mut buffers = BatchBuffers::new();
+        for graphs in [&graphs[..], &graphs[3..7]] {
+            let mut batch = vec![0.0; graphs.len()];
+            AnimationGraph::sample_property_batch_with(graphs, &path, &mut batch, &mut buffers)
+                .unwrap();
+            for (graph, value) in graphs.iter().zip(batch) {
+                assert_eq!(value, sample_f32(graph, &path));
+            }
+        }
+
+        let mut wrong_type = vec![Vec3::ZERO; graphs.len()];
+        assert!(AnimationGraph::sample_property_batch(&graphs, &path, &mut wrong_type).is_err());
+    }
+
+    #[test]
+    pub fn test_reverse_playback_loop_wraps() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![0.0, 1.0, 2.0]);
+        let (mut graph, path, clip) = single_clip_graph(curve.clone());
+        assert!(graph.set_playback_mode(clip, PlaybackMode::Loop).is_ok());
+
+        graph.advance_time(-0.5);
+        graph.evaluate();
+        assert_eq!(graph.clip_time(clip).ok(), Some(1.5));
+        assert_eq!(sample_f32(&graph, &path), curve.sample(1.5));
+
+        graph.advance_time(-7.0);
+        assert!((graph.clip_time(clip).ok().unwrap() - 0.5).abs() < 1e-5);
+        assert_eq!(graph.is_finished_reverse(clip).ok(), Some(false));
+    }
+
+    #[test]
+    pub fn test_reverse_playback_once_clamps() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![0.0, 1.0, 2.0]);
+        let (mut graph, path, clip) = single_clip_graph(curve.clone());
+
+        graph.advance_time(1.0);
+        graph.advance_time(-0.75);
+        assert_eq!(graph.clip_time(clip).ok(), Some(0.25));
+        assert_eq!(graph.is_finished_reverse(clip).ok(), Some(false));
+
+        graph.advance_time(-0.75);
+        graph.evaluate();
+        assert_eq!(graph.clip_time(clip).ok(), Some(0.0));
+        assert_eq!(graph.is_finished_reverse(clip).ok(), Some(true));
+        assert_eq!(graph.is_finished(clip).ok(), Some(false));
+        assert_eq!(sample_f32(&graph, &path), curve.sample(0.0));
+
+        graph.advance_time(5.0);
+        assert_eq!(graph.clip_time(clip).ok(), Some(2.0));
+        assert_eq!(graph.is_finished(clip).ok(), Some(true));
+        assert_eq!(graph.is_finished_reverse(clip).ok(), Some(false));
+    }
+
+    #[test]
+    pub fn test_trimmed_clip_nodes_share_curves() {
+        let path = test_path();
+        let curve = CurveFixed::from_keyframes(1.0, (0..=10).map(|x| x as f32).collect());
+        let clip = AnimationClip::builder()
+            .add_curve(path.clone(), curve)
+            .build();
+        let mut graph = AnimationGraph::new();
+        let looped = graph.add_clip(&clip).unwrap();
+        let once = graph.add_clip(&clip).unwrap();
+        assert!(graph.add_input(NodeId::ROOT, looped).is_ok());
+        assert!(graph.add_input(NodeId::ROOT, once).is_ok());
+
+        assert!(graph.set_playback_mode(looped, PlaybackMode::Loop).is_ok());
+        assert!(graph.set_clip_range(looped, 2.0..4.0).is_ok());
+        assert!(graph.set_clip_start_offset(once, 6.0).is_ok());
+        assert!(graph.set_clip_range(once, 6.0..9.0).is_ok());
+        assert_eq!(graph.clip_duration(looped).ok(), Some(2.0));
+        assert_eq!(graph.clip_duration(once).ok(), Some(3.0));
+
+        graph.advance_time(3.0);
+        assert_eq!(graph.clip_time(looped).ok(), Some(1.0));
+        assert_eq!(graph.clip_time(once).ok(), Some(3.0));
+        assert_eq!(graph.is_finished(looped).ok(), Some(false));
+        assert_eq!(graph.is_finished(once).ok(), Some(true));
+
+        let set_weights = |graph: &mut AnimationGraph, looped_weight: f32| {
+            let root = graph.nodes.get_mut(NodeId::ROOT).unwrap();
+            root.get_input_mut(looped)
+                .unwrap()
+                .set_weight(looped_weight);
+            root.get_input_mut(once)
+                .unwrap()
+                .set_weight(1.0 - looped_weight);
+            graph.evaluate();
+        };
+        set_weights(&mut graph, 1.0);
+        assert_eq!(sample_f32(&graph, &path), 3.0);
+        set_weights(&mut graph, 0.0);
+        assert_eq!(sample_f32(&graph, &path), 9.0);
+
+        // Both nodes reference the same curve: one owned by the clip, one per
+        // node in the track, and the one fetched here.
+        let bone = graph.find_bone(path.entity()).unwrap();
+        assert_eq!(bone.tracks.len(), 1);
+        let curve = clip.get_curve::<f32>(&Hashed::new(path.clone())).unwrap();
+        assert_eq!(Arc::strong_count(&curve), 4);
+
+        assert!(graph.clear_clip_range(once).is_ok());
+        assert_eq!(graph.clip_duration(once).ok(), Some(10.0));
+    }
+
+    #[test]
+    pub fn test_handle_curve_steps_and_upgrades() {
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .add_plugin(AssetPlugin)
+            .add_asset::<TestAsset>();
+        let handles: Vec<Handle<TestAsset>> = {
+            let mut assets = app.world.get_resource_mut::<Assets<TestAsset>>().unwrap();
+            (0..3).map(|_| assets.add(TestAsset)).collect()
+        };
+
+        let mut registry = TypeRegistry::default();
+        registry.register::<TestHandle>();
+        let path = PropertyPath::parse(
+            &registry,
+            "a@bevy_prototype_animation::graph::test::TestHandle.handle",
+        )
+        .unwrap();
+        let clip = AnimationClip::builder()
+            .add_curve(
+                path.clone(),
+                CurveFixed::from_keyframes(1.0, handles.clone()),
+            )
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        assert!(graph.add_input(NodeId::ROOT, node).is_ok());
+
+        let mut output = Handle::<TestAsset>::default();
+        for (time, expected) in [(0.0, 0), (0.5, 0), (1.0, 1), (1.75, 1), (2.0, 2), (0.25, 0)] {
+            graph.sample_at(time);
+            let track = graph
+                .find_bone(path.entity())
+                .and_then(|bone| bone.tracks.get(path.access()))
+                .unwrap();
+            let result =
+                track.blend_via_reflect(&graph.state, &mut output, WorldResources::new(&app.world));
+            assert!(result.is_ok());
+            assert_eq!(output.id, handles[expected].id);
+            assert!(output.is_strong());
+        }
+    }
+
+    #[test]
+    pub fn test_validate_against_hierarchy() {
+        let (graph, path, _) = single_clip_graph(CurveFixed::from_keyframes(1.0, vec![0.0, 1.0]));
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        let mut world = World::new();
+        let root = world.spawn().id();
+        let errors = graph.validate_against(&registry, &world, root);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, path);
+        assert_eq!(errors[0].kind, ClipValidationErrorKind::MissingEntity);
+
+        let bone = world.spawn().insert(Name::new("a")).id();
+        world.entity_mut(root).insert(Children::with(&[bone]));
+        let errors = graph.validate_against(&registry, &world, root);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ClipValidationErrorKind::MissingComponent);
+
+        world.entity_mut(bone).insert(Test::default());
+        assert!(graph.validate_against(&registry, &world, root).is_empty());
+    }
+
+    #[test]
+    pub fn test_conflicting_clip_types_are_rejected() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        let path = PropertyPath::parse(
+            &registry,
+            "root@bevy_prototype_animation::graph::test::Test.a",
+        )
+        .unwrap();
+        let scalar = AnimationClip::builder()
+            .add_curve(
+                path.clone(),
+                CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]),
+            )
+            .build();
+        let vector = AnimationClip::builder()
+            .add_curve(
+                path.clone(),
+                CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::ONE]),
+            )
+            .build();
+
+        let mut graph = AnimationGraph::new();
+        graph.add_clip(&scalar).unwrap();
+        let err = graph.add_clip(&vector).unwrap_err();
+        assert!(matches!(
+            err,
+            AnimationGraphError::Track(TrackError::ConflictingType { .. })
+        ));
+        assert!(err.to_string().contains(&path.to_string()));
+        // The failed clip should not have been added to the graph.
+        assert_eq!(graph.state.clips.len(), 1);
+    }
+
+    #[test]
+    pub fn test_type_conflicts_are_all_reported() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        let path = |entity: &str| {
+            let path = format!("{}@bevy_prototype_animation::graph::test::Test.a", entity);
+            PropertyPath::parse(&registry, &path).unwrap()
+        };
+        let (a, b, c) = (path("a"), path("b"), path("c"));
+        let scalars = CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0, 2.0]);
+        let vectors = CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::ONE]);
+        let clip_a = AnimationClip::builder()
+            .add_curve(b.clone(), scalars.clone())
+            .add_curve(a.clone(), scalars.clone())
+            .build();
+        let clip_b = AnimationClip::builder()
+            .add_curve(c.clone(), scalars.clone())
+            .add_curve(b.clone(), vectors.clone())
+            .add_curve(a.clone(), vectors)
+            .build();
+
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip_a).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+        let conflicts = match graph.add_clip(&clip_b) {
+            Err(AnimationGraphError::Track(TrackError::ConflictingType { conflicts })) => conflicts,
+            result => panic!("expected a type conflict, got {:?}", result),
+        };
+        let paths: Vec<_> = conflicts.iter().map(|conflict| &conflict.path).collect();
+        assert_eq!(paths, vec![&a, &b]);
+        for conflict in conflicts.iter() {
+            assert_eq!(conflict.existing, TypeId::of::<f32>());
+            assert_eq!(conflict.existing_name, std::any::type_name::<f32>());
+            assert_eq!(conflict.incoming, TypeId::of::<Vec3>());
+            assert_eq!(conflict.incoming_name, std::any::type_name::<Vec3>());
+        }
+
+        // Nothing from the rejected clip is left behind, including the bone
+        // it would have added.
+        assert_eq!(graph.state.clips.len(), 1);
+        assert!(graph.find_bone(c.entity()).is_none());
+        graph.advance_time(0.5);
+        graph.evaluate();
+        let mut values = [0.0f32; 1];
+        for path in [&a, &b] {
+            AnimationGraph::sample_property_batch(&[&graph], path, &mut values).unwrap();
+            assert_eq!(values[0], scalars.sample(0.5));
+        }
+    }
+
+    #[test]
+    pub fn test_clip_batches_are_added_atomically() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        let path = |entity: &str| {
+            let path = format!("{}@bevy_prototype_animation::graph::test::Test.a", entity);
+            PropertyPath::parse(&registry, &path).unwrap()
+        };
+        let (a, b, c) = (path("a"), path("b"), path("c"));
+        let scalars = |offset: f32| CurveFixed::from_keyframes(1.0, vec![offset, offset + 1.0]);
+        let vectors = CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::ONE]);
+        let clip = |paths: &[&PropertyPath], offset: f32| {
+            paths
+                .iter()
+                .fold(AnimationClip::builder(), |builder, path| {
+                    builder.add_curve((*path).clone(), scalars(offset))
+                })
+                .build()
+        };
+        let good = [clip(&[&a], 0.0), clip(&[&a, &b], 1.0), clip(&[&c], 2.0)];
+        // Only conflicts with another clip in the same batch.
+        let bad = AnimationClip::builder()
+            .add_curve(b.clone(), vectors)
+            .build();
+
+        let mut graph = AnimationGraph::new();
+        graph.reserve(3, 4);
+        let batch = [&good[0], &good[1], &bad, &good[2]];
+        let conflicts = match graph.add_clips(batch) {
+            Err(AnimationGraphError::Track(TrackError::ConflictingType { conflicts })) => conflicts,
+            result => panic!("expected a type conflict, got {:?}", result),
+        };
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, b);
+        assert_eq!(conflicts[0].existing, TypeId::of::<f32>());
+        assert_eq!(conflicts[0].incoming, TypeId::of::<Vec3>());
+        // Nothing from the batch was added, not even the clips before the
+        // conflicting one.
+        assert_eq!(graph.state.clips.len(), 0);
+        assert_eq!(graph.nodes.count(), 1);
+        assert_eq!(graph.bones().count(), 0);
+
+        let nodes = graph.add_clips(&good).unwrap();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(graph.bones().count(), 3);
+        assert!(graph.clips.is_dirty());
+        for (idx, node) in nodes.iter().enumerate() {
+            assert_eq!(graph.clip_id(*node).unwrap(), ClipId(idx as u16));
+        }
+
+        // Batches are added exactly like single clips.
+        let mut single = AnimationGraph::new();
+        for clip in good.iter() {
+            single.add_clip(clip).unwrap();
+        }
+        for graph in [&mut graph, &mut single] {
+            for node in nodes.iter() {
+                graph.add_input(NodeId::ROOT, *node).unwrap();
+            }
+            graph.advance_time(0.5);
+            graph.evaluate();
+        }
+        for path in [&a, &b, &c] {
+            assert_eq!(sample_f32(&graph, path), sample_f32(&single, path));
+        }
+    }
+
+    #[test]
+    pub fn test_phase_offsets_shift_shared_clips() {
+        let curve = CurveFixed::from_keyframes(4.0, vec![0.0f32, 1.0, 2.0, 3.0, 4.0]);
+        let path = test_path();
+        let clip = AnimationClip::builder()
+            .add_curve(path.clone(), curve.clone())
+            .build();
+        let graph = |offset: f32| {
+            let mut graph = AnimationGraph::new();
+            let node = graph.add_clip(&clip).unwrap();
+            graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+            graph.set_clip_phase_offset(node, offset).unwrap();
+            graph.add_input(NodeId::ROOT, node).unwrap();
+            (graph, node)
+        };
+        let (mut unshifted, node) = graph(0.0);
+        let (mut shifted, _) = graph(0.25);
+        assert_eq!(shifted.clip_phase_offset(node).unwrap(), 0.25);
+
+        for (time, shifted_time) in [(0.5, 0.75), (0.9, 0.15)] {
+            for graph in [&mut unshifted, &mut shifted] {
+                graph.set_time(node, time).unwrap();
+                graph.evaluate();
+            }
+            // Only the sampled time is shifted.
+            assert_eq!(shifted.clip_time(node).unwrap(), time);
+            assert_eq!(sample_f32(&unshifted, &path), curve.sample(time));
+            assert!((sample_f32(&shifted, &path) - curve.sample(shifted_time)).abs() < 1e-5);
+        }
+
+        // Clips that play once clamp the shifted time instead.
+        shifted.set_playback_mode(node, PlaybackMode::Once).unwrap();
+        shifted.set_time(node, 0.9).unwrap();
+        shifted.evaluate();
+        assert_eq!(sample_f32(&shifted, &path), curve.sample(1.0));
+    }
+
+    #[test]
+    pub fn test_phase_offsets_apply_after_sync() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        let path = |entity: &str| {
+            let path = format!("{}@bevy_prototype_animation::graph::test::Test.a", entity);
+            PropertyPath::parse(&registry, &path).unwrap()
+        };
+        let (leader_path, follower_path) = (path("leader"), path("follower"));
+        let curve = CurveFixed::from_keyframes(4.0, vec![0.0f32, 1.0, 2.0, 3.0, 4.0]);
+        let mut graph = AnimationGraph::new();
+        let mut add = |path: &PropertyPath, weight: f32| {
+            let clip = AnimationClip::builder()
+                .add_curve(path.clone(), curve.clone())
+                .build();
+            let node = graph.add_clip(&clip).unwrap();
+            graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+            graph.set_sync_group(node, "gait", false).unwrap();
+            graph
+                .add_input(NodeId::ROOT, node)
+                .unwrap()
+                .set_weight(weight);
+            node
+        };
+        let leader = add(&leader_path, 1.0);
+        let follower = add(&follower_path, 1.0);
+        graph.set_clip_phase_offset(follower, 0.5).unwrap();
+
+        for _ in 0..8 {
+            graph.advance_time(0.15);
+            graph.evaluate();
+            let time = graph.clip_time(leader).unwrap();
+            assert_eq!(graph.clip_time(follower).unwrap(), time);
+            let shifted = (time + 0.5).rem_euclid(1.0);
+            assert!((sample_f32(&graph, &leader_path) - curve.sample(time)).abs() < 1e-5);
+            assert!((sample_f32(&graph, &follower_path) - curve.sample(shifted)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    pub fn test_randomized_phases_are_reproducible() {
+        let clip = |duration: f32| {
+            AnimationClip::builder()
+                .add_curve(
+                    test_path(),
+                    CurveFixed::from_keyframes(duration.recip(), vec![0.0f32, 1.0]),
+                )
+                .build()
+        };
+        let build = |seed: u64| {
+            let mut graph = AnimationGraph::new();
+            let nodes: Vec<_> = [1.0, 2.0, 0.5, 3.0]
+                .iter()
+                .map(|duration| graph.add_clip(&clip(*duration)).unwrap())
+                .collect();
+            for node in &nodes[..3] {
+                graph.set_playback_mode(*node, PlaybackMode::Loop).unwrap();
+            }
+            graph.randomize_phases(seed);
+            let offsets: Vec<_> = nodes
+                .iter()
+                .map(|node| graph.clip_phase_offset(*node).unwrap())
+                .collect();
+            offsets
+        };
+
+        let offsets = build(7);
+        assert_eq!(offsets, build(7));
+        assert_ne!(offsets, build(8));
+        for (offset, duration) in offsets[..3].iter().zip([1.0, 2.0, 0.5]) {
+            assert!((0.0..duration).contains(offset));
+        }
+        // Clips that don't loop keep their offset.
+        assert_eq!(offsets[3], 0.0);
+    }
+
+    #[test]
+    pub fn test_shared_curve_shares_tracks() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        let paths: Vec<PropertyPath> = (0..20)
+            .map(|i| {
+                let path = format!("e{}@bevy_prototype_animation::graph::test::Test.a", i);
+                PropertyPath::parse(&registry, &path).unwrap()
+            })
+            .collect();
+        let clip = AnimationClip::builder()
+            .add_curve_multi(
+                paths.iter().cloned(),
+                CurveFixed::from_keyframes(1.0, vec![0.0f32, 2.0]),
+            )
+            .build();
+        let mut graph = AnimationGraph::new();
+        let fade = graph.add_clip(&clip).unwrap();
+        assert!(graph.add_input(NodeId::ROOT, fade).is_ok());
+
+        // One reference per property in the clip, one for the shared track,
+        // and the one fetched here.
+        let curve = clip
+            .get_curve::<f32>(&Hashed::new(paths[0].clone()))
+            .unwrap();
+        assert_eq!(Arc::strong_count(&curve), 22);
+        let track = &graph.find_bone(paths[0].entity()).unwrap().tracks[paths[0].access()];
+        for path in paths.iter() {
+            let other = &graph.find_bone(path.entity()).unwrap().tracks[path.access()];
+            assert!(Arc::ptr_eq(track, other));
+        }
+
+        graph.sample_at(0.5);
+        for path in paths.iter() {
+            assert_eq!(sample_f32(&graph, path), 1.0);
+        }
+
+        // Adding distinct curves afterwards must not affect the other bones.
+        let mut builder = AnimationClip::builder();
+        for (i, path) in paths.iter().enumerate() {
+            builder = builder.add_curve(path.clone(), CurveFixed::from_constant(i as f32));
+        }
+        let offsets = graph.add_clip(&builder.build()).unwrap();
+        assert!(graph.add_input(NodeId::ROOT, offsets).is_ok());
+        graph
+            .nodes
+            .get_mut(NodeId::ROOT)
+            .and_then(|root| root.get_input_mut(fade))
+            .unwrap()
+            .set_weight(0.0);
+        graph.sample_at(0.5);
+        for (i, path) in paths.iter().enumerate() {
+            assert_eq!(sample_f32(&graph, path), i as f32);
+        }
+    }
+
+    #[test]
+    pub fn test_fixed_interpolated_update_mode() {
+        let curve = CurveFixed::from_keyframes(1.0, (0..=10).map(|x| x as f32).collect());
+        let (mut graph, path, clip) = single_clip_graph(curve);
+        for hz in [0.0, -10.0, f32::NAN, f32::INFINITY] {
+            assert!(graph
+                .set_update_mode(UpdateMode::FixedInterpolated { hz })
+                .is_err());
+        }
+        assert_eq!(graph.update_mode(), UpdateMode::PerFrame);
+        assert!(graph
+            .set_update_mode(UpdateMode::FixedInterpolated { hz: 10.0 })
+            .is_ok());
+        graph.evaluate();
+
+        // Render deltas that don't line up with the fixed steps.
+        for (delta, clip_time, expected) in [
+            (0.25, 0.2, 0.15),
+            (0.02, 0.2, 0.17),
+            (0.04, 0.3, 0.21),
+            (0.0, 0.3, 0.21),
+        ] {
+            graph.advance_time(delta);
+            graph.evaluate();
+            assert!((graph.clip_time(clip).ok().unwrap() - clip_time).abs() < 1e-5);
+            assert!((sample_f32(&graph, &path) - expected).abs() < 1e-5);
+        }
+
+        assert!(graph.set_update_mode(UpdateMode::PerFrame).is_ok());
+        graph.advance_time(0.25);
+        graph.evaluate();
+        assert!((sample_f32(&graph, &path) - 0.55).abs() < 1e-5);
+    }
+
+    #[test]
+    pub fn test_all_zero_weights_are_not_nan() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![1.0f32, 2.0]);
+        let (mut graph, path, clip) = single_clip_graph(curve);
+        graph
+            .override_input_weight(NodeId::ROOT, clip, 0.0)
+            .unwrap();
+        graph.evaluate();
+        assert_eq!(graph.clip_weight(clip).unwrap(), 0.0);
+        assert_eq!(graph.total_weight(), 0.0);
+        assert_eq!(sample_f32(&graph, &path), 0.0);
+    }
+
+    #[test]
+    pub fn test_fixed_interpolated_catch_up_is_capped() {
+        let curve = CurveFixed::from_keyframes(1.0, (0..=10).map(|x| x as f32).collect());
+        let (mut graph, path, clip) = single_clip_graph(curve);
+        graph
+            .set_update_mode(UpdateMode::FixedInterpolated { hz: 10.0 })
+            .unwrap();
+        graph.evaluate();
+
+        // A long stall only advances the clip by the maximum number of steps,
+        // and the dropped steps aren't caught up on later.
+        graph.advance_time(5.05);
+        graph.evaluate();
+        let max_time = MAX_FIXED_STEPS_PER_UPDATE as f32 * 0.1;
+        assert!((graph.clip_time(clip).unwrap() - max_time).abs() < 1e-4);
+        assert!((sample_f32(&graph, &path) - (max_time - 0.05)).abs() < 1e-4);
+        graph.advance_time(0.1);
+        assert!((graph.clip_time(clip).unwrap() - (max_time + 0.1)).abs() < 1e-4);
+
+        // The playback mode also applies to the previous step.
+        graph.set_playback_mode(clip, PlaybackMode::Loop).unwrap();
+        let clip_id = graph.clip_id(clip).unwrap();
+        let (previous, _) = graph.state.previous.as_ref().unwrap();
+        assert_eq!(previous[clip_id.0 as usize].mode, PlaybackMode::Loop);
+    }
+
+    #[test]
+    pub fn test_single_clips_are_sampled_directly() {
+        let path = test_path();
+        let curve = CurveFixed::from_keyframes(3.0, vec![0.1f32, 0.7, 0.3, 0.9]);
+        let clip = AnimationClip::builder()
+            .add_curve(path.clone(), curve.clone())
+            .build();
+        let other = AnimationClip::builder()
+            .add_curve(
+                path.clone(),
+                CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]),
+            )
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        let other_node = graph.add_clip(&other).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+        graph
+            .add_input(NodeId::ROOT, other_node)
+            .unwrap()
+            .set_weight(0.0);
+        let clip_id = graph.clip_id(node).unwrap();
+
+        let mut time = 0.0;
+        for _ in 0..10 {
+            graph.advance_time(0.13);
+            time += 0.13;
+            graph.evaluate();
+            assert_eq!(graph.state.single_clip(), Some(clip_id));
+            assert_eq!(
+                sample_f32(&graph, &path).to_bits(),
+                curve.sample(time).to_bits()
+            );
+        }
+
+        // Blends of several clips, and additive clips, are blended as usual.
+        graph
+            .override_input_weight(NodeId::ROOT, other_node, 0.5)
+            .unwrap();
+        graph.evaluate();
+        assert_eq!(graph.state.single_clip(), None);
+        graph
+            .override_input_weight(NodeId::ROOT, other_node, 0.0)
+            .unwrap();
+        graph.set_additive(node, true).unwrap();
+        graph.evaluate();
+        assert_eq!(graph.state.single_clip(), None);
+    }
+
+    #[test]
+    pub fn test_deterministic_weights_match_reference_bits() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]);
+        let (mut graph, _, first) = single_clip_graph(curve.clone());
+        let clip = AnimationClip::builder()
+            .add_curve(test_path(), curve)
+            .build();
+        let second = graph.add_clip(&clip).unwrap();
+        let blend = graph
+            .nodes
+            .add(Node::Blend {
+                inputs: Vec::new(),
+                propogate_time: true,
+            })
+            .unwrap();
+        // root -> first (0.3), root -> blend (0.7) -> first (0.25), second (0.75)
+        graph
+            .input_mut(NodeId::ROOT, first)
+            .unwrap()
+            .set_weight(0.3);
+        graph
+            .add_input(NodeId::ROOT, blend)
+            .unwrap()
+            .set_weight(0.7);
+        graph.add_input(blend, first).unwrap().set_weight(0.25);
+        graph.add_input(blend, second).unwrap().set_weight(0.75);
+        graph.set_deterministic(true);
+        assert!(graph.clone().is_deterministic());
+
+        // In Q16.16, 0.3 + 0.7 * 0.25 is (19661 + 11469) / 2^16, and
+        // 0.7 * 0.75 is 34406 / 2^16.
+        for _ in 0..3 {
+            graph.evaluate();
+            let first = graph.clip_weight(first).unwrap();
+            let second = graph.clip_weight(second).unwrap();
+            assert_eq!(first.to_bits(), 0x3ef3_3400);
+            assert_eq!(second.to_bits(), 0x3f06_6600);
+            assert_eq!(graph.total_weight().to_bits(), 1.0f32.to_bits());
+        }
+
+        graph.set_deterministic(false);
+        graph.evaluate();
+        let weight = graph.clip_weight(first).unwrap();
+        assert!((weight - 0.475).abs() < 1e-6);
+        assert_ne!(weight.to_bits(), 0x3ef3_3400);
+    }
+
+    #[test]
+    pub fn test_solo_and_mute_leave_authored_weights() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]);
+        let (mut graph, _, a) = single_clip_graph(curve.clone());
+        let clip = AnimationClip::builder()
+            .add_curve(test_path(), curve)
+            .build();
+        let b = graph.add_clip(&clip).unwrap();
+        graph.input_mut(NodeId::ROOT, a).unwrap().set_weight(0.5);
+        graph.add_input(NodeId::ROOT, b).unwrap().set_weight(0.5);
+        let weights = |graph: &mut AnimationGraph| {
+            graph.evaluate();
+            (graph.clip_weight(a).unwrap(), graph.clip_weight(b).unwrap())
+        };
+        assert_eq!(weights(&mut graph), (0.5, 0.5));
+
+        assert!(graph.solo(a).is_ok());
+        assert!(graph.is_soloed(a) && !graph.is_soloed(b));
+        assert_eq!(weights(&mut graph), (1.0, 0.0));
+        assert_eq!(graph.total_weight(), 1.0);
+        assert!(graph.solo(b).is_ok());
+        assert_eq!(weights(&mut graph), (0.5, 0.5));
+        graph.clear_solo_mute();
+        assert_eq!(weights(&mut graph), (0.5, 0.5));
+
+        assert!(graph.mute(b).is_ok());
+        assert!(graph.is_muted(b));
+        assert_eq!(weights(&mut graph), (0.5, 0.0));
+        assert!(graph.mute(NodeId::ROOT).is_ok());
+        assert_eq!(weights(&mut graph), (0.0, 0.0));
+        assert_eq!(graph.total_weight(), 0.0);
+        graph.clear_solo_mute();
+        assert!(!graph.is_muted(b));
+        assert_eq!(weights(&mut graph), (0.5, 0.5));
+        assert_eq!(graph.input(NodeId::ROOT, b).unwrap().weight(), 0.5);
+
+        assert!(matches!(
+            graph.solo(NodeId::ROOT),
+            Err(AnimationGraphError::NotClipNode(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_paused_clips_keep_their_time() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0, 2.0, 3.0]);
+        let (mut graph, _, a) = single_clip_graph(curve.clone());
+        let clip = AnimationClip::builder()
+            .add_curve(test_path(), curve)
+            .build();
+        let b = graph.add_clip(&clip).unwrap();
+        graph.add_input(NodeId::ROOT, b).unwrap();
+
+        graph.advance_time(0.5);
+        assert!(graph.pause_clip(a).is_ok());
+        assert!(graph.is_clip_paused(a).unwrap());
+        for _ in 0..4 {
+            graph.advance_time(0.25);
+        }
+        assert_eq!(graph.clip_time(a).unwrap(), 0.5);
+        assert_eq!(graph.clip_time(b).unwrap(), 1.5);
+
+        assert!(graph.resume_clip(a).is_ok());
+        graph.advance_time(0.25);
+        assert_eq!(graph.clip_time(a).unwrap(), 0.75);
+        assert!(graph.pause_clip(NodeId::ROOT).is_err());
+    }
+
+    #[test]
+    pub fn test_node_duration_and_remaining_time() {
+        let curve = CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0, 2.0]);
+        let (mut graph, _, a) = single_clip_graph(curve.clone());
+        graph.set_clip_speed(a, 1.5).unwrap();
+        assert_eq!(graph.clip_speed(a).unwrap(), 1.5);
+        assert!((graph.node_duration(a).unwrap() - 2.0 / 1.5).abs() < 1e-5);
+        assert!((graph.remaining_time(a).unwrap() - 2.0 / 1.5).abs() < 1e-5);
+
+        graph.advance_time(0.4);
+        assert!((graph.clip_time(a).unwrap() - 0.6).abs() < 1e-5);
+        assert!((graph.remaining_time(a).unwrap() - (2.0 / 1.5 - 0.4)).abs() < 1e-5);
+
+        graph.set_playback_mode(a, PlaybackMode::Loop).unwrap();
+        assert!(graph.node_duration(a).is_some());
+        assert_eq!(graph.remaining_time(a), None);
+    }
+
+    #[test]
+    pub fn test_blend_node_duration_is_longest_input() {
+        let clip = |duration: f32| {
+            AnimationClip::builder()
+                .add_curve(
+                    test_path(),
+                    CurveFixed::from_keyframes(1.0 / duration, vec![0.0f32, 1.0]),
+                )
+                .build()
+        };
+        let mut graph = AnimationGraph::new();
+        let blend = graph
+            .nodes
+            .add(Node::Blend {
+                inputs: Vec::new(),
+                propogate_time: false,
+            })
+            .unwrap();
+        let short = graph.add_clip(&clip(1.0)).unwrap();
+        let long = graph.add_clip(&clip(3.0)).unwrap();
+        graph.add_input(NodeId::ROOT, blend).unwrap();
+        graph.add_input(blend, short).unwrap();
+        graph.add_input(blend, long).unwrap();
+        // Cycles are only traversed once.
+        graph.add_input(blend, NodeId::ROOT).unwrap();
+
+        assert!((graph.node_duration(blend).unwrap() - 3.0).abs() < 1e-5);
+        assert!((graph.node_duration(NodeId::ROOT).unwrap() - 3.0).abs() < 1e-5);
+        graph.advance_time(0.5);
+        assert!((graph.remaining_time(blend).unwrap() - 2.5).abs() < 1e-5);
+    }
+
+    #[test]
+    pub fn test_additive_clip_composes_with_base() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Transform>();
+        let path = |field: &str| {
+            let path = format!(
+                "a@bevy_transform::components::transform::Transform.{}",
+                field
+            );
+            PropertyPath::parse(&registry, &path).unwrap()
+        };
+        let clip = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let translation: Vec<Vec3> = (0..5)
+                .map(|_| Vec3::new(rng.gen(), rng.gen(), rng.gen()))
+                .collect();
+            let rotation: Vec<Quat> = (0..5)
+                .map(|_| Quat::from_euler(EulerRot::XYZ, rng.gen(), rng.gen(), rng.gen()))
+                .collect();
+            AnimationClip::builder()
+                .add_curve(
+                    path("translation"),
+                    CurveFixed::from_keyframes(2.0, translation),
+                )
+                .add_curve(path("rotation"), CurveFixed::from_keyframes(2.0, rotation))
+        };
+        let base = clip(1).build();
+        let offset = clip(2).build();
+        let additive = clip(2).make_additive(&base, 0.0).build();
+        assert!(!base.is_additive());
+        assert!(additive.is_additive());
+
+        let mut graph = AnimationGraph::new();
+        let base_node = graph.add_clip(&base).unwrap();
+        let additive_node = graph.add_clip(&additive).unwrap();
+        assert!(!graph.is_additive(base_node).unwrap());
+        assert!(graph.is_additive(additive_node).unwrap());
+        graph.add_input(NodeId::ROOT, base_node).unwrap();
+        graph.add_input(NodeId::ROOT, additive_node).unwrap();
+
+        let translation_path = Hashed::new(path("translation"));
+        let rotation_path = Hashed::new(path("rotation"));
+        let base_translation = base.get_curve::<Vec3>(&translation_path).unwrap();
+        let base_rotation = base.get_curve::<Quat>(&rotation_path).unwrap();
+        let offset_translation = offset.get_curve::<Vec3>(&translation_path).unwrap();
+        let offset_rotation = offset.get_curve::<Quat>(&rotation_path).unwrap();
+        let reference_translation = base_translation.sample(0.0);
+        let reference_rotation = base_rotation.sample(0.0);
+        let bone = graph.find_bone(path("translation").entity()).unwrap();
+        let translation = bone.tracks[path("translation").access()].clone();
+        let rotation = bone.tracks[path("rotation").access()].clone();
+        for time in [0.0, 0.3, 1.1, 2.0] {
+            graph.sample_at(time);
+            let expected = base_translation.sample(time)
+                + (offset_translation.sample(time) - reference_translation);
+            let actual = translation
+                .as_any()
+                .downcast_ref::<CurveTrack<Vec3>>()
+                .unwrap()
+                .sample_and_blend(&graph.state);
+            assert!(actual.abs_diff_eq(expected, 1e-5));
+
+            let expected = base_rotation.sample(time)
+                * (reference_rotation.inverse() * offset_rotation.sample(time));
+            let actual = rotation
+                .as_any()
+                .downcast_ref::<CurveTrack<Quat>>()
+                .unwrap()
+                .sample_and_blend(&graph.state);
+            assert!(actual.normalize().dot(expected.normalize()).abs() > 1.0 - 1e-5);
+        }
+    }
+
+    #[test]
+    pub fn test_sync_group_keeps_clips_phase_aligned() {
+        let clip = |duration: f32| {
+            AnimationClip::builder()
+                .add_curve(
+                    test_path(),
+                    CurveFixed::from_keyframes(duration.recip(), vec![0.0f32, 1.0]),
+                )
+                .build()
+        };
+        let mut graph = AnimationGraph::new();
+        let walk = graph.add_clip(&clip(1.0)).unwrap();
+        let run = graph.add_clip(&clip(0.6)).unwrap();
+        for node in [walk, run] {
+            graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+            graph.set_sync_group(node, "locomotion", true).unwrap();
+            graph.add_input(NodeId::ROOT, node).unwrap();
+        }
+
+        let frames = 40;
+        let delta_time = 1.0 / 30.0;
+        let mut previous_phase = 0.0;
+        for frame in 0..=frames {
+            // Fade from walking to running.
+            let t = frame as f32 / frames as f32;
+            let root = graph.nodes.get_mut(NodeId::ROOT).unwrap();
+            root.get_input_mut(walk).unwrap().set_weight(1.0 - t);
+            root.get_input_mut(run).unwrap().set_weight(t);
+            graph.evaluate();
+            graph.advance_time(delta_time);
+
+            let walk_phase = graph.clip_time(walk).unwrap() / 1.0;
+            let run_phase = graph.clip_time(run).unwrap() / graph.clip_duration(run).unwrap();
+            assert!((walk_phase - run_phase).abs() < 1e-4);
+
+            // The phase advances smoothly, at the rate of whichever clip leads.
+            let step = (walk_phase - previous_phase).rem_euclid(1.0);
+            assert!(step >= delta_time / 1.0 - 1e-4);
+            assert!(step <= delta_time / 0.6 + 1e-4);
+            previous_phase = walk_phase;
+        }
+    }
+
+    #[test]
+    pub fn test_fused_transform_matches_unfused() {
+        let mut rng = StdRng::seed_from_u64(0x7f);
+        let mut registry = TypeRegistry::default();
+        registry.register::<Transform>();
+        let path = |field: &str| {
+            let path = format!(
+                "a@bevy_transform::components::transform::Transform.{}",
+                field
+            );
+            PropertyPath::parse(&registry, &path).unwrap()
+        };
+        let mut graph = AnimationGraph::new();
+        for weight in [0.4, 0.6] {
+            let mut vec3 = || Vec3::new(rng.gen(), rng.gen(), rng.gen());
+            let translation: Vec<Vec3> = (0..5).map(|_| vec3()).collect();
+            let scale: Vec<Vec3> = (0..5).map(|_| vec3()).collect();
+            let rotation: Vec<Quat> = (0..5)
+                .map(|_| Quat::from_euler(EulerRot::XYZ, rng.gen(), rng.gen(), rng.gen()))
+                .collect();
+            let clip = AnimationClip::builder()
+                .add_curve(
+                    path("translation"),
+                    CurveFixed::from_keyframes(2.0, translation),
+                )
+                .add_curve(path("rotation"), CurveFixed::from_keyframes(2.0, rotation))
+                .add_curve(path("scale"), CurveFixed::from_keyframes(2.0, scale))
+                .build();
+            let node = graph.add_clip(&clip).unwrap();
+            graph
+                .add_input(NodeId::ROOT, node)
+                .ok()
+                .unwrap()
+                .set_weight(weight);
+        }
+
+        let times: Vec<f32> = (0..100).map(|_| rng.gen_range(-0.5..2.5)).collect();
+        let sample = |graph: &AnimationGraph, field: &str| {
+            let path = path(field);
+            graph.find_bone(path.entity()).unwrap().tracks[path.access()].clone()
+        };
+        let expected: Vec<Transform> = times
+            .iter()
+            .map(|time| {
+                graph.sample_at(*time);
+                let track = |field| sample(&graph, field);
+                let vec3 = |track: Arc<dyn Track>| {
+                    track
+                        .as_any()
+                        .downcast_ref::<CurveTrack<Vec3>>()
+                        .unwrap()
+                        .sample_and_blend(&graph.state)
+                };
+                Transform {
+                    translation: vec3(track("translation")),
+                    rotation: track("rotation")
+                        .as_any()
+                        .downcast_ref::<CurveTrack<Quat>>()
+                        .unwrap()
+                        .sample_and_blend(&graph.state),
+                    scale: vec3(track("scale")),
+                }
+            })
+            .collect();
+
+        graph.optimize();
+        let bone = graph.find_bone(path("scale").entity()).unwrap();
+        assert_eq!(bone.tracks.len(), 1);
+        let track = bone.tracks.values().next().unwrap().clone();
+        let track = track
+            .as_any()
+            .downcast_ref::<CurveTrack<Transform>>()
+            .unwrap();
+        for (time, expected) in times.iter().zip(expected) {
+            graph.sample_at(*time);
+            let actual = track.sample_and_blend(&graph.state);
+            assert!(actual.translation.abs_diff_eq(expected.translation, 1e-5));
+            assert!(actual.rotation.abs_diff_eq(expected.rotation, 1e-5));
+            assert!(actual.scale.abs_diff_eq(expected.scale, 1e-5));
+        }
+    }
+
+    #[test]
+    pub fn test_eased_crossfade_weights() {
+        let path = test_path();
+        let clip = AnimationClip::builder()
+            .add_curve(path, CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]))
+            .build();
+        let mut graph = AnimationGraph::new();
+        let from = graph.add_clip(&clip).unwrap();
+        let to = graph.add_clip(&clip).unwrap();
+        for node in [from, to] {
+            graph
+                .add_input(NodeId::ROOT, node)
+                .unwrap()
+                .set_easing(Easing::CubicInOut);
+        }
+
+        for (t, expected) in [(0.25, 0.0625), (0.5, 0.5), (0.75, 0.9375)] {
+            let root = graph.nodes.get_mut(NodeId::ROOT).unwrap();
+            root.get_input_mut(from).unwrap().set_weight(1.0 - t);
+            root.get_input_mut(to).unwrap().set_weight(t);
+            graph.evaluate();
+            let weight = |node| graph.state.clips[graph.clip_id(node).unwrap().0 as usize].weight;
+            assert!((weight(to) - expected).abs() < 1e-6);
+            assert!((weight(from) - (1.0 - expected)).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    pub fn test_weight_bindings_cross_over() {
+        let path = test_path();
+        let clip = AnimationClip::builder()
+            .add_curve(path, CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]))
+            .build();
+        let mut graph = AnimationGraph::new();
+        let walk = graph.add_clip(&clip).unwrap();
+        let run = graph.add_clip(&clip).unwrap();
+        for (node, out_range) in [(walk, (1.0, 0.0)), (run, (0.0, 1.0))] {
+            graph
+                .add_input(NodeId::ROOT, node)
+                .unwrap()
+                .set_weight_binding(Some(WeightBinding {
+                    param: "speed".into(),
+                    in_range: (0.0, 6.0),
+                    out_range,
+                    clamp: true,
+                }));
+        }
+
+        // Unset parameters use the missing value.
+        graph.set_missing_param_value(6.0);
+        graph.evaluate();
+        let weight = |graph: &AnimationGraph, node| {
+            graph.state.clips[graph.clip_id(node).unwrap().0 as usize].weight
+        };
+        assert_eq!(weight(&graph, walk), 0.0);
+        assert_eq!(weight(&graph, run), 1.0);
+
+        let mut last_run = -1.0;
+        for step in 0..=8 {
+            let speed = step as f32;
+            graph.set_param("speed", speed);
+            assert_eq!(graph.get_param("speed"), Some(speed));
+            graph.evaluate();
+            let (walk, run) = (weight(&graph, walk), weight(&graph, run));
+            let expected = (speed / 6.0).min(1.0);
+            assert!((run - expected).abs() < 1e-6);
+            assert!((walk + run - 1.0).abs() < 1e-6);
+            assert!(run >= last_run);
+            last_run = run;
+        }
+        assert_eq!(graph.get_param("missing"), None);
+    }
+
+    #[test]
+    pub fn test_weight_overrides_are_per_instance() {
+        let path = test_path();
+        let walk_clip = AnimationClip::builder()
+            .add_curve(path.clone(), CurveFixed::from_constant(0.0f32))
+            .build();
+        let run_clip = AnimationClip::builder()
+            .add_curve(path.clone(), CurveFixed::from_constant(1.0f32))
+            .build();
+        let mut template = AnimationGraph::new();
+        let walk = template.add_clip(&walk_clip).unwrap();
+        let run = template.add_clip(&run_clip).unwrap();
+        for node in [walk, run] {
+            template
+                .add_input(NodeId::ROOT, node)
+                .unwrap()
+                .set_weight(0.5);
+        }
+
+        let mut a = template.clone();
+        let mut b = template.clone();
+        a.override_input_weight(NodeId::ROOT, walk, 0.75).unwrap();
+        a.override_input_weight(NodeId::ROOT, run, 0.25).unwrap();
+        b.override_input_weight(NodeId::ROOT, walk, 0.0).unwrap();
+        b.override_input_weight(NodeId::ROOT, run, 1.0).unwrap();
+        for graph in [&mut template, &mut a, &mut b] {
+            graph.evaluate();
+        }
+        assert_eq!(sample_f32(&template, &path), 0.5);
+        assert_eq!(sample_f32(&a, &path), 0.25);
+        assert_eq!(sample_f32(&b, &path), 1.0);
+
+        // The inputs themselves are left untouched.
+        for graph in [&template, &a, &b] {
+            let root = graph.nodes.get(NodeId::ROOT).unwrap();
+            assert!(root.inputs().all(|input| input.weight() == 0.5));
+        }
+        assert_eq!(template.input_weight_override(NodeId::ROOT, run), None);
+        assert_eq!(a.input_weight_override(NodeId::ROOT, run), Some(0.25));
+
+        assert_eq!(a.clear_override(NodeId::ROOT, walk), Some(0.75));
+        assert_eq!(a.clear_override(NodeId::ROOT, walk), None);
+        a.evaluate();
+        // Walk is back to its own weight, and run is still overridden.
+        let weight = |node| a.state.clips[a.clip_id(node).unwrap().0 as usize].weight;
+        assert_eq!(weight(walk), 0.5);
+        assert_eq!(weight(run), 0.25);
+
+        assert!(matches!(
+            a.override_input_weight(NodeId::ROOT, NodeId::ROOT, 1.0),
+            Err(AnimationGraphError::InputNotFound(NodeId::ROOT))
+        ));
+        assert!(matches!(
+            a.override_input_weight(walk, run, 1.0),
+            Err(AnimationGraphError::NotBlendNode(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_cloned_graphs_are_new_graphs() {
+        let clip = AnimationClip::builder()
+            .add_curve(
+                test_path(),
+                CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]),
+            )
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+        graph.clips.set_dirty(false);
+        graph.advance_time(0.5);
+        graph.evaluate();
+
+        let mut cloned = graph.clone();
+        assert_ne!(cloned.nonce, graph.nonce);
+        assert!(cloned.clips.is_dirty());
+        assert_eq!(cloned.clip_time(node).unwrap(), 0.5);
+        cloned.evaluate();
+        assert_eq!(cloned.state.clips[0].weight, graph.state.clips[0].weight);
+    }
+
+    #[test]
+    pub fn test_param_curves_drive_weight_bindings() {
+        let path = test_path();
+        let clip = AnimationClip::builder()
+            .add_curve(path, CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]))
+            .build();
+        let cutscene = AnimationClip::builder()
+            .add_param_curve(
+                "worried",
+                CurveVariableLinear::from_line(0.0, 2.0, 0.0, 1.0),
+            )
+            .build();
+        assert_eq!(cutscene.duration(), 2.0);
+        let mut graph = AnimationGraph::new();
+        let neutral = graph.add_clip(&clip).unwrap();
+        let worried = graph.add_clip(&clip).unwrap();
+        for (node, out_range) in [(neutral, (1.0, 0.0)), (worried, (0.0, 1.0))] {
+            graph
+                .add_input(NodeId::ROOT, node)
+                .unwrap()
+                .set_weight_binding(Some(WeightBinding {
+                    param: "worried".into(),
+                    in_range: (0.0, 1.0),
+                    out_range,
+                    clamp: true,
+                }));
+        }
+        // The clip drives the parameter without being blended.
+        graph.add_clip(&cutscene).unwrap();
+
+        let weight = |graph: &AnimationGraph, node| {
+            graph.state.clips[graph.clip_id(node).unwrap().0 as usize].weight
+        };
+        for step in 0..=10 {
+            graph.evaluate();
+            let time = (step as f32 * 0.25).min(2.0);
+            assert!((graph.get_param("worried").unwrap() - time / 2.0).abs() < 1e-6);
+            assert!((weight(&graph, worried) - time / 2.0).abs() < 1e-6);
+            assert!((weight(&graph, neutral) + weight(&graph, worried) - 1.0).abs() < 1e-6);
+            graph.advance_time(0.25);
+        }
+    }
+
+    #[test]
+    pub fn test_whole_component_and_field_tracks_are_separate() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Transform>();
+        let path = |path: &str| PropertyPath::parse(&registry, path).unwrap();
+        let whole = path("a@bevy_transform::components::transform::Transform");
+        let translation = path("a@bevy_transform::components::transform::Transform.translation");
+        let mut graph = AnimationGraph::new();
+        graph
+            .add_clip(
+                &AnimationClip::builder()
+                    .add_curve(
+                        whole.clone(),
+                        CurveFixed::from_keyframes(1.0, vec![Transform::identity(); 2]),
+                    )
+                    .build(),
+            )
+            .unwrap();
+        graph
+            .add_clip(
+                &AnimationClip::builder()
+                    .add_curve(
+                        translation.clone(),
+                        CurveFixed::from_keyframes(1.0, vec![Vec3::X; 2]),
+                    )
+                    .build(),
+            )
+            .unwrap();
+        let bone = graph.find_bone(whole.entity()).unwrap();
+        let properties: Vec<_> = bone.properties().collect();
+        assert_eq!(properties, vec![whole.access(), translation.access()]);
+
+        // A whole component curve must still match the type of the component.
+        let result = graph.add_clip(
+            &AnimationClip::builder()
+                .add_curve(whole, CurveFixed::from_keyframes(1.0, vec![Vec3::X; 2]))
+                .build(),
+        );
+        assert!(matches!(
+            result,
+            Err(AnimationGraphError::Track(
+                TrackError::ConflictingType { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    pub fn test_total_influence_only_counts_animating_clips() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        let path = |path: &str| PropertyPath::parse(&registry, path).unwrap();
+        let (a, b) = (
+            path("a@bevy_prototype_animation::graph::test::Test.a"),
+            path("b@bevy_prototype_animation::graph::test::Test.a"),
+        );
+        let curve = CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]);
+        let both = AnimationClip::builder()
+            .add_curve(a.clone(), curve.clone())
+            .add_curve(b.clone(), curve.clone())
+            .build();
+        let only_a = AnimationClip::builder().add_curve(a.clone(), curve).build();
+        let mut graph = AnimationGraph::new();
+        for (clip, weight) in [(&both, 0.25), (&only_a, 0.75)] {
+            let node = graph.add_clip(clip).unwrap();
+            graph
+                .add_input(NodeId::ROOT, node)
+                .unwrap()
+                .set_weight(weight);
+        }
+        graph.evaluate();
+
+        assert!((graph.total_influence(a.entity()) - 1.0).abs() < 1e-6);
+        assert!((graph.total_influence(b.entity()) - 0.25).abs() < 1e-6);
+        assert_eq!(graph.total_influence(&"c".parse().unwrap()), 0.0);
+        let entities: Vec<_> = graph.animated_entities().collect();
+        assert_eq!(entities.len(), 2);
+        assert!(entities.contains(&(a.entity(), None, 1)));
+        assert!(entities.contains(&(b.entity(), None, 1)));
+    }
+
+    #[test]
+    pub fn test_time_warps_hold_and_resume() {
+        let (mut graph, path, node) =
+            single_clip_graph(CurveFixed::from_keyframes(1.0, vec![0.0, 1.0, 2.0]));
+        graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+        // Holds the clip at 0.5 seconds for half a second.
+        let warp =
+            CurveVariableLinear::with_keyframes(vec![0.0, 0.5, 1.0, 2.5], vec![0.0, 0.5, 0.5, 2.0])
+                .unwrap();
+        graph.set_time_warp(node, warp).unwrap();
+        assert_eq!(graph.clip_duration(node).unwrap(), 2.5);
+
+        let mut samples = Vec::new();
+        for _ in 0..30 {
+            graph.evaluate();
+            samples.push(sample_f32(&graph, &path));
+            graph.advance_time(0.125);
+        }
+        let expected = |local: f32| {
+            let local = local % 2.5;
+            if local < 0.5 {
+                local
+            } else if local < 1.0 {
+                0.5
+            } else {
+                0.5 + local - 1.0
+            }
+        };
+        for (step, sample) in samples.iter().enumerate() {
+            let local = step as f32 * 0.125;
+            assert!(
+                (sample - expected(local)).abs() < 1e-5,
+                "{} != {} at {}",
+                sample,
+                expected(local),
+                local
+            );
+        }
+        // The held frames are identical, and the clip resumes afterwards.
+        assert!(samples[4..=8].iter().all(|sample| *sample == samples[4]));
+        assert!(samples[9] > samples[8]);
+        // The clip loops in warped time.
+        assert_eq!(samples[20], 0.0);
+
+        graph.clear_time_warp(node).unwrap();
+        assert_eq!(graph.clip_duration(node).unwrap(), 2.0);
+    }
+
+    #[test]
+    pub fn test_full_graph_returns_errors() {
+        let clip = AnimationClip::builder().build();
+        let mut graph = AnimationGraph::with_capacity(16, 16);
+        let mut last = None;
+        let error = loop {
+            match graph.add_clip(&clip) {
+                Ok(node) => last = Some(node),
+                Err(error) => break error,
+            }
+        };
+        assert!(matches!(error, AnimationGraphError::GraphFull));
+        assert_eq!(graph.clip_count(), AnimationGraph::MAX_CLIPS);
+        assert_eq!(graph.node_count(), AnimationGraph::MAX_NODES);
+        assert!(matches!(
+            graph.capture_pose(&World::new()),
+            Err(AnimationGraphError::GraphFull)
+        ));
+        assert_eq!(graph.clip_count(), AnimationGraph::MAX_CLIPS);
+
+        // The graph is still usable once full.
+        assert!(graph.add_input(NodeId::ROOT, last.unwrap()).is_ok());
+        graph.evaluate();
+    }
+
+    #[test]
+    pub fn test_node_limit_returns_errors() {
+        let mut graph = AnimationGraph::new();
+        let error = loop {
+            let result = graph.nodes.add(Node::Blend {
+                inputs: Vec::new(),
+                propogate_time: true,
+            });
+            if let Err(error) = result {
+                break error;
+            }
+        };
+        assert!(matches!(error, AnimationGraphError::GraphFull));
+        assert_eq!(graph.node_count(), AnimationGraph::MAX_NODES);
+        // Clips can't be added without a node to sample them.
+        let clip = AnimationClip::builder().build();
+        assert!(matches!(
+            graph.add_clip(&clip),
+            Err(AnimationGraphError::GraphFull)
+        ));
+        assert_eq!(graph.clip_count(), 0);
+    }
+
+    #[test]
+    pub fn test_phase_driven_clip_tracks_external_phase() {
+        let curve = CurveFixed::from_keyframes(2.0, vec![0.0, 4.0, 1.0, 3.0, 2.0]);
+        let (mut graph, path, clip) = single_clip_graph(curve.clone());
+        assert_eq!(graph.clip_duration(clip).ok(), Some(2.0));
+        graph.set_normalized_time(clip, true).unwrap();
+        assert_eq!(graph.is_normalized_time(clip).ok(), Some(true));
+
+        for phase in [0.0, 0.1, 0.25, 0.33, 0.5, 0.8, 1.0] {
+            graph.set_time(clip, phase).unwrap();
+            graph.evaluate();
+            assert_eq!(graph.clip_time(clip).ok(), Some(phase));
+            assert_eq!(sample_f32(&graph, &path), curve.sample_normalized(phase));
+        }
+
+        // Phases outside of 0..=1 follow the playback mode.
+        graph.set_time(clip, 1.25).unwrap();
+        assert_eq!(graph.clip_time(clip).ok(), Some(1.0));
+        graph.set_playback_mode(clip, PlaybackMode::Loop).unwrap();
+        graph.set_time(clip, 1.25).unwrap();
+        assert_eq!(graph.clip_time(clip).ok(), Some(0.25));
+        graph.set_time(clip, -0.25).unwrap();
+        assert_eq!(graph.clip_time(clip).ok(), Some(0.75));
+
+        // Advancing time still moves through the clip in seconds.
+        graph.advance_time(1.0);
+        assert!((graph.clip_time(clip).unwrap() - 0.25).abs() < 1e-6);
+
+        // Switching back preserves the position in the clip.
+        graph.set_normalized_time(clip, false).unwrap();
+        assert!((graph.clip_time(clip).unwrap() - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    pub fn test_clips_are_found_by_name_and_tag() {
+        let path = test_path();
+        let clip = |name: &'static str, tags: &[&'static str]| {
+            tags.iter()
+                .fold(
+                    AnimationClip::builder().name(name).add_curve(
+                        path.clone(),
+                        CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]),
+                    ),
+                    |builder, tag| builder.tag(*tag),
+                )
+                .build()
+        };
+        let mut graph = AnimationGraph::new();
+        let punch = graph.add_clip(&clip("punch", &["combat"])).unwrap();
+        let kick = graph
+            .add_clips(&[clip("kick", &["combat", "lower_body"])])
+            .unwrap()[0];
+        let wave = graph.add_clip(&clip("wave", &["upper_body"])).unwrap();
+
+        assert_eq!(graph.find_clip_by_name("punch"), Some(punch));
+        assert_eq!(graph.find_clip_by_name("kick"), Some(kick));
+        assert_eq!(graph.find_clip_by_name("run"), None);
+        let combat: Vec<_> = graph.clips_with_tag("combat").collect();
+        assert_eq!(combat, vec![punch, kick]);
+        assert_eq!(graph.clips_with_tag("idle").count(), 0);
+
+        // The last clip added with a name wins.
+        let other_punch = graph.add_clip(&clip("punch", &[])).unwrap();
+        assert_eq!(graph.find_clip_by_name("punch"), Some(other_punch));
 
-        self.state.normalize_weights();
+        // Reloading a clip replaces its name and tags.
+        graph
+            .reload_clip(wave, &clip("greet", &["combat"]))
+            .unwrap();
+        assert_eq!(graph.find_clip_by_name("wave"), None);
+        assert_eq!(graph.find_clip_by_name("greet"), Some(wave));
+        assert_eq!(graph.clips_with_tag("upper_body").count(), 0);
+        assert_eq!(graph.clips_with_tag("combat").count(), 3);
     }
 }
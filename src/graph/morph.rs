@@ -0,0 +1,384 @@
+//! Morph target weights, animated as a single track per list of weights. See
+//! [`AnimationClipBuilder::add_morph_curves`].
+//!
+//! [`AnimationClipBuilder::add_morph_curves`]: crate::clip::AnimationClipBuilder::add_morph_curves
+
+use crate::{
+    clip::ClipCurve,
+    curve::{Curve, CurveFixed},
+    graph::{ClipId, ClipState, GraphState, Track, TrackError},
+    path::AccessPath,
+    Animatable, BlendInput, WorldResources,
+};
+use bevy_log::warn;
+use bevy_reflect::{Reflect, ReflectMut, ReflectRef};
+use bevy_utils::HashMap;
+use smallvec::SmallVec;
+use std::{
+    any::{Any, TypeId},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// The curve of a morph target weight, and the index of the weight.
+type IndexedCurve = (usize, Arc<dyn Curve<f32>>);
+
+/// The curves of one clip for some of the indices of a list of morph target
+/// weights, sorted by index.
+#[derive(Clone)]
+pub(crate) struct MorphCurves(Arc<[IndexedCurve]>);
+
+impl MorphCurves {
+    /// Sorts the curves by index. If an index has multiple curves, the first
+    /// one is kept.
+    pub fn new(mut curves: Vec<IndexedCurve>) -> Self {
+        curves.sort_by_key(|(idx, _)| *idx);
+        curves.dedup_by_key(|(idx, _)| *idx);
+        Self(curves.into())
+    }
+
+    /// The length of the shortest list holding every animated index.
+    fn len(&self) -> usize {
+        self.0.last().map_or(0, |(idx, _)| idx + 1)
+    }
+
+    fn curve(&self, idx: usize) -> Option<&Arc<dyn Curve<f32>>> {
+        self.0
+            .binary_search_by_key(&idx, |(idx, _)| *idx)
+            .ok()
+            .map(|position| &self.0[position].1)
+    }
+
+    /// Samples every curve into a list of weights. Indices without a curve
+    /// are 0.
+    fn sample(&self, time: f32) -> Vec<f32> {
+        let mut weights = vec![0.0; self.len()];
+        for (idx, curve) in self.0.iter() {
+            weights[*idx] = curve.sample(time);
+        }
+        weights
+    }
+}
+
+impl ClipCurve for MorphCurves {
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<Vec<f32>>()
+    }
+    fn value_type_name(&self) -> &'static str {
+        std::any::type_name::<Vec<f32>>()
+    }
+    fn duration(&self) -> f32 {
+        self.0
+            .iter()
+            .map(|(_, curve)| curve.duration())
+            .fold(0.0, f32::max)
+    }
+    fn keyframe_count(&self) -> usize {
+        self.0.iter().map(|(_, curve)| curve.keyframe_count()).sum()
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn into_track(&self, clip_id: ClipId) -> Box<dyn Track> {
+        Box::new(MorphTrack::new(clip_id, self.clone()))
+    }
+    fn curve_ptr(&self) -> *const () {
+        Arc::as_ptr(&self.0) as *const ()
+    }
+    fn sample_reflect(&self, time: f32) -> Box<dyn Reflect> {
+        Box::new(self.sample(time))
+    }
+    fn find_non_finite(&self) -> Option<usize> {
+        self.0.iter().find_map(|(_, curve)| curve.find_non_finite())
+    }
+    fn clone_curve(&self) -> Box<dyn ClipCurve> {
+        Box::new(self.clone())
+    }
+}
+
+/// Blends the weights of a list of morph targets across clips, one index at
+/// a time, and writes every animated index in a single pass over the list.
+///
+/// Blended values are lists long enough to hold every animated index. Indices
+/// the track doesn't animate are 0, and are never written.
+#[derive(Clone)]
+pub(crate) struct MorphTrack {
+    // Sorted by ClipId.
+    curves: SmallVec<[(ClipId, MorphCurves); 1]>,
+    // Every index animated by any of the clips, sorted.
+    indices: Vec<usize>,
+    rest: Option<Vec<f32>>,
+    // Set once indices beyond the end of the animated list were reported.
+    warned: Arc<AtomicBool>,
+}
+
+impl MorphTrack {
+    fn new(clip_id: ClipId, curves: MorphCurves) -> Self {
+        let mut track = Self {
+            curves: SmallVec::new(),
+            indices: Vec::new(),
+            rest: None,
+            warned: Arc::default(),
+        };
+        track.add_curves(clip_id, curves);
+        track
+    }
+
+    fn add_curves(&mut self, clip_id: ClipId, curves: MorphCurves) {
+        match self.curves.binary_search_by_key(&clip_id, |(id, _)| *id) {
+            Ok(idx) => self.curves[idx].1 = curves,
+            Err(idx) => self.curves.insert(idx, (clip_id, curves)),
+        }
+        self.update_indices();
+    }
+
+    fn clip_curves(&self, clip_id: ClipId) -> Option<&MorphCurves> {
+        self.curves
+            .binary_search_by_key(&clip_id, |(id, _)| *id)
+            .ok()
+            .map(|idx| &self.curves[idx].1)
+    }
+
+    fn update_indices(&mut self) {
+        self.indices.clear();
+        for (_, curves) in self.curves.iter() {
+            self.indices.extend(curves.0.iter().map(|(idx, _)| *idx));
+        }
+        self.indices.sort_unstable();
+        self.indices.dedup();
+    }
+
+    fn len(&self) -> usize {
+        self.indices.last().map_or(0, |idx| idx + 1)
+    }
+
+    fn blend(&self, state: &GraphState) -> Vec<f32> {
+        let mut weights = self.blend_clips(&state.clips);
+        if let Some((previous, alpha)) = &state.previous {
+            let previous = self.blend_clips(previous);
+            for &idx in self.indices.iter() {
+                weights[idx] = f32::interpolate(&previous[idx], &weights[idx], *alpha);
+            }
+        }
+        debug_assert!(
+            weights.iter().all(|weight| weight.is_finite()),
+            "Blending a morph target track produced a non-finite weight.",
+        );
+        weights
+    }
+
+    fn blend_clips(&self, clips: &[ClipState]) -> Vec<f32> {
+        let mut weights = vec![0.0; self.len()];
+        for &idx in self.indices.iter() {
+            weights[idx] = f32::blend(self.blend_inputs(clips, idx));
+        }
+        weights
+    }
+
+    /// The weighted samples of each clip animating the weight at `idx`, and
+    /// the rest value receiving the weight they don't cover.
+    fn blend_inputs<'a>(
+        &'a self,
+        clips: &'a [ClipState],
+        idx: usize,
+    ) -> impl Iterator<Item = BlendInput<f32>> + 'a {
+        let animating = move || {
+            self.curves.iter().filter_map(move |(clip_id, curves)| {
+                Some((clips.get(clip_id.0 as usize)?, curves.curve(idx)?))
+            })
+        };
+        let rest = self.rest.as_ref().and_then(|rest| {
+            let weight: f32 = animating()
+                .filter(|(clip, _)| !clip.additive)
+                .map(|(clip, _)| clip.weight)
+                .sum();
+            (weight < 1.0).then(|| BlendInput {
+                weight: 1.0 - weight,
+                value: rest.get(idx).copied().unwrap_or_default(),
+                additive: false,
+            })
+        });
+        animating()
+            .filter(|(clip, _)| clip.weight != 0.0)
+            .map(|(clip, curve)| BlendInput {
+                weight: clip.weight,
+                value: curve.sample(clip.sample_time()),
+                additive: clip.additive,
+            })
+            .chain(rest)
+    }
+
+    /// Writes the animated indices of `weights` to a reflected list of `f32`s.
+    /// Indices beyond the end of the list are skipped.
+    fn write(&self, weights: &[f32], output: &mut dyn Reflect) -> Result<bool, TrackError> {
+        let list = match output.reflect_mut() {
+            ReflectMut::List(list) => list,
+            _ => return Err(TrackError::incorrect_type::<Vec<f32>>(output.type_name())),
+        };
+        let len = list.len();
+        let mut written = false;
+        for &idx in self.indices.iter().take_while(|idx| **idx < len) {
+            let value = list.get_mut(idx).unwrap();
+            let value = match value.downcast_mut::<f32>() {
+                Some(value) => value,
+                None => return Err(TrackError::incorrect_type::<f32>(value.type_name())),
+            };
+            if *value != weights[idx] {
+                *value = weights[idx];
+                written = true;
+            }
+        }
+        if self.len() > len && !self.warned.swap(true, Ordering::Relaxed) {
+            warn!(
+                "Morph target weights up to index {} are animated, but the list only has {} weights. The indices out of range are skipped.",
+                self.len() - 1,
+                len
+            );
+        }
+        Ok(written)
+    }
+}
+
+/// Reads a reflected list of `f32`s, which may be a [`Vec`] or a dynamic
+/// list cloned from one.
+fn read_weights(value: &dyn Reflect) -> Result<Vec<f32>, TrackError> {
+    if let Some(weights) = value.downcast_ref::<Vec<f32>>() {
+        return Ok(weights.clone());
+    }
+    match value.reflect_ref() {
+        ReflectRef::List(list) => list
+            .iter()
+            .map(|value| {
+                value
+                    .downcast_ref::<f32>()
+                    .copied()
+                    .ok_or_else(|| TrackError::incorrect_type::<f32>(value.type_name()))
+            })
+            .collect(),
+        _ => Err(TrackError::incorrect_type::<Vec<f32>>(value.type_name())),
+    }
+}
+
+impl Track for MorphTrack {
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<Vec<f32>>()
+    }
+    fn value_type_name(&self) -> &'static str {
+        std::any::type_name::<Vec<f32>>()
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn clone_track(&self) -> Box<dyn Track> {
+        Box::new(self.clone())
+    }
+    fn sample_clip_boxed(&self, clip_id: ClipId, time: f32) -> Option<Box<dyn Reflect>> {
+        Some(Box::new(self.clip_curves(clip_id)?.sample(time)))
+    }
+    fn blend_boxed(&self, state: &GraphState) -> Box<dyn Reflect> {
+        Box::new(self.blend(state))
+    }
+
+    fn add_generic_curve(
+        &mut self,
+        clip_id: ClipId,
+        curve: &dyn ClipCurve,
+    ) -> Result<(), TrackError> {
+        let curves = curve
+            .as_any()
+            .downcast_ref::<MorphCurves>()
+            .ok_or_else(|| TrackError::incorrect_type::<Vec<f32>>(curve.value_type_name()))?;
+        self.add_curves(clip_id, curves.clone());
+        Ok(())
+    }
+
+    fn animates_clip(&self, clip_id: ClipId) -> bool {
+        self.clip_curves(clip_id).is_some()
+    }
+
+    fn remove_clip_curves(
+        &mut self,
+        access: &AccessPath,
+        clip_id: ClipId,
+        keep: &dyn Fn(&AccessPath) -> bool,
+    ) -> bool {
+        if !keep(access) {
+            self.curves.retain(|(id, _)| *id != clip_id);
+            self.update_indices();
+        }
+        self.curves.is_empty()
+    }
+
+    fn evict_clip_curves(&mut self, clip_id: ClipId) {
+        if let Ok(idx) = self.curves.binary_search_by_key(&clip_id, |(id, _)| *id) {
+            let curves = &mut self.curves[idx].1;
+            let placeholders = curves
+                .0
+                .iter()
+                .map(|(idx, curve)| {
+                    let curve: Arc<dyn Curve<f32>> =
+                        Arc::new(CurveFixed::from_constant(curve.sample(0.0)));
+                    (*idx, curve)
+                })
+                .collect();
+            *curves = MorphCurves::new(placeholders);
+        }
+    }
+
+    fn curve_sizes(&self, sizes: &mut HashMap<*const (), usize>) {
+        let keyframe_size = 2 * std::mem::size_of::<f32>();
+        for (_, curves) in self.curves.iter() {
+            for (_, curve) in curves.0.iter() {
+                let size = curve.keyframe_count() * keyframe_size;
+                sizes.insert(Arc::as_ptr(curve) as *const (), size);
+            }
+        }
+    }
+
+    fn add_snapshot(&mut self, pose_id: ClipId, value: &dyn Reflect) -> Result<(), TrackError> {
+        let weights = read_weights(value)?;
+        let curves = self
+            .indices
+            .iter()
+            .filter_map(|idx| {
+                let curve: Arc<dyn Curve<f32>> =
+                    Arc::new(CurveFixed::from_constant(*weights.get(*idx)?));
+                Some((*idx, curve))
+            })
+            .collect();
+        self.add_curves(pose_id, MorphCurves::new(curves));
+        Ok(())
+    }
+
+    fn set_rest_value(&mut self, value: &dyn Reflect) -> Result<(), TrackError> {
+        self.rest = Some(read_weights(value)?);
+        Ok(())
+    }
+
+    fn clear_rest_value(&mut self) {
+        self.rest = None;
+    }
+
+    fn blend_via_reflect(
+        &self,
+        state: &GraphState,
+        output: &mut dyn Reflect,
+        _resources: WorldResources<'_>,
+    ) -> Result<bool, TrackError> {
+        self.write(&self.blend(state), output)
+    }
+
+    fn write_blended(
+        &self,
+        value: &dyn Reflect,
+        output: &mut dyn Reflect,
+        _resources: WorldResources<'_>,
+    ) -> Result<bool, TrackError> {
+        self.write(&read_weights(value)?, output)
+    }
+}
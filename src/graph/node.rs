@@ -1,27 +1,66 @@
-use crate::graph::ClipId;
-
-// An opaque ID of a node within the graph.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+use crate::graph::{AnimationGraph, AnimationGraphError, ClipId, Easing, WeightBinding};
+use bevy_reflect::{impl_reflect_value, ReflectDeserialize};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// An opaque ID of a node within the graph.
+///
+/// IDs are assigned in the order nodes are added, so the same ID refers to
+/// the same node in every identically built graph, and IDs can be saved
+/// alongside a [`GraphRuntimeState`](crate::graph::GraphRuntimeState). An ID
+/// loaded from elsewhere can be checked with
+/// [`AnimationGraph::contains_node`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct NodeId(u16);
 
+impl_reflect_value!(NodeId(Hash, PartialEq, Serialize, Deserialize));
+
 impl NodeId {
     pub const ROOT: NodeId = NodeId(0);
 }
 
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if *self == Self::ROOT {
+            f.write_str("ROOT")
+        } else {
+            self.0.fmt(f)
+        }
+    }
+}
+
+#[derive(Default, Clone)]
 pub(super) struct GraphNodes {
     nodes: Vec<Node>,
 }
 
 impl GraphNodes {
-    pub fn add(&mut self, node: Node) -> NodeId {
-        let id = NodeId(
-            self.nodes
-                .len()
-                .try_into()
-                .expect("AnimationGraph has more than u16::MAX nodes."),
-        );
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn add(&mut self, node: Node) -> Result<NodeId, AnimationGraphError> {
+        if self.is_full() {
+            return Err(AnimationGraphError::GraphFull);
+        }
+        let id = NodeId(self.nodes.len() as u16);
         self.nodes.push(node);
-        id
+        Ok(id)
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+    }
+
+    pub fn count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.nodes.len() >= AnimationGraph::MAX_NODES
     }
 
     pub fn get(&self, node: NodeId) -> Option<&Node> {
@@ -31,8 +70,23 @@ impl GraphNodes {
     pub fn get_mut(&mut self, node: NodeId) -> Option<&mut Node> {
         self.nodes.get_mut(node.0 as usize)
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &Node)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (NodeId(idx as u16), node))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (NodeId, &mut Node)> {
+        self.nodes
+            .iter_mut()
+            .enumerate()
+            .map(|(idx, node)| (NodeId(idx as u16), node))
+    }
 }
 
+#[derive(Clone)]
 pub enum Node {
     Blend {
         inputs: Vec<NodeInput>,
@@ -42,22 +96,62 @@ pub enum Node {
     Clip {
         clip: ClipId,
     },
+    /// A captured pose. Samples to the same values regardless of time.
+    ///
+    /// Snapshots share the ID space of clips, as they participate in blending
+    /// like a clip whose curves are constant.
+    Snapshot {
+        pose_id: ClipId,
+    },
+    /// Plays one of its inputs at a time, picked at random with a probability
+    /// proportional to its probability weight whenever the node is
+    /// [retriggered](AnimationGraph::retrigger). The active input is blended
+    /// with a weight of 1, and the others aren't blended at all.
+    Random {
+        inputs: Vec<(f32, NodeInput)>,
+        /// The index of the active input.
+        active: Option<usize>,
+        /// Whether a new input is picked whenever the clip of the active
+        /// input loops or finishes.
+        reselect_on_loop: bool,
+    },
 }
 
 impl Node {
     pub fn get_input_mut(&mut self, input_id: NodeId) -> Option<&mut NodeInput> {
-        if let Self::Blend { inputs, .. } = self {
-            inputs.iter_mut().find(|input| input.node_id == input_id)
-        } else {
-            None
-        }
+        self.inputs_mut().find(|input| input.node_id == input_id)
+    }
+
+    /// Iterates over the inputs of a blend or random node. Other nodes have
+    /// no inputs.
+    pub fn inputs(&self) -> impl Iterator<Item = &NodeInput> {
+        let (blend, random): (&[NodeInput], &[(f32, NodeInput)]) = match self {
+            Self::Blend { inputs, .. } => (inputs, &[]),
+            Self::Random { inputs, .. } => (&[], inputs),
+            _ => (&[], &[]),
+        };
+        blend.iter().chain(random.iter().map(|(_, input)| input))
+    }
+
+    pub fn inputs_mut(&mut self) -> impl Iterator<Item = &mut NodeInput> {
+        let (blend, random): (&mut [NodeInput], &mut [(f32, NodeInput)]) = match self {
+            Self::Blend { inputs, .. } => (inputs, &mut []),
+            Self::Random { inputs, .. } => (&mut [], inputs),
+            _ => (&mut [], &mut []),
+        };
+        blend
+            .iter_mut()
+            .chain(random.iter_mut().map(|(_, input)| input))
     }
 }
 
+#[derive(Clone)]
 pub struct NodeInput {
     node_id: NodeId,
     connected: bool,
     weight: f32,
+    easing: Easing,
+    weight_binding: Option<WeightBinding>,
 }
 
 impl NodeInput {
@@ -66,6 +160,8 @@ impl NodeInput {
             node_id,
             connected: true,
             weight: 1.0,
+            easing: Easing::Linear,
+            weight_binding: None,
         }
     }
 
@@ -85,6 +181,10 @@ impl NodeInput {
         self.connected = false;
     }
 
+    pub(super) fn set_connected(&mut self, connected: bool) {
+        self.connected = connected;
+    }
+
     pub fn weight(&self) -> f32 {
         self.weight
     }
@@ -92,6 +192,32 @@ impl NodeInput {
     pub fn set_weight(&mut self, weight: f32) {
         self.weight = weight
     }
+
+    /// The weight of the input after easing, as used when evaluating the graph.
+    pub fn eased_weight(&self) -> f32 {
+        self.easing.ease(self.weight)
+    }
+
+    pub fn easing(&self) -> &Easing {
+        &self.easing
+    }
+
+    /// Sets the easing function applied to the input's weight whenever the
+    /// graph is evaluated. Defaults to [`Easing::Linear`].
+    pub fn set_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
+    pub fn weight_binding(&self) -> Option<&WeightBinding> {
+        self.weight_binding.as_ref()
+    }
+
+    /// Binds the input's weight to a parameter of the graph. While bound, the
+    /// weight is overwritten every time the graph is evaluated, before easing
+    /// is applied. Pass `None` to unbind it, leaving the last evaluated weight.
+    pub fn set_weight_binding(&mut self, binding: Option<WeightBinding>) {
+        self.weight_binding = binding;
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,155 @@
+use crate::{curve::Curve, graph::ClipId};
+use bevy_log::warn;
+use bevy_utils::{HashMap, HashSet};
+use std::{borrow::Cow, sync::Arc};
+
+/// Drives the weight of a [`NodeInput`] from a parameter of its graph, set
+/// with [`AnimationGraph::set_param`]. The parameter is linearly remapped from
+/// `in_range` onto `out_range` every time the graph is evaluated.
+///
+/// ```rust,ignore
+/// graph.add_input(locomotion, run)?.set_weight_binding(Some(WeightBinding {
+///     param: "speed".into(),
+///     in_range: (0.0, 6.0),
+///     out_range: (0.0, 1.0),
+///     clamp: true,
+/// }));
+/// graph.set_param("speed", 4.5);
+/// ```
+///
+/// [`NodeInput`]: crate::graph::NodeInput
+/// [`AnimationGraph::set_param`]: crate::graph::AnimationGraph::set_param
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightBinding {
+    pub param: Cow<'static, str>,
+    pub in_range: (f32, f32),
+    pub out_range: (f32, f32),
+    /// Whether the parameter is clamped to `in_range` before being remapped.
+    pub clamp: bool,
+}
+
+impl WeightBinding {
+    /// Remaps a parameter value into a weight.
+    pub fn remap(&self, value: f32) -> f32 {
+        let (in_start, in_end) = self.in_range;
+        let (out_start, out_end) = self.out_range;
+        let mut t = if in_start == in_end {
+            // Treat an empty range as a step at its start.
+            if value < in_start {
+                0.0
+            } else {
+                1.0
+            }
+        } else {
+            (value - in_start) / (in_end - in_start)
+        };
+        if self.clamp {
+            t = t.clamp(0.0, 1.0);
+        }
+        out_start + (out_end - out_start) * t
+    }
+}
+
+/// A curve from a clip driving a parameter of the graph. See
+/// [`AnimationClipBuilder::add_param_curve`].
+///
+/// [`AnimationClipBuilder::add_param_curve`]: crate::clip::AnimationClipBuilder::add_param_curve
+#[derive(Clone)]
+pub(super) struct ParamCurve {
+    pub clip_id: ClipId,
+    pub param: Cow<'static, str>,
+    pub curve: Arc<dyn Curve<f32>>,
+}
+
+#[derive(Clone)]
+pub(super) struct GraphParams {
+    values: HashMap<Cow<'static, str>, f32>,
+    pub(super) missing_value: f32,
+    // Missing parameters that have already been warned about.
+    warned: HashSet<Cow<'static, str>>,
+}
+
+impl Default for GraphParams {
+    fn default() -> Self {
+        Self {
+            values: HashMap::default(),
+            missing_value: 0.0,
+            warned: HashSet::default(),
+        }
+    }
+}
+
+impl GraphParams {
+    pub fn get(&self, name: &str) -> Option<f32> {
+        self.values.get(name).copied()
+    }
+
+    pub fn set(&mut self, name: Cow<'static, str>, value: f32) {
+        self.warned.remove(&name);
+        self.values.insert(name, value);
+    }
+
+    /// Updates a parameter only if it's already set, without allocating.
+    /// Returns whether it was set.
+    pub fn set_existing(&mut self, name: &str, value: f32) -> bool {
+        match self.values.get_mut(name) {
+            Some(current) => {
+                *current = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Cow<'static, str>, f32)> {
+        self.values.iter().map(|(name, value)| (name, *value))
+    }
+
+    /// Replaces all of the parameters.
+    pub fn replace(&mut self, values: impl Iterator<Item = (Cow<'static, str>, f32)>) {
+        self.values.clear();
+        for (name, value) in values {
+            self.set(name, value);
+        }
+    }
+
+    /// Evaluates a binding, warning the first time its parameter is missing.
+    pub fn evaluate(&mut self, binding: &WeightBinding) -> f32 {
+        let value = match self.values.get(&binding.param) {
+            Some(value) => *value,
+            None => {
+                if self.warned.insert(binding.param.clone()) {
+                    warn!(
+                        "AnimationGraph parameter '{}' is not set. Using {} instead.",
+                        binding.param, self.missing_value
+                    );
+                }
+                self.missing_value
+            }
+        };
+        binding.remap(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_remap() {
+        let mut binding = WeightBinding {
+            param: "speed".into(),
+            in_range: (2.0, 6.0),
+            out_range: (1.0, 0.0),
+            clamp: true,
+        };
+        assert_eq!(binding.remap(2.0), 1.0);
+        assert_eq!(binding.remap(3.0), 0.75);
+        assert_eq!(binding.remap(6.0), 0.0);
+        assert_eq!(binding.remap(10.0), 0.0);
+        assert_eq!(binding.remap(0.0), 1.0);
+        binding.clamp = false;
+        assert_eq!(binding.remap(10.0), -1.0);
+        assert_eq!(binding.remap(0.0), 1.5);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::{
+    graph::{AnimationGraph, OutputMode},
+    path::{AccessPath, EntityPath, PropertyPath},
+};
+use bevy_ecs::prelude::*;
+use bevy_math::{Quat, Vec2, Vec3, Vec4};
+use bevy_reflect::Reflect;
+use bevy_transform::prelude::Transform;
+use bevy_utils::HashMap;
+use std::any::TypeId;
+
+/// The blended values of an [`AnimationGraph`] in [`OutputMode::Buffer`].
+///
+/// This is added to and kept up to date on the graph's entity by
+/// [`write_animated_poses_system`]. Values are keyed by the path of the bone
+/// and the property they animate, and are only written for bones and
+/// properties the graph animates. Bones do not need to be bound to an entity.
+///
+/// Unlike values applied to entities, sampled [`Handle`]s are weak.
+///
+/// [`Handle`]: bevy_asset::Handle
+#[derive(Component, Default)]
+pub struct AnimatedPose {
+    bones: HashMap<EntityPath, HashMap<AccessPath, Box<dyn Reflect>>>,
+}
+
+impl AnimatedPose {
+    /// Gets the value of a property of a bone.
+    pub fn get(&self, entity: &EntityPath, access: &AccessPath) -> Option<&dyn Reflect> {
+        self.bones
+            .get(entity)
+            .and_then(|bone| bone.get(access))
+            .map(AsRef::as_ref)
+    }
+
+    /// Gets the value of a property, if it's of type `T`.
+    pub fn value<T: Reflect>(&self, path: &PropertyPath) -> Option<&T> {
+        self.get(path.entity(), path.access())
+            .and_then(|value| value.downcast_ref::<T>())
+    }
+
+    /// Gets the animated [`Transform`] of a bone.
+    ///
+    /// If the bone's [`Transform`] is animated by its parts, the parts that
+    /// aren't animated are taken from [`Transform::identity`]. Returns `None`
+    /// if no part of the bone's [`Transform`] is animated.
+    pub fn transform(&self, entity: &EntityPath) -> Option<Transform> {
+        let bone = self.bones.get(entity)?;
+        let mut transform = None;
+        for (access, value) in bone.iter() {
+            if access.component_type_id() != TypeId::of::<Transform>() {
+                continue;
+            }
+            let output = transform.get_or_insert_with(Transform::identity);
+            if access.field_path().is_root() {
+                if let Some(value) = value.downcast_ref::<Transform>() {
+                    *output = *value;
+                }
+            } else if let Ok(field) = access.field_path().field_mut(output) {
+                field.apply(value.as_ref());
+            }
+        }
+        transform
+    }
+
+    /// Iterates over the bones and properties in the pose, and their values.
+    pub fn iter(&self) -> impl Iterator<Item = (&EntityPath, &AccessPath, &dyn Reflect)> {
+        self.bones.iter().flat_map(|(entity, bone)| {
+            bone.iter()
+                .map(move |(access, value)| (entity, access, value.as_ref()))
+        })
+    }
+
+    pub(super) fn write(&mut self, graph: &AnimationGraph) {
+        for bone in graph.bones() {
+            let values = self.bones.entry(bone.path.clone()).or_default();
+            for track in bone.tracks() {
+                if let Some(value) = values.get_mut(track.property) {
+                    track.track.blend_into_boxed(&graph.state, value);
+                } else {
+                    let value = track.track.blend_boxed(&graph.state);
+                    values.insert(track.property.clone(), value);
+                }
+            }
+        }
+    }
+}
+
+/// A blended value sampled by [`AnimationGraph::sample_pose`].
+///
+/// Common value types are stored unboxed. Values of any other type, including
+/// whole components other than [`Transform`], fall back to [`PoseValue::Reflect`].
+pub enum PoseValue {
+    F32(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Vec4(Vec4),
+    Quat(Quat),
+    Transform(Transform),
+    Reflect(Box<dyn Reflect>),
+}
+
+impl PoseValue {
+    /// Stores a boxed value, unboxing it if it's of a common type.
+    pub fn from_boxed(value: Box<dyn Reflect>) -> Self {
+        let any = value.any();
+        if let Some(value) = any.downcast_ref::<f32>() {
+            Self::F32(*value)
+        } else if let Some(value) = any.downcast_ref::<Vec2>() {
+            Self::Vec2(*value)
+        } else if let Some(value) = any.downcast_ref::<Vec3>() {
+            Self::Vec3(*value)
+        } else if let Some(value) = any.downcast_ref::<Vec4>() {
+            Self::Vec4(*value)
+        } else if let Some(value) = any.downcast_ref::<Quat>() {
+            Self::Quat(*value)
+        } else if let Some(value) = any.downcast_ref::<Transform>() {
+            Self::Transform(*value)
+        } else {
+            Self::Reflect(value)
+        }
+    }
+
+    /// Gets the value as a [`Reflect`], regardless of how it's stored.
+    pub fn as_reflect(&self) -> &dyn Reflect {
+        match self {
+            Self::F32(value) => value,
+            Self::Vec2(value) => value,
+            Self::Vec3(value) => value,
+            Self::Vec4(value) => value,
+            Self::Quat(value) => value,
+            Self::Transform(value) => value,
+            Self::Reflect(value) => value.as_ref(),
+        }
+    }
+
+    /// Gets the value, if it's of type `T`.
+    pub fn downcast_ref<T: Reflect>(&self) -> Option<&T> {
+        self.as_reflect().downcast_ref::<T>()
+    }
+}
+
+/// A user provided buffer for the blended values of an [`AnimationGraph`],
+/// filled by [`AnimationGraph::sample_pose`].
+///
+/// Values are keyed by the path of the bone and the property they animate.
+/// Keys are cloned from the graph's interned paths, so they share its storage.
+/// Sampling only overwrites the values of the properties the graph animates, so
+/// a buffer reused between graphs should be [`clear`]ed first.
+///
+/// [`clear`]: PoseBuffer::clear
+#[derive(Default)]
+pub struct PoseBuffer {
+    values: HashMap<(EntityPath, AccessPath), PoseValue>,
+}
+
+impl PoseBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the value of a property of a bone.
+    pub fn get(&self, entity: &EntityPath, access: &AccessPath) -> Option<&PoseValue> {
+        self.values.get(&(entity.clone(), access.clone()))
+    }
+
+    /// Gets the value of a property, if it's of type `T`.
+    pub fn value<T: Reflect>(&self, path: &PropertyPath) -> Option<&T> {
+        self.get(path.entity(), path.access())
+            .and_then(PoseValue::downcast_ref)
+    }
+
+    /// Iterates over the bones and properties in the buffer, and their values.
+    pub fn iter(&self) -> impl Iterator<Item = (&EntityPath, &AccessPath, &PoseValue)> {
+        self.values
+            .iter()
+            .map(|((entity, access), value)| (entity, access, value))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Removes all values, keeping the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    pub(super) fn write(&mut self, graph: &AnimationGraph) {
+        for bone in graph.bones() {
+            for track in bone.tracks() {
+                let key = (bone.path.clone(), track.property.clone());
+                match self.values.get_mut(&key) {
+                    Some(PoseValue::Reflect(value)) => {
+                        track.track.blend_into_boxed(&graph.state, value);
+                    }
+                    Some(value) => {
+                        *value = PoseValue::from_boxed(track.track.blend_boxed(&graph.state));
+                    }
+                    None => {
+                        let value = PoseValue::from_boxed(track.track.blend_boxed(&graph.state));
+                        self.values.insert(key, value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Writes the blended values of all changed [`AnimationGraph`]s in
+/// [`OutputMode::Buffer`] into their [`AnimatedPose`]s, adding one if the
+/// graph's entity doesn't have one. Graphs that skipped their update or have a
+/// total weight of 0 are left as is.
+pub fn write_animated_poses_system(
+    mut graphs: Query<
+        (Entity, &AnimationGraph, Option<&mut AnimatedPose>),
+        Changed<AnimationGraph>,
+    >,
+    mut commands: Commands,
+) {
+    for (entity, graph, pose) in graphs.iter_mut() {
+        if graph.output_mode() != OutputMode::Buffer
+            || graph.is_update_skipped()
+            || graph.total_weight() == 0.0
+        {
+            continue;
+        }
+        if let Some(mut pose) = pose {
+            pose.write(graph);
+        } else {
+            let mut pose = AnimatedPose::default();
+            pose.write(graph);
+            commands.entity(entity).insert(pose);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        clip::AnimationClip,
+        curve::{Curve, CurveFixed},
+        graph::NodeId,
+        AnimationPlugin,
+    };
+    use bevy_app::App;
+    use bevy_asset::AssetPlugin;
+    use bevy_core::Name;
+    use bevy_math::{Quat, Vec3};
+    use bevy_reflect::TypeRegistryArc;
+    use bevy_tasks::{ComputeTaskPool, IoTaskPool, TaskPool};
+    use bevy_transform::prelude::Children;
+    use std::str::FromStr;
+
+    #[test]
+    pub fn test_buffer_mode_leaves_entities_untouched() {
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .insert_resource(ComputeTaskPool(TaskPool::new()))
+            .add_plugin(AssetPlugin)
+            .add_plugin(AnimationPlugin::default())
+            .register_type::<Transform>();
+        let path = |field: &str| {
+            let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+            let path = format!(
+                "a@bevy_transform::components::transform::Transform.{}",
+                field
+            );
+            PropertyPath::parse(&registry.read(), &path).unwrap()
+        };
+        let (translation, rotation) = (path("translation"), path("rotation"));
+
+        let translations = CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::X * 2.0]);
+        let rotations =
+            CurveFixed::from_keyframes(1.0, vec![Quat::IDENTITY, Quat::from_rotation_y(1.0)]);
+        let clip = AnimationClip::builder()
+            .add_curve(translation.clone(), translations.clone())
+            .add_curve(rotation, rotations.clone())
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+        graph.set_output_mode(OutputMode::Buffer);
+
+        let bone = app
+            .world
+            .spawn()
+            .insert(Name::new("a"))
+            .insert(Transform::from_xyz(0.0, 5.0, 0.0))
+            .id();
+        let root = app
+            .world
+            .spawn()
+            .insert(graph)
+            .insert(Children::with(&[bone]))
+            .id();
+
+        for time in [0.25, 0.5, 1.0] {
+            app.world
+                .get_mut::<AnimationGraph>(root)
+                .unwrap()
+                .sample_at(time);
+            app.update();
+            assert_eq!(
+                *app.world.get::<Transform>(bone).unwrap(),
+                Transform::from_xyz(0.0, 5.0, 0.0)
+            );
+            let pose = app.world.get::<AnimatedPose>(root).unwrap();
+            assert_eq!(
+                pose.value::<Vec3>(&translation),
+                Some(&translations.sample(time))
+            );
+            let transform = pose.transform(&EntityPath::from_str("a").unwrap()).unwrap();
+            assert_eq!(transform.translation, translations.sample(time));
+            assert!(
+                transform
+                    .rotation
+                    .normalize()
+                    .dot(rotations.sample(time).normalize())
+                    > 1.0 - 1e-5
+            );
+            assert_eq!(transform.scale, Vec3::ONE);
+        }
+
+        // Switching back applies values to the bound entities again.
+        let mut graph = app.world.get_mut::<AnimationGraph>(root).unwrap();
+        graph.set_output_mode(OutputMode::Entities);
+        graph.sample_at(0.5);
+        app.update();
+        let transform = app.world.get::<Transform>(bone).unwrap();
+        assert_eq!(transform.translation, translations.sample(0.5));
+    }
+}
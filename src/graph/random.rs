@@ -0,0 +1,303 @@
+use crate::graph::{AnimationGraph, AnimationGraphError, Node, NodeId, NodeInput, PlaybackMode};
+use smallvec::SmallVec;
+
+/// A small, seedable random number generator (SplitMix64). Its output only
+/// depends on the seed, so selections are reproducible across platforms and
+/// dependency versions.
+#[derive(Debug, Clone)]
+pub(super) struct GraphRng {
+    state: u64,
+}
+
+impl GraphRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly distributed value in `[0, 1)`.
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Picks the index of one of `inputs`, with a probability proportional to
+    /// its probability weight. Disconnected inputs and inputs without a
+    /// positive weight are never picked.
+    fn select(&mut self, inputs: &[(f32, NodeInput)]) -> Option<usize> {
+        let candidates = || {
+            inputs
+                .iter()
+                .enumerate()
+                .filter(|(_, (weight, input))| *weight > 0.0 && input.is_connected())
+        };
+        let total: f32 = candidates().map(|(_, (weight, _))| weight).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut target = self.next_f32() * total;
+        let mut selected = None;
+        for (idx, (weight, _)) in candidates() {
+            selected = Some(idx);
+            if target < *weight {
+                break;
+            }
+            target -= weight;
+        }
+        selected
+    }
+}
+
+impl AnimationGraph {
+    /// Adds a [`Node::Random`] node to the graph, which plays one of its
+    /// inputs at a time, picked at random. Inputs added with
+    /// [`add_input`](Self::add_input) have a probability weight of 1. See
+    /// [`add_random_input`](Self::add_random_input).
+    ///
+    /// An input is picked the first time the graph is evaluated, and again
+    /// whenever the node is [retriggered](Self::retrigger). If
+    /// `reselect_on_loop` is set, a new input is also picked whenever the
+    /// active input's clip loops or finishes. Only inputs that are clip nodes
+    /// are checked for loops.
+    pub fn add_random(&mut self, reselect_on_loop: bool) -> Result<NodeId, AnimationGraphError> {
+        self.nodes.add(Node::Random {
+            inputs: Vec::new(),
+            active: None,
+            reselect_on_loop,
+        })
+    }
+
+    /// Adds an input to a random node. The chance of picking the input is its
+    /// probability weight divided by the sum of the probability weights of
+    /// the node's connected inputs.
+    pub fn add_random_input(
+        &mut self,
+        target: NodeId,
+        input: NodeId,
+        probability_weight: f32,
+    ) -> Result<&mut NodeInput, AnimationGraphError> {
+        match self.nodes.get(target) {
+            Some(Node::Random { .. }) => {}
+            Some(_) => return Err(AnimationGraphError::NotRandomNode(target)),
+            None => return Err(AnimationGraphError::NodeNotFound(target)),
+        }
+        self.add_input(target, input)?;
+        match self.nodes.get_mut(target) {
+            Some(Node::Random { inputs, .. }) => {
+                let (weight, input) = inputs.last_mut().unwrap();
+                *weight = probability_weight;
+                Ok(input)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Picks a new active input for a random node, and restarts it from the
+    /// beginning. Returns the node ID of the picked input, or `None` if none
+    /// of the node's inputs can be picked.
+    pub fn retrigger(&mut self, node_id: NodeId) -> Result<Option<NodeId>, AnimationGraphError> {
+        let selected = match self.nodes.get_mut(node_id) {
+            Some(Node::Random { inputs, active, .. }) => {
+                *active = self.rng.select(inputs);
+                active.map(|active| inputs[active].1.node_id())
+            }
+            Some(_) => return Err(AnimationGraphError::NotRandomNode(node_id)),
+            None => return Err(AnimationGraphError::NodeNotFound(node_id)),
+        };
+        if let Some(selected) = selected {
+            self.set_time(selected, 0.0)?;
+        }
+        Ok(selected)
+    }
+
+    /// Gets the node ID of the active input of a random node.
+    pub fn active_random_input(
+        &self,
+        node_id: NodeId,
+    ) -> Result<Option<NodeId>, AnimationGraphError> {
+        match self.nodes.get(node_id) {
+            Some(Node::Random { inputs, active, .. }) => Ok(active
+                .and_then(|active| inputs.get(active))
+                .map(|(_, input)| input.node_id())),
+            Some(_) => Err(AnimationGraphError::NotRandomNode(node_id)),
+            None => Err(AnimationGraphError::NodeNotFound(node_id)),
+        }
+    }
+
+    /// Reseeds the random number generator used to pick the inputs of random
+    /// nodes. Graphs with the same seed and structure make the same
+    /// selections. Graphs are seeded differently by default.
+    pub fn set_rng_seed(&mut self, seed: u64) {
+        self.rng = GraphRng::new(seed);
+    }
+
+    /// Gives every looping clip node a random [phase
+    /// offset](Self::set_clip_phase_offset) between 0 and its duration, so
+    /// that crowds sharing the same graph don't move in unison. The offsets
+    /// only depend on `seed` and the graph's structure, and don't affect the
+    /// selections of random nodes.
+    pub fn randomize_phases(&mut self, seed: u64) {
+        let mut rng = GraphRng::new(seed);
+        for clip in self.nodes.iter().filter_map(|(_, node)| match node {
+            Node::Clip { clip } => Some(*clip),
+            _ => None,
+        }) {
+            let state = &self.state.clips[clip.0 as usize];
+            if state.mode == PlaybackMode::Loop {
+                let offset = rng.next_f32() * state.local_duration();
+                self.state.set_phase_offset(clip, offset);
+            }
+        }
+    }
+
+    /// Picks inputs for random nodes that don't have one yet, or whose active
+    /// clip looped or finished, then resets the loop signals of every clip.
+    pub(super) fn update_random_nodes(&mut self) {
+        let mut pending: SmallVec<[NodeId; 4]> = SmallVec::new();
+        for (node_id, node) in self.nodes.iter() {
+            if let Node::Random {
+                inputs,
+                active,
+                reselect_on_loop,
+            } = node
+            {
+                let reselect = match active.and_then(|active| inputs.get(active)) {
+                    Some(_) if !reselect_on_loop => false,
+                    Some((_, input)) => match self.nodes.get(input.node_id()) {
+                        Some(Node::Clip { clip }) => {
+                            let clip = &self.state.clips[clip.0 as usize];
+                            clip.looped || clip.finished || clip.finished_reverse
+                        }
+                        _ => false,
+                    },
+                    None => !inputs.is_empty(),
+                };
+                if reselect {
+                    pending.push(node_id);
+                }
+            }
+        }
+        for node_id in pending {
+            // The node was found above.
+            let _ = self.retrigger(node_id);
+        }
+        for clip in self.state.clips.iter_mut() {
+            clip.looped = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{clip::AnimationClip, curve::CurveFixed, graph::PlaybackMode, path::PropertyPath};
+    use bevy_math::Vec3;
+    use bevy_reflect::TypeRegistry;
+    use bevy_transform::prelude::Transform;
+
+    /// Builds a graph with a random node blending looping one second clips,
+    /// with the given probability weights.
+    fn random_graph(
+        weights: &[f32],
+        reselect_on_loop: bool,
+    ) -> (AnimationGraph, NodeId, Vec<NodeId>) {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Transform>();
+        let path = PropertyPath::parse(
+            &registry,
+            "a@bevy_transform::components::transform::Transform.translation",
+        )
+        .unwrap();
+        let mut graph = AnimationGraph::new();
+        let random = graph.add_random(reselect_on_loop).unwrap();
+        graph.add_input(NodeId::ROOT, random).unwrap();
+        let clips = weights
+            .iter()
+            .enumerate()
+            .map(|(idx, weight)| {
+                let clip = AnimationClip::builder()
+                    .add_curve(
+                        path.clone(),
+                        CurveFixed::from_keyframes(1.0, vec![Vec3::X * idx as f32; 2]),
+                    )
+                    .build();
+                let node = graph.add_clip(&clip).unwrap();
+                graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+                graph.add_random_input(random, node, *weight).unwrap();
+                node
+            })
+            .collect();
+        (graph, random, clips)
+    }
+
+    fn selections(seed: u64) -> Vec<NodeId> {
+        let (mut graph, random, _) = random_graph(&[1.0, 2.0, 3.0], false);
+        graph.set_rng_seed(seed);
+        (0..32)
+            .map(|_| graph.retrigger(random).unwrap().unwrap())
+            .collect()
+    }
+
+    #[test]
+    pub fn test_seeded_selection_is_reproducible() {
+        let sequence = selections(42);
+        assert_eq!(sequence, selections(42));
+        assert_ne!(sequence, selections(43));
+        let (_, _, clips) = random_graph(&[1.0, 2.0, 3.0], false);
+        for clip in clips {
+            assert!(sequence.contains(&clip));
+        }
+    }
+
+    #[test]
+    pub fn test_zero_probability_inputs_are_never_picked() {
+        let (mut graph, random, clips) = random_graph(&[1.0, 0.0], false);
+        for seed in 0..100 {
+            graph.set_rng_seed(seed);
+            assert_eq!(graph.retrigger(random).unwrap(), Some(clips[0]));
+        }
+        assert!(matches!(
+            graph.retrigger(NodeId::ROOT),
+            Err(AnimationGraphError::NotRandomNode(NodeId::ROOT))
+        ));
+    }
+
+    #[test]
+    pub fn test_only_the_active_input_is_blended() {
+        let (mut graph, random, clips) = random_graph(&[1.0, 1.0, 1.0], true);
+        graph.set_rng_seed(7);
+        graph.evaluate();
+        let mut active = graph.active_random_input(random).unwrap().unwrap();
+        let mut picked = vec![active];
+        for _ in 0..40 {
+            // Clips only loop every fourth step.
+            graph.advance_time(0.25);
+            let looped = graph.state.clips.iter().any(|clip| clip.looped);
+            graph.evaluate();
+            let current = graph.active_random_input(random).unwrap().unwrap();
+            if !looped {
+                assert_eq!(current, active);
+            }
+            active = current;
+            picked.push(active);
+
+            for clip in clips.iter() {
+                let weight = graph.state.clips[graph.clip_id(*clip).unwrap().0 as usize].weight;
+                assert_eq!(weight, if *clip == active { 1.0 } else { 0.0 });
+            }
+        }
+        for clip in clips {
+            assert!(picked.contains(&clip));
+        }
+    }
+}
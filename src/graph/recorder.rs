@@ -0,0 +1,330 @@
+use crate::{
+    clip::{AnimationClip, ClipCurve, CurveWrapper},
+    curve::CurveFixed,
+    graph::AnimationGraph,
+    path::PropertyPath,
+    target, Animatable,
+};
+use bevy_ecs::prelude::*;
+use bevy_reflect::{Reflect, TypeRegistryArc};
+use bevy_utils::Hashed;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// A non-generic interface for recording the values of a property.
+pub trait PropertyRecording: Send + Sync {
+    /// Records the value of the property for a given frame. Returns false if
+    /// the value is not of the recorded type.
+    fn record(&mut self, frame: usize, value: &dyn Reflect) -> bool;
+
+    /// Converts the recorded values into a curve, leaving the recording empty.
+    /// Returns `None` if nothing was recorded.
+    fn take_curve(&mut self, sample_rate: f32) -> Option<Box<dyn ClipCurve>>;
+}
+
+pub(crate) struct RecordedValues<T> {
+    /// The frame the first value was recorded in.
+    start: usize,
+    values: Vec<T>,
+}
+
+impl<T> Default for RecordedValues<T> {
+    fn default() -> Self {
+        Self {
+            start: 0,
+            values: Vec::new(),
+        }
+    }
+}
+
+impl<T: Animatable> PropertyRecording for RecordedValues<T> {
+    fn record(&mut self, frame: usize, value: &dyn Reflect) -> bool {
+        let value = match value.downcast_ref::<T>() {
+            Some(value) => value.clone(),
+            None => return false,
+        };
+        if let Some(last) = self.values.last().cloned() {
+            // Hold the last value through frames that couldn't be recorded.
+            let end = frame.saturating_sub(self.start);
+            self.values.resize(end.max(self.values.len()), last);
+        } else {
+            self.start = frame;
+        }
+        self.values.push(value);
+        true
+    }
+
+    fn take_curve(&mut self, sample_rate: f32) -> Option<Box<dyn ClipCurve>> {
+        if self.values.is_empty() {
+            return None;
+        }
+        let values = std::mem::take(&mut self.values);
+        let curve = CurveFixed::from_keyframes_with_offset(sample_rate, self.start as i32, values);
+        Some(Box::new(CurveWrapper::<T>(Arc::new(curve))))
+    }
+}
+
+struct RecordedProperty {
+    path: PropertyPath,
+    // Created from the graph's track for the property on the first frame it
+    // is recorded, as the value type isn't known before then.
+    values: Option<Box<dyn PropertyRecording>>,
+}
+
+/// Records the values an [`AnimationGraph`] applies to its bound entities, and
+/// bakes them into an [`AnimationClip`].
+///
+/// Only properties animated by the graph can be recorded. Values are read
+/// from the entities the graph's bones are bound to.
+pub struct GraphRecorder {
+    sample_rate: f32,
+    properties: Vec<RecordedProperty>,
+    frame: usize,
+    recording: bool,
+}
+
+impl GraphRecorder {
+    /// Creates a recorder for a set of properties, sampled `sample_rate` times
+    /// per second. The recorder starts out stopped.
+    pub fn new(paths: Vec<PropertyPath>, sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            properties: paths
+                .into_iter()
+                .map(|path| RecordedProperty { path, values: None })
+                .collect(),
+            frame: 0,
+            recording: false,
+        }
+    }
+
+    /// Starts or resumes recording.
+    pub fn start(&mut self) {
+        self.recording = true;
+    }
+
+    /// Pauses recording. Frames recorded while stopped are ignored, and time
+    /// spent stopped is not part of the recorded clip.
+    pub fn stop(&mut self) {
+        self.recording = false;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Records the current values of the properties animated by the
+    /// [`AnimationGraph`] on `graph` as one frame. Each call is assumed to be
+    /// `1 / sample_rate` seconds after the last. Does nothing if stopped.
+    ///
+    /// Properties that couldn't be read are reported in the returned error,
+    /// and hold their last recorded value for the frame.
+    pub fn record_frame(&mut self, world: &World, graph: Entity) -> Result<(), RecordError> {
+        if !self.recording {
+            return Ok(());
+        }
+        let graph = world
+            .get::<AnimationGraph>(graph)
+            .ok_or(RecordError::MissingGraph(graph))?;
+        let type_registry = world
+            .get_resource::<TypeRegistryArc>()
+            .ok_or(RecordError::MissingTypeRegistry)?
+            .read();
+
+        let frame = self.frame;
+        self.frame += 1;
+        let mut missing = Vec::new();
+        for property in self.properties.iter_mut() {
+            let path = &property.path;
+            let bone = graph.find_bone(path.entity());
+            if property.values.is_none() {
+                property.values = bone
+                    .and_then(|bone| bone.tracks.get(path.access()))
+                    .and_then(|track| track.start_recording());
+            }
+            let value = bone
+                .and_then(|bone| bone.entity())
+                .and_then(|entity| {
+                    target::reflect_target(&type_registry, world, entity, path.access())
+                })
+                .and_then(|component| path.access().field_path().field(component).ok());
+            let recorded = match (value, property.values.as_mut()) {
+                (Some(value), Some(values)) => values.record(frame, value),
+                _ => false,
+            };
+            if !recorded {
+                missing.push(path.clone());
+            }
+        }
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(RecordError::MissingProperties(missing))
+        }
+    }
+
+    /// Bakes everything recorded so far into a clip, and stops recording.
+    /// The recorder is reset, and can be started again for a new recording.
+    ///
+    /// Properties that were never recorded are left out of the clip.
+    pub fn finish(&mut self) -> AnimationClip {
+        let mut clip = AnimationClip::builder().build();
+        for property in self.properties.iter_mut() {
+            let curve = property
+                .values
+                .as_mut()
+                .and_then(|values| values.take_curve(self.sample_rate));
+            if let Some(curve) = curve {
+                clip.curves
+                    .insert(Hashed::new(property.path.clone()), curve);
+            }
+        }
+        self.frame = 0;
+        self.recording = false;
+        clip
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum RecordError {
+    #[error("entity {0:?} does not have an AnimationGraph")]
+    MissingGraph(Entity),
+    #[error("the TypeRegistryArc resource does not exist")]
+    MissingTypeRegistry,
+    #[error("failed to record {}", display_paths(.0))]
+    MissingProperties(Vec<PropertyPath>),
+}
+
+fn display_paths(paths: &[PropertyPath]) -> String {
+    paths
+        .iter()
+        .map(|path| format!("'{}'", path))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        curve::Curve,
+        graph::{NodeId, PlaybackMode},
+        AnimationPlugin,
+    };
+    use bevy_app::App;
+    use bevy_asset::AssetPlugin;
+    use bevy_core::Name;
+    use bevy_reflect::TypeRegistry;
+    use bevy_tasks::{ComputeTaskPool, IoTaskPool, TaskPool};
+    use bevy_transform::prelude::Children;
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Test {
+        a: f32,
+    }
+
+    const SAMPLE_RATE: f32 = 10.0;
+
+    fn path(registry: &TypeRegistry, entity: &str) -> PropertyPath {
+        let path = format!(
+            "{}@bevy_prototype_animation::graph::recorder::test::Test.a",
+            entity
+        );
+        PropertyPath::parse(registry, &path).unwrap()
+    }
+
+    #[test]
+    pub fn test_recorded_clip_matches_original() {
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .insert_resource(ComputeTaskPool(TaskPool::new()))
+            .add_plugin(AssetPlugin)
+            .add_plugin(AnimationPlugin::default())
+            .register_type::<Test>();
+        let (a, b) = {
+            let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+            let registry = registry.read();
+            (path(&registry, "a"), path(&registry, "b"))
+        };
+
+        let keyframes = vec![0.0f32, 1.0, 4.0, 9.0, 16.0];
+        let clip = AnimationClip::builder()
+            .add_curve(
+                a.clone(),
+                CurveFixed::from_keyframes(2.0, keyframes.clone()),
+            )
+            .add_curve(b.clone(), CurveFixed::from_keyframes(2.0, keyframes))
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+
+        let bone = app
+            .world
+            .spawn()
+            .insert(Name::new("a"))
+            .insert(Test::default())
+            .id();
+        // "b" is missing the animated component.
+        let missing = app.world.spawn().insert(Name::new("b")).id();
+        let root = app
+            .world
+            .spawn()
+            .insert(graph)
+            .insert(Children::with(&[bone, missing]))
+            .id();
+
+        let mut recorder = GraphRecorder::new(vec![a.clone(), b.clone()], SAMPLE_RATE);
+        let record = |app: &mut App, recorder: &mut GraphRecorder, frames: usize| {
+            for frame in 0..frames {
+                let time = frame as f32 / SAMPLE_RATE;
+                let mut graph = app.world.get_mut::<AnimationGraph>(root).unwrap();
+                graph.sample_at(time);
+                app.update();
+                let result = recorder.record_frame(&app.world, root);
+                if recorder.is_recording() {
+                    match result {
+                        Err(RecordError::MissingProperties(paths)) => {
+                            assert_eq!(paths, vec![b.clone()])
+                        }
+                        _ => panic!("expected '{}' to be missing", b),
+                    }
+                } else {
+                    assert!(result.is_ok());
+                }
+            }
+        };
+
+        // Nothing is recorded until started.
+        record(&mut app, &mut recorder, 5);
+        recorder.start();
+        // One full loop of the clip.
+        record(&mut app, &mut recorder, 20);
+        let recorded = recorder.finish();
+        assert!(!recorder.is_recording());
+        let original = clip.get_curve::<f32>(&Hashed::new(a.clone())).unwrap();
+        let curve = recorded.get_curve::<f32>(&Hashed::new(a.clone())).unwrap();
+        assert!(recorded.get_curve::<f32>(&Hashed::new(b.clone())).is_err());
+        assert_eq!(curve.keyframe_count(), 20);
+        for frame in 0..=38 {
+            let time = frame as f32 / 20.0;
+            assert!((curve.sample(time) - original.sample(time)).abs() < 1e-4);
+        }
+
+        // Recording can be restarted after finishing, and stopping pauses it.
+        recorder.start();
+        record(&mut app, &mut recorder, 11);
+        recorder.stop();
+        record(&mut app, &mut recorder, 5);
+        recorder.start();
+        record(&mut app, &mut recorder, 11);
+        let recorded = recorder.finish();
+        let curve = recorded.get_curve::<f32>(&Hashed::new(a)).unwrap();
+        assert_eq!(curve.keyframe_count(), 22);
+        assert!((curve.sample(1.0) - 4.0).abs() < 1e-4);
+        assert!((curve.sample(1.1) - 0.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,640 @@
+use crate::graph::{
+    random::GraphRng, AnimationGraph, ClipId, ClipState, ClipSync, Node, PlaybackMode,
+};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use thiserror::Error;
+
+/// A serializable snapshot of the runtime state of an [`AnimationGraph`],
+/// created by [`AnimationGraph::save_state`] and restored with
+/// [`AnimationGraph::restore_state`].
+///
+/// This captures everything that changes while the graph plays: the full
+/// state of every clip, including its time, weight, finished flags, playback
+/// mode, trim range, speed, phase offset, and whether it's paused, additive,
+/// normalized, or synced; the clip states being interpolated from with
+/// [`UpdateMode::FixedInterpolated`], the leaders of sync groups, the graph's
+/// parameters, the weights of every node input, the active inputs of random
+/// nodes and the state of the generator picking them, and any time
+/// accumulated towards the next fixed step or update interval.
+///
+/// The graph's structure, such as its nodes, curves, and bindings, is not
+/// captured, and neither are the [time warps](AnimationGraph::set_time_warp)
+/// of its clips, which are curves. The state should only be restored onto
+/// the graph it was saved from, or onto an identically built graph.
+///
+/// [`UpdateMode::FixedInterpolated`]: crate::graph::UpdateMode::FixedInterpolated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphRuntimeState {
+    version: u32,
+    clips: Vec<ClipRuntimeState>,
+    previous: Option<(Vec<ClipRuntimeState>, f32)>,
+    sync_leaders: Vec<Option<ClipId>>,
+    /// The weights and connection states of the inputs of each node, indexed
+    /// by node. Only blend and random nodes have inputs.
+    inputs: Vec<Vec<(f32, bool)>>,
+    /// The active input of each random node, indexed by node.
+    active_inputs: Vec<Option<u32>>,
+    rng: u64,
+    /// Sorted by name, so that equal states serialize identically.
+    params: Vec<(String, f32)>,
+    accumulated_time: f32,
+    interval_time: f32,
+    update_skipped: bool,
+    total_weight: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct ClipRuntimeState {
+    time: f32,
+    weight: f32,
+    start: f32,
+    duration: f32,
+    clip_duration: f32,
+    mode: PlaybackMode,
+    normalized: bool,
+    additive: bool,
+    /// The sync group of the clip, and whether it follows the group's
+    /// normalized phase.
+    sync: Option<(usize, bool)>,
+    finished: bool,
+    finished_reverse: bool,
+    looped: bool,
+    phase_offset: f32,
+    paused: bool,
+    speed: f32,
+}
+
+impl ClipRuntimeState {
+    fn save(clip: &ClipState) -> Self {
+        Self {
+            time: clip.time,
+            weight: clip.weight,
+            start: clip.start,
+            duration: clip.duration,
+            clip_duration: clip.clip_duration,
+            mode: clip.mode,
+            normalized: clip.normalized,
+            additive: clip.additive,
+            sync: clip.sync.map(|sync| (sync.group, sync.normalized)),
+            finished: clip.finished,
+            finished_reverse: clip.finished_reverse,
+            looped: clip.looped,
+            phase_offset: clip.phase_offset,
+            paused: clip.paused,
+            speed: clip.speed,
+        }
+    }
+
+    fn restore(&self, clip: &mut ClipState) {
+        clip.time = self.time;
+        clip.weight = self.weight;
+        clip.start = self.start;
+        clip.duration = self.duration;
+        clip.clip_duration = self.clip_duration;
+        clip.mode = self.mode;
+        clip.normalized = self.normalized;
+        clip.additive = self.additive;
+        clip.sync = self
+            .sync
+            .map(|(group, normalized)| ClipSync { group, normalized });
+        clip.finished = self.finished;
+        clip.finished_reverse = self.finished_reverse;
+        clip.looped = self.looped;
+        clip.phase_offset = self.phase_offset;
+        clip.paused = self.paused;
+        clip.speed = self.speed;
+    }
+}
+
+impl GraphRuntimeState {
+    /// The current version of the state's format. States saved with other
+    /// versions cannot be restored.
+    pub const VERSION: u32 = 3;
+
+    /// The version of the format the state was saved with.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+/// An error returned when restoring a [`GraphRuntimeState`] onto a graph that
+/// doesn't match the one it was saved from.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum GraphRuntimeStateError {
+    #[error("the state has version {found}, but only version {expected} is supported")]
+    UnsupportedVersion { expected: u32, found: u32 },
+    #[error("the state has {found} nodes, but the graph has {expected}")]
+    NodeCountMismatch { expected: usize, found: usize },
+    #[error("the state has {found} clips, but the graph has {expected}")]
+    ClipCountMismatch { expected: usize, found: usize },
+    #[error("the state has {found} sync groups, but the graph has {expected}")]
+    SyncGroupCountMismatch { expected: usize, found: usize },
+    #[error("the state's sync group {group} is led by clip {clip:?}, which does not exist")]
+    InvalidSyncLeader { group: usize, clip: ClipId },
+    #[error("the state's clip {clip:?} is in sync group {group}, which does not exist")]
+    InvalidSyncGroup { clip: ClipId, group: usize },
+    #[error("the state has {found} inputs for node {node}, but the graph has {expected}")]
+    InputCountMismatch {
+        node: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("the state's active input {input} of node {node} is not an input of a random node")]
+    InvalidActiveInput { node: usize, input: u32 },
+}
+
+fn active_input(node: &Node) -> Option<u32> {
+    match node {
+        Node::Random { active, .. } => active.map(|active| active as u32),
+        _ => None,
+    }
+}
+
+impl AnimationGraph {
+    /// Saves the runtime state of the graph, so that it can later be restored
+    /// exactly with [`restore_state`](Self::restore_state). Useful for
+    /// rollback networking and saving games mid-animation.
+    pub fn save_state(&self) -> GraphRuntimeState {
+        let save_clips = |clips: &[ClipState]| clips.iter().map(ClipRuntimeState::save).collect();
+        let mut params: Vec<_> = self
+            .params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        params.sort_by(|(a, _), (b, _)| a.cmp(b));
+        GraphRuntimeState {
+            version: GraphRuntimeState::VERSION,
+            clips: save_clips(&self.state.clips),
+            previous: self
+                .state
+                .previous
+                .as_ref()
+                .map(|(previous, alpha)| (save_clips(previous), *alpha)),
+            sync_leaders: self
+                .state
+                .sync_groups
+                .iter()
+                .map(|group| group.leader)
+                .collect(),
+            inputs: self
+                .nodes
+                .iter()
+                .map(|(_, node)| {
+                    node.inputs()
+                        .map(|input| (input.weight(), input.is_connected()))
+                        .collect()
+                })
+                .collect(),
+            active_inputs: self
+                .nodes
+                .iter()
+                .map(|(_, node)| active_input(node))
+                .collect(),
+            rng: self.rng.state(),
+            params,
+            accumulated_time: self.accumulated_time,
+            interval_time: self.interval_time,
+            update_skipped: self.update_skipped,
+            total_weight: self.total_weight,
+        }
+    }
+
+    /// Restores a runtime state saved with [`save_state`](Self::save_state).
+    /// Playing the graph afterwards reproduces the same results as it did
+    /// after the state was saved. Parameters that were set since are
+    /// removed.
+    ///
+    /// Returns an error if the state was saved with a different version, or
+    /// if the graph's structure doesn't match the graph the state was saved
+    /// from. The graph is left unchanged on failure.
+    pub fn restore_state(
+        &mut self,
+        state: &GraphRuntimeState,
+    ) -> Result<(), GraphRuntimeStateError> {
+        self.check_state(state)?;
+        for (saved, clip) in state.clips.iter().zip(self.state.clips.iter_mut()) {
+            saved.restore(clip);
+        }
+        self.state.previous = state.previous.as_ref().map(|(previous, alpha)| {
+            let mut clips = self.state.clips.clone();
+            for (saved, clip) in previous.iter().zip(clips.iter_mut()) {
+                saved.restore(clip);
+            }
+            (clips, *alpha)
+        });
+        for (leader, group) in state
+            .sync_leaders
+            .iter()
+            .zip(self.state.sync_groups.iter_mut())
+        {
+            group.leader = *leader;
+        }
+        for (saved, (_, node)) in state.inputs.iter().zip(self.nodes.iter_mut()) {
+            for ((weight, connected), input) in saved.iter().zip(node.inputs_mut()) {
+                input.set_weight(*weight);
+                input.set_connected(*connected);
+            }
+        }
+        for (saved, (_, node)) in state.active_inputs.iter().zip(self.nodes.iter_mut()) {
+            if let Node::Random { active, .. } = node {
+                *active = saved.map(|active| active as usize);
+            }
+        }
+        self.rng = GraphRng::new(state.rng);
+        self.params.replace(
+            state
+                .params
+                .iter()
+                .map(|(name, value)| (Cow::Owned(name.clone()), *value)),
+        );
+        self.accumulated_time = state.accumulated_time;
+        self.interval_time = state.interval_time;
+        self.update_skipped = state.update_skipped;
+        self.total_weight = state.total_weight;
+        Ok(())
+    }
+
+    fn check_state(&self, state: &GraphRuntimeState) -> Result<(), GraphRuntimeStateError> {
+        if state.version != GraphRuntimeState::VERSION {
+            return Err(GraphRuntimeStateError::UnsupportedVersion {
+                expected: GraphRuntimeState::VERSION,
+                found: state.version,
+            });
+        }
+        for found in [state.inputs.len(), state.active_inputs.len()] {
+            if found != self.nodes.count() {
+                return Err(GraphRuntimeStateError::NodeCountMismatch {
+                    expected: self.nodes.count(),
+                    found,
+                });
+            }
+        }
+        let clip_counts = std::iter::once(state.clips.len())
+            .chain(state.previous.iter().map(|(previous, _)| previous.len()));
+        for found in clip_counts {
+            if found != self.state.clips.len() {
+                return Err(GraphRuntimeStateError::ClipCountMismatch {
+                    expected: self.state.clips.len(),
+                    found,
+                });
+            }
+        }
+        if state.sync_leaders.len() != self.state.sync_groups.len() {
+            return Err(GraphRuntimeStateError::SyncGroupCountMismatch {
+                expected: self.state.sync_groups.len(),
+                found: state.sync_leaders.len(),
+            });
+        }
+        for (group, leader) in state.sync_leaders.iter().enumerate() {
+            match leader {
+                Some(clip) if clip.0 as usize >= self.state.clips.len() => {
+                    return Err(GraphRuntimeStateError::InvalidSyncLeader { group, clip: *clip });
+                }
+                _ => {}
+            }
+        }
+        let saved_clips = state
+            .clips
+            .iter()
+            .chain(state.previous.iter().flat_map(|(previous, _)| previous));
+        for (idx, saved) in saved_clips.enumerate() {
+            match saved.sync {
+                Some((group, _)) if group >= self.state.sync_groups.len() => {
+                    return Err(GraphRuntimeStateError::InvalidSyncGroup {
+                        clip: ClipId((idx % self.state.clips.len()) as u16),
+                        group,
+                    });
+                }
+                _ => {}
+            }
+        }
+        for (node, (saved, (_, current))) in state.inputs.iter().zip(self.nodes.iter()).enumerate()
+        {
+            let expected = current.inputs().count();
+            if saved.len() != expected {
+                return Err(GraphRuntimeStateError::InputCountMismatch {
+                    node,
+                    expected,
+                    found: saved.len(),
+                });
+            }
+        }
+        for (node, (saved, (_, current))) in state
+            .active_inputs
+            .iter()
+            .zip(self.nodes.iter())
+            .enumerate()
+        {
+            let input = match saved {
+                Some(input) => *input,
+                None => continue,
+            };
+            let valid = match current {
+                Node::Random { inputs, .. } => (input as usize) < inputs.len(),
+                _ => false,
+            };
+            if !valid {
+                return Err(GraphRuntimeStateError::InvalidActiveInput { node, input });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        clip::AnimationClip,
+        curve::CurveFixed,
+        graph::{AnimationGraphError, NodeId, PlaybackMode, UpdateMode, WeightBinding},
+        path::PropertyPath,
+        AnimationPlugin,
+    };
+    use bevy_app::App;
+    use bevy_asset::AssetPlugin;
+    use bevy_core::Name;
+    use bevy_ecs::prelude::*;
+    use bevy_math::Vec3;
+    use bevy_reflect::TypeRegistryArc;
+    use bevy_tasks::{ComputeTaskPool, IoTaskPool, TaskPool};
+    use bevy_transform::prelude::Children;
+    use bevy_transform::prelude::Transform;
+
+    const FRAME: f32 = 1.0 / 60.0;
+
+    fn app() -> (App, Entity, Entity) {
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .insert_resource(ComputeTaskPool(TaskPool::new()))
+            .add_plugin(AssetPlugin)
+            .add_plugin(AnimationPlugin::default())
+            .register_type::<Transform>();
+        let path = {
+            let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+            PropertyPath::parse(
+                &registry.read(),
+                "a@bevy_transform::components::transform::Transform.translation",
+            )
+            .unwrap()
+        };
+
+        let mut graph = AnimationGraph::new();
+        graph
+            .set_update_mode(UpdateMode::FixedInterpolated { hz: 24.0 })
+            .unwrap();
+        for (duration, binding) in [(1.0, (1.0, 0.0)), (1.7, (0.0, 1.0))] {
+            let keyframes = vec![Vec3::ZERO, Vec3::new(1.0, 2.0, 3.0), Vec3::ZERO];
+            let clip = AnimationClip::builder()
+                .add_curve(
+                    path.clone(),
+                    CurveFixed::from_keyframes(2.0 / duration, keyframes),
+                )
+                .build();
+            let node = graph.add_clip(&clip).unwrap();
+            graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+            graph.set_sync_group(node, "walk", true).unwrap();
+            graph
+                .add_input(NodeId::ROOT, node)
+                .unwrap()
+                .set_weight_binding(Some(WeightBinding {
+                    param: "blend".into(),
+                    in_range: (0.0, 1.0),
+                    out_range: binding,
+                    clamp: true,
+                }));
+        }
+
+        let bone = app
+            .world
+            .spawn()
+            .insert(Name::new("a"))
+            .insert(Transform::default())
+            .id();
+        let root = app
+            .world
+            .spawn()
+            .insert(graph)
+            .insert(Children::with(&[bone]))
+            .id();
+        (app, root, bone)
+    }
+
+    /// Plays the graph for a number of frames, returning the bits of the
+    /// applied translation on every frame.
+    fn play(
+        app: &mut App,
+        root: Entity,
+        bone: Entity,
+        frames: std::ops::Range<u32>,
+    ) -> Vec<[u32; 3]> {
+        frames
+            .map(|frame| {
+                let mut graph = app.world.get_mut::<AnimationGraph>(root).unwrap();
+                graph.set_param("blend", (frame as f32 * 0.05).sin() * 0.5 + 0.5);
+                graph.advance_time(FRAME);
+                app.update();
+                let translation = app.world.get::<Transform>(bone).unwrap().translation;
+                translation.to_array().map(f32::to_bits)
+            })
+            .collect()
+    }
+
+    #[test]
+    pub fn test_restored_state_replays_identically() {
+        let (mut app, root, bone) = app();
+        play(&mut app, root, bone, 0..30);
+        let state = app.world.get::<AnimationGraph>(root).unwrap().save_state();
+        let first = play(&mut app, root, bone, 30..60);
+
+        let mut graph = app.world.get_mut::<AnimationGraph>(root).unwrap();
+        graph.set_param("unrelated", 1.0);
+        graph.restore_state(&state).unwrap();
+        assert_eq!(graph.get_param("unrelated"), None);
+        assert_eq!(graph.save_state(), state);
+        let second = play(&mut app, root, bone, 30..60);
+        assert_eq!(first, second);
+        // The translation actually changed over the frames.
+        assert!(first.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    pub fn test_every_clip_setting_round_trips() {
+        let mut registry = bevy_reflect::TypeRegistry::default();
+        registry.register::<Transform>();
+        let path = PropertyPath::parse(
+            &registry,
+            "a@bevy_transform::components::transform::Transform.translation",
+        )
+        .unwrap();
+        let keyframes = vec![Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::Z, Vec3::ONE];
+        let clip = AnimationClip::builder()
+            .add_curve(path, CurveFixed::from_keyframes(4.0, keyframes))
+            .build();
+        let build = || {
+            let mut graph = AnimationGraph::new();
+            let node = graph.add_clip(&clip).unwrap();
+            graph.add_input(NodeId::ROOT, node).unwrap();
+            graph.set_sync_group(node, "walk", true).unwrap();
+            (graph, node)
+        };
+
+        let (mut graph, node) = build();
+        graph.set_clip_range(node, 0.25..0.75).unwrap();
+        graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+        graph.set_normalized_time(node, true).unwrap();
+        graph.set_additive(node, true).unwrap();
+        graph.set_sync_group(node, "walk", false).unwrap();
+        graph.set_clip_phase_offset(node, 0.1).unwrap();
+        graph.set_clip_speed(node, 2.0).unwrap();
+        graph.advance_time(0.1);
+        graph.evaluate();
+        graph.pause_clip(node).unwrap();
+        let state = graph.save_state();
+
+        let loaded: GraphRuntimeState = ron::from_str(&ron::to_string(&state).unwrap()).unwrap();
+        let (mut restored, _) = build();
+        restored.restore_state(&loaded).unwrap();
+        assert_eq!(restored.save_state(), state);
+        assert_eq!(restored.clip_duration(node).unwrap(), 0.5);
+        assert_eq!(restored.state.clips[0].mode, PlaybackMode::Loop);
+        assert!(restored.is_normalized_time(node).unwrap());
+        assert!(restored.is_additive(node).unwrap());
+        assert_eq!(restored.clip_phase_offset(node).unwrap(), 0.1);
+        assert_eq!(restored.clip_speed(node).unwrap(), 2.0);
+        assert!(restored.is_clip_paused(node).unwrap());
+        assert_eq!(
+            restored.state.clips[0].time.to_bits(),
+            graph.state.clips[0].time.to_bits()
+        );
+        assert!(matches!(
+            restored.state.clips[0].sync,
+            Some(ClipSync {
+                normalized: false,
+                ..
+            })
+        ));
+
+        let mut state = state;
+        state.clips[0].sync = Some((3, false));
+        assert_eq!(
+            restored.restore_state(&state),
+            Err(GraphRuntimeStateError::InvalidSyncGroup {
+                clip: ClipId(0),
+                group: 3
+            })
+        );
+    }
+
+    #[test]
+    pub fn test_mismatched_structure_is_rejected() {
+        let (app, root, _) = app();
+        let state = app.world.get::<AnimationGraph>(root).unwrap().save_state();
+
+        let mut graph = AnimationGraph::new();
+        assert_eq!(
+            graph.restore_state(&state),
+            Err(GraphRuntimeStateError::NodeCountMismatch {
+                expected: 1,
+                found: 3
+            })
+        );
+        let clip = AnimationClip::builder().build();
+        for _ in 0..2 {
+            let node = graph.add_clip(&clip).unwrap();
+            graph.add_input(NodeId::ROOT, node).unwrap();
+        }
+        graph.add_clip(&clip).unwrap();
+        let mut state = state;
+        state.inputs.push(Vec::new());
+        state.active_inputs.push(None);
+        assert_eq!(
+            graph.restore_state(&state),
+            Err(GraphRuntimeStateError::ClipCountMismatch {
+                expected: 3,
+                found: 2
+            })
+        );
+        state.version = 0;
+        assert_eq!(
+            graph.restore_state(&state),
+            Err(GraphRuntimeStateError::UnsupportedVersion {
+                expected: GraphRuntimeState::VERSION,
+                found: 0
+            })
+        );
+    }
+
+    #[test]
+    pub fn test_ids_round_trip_with_saved_state() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct SaveGame {
+            state: GraphRuntimeState,
+            clips: Vec<(NodeId, ClipId)>,
+        }
+
+        let (mut app, root, bone) = app();
+        play(&mut app, root, bone, 0..30);
+        let graph = app.world.get::<AnimationGraph>(root).unwrap();
+        let save = SaveGame {
+            state: graph.save_state(),
+            clips: graph
+                .nodes
+                .iter()
+                .filter_map(|(node, _)| Some((node, graph.clip_id(node).ok()?)))
+                .collect(),
+        };
+        assert!(save.state.sync_leaders.iter().any(Option::is_some));
+        assert_eq!(ron::to_string(&NodeId::ROOT).unwrap(), "0");
+
+        let loaded: SaveGame = ron::from_str(&ron::to_string(&save).unwrap()).unwrap();
+        assert_eq!(loaded, save);
+        let first = play(&mut app, root, bone, 30..60);
+
+        let mut graph = app.world.get_mut::<AnimationGraph>(root).unwrap();
+        graph.restore_state(&loaded.state).unwrap();
+        for (node, clip) in loaded.clips {
+            assert!(graph.contains_node(node));
+            assert_eq!(graph.clip_id(node).unwrap(), clip);
+        }
+        assert_eq!(play(&mut app, root, bone, 30..60), first);
+    }
+
+    #[test]
+    pub fn test_out_of_range_ids_are_rejected() {
+        let (mut app, root, _) = app();
+        let mut graph = app.world.get_mut::<AnimationGraph>(root).unwrap();
+        let node: NodeId = ron::from_str("40").unwrap();
+        assert!(!graph.contains_node(node));
+        assert!(graph.contains_node(NodeId::ROOT));
+
+        assert!(matches!(
+            graph.set_time(node, 1.0),
+            Err(AnimationGraphError::NodeNotFound(id)) if id == node
+        ));
+        assert!(matches!(
+            graph.input_mut(node, NodeId::ROOT),
+            Err(AnimationGraphError::NodeNotFound(_))
+        ));
+        assert!(matches!(
+            graph.input_mut(NodeId::ROOT, node),
+            Err(AnimationGraphError::InputNotFound(_))
+        ));
+        assert!(matches!(
+            graph.override_input_weight(NodeId::ROOT, node, 0.5),
+            Err(AnimationGraphError::InputNotFound(_))
+        ));
+        assert!(graph.clip_weight(node).is_err());
+
+        let mut state = graph.save_state();
+        state.sync_leaders[0] = Some(ron::from_str("40").unwrap());
+        assert_eq!(
+            graph.restore_state(&state),
+            Err(GraphRuntimeStateError::InvalidSyncLeader {
+                group: 0,
+                clip: ClipId(40)
+            })
+        );
+    }
+}
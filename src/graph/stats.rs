@@ -0,0 +1,116 @@
+use crate::{
+    graph::Track,
+    path::{AccessPath, EntityPath, PropertyPath},
+};
+use bevy_reflect::Reflect;
+use bevy_utils::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// Profiling counters for a single animated property, collected while an
+/// [`AnimationGraph`] is applied with [`enable_track_stats`].
+///
+/// Useful to find tracks that never visibly change, so they can be stripped
+/// from their source clips.
+///
+/// [`AnimationGraph`]: crate::graph::AnimationGraph
+/// [`enable_track_stats`]: crate::graph::AnimationGraph::enable_track_stats
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TrackStats {
+    /// The number of times the track was blended and written to its entity.
+    pub samples: u64,
+    /// The number of writes skipped because the blended value was equal to
+    /// the current value of the property.
+    pub skipped: u64,
+    /// The largest change of the written value between two consecutive
+    /// samples, measured with [`Animatable::distance`]. Always 0 for tracks
+    /// that can't measure the distance between their values.
+    ///
+    /// [`Animatable::distance`]: crate::Animatable::distance
+    pub max_delta: f32,
+}
+
+#[derive(Default)]
+struct RecordedTrack {
+    stats: TrackStats,
+    last: Option<Box<dyn Reflect>>,
+}
+
+/// The stats of every track of a graph, keyed like [`AnimatedPose`].
+///
+/// [`AnimatedPose`]: crate::graph::pose::AnimatedPose
+#[derive(Default)]
+pub(crate) struct TrackStatsStorage {
+    // Locked once per bone as bones are applied in parallel.
+    bones: Mutex<HashMap<EntityPath, HashMap<AccessPath, RecordedTrack>>>,
+}
+
+pub(crate) struct TrackStatsRecorder<'a> {
+    bones: MutexGuard<'a, HashMap<EntityPath, HashMap<AccessPath, RecordedTrack>>>,
+}
+
+impl TrackStatsStorage {
+    pub fn lock(&self) -> TrackStatsRecorder<'_> {
+        TrackStatsRecorder {
+            bones: self.bones.lock().unwrap(),
+        }
+    }
+
+    /// Copies out the stats of every recorded track.
+    pub fn collect(&self) -> Vec<(PropertyPath, TrackStats)> {
+        let bones = self.bones.lock().unwrap();
+        bones
+            .iter()
+            .flat_map(|(entity, bone)| {
+                bone.iter().map(move |(access, track)| {
+                    (
+                        PropertyPath::from_parts(entity.clone(), access.clone()),
+                        track.stats,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.bones.get_mut().unwrap().clear();
+    }
+}
+
+impl<'a> TrackStatsRecorder<'a> {
+    /// Records a sample of a track. `value` is the current value of the
+    /// property, after it was written if `written` is true.
+    pub fn record(
+        &mut self,
+        entity: &EntityPath,
+        property: &AccessPath,
+        track: &dyn Track,
+        value: &dyn Reflect,
+        written: bool,
+    ) {
+        if !self.bones.contains_key(entity) {
+            self.bones.insert(entity.clone(), HashMap::default());
+        }
+        let bone = self.bones.get_mut(entity).unwrap();
+        if !bone.contains_key(property) {
+            bone.insert(property.clone(), RecordedTrack::default());
+        }
+        let recorded = bone.get_mut(property).unwrap();
+
+        recorded.stats.samples += 1;
+        if !written {
+            // The value is unchanged, so there's no delta to measure.
+            recorded.stats.skipped += 1;
+            if recorded.last.is_some() {
+                return;
+            }
+        }
+        if let Some(delta) = recorded
+            .last
+            .as_deref()
+            .and_then(|last| track.value_distance(last, value))
+        {
+            recorded.stats.max_delta = recorded.stats.max_delta.max(delta);
+        }
+        recorded.last = Some(value.clone_value());
+    }
+}
@@ -1,36 +1,61 @@
 use crate::{
     clip::AnimationClip,
     clip::{ClipCurve, CurveWrapper},
-    curve::Curve,
-    graph::GraphState,
-    path::{AccessPath, EntityPath},
-    Animatable, BlendInput,
+    curve::{Curve, CurveFixed, KeyframeIndex},
+    graph::{
+        recorder::{PropertyRecording, RecordedValues},
+        typed, ClipState, GraphState, WriteMask,
+    },
+    path::{AccessPath, EntityPath, FieldPath, PathInterner, PropertyPath},
+    Animatable, BlendInput, TransformBlendMode, WorldResources,
 };
 use bevy_ecs::prelude::{Entity, World};
-use bevy_reflect::Reflect;
-use bevy_utils::HashMap;
+use bevy_log::warn;
+use bevy_math::{Quat, Vec3};
+use bevy_reflect::{impl_reflect_value, Reflect, ReflectDeserialize};
+use bevy_transform::prelude::Transform;
+use bevy_utils::{HashMap, HashSet, Hashed, PassHash};
+use serde::{Deserialize, Serialize};
+use smallvec::{smallvec, SmallVec};
 use std::{
     any::{Any, TypeId},
+    borrow::Cow,
     collections::BTreeMap,
+    fmt,
     sync::Arc,
 };
+use thiserror::Error;
 
 pub(crate) struct BoneTrack<'a> {
     pub property: &'a AccessPath,
     pub track: &'a (dyn Track + 'static),
 }
 
-#[derive(Debug, Clone, Copy)]
+/// The id of a bone in an [`AnimationGraph`], the index of its path in the
+/// graph's [`paths`](AnimationGraph::paths). See
+/// [`AnimationGraph::bone_id`].
+///
+/// [`AnimationGraph`]: super::AnimationGraph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BoneId(usize);
 
+#[derive(Clone)]
 pub struct Bone {
     pub(super) id: BoneId,
     pub(super) path: EntityPath,
     pub(super) entity: Option<Entity>,
     // BTreeMap is used here as it's iteration is O(size) not O(capacity).
-    // like HashMap. The lexographic ordering of FieldPath also ensures that the
-    // fields on the same component applied close together during application.
-    pub(super) tracks: BTreeMap<AccessPath, Box<dyn Track + 'static>>,
+    // like HashMap. The ordering of AccessPath also ensures that the fields on
+    // the same component are applied close together during application, and
+    // that whole components are applied before their fields.
+    //
+    // Tracks are shared between bones when they are built from the same curves.
+    // Use `make_track_mut` to mutate them.
+    pub(super) tracks: BTreeMap<AccessPath, Arc<dyn Track + 'static>>,
+    // The distinct priorities of the tracks, in ascending order. Always
+    // contains the default priority.
+    pub(super) priorities: SmallVec<[i32; 1]>,
+    pub(super) write_mask: WriteMask,
 }
 
 impl Bone {
@@ -38,17 +63,51 @@ impl Bone {
         self.id
     }
 
+    /// The path of the bone, relative to the root of the graph.
+    pub fn path(&self) -> &EntityPath {
+        &self.path
+    }
+
+    /// The number of properties the bone animates.
+    pub fn track_count(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Gets the track of a property, sharing it rather than borrowing it.
+    pub(crate) fn shared_track(&self, property: &AccessPath) -> Option<Arc<dyn Track>> {
+        self.tracks.get(property).cloned()
+    }
+
+    /// Whether any of the bone's tracks has a curve from a clip.
+    pub(crate) fn animates_clip(&self, clip_id: ClipId) -> bool {
+        self.tracks
+            .values()
+            .any(|track| track.animates_clip(clip_id))
+    }
+
     pub fn properties(&self) -> impl Iterator<Item = &AccessPath> {
         self.tracks.keys()
     }
 
+    /// The tracks of the bone, in the order they're applied: by priority,
+    /// then in the order of their [`AccessPath`]s.
     pub(crate) fn tracks(&self) -> impl Iterator<Item = BoneTrack<'_>> {
-        self.tracks.iter().map(|(key, value)| BoneTrack {
-            property: &key,
-            track: value.as_ref(),
+        self.priorities.iter().flat_map(move |priority| {
+            self.tracks
+                .iter()
+                .filter(move |(key, _)| key.priority() == *priority)
+                .map(|(key, value)| BoneTrack {
+                    property: key,
+                    track: value.as_ref(),
+                })
         })
     }
 
+    /// Which of the bone's properties are written to its bound entity.
+    pub fn write_mask(&self) -> &WriteMask {
+        &self.write_mask
+    }
+
     /// Gets the currently bound entity.
     ///
     /// This may not be a valid entity ID even if available.
@@ -59,10 +118,18 @@ impl Bone {
     pub(crate) fn set_entity(&mut self, entity: Option<Entity>) {
         self.entity = entity;
     }
+
+    fn add_priority(&mut self, priority: i32) {
+        if let Err(idx) = self.priorities.binary_search(&priority) {
+            self.priorities.insert(idx, priority);
+        }
+    }
 }
 
+#[derive(Default, Clone)]
 pub(super) struct GraphClips {
-    bones: HashMap<EntityPath, BoneId>,
+    // Interns the paths of the bones. A bone's id is the id of its path.
+    paths: PathInterner,
     // Indexed by BoneId
     tracks: Vec<Bone>,
     pub(super) dirty: bool,
@@ -79,53 +146,360 @@ impl GraphClips {
         self.dirty = dirty;
     }
 
+    /// Verifies that the types of each of the curves in a clip match the types
+    /// of any existing tracks for the same properties. Every conflicting
+    /// property is reported, ordered by path.
+    pub(super) fn check_clip(&self, clip: &AnimationClip) -> Result<(), TrackError> {
+        let mut conflicts: Vec<_> = clip
+            .curves
+            .iter()
+            .filter_map(|(path, curve)| self.find_conflict(path, curve.as_ref()))
+            .collect();
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+        conflicts.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+        Err(TrackError::ConflictingType { conflicts })
+    }
+
+    /// Verifies a batch of clips like [`check_clip`](Self::check_clip). Curves
+    /// from different clips in the batch that animate the same property with
+    /// different types are reported as well.
+    pub(super) fn check_clips(&self, clips: &[&AnimationClip]) -> Result<(), TrackError> {
+        let mut conflicts = Vec::new();
+        // Keyed by the clips' prehashed paths, so they aren't hashed again.
+        let mut batch: std::collections::HashMap<
+            &Hashed<PropertyPath>,
+            (TypeId, &'static str),
+            PassHash,
+        > = Default::default();
+        for clip in clips {
+            for (path, curve) in clip.curves.iter() {
+                if let Some(conflict) = self.find_conflict(path, curve.as_ref()) {
+                    conflicts.push(conflict);
+                    continue;
+                }
+                let incoming = (curve.value_type_id(), curve.value_type_name());
+                match batch.get(path) {
+                    Some(&(existing, existing_name)) if existing != incoming.0 => {
+                        conflicts.push(TypeConflict {
+                            path: (**path).clone(),
+                            existing,
+                            existing_name,
+                            incoming: incoming.0,
+                            incoming_name: incoming.1,
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        batch.insert(path, incoming);
+                    }
+                }
+            }
+        }
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+        conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+        Err(TrackError::ConflictingType { conflicts })
+    }
+
+    fn find_conflict(&self, path: &PropertyPath, curve: &dyn ClipCurve) -> Option<TypeConflict> {
+        let conflict = |(existing, existing_name)| TypeConflict {
+            path: path.clone(),
+            existing,
+            existing_name,
+            incoming: curve.value_type_id(),
+            incoming_name: curve.value_type_name(),
+        };
+        let route = typed::find_route(path.access());
+        if let Some(route) = &route {
+            if route.value_type.0 != curve.value_type_id() {
+                return Some(conflict(route.value_type));
+            }
+        }
+        let key = route.as_ref().map_or(path.access(), |route| &route.key);
+        let track = self
+            .find_bone(path.entity())
+            .and_then(|bone| bone.tracks.get(key))?;
+        let field = match route {
+            Some(_) => path.access().field_path().clone(),
+            None => FieldPath::root(),
+        };
+        match track.field_type(&field) {
+            Some((type_id, _)) if type_id == curve.value_type_id() => None,
+            Some(existing) => Some(conflict(existing)),
+            None => Some(conflict((track.value_type_id(), track.value_type_name()))),
+        }
+    }
+
     pub(super) fn add_clip(
         &mut self,
         clip_id: ClipId,
         clip: &AnimationClip,
     ) -> Result<(), TrackError> {
         // Verify that the types for each of the tracks are identical before adding any of the curves in.
+        self.check_clip(clip)?;
+        self.add_curves(
+            clip_id,
+            clip.curves.iter().map(|(path, curve)| (&**path, curve)),
+        )
+    }
+
+    /// Adds a batch of clips that were already verified with
+    /// [`check_clips`](Self::check_clips), reserving space for all of their new
+    /// bones up front.
+    pub(super) fn add_checked_clips(
+        &mut self,
+        clips: &[(ClipId, &AnimationClip)],
+    ) -> Result<(), TrackError> {
+        let new_bones: HashSet<&EntityPath> = clips
+            .iter()
+            .flat_map(|(_, clip)| clip.curves.keys().map(|path| path.entity()))
+            .filter(|path| self.paths.entity_id(path).is_none())
+            .collect();
+        self.reserve(new_bones.len());
+        for (clip_id, clip) in clips {
+            self.add_curves(
+                *clip_id,
+                clip.curves.iter().map(|(path, curve)| (&**path, curve)),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Reserves space for at least `bones` more bones.
+    pub(super) fn reserve(&mut self, bones: usize) {
+        self.paths.reserve_entities(bones);
+        self.tracks.reserve(bones);
+    }
+
+    /// Replaces the curves a clip contributes to the tracks with the curves
+    /// of a new version of the clip. Curves whose type conflicts with an
+    /// existing track are skipped with a warning, keeping the old curve for
+    /// that property. Tracks left without any curves are removed, but their
+    /// bones are kept.
+    pub(super) fn reload_clip(
+        &mut self,
+        clip_id: ClipId,
+        clip: &AnimationClip,
+    ) -> Result<(), TrackError> {
+        let mut rejected: HashSet<(&EntityPath, &AccessPath)> = HashSet::default();
         for (path, curve) in clip.curves.iter() {
-            let valid = self
-                .find_bone(path.entity())
-                .and_then(|bone| bone.tracks.get(path.access()))
-                .map(|track| curve.value_type_id() == track.value_type_id())
-                .unwrap_or(true);
-
-            if !valid {
-                return Err(TrackError::IncorrectType);
+            // The clip's old curve is still in the tracks, so a curve that
+            // changes the type of a property is caught here.
+            if let Some(conflict) = self.find_conflict(path, curve.as_ref()) {
+                warn!("Failed to reload a clip's curve: {}", conflict);
+                rejected.insert((path.entity(), path.access()));
             }
         }
 
-        for (path, curve) in clip.curves.iter() {
-            let bone_id = if let Some(bone_id) = self.bones.get(path.entity()) {
-                *bone_id
-            } else {
-                let bone_id = BoneId(self.tracks.len());
-                self.bones.insert(path.entity().clone(), bone_id);
+        for bone in self.tracks.iter_mut() {
+            let path = &bone.path;
+            let keep = |access: &AccessPath| rejected.contains(&(path, access));
+            bone.tracks.retain(|access, track| {
+                !track.animates_clip(clip_id)
+                    || !make_track_mut(track).remove_clip_curves(access, clip_id, &keep)
+            });
+        }
+        self.dirty = true;
+
+        self.add_curves(
+            clip_id,
+            clip.curves
+                .iter()
+                .map(|(path, curve)| (&**path, curve))
+                .filter(|(path, _)| !rejected.contains(&(path.entity(), path.access()))),
+        )
+    }
+
+    /// Removes the curves a clip contributes to the tracks. Tracks left
+    /// without any curves are removed, but their bones are kept.
+    pub(super) fn remove_clip(&mut self, clip_id: ClipId) {
+        for bone in self.tracks.iter_mut() {
+            bone.tracks.retain(|access, track| {
+                !track.animates_clip(clip_id)
+                    || !make_track_mut(track).remove_clip_curves(access, clip_id, &|_| false)
+            });
+        }
+        self.dirty = true;
+    }
+
+    /// Replaces the curves a clip contributes to the tracks with constant
+    /// placeholders, keeping every bone and track. Tracks shared between
+    /// bones stay shared.
+    pub(super) fn evict_clip(&mut self, clip_id: ClipId) {
+        // Keyed by the original track, which is kept alive so that its
+        // address isn't reused by the evicted tracks.
+        let mut evicted: HashMap<*const (), Arc<dyn Track>> = HashMap::default();
+        let mut originals = Vec::new();
+        for bone in self.tracks.iter_mut() {
+            for track in bone.tracks.values_mut() {
+                if !track.animates_clip(clip_id) {
+                    continue;
+                }
+                let key = Arc::as_ptr(track) as *const ();
+                if let Some(shared) = evicted.get(&key) {
+                    *track = shared.clone();
+                    continue;
+                }
+                originals.push(track.clone());
+                make_track_mut(track).evict_clip_curves(clip_id);
+                evicted.insert(key, track.clone());
+            }
+        }
+    }
+
+    /// The estimated size of every curve referenced by the tracks, counting
+    /// shared curves once.
+    pub(super) fn resident_curve_bytes(&self) -> usize {
+        let mut sizes = HashMap::default();
+        for bone in self.tracks.iter() {
+            for track in bone.tracks.values() {
+                track.curve_sizes(&mut sizes);
+            }
+        }
+        sizes.values().sum()
+    }
+
+    /// Adds a clip's curves to the tracks, adding bones and tracks as needed.
+    /// The types of the curves must already be checked.
+    fn add_curves<'a>(
+        &mut self,
+        clip_id: ClipId,
+        curves: impl Iterator<Item = (&'a PropertyPath, &'a Box<dyn ClipCurve>)>,
+    ) -> Result<(), TrackError> {
+        // Curves shared between multiple properties produce identical tracks.
+        // Cache the results, keyed by the previous track and the added curve,
+        // so the tracks can be shared instead of reallocated. Typed tracks
+        // also depend on the field the curve is added to.
+        let mut shared: HashMap<(*const (), *const (), Option<&FieldPath>), Arc<dyn Track>> =
+            HashMap::default();
+        for (path, curve) in curves {
+            // The fields of typed components are animated by a single track
+            // for the whole component.
+            let route = typed::find_route(path.access());
+            let bone_id = BoneId(self.paths.intern_entity(path.entity()).index());
+            if bone_id.0 == self.tracks.len() {
                 self.tracks.push(Bone {
                     id: bone_id,
-                    path: path.entity().clone(),
+                    path: self.paths.share_entity(path.entity()),
                     entity: None,
                     tracks: Default::default(),
+                    priorities: smallvec![0],
+                    write_mask: WriteMask::All,
                 });
                 self.dirty = true;
-                bone_id
-            };
+            }
 
             let bone_tracks = &mut self.tracks[bone_id.0];
-            if let Some(track) = bone_tracks.tracks.get_mut(path.access()) {
-                track.add_generic_curve(clip_id, curve.as_ref()).unwrap();
+            // Removing the previous track allows it to be mutated in place if
+            // it isn't shared with any other bone.
+            let access = route.as_ref().map_or(path.access(), |route| &route.key);
+            let previous = bone_tracks.tracks.remove_entry(access);
+            // The track keeps the highest priority of its curves.
+            let priority = previous
+                .as_ref()
+                .map_or(path.access().priority(), |(key, _)| {
+                    key.priority().max(path.access().priority())
+                });
+            let previous = previous.map(|(_, track)| track);
+            let key = (
+                previous
+                    .as_ref()
+                    .map_or(std::ptr::null(), |track| Arc::as_ptr(track) as *const ()),
+                curve.curve_ptr(),
+                route.as_ref().map(|_| path.access().field_path()),
+            );
+            let track = if let Some(track) = shared.get(&key) {
+                track.clone()
             } else {
-                bone_tracks
-                    .tracks
-                    .insert(path.access().clone(), curve.into_track(clip_id));
-            }
+                let track = match (previous, &route) {
+                    (Some(mut track), _) => {
+                        make_track_mut(&mut track).add_field_curve(
+                            path.access(),
+                            clip_id,
+                            curve.as_ref(),
+                        )?;
+                        track
+                    }
+                    (None, Some(route)) => {
+                        let mut track = route.new_track();
+                        track.add_field_curve(path.access(), clip_id, curve.as_ref())?;
+                        Arc::from(track)
+                    }
+                    (None, None) => Arc::from(curve.into_track(clip_id)),
+                };
+                shared.insert(key, track.clone());
+                track
+            };
+            bone_tracks.tracks.insert(
+                self.paths.share_access(access).with_priority(priority),
+                track,
+            );
+            bone_tracks.add_priority(priority);
         }
 
         Ok(())
     }
 
+    /// Fuses the `translation`, `rotation`, and `scale` tracks of a bone's
+    /// [`Transform`] into a single track animating the entire [`Transform`].
+    /// Bones are only fused if every clip that animates one of the fields
+    /// animates all three.
+    pub(super) fn fuse_transforms(&mut self) {
+        let access = |field: &str| {
+            AccessPath::from_parts(
+                TypeId::of::<Transform>(),
+                std::any::type_name::<Transform>(),
+                FieldPath::parse(field).unwrap(),
+            )
+        };
+        let translation = access("translation");
+        let rotation = access("rotation");
+        let scale = access("scale");
+        let transform = AccessPath::from_parts(
+            TypeId::of::<Transform>(),
+            std::any::type_name::<Transform>(),
+            FieldPath::root(),
+        );
+
+        for bone in self.tracks.iter_mut() {
+            // Fusing would lose the order of fields given explicit priorities.
+            let prioritized = [&translation, &rotation, &scale].iter().any(|access| {
+                bone.tracks
+                    .get_key_value(*access)
+                    .map_or(false, |(key, _)| key.priority() != 0)
+            });
+            if prioritized || bone.tracks.contains_key(&transform) {
+                continue;
+            }
+            let tracks = &bone.tracks;
+            let track = |path: &AccessPath| tracks.get(path).map(|track| track.as_any());
+            let fused = match (track(&translation), track(&rotation), track(&scale)) {
+                (Some(translation), Some(rotation), Some(scale)) => {
+                    match (
+                        translation.downcast_ref(),
+                        rotation.downcast_ref(),
+                        scale.downcast_ref(),
+                    ) {
+                        (Some(translation), Some(rotation), Some(scale)) => {
+                            CurveTrack::fuse_transform(translation, rotation, scale)
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            if let Some(fused) = fused {
+                bone.tracks.remove(&translation);
+                bone.tracks.remove(&rotation);
+                bone.tracks.remove(&scale);
+                bone.tracks.insert(transform.clone(), Arc::new(fused));
+            }
+        }
+    }
+
     pub(super) fn get_bone(&self, id: BoneId) -> Option<&Bone> {
         self.tracks.get(id.0)
     }
@@ -138,90 +512,607 @@ impl GraphClips {
         self.tracks.iter_mut()
     }
 
+    pub(super) fn bone_id(&self, path: &EntityPath) -> Option<BoneId> {
+        self.paths.entity_id(path).map(|id| BoneId(id.index()))
+    }
+
     pub(super) fn find_bone(&self, path: &EntityPath) -> Option<&Bone> {
-        self.bones
-            .get(&path)
-            .copied()
-            .map(|bone_id| &self.tracks[bone_id.0])
+        self.bone_id(path).map(|bone_id| &self.tracks[bone_id.0])
     }
 
     pub(super) fn find_bone_mut(&mut self, path: &EntityPath) -> Option<&mut Bone> {
-        self.bones
-            .get(&path)
-            .copied()
+        self.bone_id(path)
             .map(|bone_id| &mut self.tracks[bone_id.0])
     }
+
+    pub(super) fn paths(&self) -> &PathInterner {
+        &self.paths
+    }
 }
 
-#[derive(Debug)]
+/// Gets a mutable reference to a track, cloning it first if it is shared.
+pub(super) fn make_track_mut(track: &mut Arc<dyn Track>) -> &mut dyn Track {
+    if Arc::get_mut(track).is_none() {
+        *track = Arc::from(track.clone_track());
+    }
+    Arc::get_mut(track).unwrap()
+}
+
+#[derive(Error, Debug, Clone)]
 pub enum TrackError {
-    IncorrectType,
+    #[error("expected a value of type '{expected}', found '{found}'")]
+    IncorrectType {
+        expected: &'static str,
+        found: String,
+    },
+    #[error("{}", list_conflicts(.conflicts))]
+    ConflictingType { conflicts: Vec<TypeConflict> },
+    #[error("the track does not exist")]
     MissingTrack,
 }
 
-/// A non-generic interface for all [`Track<T>`] that can be used to hide
-/// the internal type-specific implementation.
-pub(crate) trait Track: Any + Send + Sync + 'static {
+/// A property that a clip animates with a different value type than the
+/// track already animating it in a graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeConflict {
+    pub path: PropertyPath,
+    /// The value type the property is already animated as.
+    pub existing: TypeId,
+    pub existing_name: &'static str,
+    /// The value type of the rejected curve.
+    pub incoming: TypeId,
+    pub incoming_name: &'static str,
+}
+
+impl fmt::Display for TypeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' is already animated as '{}' and cannot be animated as '{}'",
+            self.path, self.existing_name, self.incoming_name
+        )
+    }
+}
+
+fn list_conflicts(conflicts: &[TypeConflict]) -> String {
+    conflicts
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl TrackError {
+    /// Creates an [`IncorrectType`](Self::IncorrectType) error for a track
+    /// expecting values of type `T`.
+    pub fn incorrect_type<T>(found: impl Into<String>) -> Self {
+        Self::IncorrectType {
+            expected: std::any::type_name::<T>(),
+            found: found.into(),
+        }
+    }
+}
+
+/// A non-generic interface for the curves animating a single property in an
+/// [`AnimationGraph`](crate::graph::AnimationGraph), and how they're blended.
+///
+/// Curves are usually blended with [`Animatable::blend`], but properties that
+/// need a different blend can be animated by a custom track. A track is
+/// created for a property by the [`ClipCurve::into_track`] of the first curve
+/// added for it, and every curve added afterwards from other clips is passed
+/// to [`add_generic_curve`](Self::add_generic_curve). Add custom curves to
+/// clips with
+/// [`AnimationClipBuilder::add_custom_curve`](crate::clip::AnimationClipBuilder::add_custom_curve).
+///
+/// Custom tracks are always written through reflection, after the
+/// [typed](crate::graph::typed) tracks.
+pub trait Track: Any + Send + Sync + 'static {
     fn value_type_id(&self) -> TypeId;
+    fn value_type_name(&self) -> &'static str;
     fn as_any(&self) -> &dyn Any;
     fn as_mut_any(&mut self) -> &mut dyn Any;
+    fn clone_track(&self) -> Box<dyn Track>;
+    /// Whether the track is applied by a typed application system instead of
+    /// through reflection. See [`typed`](crate::graph::typed).
+    fn is_typed(&self) -> bool {
+        false
+    }
+    /// Blends a typed track and writes it to the component of `entity`
+    /// immediately, outside of its typed application system. Does nothing
+    /// for other tracks, or if the entity doesn't have the component.
+    fn apply_typed(
+        &self,
+        _world: &mut World,
+        _entity: Entity,
+        _state: &GraphState,
+        _mask: &WriteMask,
+    ) {
+    }
+    /// Gets the value type of a field of the track's value, if the track
+    /// animates it. Only typed tracks animate the fields of their value
+    /// separately.
+    fn field_type(&self, field: &FieldPath) -> Option<(TypeId, &'static str)> {
+        field
+            .is_root()
+            .then(|| (self.value_type_id(), self.value_type_name()))
+    }
+    /// Creates an empty recording of values of the track's type, if the
+    /// track's property can be recorded by an
+    /// [`GraphRecorder`](crate::graph::recorder::GraphRecorder).
+    fn start_recording(&self) -> Option<Box<dyn PropertyRecording>> {
+        None
+    }
+    /// Measures the distance between two values of the track's type, used
+    /// for [`TrackStats::max_delta`]. See [`Animatable::distance`].
+    ///
+    /// [`TrackStats::max_delta`]: crate::graph::TrackStats::max_delta
+    fn value_distance(&self, _a: &dyn Reflect, _b: &dyn Reflect) -> Option<f32> {
+        None
+    }
+    /// Samples the curve of a single clip into a new boxed value, ignoring
+    /// the rest of the graph. Returns `None` if the clip doesn't animate the
+    /// track.
+    fn sample_clip_boxed(&self, clip_id: ClipId, time: f32) -> Option<Box<dyn Reflect>>;
+    /// Blends all of the values in the track into a new boxed value.
+    fn blend_boxed(&self, state: &GraphState) -> Box<dyn Reflect>;
+    /// Blends all of the values in the track into an existing boxed value,
+    /// replacing it if it is of a different type.
+    fn blend_into_boxed(&self, state: &GraphState, output: &mut Box<dyn Reflect>) {
+        *output = self.blend_boxed(state);
+    }
+    /// Adds the curve of another clip to the track. Returns an
+    /// [`IncorrectType`](TrackError::IncorrectType) error if the curve isn't
+    /// of a type the track can blend.
     fn add_generic_curve(
         &mut self,
         clip_id: ClipId,
         curve: &dyn ClipCurve,
     ) -> Result<(), TrackError>;
 
+    /// Adds a curve for the property at `access`, which is either the track's
+    /// property or, for typed tracks, one of its fields.
+    fn add_field_curve(
+        &mut self,
+        _access: &AccessPath,
+        clip_id: ClipId,
+        curve: &dyn ClipCurve,
+    ) -> Result<(), TrackError> {
+        self.add_generic_curve(clip_id, curve)
+    }
+
+    /// Whether a clip contributes a curve to the track.
+    fn animates_clip(&self, clip_id: ClipId) -> bool;
+
+    /// Removes the curves a clip contributes to the track, except for the
+    /// properties `keep` returns true for. `access` is the track's property,
+    /// and typed tracks also check each of their fields. Returns true if the
+    /// track no longer has any curves.
+    fn remove_clip_curves(
+        &mut self,
+        access: &AccessPath,
+        clip_id: ClipId,
+        keep: &dyn Fn(&AccessPath) -> bool,
+    ) -> bool;
+
+    /// Replaces the curve a clip contributes to the track with a constant
+    /// placeholder, releasing its keyframes while keeping the track. Tracks
+    /// that can't do so keep their curves. See
+    /// [`AnimationGraph::add_lazy_clip_asset`].
+    ///
+    /// [`AnimationGraph::add_lazy_clip_asset`]: crate::graph::AnimationGraph::add_lazy_clip_asset
+    fn evict_clip_curves(&mut self, _clip_id: ClipId) {}
+
+    /// Adds the estimated size in bytes of each of the track's curves to
+    /// `sizes`, keyed by the address of the curve, so that curves shared
+    /// between tracks are only counted once. Tracks that can't estimate the
+    /// size of their curves add nothing.
+    fn curve_sizes(&self, _sizes: &mut HashMap<*const (), usize>) {}
+
+    /// Adds a constant snapshot of a value as the input for a given pose.
+    fn add_snapshot(&mut self, pose_id: ClipId, value: &dyn Reflect) -> Result<(), TrackError>;
+
+    /// Sets the rest value that the track blends towards when the clips
+    /// animating it have a total weight below 1.
+    fn set_rest_value(&mut self, value: &dyn Reflect) -> Result<(), TrackError>;
+
+    /// Removes the track's rest value, if any.
+    fn clear_rest_value(&mut self);
+
     /// Blends all of the values in the track and then postprocesses the
-    /// result using the provided [`World`] reference.
+    /// result using the provided resources. See [`Animatable::post_process`].
     ///
-    /// # Safety
-    /// The provided [`World`] cannot have be mutated on a different thread.
-    unsafe fn blend_via_reflect(
+    /// Returns whether the output was written, as values equal to the output
+    /// are skipped. If `output` isn't of the track's type, it must be left
+    /// untouched and an [`IncorrectType`](TrackError::IncorrectType) error
+    /// returned. The blended value must match [`blend_boxed`](Self::blend_boxed),
+    /// as graphs sharing their clips may be blended ahead of time and written
+    /// with [`write_blended`](Self::write_blended) instead.
+    fn blend_via_reflect(
         &self,
         state: &GraphState,
         output: &mut dyn Reflect,
-        world: &World,
-    ) -> Result<(), TrackError>;
+        resources: WorldResources<'_>,
+    ) -> Result<bool, TrackError>;
+
+    /// Writes a value blended ahead of time with
+    /// [`blend_boxed`](Self::blend_boxed), in the same way as
+    /// [`blend_via_reflect`](Self::blend_via_reflect).
+    fn write_blended(
+        &self,
+        value: &dyn Reflect,
+        output: &mut dyn Reflect,
+        resources: WorldResources<'_>,
+    ) -> Result<bool, TrackError>;
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The ID of a clip within a graph, returned by
+/// [`AnimationGraph::clip_id`](crate::graph::AnimationGraph::clip_id). Like
+/// [`NodeId`](crate::graph::NodeId)s, clip IDs are assigned in order and can
+/// be serialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct ClipId(pub u16);
 
+impl_reflect_value!(ClipId(Hash, PartialEq, Serialize, Deserialize));
+
+#[derive(Clone)]
 pub(crate) struct CurveTrack<T: Animatable> {
-    curves: Vec<Option<Arc<dyn Curve<T>>>>,
+    // Sorted by ClipId. Stored sparsely, as most clips in a graph only animate
+    // a small subset of its properties.
+    curves: SmallVec<[(ClipId, Arc<dyn Curve<T>>); 1]>,
+    // Receives the weight not covered by the clips animating the track. If
+    // not set, the remainder goes to the type's rest value. See
+    // `Animatable::blend`.
+    rest: Option<T>,
 }
 
 impl<T: Animatable> CurveTrack<T> {
     pub(crate) fn new(curve: Arc<dyn Curve<T>>, clip_id: ClipId) -> Self {
-        let index = clip_id.0 as usize;
-        let mut curves = Vec::with_capacity(index);
-        curves.resize_with(index + 1, || None);
-        curves[index] = Some(curve);
-        Self { curves }
+        let mut curves = SmallVec::new();
+        curves.push((clip_id, curve));
+        Self { curves, rest: None }
     }
 
     pub(crate) fn add_curve(&mut self, clip_id: ClipId, curve: Arc<dyn Curve<T>>) {
-        let idx = clip_id.0 as usize;
-        if idx >= self.curves.len() {
-            self.curves.resize_with(idx + 1, || None);
+        match self.curves.binary_search_by_key(&clip_id, |(id, _)| *id) {
+            Ok(idx) => self.curves[idx].1 = curve,
+            Err(idx) => self.curves.insert(idx, (clip_id, curve)),
         }
-        self.curves[idx] = Some(curve);
+    }
+
+    /// Removes the curve a clip contributes to the track, if any.
+    pub(crate) fn remove_curve(&mut self, clip_id: ClipId) {
+        if let Ok(idx) = self.curves.binary_search_by_key(&clip_id, |(id, _)| *id) {
+            self.curves.remove(idx);
+        }
+    }
+
+    /// Replaces the curve a clip contributes to the track, if any, with its
+    /// first value.
+    pub(crate) fn evict_curve(&mut self, clip_id: ClipId) {
+        if let Ok(idx) = self.curves.binary_search_by_key(&clip_id, |(id, _)| *id) {
+            let curve = &mut self.curves[idx].1;
+            let placeholder = curve.sample(0.0);
+            *curve = Arc::new(CurveFixed::from_constant(placeholder));
+        }
+    }
+
+    /// The estimated size of each of the track's curves: their keyframes,
+    /// and the time of each keyframe.
+    pub(crate) fn curve_sizes(&self, sizes: &mut HashMap<*const (), usize>) {
+        let keyframe_size = std::mem::size_of::<T>() + std::mem::size_of::<f32>();
+        for (_, curve) in self.curves.iter() {
+            let size = curve.keyframe_count() * keyframe_size;
+            sizes.insert(Arc::as_ptr(curve) as *const (), size);
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.curves.is_empty()
+    }
+
+    pub(crate) fn set_rest(&mut self, rest: Option<T>) {
+        self.rest = rest;
+    }
+
+    /// Gets the curve a clip contributes to the track, if any.
+    pub(crate) fn clip_curve(&self, clip_id: ClipId) -> Option<&Arc<dyn Curve<T>>> {
+        self.curves
+            .binary_search_by_key(&clip_id, |(id, _)| *id)
+            .ok()
+            .map(|idx| &self.curves[idx].1)
     }
 
     pub(crate) fn sample_and_blend(&self, state: &GraphState) -> T {
-        let inputs = state
-            .clips
-            .iter()
-            .zip(self.curves.iter())
-            .filter(|(clip, curve)| clip.weight != 0.0 && curve.is_some())
-            .map(|(clip, curve)| BlendInput {
-                weight: clip.weight,
-                value: curve.as_ref().unwrap().sample(clip.time),
-                // TODO: Expose this at the node level
+        self.sample_and_blend_with(state, None)
+    }
+
+    /// Checks if both tracks are built from the same curves for the same
+    /// clips, in which case their curves can be sampled together.
+    pub(crate) fn shares_curves(&self, other: &Self) -> bool {
+        self.curves.len() == other.curves.len()
+            && self.curves.iter().zip(other.curves.iter()).all(|(a, b)| {
+                a.0 == b.0 && Arc::as_ptr(&a.1) as *const () == Arc::as_ptr(&b.1) as *const ()
+            })
+    }
+
+    /// Samples and blends several tracks for their graph states at once,
+    /// producing the same values as calling [`sample_and_blend`] on each of
+    /// them. The curve of each clip is sampled for every state that weighs
+    /// the clip in a single [`Curve::sample_batch`] call, so every track must
+    /// [share its curves](Self::shares_curves) with the first one.
+    ///
+    /// [`sample_and_blend`]: Self::sample_and_blend
+    ///
+    /// # Panics
+    /// This will panic if `batch` and `out` have different lengths.
+    pub(crate) fn sample_and_blend_batch(
+        batch: &[(&Self, &GraphState)],
+        out: &mut [T],
+        buffers: &mut BatchBuffers<T>,
+    ) {
+        assert_eq!(batch.len(), out.len(), "mismatched batch lengths");
+        let first = match batch.first() {
+            Some((track, _)) => *track,
+            None => return,
+        };
+        debug_assert!(batch.iter().all(|(track, _)| first.shares_curves(track)));
+        let count = batch.len();
+        buffers.slots.clear();
+        buffers.slots.resize(count * first.curves.len(), None);
+        let mut len = 0;
+        for (curve_idx, (clip_id, curve)) in first.curves.iter().enumerate() {
+            buffers.times.clear();
+            for (idx, (_, state)) in batch.iter().enumerate() {
+                match state.clips.get(clip_id.0 as usize) {
+                    Some(clip) if clip.weight != 0.0 => {
+                        buffers.slots[curve_idx * count + idx] = Some(len + buffers.times.len());
+                        buffers.times.push(clip.sample_time());
+                    }
+                    _ => {}
+                }
+            }
+            let end = len + buffers.times.len();
+            if buffers.values.len() < end {
+                // The batch is written over initialized values. `out` is the
+                // only source of them until the buffer has grown.
+                buffers.values.resize(end, out[0].clone());
+            }
+            curve.sample_batch(&buffers.times, &mut buffers.values[len..end]);
+            len = end;
+        }
+        for (idx, ((track, state), out)) in batch.iter().zip(out.iter_mut()).enumerate() {
+            let samples = BatchSamples {
+                values: &buffers.values,
+                slots: &buffers.slots,
+                count,
+                idx,
+            };
+            *out = track.sample_and_blend_with(state, Some(samples));
+        }
+    }
+
+    fn sample_and_blend_with(&self, state: &GraphState, samples: Option<BatchSamples<T>>) -> T {
+        let mode = state.transform_blend_mode;
+        let current = match self.sample_single_clip(state, samples.as_ref()) {
+            Some(value) => value,
+            None => self.blend_clips(&state.clips, mode, samples),
+        };
+        let value = match &state.previous {
+            // Only the current clip states are sampled in batches.
+            Some((previous, alpha)) => {
+                T::interpolate(&self.blend_clips(previous, mode, None), &current, *alpha)
+            }
+            None => current,
+        };
+        debug_assert!(
+            value.is_finite(),
+            "Blending a track of {} produced a non-finite value.",
+            std::any::type_name::<T>(),
+        );
+        value
+    }
+
+    /// Samples the curve of the clip the graph plays on its own, skipping the
+    /// blend. Returns `None` if there's no such clip, or if the track doesn't
+    /// animate it.
+    fn sample_single_clip(
+        &self,
+        state: &GraphState,
+        samples: Option<&BatchSamples<T>>,
+    ) -> Option<T> {
+        let clip_id = state.single_clip?;
+        let curve_idx = self.curves.iter().position(|(id, _)| *id == clip_id)?;
+        Some(match samples.and_then(|samples| samples.get(curve_idx)) {
+            Some(value) => value.clone(),
+            None => {
+                let clip = &state.clips[clip_id.0 as usize];
+                self.curves[curve_idx].1.sample(clip.sample_time())
+            }
+        })
+    }
+
+    fn blend_clips(
+        &self,
+        clips: &[ClipState],
+        mode: TransformBlendMode,
+        samples: Option<BatchSamples<T>>,
+    ) -> T {
+        T::blend_with_mode(self.blend_inputs(clips, samples), mode)
+    }
+
+    fn blend_inputs<'a>(
+        &'a self,
+        clips: &'a [ClipState],
+        samples: Option<BatchSamples<'a, T>>,
+    ) -> impl Iterator<Item = BlendInput<T>> + 'a {
+        let rest = self.rest.as_ref().and_then(|rest| {
+            let weight: f32 = self
+                .curves
+                .iter()
+                .filter_map(|(clip_id, _)| clips.get(clip_id.0 as usize))
+                .filter(|clip| !clip.additive)
+                .map(|clip| clip.weight)
+                .sum();
+            (weight < 1.0).then(|| BlendInput {
+                weight: 1.0 - weight,
+                value: rest.clone(),
                 additive: false,
-            });
+            })
+        });
+        self.curves
+            .iter()
+            .enumerate()
+            .filter_map(move |(curve_idx, (clip_id, curve))| {
+                let clip = clips.get(clip_id.0 as usize)?;
+                (clip.weight != 0.0).then(|| BlendInput {
+                    weight: clip.weight,
+                    value: match samples.as_ref().and_then(|samples| samples.get(curve_idx)) {
+                        Some(value) => value.clone(),
+                        None => curve.sample(clip.sample_time()),
+                    },
+                    additive: clip.additive,
+                })
+            })
+            .chain(rest)
+    }
+}
+
+/// Scratch buffers for sampling tracks in batches with
+/// [`AnimationGraph::sample_property_batch_with`]. Keeping them between
+/// batches avoids reallocating them each time.
+///
+/// [`AnimationGraph::sample_property_batch_with`]: crate::graph::AnimationGraph::sample_property_batch_with
+pub struct BatchBuffers<T> {
+    /// The times to sample the current curve at.
+    times: Vec<f32>,
+    /// The samples of every curve in the batch, grouped by curve.
+    values: Vec<T>,
+    /// The index in `values` of the sample of each curve for each state in
+    /// the batch, grouped by curve. Clips without a weight aren't sampled.
+    slots: Vec<Option<usize>>,
+}
+
+impl<T> BatchBuffers<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T> Default for BatchBuffers<T> {
+    fn default() -> Self {
+        Self {
+            times: Vec::new(),
+            values: Vec::new(),
+            slots: Vec::new(),
+        }
+    }
+}
+
+/// Curve values sampled ahead of time by
+/// [`CurveTrack::sample_and_blend_batch`] for one of the states in the batch.
+struct BatchSamples<'a, T> {
+    values: &'a [T],
+    slots: &'a [Option<usize>],
+    /// The number of states in the batch.
+    count: usize,
+    /// The index of the state in the batch.
+    idx: usize,
+}
+
+impl<'a, T> BatchSamples<'a, T> {
+    /// Gets the sample of a curve, if its clip has a weight.
+    fn get(&self, curve_idx: usize) -> Option<&'a T> {
+        let slot = self.slots[curve_idx * self.count + self.idx]?;
+        Some(&self.values[slot])
+    }
+}
+
+impl CurveTrack<Transform> {
+    /// Combines the curves for each field of a [`Transform`]. Returns `None` if
+    /// any clip doesn't have curves for all three fields, or if only some of
+    /// the fields have rest values.
+    fn fuse_transform(
+        translation: &CurveTrack<Vec3>,
+        rotation: &CurveTrack<Quat>,
+        scale: &CurveTrack<Vec3>,
+    ) -> Option<Self> {
+        if translation.curves.len() != rotation.curves.len()
+            || translation.curves.len() != scale.curves.len()
+        {
+            return None;
+        }
+        let rest = match (&translation.rest, &rotation.rest, &scale.rest) {
+            (Some(translation), Some(rotation), Some(scale)) => Some(Transform {
+                translation: *translation,
+                rotation: *rotation,
+                scale: *scale,
+            }),
+            (None, None, None) => None,
+            _ => return None,
+        };
+        let curves = translation
+            .curves
+            .iter()
+            .zip(rotation.curves.iter())
+            .zip(scale.curves.iter())
+            .map(
+                |(((clip_id, translation), (rotation_id, rotation)), (scale_id, scale))| {
+                    if clip_id != rotation_id || clip_id != scale_id {
+                        return None;
+                    }
+                    let curve: Arc<dyn Curve<Transform>> = Arc::new(FusedTransformCurve {
+                        translation: translation.clone(),
+                        rotation: rotation.clone(),
+                        scale: scale.clone(),
+                    });
+                    Some((*clip_id, curve))
+                },
+            )
+            .collect::<Option<_>>()?;
+        Some(Self { curves, rest })
+    }
+}
+
+/// A [`Transform`] curve assembled from curves for each of its fields.
+struct FusedTransformCurve {
+    translation: Arc<dyn Curve<Vec3>>,
+    rotation: Arc<dyn Curve<Quat>>,
+    scale: Arc<dyn Curve<Vec3>>,
+}
+
+impl Curve<Transform> for FusedTransformCurve {
+    fn duration(&self) -> f32 {
+        self.translation
+            .duration()
+            .max(self.rotation.duration())
+            .max(self.scale.duration())
+    }
 
-        T::blend(inputs)
+    fn time_offset(&self) -> f32 {
+        self.translation
+            .time_offset()
+            .min(self.rotation.time_offset())
+            .min(self.scale.time_offset())
+    }
+
+    fn keyframe_count(&self) -> usize {
+        self.translation
+            .keyframe_count()
+            .max(self.rotation.keyframe_count())
+            .max(self.scale.keyframe_count())
+    }
+
+    fn sample(&self, time: f32) -> Transform {
+        Transform {
+            translation: self.translation.sample(time),
+            rotation: self.rotation.sample(time),
+            scale: self.scale.sample(time),
+        }
+    }
+
+    fn sample_with_cursor(&self, _: KeyframeIndex, time: f32) -> (KeyframeIndex, Transform) {
+        (0, self.sample(time))
     }
 }
 
@@ -229,12 +1120,38 @@ impl<T: Animatable> Track for CurveTrack<T> {
     fn value_type_id(&self) -> TypeId {
         TypeId::of::<T>()
     }
+    fn value_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
     fn as_any(&self) -> &dyn Any {
         self as &_
     }
     fn as_mut_any(&mut self) -> &mut dyn Any {
         self as &mut _
     }
+    fn clone_track(&self) -> Box<dyn Track> {
+        Box::new(self.clone())
+    }
+    fn start_recording(&self) -> Option<Box<dyn PropertyRecording>> {
+        Some(Box::new(RecordedValues::<T>::default()))
+    }
+    fn value_distance(&self, a: &dyn Reflect, b: &dyn Reflect) -> Option<f32> {
+        Some(T::distance(a.downcast_ref()?, b.downcast_ref()?))
+    }
+    fn sample_clip_boxed(&self, clip_id: ClipId, time: f32) -> Option<Box<dyn Reflect>> {
+        let value = self.clip_curve(clip_id)?.sample(time);
+        Some(Box::new(value))
+    }
+    fn blend_boxed(&self, state: &GraphState) -> Box<dyn Reflect> {
+        Box::new(self.sample_and_blend(state))
+    }
+    fn blend_into_boxed(&self, state: &GraphState, output: &mut Box<dyn Reflect>) {
+        let value = self.sample_and_blend(state);
+        match output.downcast_mut::<T>() {
+            Some(output) => *output = value,
+            None => *output = Box::new(value),
+        }
+    }
 
     fn add_generic_curve(
         &mut self,
@@ -243,38 +1160,308 @@ impl<T: Animatable> Track for CurveTrack<T> {
     ) -> Result<(), TrackError> {
         match curve.as_any().downcast_ref::<CurveWrapper<T>>() {
             Some(curve) => Ok(self.add_curve(clip_id, curve.0.clone())),
-            None => Err(TrackError::IncorrectType),
+            None => Err(TrackError::incorrect_type::<T>(curve.value_type_name())),
         }
     }
 
-    unsafe fn blend_via_reflect(
+    fn animates_clip(&self, clip_id: ClipId) -> bool {
+        self.clip_curve(clip_id).is_some()
+    }
+
+    fn remove_clip_curves(
+        &mut self,
+        access: &AccessPath,
+        clip_id: ClipId,
+        keep: &dyn Fn(&AccessPath) -> bool,
+    ) -> bool {
+        if !keep(access) {
+            self.remove_curve(clip_id);
+        }
+        self.is_empty()
+    }
+
+    fn evict_clip_curves(&mut self, clip_id: ClipId) {
+        self.evict_curve(clip_id);
+    }
+
+    fn curve_sizes(&self, sizes: &mut HashMap<*const (), usize>) {
+        CurveTrack::curve_sizes(self, sizes);
+    }
+
+    fn add_snapshot(&mut self, pose_id: ClipId, value: &dyn Reflect) -> Result<(), TrackError> {
+        let value = value
+            .downcast_ref::<T>()
+            .ok_or_else(|| TrackError::incorrect_type::<T>(value.type_name()))?
+            .clone();
+        self.add_curve(pose_id, Arc::new(CurveFixed::from_constant(value)));
+        Ok(())
+    }
+
+    fn set_rest_value(&mut self, value: &dyn Reflect) -> Result<(), TrackError> {
+        let value = value
+            .downcast_ref::<T>()
+            .ok_or_else(|| TrackError::incorrect_type::<T>(value.type_name()))?;
+        self.rest = Some(value.clone());
+        Ok(())
+    }
+
+    fn clear_rest_value(&mut self) {
+        self.rest = None;
+    }
+
+    fn blend_via_reflect(
         &self,
         state: &GraphState,
         output: &mut dyn Reflect,
-        world: &World,
-    ) -> Result<(), TrackError> {
-        if output.any().type_id() == TypeId::of::<T>() {
-            let mut value = self.sample_and_blend(state);
-            if !matches!(value.reflect_partial_eq(output), Some(true)) {
-                // SAFE: Only read-only access to the World's resources is
-                // used here. No mutation nor reading of component/entity
-                // data is done, as required by Animatable::post_process.
-                value.post_process(world);
-                output.apply(&value);
-            }
-            Ok(())
-        } else {
-            Err(TrackError::IncorrectType)
+        resources: WorldResources<'_>,
+    ) -> Result<bool, TrackError> {
+        if !output.any().is::<T>() {
+            return Err(TrackError::incorrect_type::<T>(output.type_name()));
         }
+        let output = output.downcast_mut::<T>().unwrap();
+        Ok(write_value(
+            Cow::Owned(self.sample_and_blend(state)),
+            output,
+            resources,
+        ))
     }
+
+    fn write_blended(
+        &self,
+        value: &dyn Reflect,
+        output: &mut dyn Reflect,
+        resources: WorldResources<'_>,
+    ) -> Result<bool, TrackError> {
+        let value = value
+            .downcast_ref::<T>()
+            .ok_or_else(|| TrackError::incorrect_type::<T>(value.type_name()))?;
+        if !output.any().is::<T>() {
+            return Err(TrackError::incorrect_type::<T>(output.type_name()));
+        }
+        let output = output.downcast_mut::<T>().unwrap();
+        Ok(write_value(Cow::Borrowed(value), output, resources))
+    }
+}
+
+/// Writes a blended value to a property, unless it is unchanged. Returns
+/// whether the value was written. Borrowed values are only cloned if they
+/// are written.
+pub(crate) fn write_value<T: Animatable>(
+    value: Cow<'_, T>,
+    output: &mut T,
+    resources: WorldResources<'_>,
+) -> bool {
+    let unchanged = match value.reflect_partial_eq(output) {
+        Some(eq) => eq,
+        // Some types, like floats, can't be compared through reflection.
+        None => T::distance(&value, output) == 0.0,
+    };
+    if unchanged {
+        return false;
+    }
+    let mut value = value.into_owned();
+    value.post_process(resources);
+    // Assign rather than apply via reflection, as reflection may skip
+    // non-reflected state (i.e. the strong reference of a Handle).
+    *output = value;
+    true
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use bevy_core::Name;
+    use bevy_ecs::prelude::World;
     use bevy_math::*;
+    use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 
     assert_impl_all!(GraphClips: Send, Sync);
     assert_impl_all!(TrackError: Send, Sync);
     assert_impl_all!(dyn Track: Send, Sync);
+
+    struct Scale(f32);
+
+    #[derive(Reflect, Clone, Default, Debug, PartialEq)]
+    struct Scaled {
+        value: f32,
+    }
+
+    impl Animatable for Scaled {
+        fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
+            Self {
+                value: f32::interpolate(&a.value, &b.value, t),
+            }
+        }
+
+        fn blend(inputs: impl Iterator<Item = BlendInput<Self>>) -> Self {
+            Self {
+                value: f32::blend(inputs.map(|input| BlendInput {
+                    weight: input.weight,
+                    value: input.value.value,
+                    additive: input.additive,
+                })),
+            }
+        }
+
+        fn post_process(&mut self, resources: WorldResources<'_>) {
+            self.value *= resources.get::<Scale>().unwrap().0;
+        }
+    }
+
+    #[test]
+    pub fn test_post_process_only_runs_on_application() {
+        let mut world = World::new();
+        world.insert_resource(Scale(3.0));
+        let curve = CurveFixed::from_constant(Scaled { value: 2.0 });
+        let track = CurveTrack::<Scaled>::new(Arc::new(curve), ClipId(0));
+        let mut state = GraphState::default();
+        let clip = state.add_clip(0.0).unwrap();
+        state.add_weight(clip, 1.0);
+
+        assert_eq!(track.sample_and_blend(&state), Scaled { value: 2.0 });
+
+        let mut output = Scaled::default();
+        let result = track.blend_via_reflect(&state, &mut output, WorldResources::new(&world));
+        assert!(result.is_ok());
+        assert_eq!(output, Scaled { value: 6.0 });
+    }
+
+    #[test]
+    pub fn test_dual_quaternion_blend_follows_arc() {
+        // Two poses of a point orbiting the origin at a radius of 1, 180 degrees apart.
+        let a = Transform::from_xyz(1.0, 0.0, 0.0);
+        let b = Transform {
+            translation: Vec3::new(-1.0, 0.0, 0.0),
+            rotation: Quat::from_rotation_z(std::f32::consts::PI),
+            scale: Vec3::splat(3.0),
+        };
+        let mut track =
+            CurveTrack::<Transform>::new(Arc::new(CurveFixed::from_constant(a)), ClipId(0));
+        track.add_curve(ClipId(1), Arc::new(CurveFixed::from_constant(b)));
+        let mut state = GraphState::default();
+        let first = state.add_clip(0.0).unwrap();
+        let second = state.add_clip(0.0).unwrap();
+        state.add_weight(first, 0.5);
+        state.add_weight(second, 0.5);
+
+        // Blending componentwise cuts straight through the origin.
+        let linear = Transform::interpolate(&a, &b, 0.5);
+        assert!(linear.translation.length() < 1e-5);
+
+        state.transform_blend_mode = TransformBlendMode::DualQuaternion;
+        let blended = track.sample_and_blend(&state);
+        // Exactly 180 degrees apart, either direction around the arc is valid,
+        // but the translation must follow the rotation.
+        assert!((blended.translation.y.abs() - 1.0).abs() < 1e-4);
+        let rotated = blended.rotation * Vec3::X;
+        assert!((blended.translation - rotated).length() < 1e-4);
+        assert!((blended.scale - Vec3::splat(2.0)).length() < 1e-5);
+
+        // Every blend along the way stays on the arc.
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            let mut state = GraphState {
+                transform_blend_mode: TransformBlendMode::DualQuaternion,
+                ..Default::default()
+            };
+            let first = state.add_clip(0.0).unwrap();
+            let second = state.add_clip(0.0).unwrap();
+            state.add_weight(first, 1.0 - t);
+            state.add_weight(second, t);
+            let blended = track.sample_and_blend(&state);
+            assert!((blended.translation.length() - 1.0).abs() < 1e-4);
+            let rotated = blended.rotation * Vec3::X;
+            assert!((blended.translation - rotated).length() < 1e-4);
+            assert!(blended.translation.z.abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    pub fn test_tracks_only_store_animated_clips() {
+        const CLIPS: u16 = 64;
+        let access = AccessPath::from_parts(
+            TypeId::of::<Transform>(),
+            std::any::type_name::<Transform>(),
+            FieldPath::parse("translation").unwrap(),
+        );
+        let mut clips = GraphClips::default();
+        for idx in 0..CLIPS {
+            let entity = EntityPath::from_parts(vec![Name::new(format!("bone{}", idx))]);
+            let clip = AnimationClip::builder()
+                .add_curve(
+                    PropertyPath::from_parts(entity, access.clone()),
+                    CurveFixed::from_constant(Vec3::splat(idx as f32)),
+                )
+                .build();
+            clips.add_clip(ClipId(idx), &clip).unwrap();
+        }
+
+        assert_eq!(clips.bones().count(), CLIPS as usize);
+        for bone in clips.bones() {
+            let track = bone.tracks[&access]
+                .as_any()
+                .downcast_ref::<CurveTrack<Vec3>>()
+                .unwrap();
+            // Only the clip animating the bone has a curve stored, and it's
+            // stored inline without a separate allocation.
+            assert_eq!(track.curves.len(), 1);
+            assert!(!track.curves.spilled());
+            assert_eq!(track.curves[0].0, ClipId(bone.id().0 as u16));
+        }
+    }
+
+    #[test]
+    pub fn test_sparse_blend_matches_dense_blend() {
+        const CLIPS: usize = 16;
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..32 {
+            let mut state = GraphState::default();
+            let mut dense: Vec<Option<Arc<dyn Curve<Vec3>>>> = Vec::new();
+            let mut track: Option<CurveTrack<Vec3>> = None;
+            // Add the curves out of order to exercise the sorted insertion.
+            let mut order: Vec<_> = (0..CLIPS).collect();
+            order.shuffle(&mut rng);
+            for _ in 0..CLIPS {
+                let clip_id = state.add_clip(1.0).unwrap();
+                state.set_time(clip_id, rng.gen_range(0.0..1.0));
+                if rng.gen_bool(0.5) {
+                    state.add_weight(clip_id, rng.gen_range(0.0..1.0));
+                }
+                dense.push(None);
+            }
+            for idx in order {
+                if rng.gen_bool(0.3) {
+                    continue;
+                }
+                let keyframes = (0..4)
+                    .map(|_| Vec3::new(rng.gen(), rng.gen(), rng.gen()))
+                    .collect();
+                let curve: Arc<dyn Curve<Vec3>> =
+                    Arc::new(CurveFixed::from_keyframes(3.0, keyframes));
+                dense[idx] = Some(curve.clone());
+                match track.as_mut() {
+                    Some(track) => track.add_curve(ClipId(idx as u16), curve),
+                    None => track = Some(CurveTrack::new(curve, ClipId(idx as u16))),
+                }
+            }
+            let track = match track {
+                Some(track) => track,
+                None => continue,
+            };
+
+            let expected = Vec3::blend(
+                state
+                    .clips
+                    .iter()
+                    .zip(dense.iter())
+                    .filter(|(clip, curve)| clip.weight != 0.0 && curve.is_some())
+                    .map(|(clip, curve)| BlendInput {
+                        weight: clip.weight,
+                        value: curve.as_ref().unwrap().sample(clip.sample_time()),
+                        additive: clip.additive,
+                    }),
+            );
+            assert_eq!(track.sample_and_blend(&state), expected);
+        }
+    }
 }
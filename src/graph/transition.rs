@@ -0,0 +1,231 @@
+use crate::graph::{
+    application::BoneBinding, hierarchy::bind_graph, make_track_mut, AnimationGraph,
+    AnimationGraphError, ClipId, Node, NodeId,
+};
+use bevy_ecs::prelude::*;
+use bevy_log::warn;
+use smallvec::SmallVec;
+
+/// A crossfade from a captured pose into the rest of a graph. See
+/// [`AnimationGraph::fade_from_pose`].
+#[derive(Debug, Clone)]
+pub(super) struct PoseFade {
+    pose_id: ClipId,
+    duration: f32,
+    elapsed: f32,
+}
+
+impl PoseFade {
+    pub fn pose_id(&self) -> ClipId {
+        self.pose_id
+    }
+
+    /// How far along the fade is, from 0 to 1. The pose is blended over the
+    /// graph with the remaining weight.
+    pub fn progress(&self) -> f32 {
+        if self.duration > 0.0 {
+            (self.elapsed / self.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Replaces the [`AnimationGraph`] of an entity with a new graph, crossfading
+/// from the entity's current pose into the new graph over `duration` seconds
+/// of the new graph's time.
+///
+/// When the component is added, [`transition_graphs_system`] binds the new
+/// graph to the hierarchy, captures the current pose into it (see
+/// [`AnimationGraph::capture_pose_from`]), and installs it in place of the
+/// old graph in the same frame. The component is removed once the crossfade
+/// finishes.
+///
+/// ```rust,ignore
+/// commands
+///     .entity(rider)
+///     .insert(AnimationGraphTransition::new(mounted_graph, 0.3));
+/// ```
+#[derive(Component)]
+pub struct AnimationGraphTransition {
+    // Taken once the new graph is installed.
+    new_graph: Option<AnimationGraph>,
+    duration: f32,
+}
+
+impl AnimationGraphTransition {
+    pub fn new(new_graph: AnimationGraph, duration: f32) -> Self {
+        Self {
+            new_graph: Some(new_graph),
+            duration,
+        }
+    }
+
+    /// The duration of the crossfade, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Whether the new graph has been installed and is fading in.
+    pub fn is_started(&self) -> bool {
+        self.new_graph.is_none()
+    }
+}
+
+impl AnimationGraph {
+    /// Captures the current pose into the graph's snapshot node, like
+    /// [`capture_pose`](Self::capture_pose), for a graph that is replacing
+    /// `previous`. The graph must already be bound.
+    ///
+    /// Properties animated by both graphs are captured from the last
+    /// evaluation of `previous`. Properties only this graph animates, or that
+    /// `previous` doesn't write, are read from their bound entities instead.
+    pub fn capture_pose_from(
+        &mut self,
+        previous: &AnimationGraph,
+        world: &World,
+    ) -> Result<NodeId, AnimationGraphError> {
+        let (node, pose_id) = self.reset_snapshot()?;
+        for bone in self.clips.bones_mut() {
+            if bone.entity().is_none() {
+                continue;
+            }
+            let previous_bone = match previous.find_bone(&bone.path) {
+                Some(previous_bone) if previous_bone.entity().is_some() => previous_bone,
+                _ => continue,
+            };
+            for (property, track) in bone.tracks.iter_mut() {
+                if !previous.writes(previous_bone, property) {
+                    continue;
+                }
+                let value = match previous_bone.tracks.get(property) {
+                    Some(previous_track)
+                        if previous_track.value_type_id() == track.value_type_id() =>
+                    {
+                        previous_track.blend_boxed(&previous.state)
+                    }
+                    _ => continue,
+                };
+                let _ = make_track_mut(track).add_snapshot(pose_id, value.as_ref());
+            }
+        }
+        self.for_each_bound_value(world, |track, value| {
+            if !track.animates_clip(pose_id) {
+                // A type mismatch here means the property is not animatable as
+                // the track's type, so it's skipped.
+                let _ = make_track_mut(track).add_snapshot(pose_id, value);
+            }
+        });
+        Ok(node)
+    }
+
+    /// Crossfades from a snapshot node into the rest of the graph over
+    /// `duration` seconds of the graph's time, replacing any ongoing fade.
+    ///
+    /// The snapshot node doesn't need to be an input of any other node. While
+    /// fading, the snapshot is blended over the rest of the graph with a
+    /// weight of `1 - t`, where `t` goes from 0 to 1 over the fade.
+    pub fn fade_from_pose(
+        &mut self,
+        snapshot: NodeId,
+        duration: f32,
+    ) -> Result<(), AnimationGraphError> {
+        match self.nodes.get(snapshot) {
+            Some(Node::Snapshot { pose_id }) => {
+                self.pose_fade = Some(PoseFade {
+                    pose_id: *pose_id,
+                    duration,
+                    elapsed: 0.0,
+                });
+                Ok(())
+            }
+            Some(_) => Err(AnimationGraphError::NotSnapshotNode(snapshot)),
+            None => Err(AnimationGraphError::NodeNotFound(snapshot)),
+        }
+    }
+
+    /// Whether the graph is crossfading from a pose. See
+    /// [`fade_from_pose`](Self::fade_from_pose).
+    pub fn is_fading(&self) -> bool {
+        self.pose_fade.is_some()
+    }
+
+    /// Advances the fade from a pose, if any, ending it once its duration has
+    /// elapsed.
+    pub(super) fn advance_pose_fade(&mut self, delta_time: f32) {
+        if let Some(fade) = self.pose_fade.as_mut() {
+            fade.elapsed += delta_time.abs();
+            if fade.elapsed >= fade.duration {
+                self.pose_fade = None;
+            }
+        }
+    }
+}
+
+/// Starts and finishes [`AnimationGraphTransition`]s.
+///
+/// This MUST be added as an exclusive system, and should run before the graphs
+/// are bound and evaluated, so that new graphs are applied in the same frame
+/// they are installed.
+pub fn transition_graphs_system(world: &mut World) {
+    let mut transitions = world.query::<(Entity, &AnimationGraphTransition)>();
+    let pending: SmallVec<[(Entity, bool); 4]> = transitions
+        .iter(world)
+        .map(|(entity, transition)| (entity, transition.is_started()))
+        .collect();
+    for (entity, started) in pending {
+        if started {
+            let fading = matches!(
+                world.get::<AnimationGraph>(entity),
+                Some(graph) if graph.is_fading()
+            );
+            if !fading {
+                world
+                    .entity_mut(entity)
+                    .remove::<AnimationGraphTransition>();
+            }
+            continue;
+        }
+        let mut transition = world.get_mut::<AnimationGraphTransition>(entity).unwrap();
+        let duration = transition.duration;
+        let graph = transition.new_graph.take();
+        if let Some(graph) = graph {
+            start_transition(world, entity, graph, duration);
+        }
+    }
+}
+
+fn start_transition(world: &mut World, root: Entity, mut graph: AnimationGraph, duration: f32) {
+    // Bind before capturing, so that the pose is captured for every bone and
+    // the new graph is applied without waiting a frame to be bound.
+    bind_graph(world, root, &mut graph);
+    let previous = world.get::<AnimationGraph>(root);
+    let snapshot = match previous {
+        Some(previous) => graph.capture_pose_from(previous, world),
+        None => graph.capture_pose(world),
+    };
+    match snapshot.and_then(|snapshot| graph.fade_from_pose(snapshot, duration)) {
+        Ok(()) => {}
+        Err(err) => warn!("Failed to crossfade into a new animation graph: {}", err),
+    }
+
+    // Bones only animated by the previous graph are left where it left them.
+    let stale: SmallVec<[Entity; 8]> = previous
+        .into_iter()
+        .flat_map(|previous| {
+            previous
+                .bones()
+                .filter_map(|bone| bone.entity())
+                .filter(|entity| {
+                    matches!(
+                        world.get::<BoneBinding>(*entity),
+                        Some(binding) if binding.graph_nonce == previous.nonce
+                    )
+                })
+        })
+        .collect();
+    for entity in stale {
+        world.entity_mut(entity).remove::<BoneBinding>();
+    }
+    world.entity_mut(root).insert(graph);
+}
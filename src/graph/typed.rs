@@ -0,0 +1,1026 @@
+//! Typed tracks, which animate the fields of a component without reflection.
+//!
+//! Components are animated through reflection by default: every frame, each
+//! animated property is looked up in the [`TypeRegistry`], reached through
+//! its field path, and assigned via [`Reflect`]. For components animated on
+//! many entities, that overhead can dominate. Components registered with
+//! [`animate_component!`] and a [`TypedAnimationPlugin`] are instead animated
+//! by a generated track for the whole component, and applied by a system
+//! querying the component directly.
+//!
+//! ```rust,ignore
+//! #[derive(Component, Reflect, Clone, Default)]
+//! #[reflect(Component)]
+//! struct Glow {
+//!     intensity: f32,
+//!     offset: Vec3,
+//! }
+//!
+//! animate_component!(Glow { intensity: f32, offset: Vec3 });
+//!
+//! app.add_plugin(AnimationPlugin::default())
+//!     .add_plugin(TypedAnimationPlugin::<Glow>::default());
+//! ```
+//!
+//! Clips are built exactly as before, with property paths such as
+//! `"lamp@my_game::Glow.intensity"`. When added to a graph, curves for the
+//! listed fields, or for the whole component, are routed into the typed
+//! track. Nested fields, such as `"offset.x"`, and any other properties stay
+//! on the reflection path. Typed fields are blended, weighted and masked
+//! exactly like reflected ones.
+//!
+//! The plugin adds a [`ReflectTypedComponent`] to the component's
+//! registration in the app's type registry. Paths parsed against that
+//! registry remember it, and curves for them are routed into typed tracks
+//! when they are added to a graph, so components must be registered before
+//! their paths are parsed. Paths built in code can use
+//! [`AccessPath::of_typed`]. Like reflected properties, typed fields are
+//! only written when their blended value changes, and are post-processed
+//! with [`Animatable::post_process`].
+//!
+//! [`TypeRegistry`]: bevy_reflect::TypeRegistry
+//! [`animate_component!`]: crate::animate_component
+
+use crate::{
+    clip::{ClipCurve, CurveWrapper},
+    curve::{mapped_curve_methods, Curve, CurveFixed},
+    graph::{
+        application::{self, BoneBinding, EntityWriter},
+        recorder::{PropertyRecording, RecordedValues},
+        track::write_value,
+        AnimationGraph, ClipId, CurveTrack, GraphState, Track, TrackError, WriteMask,
+    },
+    path::{Access, AccessPath, FieldPath},
+    target, Animatable, AnimationSystem, WorldResources,
+};
+use bevy_app::prelude::*;
+use bevy_ecs::{component::Component, prelude::*};
+use bevy_reflect::{FromType, GetTypeRegistration, Reflect};
+use bevy_tasks::ComputeTaskPool;
+use bevy_transform::TransformSystem;
+use bevy_utils::HashMap;
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    fmt,
+    marker::PhantomData,
+    sync::Arc,
+};
+
+const TYPED_BATCH_SIZE: usize = 32;
+
+/// A component whose fields are animated by a typed track. Implemented by
+/// [`animate_component!`](crate::animate_component), along with a fieldwise
+/// [`Animatable`] implementation for the component.
+pub trait TypedComponent: Component + Animatable + Default + GetTypeRegistration {
+    #[doc(hidden)]
+    type Tracks: Clone + Default + Send + Sync + 'static;
+
+    #[doc(hidden)]
+    fn visit_tracks<V: FieldVisitor<Self>>(tracks: &Self::Tracks, visitor: &mut V);
+
+    #[doc(hidden)]
+    fn visit_tracks_mut<V: FieldVisitorMut<Self>>(tracks: &mut Self::Tracks, visitor: &mut V);
+}
+
+#[doc(hidden)]
+pub trait FieldVisitor<C> {
+    fn visit<T: Animatable>(
+        &mut self,
+        name: &'static str,
+        track: &FieldTrack<T>,
+        field: fn(&mut C) -> &mut T,
+    );
+}
+
+#[doc(hidden)]
+pub trait FieldVisitorMut<C> {
+    fn visit<T: Animatable>(
+        &mut self,
+        name: &'static str,
+        track: &mut FieldTrack<T>,
+        field: fn(&C) -> &T,
+    );
+}
+
+/// The curves animating one field of a [`TypedComponent`].
+#[doc(hidden)]
+pub struct FieldTrack<T: Animatable> {
+    // Not set until a clip animates the field.
+    track: Option<(AccessPath, CurveTrack<T>)>,
+}
+
+impl<T: Animatable> Default for FieldTrack<T> {
+    fn default() -> Self {
+        Self { track: None }
+    }
+}
+
+impl<T: Animatable> Clone for FieldTrack<T> {
+    fn clone(&self) -> Self {
+        Self {
+            track: self.track.clone(),
+        }
+    }
+}
+
+/// Adds the plumbing for animating a component without reflection: a typed
+/// track for the listed fields, and a fieldwise [`Animatable`] implementation.
+/// The component must be [`Reflect`], [`Clone`] and [`Default`], and its
+/// fields [`Animatable`]. Fields that aren't listed are left untouched.
+///
+/// ```rust,ignore
+/// animate_component!(Glow { intensity: f32, offset: Vec3 });
+/// ```
+///
+/// The component is only animated through its typed track once its
+/// [`TypedAnimationPlugin`] has been added. See the [module
+/// documentation](crate::graph::typed).
+///
+/// [`Animatable`]: crate::Animatable
+/// [`Reflect`]: bevy_reflect::Reflect
+/// [`TypedAnimationPlugin`]: crate::graph::typed::TypedAnimationPlugin
+#[macro_export]
+macro_rules! animate_component {
+    // A single field is blended straight from the inputs.
+    (@blend $component: ty, $inputs: ident, $field: ident: $field_ty: ty) => {{
+        let mut inputs = $inputs.peekable();
+        let mut output = inputs
+            .peek()
+            .map_or_else(::std::default::Default::default, |input| input.value.clone());
+        output.$field = <$field_ty as $crate::Animatable>::blend(inputs.map(|input| {
+            $crate::BlendInput {
+                weight: input.weight,
+                value: input.value.$field.clone(),
+                additive: input.additive,
+            }
+        }));
+        output
+    }};
+    // Every field needs its own pass over the inputs, so they are buffered
+    // in a scratch buffer reused between blends.
+    (@blend $component: ty, $inputs: ident, $($field: ident: $field_ty: ty),+) => {{
+        ::std::thread_local! {
+            static INPUTS: ::std::cell::RefCell<::std::vec::Vec<$crate::BlendInput<$component>>> =
+                ::std::cell::RefCell::new(::std::vec::Vec::new());
+        }
+        INPUTS.with(|buffer| {
+            let mut buffer = buffer.borrow_mut();
+            buffer.extend($inputs);
+            let mut output = buffer
+                .first()
+                .map_or_else(::std::default::Default::default, |input| input.value.clone());
+            $(output.$field = <$field_ty as $crate::Animatable>::blend(
+                buffer.iter().map(|input| $crate::BlendInput {
+                    weight: input.weight,
+                    value: input.value.$field.clone(),
+                    additive: input.additive,
+                }),
+            );)+
+            buffer.clear();
+            output
+        })
+    }};
+    ($component: ty { $($field: ident: $field_ty: ty),+ $(,)? }) => {
+        impl $crate::Animatable for $component {
+            fn interpolate(a: &Self, b: &Self, t: f32) -> Self {
+                let mut output = a.clone();
+                $(output.$field = $crate::Animatable::interpolate(&a.$field, &b.$field, t);)+
+                output
+            }
+
+            fn blend(inputs: impl Iterator<Item = $crate::BlendInput<Self>>) -> Self {
+                $crate::animate_component!(@blend $component, inputs, $($field: $field_ty),+)
+            }
+
+            fn difference(a: &Self, b: &Self) -> Self {
+                let mut output = a.clone();
+                $(output.$field = $crate::Animatable::difference(&a.$field, &b.$field);)+
+                output
+            }
+
+            fn distance(a: &Self, b: &Self) -> f32 {
+                0.0f32 $(.max($crate::Animatable::distance(&a.$field, &b.$field)))+
+            }
+
+            fn is_finite(&self) -> bool {
+                true $(&& $crate::Animatable::is_finite(&self.$field))+
+            }
+
+            fn post_process(&mut self, resources: $crate::WorldResources<'_>) {
+                $($crate::Animatable::post_process(&mut self.$field, resources);)+
+            }
+        }
+
+        const _: () = {
+            #[derive(Clone, Default)]
+            pub struct Tracks {
+                $($field: $crate::graph::typed::FieldTrack<$field_ty>,)+
+            }
+
+            impl $crate::graph::typed::TypedComponent for $component {
+                type Tracks = Tracks;
+
+                fn visit_tracks<V: $crate::graph::typed::FieldVisitor<Self>>(
+                    tracks: &Tracks,
+                    visitor: &mut V,
+                ) {
+                    $(visitor.visit(
+                        stringify!($field),
+                        &tracks.$field,
+                        |value: &mut Self| &mut value.$field,
+                    );)+
+                }
+
+                fn visit_tracks_mut<V: $crate::graph::typed::FieldVisitorMut<Self>>(
+                    tracks: &mut Tracks,
+                    visitor: &mut V,
+                ) {
+                    $(visitor.visit(
+                        stringify!($field),
+                        &mut tracks.$field,
+                        |value: &Self| &value.$field,
+                    );)+
+                }
+            }
+        };
+    };
+}
+
+/// Animates a [`TypedComponent`] without reflection, by registering it for
+/// typed tracks and adding [`animate_typed_components_system`] for it.
+///
+/// Like the [`AnimationPlugin`], the system is added to
+/// [`CoreStage::Update`] by default, and should be added to the same stage.
+///
+/// [`AnimationPlugin`]: crate::AnimationPlugin
+pub struct TypedAnimationPlugin<C, S = CoreStage> {
+    stage: S,
+    marker: PhantomData<fn() -> C>,
+}
+
+impl<C> Default for TypedAnimationPlugin<C> {
+    fn default() -> Self {
+        Self {
+            stage: CoreStage::Update,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<C, S: StageLabel + Clone> TypedAnimationPlugin<C, S> {
+    /// Adds the application system to the provided stage instead.
+    pub fn in_stage<T: StageLabel + Clone>(self, stage: T) -> TypedAnimationPlugin<C, T> {
+        TypedAnimationPlugin {
+            stage,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<C: TypedComponent, S: StageLabel + Clone> Plugin for TypedAnimationPlugin<C, S> {
+    fn build(&self, app: &mut App) {
+        // Still registered for reflection, which is used to capture poses,
+        // record graphs and validate clips.
+        app.register_type::<C>();
+        target::insert_type_data::<C, ReflectTypedComponent>(app);
+        // Like animate_entities_system, this runs at the end of the stage, so
+        // newly bound entities are animated in the same frame.
+        app.add_system_to_stage(
+            self.stage.clone(),
+            animate_typed_components_system::<C>
+                .exclusive_system()
+                .at_end()
+                .label(AnimationSystem::GraphSamplingTyped)
+                .before(AnimationSystem::GraphSamplingGeneric)
+                .after(AnimationSystem::GraphHierarchyBind)
+                .after(AnimationSystem::GraphEvaluation)
+                .before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Type data for components animated by typed tracks, added to their
+/// registration by the [`TypedAnimationPlugin`]. Paths to the component
+/// parsed against the registry keep a copy, which routes their curves into
+/// the component's typed track when they are added to a graph.
+#[derive(Clone, Copy)]
+pub struct ReflectTypedComponent {
+    // A single pointer, as it's stored in every path to the component.
+    routes: &'static TypedRoutes,
+}
+
+struct TypedRoutes {
+    new_track: fn() -> Box<dyn Track>,
+    field_type: fn(&FieldPath) -> Option<(TypeId, &'static str)>,
+}
+
+struct RoutesOf<C>(PhantomData<C>);
+
+impl<C: TypedComponent> RoutesOf<C> {
+    const ROUTES: TypedRoutes = TypedRoutes {
+        new_track: || Box::new(TypedTrack::<C>::default()),
+        field_type: field_type::<C>,
+    };
+}
+
+impl fmt::Debug for ReflectTypedComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReflectTypedComponent")
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: TypedComponent> FromType<C> for ReflectTypedComponent {
+    fn from_type() -> Self {
+        Self {
+            routes: &RoutesOf::<C>::ROUTES,
+        }
+    }
+}
+
+/// Applies the typed tracks of every changed [`AnimationGraph`] to the bound
+/// entities with a `C`. Follows the same rules as
+/// [`animate_entities_system`], which applies the rest of the graph's
+/// properties.
+///
+/// This MUST be added as an exclusive system.
+///
+/// [`animate_entities_system`]: crate::graph::application::animate_entities_system
+pub fn animate_typed_components_system<C: TypedComponent>(
+    world: &World,
+    entities: Query<(Entity, &BoneBinding), With<C>>,
+    graphs: Query<(&AnimationGraph, ChangeTrackers<AnimationGraph>)>,
+    task_pool: Res<ComputeTaskPool>,
+) {
+    let key = component_access::<C>(None);
+    let items = entities.iter().filter_map(|(entity, binding)| {
+        // Invalid bindings are removed by animate_entities_system.
+        match application::applicable_bones(entity, binding, &graphs) {
+            Ok(Some((graph, bones))) => Some((entity, graph, bones)),
+            _ => None,
+        }
+    });
+    // SAFE: This system is exclusive, so nothing else accesses the World
+    // while it runs. The writers only write `C`, and the only components read
+    // while they exist are the AnimationGraphs and BoneBindings in the
+    // queries, neither of which is animatable.
+    let mut writers = unsafe { EntityWriter::disjoint(world, items, |(entity, ..)| *entity) };
+    let resources = WorldResources::new(world);
+    let key = &key;
+    task_pool.scope(|scope| {
+        for batch in writers.chunks_mut(TYPED_BATCH_SIZE) {
+            scope.spawn(async move {
+                for ((_, graph, bones), writer) in batch {
+                    // Blended into a copy, so the component is only marked as
+                    // changed if a field is.
+                    let mut value = match writer.get::<C>() {
+                        Some(component) => component.clone(),
+                        None => continue,
+                    };
+                    let mut changed = false;
+                    for bone in bones.iter() {
+                        let track = bone
+                            .tracks
+                            .get(key)
+                            .and_then(|track| track.as_any().downcast_ref::<TypedTrack<C>>());
+                        if let Some(track) = track {
+                            changed |=
+                                track.apply(&graph.state, &bone.write_mask, &mut value, resources);
+                        }
+                    }
+                    if changed {
+                        if let Some(mut component) = writer.get_mut::<C>() {
+                            *component = value;
+                        }
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Where the curves for a property of a typed component are stored.
+pub(crate) struct TypedRoute {
+    /// The property of the typed track, which animates the whole component.
+    pub key: AccessPath,
+    /// The value type of the routed property.
+    pub value_type: (TypeId, &'static str),
+    new_track: fn() -> Box<dyn Track>,
+}
+
+impl TypedRoute {
+    pub fn new_track(&self) -> Box<dyn Track> {
+        (self.new_track)()
+    }
+}
+
+/// Finds the typed track the curves for a property are routed to. Returns
+/// `None` if the path isn't to a typed component, or if it isn't the
+/// component itself or one of its listed fields.
+pub(crate) fn find_route(access: &AccessPath) -> Option<TypedRoute> {
+    let routes = access.typed()?.routes;
+    let value_type = (routes.field_type)(access.field_path())?;
+    Some(TypedRoute {
+        key: AccessPath::from_parts(
+            access.component_type_id(),
+            access.component_name(),
+            FieldPath::root(),
+        ),
+        value_type,
+        new_track: routes.new_track,
+    })
+}
+
+fn component_access<C: 'static>(field: Option<&str>) -> AccessPath {
+    let mut path = FieldPath::root();
+    if let Some(field) = field {
+        path.push(Access::Field(field.to_owned()));
+    }
+    AccessPath::from_parts(TypeId::of::<C>(), std::any::type_name::<C>(), path)
+}
+
+/// Gets the value type of the component itself, or of one of its listed
+/// fields.
+fn field_type<C: TypedComponent>(field: &FieldPath) -> Option<(TypeId, &'static str)> {
+    struct FindField<'a> {
+        name: &'a str,
+        found: Option<(TypeId, &'static str)>,
+    }
+
+    impl<'a, C> FieldVisitor<C> for FindField<'a> {
+        fn visit<T: Animatable>(&mut self, name: &str, _: &FieldTrack<T>, _: fn(&mut C) -> &mut T) {
+            if name == self.name {
+                self.found = Some((TypeId::of::<T>(), std::any::type_name::<T>()));
+            }
+        }
+    }
+
+    let mut parts = field.iter();
+    let name = match (parts.next(), parts.next()) {
+        (None, _) => return Some((TypeId::of::<C>(), std::any::type_name::<C>())),
+        (Some(Access::Field(name)), None) => name,
+        _ => return None,
+    };
+    let mut visitor = FindField { name, found: None };
+    C::visit_tracks(&Default::default(), &mut visitor);
+    visitor.found
+}
+
+/// The typed track for a whole [`TypedComponent`], with the curves of each of
+/// its fields.
+pub(crate) struct TypedTrack<C: TypedComponent> {
+    tracks: C::Tracks,
+}
+
+impl<C: TypedComponent> Default for TypedTrack<C> {
+    fn default() -> Self {
+        Self {
+            tracks: Default::default(),
+        }
+    }
+}
+
+impl<C: TypedComponent> Clone for TypedTrack<C> {
+    fn clone(&self) -> Self {
+        Self {
+            tracks: self.tracks.clone(),
+        }
+    }
+}
+
+impl<C: TypedComponent> TypedTrack<C> {
+    /// Blends each animated field allowed by the mask, and writes it to the
+    /// component. Other fields are left unchanged.
+    pub(crate) fn blend(&self, state: &GraphState, mask: &WriteMask, output: &mut C) {
+        self.visit_fields(state, mask, output, None);
+    }
+
+    /// Like [`blend`](Self::blend), but only writes the fields whose blended
+    /// value changed, post-processing them like reflected properties are.
+    /// Returns whether any field was written.
+    pub(crate) fn apply(
+        &self,
+        state: &GraphState,
+        mask: &WriteMask,
+        output: &mut C,
+        resources: WorldResources<'_>,
+    ) -> bool {
+        self.visit_fields(state, mask, output, Some(resources))
+    }
+
+    fn visit_fields(
+        &self,
+        state: &GraphState,
+        mask: &WriteMask,
+        output: &mut C,
+        resources: Option<WorldResources<'_>>,
+    ) -> bool {
+        struct Apply<'a, C> {
+            state: &'a GraphState,
+            mask: &'a WriteMask,
+            output: &'a mut C,
+            resources: Option<WorldResources<'a>>,
+            written: bool,
+        }
+
+        impl<'a, C> FieldVisitor<C> for Apply<'a, C> {
+            fn visit<T: Animatable>(
+                &mut self,
+                _: &str,
+                track: &FieldTrack<T>,
+                field: fn(&mut C) -> &mut T,
+            ) {
+                if let Some((access, track)) = &track.track {
+                    if self.mask.allows(access) {
+                        let value = track.sample_and_blend(self.state);
+                        let output = field(self.output);
+                        self.written |= match self.resources {
+                            Some(resources) => write_value(Cow::Owned(value), output, resources),
+                            None => {
+                                *output = value;
+                                true
+                            }
+                        };
+                    }
+                }
+            }
+        }
+
+        let mut visitor = Apply {
+            state,
+            mask,
+            output,
+            resources,
+            written: false,
+        };
+        C::visit_tracks(&self.tracks, &mut visitor);
+        visitor.written
+    }
+
+    fn add_curve(&mut self, field: Option<&str>, clip_id: ClipId, curve: &dyn ClipCurve) {
+        struct AddCurve<'a, C> {
+            field: Option<&'a str>,
+            clip_id: ClipId,
+            // Set if the curve animates the whole component.
+            component: Option<&'a Arc<dyn Curve<C>>>,
+            curve: &'a dyn ClipCurve,
+        }
+
+        impl<'a, C: Animatable> FieldVisitorMut<C> for AddCurve<'a, C> {
+            fn visit<T: Animatable>(
+                &mut self,
+                name: &'static str,
+                track: &mut FieldTrack<T>,
+                field: fn(&C) -> &T,
+            ) {
+                let curve: Arc<dyn Curve<T>> = match self.component {
+                    Some(curve) => Arc::new(FieldCurve {
+                        curve: curve.clone(),
+                        field,
+                    }),
+                    None if self.field == Some(name) => {
+                        match self.curve.as_any().downcast_ref::<CurveWrapper<T>>() {
+                            Some(curve) => curve.0.clone(),
+                            None => return,
+                        }
+                    }
+                    None => return,
+                };
+                match &mut track.track {
+                    Some((_, track)) => track.add_curve(self.clip_id, curve),
+                    None => {
+                        let access = component_access::<C>(Some(name));
+                        track.track = Some((access, CurveTrack::new(curve, self.clip_id)));
+                    }
+                }
+            }
+        }
+
+        let component = curve
+            .as_any()
+            .downcast_ref::<CurveWrapper<C>>()
+            .map(|curve| &curve.0);
+        C::visit_tracks_mut(
+            &mut self.tracks,
+            &mut AddCurve {
+                field,
+                clip_id,
+                component: if field.is_none() { component } else { None },
+                curve,
+            },
+        );
+    }
+
+    /// Runs `f` on each animated field of the track.
+    fn for_each_field_mut(&mut self, f: impl FnMut(&mut dyn AnimatedField<C>)) {
+        struct ForEach<F>(F);
+
+        impl<C, F: FnMut(&mut dyn AnimatedField<C>)> FieldVisitorMut<C> for ForEach<F> {
+            fn visit<T: Animatable>(
+                &mut self,
+                _: &'static str,
+                track: &mut FieldTrack<T>,
+                field: fn(&C) -> &T,
+            ) {
+                if let Some((_, track)) = &mut track.track {
+                    (self.0)(&mut (track, field));
+                }
+            }
+        }
+
+        C::visit_tracks_mut(&mut self.tracks, &mut ForEach(f));
+    }
+}
+
+/// An animated field of a typed track, along with its accessor.
+trait AnimatedField<C> {
+    fn add_snapshot(&mut self, pose_id: ClipId, value: &C);
+    fn set_rest_value(&mut self, value: Option<&C>);
+    fn evict_curve(&mut self, clip_id: ClipId);
+}
+
+impl<C, T: Animatable> AnimatedField<C> for (&mut CurveTrack<T>, fn(&C) -> &T) {
+    fn add_snapshot(&mut self, pose_id: ClipId, value: &C) {
+        let value = (self.1)(value).clone();
+        self.0
+            .add_curve(pose_id, Arc::new(CurveFixed::from_constant(value)));
+    }
+
+    fn set_rest_value(&mut self, value: Option<&C>) {
+        self.0.set_rest(value.map(|value| (self.1)(value).clone()));
+    }
+
+    fn evict_curve(&mut self, clip_id: ClipId) {
+        self.0.evict_curve(clip_id);
+    }
+}
+
+impl<C: TypedComponent> Track for TypedTrack<C> {
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<C>()
+    }
+    fn value_type_name(&self) -> &'static str {
+        std::any::type_name::<C>()
+    }
+    fn as_any(&self) -> &dyn Any {
+        self as &_
+    }
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self as &mut _
+    }
+    fn clone_track(&self) -> Box<dyn Track> {
+        Box::new(self.clone())
+    }
+    fn is_typed(&self) -> bool {
+        true
+    }
+    fn apply_typed(&self, world: &mut World, entity: Entity, state: &GraphState, mask: &WriteMask) {
+        let mut value = match world.get::<C>(entity) {
+            Some(component) => component.clone(),
+            None => return,
+        };
+        if self.apply(state, mask, &mut value, WorldResources::new(world)) {
+            if let Some(mut component) = world.get_mut::<C>(entity) {
+                *component = value;
+            }
+        }
+    }
+    fn field_type(&self, field: &FieldPath) -> Option<(TypeId, &'static str)> {
+        field_type::<C>(field)
+    }
+    fn start_recording(&self) -> Option<Box<dyn PropertyRecording>> {
+        Some(Box::new(RecordedValues::<C>::default()))
+    }
+    fn value_distance(&self, a: &dyn Reflect, b: &dyn Reflect) -> Option<f32> {
+        Some(C::distance(a.downcast_ref()?, b.downcast_ref()?))
+    }
+    fn sample_clip_boxed(&self, clip_id: ClipId, time: f32) -> Option<Box<dyn Reflect>> {
+        struct SampleClip<C> {
+            clip_id: ClipId,
+            time: f32,
+            output: C,
+            found: bool,
+        }
+
+        impl<C> FieldVisitor<C> for SampleClip<C> {
+            fn visit<T: Animatable>(
+                &mut self,
+                _: &str,
+                track: &FieldTrack<T>,
+                field: fn(&mut C) -> &mut T,
+            ) {
+                let curve = track
+                    .track
+                    .as_ref()
+                    .and_then(|(_, track)| track.clip_curve(self.clip_id));
+                if let Some(curve) = curve {
+                    *field(&mut self.output) = curve.sample(self.time);
+                    self.found = true;
+                }
+            }
+        }
+
+        let mut visitor = SampleClip {
+            clip_id,
+            time,
+            output: C::default(),
+            found: false,
+        };
+        C::visit_tracks(&self.tracks, &mut visitor);
+        visitor
+            .found
+            .then(|| Box::new(visitor.output) as Box<dyn Reflect>)
+    }
+    fn blend_boxed(&self, state: &GraphState) -> Box<dyn Reflect> {
+        let mut value = C::default();
+        self.blend(state, &WriteMask::All, &mut value);
+        Box::new(value)
+    }
+    fn blend_into_boxed(&self, state: &GraphState, output: &mut Box<dyn Reflect>) {
+        match output.downcast_mut::<C>() {
+            Some(output) => self.blend(state, &WriteMask::All, output),
+            None => *output = self.blend_boxed(state),
+        }
+    }
+
+    fn add_generic_curve(
+        &mut self,
+        clip_id: ClipId,
+        curve: &dyn ClipCurve,
+    ) -> Result<(), TrackError> {
+        self.add_field_curve(&component_access::<C>(None), clip_id, curve)
+    }
+
+    fn add_field_curve(
+        &mut self,
+        access: &AccessPath,
+        clip_id: ClipId,
+        curve: &dyn ClipCurve,
+    ) -> Result<(), TrackError> {
+        let expected = match field_type::<C>(access.field_path()) {
+            Some(expected) => expected,
+            None => return Err(TrackError::MissingTrack),
+        };
+        if expected.0 != curve.value_type_id() {
+            return Err(TrackError::IncorrectType {
+                expected: expected.1,
+                found: curve.value_type_name().to_owned(),
+            });
+        }
+        let field = match access.field_path().iter().next() {
+            Some(Access::Field(field)) => Some(field.as_str()),
+            _ => None,
+        };
+        self.add_curve(field, clip_id, curve);
+        Ok(())
+    }
+
+    fn animates_clip(&self, clip_id: ClipId) -> bool {
+        struct AnimatesClip {
+            clip_id: ClipId,
+            found: bool,
+        }
+
+        impl<C> FieldVisitor<C> for AnimatesClip {
+            fn visit<T: Animatable>(
+                &mut self,
+                _: &str,
+                track: &FieldTrack<T>,
+                _: fn(&mut C) -> &mut T,
+            ) {
+                if let Some((_, track)) = &track.track {
+                    self.found |= track.clip_curve(self.clip_id).is_some();
+                }
+            }
+        }
+
+        let mut visitor = AnimatesClip {
+            clip_id,
+            found: false,
+        };
+        C::visit_tracks(&self.tracks, &mut visitor);
+        visitor.found
+    }
+
+    fn remove_clip_curves(
+        &mut self,
+        access: &AccessPath,
+        clip_id: ClipId,
+        keep: &dyn Fn(&AccessPath) -> bool,
+    ) -> bool {
+        struct RemoveClip<'a> {
+            clip_id: ClipId,
+            // Kept if the clip animates the whole component.
+            keep_component: bool,
+            keep: &'a dyn Fn(&AccessPath) -> bool,
+            empty: bool,
+        }
+
+        impl<'a, C> FieldVisitorMut<C> for RemoveClip<'a> {
+            fn visit<T: Animatable>(
+                &mut self,
+                _: &'static str,
+                field: &mut FieldTrack<T>,
+                _: fn(&C) -> &T,
+            ) {
+                if let Some((access, track)) = &mut field.track {
+                    if !self.keep_component && !(self.keep)(access) {
+                        track.remove_curve(self.clip_id);
+                    }
+                    if track.is_empty() {
+                        field.track = None;
+                    }
+                }
+                self.empty &= field.track.is_none();
+            }
+        }
+
+        let mut visitor = RemoveClip {
+            clip_id,
+            keep_component: keep(access),
+            keep,
+            empty: true,
+        };
+        C::visit_tracks_mut(&mut self.tracks, &mut visitor);
+        visitor.empty
+    }
+
+    fn evict_clip_curves(&mut self, clip_id: ClipId) {
+        self.for_each_field_mut(|field| field.evict_curve(clip_id));
+    }
+
+    fn curve_sizes(&self, sizes: &mut HashMap<*const (), usize>) {
+        struct CurveSizes<'a>(&'a mut HashMap<*const (), usize>);
+
+        impl<'a, C> FieldVisitor<C> for CurveSizes<'a> {
+            fn visit<T: Animatable>(
+                &mut self,
+                _: &str,
+                track: &FieldTrack<T>,
+                _: fn(&mut C) -> &mut T,
+            ) {
+                if let Some((_, track)) = &track.track {
+                    track.curve_sizes(self.0);
+                }
+            }
+        }
+
+        C::visit_tracks(&self.tracks, &mut CurveSizes(sizes));
+    }
+
+    fn add_snapshot(&mut self, pose_id: ClipId, value: &dyn Reflect) -> Result<(), TrackError> {
+        let value = value
+            .downcast_ref::<C>()
+            .ok_or_else(|| TrackError::incorrect_type::<C>(value.type_name()))?;
+        self.for_each_field_mut(|field| field.add_snapshot(pose_id, value));
+        Ok(())
+    }
+
+    fn set_rest_value(&mut self, value: &dyn Reflect) -> Result<(), TrackError> {
+        let value = value
+            .downcast_ref::<C>()
+            .ok_or_else(|| TrackError::incorrect_type::<C>(value.type_name()))?;
+        self.for_each_field_mut(|field| field.set_rest_value(Some(value)));
+        Ok(())
+    }
+
+    fn clear_rest_value(&mut self) {
+        self.for_each_field_mut(|field| field.set_rest_value(None));
+    }
+
+    fn blend_via_reflect(
+        &self,
+        state: &GraphState,
+        output: &mut dyn Reflect,
+        resources: WorldResources<'_>,
+    ) -> Result<bool, TrackError> {
+        if !output.any().is::<C>() {
+            return Err(TrackError::incorrect_type::<C>(output.type_name()));
+        }
+        let output = output.downcast_mut::<C>().unwrap();
+        Ok(self.apply(state, &WriteMask::All, output, resources))
+    }
+
+    fn write_blended(
+        &self,
+        value: &dyn Reflect,
+        output: &mut dyn Reflect,
+        resources: WorldResources<'_>,
+    ) -> Result<bool, TrackError> {
+        let value = value
+            .downcast_ref::<C>()
+            .ok_or_else(|| TrackError::incorrect_type::<C>(value.type_name()))?;
+        if !output.any().is::<C>() {
+            return Err(TrackError::incorrect_type::<C>(output.type_name()));
+        }
+        let output = output.downcast_mut::<C>().unwrap();
+        Ok(write_value(Cow::Borrowed(value), output, resources))
+    }
+}
+
+/// A curve for a field of a component, sampled from a curve for the whole
+/// component.
+struct FieldCurve<C, T> {
+    curve: Arc<dyn Curve<C>>,
+    field: fn(&C) -> &T,
+}
+
+impl<C: Animatable, T: Animatable> Curve<T> for FieldCurve<C, T> {
+    mapped_curve_methods!(T, curve, |this, _, value| (this.field)(&value).clone());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{clip::AnimationClip, graph::NodeId, path::PropertyPath};
+    use bevy_math::Vec3;
+
+    #[derive(Component, Reflect, Clone, Default, Debug, PartialEq)]
+    struct Routed {
+        intensity: f32,
+        offset: Vec3,
+    }
+
+    crate::animate_component!(Routed {
+        intensity: f32,
+        offset: Vec3
+    });
+
+    fn routed_path(field: &str) -> PropertyPath {
+        PropertyPath::from_parts(
+            "lamp".parse().unwrap(),
+            AccessPath::of_typed::<Routed>(field).unwrap(),
+        )
+    }
+
+    #[test]
+    pub fn test_curves_are_routed_by_field() {
+        let whole = routed_path("");
+        let clip = AnimationClip::builder()
+            .add_curve(
+                routed_path("intensity"),
+                CurveFixed::from_keyframes(1.0, vec![0.0f32, 2.0]),
+            )
+            // Nested fields stay on the reflection path.
+            .add_curve(
+                routed_path("offset.x"),
+                CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]),
+            )
+            .build();
+        let other = AnimationClip::builder()
+            .add_curve(
+                whole.clone(),
+                CurveFixed::from_keyframes(
+                    1.0,
+                    vec![
+                        Routed::default(),
+                        Routed {
+                            intensity: 4.0,
+                            offset: Vec3::Y,
+                        },
+                    ],
+                ),
+            )
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        let other = graph.add_clip(&other).unwrap();
+
+        let bone = graph.find_bone(whole.entity()).unwrap();
+        let properties: Vec<_> = bone.properties().cloned().collect();
+        assert_eq!(
+            properties,
+            vec![
+                whole.access().clone(),
+                routed_path("offset.x").access().clone()
+            ]
+        );
+        assert!(bone.tracks[whole.access()].is_typed());
+
+        // Fields are sampled through the track for the whole component, and
+        // whole component curves are split into their fields.
+        let intensity = routed_path("intensity");
+        assert_eq!(
+            graph.sample_clip_property(node, &intensity, 0.5),
+            Some(1.0f32)
+        );
+        assert_eq!(
+            graph.sample_clip_property(other, &intensity, 0.5),
+            Some(2.0f32)
+        );
+        assert_eq!(
+            graph.sample_clip_property(other, &routed_path("offset"), 0.5),
+            Some(Vec3::Y * 0.5)
+        );
+        assert_eq!(
+            graph.sample_clip_property::<f32>(NodeId::ROOT, &intensity, 0.5),
+            None
+        );
+
+        let mismatched = AnimationClip::builder()
+            .add_curve(intensity, CurveFixed::from_constant(Vec3::ONE))
+            .build();
+        assert!(graph.add_clip(&mismatched).is_err());
+    }
+}
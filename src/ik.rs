@@ -0,0 +1,187 @@
+use crate::{graph::AnimationGraph, path::EntityPath};
+use bevy_ecs::prelude::*;
+use bevy_log::warn;
+use bevy_math::{Quat, Vec3};
+use bevy_transform::prelude::{Parent, Transform};
+use bevy_utils::HashSet;
+
+/// Lengths and distances below this are treated as zero.
+const IK_EPSILON: f32 = 1e-5;
+
+/// Bends a two-bone chain of an [`AnimationGraph`], like a leg or an arm, so
+/// that its tip reaches towards `target`. Solved after the graph is applied,
+/// on top of the animated pose.
+///
+/// `chain` holds the paths of the root joint and the tip of the chain,
+/// relative to the graph, e.g. `["hip/thigh", "hip/thigh/shin/foot"]`. The
+/// middle joint is the parent of the tip, and must be a child of the root
+/// joint. Both joints must be bound by the graph, so they need to be animated
+/// by at least one of its clips.
+///
+/// Only the rotations of the two joints are changed. The solved rotations are
+/// blended with the animated ones by `weight`, from 0 (fully animated) to 1
+/// (fully solved). Joints masked out of the graph with
+/// [`AnimationGraph::set_bone_write_mask`] aren't animated, so they're blended
+/// with their current rotation instead; with a weight of 1, the chain is fully
+/// handed over to the constraint.
+#[derive(Component, Clone, Debug)]
+pub struct IkConstraint {
+    /// The entity with the [`AnimationGraph`] the chain belongs to.
+    pub graph: Entity,
+    /// The paths of the root joint and the tip of the chain.
+    pub chain: [EntityPath; 2],
+    /// The entity whose translation the tip reaches towards.
+    pub target: Entity,
+    /// How much of the solved pose is applied, from 0 to 1.
+    pub weight: f32,
+}
+
+/// Solves every [`IkConstraint`]. Must run after the graphs are applied, and
+/// before transforms are propagated.
+///
+/// Transforms haven't been propagated yet, so world space transforms are
+/// computed from the [`Transform`]s of each entity's ancestors. Chains that
+/// aren't currently bound are left untouched.
+pub fn solve_ik_constraints_system(
+    constraints: Query<(Entity, &IkConstraint)>,
+    graphs: Query<&AnimationGraph>,
+    mut transforms: Query<&mut Transform>,
+    parents: Query<&Parent>,
+    mut invalid: Local<HashSet<Entity>>,
+) {
+    for (entity, constraint) in constraints.iter() {
+        let weight = constraint.weight.clamp(0.0, 1.0);
+        if weight <= 0.0 {
+            continue;
+        }
+        let graph = match graphs.get(constraint.graph) {
+            Ok(graph) => graph,
+            Err(_) => continue,
+        };
+        let [root_path, tip_path] = &constraint.chain;
+        let (root, tip) = match (graph.bound_entity(root_path), graph.bound_entity(tip_path)) {
+            (Some(root), Some(tip)) => (root, tip),
+            _ => continue,
+        };
+        let middle = match parents.get(tip) {
+            Ok(parent) if parents.get(parent.0).ok().map(|p| p.0) == Some(root) => parent.0,
+            _ => {
+                if invalid.insert(entity) {
+                    warn!(
+                        "IK constraint {:?}: the parent of '{}' is not a child of '{}'.",
+                        entity, tip_path, root_path
+                    );
+                }
+                continue;
+            }
+        };
+        invalid.remove(&entity);
+
+        let parent_global = match parents.get(root) {
+            Ok(parent) => global_transform(parent.0, &transforms, &parents),
+            Err(_) => Some(Transform::identity()),
+        };
+        let (parent_global, root_global, middle_global, tip_global, target) = match (
+            parent_global,
+            transforms.get(root),
+            transforms.get(middle),
+            transforms.get(tip),
+            global_transform(constraint.target, &transforms, &parents),
+        ) {
+            (Some(parent_global), Ok(root), Ok(middle), Ok(tip), Some(target)) => {
+                let root_global = parent_global.mul_transform(*root);
+                let middle_global = root_global.mul_transform(*middle);
+                let tip_global = middle_global.mul_transform(*tip);
+                (
+                    parent_global,
+                    root_global,
+                    middle_global,
+                    tip_global,
+                    target.translation,
+                )
+            }
+            _ => continue,
+        };
+        let (root_rotation, middle_rotation) = match solve_two_bone(
+            root_global.translation,
+            middle_global.translation,
+            tip_global.translation,
+            target,
+        ) {
+            Some(rotations) => rotations,
+            None => continue,
+        };
+
+        // Back into the local space of each joint's parent.
+        let root_world = root_rotation * root_global.rotation;
+        let middle_world = root_rotation * middle_rotation * middle_global.rotation;
+        let root_local = parent_global.rotation.inverse() * root_world;
+        let middle_local = root_world.inverse() * middle_world;
+        for (joint, solved) in [(root, root_local), (middle, middle_local)] {
+            if let Ok(mut transform) = transforms.get_mut(joint) {
+                transform.rotation = transform.rotation.slerp(solved, weight).normalize();
+            }
+        }
+    }
+}
+
+/// Computes the world space transform of an entity from its [`Transform`] and
+/// those of its ancestors.
+fn global_transform(
+    entity: Entity,
+    transforms: &Query<&mut Transform>,
+    parents: &Query<&Parent>,
+) -> Option<Transform> {
+    let mut global = *transforms.get(entity).ok()?;
+    let mut current = entity;
+    while let Ok(parent) = parents.get(current) {
+        current = parent.0;
+        match transforms.get(current) {
+            Ok(transform) => global = transform.mul_transform(global),
+            Err(_) => break,
+        }
+    }
+    Some(global)
+}
+
+/// Solves a two-bone chain with joints at `root`, `middle` and `tip`, all in
+/// world space. Returns the world space rotations to apply to the root joint
+/// and to the middle joint, before the root's, for the tip to reach `target`,
+/// or as close to it as the chain allows.
+fn solve_two_bone(root: Vec3, middle: Vec3, tip: Vec3, target: Vec3) -> Option<(Quat, Quat)> {
+    let upper = (middle - root).length();
+    let lower = (tip - middle).length();
+    let to_target = target - root;
+    if upper < IK_EPSILON || lower < IK_EPSILON || to_target.length() < IK_EPSILON {
+        return None;
+    }
+    // Unreachable targets stretch the chain as far as it goes.
+    let distance = to_target.length().clamp(
+        (upper - lower).abs() + IK_EPSILON,
+        upper + lower - IK_EPSILON,
+    );
+
+    // Bend the middle joint so the tip is `distance` away from the root.
+    let (to_root, to_tip) = (root - middle, tip - middle);
+    let mut axis = to_root.cross(to_tip);
+    // Straight chains bend in the plane of the target, or in any plane at all.
+    for fallback in [to_target, Vec3::Y, Vec3::X] {
+        if axis.length_squared() > IK_EPSILON * IK_EPSILON {
+            break;
+        }
+        axis = to_root.cross(fallback);
+    }
+    let angle = to_root
+        .normalize()
+        .dot(to_tip.normalize())
+        .clamp(-1.0, 1.0)
+        .acos();
+    let cos_solved = (upper * upper + lower * lower - distance * distance) / (2.0 * upper * lower);
+    let solved = cos_solved.clamp(-1.0, 1.0).acos();
+    let bend = Quat::from_axis_angle(axis.normalize(), solved - angle);
+
+    // Then swing the root joint so the tip points at the target.
+    let bent_tip = middle + bend * to_tip;
+    let swing = Quat::from_rotation_arc((bent_tip - root).normalize(), to_target.normalize());
+    Some((swing, bend))
+}
@@ -5,12 +5,27 @@ extern crate static_assertions;
 mod animatable;
 pub mod clip;
 pub mod curve;
+pub mod diagnostics;
 pub mod graph;
+pub mod ik;
 pub mod path;
+pub mod retarget;
+pub mod socket;
+#[cfg(feature = "sprite")]
+pub mod sprite;
+pub mod target;
+#[cfg(feature = "test_utils")]
+pub mod test_utils;
+#[cfg(feature = "ui")]
+pub mod ui;
 mod util;
 
 pub mod prelude {
-    pub use crate::{clip::AnimationClip, curve::Curve, graph::AnimationGraph};
+    pub use crate::{
+        clip::AnimationClip,
+        curve::Curve,
+        graph::{hierarchy::AnimationGraphBundle, AnimationGraph, ClipId, NodeId},
+    };
 }
 
 use crate::prelude::*;
@@ -18,47 +33,544 @@ pub use animatable::*;
 use bevy_app::prelude::*;
 use bevy_asset::prelude::*;
 use bevy_ecs::prelude::*;
+use bevy_log::{info_span, warn};
+use bevy_tasks::ComputeTaskPool;
 use bevy_transform::TransformSystem;
+use diagnostics::AnimationDiagnostics;
+use std::time::Instant;
+
+/// How many graphs each task evaluates in [`evaluate_graph_system`].
+const EVALUATE_BATCH_SIZE: usize = 4;
 
 #[derive(Clone, Debug, SystemLabel, PartialEq, Eq, Hash)]
 pub enum AnimationSystem {
+    ClipReload,
+    GraphTransition,
+    GraphLod,
     GraphEvaluation,
     GraphHierarchyDirtyCheck,
     GraphHierarchyBind,
+    GraphAutoBind,
+    GraphBindingCleanup,
     GraphSamplingSkeletal,
     GraphSamplingGeneric,
+    GraphSamplingTyped,
+    GraphSamplingBuffer,
+    InverseKinematics,
+    TransformPropagation,
+    SocketAttachment,
+}
+
+/// Adds animation support to an [`App`].
+///
+/// By default, all of the animation systems are added to [`CoreStage::Update`].
+/// The stage can be changed with [`AnimationPlugin::in_stage`], and individual
+/// systems can be left out so that they can be added manually with custom
+/// ordering:
+///
+/// ```rust,ignore
+/// app.add_plugin(AnimationPlugin::default().in_stage(MyFixedStage).without_application());
+/// ```
+pub struct AnimationPlugin<S = CoreStage> {
+    stage: S,
+    enable_binding: bool,
+    enable_application: bool,
+    propagate_transforms: bool,
+}
+
+impl Default for AnimationPlugin {
+    fn default() -> Self {
+        Self {
+            stage: CoreStage::Update,
+            enable_binding: true,
+            enable_application: true,
+            propagate_transforms: false,
+        }
+    }
 }
 
-pub struct AnimationPlugin;
+impl AnimationPlugin {
+    /// Evaluates and applies graphs in [`CoreStage::PreUpdate`], and then
+    /// propagates the animated transforms, so that systems in
+    /// [`CoreStage::Update`] see the poses of the current frame. Useful when
+    /// gameplay depends on the positions of bones, like hit detection keyed
+    /// off of a hand.
+    ///
+    /// Graphs should be advanced before the stage runs, e.g. in
+    /// [`CoreStage::First`]. Typed components should be applied in the same
+    /// stage with [`TypedAnimationPlugin::in_stage`].
+    ///
+    /// [`TypedAnimationPlugin::in_stage`]: crate::graph::typed::TypedAnimationPlugin::in_stage
+    pub fn early() -> Self {
+        Self::default()
+            .in_stage(CoreStage::PreUpdate)
+            .with_transform_propagation()
+    }
+}
+
+impl<S: StageLabel + Clone> AnimationPlugin<S> {
+    /// Adds the animation systems to the provided stage instead.
+    pub fn in_stage<T: StageLabel + Clone>(self, stage: T) -> AnimationPlugin<T> {
+        AnimationPlugin {
+            stage,
+            enable_binding: self.enable_binding,
+            enable_application: self.enable_application,
+            propagate_transforms: self.propagate_transforms,
+        }
+    }
 
-impl Plugin for AnimationPlugin {
+    /// Propagates transforms at the end of the animation stage, right after
+    /// the graphs are applied, instead of waiting for
+    /// [`TransformSystem::TransformPropagate`]. Only needed when the stage
+    /// runs before [`CoreStage::PostUpdate`]. See [`AnimationPlugin::early`].
+    pub fn with_transform_propagation(mut self) -> Self {
+        self.propagate_transforms = true;
+        self
+    }
+
+    /// Skips adding [`dirty_hierarchy_system`], [`bind_hierarchy_system`] and
+    /// [`bind_auto_bound_graphs_system`].
+    ///
+    /// [`dirty_hierarchy_system`]: crate::graph::hierarchy::dirty_hierarchy_system
+    /// [`bind_hierarchy_system`]: crate::graph::hierarchy::bind_hierarchy_system
+    /// [`bind_auto_bound_graphs_system`]: crate::graph::hierarchy::bind_auto_bound_graphs_system
+    pub fn without_binding(mut self) -> Self {
+        self.enable_binding = false;
+        self
+    }
+
+    /// Skips adding [`animate_entities_system`] and
+    /// [`solve_ik_constraints_system`].
+    ///
+    /// [`animate_entities_system`]: crate::graph::application::animate_entities_system
+    /// [`solve_ik_constraints_system`]: crate::ik::solve_ik_constraints_system
+    pub fn without_application(mut self) -> Self {
+        self.enable_application = false;
+        self
+    }
+}
+
+impl<S: StageLabel + Clone> Plugin for AnimationPlugin<S> {
     fn build(&self, app: &mut App) {
         app.add_asset::<clip::AnimationClip>()
-            .add_system(evaluate_graph_system.label(AnimationSystem::GraphEvaluation))
-            .add_system(
+            .register_type::<clip::AnimationClip>()
+            .register_type::<AnimationGraph>()
+            .register_type::<graph::NodeId>()
+            .register_type::<graph::ClipId>()
+            .init_resource::<AnimationDiagnostics>()
+            .init_resource::<path::PathAliases>()
+            .add_system_to_stage(
+                CoreStage::First,
+                diagnostics::reset_animation_diagnostics_system,
+            )
+            // Graphs are swapped before anything else runs, so that the new
+            // graph is bound, evaluated and applied in the same frame.
+            .add_system_to_stage(
+                self.stage.clone(),
+                graph::transition::transition_graphs_system
+                    .exclusive_system()
+                    .at_start()
+                    .label(AnimationSystem::GraphTransition),
+            )
+            .add_system_to_stage(
+                self.stage.clone(),
+                reload_modified_clips_system
+                    .label(AnimationSystem::ClipReload)
+                    .before(AnimationSystem::GraphHierarchyBind)
+                    .before(AnimationSystem::GraphEvaluation),
+            )
+            .add_system_to_stage(
+                self.stage.clone(),
+                restore_evicted_clips_system
+                    .label(AnimationSystem::ClipReload)
+                    .before(AnimationSystem::GraphHierarchyBind)
+                    .before(AnimationSystem::GraphEvaluation),
+            )
+            .add_system_to_stage(
+                self.stage.clone(),
+                graph::lod::apply_animation_lod_system.label(AnimationSystem::GraphLod),
+            )
+            .add_system_to_stage(
+                self.stage.clone(),
+                evaluate_graph_system
+                    .label(AnimationSystem::GraphEvaluation)
+                    .after(AnimationSystem::GraphLod),
+            );
+
+        // Record the counters once every animation system has run, wherever
+        // they were added.
+        #[cfg(feature = "diagnostics")]
+        {
+            app.init_resource::<bevy_diagnostic::Diagnostics>();
+            let mut diagnostics = app
+                .world
+                .get_resource_mut::<bevy_diagnostic::Diagnostics>()
+                .unwrap();
+            AnimationDiagnostics::register(&mut diagnostics);
+            app.add_system_to_stage(
+                CoreStage::Last,
+                diagnostics::record_animation_diagnostics_system,
+            );
+        }
+
+        // Register the sprite components so clips can animate them via reflection.
+        #[cfg(feature = "sprite")]
+        app.register_type::<bevy_sprite::Sprite>()
+            .register_type::<bevy_sprite::TextureAtlasSprite>();
+
+        // Register the UI components so clips can animate them via reflection.
+        #[cfg(feature = "ui")]
+        app.register_type::<bevy_ui::Style>()
+            .register_type::<bevy_ui::UiColor>();
+
+        if self.enable_binding {
+            app.add_system_to_stage(
+                self.stage.clone(),
                 graph::hierarchy::dirty_hierarchy_system
                     .label(AnimationSystem::GraphHierarchyDirtyCheck)
                     .after(TransformSystem::ParentUpdate),
             )
-            .add_system(
+            .add_system_to_stage(
+                self.stage.clone(),
                 graph::hierarchy::bind_hierarchy_system
                     .label(AnimationSystem::GraphHierarchyBind)
-                    .after(AnimationSystem::GraphHierarchyDirtyCheck),
+                    .after(AnimationSystem::GraphHierarchyDirtyCheck)
+                    .before(AnimationSystem::GraphEvaluation),
             )
-            .add_system(
+            // Graphs added after the animation systems ran are still bound and
+            // applied in the same frame, before their transforms propagate.
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                graph::hierarchy::bind_auto_bound_graphs_system
+                    .exclusive_system()
+                    .at_start()
+                    .label(AnimationSystem::GraphAutoBind),
+            );
+        }
+
+        // Removed graphs are only visible until the end of the frame, so clean
+        // up their bindings late in the frame regardless of the animation stage.
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            graph::application::cleanup_bindings_system.label(AnimationSystem::GraphBindingCleanup),
+        );
+
+        // Sockets follow the final propagated transforms, so they're always
+        // attached at the end of the frame regardless of the animation stage.
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            socket::attach_sockets_system
+                .label(AnimationSystem::SocketAttachment)
+                .after(TransformSystem::TransformPropagate),
+        );
+
+        if self.enable_application {
+            app.add_system_to_stage(
+                self.stage.clone(),
+                graph::pose::write_animated_poses_system
+                    .label(AnimationSystem::GraphSamplingBuffer)
+                    .after(AnimationSystem::GraphEvaluation),
+            );
+            // Exclusive systems do not respect ordering relative to parallel
+            // systems, so run at the end of the stage to ensure graphs have
+            // been evaluated and bound by the time they are applied.
+            app.add_system_to_stage(
+                self.stage.clone(),
                 graph::application::animate_entities_system
                     .exclusive_system()
+                    .at_end()
                     .label(AnimationSystem::GraphSamplingGeneric)
                     .after(AnimationSystem::GraphHierarchyBind)
                     .after(AnimationSystem::GraphEvaluation)
                     .before(TransformSystem::TransformPropagate),
+            )
+            // IK is layered on top of the applied pose.
+            .add_system_to_stage(
+                self.stage.clone(),
+                ik::solve_ik_constraints_system
+                    .exclusive_system()
+                    .at_end()
+                    .label(AnimationSystem::InverseKinematics)
+                    .after(AnimationSystem::GraphSamplingGeneric)
+                    .before(TransformSystem::TransformPropagate),
             );
+        }
+
+        // Propagation in PostUpdate still runs later, but only has to update
+        // the transforms changed since.
+        if self.propagate_transforms {
+            app.add_system_to_stage(
+                self.stage.clone(),
+                bevy_transform::transform_propagate_system::transform_propagate_system
+                    .exclusive_system()
+                    .at_end()
+                    .label(AnimationSystem::TransformPropagation)
+                    .after(AnimationSystem::GraphSamplingTyped)
+                    .after(AnimationSystem::GraphSamplingGeneric)
+                    .after(AnimationSystem::InverseKinematics),
+            );
+        }
     }
 }
 
 /// Evaluates all altered [`AnimationGraph`]s and updates it's internal state.
-pub fn evaluate_graph_system(mut graphs: Query<&mut AnimationGraph, Changed<AnimationGraph>>) {
+/// Graphs waiting on their update interval are skipped.
+///
+/// Graphs only mutate their own state while evaluating, so they are evaluated
+/// in parallel.
+pub fn evaluate_graph_system(
+    mut graphs: Query<&mut AnimationGraph, Changed<AnimationGraph>>,
+    task_pool: Res<ComputeTaskPool>,
+    mut diagnostics: ResMut<AnimationDiagnostics>,
+) {
+    let _span = info_span!("evaluate_animation_graphs").entered();
+    let start = Instant::now();
+    graphs.par_for_each_mut(&task_pool, EVALUATE_BATCH_SIZE, |mut graph| {
+        if !graph.is_update_skipped() {
+            graph.evaluate();
+        }
+    });
+    // Summed afterwards rather than by the tasks, so the counters don't need
+    // to be shared between threads.
+    for graph in graphs.iter().filter(|graph| !graph.is_update_skipped()) {
+        let stats = graph.stats();
+        diagnostics.graphs_evaluated += 1;
+        diagnostics.nodes_visited += stats.nodes_visited;
+        diagnostics.active_clips += stats.active_clips;
+    }
+    diagnostics.evaluation_time += start.elapsed();
+}
+
+/// Reloads the clips of every [`AnimationGraph`] that were added from a
+/// modified [`AnimationClip`] asset, keeping each clip's time and weight.
+/// See [`AnimationGraph::reload_clip`].
+pub fn reload_modified_clips_system(
+    mut events: EventReader<AssetEvent<AnimationClip>>,
+    clips: Res<Assets<AnimationClip>>,
+    mut graphs: Query<&mut AnimationGraph>,
+) {
+    for event in events.iter() {
+        let handle = match event {
+            AssetEvent::Modified { handle } => handle,
+            _ => continue,
+        };
+        let clip = match clips.get(handle) {
+            Some(clip) => clip,
+            None => continue,
+        };
+        for mut graph in graphs.iter_mut() {
+            // Avoid marking unrelated graphs as changed.
+            if !graph.references_clip_asset(handle) {
+                continue;
+            }
+            if let Err(err) = graph.reload_clip_asset(handle, clip) {
+                warn!("Failed to reload an animation clip: {}", err);
+            }
+        }
+    }
+}
+
+/// Restores the curves of the evicted clips of every [`AnimationGraph`] that
+/// were given a weight again. See [`AnimationGraph::add_lazy_clip_asset`].
+pub fn restore_evicted_clips_system(
+    clips: Res<Assets<AnimationClip>>,
+    mut graphs: Query<&mut AnimationGraph>,
+) {
     for mut graph in graphs.iter_mut() {
-        graph.evaluate();
+        // Avoid marking graphs without requested clips as changed.
+        if !graph.has_requested_clips() {
+            continue;
+        }
+        if let Err(err) = graph.restore_evicted_clips(&clips) {
+            warn!("Failed to restore an evicted animation clip: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{curve::CurveFixed, path::PropertyPath};
+    use bevy_asset::AssetPlugin;
+    use bevy_core::Name;
+    use bevy_ecs::schedule::ShouldRun;
+    use bevy_reflect::{Reflect, TypeRegistryArc};
+    use bevy_tasks::{ComputeTaskPool, IoTaskPool, TaskPool};
+    use bevy_transform::prelude::Children;
+    use graph::application::BoneBinding;
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Test {
+        a: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, StageLabel)]
+    struct FixedStage;
+
+    fn every_other_frame(mut frame: Local<u32>) -> ShouldRun {
+        *frame += 1;
+        if *frame % 2 == 1 {
+            ShouldRun::Yes
+        } else {
+            ShouldRun::No
+        }
+    }
+
+    fn advance_graphs_system(mut graphs: Query<&mut AnimationGraph>) {
+        for mut graph in graphs.iter_mut() {
+            graph.advance_time(1.0);
+        }
+    }
+
+    #[test]
+    pub fn test_plugin_in_custom_stage() {
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .insert_resource(ComputeTaskPool(TaskPool::new()))
+            .add_plugin(AssetPlugin)
+            .add_stage_after(
+                CoreStage::Update,
+                FixedStage,
+                SystemStage::parallel().with_run_criteria(every_other_frame),
+            )
+            .add_plugin(AnimationPlugin::default().in_stage(FixedStage))
+            .add_system(advance_graphs_system)
+            .register_type::<Test>();
+
+        let path = {
+            let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+            PropertyPath::parse(&registry.read(), "a@bevy_prototype_animation::test::Test.a")
+                .unwrap()
+        };
+        let clip = AnimationClip::builder()
+            .add_curve(
+                path,
+                CurveFixed::from_keyframes(1.0, (0..=10).map(|x| x as f32).collect()),
+            )
+            .build();
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        assert!(graph.add_input(graph::NodeId::ROOT, node).is_ok());
+
+        let bone = app
+            .world
+            .spawn()
+            .insert(Name::new("a"))
+            .insert(Test::default())
+            .id();
+        app.world
+            .spawn()
+            .insert(graph)
+            .insert(Children::with(&[bone]));
+
+        let mut values = Vec::new();
+        for _ in 0..6 {
+            app.update();
+            values.push(app.world.get::<Test>(bone).unwrap().a);
+        }
+        assert_eq!(values, vec![1.0, 1.0, 3.0, 3.0, 5.0, 5.0]);
+    }
+
+    #[test]
+    pub fn test_despawned_graph_bindings_are_removed() {
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .insert_resource(ComputeTaskPool(TaskPool::new()))
+            .add_plugin(AssetPlugin)
+            .add_plugin(AnimationPlugin::default())
+            .register_type::<Test>();
+
+        let path = {
+            let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+            PropertyPath::parse(&registry.read(), "a@bevy_prototype_animation::test::Test.a")
+                .unwrap()
+        };
+        let clip = AnimationClip::builder()
+            .add_curve(path, CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0]))
+            .build();
+        let mut spawn_graph = || {
+            let mut graph = AnimationGraph::new();
+            let node = graph.add_clip(&clip).unwrap();
+            assert!(graph.add_input(graph::NodeId::ROOT, node).is_ok());
+            let bone = app
+                .world
+                .spawn()
+                .insert(Name::new("a"))
+                .insert(Test::default())
+                .id();
+            app.world
+                .spawn()
+                .insert(graph)
+                .insert(Children::with(&[bone]))
+                .id()
+        };
+        let despawned = spawn_graph();
+        // Keep another graph alive so the graphs query isn't empty.
+        spawn_graph();
+
+        app.update();
+        let mut bindings = app.world.query::<&BoneBinding>();
+        assert_eq!(bindings.iter(&app.world).count(), 2);
+
+        app.world.despawn(despawned);
+        app.update();
+        let bindings: Vec<_> = bindings.iter(&app.world).collect();
+        assert_eq!(bindings.len(), 1);
+        assert_ne!(bindings[0].graph(), despawned);
+    }
+
+    #[test]
+    pub fn test_clips_and_graphs_are_registered() {
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .insert_resource(ComputeTaskPool(TaskPool::new()))
+            .add_plugin(AssetPlugin)
+            .add_plugin(AnimationPlugin::default())
+            .register_type::<Test>();
+
+        {
+            let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+            let registry = registry.read();
+            for name in [
+                std::any::type_name::<AnimationClip>(),
+                std::any::type_name::<AnimationGraph>(),
+            ] {
+                assert!(registry.get_with_name(name).is_some(), "{}", name);
+            }
+            // Clips must not be able to animate graphs.
+            let graph = registry.get(std::any::TypeId::of::<AnimationGraph>());
+            assert!(graph.unwrap().data::<ReflectComponent>().is_none());
+        }
+
+        let path = {
+            let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+            PropertyPath::parse(&registry.read(), "a@bevy_prototype_animation::test::Test.a")
+                .unwrap()
+        };
+        let clip = AnimationClip::builder()
+            .add_curve(
+                path,
+                CurveFixed::from_keyframes(2.0, vec![0.0f32, 1.0, 2.0]),
+            )
+            .build();
+        let mut clips = app
+            .world
+            .get_resource_mut::<Assets<AnimationClip>>()
+            .unwrap();
+        let handle = clips.add(clip.clone());
+        let stored = clips.get(&handle).unwrap();
+        assert_eq!(stored.duration(), clip.duration());
+        assert_eq!(
+            stored.properties().collect::<Vec<_>>(),
+            clip.properties().collect::<Vec<_>>()
+        );
+
+        // Reflected clips are cloned as values.
+        let cloned = stored.clone_value();
+        assert_eq!(
+            cloned.downcast_ref::<AnimationClip>().unwrap().duration(),
+            clip.duration()
+        );
     }
 }
@@ -1,5 +1,8 @@
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::num::ParseIntError;
+use std::sync::Arc;
 
 use bevy_reflect::{Reflect, ReflectMut, ReflectRef};
 use thiserror::Error;
@@ -35,9 +38,40 @@ pub enum ReflectPathError<'a> {
 /// A path to a field within a type. Can be used like [`Reflect::GetPath`] functions to get
 /// references to the inner fields of a type.
 ///
+/// Clones of a path share the same storage.
+///
 /// [`GetPath`]: bevy_reflect::GetPath
-#[derive(Clone, Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
-pub struct FieldPath(Box<[(Access, usize)]>);
+#[derive(Clone, Debug)]
+pub struct FieldPath(Arc<[(Access, usize)]>);
+
+// The indices are only used for error reporting, and are ignored when
+// comparing or hashing paths.
+impl PartialEq for FieldPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for FieldPath {}
+
+impl Hash for FieldPath {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.len().hash(state);
+        self.iter().for_each(|access| access.hash(state));
+    }
+}
+
+impl PartialOrd for FieldPath {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FieldPath {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
 
 impl FieldPath {
     /// Parses a [`FieldPath`] from a string. For the exact format, see [`GetPath`].
@@ -47,7 +81,44 @@ impl FieldPath {
         for (access, idx) in PathParser::new(string) {
             parts.push((access?.to_owned(), idx));
         }
-        Ok(Self(parts.into_boxed_slice()))
+        Ok(Self(parts.into()))
+    }
+
+    /// A path to the root value itself, with no fields.
+    pub fn root() -> Self {
+        Self(Arc::new([]))
+    }
+
+    /// Checks if the path refers to the root value itself.
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over the accesses in the path, starting from the root.
+    pub fn iter(&self) -> impl Iterator<Item = &Access> {
+        self.0.iter().map(|(access, _)| access)
+    }
+
+    /// Appends an access to the end of the path.
+    pub fn push(&mut self, access: Access) {
+        // Errors report where in the path's string form they occurred.
+        let index = if self.is_root() {
+            0
+        } else {
+            self.to_string().len() + 1
+        };
+        let mut parts = self.0.to_vec();
+        parts.push((access, index));
+        self.0 = parts.into();
+    }
+
+    /// Removes the last access from the path and returns it, or `None` if the
+    /// path is the root.
+    pub fn pop(&mut self) -> Option<Access> {
+        let mut parts = self.0.to_vec();
+        let access = parts.pop().map(|(access, _)| access);
+        self.0 = parts.into();
+        access
     }
 
     /// Gets a read-only reference of given field.
@@ -80,7 +151,7 @@ impl FieldPath {
 impl fmt::Display for FieldPath {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for (idx, (access, _)) in self.0.iter().enumerate() {
-            if idx != 0 {
+            if idx != 0 && !matches!(access, Access::ListIndex(_)) {
                 f.write_str(".")?;
             }
             match access {
@@ -101,10 +172,14 @@ impl fmt::Display for FieldPath {
     }
 }
 
+/// A single step of a [`FieldPath`].
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
-enum Access {
+pub enum Access {
+    /// A named field of a struct: `.field`.
     Field(String),
+    /// A field of a tuple struct: `.0`.
     TupleIndex(usize),
+    /// An element of a list: `[0]`.
     ListIndex(usize),
 }
 
@@ -0,0 +1,202 @@
+use super::{AccessPath, EntityPath, PropertyPath};
+use bevy_utils::HashMap;
+
+/// The id of an [`EntityPath`] in a [`PathInterner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EntityPathId(u32);
+
+impl EntityPathId {
+    /// The index of the path in the order it was interned.
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// The id of an [`AccessPath`] in a [`PathInterner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AccessPathId(u32);
+
+impl AccessPathId {
+    /// The index of the path in the order it was interned.
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Maps [`EntityPath`]s and [`AccessPath`]s to small integer ids, in the
+/// order they were first interned.
+///
+/// Every [`AnimationGraph`] interns the paths of its bones, so looking up a
+/// bone by id is a plain index. An interner can also be shared by a set of
+/// clips with [`AnimationClip::share_paths`], so that clips parsed
+/// separately for the same skeleton share the storage of their paths.
+///
+/// Interned paths are kept until the interner is dropped.
+///
+/// [`AnimationGraph`]: crate::graph::AnimationGraph
+/// [`AnimationClip::share_paths`]: crate::clip::AnimationClip::share_paths
+#[derive(Debug, Default, Clone)]
+pub struct PathInterner {
+    entity_ids: HashMap<EntityPath, EntityPathId>,
+    entities: Vec<EntityPath>,
+    access_ids: HashMap<AccessPath, AccessPathId>,
+    accesses: Vec<AccessPath>,
+}
+
+impl PathInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the id of an entity path, interning it if it wasn't already.
+    pub fn intern_entity(&mut self, path: &EntityPath) -> EntityPathId {
+        if let Some(id) = self.entity_ids.get(path) {
+            return *id;
+        }
+        let id = EntityPathId(self.entities.len() as u32);
+        self.entity_ids.insert(path.clone(), id);
+        self.entities.push(path.clone());
+        id
+    }
+
+    /// Gets the id of an entity path, if it was interned.
+    #[inline]
+    pub fn entity_id(&self, path: &EntityPath) -> Option<EntityPathId> {
+        self.entity_ids.get(path).copied()
+    }
+
+    /// Gets the interned entity path with an id.
+    #[inline]
+    pub fn entity(&self, id: EntityPathId) -> Option<&EntityPath> {
+        self.entities.get(id.index())
+    }
+
+    /// Gets the id of an access path, interning it if it wasn't already.
+    /// Paths differing only in their priority share the same id.
+    pub fn intern_access(&mut self, path: &AccessPath) -> AccessPathId {
+        if let Some(id) = self.access_ids.get(path) {
+            return *id;
+        }
+        let id = AccessPathId(self.accesses.len() as u32);
+        self.access_ids.insert(path.clone(), id);
+        self.accesses.push(path.clone());
+        id
+    }
+
+    /// Gets the id of an access path, if it was interned.
+    #[inline]
+    pub fn access_id(&self, path: &AccessPath) -> Option<AccessPathId> {
+        self.access_ids.get(path).copied()
+    }
+
+    /// Gets the interned access path with an id. Its priority is the one of
+    /// the path it was first interned from.
+    #[inline]
+    pub fn access(&self, id: AccessPathId) -> Option<&AccessPath> {
+        self.accesses.get(id.index())
+    }
+
+    /// Reserves space for at least `additional` more entity paths.
+    pub fn reserve_entities(&mut self, additional: usize) {
+        self.entity_ids.reserve(additional);
+        self.entities.reserve(additional);
+    }
+
+    /// The number of distinct entity paths interned.
+    #[inline]
+    pub fn entity_count(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// The number of distinct access paths interned.
+    #[inline]
+    pub fn access_count(&self) -> usize {
+        self.accesses.len()
+    }
+
+    /// Interns an entity path, returning a copy of it that shares the storage
+    /// of the interned path.
+    pub fn share_entity(&mut self, path: &EntityPath) -> EntityPath {
+        let id = self.intern_entity(path);
+        self.entities[id.index()].clone()
+    }
+
+    /// Interns an access path, returning a copy of it that shares the storage
+    /// of the interned path. The copy keeps the priority of `path`.
+    pub fn share_access(&mut self, path: &AccessPath) -> AccessPath {
+        let id = self.intern_access(path);
+        AccessPath {
+            priority: path.priority,
+            typed: path.typed,
+            ..self.accesses[id.index()].clone()
+        }
+    }
+
+    /// Interns both parts of a property path, like
+    /// [`share_entity`](Self::share_entity) and
+    /// [`share_access`](Self::share_access).
+    pub fn share_property(&mut self, path: &PropertyPath) -> PropertyPath {
+        PropertyPath::from_parts(
+            self.share_entity(path.entity()),
+            self.share_access(path.access()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy_transform::prelude::Transform;
+
+    #[test]
+    pub fn test_intern_assigns_ids_in_order() {
+        let mut interner = PathInterner::new();
+        let hips: EntityPath = "root/hips".parse().unwrap();
+        let spine: EntityPath = "root/hips/spine".parse().unwrap();
+        assert_eq!(interner.intern_entity(&hips).index(), 0);
+        assert_eq!(interner.intern_entity(&spine).index(), 1);
+        // Parsed separately, but equal to an interned path.
+        let id = interner.intern_entity(&"root/hips".parse().unwrap());
+        assert_eq!(id.index(), 0);
+        assert_eq!(interner.entity(id), Some(&hips));
+        assert_eq!(interner.entity_count(), 2);
+        assert_eq!(interner.entity_id(&"root".parse().unwrap()), None);
+
+        let translation = AccessPath::of::<Transform>("translation").unwrap();
+        let id = interner.intern_access(&translation);
+        assert_eq!(
+            interner.intern_access(&translation.clone().with_priority(2)),
+            id
+        );
+        assert_eq!(interner.access(id), Some(&translation));
+        assert_eq!(interner.access_count(), 1);
+    }
+
+    #[test]
+    pub fn test_shared_paths_share_storage() {
+        let mut interner = PathInterner::new();
+        let path = PropertyPath::from_parts(
+            "root/hips".parse().unwrap(),
+            AccessPath::of::<Transform>("translation").unwrap(),
+        );
+        let first = interner.share_property(&path);
+        let parsed = PropertyPath::from_parts(
+            "root/hips".parse().unwrap(),
+            AccessPath::of::<Transform>("translation")
+                .unwrap()
+                .with_priority(1),
+        );
+        let second = interner.share_property(&parsed);
+        assert_eq!(first, second);
+        assert!(first.entity().ptr_eq(second.entity()));
+        assert!(!second.entity().ptr_eq(parsed.entity()));
+        assert!(std::ptr::eq(
+            first.access().component_name(),
+            second.access().component_name()
+        ));
+        assert_eq!(first.access().priority(), 0);
+        assert_eq!(second.access().priority(), 1);
+    }
+}
@@ -1,13 +1,22 @@
+use crate::graph::typed::{ReflectTypedComponent, TypedComponent};
 use bevy_core::Name;
-use bevy_reflect::TypeRegistry;
+use bevy_ecs::{component::Component, system::Resource};
+use bevy_reflect::{FromType, Reflect, TypeRegistration, TypeRegistry};
+use bevy_utils::HashMap;
+use once_cell::sync::Lazy;
 use std::any::TypeId;
 use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
 use std::convert::Infallible;
-use std::fmt;
+use std::fmt::{self, Write};
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
+use std::sync::Arc;
 
 mod field;
-pub use field::{FieldPath, ReflectPathError};
+mod interner;
+pub use field::{Access, FieldPath, ReflectPathError};
+pub use interner::{AccessPathId, EntityPathId, PathInterner};
 
 /// A named path through a hierarchy of entities.
 ///
@@ -16,20 +25,48 @@ pub use field::{FieldPath, ReflectPathError};
 /// `root//hips`.
 ///
 /// This type comes pre-split into individual levels, unlike a normal string.
-#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+///
+/// An empty path, parsed from `""`, refers to the root entity itself: the
+/// entity with the [`AnimationGraph`](crate::graph::AnimationGraph). See
+/// [`EntityPath::root`].
+///
+/// Entity paths share their parts, so cloning is cheap. A [`PathInterner`]
+/// deduplicates paths parsed separately, so many clips targeting the same
+/// hierarchy do not duplicate their paths. The hash of the parts is computed
+/// once when the path is built, so hashing a path only hashes an integer.
+#[derive(Clone, Debug)]
 pub struct EntityPath {
-    parts: Box<[Name]>,
+    parts: Arc<[Name]>,
+    hash: u64,
 }
 
 impl EntityPath {
     const SEPERATOR: &'static str = "/";
 
     pub fn from_parts(parts: Vec<Name>) -> Self {
+        // The default hasher has fixed keys, so equal paths always have the
+        // same hash.
+        let mut hasher = DefaultHasher::new();
+        parts.hash(&mut hasher);
         Self {
-            parts: parts.into_boxed_slice(),
+            hash: hasher.finish(),
+            parts: parts.into(),
         }
     }
 
+    /// The empty path, referring to the root entity of a hierarchy rather
+    /// than one of its descendants. Displayed as `""`, so property paths to
+    /// the root take the form of `"@Type.field"`.
+    pub fn root() -> Self {
+        Self::from_parts(Vec::new())
+    }
+
+    /// Whether this is the [`root`](Self::root) path.
+    #[inline]
+    pub fn is_root(&self) -> bool {
+        self.parts.is_empty()
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &Name> {
         self.parts.iter()
     }
@@ -43,6 +80,44 @@ impl EntityPath {
     pub fn is_empty(&self) -> bool {
         self.parts.is_empty()
     }
+
+    /// Whether both paths share the same parts, rather than only being equal.
+    #[inline]
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.parts, &other.parts)
+    }
+}
+
+impl PartialEq for EntityPath {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || (self.hash == other.hash && self.parts == other.parts)
+    }
+}
+
+impl Eq for EntityPath {}
+
+impl Hash for EntityPath {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl PartialOrd for EntityPath {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EntityPath {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self == other {
+            Ordering::Equal
+        } else {
+            self.parts.cmp(&other.parts)
+        }
+    }
 }
 
 impl FromStr for EntityPath {
@@ -75,33 +150,249 @@ impl fmt::Display for EntityPath {
 /// This represents a String-like path taking the form of "root.a.b.c.d".
 ///
 /// This type comes pre-split into individual levels, unlike a normal string.
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+///
+/// Paths are ordered by component, then by field path, with every path
+/// ordered before the paths to its fields. The tracks of a bone are applied
+/// in this order, so a track animating a whole component is applied before
+/// the tracks animating its fields, which refine it. Tracks can be reordered
+/// with [priorities](Self::with_priority).
+///
+/// Paths may also [target](AccessTarget) a resource, or an asset referenced
+/// by a component, instead of a component. Their text form is then prefixed
+/// by `res:` or `asset:`, like `res:game::Fog.density` or
+/// `asset:Handle<ColorMaterial>#color`.
+///
+/// Paths to components registered with a
+/// [`TypedAnimationPlugin`](crate::graph::typed::TypedAnimationPlugin) are
+/// animated by typed tracks when parsed against the same registry, or built
+/// with [`of_typed`](Self::of_typed).
+#[derive(Clone, Debug)]
 pub struct AccessPath {
     component_type_id: TypeId,
-    component_name: String,
+    component_name: Arc<str>,
+    target: AccessTarget,
     field_path: FieldPath,
+    priority: i32,
+    typed: Option<ReflectTypedComponent>,
+}
+
+/// What the type of an [`AccessPath`] names, and what its field path is
+/// relative to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AccessTarget {
+    /// A component of the bound entity.
+    Component,
+    /// A resource, registered with [`ReflectResource`]. Resources aren't tied
+    /// to an entity, so their paths are usually on the root of the graph.
+    ///
+    /// [`ReflectResource`]: crate::target::ReflectResource
+    Resource,
+    /// An asset, referenced by the [`Handle`] at the given field path of a
+    /// component of the bound entity. The handle's type must be registered
+    /// with [`ReflectHandle`]. The path's field path is into the asset.
+    ///
+    /// [`Handle`]: bevy_asset::Handle
+    /// [`ReflectHandle`]: crate::target::ReflectHandle
+    Asset(FieldPath),
+}
+
+impl Default for AccessTarget {
+    fn default() -> Self {
+        Self::Component
+    }
 }
 
 impl AccessPath {
     const SEPERATOR: &'static str = ".";
+    const RESOURCE_PREFIX: &'static str = "res:";
+    const ASSET_PREFIX: &'static str = "asset:";
+    const ASSET_SEPERATOR: char = '#';
 
+    /// Parses an [`AccessPath`] from a component name followed by an optional
+    /// field path. A path without any field refers to the whole component.
+    ///
+    /// The component can be named by its full type name, or by its short name
+    /// if no other registered type shares it, like `Transform.translation`.
+    /// Either way, the path stores and displays the full name.
+    ///
+    /// Resources are prefixed by `res:`, like `res:Fog.density`. Assets are
+    /// prefixed by `asset:`, followed by the path to their handle and the
+    /// field path within the asset, separated by a "#", like
+    /// `asset:Handle<ColorMaterial>#color`.
     pub fn parse<'a>(
         registry: &'a TypeRegistry,
         path: &'a str,
     ) -> Result<Self, ParsePathError<'a>> {
-        let (component, field) = path
-            .split_once(Self::SEPERATOR)
-            .ok_or(ParsePathError::NoComponentName)?;
-        let registration = registry
-            .get_with_name(component)
-            .ok_or(ParsePathError::InvalidComponentType)?;
+        Self::parse_with_aliases(registry, &NO_ALIASES, path)
+    }
+
+    /// Parses an [`AccessPath`] like [`parse`](Self::parse), replacing the
+    /// component name with its alias in `aliases`, if it has one. Aliases take
+    /// precedence over registered type names.
+    pub fn parse_with_aliases<'a>(
+        registry: &'a TypeRegistry,
+        aliases: &'a PathAliases,
+        path: &'a str,
+    ) -> Result<Self, ParsePathError<'a>> {
+        let (target, path, asset_field) = split_target(path)?;
+        let (component, field) = path.split_once(Self::SEPERATOR).unwrap_or((path, ""));
+        if component.is_empty() {
+            return Err(ParsePathError::NoComponentName);
+        }
+        let mut field_path = FieldPath::parse(field)?;
+        let component = match aliases.get(component) {
+            Some(alias) => {
+                let (component, prefix) = alias.split_once(Self::SEPERATOR).unwrap_or((alias, ""));
+                let mut prefixed = FieldPath::parse(prefix)?;
+                for access in field_path.iter() {
+                    prefixed.push(access.clone());
+                }
+                field_path = prefixed;
+                component
+            }
+            None => component,
+        };
+        let registration = find_component(registry, component)?;
+        // For assets, the field path parsed so far leads to the handle.
+        let (target, field_path) = match asset_field {
+            Some(asset_field) => (
+                AccessTarget::Asset(field_path),
+                FieldPath::parse(asset_field)?,
+            ),
+            None => (target, field_path),
+        };
+        let typed = match target {
+            AccessTarget::Component => registration.data::<ReflectTypedComponent>().copied(),
+            _ => None,
+        };
         Ok(Self {
             component_type_id: registration.type_id(),
-            component_name: component.to_string(),
+            component_name: registration.name().into(),
+            target,
+            field_path,
+            priority: 0,
+            typed,
+        })
+    }
+
+    /// Constructs an [`AccessPath`] to a field of the component type `T`,
+    /// without looking the type up in a [`TypeRegistry`]. An empty `field`
+    /// refers to the whole component.
+    ///
+    /// ```rust,ignore
+    /// let path = AccessPath::of::<Transform>("translation")?;
+    /// ```
+    pub fn of<T: Component + Reflect>(field: &str) -> Result<Self, ReflectPathError<'_>> {
+        Ok(Self {
+            component_type_id: TypeId::of::<T>(),
+            component_name: std::any::type_name::<T>().into(),
+            target: AccessTarget::Component,
             field_path: FieldPath::parse(field)?,
+            priority: 0,
+            typed: None,
+        })
+    }
+
+    /// Constructs an [`AccessPath`] to a field of the component type `C`, like
+    /// [`of`](Self::of), whose curves are animated by the typed track of `C`.
+    /// The component must still be registered with a
+    /// [`TypedAnimationPlugin`](crate::graph::typed::TypedAnimationPlugin) for
+    /// the track to be applied.
+    pub fn of_typed<C: TypedComponent>(field: &str) -> Result<Self, ReflectPathError<'_>> {
+        Ok(Self {
+            typed: Some(<ReflectTypedComponent as FromType<C>>::from_type()),
+            ..Self::of::<C>(field)?
         })
     }
 
+    /// Constructs an [`AccessPath`] to a field of the resource type `R`. An
+    /// empty `field` refers to the whole resource.
+    ///
+    /// ```rust,ignore
+    /// let path = AccessPath::of_resource::<Fog>("density")?;
+    /// ```
+    pub fn of_resource<R: Resource + Reflect>(field: &str) -> Result<Self, ReflectPathError<'_>> {
+        Ok(Self {
+            component_type_id: TypeId::of::<R>(),
+            component_name: std::any::type_name::<R>().into(),
+            target: AccessTarget::Resource,
+            field_path: FieldPath::parse(field)?,
+            priority: 0,
+            typed: None,
+        })
+    }
+
+    /// Constructs an [`AccessPath`] to a field of the asset referenced by the
+    /// handle at `handle_field` in the component type `T`. An empty
+    /// `asset_field` refers to the whole asset.
+    ///
+    /// ```rust,ignore
+    /// let path = AccessPath::of_asset::<Handle<ColorMaterial>>("", "color")?;
+    /// ```
+    pub fn of_asset<'a, T: Component + Reflect>(
+        handle_field: &'a str,
+        asset_field: &'a str,
+    ) -> Result<Self, ReflectPathError<'a>> {
+        Ok(Self {
+            component_type_id: TypeId::of::<T>(),
+            component_name: std::any::type_name::<T>().into(),
+            target: AccessTarget::Asset(FieldPath::parse(handle_field)?),
+            field_path: FieldPath::parse(asset_field)?,
+            priority: 0,
+            typed: None,
+        })
+    }
+
+    /// Constructs an [`AccessPath`] from it's constituent parts. `component_name`
+    /// should be the name the component type is registered with.
+    pub fn from_parts(
+        component_type_id: TypeId,
+        component_name: impl Into<String>,
+        field_path: FieldPath,
+    ) -> Self {
+        Self {
+            component_type_id,
+            component_name: component_name.into().into(),
+            target: AccessTarget::Component,
+            field_path,
+            priority: 0,
+            typed: None,
+        }
+    }
+
+    /// Changes what the path targets. The type of the path must then be a
+    /// resource, or a component holding the asset's handle.
+    pub fn with_target(mut self, target: AccessTarget) -> Self {
+        if target != AccessTarget::Component {
+            self.typed = None;
+        }
+        self.target = target;
+        self
+    }
+
+    /// Sets the priority of the path, which orders the tracks of a bone when
+    /// the default order isn't the one needed. Tracks are applied from the
+    /// lowest priority to the highest, and in the default order for the same
+    /// priority, so a track with a higher priority overrides the others on
+    /// the properties they both write. Paths have a priority of 0 by default.
+    ///
+    /// The priority isn't part of the path itself: it isn't compared, hashed
+    /// or displayed. When several clips animate the same property, the track
+    /// takes the highest priority any of them gives it.
+    ///
+    /// ```rust,ignore
+    /// // Written after the translation track, overriding it.
+    /// let path = AccessPath::of::<Transform>("")?.with_priority(1);
+    /// ```
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
     pub fn component_type_id(&self) -> TypeId {
         self.component_type_id
     }
@@ -110,19 +401,171 @@ impl AccessPath {
         self.component_name.as_ref()
     }
 
+    pub fn target(&self) -> &AccessTarget {
+        &self.target
+    }
+
+    /// Whether the path targets a component of the bound entity, rather than
+    /// a resource or an asset.
+    #[inline]
+    pub fn is_component(&self) -> bool {
+        self.target == AccessTarget::Component
+    }
+
     pub fn field_path(&self) -> &FieldPath {
         &self.field_path
     }
+
+    pub fn field_path_mut(&mut self) -> &mut FieldPath {
+        &mut self.field_path
+    }
+
+    /// The typed track registration of the path's component, if its curves
+    /// are routed into a typed track.
+    pub(crate) fn typed(&self) -> Option<&ReflectTypedComponent> {
+        self.typed.as_ref()
+    }
+}
+
+/// Splits the target prefix off of an access path. For assets, also splits
+/// off the field path within the asset.
+fn split_target<'e>(path: &str) -> Result<(AccessTarget, &str, Option<&str>), ParsePathError<'e>> {
+    if let Some(path) = path.strip_prefix(AccessPath::RESOURCE_PREFIX) {
+        Ok((AccessTarget::Resource, path, None))
+    } else if let Some(path) = path.strip_prefix(AccessPath::ASSET_PREFIX) {
+        let (handle, asset_field) = path
+            .split_once(AccessPath::ASSET_SEPERATOR)
+            .ok_or(ParsePathError::MissingAssetDelimiter)?;
+        Ok((
+            AccessTarget::Asset(FieldPath::root()),
+            handle,
+            Some(asset_field),
+        ))
+    } else {
+        Ok((AccessTarget::Component, path, None))
+    }
+}
+
+/// Looks up a component type by its full name, falling back to its short
+/// name.
+fn find_component<'r, 'a>(
+    registry: &'r TypeRegistry,
+    name: &'a str,
+) -> Result<&'r TypeRegistration, ParsePathError<'a>> {
+    if let Some(registration) = registry
+        .get_with_name(name)
+        .or_else(|| registry.get_with_short_name(name))
+    {
+        return Ok(registration);
+    }
+    let mut candidates: Vec<&'static str> = registry
+        .iter()
+        .filter(|registration| registration.short_name() == name)
+        .map(|registration| registration.name())
+        .collect();
+    if candidates.len() > 1 {
+        candidates.sort_unstable();
+        Err(ParsePathError::AmbiguousShortName { name, candidates })
+    } else {
+        Err(ParsePathError::InvalidComponentType)
+    }
+}
+
+/// Shorthand names for components and the fields within them, used when
+/// parsing paths with [`PropertyPath::parse_with_aliases`].
+///
+/// An alias replaces the component name of a path with another component
+/// name, optionally followed by fields. With the aliases below, both
+/// `hips@T.translation.x` and `hips@translation.x` parse to the same path as
+/// `hips@bevy_transform::components::transform::Transform.translation.x`.
+///
+/// ```rust,ignore
+/// let mut aliases = PathAliases::default();
+/// aliases
+///     .insert_component::<Transform>("T")
+///     .insert("translation", "Transform.translation");
+/// ```
+///
+/// The [`AnimationPlugin`](crate::AnimationPlugin) adds an empty set of
+/// aliases as a resource. Parsed paths always store the full component name,
+/// so they display the same regardless of the aliases they were parsed with.
+#[derive(Debug, Default, Clone)]
+pub struct PathAliases {
+    aliases: HashMap<String, String>,
+}
+
+/// The aliases used by [`PropertyPath::parse`], which has none.
+static NO_ALIASES: Lazy<PathAliases> = Lazy::new(PathAliases::default);
+
+impl PathAliases {
+    /// Adds an alias for a component name, optionally followed by fields,
+    /// like `"Transform.translation"`. The component can be named by its
+    /// short name. Replaces any previous alias with the same name.
+    pub fn insert(&mut self, alias: impl Into<String>, access: impl Into<String>) -> &mut Self {
+        self.aliases.insert(alias.into(), access.into());
+        self
+    }
+
+    /// Adds an alias for the component type `T`.
+    pub fn insert_component<T: Component>(&mut self, alias: impl Into<String>) -> &mut Self {
+        self.insert(alias, std::any::type_name::<T>())
+    }
+
+    /// Removes an alias, returning what it stood for.
+    pub fn remove(&mut self, alias: &str) -> Option<String> {
+        self.aliases.remove(alias)
+    }
+
+    /// Gets what an alias stands for, if it's defined.
+    pub fn get(&self, alias: &str) -> Option<&str> {
+        self.aliases.get(alias).map(String::as_str)
+    }
 }
 
 impl fmt::Display for AccessPath {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.target {
+            AccessTarget::Component => {}
+            AccessTarget::Resource => f.write_str(Self::RESOURCE_PREFIX)?,
+            AccessTarget::Asset(_) => f.write_str(Self::ASSET_PREFIX)?,
+        }
         f.write_str(self.component_name.as_ref())?;
-        f.write_str(Self::SEPERATOR)?;
+        if let AccessTarget::Asset(handle_field) = &self.target {
+            if !handle_field.is_root() {
+                f.write_str(Self::SEPERATOR)?;
+            }
+            handle_field.fmt(f)?;
+            f.write_char(Self::ASSET_SEPERATOR)?;
+        } else if !self.field_path.is_root() {
+            f.write_str(Self::SEPERATOR)?;
+        }
         self.field_path.fmt(f)
     }
 }
 
+// The priority only orders the application of tracks, and the typed
+// registration only routes curves, so both are ignored when comparing or
+// hashing paths.
+impl PartialEq for AccessPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.component_type_id == other.component_type_id
+            && self.component_name == other.component_name
+            && self.target == other.target
+            && self.field_path == other.field_path
+    }
+}
+
+impl Eq for AccessPath {}
+
+impl Hash for AccessPath {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.component_type_id.hash(state);
+        self.component_name.hash(state);
+        self.target.hash(state);
+        self.field_path.hash(state);
+    }
+}
+
 impl PartialOrd for AccessPath {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         // As the component name is going to be the same if the type ID
@@ -130,6 +573,7 @@ impl PartialOrd for AccessPath {
         Some(
             self.component_type_id
                 .cmp(&other.component_type_id)
+                .then_with(|| self.target.cmp(&other.target))
                 .then(self.field_path.cmp(&other.field_path)),
         )
     }
@@ -146,7 +590,8 @@ impl Ord for AccessPath {
 /// Each part of the full path is accessible separately.
 ///
 /// This represents a String-like path taking the form of "root/a/b/c/@droot.a.b.c.d".
-/// Each part of the path is delimited by a "@".
+/// Each part of the path is delimited by a "@". A path without any fields,
+/// like "root/a@droot", selects the whole component.
 #[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct PropertyPath {
     entity: EntityPath,
@@ -156,16 +601,41 @@ pub struct PropertyPath {
 impl PropertyPath {
     const SEPERATOR: char = '@';
 
+    /// Parses a [`PropertyPath`] from an entity path and an access path,
+    /// separated by a "@". See [`AccessPath::parse`].
     pub fn parse<'a>(
         registry: &'a TypeRegistry,
         path: &'a str,
+    ) -> Result<Self, ParsePathError<'a>> {
+        Self::parse_with_aliases(registry, &NO_ALIASES, path)
+    }
+
+    /// Parses a [`PropertyPath`] like [`parse`](Self::parse), expanding the
+    /// [`PathAliases`] in its access path.
+    pub fn parse_with_aliases<'a>(
+        registry: &'a TypeRegistry,
+        aliases: &'a PathAliases,
+        path: &'a str,
     ) -> Result<Self, ParsePathError<'a>> {
         let (entity, access) = path
             .split_once(Self::SEPERATOR)
             .ok_or(ParsePathError::MissingDelimiter)?;
         Ok(Self::from_parts(
             EntityPath::from_str(entity).unwrap(),
-            AccessPath::parse(registry, access)?,
+            AccessPath::parse_with_aliases(registry, aliases, access)?,
+        ))
+    }
+
+    /// Constructs a [`PropertyPath`] to a field of the component type `T` on
+    /// the entity at `entity_path`, without looking the type up in a
+    /// [`TypeRegistry`]. See [`AccessPath::of`].
+    pub fn new<'a, T: Component + Reflect>(
+        entity_path: &str,
+        field: &'a str,
+    ) -> Result<Self, ReflectPathError<'a>> {
+        Ok(Self::from_parts(
+            EntityPath::from_str(entity_path).unwrap(),
+            AccessPath::of::<T>(field)?,
         ))
     }
 
@@ -190,10 +660,168 @@ impl PropertyPath {
     }
 }
 
+impl fmt::Display for PropertyPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.entity.fmt(f)?;
+        f.write_char(Self::SEPERATOR)?;
+        self.access.fmt(f)
+    }
+}
+
+impl From<(&str, AccessPath)> for PropertyPath {
+    fn from((entity, access): (&str, AccessPath)) -> Self {
+        Self::from_parts(EntityPath::from_str(entity).unwrap(), access)
+    }
+}
+
+/// A [`PropertyPath`] whose component type has not been looked up yet.
+///
+/// Parsing an unresolved path doesn't need a [`TypeRegistry`], so it can be
+/// done before the component types are registered, like while loading an
+/// asset. Its component type and field path are only checked once it's
+/// [resolved](Self::resolve).
+///
+/// ```rust,ignore
+/// let unresolved: UnresolvedPropertyPath = "root/hips@game::Wings.spread".parse()?;
+/// // ...later, once `game::Wings` is registered...
+/// let path = unresolved.resolve(&registry)?;
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct UnresolvedPropertyPath {
+    entity: EntityPath,
+    target: UnresolvedTarget,
+    component_name: String,
+    // For assets, the path to the handle.
+    field_path: String,
+}
+
+/// The [`AccessTarget`] of an [`UnresolvedPropertyPath`], with the field path
+/// within assets left unparsed.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+enum UnresolvedTarget {
+    Component,
+    Resource,
+    Asset(String),
+}
+
+impl UnresolvedPropertyPath {
+    pub fn entity(&self) -> &EntityPath {
+        &self.entity
+    }
+
+    pub fn component_name(&self) -> &str {
+        self.component_name.as_ref()
+    }
+
+    /// Looks up the component type in `registry` and parses the field path,
+    /// producing a [`PropertyPath`] identical to one parsed with
+    /// [`PropertyPath::parse`].
+    pub fn resolve<'a>(
+        &'a self,
+        registry: &TypeRegistry,
+    ) -> Result<PropertyPath, ParsePathError<'a>> {
+        let registration = find_component(registry, &self.component_name)?;
+        let field_path = FieldPath::parse(&self.field_path)?;
+        let access = match &self.target {
+            UnresolvedTarget::Component => {
+                AccessPath::from_parts(registration.type_id(), registration.name(), field_path)
+            }
+            UnresolvedTarget::Resource => {
+                AccessPath::from_parts(registration.type_id(), registration.name(), field_path)
+                    .with_target(AccessTarget::Resource)
+            }
+            UnresolvedTarget::Asset(asset_field) => AccessPath::from_parts(
+                registration.type_id(),
+                registration.name(),
+                FieldPath::parse(asset_field)?,
+            )
+            .with_target(AccessTarget::Asset(field_path)),
+        };
+        Ok(PropertyPath::from_parts(self.entity.clone(), access))
+    }
+}
+
+impl FromStr for UnresolvedPropertyPath {
+    type Err = ParsePathError<'static>;
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let (entity, access) = src
+            .split_once(PropertyPath::SEPERATOR)
+            .ok_or(ParsePathError::MissingDelimiter)?;
+        let (target, access, asset_field) = split_target(access)?;
+        let (component, field) = access
+            .split_once(AccessPath::SEPERATOR)
+            .unwrap_or((access, ""));
+        if component.is_empty() {
+            return Err(ParsePathError::NoComponentName);
+        }
+        let target = match (target, asset_field) {
+            (_, Some(asset_field)) => UnresolvedTarget::Asset(asset_field.to_string()),
+            (AccessTarget::Resource, _) => UnresolvedTarget::Resource,
+            _ => UnresolvedTarget::Component,
+        };
+        Ok(Self {
+            entity: EntityPath::from_str(entity).unwrap(),
+            target,
+            component_name: component.to_string(),
+            field_path: field.to_string(),
+        })
+    }
+}
+
+impl From<&PropertyPath> for UnresolvedPropertyPath {
+    fn from(path: &PropertyPath) -> Self {
+        let (target, field_path) = match &path.access.target {
+            AccessTarget::Component => (UnresolvedTarget::Component, &path.access.field_path),
+            AccessTarget::Resource => (UnresolvedTarget::Resource, &path.access.field_path),
+            AccessTarget::Asset(handle_field) => (
+                UnresolvedTarget::Asset(path.access.field_path.to_string()),
+                handle_field,
+            ),
+        };
+        Self {
+            entity: path.entity.clone(),
+            target,
+            component_name: path.access.component_name.to_string(),
+            field_path: field_path.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for UnresolvedPropertyPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.entity.fmt(f)?;
+        f.write_char(PropertyPath::SEPERATOR)?;
+        match self.target {
+            UnresolvedTarget::Component => {}
+            UnresolvedTarget::Resource => f.write_str(AccessPath::RESOURCE_PREFIX)?,
+            UnresolvedTarget::Asset(_) => f.write_str(AccessPath::ASSET_PREFIX)?,
+        }
+        f.write_str(&self.component_name)?;
+        if !self.field_path.is_empty() {
+            f.write_str(AccessPath::SEPERATOR)?;
+        }
+        f.write_str(&self.field_path)?;
+        if let UnresolvedTarget::Asset(asset_field) = &self.target {
+            f.write_char(AccessPath::ASSET_SEPERATOR)?;
+            f.write_str(asset_field)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum ParsePathError<'a> {
     MissingDelimiter,
+    /// An asset path has no "#" between the path to the handle and the field
+    /// path within the asset.
+    MissingAssetDelimiter,
     InvalidComponentType,
+    /// The component was named by a short name shared by several registered
+    /// types, listed by their full names.
+    AmbiguousShortName {
+        name: &'a str,
+        candidates: Vec<&'static str>,
+    },
     NoComponentName,
     InvalidFieldPath(ReflectPathError<'a>),
 }
@@ -207,8 +835,7 @@ impl<'a> From<ReflectPathError<'a>> for ParsePathError<'a> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use bevy_ecs::prelude::*;
-    use bevy_reflect::prelude::*;
+    use bevy_utils::HashSet;
 
     #[derive(Component, Reflect)]
     struct Test {
@@ -233,6 +860,47 @@ mod test {
         assert_eq!(vec, vec!["a", "b", "c", "dead", "e", "f", "", "g"]);
     }
 
+    #[test]
+    pub fn test_entity_paths_compare_by_parts() {
+        let a = EntityPath::from_str("root/hips/spine").unwrap();
+        let b = EntityPath::from_parts(vec![
+            Name::new("root"),
+            Name::new("hips"),
+            Name::new("spine"),
+        ]);
+        let c = EntityPath::from_str("root/hips").unwrap();
+        assert_eq!(a, b);
+        assert!(!a.ptr_eq(&b));
+        assert!(a.ptr_eq(&a.clone()));
+        let mut paths = HashSet::default();
+        paths.insert(a.clone());
+        assert!(paths.contains(&b));
+        assert!(!paths.contains(&c));
+        assert_ne!(a, c);
+        assert_eq!(c.cmp(&a), Ordering::Less);
+        assert_eq!(a.to_string(), "root/hips/spine");
+    }
+
+    #[test]
+    pub fn test_root_entity_path() {
+        let root = EntityPath::root();
+        assert!(root.is_root());
+        assert_eq!(root.to_string(), "");
+        assert_eq!(EntityPath::from_str("").unwrap(), root);
+        assert_eq!(EntityPath::from_str(&root.to_string()).unwrap(), root);
+        assert!(!EntityPath::from_str("a").unwrap().is_root());
+
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        let path = PropertyPath::parse(&registry, "@bevy_prototype_animation::path::test::Test.a")
+            .unwrap();
+        assert_eq!(*path.entity(), root);
+        assert_eq!(
+            path.to_string(),
+            "@bevy_prototype_animation::path::test::Test.a"
+        );
+    }
+
     #[test]
     pub fn test_parse_access_path() {
         let mut registry = TypeRegistry::default();
@@ -268,6 +936,88 @@ mod test {
         assert_eq!(path, Err(ParsePathError::InvalidComponentType));
     }
 
+    mod other {
+        use super::*;
+
+        /// Shares its short name with the outer `Test`.
+        #[derive(Component, Reflect)]
+        pub struct Test {
+            a: u32,
+        }
+
+        #[derive(Component, Reflect)]
+        pub struct Unique {
+            pub value: f32,
+        }
+    }
+
+    #[test]
+    pub fn test_parse_short_component_names() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        registry.register::<other::Unique>();
+        let path = PropertyPath::parse(&registry, "hips@Unique.value").unwrap();
+        assert_eq!(
+            path.access().component_type_id(),
+            TypeId::of::<other::Unique>()
+        );
+        // Paths always display the full name, so they parse back to the same
+        // path without the short name.
+        let full = "hips@bevy_prototype_animation::path::test::other::Unique.value";
+        assert_eq!(path.to_string(), full);
+        assert_eq!(PropertyPath::parse(&registry, full), Ok(path.clone()));
+        let unresolved: UnresolvedPropertyPath = "hips@Unique.value".parse().unwrap();
+        assert_eq!(unresolved.resolve(&registry), Ok(path));
+
+        registry.register::<other::Test>();
+        assert_eq!(
+            AccessPath::parse(&registry, "Test.a"),
+            Err(ParsePathError::AmbiguousShortName {
+                name: "Test",
+                candidates: vec![
+                    "bevy_prototype_animation::path::test::Test",
+                    "bevy_prototype_animation::path::test::other::Test",
+                ],
+            })
+        );
+        // Full names are still unambiguous.
+        assert!(
+            AccessPath::parse(&registry, "bevy_prototype_animation::path::test::Test.a").is_ok()
+        );
+    }
+
+    #[test]
+    pub fn test_parse_path_aliases() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        registry.register::<other::Test>();
+        let mut aliases = PathAliases::default();
+        aliases
+            .insert_component::<Test>("T")
+            .insert("b", "bevy_prototype_animation::path::test::Test.b");
+        let full = PropertyPath::parse(
+            &registry,
+            "hips@bevy_prototype_animation::path::test::Test.b.c",
+        )
+        .unwrap();
+        for path in ["hips@T.b.c", "hips@b.c"] {
+            let path = PropertyPath::parse_with_aliases(&registry, &aliases, path).unwrap();
+            assert_eq!(path, full);
+            assert_eq!(path.to_string(), full.to_string());
+        }
+        // Aliases may use short names, as long as they're unambiguous.
+        aliases.insert("c", "Test.c");
+        assert!(matches!(
+            PropertyPath::parse_with_aliases(&registry, &aliases, "hips@c"),
+            Err(ParsePathError::AmbiguousShortName { name: "Test", .. })
+        ));
+        assert_eq!(aliases.remove("c").as_deref(), Some("Test.c"));
+        assert_eq!(
+            PropertyPath::parse(&registry, "hips@T.b"),
+            Err(ParsePathError::InvalidComponentType)
+        );
+    }
+
     #[test]
     pub fn test_parse_property_path() {
         let mut registry = TypeRegistry::default();
@@ -283,6 +1033,125 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_parse_whole_component_property_path() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        let path_str = "root/hips@bevy_prototype_animation::path::test::Test";
+        let path = PropertyPath::parse(&registry, path_str).unwrap();
+        assert!(path.access().field_path().is_root());
+        assert_eq!(path.access().component_type_id(), TypeId::of::<Test>());
+        assert_eq!(path.to_string(), path_str);
+
+        let mut test = Test { a: 1, b: 2, c: 3 };
+        let root = path.access().field_path().field_mut(&mut test).unwrap();
+        assert_eq!(root.downcast_ref::<Test>().map(|test| test.b), Some(2));
+        assert_eq!(
+            PropertyPath::parse(&registry, "root@.a"),
+            Err(ParsePathError::NoComponentName)
+        );
+    }
+
+    #[test]
+    pub fn test_typed_paths_match_parsed_paths() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        let parsed = PropertyPath::parse(
+            &registry,
+            "root/hips@bevy_prototype_animation::path::test::Test.b",
+        )
+        .unwrap();
+        let access = AccessPath::of::<Test>("b").unwrap();
+        assert_eq!(&access, parsed.access());
+        assert_eq!(PropertyPath::new::<Test>("root/hips", "b").unwrap(), parsed);
+        assert_eq!(PropertyPath::from(("root/hips", access)), parsed);
+        assert!(AccessPath::of::<Test>("").unwrap().field_path().is_root());
+        assert_eq!(
+            AccessPath::of::<Test>("b..c"),
+            Err(ReflectPathError::ExpectedIdent { index: 2 })
+        );
+    }
+
+    #[test]
+    pub fn test_whole_components_are_ordered_before_their_fields() {
+        let whole = AccessPath::of::<Test>("").unwrap();
+        let field = AccessPath::of::<Test>("b").unwrap();
+        let nested = AccessPath::of::<Test>("b.c").unwrap();
+        assert!(whole < field && field < nested);
+
+        let prioritized = whole.clone().with_priority(1);
+        assert_eq!(prioritized.priority(), 1);
+        assert_eq!(prioritized, whole);
+        assert_eq!(prioritized.to_string(), whole.to_string());
+        let mut paths = HashSet::default();
+        paths.insert(whole);
+        assert!(paths.contains(&prioritized));
+    }
+
+    #[test]
+    pub fn test_resource_and_asset_paths_round_trip() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Test>();
+        let resource = "@res:bevy_prototype_animation::path::test::Test.b";
+        let asset = "hips@asset:bevy_prototype_animation::path::test::Test.a#color.r";
+        let whole_asset = "hips@asset:bevy_prototype_animation::path::test::Test#";
+        for (path_str, access) in [
+            (resource, AccessPath::of_resource::<Test>("b").unwrap()),
+            (asset, AccessPath::of_asset::<Test>("a", "color.r").unwrap()),
+            (whole_asset, AccessPath::of_asset::<Test>("", "").unwrap()),
+        ] {
+            let path = PropertyPath::parse(&registry, path_str).unwrap();
+            assert_eq!(path.access(), &access);
+            assert!(!access.is_component());
+            assert_eq!(path.to_string(), path_str);
+            let unresolved = UnresolvedPropertyPath::from_str(path_str).unwrap();
+            assert_eq!(unresolved.to_string(), path_str);
+            assert_eq!(unresolved.resolve(&registry), Ok(path.clone()));
+            assert_eq!(UnresolvedPropertyPath::from(&path), unresolved);
+        }
+
+        let path = PropertyPath::parse(&registry, asset).unwrap();
+        assert_eq!(
+            path.access().target(),
+            &AccessTarget::Asset(FieldPath::parse("a").unwrap())
+        );
+        assert_eq!(path.access().field_path().to_string(), "color.r");
+        // The same fields of different targets are different properties.
+        let component = AccessPath::of::<Test>("b").unwrap();
+        assert_ne!(component, AccessPath::of_resource::<Test>("b").unwrap());
+        assert!(component.is_component());
+        assert_eq!(
+            PropertyPath::parse(&registry, "hips@asset:Test.a"),
+            Err(ParsePathError::MissingAssetDelimiter)
+        );
+    }
+
+    #[test]
+    pub fn test_unresolved_paths_resolve_after_registration() {
+        let path_str = "root/hips@bevy_prototype_animation::path::test::Test.b";
+        let unresolved = UnresolvedPropertyPath::from_str(path_str).unwrap();
+        assert_eq!(unresolved.to_string(), path_str);
+
+        let mut registry = TypeRegistry::default();
+        assert_eq!(
+            unresolved.resolve(&registry),
+            Err(ParsePathError::InvalidComponentType)
+        );
+        registry.register::<Test>();
+        let path = unresolved.resolve(&registry).unwrap();
+        assert_eq!(path, PropertyPath::parse(&registry, path_str).unwrap());
+        assert_eq!(UnresolvedPropertyPath::from(&path), unresolved);
+
+        assert_eq!(
+            UnresolvedPropertyPath::from_str("root/hips"),
+            Err(ParsePathError::MissingDelimiter)
+        );
+        assert_eq!(
+            UnresolvedPropertyPath::from_str("root@.b"),
+            Err(ParsePathError::NoComponentName)
+        );
+    }
+
     #[test]
     pub fn test_parse_property_path_works_with_empty_entity() {
         let mut registry = TypeRegistry::default();
@@ -298,6 +1167,48 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_field_path_display_round_trips() {
+        for path_str in ["a", "a.b.c", "a.0.b", "a[1].b", "0[2][3]"] {
+            let path = FieldPath::parse(path_str).unwrap();
+            assert_eq!(path.to_string(), path_str);
+            assert_eq!(FieldPath::parse(&path.to_string()).unwrap(), path);
+        }
+    }
+
+    #[test]
+    pub fn test_field_path_push_and_pop() {
+        let mut path = FieldPath::root();
+        assert_eq!(path.pop(), None);
+        path.push(Access::Field("a".to_string()));
+        path.push(Access::TupleIndex(0));
+        path.push(Access::ListIndex(2));
+        assert_eq!(path.to_string(), "a.0[2]");
+        let accesses: Vec<_> = path.iter().cloned().collect();
+        assert_eq!(
+            accesses,
+            vec![
+                Access::Field("a".to_string()),
+                Access::TupleIndex(0),
+                Access::ListIndex(2)
+            ]
+        );
+
+        assert_eq!(path.pop(), Some(Access::ListIndex(2)));
+        assert_eq!(path.pop(), Some(Access::TupleIndex(0)));
+        assert_eq!(path, FieldPath::parse("a").unwrap());
+
+        let test = Test { a: 1, b: 2, c: 3 };
+        path.pop();
+        assert!(path.is_root());
+        path.push(Access::Field("b".to_string()));
+        let field = path.field(&test).unwrap();
+        assert_eq!(field.downcast_ref::<u32>(), Some(&2));
+        path.pop();
+        path.push(Access::Field("d".to_string()));
+        assert!(path.field(&test).is_err());
+    }
+
     #[test]
     pub fn test_parse_property_path_fails_on_empty_field() {
         let mut registry = TypeRegistry::default();
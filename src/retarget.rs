@@ -0,0 +1,346 @@
+//! Copying clips between hierarchies with different bone names and
+//! proportions.
+
+use crate::{
+    clip::{AnimationClip, ClipCurve, CurveWrapper},
+    curve::{mapped_curve_methods, Curve},
+    path::{AccessPath, EntityPath, PropertyPath},
+    Animatable,
+};
+use bevy_math::{Quat, Vec3};
+use bevy_transform::prelude::Transform;
+use bevy_utils::{HashMap, Hashed};
+use std::{any::TypeId, sync::Arc};
+
+/// What [`retarget_clip`] does with curves for entities that aren't covered by
+/// any rename in a [`RetargetMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnmappedPaths {
+    /// Leaves the curves out of the retargeted clip.
+    Drop,
+    /// Copies the curves into the retargeted clip with their paths unchanged.
+    PassThrough,
+}
+
+impl Default for UnmappedPaths {
+    fn default() -> Self {
+        Self::PassThrough
+    }
+}
+
+/// Adjustments applied to the animated [`Transform`] of a single bone when
+/// retargeting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoneRetarget {
+    /// Multiplies the translation of the bone. Used to account for bones of
+    /// different lengths.
+    pub translation_scale: f32,
+    /// Rotates the bone's rotation, applied before the animated rotation. Used
+    /// to account for bones with different rest orientations.
+    pub rotation_offset: Quat,
+}
+
+impl Default for BoneRetarget {
+    fn default() -> Self {
+        Self {
+            translation_scale: 1.0,
+            rotation_offset: Quat::IDENTITY,
+        }
+    }
+}
+
+impl BoneRetarget {
+    fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Describes how to map the entities animated by a clip from one hierarchy
+/// onto another. See [`retarget_clip`].
+#[derive(Default, Clone)]
+pub struct RetargetMap {
+    renames: HashMap<EntityPath, EntityPath>,
+    bones: HashMap<EntityPath, BoneRetarget>,
+    unmapped: UnmappedPaths,
+}
+
+impl RetargetMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renames `from` to `to`, including the paths of all of `from`'s
+    /// descendants. When multiple renames apply to a path, the longest one is
+    /// used.
+    pub fn rename(mut self, from: EntityPath, to: EntityPath) -> Self {
+        self.renames.insert(from, to);
+        self
+    }
+
+    /// Scales the animated translation of `bone`. `bone` is the path of the
+    /// bone before any renames.
+    pub fn scale_translation(mut self, bone: EntityPath, scale: f32) -> Self {
+        self.bones.entry(bone).or_default().translation_scale = scale;
+        self
+    }
+
+    /// Offsets the animated rotation of `bone`. `bone` is the path of the bone
+    /// before any renames.
+    pub fn offset_rotation(mut self, bone: EntityPath, offset: Quat) -> Self {
+        self.bones.entry(bone).or_default().rotation_offset = offset;
+        self
+    }
+
+    /// Sets what happens to curves for entities not covered by any rename.
+    /// Defaults to [`UnmappedPaths::PassThrough`].
+    pub fn unmapped(mut self, unmapped: UnmappedPaths) -> Self {
+        self.unmapped = unmapped;
+        self
+    }
+
+    /// Maps an entity path onto the target hierarchy. Returns `None` if no
+    /// rename covers the path.
+    pub fn map_path(&self, path: &EntityPath) -> Option<EntityPath> {
+        let (from, to) = self
+            .renames
+            .iter()
+            .filter(|(from, _)| {
+                from.len() <= path.len() && from.iter().zip(path.iter()).all(|(a, b)| a == b)
+            })
+            .max_by_key(|(from, _)| from.len())?;
+        let parts = to
+            .iter()
+            .chain(path.iter().skip(from.len()))
+            .cloned()
+            .collect();
+        Some(EntityPath::from_parts(parts))
+    }
+}
+
+/// Creates a copy of `clip` for another hierarchy, renaming the entities it
+/// animates and adjusting bone transforms as described by `map`.
+///
+/// Bone adjustments are applied to curves of [`Transform`]s, and to [`Vec3`]
+/// and [`Quat`] curves of a [`Transform`]'s translation and rotation. Other
+/// curves, including parameter curves, are copied unchanged, and share their
+/// underlying curve with `clip`.
+pub fn retarget_clip(clip: &AnimationClip, map: &RetargetMap) -> AnimationClip {
+    let mut retargeted = AnimationClip::builder().build();
+    retargeted.additive = clip.additive;
+    retargeted.params = clip.params.clone();
+    retargeted.name = clip.name.clone();
+    retargeted.tags = clip.tags.clone();
+    for (path, curve) in clip.curves.iter() {
+        let entity = match (map.map_path(path.entity()), map.unmapped) {
+            (Some(entity), _) => entity,
+            (None, UnmappedPaths::PassThrough) => path.entity().clone(),
+            (None, UnmappedPaths::Drop) => continue,
+        };
+        let curve = map
+            .bones
+            .get(path.entity())
+            .filter(|bone| !bone.is_identity())
+            .and_then(|bone| retarget_curve(curve.as_ref(), path.access(), bone))
+            .unwrap_or_else(|| curve.clone_curve());
+        let path = PropertyPath::from_parts(entity, path.access().clone());
+        retargeted.curves.insert(Hashed::new(path), curve);
+    }
+    retargeted
+}
+
+fn retarget_curve(
+    curve: &dyn ClipCurve,
+    access: &AccessPath,
+    bone: &BoneRetarget,
+) -> Option<Box<dyn ClipCurve>> {
+    if access.component_type_id() != TypeId::of::<Transform>() {
+        return None;
+    }
+    match access.field_path().to_string().as_str() {
+        "" => retargeted::<Transform>(curve, bone),
+        "translation" => retargeted::<Vec3>(curve, bone),
+        "rotation" => retargeted::<Quat>(curve, bone),
+        _ => None,
+    }
+}
+
+fn retargeted<T: Retarget>(
+    curve: &dyn ClipCurve,
+    bone: &BoneRetarget,
+) -> Option<Box<dyn ClipCurve>> {
+    let curve = curve.as_any().downcast_ref::<CurveWrapper<T>>()?;
+    let curve = RetargetedCurve {
+        curve: curve.0.clone(),
+        bone: *bone,
+    };
+    Some(Box::new(CurveWrapper::<T>(Arc::new(curve))))
+}
+
+trait Retarget: Animatable {
+    fn retarget(self, bone: &BoneRetarget) -> Self;
+}
+
+impl Retarget for Vec3 {
+    fn retarget(self, bone: &BoneRetarget) -> Self {
+        self * bone.translation_scale
+    }
+}
+
+impl Retarget for Quat {
+    fn retarget(self, bone: &BoneRetarget) -> Self {
+        bone.rotation_offset * self
+    }
+}
+
+impl Retarget for Transform {
+    fn retarget(self, bone: &BoneRetarget) -> Self {
+        Self {
+            translation: self.translation.retarget(bone),
+            rotation: self.rotation.retarget(bone),
+            scale: self.scale,
+        }
+    }
+}
+
+/// A curve sampling another curve with a bone's retargeting adjustments
+/// applied.
+struct RetargetedCurve<T> {
+    curve: Arc<dyn Curve<T>>,
+    bone: BoneRetarget,
+}
+
+impl<T: Retarget> Curve<T> for RetargetedCurve<T> {
+    mapped_curve_methods!(T, curve, |this, _, value| value.retarget(&this.bone));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{curve::CurveFixed, path::FieldPath};
+    use std::str::FromStr;
+
+    fn path<T: 'static>(entity: &str, field: &str) -> PropertyPath {
+        let access = AccessPath::from_parts(
+            TypeId::of::<T>(),
+            std::any::type_name::<T>(),
+            FieldPath::parse(field).unwrap(),
+        );
+        PropertyPath::from_parts(EntityPath::from_str(entity).unwrap(), access)
+    }
+
+    fn entity(path: &str) -> EntityPath {
+        EntityPath::from_str(path).unwrap()
+    }
+
+    fn clip() -> AnimationClip {
+        let transforms = vec![
+            Transform::from_xyz(0.0, 1.0, 0.0),
+            Transform::from_xyz(1.0, 2.0, 0.0).with_rotation(Quat::from_rotation_y(1.0)),
+        ];
+        AnimationClip::builder()
+            .add_curve(
+                path::<Transform>("Armature/Hips", ""),
+                CurveFixed::from_keyframes(1.0, transforms),
+            )
+            .add_curve(
+                path::<Transform>("Armature/Hips/Spine", "translation"),
+                CurveFixed::from_keyframes(1.0, vec![Vec3::Y, Vec3::ONE]),
+            )
+            .add_curve(
+                path::<Transform>("Armature/Hips/Spine", "scale"),
+                CurveFixed::from_keyframes(1.0, vec![Vec3::ONE, Vec3::splat(2.0)]),
+            )
+            .add_curve(
+                path::<Transform>("Camera", "translation"),
+                CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::X]),
+            )
+            .build()
+    }
+
+    fn map() -> RetargetMap {
+        RetargetMap::new()
+            .rename(entity("Armature/Hips"), entity("Root/Pelvis"))
+            .scale_translation(entity("Armature/Hips"), 1.2)
+            .scale_translation(entity("Armature/Hips/Spine"), 1.2)
+    }
+
+    fn sample<T: Animatable>(clip: &AnimationClip, path: PropertyPath, time: f32) -> T {
+        clip.get_curve::<T>(&Hashed::new(path))
+            .unwrap()
+            .sample(time)
+    }
+
+    #[test]
+    pub fn test_retarget_renames_and_scales() {
+        let original = clip();
+        let retargeted = retarget_clip(&original, &map());
+
+        let mut paths: Vec<_> = retargeted
+            .properties()
+            .map(|path| path.to_string())
+            .collect();
+        paths.sort();
+        let transform = std::any::type_name::<Transform>();
+        assert_eq!(
+            paths,
+            vec![
+                format!("Camera@{}.translation", transform),
+                format!("Root/Pelvis/Spine@{}.scale", transform),
+                format!("Root/Pelvis/Spine@{}.translation", transform),
+                format!("Root/Pelvis@{}", transform),
+            ]
+        );
+
+        for time in [0.0, 0.25, 0.5, 1.0] {
+            let hips: Transform = sample(&original, path::<Transform>("Armature/Hips", ""), time);
+            let pelvis: Transform = sample(&retargeted, path::<Transform>("Root/Pelvis", ""), time);
+            assert!((pelvis.translation - hips.translation * 1.2).length() < 1e-5);
+            assert_eq!(pelvis.rotation, hips.rotation);
+            assert_eq!(pelvis.scale, hips.scale);
+
+            let spine: Vec3 = sample(
+                &original,
+                path::<Transform>("Armature/Hips/Spine", "translation"),
+                time,
+            );
+            let retargeted_spine: Vec3 = sample(
+                &retargeted,
+                path::<Transform>("Root/Pelvis/Spine", "translation"),
+                time,
+            );
+            assert!((retargeted_spine - spine * 1.2).length() < 1e-5);
+
+            // Scale curves and unscaled bones are left as is.
+            let scale: Vec3 = sample(
+                &retargeted,
+                path::<Transform>("Root/Pelvis/Spine", "scale"),
+                time,
+            );
+            assert_eq!(scale, Vec3::ONE.lerp(Vec3::splat(2.0), time));
+            let camera: Vec3 = sample(
+                &retargeted,
+                path::<Transform>("Camera", "translation"),
+                time,
+            );
+            assert_eq!(camera, Vec3::X * time);
+        }
+    }
+
+    #[test]
+    pub fn test_retarget_drops_unmapped_paths() {
+        let map = map()
+            .offset_rotation(entity("Armature/Hips"), Quat::from_rotation_x(0.5))
+            .unmapped(UnmappedPaths::Drop);
+        let original = clip();
+        let retargeted = retarget_clip(&original, &map);
+        assert_eq!(retargeted.properties().count(), 3);
+        assert!(retargeted
+            .properties()
+            .all(|path| path.entity().iter().next().unwrap().as_str() == "Root"));
+
+        let hips: Transform = sample(&original, path::<Transform>("Armature/Hips", ""), 1.0);
+        let pelvis: Transform = sample(&retargeted, path::<Transform>("Root/Pelvis", ""), 1.0);
+        let expected = Quat::from_rotation_x(0.5) * hips.rotation;
+        assert!(pelvis.rotation.dot(expected).abs() > 1.0 - 1e-5);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{graph::AnimationGraph, path::EntityPath};
+use bevy_ecs::prelude::*;
+use bevy_log::warn;
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_utils::HashSet;
+
+/// Attaches an entity to a bone of an [`AnimationGraph`]. The entity's
+/// [`GlobalTransform`] follows the bone's every frame, offset by `offset` in the
+/// bone's local space.
+///
+/// Sockets cannot be attached to other sockets.
+#[derive(Component, Clone, Debug)]
+pub struct Socket {
+    /// The entity with the [`AnimationGraph`] the bone belongs to.
+    pub graph: Entity,
+    /// The path of the bone relative to the graph.
+    pub path: EntityPath,
+    /// The offset from the bone.
+    pub offset: Transform,
+}
+
+/// Copies the [`GlobalTransform`] of the bones each [`Socket`] is attached to.
+/// Must run after transforms have been propagated.
+///
+/// Sockets whose bone is not currently bound are left untouched.
+pub fn attach_sockets_system(
+    mut sockets: Query<(Entity, &Socket, &mut GlobalTransform)>,
+    graphs: Query<&AnimationGraph>,
+    bones: Query<&GlobalTransform, Without<Socket>>,
+    mut unbound: Local<HashSet<Entity>>,
+) {
+    for (entity, socket, mut transform) in sockets.iter_mut() {
+        let bone = graphs
+            .get(socket.graph)
+            .ok()
+            .and_then(|graph| graph.bound_entity(&socket.path))
+            .and_then(|bone| bones.get(bone).ok());
+        if let Some(bone) = bone {
+            *transform = bone.mul_transform(socket.offset);
+            unbound.remove(&entity);
+        } else if unbound.insert(entity) {
+            warn!(
+                "Socket {:?} is attached to '{}', which is not bound to an entity.",
+                entity, socket.path
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{clip::AnimationClip, curve::CurveFixed, path::PropertyPath};
+    use bevy_math::*;
+    use bevy_reflect::TypeRegistry;
+    use std::str::FromStr;
+
+    #[test]
+    pub fn test_socket_follows_bone() {
+        let mut world = World::new();
+        let bone = world.spawn().insert(GlobalTransform::identity()).id();
+        let root = world.spawn().id();
+        let mut registry = TypeRegistry::default();
+        registry.register::<Transform>();
+        let property = PropertyPath::parse(
+            &registry,
+            "hand@bevy_transform::components::transform::Transform.translation",
+        )
+        .unwrap();
+        let path = property.entity().clone();
+        let clip = AnimationClip::builder()
+            .add_curve(property, CurveFixed::from_constant(Vec3::ZERO))
+            .build();
+        let mut graph = AnimationGraph::new();
+        graph.add_clip(&clip).unwrap();
+        graph.find_bone_mut(&path).unwrap().set_entity(Some(bone));
+        world.entity_mut(root).insert(graph);
+
+        let offset = Transform::from_xyz(0.0, 1.0, 0.0);
+        let socket = world
+            .spawn()
+            .insert(Socket {
+                graph: root,
+                path: path.clone(),
+                offset,
+            })
+            .insert(GlobalTransform::identity())
+            .id();
+        let unbound = world
+            .spawn()
+            .insert(Socket {
+                graph: root,
+                path: EntityPath::from_str("foot").unwrap(),
+                offset,
+            })
+            .insert(GlobalTransform::from_xyz(5.0, 5.0, 5.0))
+            .id();
+
+        let mut stage = SystemStage::single(attach_sockets_system);
+        for x in [1.0, 2.0, 3.0] {
+            *world.get_mut::<GlobalTransform>(bone).unwrap() =
+                GlobalTransform::from_xyz(x, 0.0, 0.0);
+            stage.run(&mut world);
+            let transform = world.get::<GlobalTransform>(socket).unwrap();
+            assert_eq!(transform.translation, Vec3::new(x, 1.0, 0.0));
+            let transform = world.get::<GlobalTransform>(unbound).unwrap();
+            assert_eq!(transform.translation, Vec3::new(5.0, 5.0, 5.0));
+        }
+    }
+}
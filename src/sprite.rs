@@ -0,0 +1,158 @@
+//! Prebuilt clips for animating [`bevy_sprite`] components.
+//!
+//! Only available with the `sprite` feature.
+
+use crate::{
+    clip::AnimationClip,
+    curve::CurveFixed,
+    path::{AccessPath, EntityPath, FieldPath, PropertyPath},
+};
+use bevy_render::color::Color;
+use bevy_sprite::{Sprite, TextureAtlasSprite};
+use std::{any::TypeId, ops::Range};
+
+fn property<T: 'static>(entity: EntityPath, field: &str) -> PropertyPath {
+    let access = AccessPath::from_parts(
+        TypeId::of::<T>(),
+        std::any::type_name::<T>(),
+        FieldPath::parse(field).unwrap(),
+    );
+    PropertyPath::from_parts(entity, access)
+}
+
+/// Flipbook animations of a [`TextureAtlasSprite`]'s index.
+pub struct SpriteSheetClip;
+
+// These are only namespaces for constructing clips.
+#[allow(clippy::new_ret_no_self)]
+impl SpriteSheetClip {
+    /// Creates a clip that steps the [`TextureAtlasSprite`] on the graph's own
+    /// entity through `frames` at `fps` frames per second. Every frame is shown
+    /// for the same amount of time, so the clip loops cleanly with
+    /// [`PlaybackMode::Loop`].
+    ///
+    /// ```rust,ignore
+    /// let mut graph = AnimationGraph::new();
+    /// let node = graph.add_clip(&SpriteSheetClip::new(0..8, 12.0))?;
+    /// graph.set_playback_mode(node, PlaybackMode::Loop)?;
+    /// graph.add_input(NodeId::ROOT, node)?;
+    /// commands.spawn_bundle(sprite_sheet_bundle).insert(graph);
+    /// ```
+    ///
+    /// # Panics
+    /// This will panic if `frames` is empty.
+    ///
+    /// [`PlaybackMode::Loop`]: crate::graph::PlaybackMode::Loop
+    pub fn new(frames: Range<usize>, fps: f32) -> AnimationClip {
+        Self::for_entity(EntityPath::from_parts(Vec::new()), frames, fps)
+    }
+
+    /// Creates a flipbook clip like [`SpriteSheetClip::new`] for the
+    /// [`TextureAtlasSprite`] on a descendant of the graph's entity.
+    pub fn for_entity(entity: EntityPath, frames: Range<usize>, fps: f32) -> AnimationClip {
+        assert!(
+            !frames.is_empty(),
+            "A sprite sheet clip needs at least one frame."
+        );
+        let last = frames.end - 1;
+        // Repeat the last frame so that it is shown for a full frame before
+        // the clip ends.
+        let keyframes = frames.chain(std::iter::once(last)).collect();
+        AnimationClip::builder()
+            .add_curve(
+                property::<TextureAtlasSprite>(entity, "index"),
+                CurveFixed::from_keyframes(fps, keyframes),
+            )
+            .build()
+    }
+}
+
+/// Color fades of [`Sprite`]s and [`TextureAtlasSprite`]s.
+pub struct SpriteFadeClip;
+
+// These are only namespaces for constructing clips.
+#[allow(clippy::new_ret_no_self)]
+impl SpriteFadeClip {
+    /// Creates a clip that fades the [`Sprite`] on the graph's own entity from
+    /// one color to another over `duration` seconds. Colors are interpolated
+    /// in linear space.
+    pub fn new(from: Color, to: Color, duration: f32) -> AnimationClip {
+        Self::fade::<Sprite>(EntityPath::from_parts(Vec::new()), from, to, duration)
+    }
+
+    /// Creates a clip like [`SpriteFadeClip::new`] for the
+    /// [`TextureAtlasSprite`] on the graph's own entity.
+    pub fn atlas(from: Color, to: Color, duration: f32) -> AnimationClip {
+        Self::fade::<TextureAtlasSprite>(EntityPath::from_parts(Vec::new()), from, to, duration)
+    }
+
+    /// Creates a fade for the `color` field of a sprite component on a
+    /// descendant of the graph's entity.
+    pub fn fade<T: 'static>(
+        entity: EntityPath,
+        from: Color,
+        to: Color,
+        duration: f32,
+    ) -> AnimationClip {
+        let frame_rate = if duration > 0.0 {
+            duration.recip()
+        } else {
+            1.0
+        };
+        AnimationClip::builder()
+            .add_curve(
+                property::<T>(entity, "color"),
+                CurveFixed::from_keyframes(frame_rate, vec![from, to]),
+            )
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        graph::{AnimationGraph, NodeId, PlaybackMode},
+        AnimationPlugin,
+    };
+    use bevy_app::prelude::*;
+    use bevy_asset::AssetPlugin;
+    use bevy_ecs::prelude::*;
+    use bevy_tasks::{ComputeTaskPool, IoTaskPool, TaskPool};
+
+    const FPS: f32 = 4.0;
+
+    fn advance_graphs_system(mut graphs: Query<&mut AnimationGraph>) {
+        for mut graph in graphs.iter_mut() {
+            graph.advance_time(FPS.recip());
+        }
+    }
+
+    #[test]
+    pub fn test_sprite_sheet_clip_loops_at_fps() {
+        let mut app = App::new();
+        app.insert_resource(IoTaskPool(TaskPool::new()))
+            .insert_resource(ComputeTaskPool(TaskPool::new()))
+            .add_plugin(AssetPlugin)
+            .add_plugin(AnimationPlugin::default())
+            .add_system_to_stage(CoreStage::PreUpdate, advance_graphs_system);
+
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&SpriteSheetClip::new(2..6, FPS)).unwrap();
+        graph.set_playback_mode(node, PlaybackMode::Loop).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+        let sprite = app
+            .world
+            .spawn()
+            .insert(TextureAtlasSprite::new(0))
+            .insert(graph)
+            .id();
+
+        let mut indices = Vec::new();
+        for _ in 0..8 {
+            app.update();
+            indices.push(app.world.get::<TextureAtlasSprite>(sprite).unwrap().index);
+        }
+        assert_eq!(indices, vec![3, 4, 5, 2, 3, 4, 5, 2]);
+    }
+}
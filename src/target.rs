@@ -0,0 +1,225 @@
+//! Animating resources and assets, which aren't components of the bound
+//! entities. See [`AccessTarget`].
+//!
+//! Components are written in parallel, but resources and assets may be
+//! shared by many graphs, so their tracks are blended with the components and
+//! then written one at a time, once the components are written.
+
+use crate::{
+    path::{AccessPath, AccessTarget, FieldPath},
+    WorldResources,
+};
+use bevy_app::App;
+use bevy_asset::{Asset, Assets, Handle, HandleId};
+use bevy_ecs::{prelude::*, system::Resource};
+use bevy_reflect::{
+    FromType, GetTypeRegistration, Reflect, TypeData, TypeRegistration, TypeRegistry,
+    TypeRegistryArc,
+};
+use std::any::TypeId;
+
+/// Called with a resource or asset to write, and the other resources of the
+/// world.
+pub type WriteTarget<'f> = dyn FnMut(&mut dyn Reflect, WorldResources<'_>) + 'f;
+
+/// Type data for resources that can be animated. Adding `#[reflect(Resource)]`
+/// to a resource, or registering it with
+/// [`AnimationTargetAppExt::register_animated_resource`], allows clips to
+/// animate it with paths prefixed by `res:`.
+#[derive(Clone)]
+pub struct ReflectResource {
+    reflect: fn(&World) -> Option<&dyn Reflect>,
+    scope: fn(&mut World, &mut WriteTarget) -> bool,
+}
+
+impl ReflectResource {
+    /// Gets the resource, if it exists.
+    pub fn reflect<'w>(&self, world: &'w World) -> Option<&'w dyn Reflect> {
+        (self.reflect)(world)
+    }
+
+    /// Calls `f` with the resource and the other resources of the world.
+    /// Returns false if the resource doesn't exist.
+    pub fn scope(&self, world: &mut World, f: &mut WriteTarget) -> bool {
+        (self.scope)(world, f)
+    }
+}
+
+impl<R: Resource + Reflect> FromType<R> for ReflectResource {
+    fn from_type() -> Self {
+        Self {
+            reflect: |world| {
+                world
+                    .get_resource::<R>()
+                    .map(|resource| resource as &dyn Reflect)
+            },
+            scope: |world, f| {
+                if !world.contains_resource::<R>() {
+                    return false;
+                }
+                world.resource_scope(|world, mut resource: Mut<R>| {
+                    f(&mut *resource, WorldResources::new(world))
+                });
+                true
+            },
+        }
+    }
+}
+
+/// Type data for [`Handle`]s to assets that can be animated. Registered with
+/// [`AnimationTargetAppExt::register_animated_asset`], which allows clips to
+/// animate the assets with paths prefixed by `asset:`.
+#[derive(Clone)]
+pub struct ReflectHandle {
+    id: fn(&dyn Reflect) -> Option<HandleId>,
+    reflect: fn(&World, HandleId) -> Option<&dyn Reflect>,
+    scope: fn(&mut World, HandleId, &mut WriteTarget) -> bool,
+}
+
+impl ReflectHandle {
+    /// Gets the ID of a reflected handle, if it's of the registered type.
+    pub fn id(&self, handle: &dyn Reflect) -> Option<HandleId> {
+        (self.id)(handle)
+    }
+
+    /// Gets the asset with the given ID, if it's loaded.
+    pub fn reflect<'w>(&self, world: &'w World, id: HandleId) -> Option<&'w dyn Reflect> {
+        (self.reflect)(world, id)
+    }
+
+    /// Calls `f` with the asset with the given ID and the resources of the
+    /// world. Returns false if the asset isn't loaded.
+    pub fn scope(&self, world: &mut World, id: HandleId, f: &mut WriteTarget) -> bool {
+        (self.scope)(world, id, f)
+    }
+}
+
+impl<A: Asset + Reflect> FromType<Handle<A>> for ReflectHandle {
+    fn from_type() -> Self {
+        Self {
+            id: |handle| handle.downcast_ref::<Handle<A>>().map(|handle| handle.id),
+            reflect: |world, id| {
+                let assets = world.get_resource::<Assets<A>>()?;
+                assets.get(id).map(|asset| asset as &dyn Reflect)
+            },
+            scope: |world, id, f| {
+                if !world.contains_resource::<Assets<A>>() {
+                    return false;
+                }
+                world.resource_scope(
+                    |world, mut assets: Mut<Assets<A>>| match assets.get_mut(id) {
+                        Some(asset) => {
+                            f(asset, WorldResources::new(world));
+                            true
+                        }
+                        None => false,
+                    },
+                )
+            },
+        }
+    }
+}
+
+/// Registers resources and assets so they can be animated.
+pub trait AnimationTargetAppExt {
+    /// Registers the resource `R` with its [`ReflectResource`].
+    fn register_animated_resource<R: Resource + Reflect + GetTypeRegistration>(
+        &mut self,
+    ) -> &mut Self;
+
+    /// Registers the [`Handle`]s to the asset `A` with their
+    /// [`ReflectHandle`], so the assets they reference can be animated.
+    fn register_animated_asset<A: Asset + Reflect>(&mut self) -> &mut Self;
+}
+
+impl AnimationTargetAppExt for App {
+    fn register_animated_resource<R: Resource + Reflect + GetTypeRegistration>(
+        &mut self,
+    ) -> &mut Self {
+        self.register_type::<R>();
+        insert_type_data::<R, ReflectResource>(self);
+        self
+    }
+
+    fn register_animated_asset<A: Asset + Reflect>(&mut self) -> &mut Self {
+        self.register_type::<Handle<A>>();
+        insert_type_data::<Handle<A>, ReflectHandle>(self);
+        self
+    }
+}
+
+/// Adds the type data `D` to the registration of `T`.
+pub(crate) fn insert_type_data<T: Reflect, D: FromType<T> + TypeData>(app: &mut App) {
+    let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+    let mut registry = registry.write();
+    if let Some(registration) = registry.get_mut(TypeId::of::<T>()) {
+        registration.insert(D::from_type());
+    }
+}
+
+/// Gets the value targeted by a path bound to `entity`: one of its
+/// components, a resource, or an asset referenced by one of its components.
+pub(crate) fn reflect_target<'w>(
+    registry: &TypeRegistry,
+    world: &'w World,
+    entity: Entity,
+    access: &AccessPath,
+) -> Option<&'w dyn Reflect> {
+    let registration = registry.get(access.component_type_id())?;
+    match access.target() {
+        AccessTarget::Component => registration
+            .data::<ReflectComponent>()?
+            .reflect_component(world, entity),
+        AccessTarget::Resource => registration.data::<ReflectResource>()?.reflect(world),
+        AccessTarget::Asset(handle_field) => {
+            let (reflect, id) = find_asset(registry, world, entity, registration, handle_field)?;
+            reflect.reflect(world, id)
+        }
+    }
+}
+
+/// Calls `f` with the resource or asset targeted by a path bound to `entity`.
+/// Returns false if the target doesn't exist, or is a component.
+pub(crate) fn write_target(
+    registry: &TypeRegistry,
+    world: &mut World,
+    entity: Entity,
+    access: &AccessPath,
+    f: &mut WriteTarget,
+) -> bool {
+    let registration = match registry.get(access.component_type_id()) {
+        Some(registration) => registration,
+        None => return false,
+    };
+    match access.target() {
+        AccessTarget::Component => false,
+        AccessTarget::Resource => match registration.data::<ReflectResource>() {
+            Some(reflect) => reflect.scope(world, f),
+            None => false,
+        },
+        AccessTarget::Asset(handle_field) => {
+            match find_asset(registry, world, entity, registration, handle_field) {
+                Some((reflect, id)) => reflect.scope(world, id, f),
+                None => false,
+            }
+        }
+    }
+}
+
+/// Finds the handle at `handle_field` in a component of `entity`.
+fn find_asset<'r>(
+    registry: &'r TypeRegistry,
+    world: &World,
+    entity: Entity,
+    registration: &TypeRegistration,
+    handle_field: &FieldPath,
+) -> Option<(&'r ReflectHandle, HandleId)> {
+    let component = registration
+        .data::<ReflectComponent>()?
+        .reflect_component(world, entity)?;
+    let handle = handle_field.field(component).ok()?;
+    let reflect = registry
+        .get(handle.any().type_id())?
+        .data::<ReflectHandle>()?;
+    Some((reflect, reflect.id(handle)?))
+}
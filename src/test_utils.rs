@@ -0,0 +1,166 @@
+//! Helpers for testing animations in a headless [`App`], without a window or
+//! renderer. Requires the `test_utils` feature.
+//!
+//! ```rust,ignore
+//! let mut app = test_app();
+//! let hierarchy = TestHierarchy::spawn(&mut app.world, &["body", "body/arm"]);
+//! let path = property_path(&app, "body/arm@bevy_transform::components::transform::Transform.translation");
+//! // ...build a graph animating `path`...
+//! app.world.entity_mut(hierarchy.root()).insert(graph);
+//! step(&mut app, 0.1);
+//! ```
+
+use crate::{
+    graph::AnimationGraph,
+    path::{EntityPath, PathAliases, PropertyPath},
+    AnimationPlugin,
+};
+use bevy_app::App;
+use bevy_asset::AssetPlugin;
+use bevy_core::{CorePlugin, Name};
+use bevy_ecs::prelude::*;
+use bevy_reflect::TypeRegistryArc;
+use bevy_transform::{
+    prelude::{BuildWorldChildren, GlobalTransform, Transform},
+    TransformPlugin,
+};
+use bevy_utils::HashMap;
+use std::str::FromStr;
+
+/// Creates an [`App`] with the plugins needed to run the [`AnimationPlugin`]
+/// headlessly: the [`CorePlugin`] from `MinimalPlugins`, plus the
+/// [`TransformPlugin`] and [`AssetPlugin`].
+///
+/// Graph times are not advanced automatically. Use [`step`] to advance them by
+/// an exact delta instead of depending on the wall clock.
+pub fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugin(CorePlugin)
+        .add_plugin(TransformPlugin)
+        .add_plugin(AssetPlugin)
+        .add_plugin(AnimationPlugin::default());
+    app
+}
+
+/// Advances the time of every [`AnimationGraph`] by `delta_time`, then runs
+/// the app's schedule once.
+pub fn step(app: &mut App, delta_time: f32) {
+    let mut graphs = app.world.query::<&mut AnimationGraph>();
+    for mut graph in graphs.iter_mut(&mut app.world) {
+        graph.advance_time(delta_time);
+    }
+    app.update();
+}
+
+/// Parses a [`PropertyPath`] using the app's type registry, and its
+/// [`PathAliases`] if it has any.
+///
+/// # Panics
+/// This will panic if the path is invalid or its component type isn't
+/// registered.
+pub fn property_path(app: &App, path: &str) -> PropertyPath {
+    let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+    let registry = registry.read();
+    let no_aliases = PathAliases::default();
+    let aliases = app
+        .world
+        .get_resource::<PathAliases>()
+        .unwrap_or(&no_aliases);
+    match PropertyPath::parse_with_aliases(&registry, aliases, path) {
+        Ok(path) => path,
+        Err(err) => panic!("Invalid property path '{}': {:?}", path, err),
+    }
+}
+
+/// A hierarchy of named entities spawned beneath an unnamed root entity.
+pub struct TestHierarchy {
+    root: Entity,
+    entities: HashMap<EntityPath, Entity>,
+}
+
+impl TestHierarchy {
+    /// Spawns the entities at `paths`, relative to a new root entity. The
+    /// ancestors of every path are spawned as well. Every entity, including the
+    /// root, has a [`Transform`] and [`GlobalTransform`].
+    pub fn spawn(world: &mut World, paths: &[&str]) -> Self {
+        let root = world
+            .spawn()
+            .insert_bundle((Transform::identity(), GlobalTransform::identity()))
+            .id();
+        let mut hierarchy = Self {
+            root,
+            entities: HashMap::default(),
+        };
+        for path in paths {
+            // Parsing entity paths cannot fail.
+            let path = EntityPath::from_str(path).unwrap();
+            let mut parts = Vec::with_capacity(path.len());
+            let mut parent = root;
+            for name in path.iter() {
+                parts.push(name.clone());
+                let current = EntityPath::from_parts(parts.clone());
+                parent = match hierarchy.entities.get(&current) {
+                    Some(entity) => *entity,
+                    None => hierarchy.spawn_named(world, parent, current),
+                };
+            }
+        }
+        hierarchy
+    }
+
+    /// The unnamed root entity. This is where the [`AnimationGraph`] animating
+    /// the hierarchy should be added.
+    pub fn root(&self) -> Entity {
+        self.root
+    }
+
+    /// Gets the entity at a path relative to the root. The empty path is the
+    /// root itself.
+    pub fn get(&self, path: &str) -> Option<Entity> {
+        let path = EntityPath::from_str(path).ok()?;
+        if path.is_root() {
+            return Some(self.root);
+        }
+        self.entities.get(&path).copied()
+    }
+
+    /// Gets the entity at a path relative to the root.
+    ///
+    /// # Panics
+    /// This will panic if no entity was spawned at the path.
+    pub fn entity(&self, path: &str) -> Entity {
+        match self.get(path) {
+            Some(entity) => entity,
+            None => panic!("No entity was spawned at '{}'", path),
+        }
+    }
+
+    /// Spawns a named child beneath the entity at `parent`, or beneath the root
+    /// if `parent` is empty. Useful for testing rebinding.
+    ///
+    /// # Panics
+    /// This will panic if no entity was spawned at `parent`.
+    pub fn spawn_child(&mut self, world: &mut World, parent: &str, name: &str) -> Entity {
+        let parent_path = EntityPath::from_str(parent).unwrap();
+        let parent = if parent_path.is_empty() {
+            self.root
+        } else {
+            self.entity(parent)
+        };
+        let mut parts: Vec<_> = parent_path.iter().cloned().collect();
+        parts.push(Name::new(name.to_string()));
+        self.spawn_named(world, parent, EntityPath::from_parts(parts))
+    }
+
+    fn spawn_named(&mut self, world: &mut World, parent: Entity, path: EntityPath) -> Entity {
+        // Paths spawned by the hierarchy are never empty.
+        let name = path.iter().last().unwrap().clone();
+        let child = world
+            .spawn()
+            .insert_bundle((name, Transform::identity(), GlobalTransform::identity()))
+            .id();
+        world.entity_mut(parent).push_children(&[child]);
+        self.entities.insert(path, child);
+        child
+    }
+}
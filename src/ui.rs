@@ -0,0 +1,98 @@
+//! Property paths for animating [`bevy_ui`] nodes.
+//!
+//! Only available with the `ui` feature.
+
+use crate::path::{AccessPath, EntityPath, FieldPath, PropertyPath};
+use bevy_ui::{Style, UiColor};
+use std::any::TypeId;
+
+fn property<T: 'static>(entity: EntityPath, field: &str) -> PropertyPath {
+    let access = AccessPath::from_parts(
+        TypeId::of::<T>(),
+        std::any::type_name::<T>(),
+        FieldPath::parse(field).unwrap(),
+    );
+    PropertyPath::from_parts(entity, access)
+}
+
+/// Builds the [`PropertyPath`]s of commonly animated UI properties, so they
+/// don't need to be parsed from strings like
+/// `"panel@bevy_ui::ui_node::Style.size.width"`.
+///
+/// ```rust,ignore
+/// let panel: EntityPath = "panel".parse().unwrap();
+/// let clip = AnimationClip::builder()
+///     .add_curve(
+///         UiProperty::width(panel.clone()),
+///         CurveFixed::from_keyframes(2.0, vec![Val::Px(0.0), Val::Px(200.0)]),
+///     )
+///     .add_curve(
+///         UiProperty::color(panel),
+///         CurveFixed::from_keyframes(2.0, vec![UiColor(Color::NONE), UiColor(Color::WHITE)]),
+///     )
+///     .build();
+/// ```
+///
+/// Every entity is relative to the entity with the [`AnimationGraph`]. An
+/// empty [`EntityPath`] animates the graph's own entity.
+///
+/// [`AnimationGraph`]: crate::graph::AnimationGraph
+pub struct UiProperty;
+
+impl UiProperty {
+    /// A field of a [`Style`], such as `"size.width"` or `"margin.left"`.
+    ///
+    /// # Panics
+    /// This will panic if `field` isn't a syntactically valid field path.
+    /// Whether the field exists is only checked when the clip is validated
+    /// against a hierarchy or applied.
+    pub fn style(entity: EntityPath, field: &str) -> PropertyPath {
+        match FieldPath::parse(field) {
+            Ok(_) => property::<Style>(entity, field),
+            Err(err) => panic!("Invalid field path '{}': {}", field, err),
+        }
+    }
+
+    /// The width of a node, animated as a [`Val`](bevy_ui::Val).
+    pub fn width(entity: EntityPath) -> PropertyPath {
+        property::<Style>(entity, "size.width")
+    }
+
+    /// The height of a node, animated as a [`Val`](bevy_ui::Val).
+    pub fn height(entity: EntityPath) -> PropertyPath {
+        property::<Style>(entity, "size.height")
+    }
+
+    /// The width and height of a node, animated as a
+    /// [`Size<Val>`](bevy_math::Size).
+    pub fn size(entity: EntityPath) -> PropertyPath {
+        property::<Style>(entity, "size")
+    }
+
+    /// The position of a node, animated as a [`Rect<Val>`](bevy_math::Rect).
+    pub fn position(entity: EntityPath) -> PropertyPath {
+        property::<Style>(entity, "position")
+    }
+
+    /// The margin of a node, animated as a [`Rect<Val>`](bevy_math::Rect).
+    pub fn margin(entity: EntityPath) -> PropertyPath {
+        property::<Style>(entity, "margin")
+    }
+
+    /// The padding of a node, animated as a [`Rect<Val>`](bevy_math::Rect).
+    pub fn padding(entity: EntityPath) -> PropertyPath {
+        property::<Style>(entity, "padding")
+    }
+
+    /// The background color of a node, animated as a [`UiColor`].
+    pub fn color(entity: EntityPath) -> PropertyPath {
+        PropertyPath::from_parts(
+            entity,
+            AccessPath::from_parts(
+                TypeId::of::<UiColor>(),
+                std::any::type_name::<UiColor>(),
+                FieldPath::root(),
+            ),
+        )
+    }
+}
@@ -0,0 +1,145 @@
+//! Counts heap allocations made by the graph, and the memory kept alive by
+//! clips. This replaces the global allocator, so it's kept in its own test
+//! binary, away from the timing and behaviour of the other tests.
+
+use bevy_math::Vec3;
+use bevy_prototype_animation::{
+    curve::CurveFixed,
+    graph::NodeId,
+    path::{AccessPath, EntityPath, PathInterner, PropertyPath},
+    prelude::*,
+};
+use bevy_transform::prelude::Transform;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = Cell::new(0);
+    static LIVE_BYTES: Cell<isize> = Cell::new(0);
+}
+
+// SAFE: Defers to the system allocator for everything.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        let _ = LIVE_BYTES.try_with(|bytes| bytes.set(bytes.get() + layout.size() as isize));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let _ = LIVE_BYTES.try_with(|bytes| bytes.set(bytes.get() - layout.size() as isize));
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The number of allocations made by the current thread so far.
+fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+/// The number of bytes allocated by the current thread that it hasn't freed
+/// yet.
+fn live_bytes() -> isize {
+    LIVE_BYTES.with(|bytes| bytes.get())
+}
+
+fn translation_path(bone: &str) -> PropertyPath {
+    let entity: EntityPath = bone.parse().unwrap();
+    PropertyPath::from_parts(entity, AccessPath::of::<Transform>("translation").unwrap())
+}
+
+#[test]
+fn test_evaluate_is_allocation_free_and_deterministic() {
+    let curve = CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::ONE]);
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path("a"), curve)
+        .build();
+    let mut graph = AnimationGraph::new();
+    let mut nodes = Vec::new();
+    for weight in [1.0, 0.3, 0.1, 0.7] {
+        let node = graph.add_clip(&clip).unwrap();
+        graph
+            .add_input(NodeId::ROOT, node)
+            .unwrap()
+            .set_weight(weight);
+        nodes.push(node);
+    }
+    // Reach the first clip through multiple paths.
+    let random = graph.add_random(false).unwrap();
+    graph.add_input(NodeId::ROOT, random).unwrap();
+    graph.add_input(random, nodes[0]).unwrap();
+
+    let weights = |graph: &AnimationGraph| -> Vec<u32> {
+        nodes
+            .iter()
+            .map(|node| graph.clip_weight(*node).unwrap().to_bits())
+            .collect()
+    };
+
+    // Warm up the scratch buffers.
+    graph.evaluate();
+    assert!(graph.set_time(NodeId::ROOT, 0.5).is_ok());
+    let expected = weights(&graph);
+
+    let before = allocations();
+    for _ in 0..1000 {
+        assert!(graph.set_time(NodeId::ROOT, 0.5).is_ok());
+        graph.evaluate();
+    }
+    assert_eq!(allocations(), before);
+    assert_eq!(weights(&graph), expected);
+}
+
+/// Builds a clip animating every bone of a skeleton, parsing each of its
+/// paths separately, like clips loaded one at a time.
+fn skeleton_clip(bones: usize) -> AnimationClip {
+    let mut builder = AnimationClip::builder();
+    for bone in 0..bones {
+        let curve = CurveFixed::from_keyframes(1.0, vec![Vec3::ZERO, Vec3::ONE]);
+        builder = builder.add_curve(translation_path(&format!("root/hips/bone_{}", bone)), curve);
+    }
+    builder.build()
+}
+
+#[test]
+fn test_shared_paths_reduce_clip_memory() {
+    const CLIPS: usize = 16;
+    const BONES: usize = 100;
+
+    let start = live_bytes();
+    let mut clips: Vec<_> = (0..CLIPS).map(|_| skeleton_clip(BONES)).collect();
+    let separate = live_bytes() - start;
+
+    let mut interner = PathInterner::new();
+    for clip in clips.iter_mut() {
+        clip.share_paths(&mut interner);
+    }
+    // The interner is counted, as it keeps the shared paths alive.
+    let shared = live_bytes() - start;
+    assert_eq!(interner.entity_count(), BONES);
+    assert_eq!(interner.access_count(), 1);
+    assert!(
+        shared * 4 < separate * 3,
+        "sharing paths only reduced the clips from {} to {} bytes",
+        separate,
+        shared
+    );
+
+    // Graphs share the paths of the clips' bones as well.
+    let mut graph = AnimationGraph::new();
+    for clip in clips.iter() {
+        graph.add_clip(clip).unwrap();
+    }
+    let path: EntityPath = "root/hips/bone_7".parse().unwrap();
+    let id = graph.bone_id(&path).unwrap();
+    let bone = graph.get_bone(id).unwrap();
+    assert_eq!(bone.path(), &path);
+    assert_eq!(graph.paths().entity_count(), BONES);
+}
@@ -0,0 +1,2005 @@
+//! Exercises the full pipeline, from clips through graph evaluation and
+//! binding to application, in a headless app.
+
+use bevy_app::{App, CoreStage};
+use bevy_asset::{AddAsset, AssetPlugin, Assets, Handle};
+use bevy_core::{CorePlugin, Name};
+use bevy_ecs::prelude::*;
+use bevy_math::{Quat, Vec3};
+use bevy_prototype_animation::{
+    clip::{ClipCurve, ClipValidationErrorKind},
+    curve::{Curve, CurveFixed, Tween},
+    diagnostics::AnimationDiagnostics,
+    graph::{
+        application::{apply_clip_to_world, AnimationBudget, AnimationPriority, BoneBinding},
+        hierarchy::BindAnimationGraphExt,
+        pose::{PoseBuffer, PoseValue},
+        transition::AnimationGraphTransition,
+        AnimationGraph, ClipId, Easing, GraphState, NodeId, RootTransformPolicy, Track, TrackError,
+        WriteMask,
+    },
+    ik::IkConstraint,
+    path::{AccessPath, EntityPath, PropertyPath},
+    prelude::*,
+    target::AnimationTargetAppExt,
+    test_utils::{property_path, step, test_app, TestHierarchy},
+    AnimationPlugin, AnimationSystem, WorldResources,
+};
+use bevy_reflect::{Reflect, TypeRegistry, TypeRegistryArc, TypeUuid};
+use bevy_tasks::{ComputeTaskPool, TaskPoolBuilder};
+use bevy_transform::{
+    prelude::{BuildChildren, Children, GlobalTransform, Transform},
+    TransformPlugin,
+};
+use bevy_utils::Hashed;
+
+use std::{
+    any::{Any, TypeId},
+    str::FromStr,
+    sync::Arc,
+};
+
+const DELTA: f32 = 0.1;
+
+fn translation_path(app: &App, entity: &str) -> PropertyPath {
+    property_path(
+        app,
+        &format!(
+            "{}@bevy_transform::components::transform::Transform.translation",
+            entity
+        ),
+    )
+}
+
+fn translations(offset: f32) -> CurveFixed<Vec3> {
+    CurveFixed::from_keyframes(
+        4.0,
+        (0..=8)
+            .map(|idx| Vec3::new(idx as f32, offset, -(idx as f32)))
+            .collect(),
+    )
+}
+
+fn spawn_graph(app: &mut App, hierarchy: &TestHierarchy, clip: &AnimationClip) {
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(clip).unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+}
+
+fn assert_close(actual: Vec3, expected: Vec3) {
+    assert!(
+        actual.abs_diff_eq(expected, 1e-5),
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn test_translation_clip_matches_curve_sampling() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body/arm"]);
+    let (body, arm) = (hierarchy.entity("body"), hierarchy.entity("body/arm"));
+    let (body_curve, arm_curve) = (translations(1.0), translations(2.0));
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body"), body_curve.clone())
+        .add_curve(translation_path(&app, "body/arm"), arm_curve.clone())
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+
+    let mut time = 0.0;
+    for _ in 0..15 {
+        step(&mut app, DELTA);
+        time += DELTA;
+        let body_translation = app.world.get::<Transform>(body).unwrap().translation;
+        let arm_translation = app.world.get::<Transform>(arm).unwrap().translation;
+        assert_close(body_translation, body_curve.sample(time));
+        assert_close(arm_translation, arm_curve.sample(time));
+        // The animated transforms are propagated in the same frame.
+        let global = app.world.get::<GlobalTransform>(arm).unwrap().translation;
+        assert_close(global, body_curve.sample(time) + arm_curve.sample(time));
+    }
+}
+
+#[test]
+fn test_empty_entity_paths_animate_the_graph_root() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body"]);
+    let (root, body) = (hierarchy.root(), hierarchy.entity("body"));
+    assert_eq!(hierarchy.entity(""), root);
+    let (root_curve, body_curve) = (translations(1.0), translations(2.0));
+    let root_path = translation_path(&app, "");
+    assert!(root_path.entity().is_root());
+    let clip = AnimationClip::builder()
+        .add_curve(root_path.clone(), root_curve.clone())
+        .add_curve(translation_path(&app, "body"), body_curve.clone())
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+
+    let mut time = 0.0;
+    for _ in 0..5 {
+        step(&mut app, DELTA);
+        time += DELTA;
+        let root_translation = app.world.get::<Transform>(root).unwrap().translation;
+        assert_close(root_translation, root_curve.sample(time));
+        let global = app.world.get::<GlobalTransform>(body).unwrap().translation;
+        assert_close(global, root_curve.sample(time) + body_curve.sample(time));
+    }
+    let graph = app.world.get::<AnimationGraph>(root).unwrap();
+    assert_eq!(graph.bound_entity(root_path.entity()), Some(root));
+}
+
+#[test]
+fn test_root_transform_policy_controls_the_root_transform() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body"]);
+    let (root, body) = (hierarchy.root(), hierarchy.entity("body"));
+    let (root_curve, body_curve) = (translations(1.0), translations(2.0));
+    let clip = AnimationClip::builder()
+        .add_curve(
+            property_path(&app, "@bevy_transform::components::transform::Transform"),
+            CurveFixed::from_keyframes(
+                4.0,
+                (0..=8)
+                    .map(|idx| Transform::from_translation(root_curve.keyframes[idx]))
+                    .collect(),
+            ),
+        )
+        .add_curve(translation_path(&app, "body"), body_curve.clone())
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+    let controlled = Transform::from_xyz(5.0, 0.0, 5.0);
+
+    let mut time = 0.0;
+    for policy in [
+        RootTransformPolicy::Apply,
+        RootTransformPolicy::Ignore,
+        RootTransformPolicy::Apply,
+    ] {
+        app.world
+            .get_mut::<AnimationGraph>(root)
+            .unwrap()
+            .set_root_transform_policy(policy);
+        // Stands in for a character controller moving the root.
+        *app.world.get_mut::<Transform>(root).unwrap() = controlled;
+        step(&mut app, DELTA);
+        time += DELTA;
+
+        let root_translation = app.world.get::<Transform>(root).unwrap().translation;
+        match policy {
+            RootTransformPolicy::Apply => assert_close(root_translation, root_curve.sample(time)),
+            RootTransformPolicy::Ignore => assert_eq!(root_translation, controlled.translation),
+        }
+        // Other bones are animated regardless of the policy.
+        let body_translation = app.world.get::<Transform>(body).unwrap().translation;
+        assert_close(body_translation, body_curve.sample(time));
+    }
+}
+
+#[test]
+fn test_typed_paths_are_applied_like_parsed_paths() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["typed", "parsed"]);
+    let curve = translations(3.0);
+    let typed = PropertyPath::from(("typed", AccessPath::of::<Transform>("translation").unwrap()));
+    assert_eq!(typed.access(), translation_path(&app, "parsed").access());
+    let clip = AnimationClip::builder()
+        .add_curve(typed, curve.clone())
+        .add_curve(translation_path(&app, "parsed"), curve)
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+
+    let (typed, parsed) = (hierarchy.entity("typed"), hierarchy.entity("parsed"));
+    for _ in 0..10 {
+        step(&mut app, DELTA);
+        let typed = *app.world.get::<Transform>(typed).unwrap();
+        assert_eq!(typed, *app.world.get::<Transform>(parsed).unwrap());
+        assert_ne!(typed, Transform::identity());
+    }
+}
+
+#[test]
+fn test_single_clips_apply_exact_curve_values() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body"]);
+    let body = hierarchy.entity("body");
+    let translation = translations(0.3);
+    let rotation = CurveFixed::from_keyframes(
+        4.0,
+        (0..=8)
+            .map(|idx| Quat::from_rotation_y(idx as f32 * 0.7))
+            .collect(),
+    );
+    let scale = CurveFixed::from_keyframes(
+        4.0,
+        (0..=8)
+            .map(|idx| Vec3::splat(0.1 + idx as f32 * 0.37))
+            .collect(),
+    );
+    let path = |field: &str| {
+        property_path(
+            &app,
+            &format!(
+                "body@bevy_transform::components::transform::Transform.{}",
+                field
+            ),
+        )
+    };
+    let clip = AnimationClip::builder()
+        .add_curve(path("translation"), translation.clone())
+        .add_curve(path("rotation"), rotation.clone())
+        .add_curve(path("scale"), scale.clone())
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+
+    let mut time = 0.0;
+    for _ in 0..8 {
+        step(&mut app, DELTA);
+        time += DELTA;
+        let transform = app.world.get::<Transform>(body).unwrap();
+        assert_eq!(transform.translation, translation.sample(time));
+        assert_eq!(transform.rotation, rotation.sample(time));
+        assert_eq!(transform.scale, scale.sample(time));
+    }
+}
+
+#[test]
+fn test_tween_clips_match_tween_sampling() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["door"]);
+    let door = hierarchy.entity("door");
+    let path = |field: &str| {
+        property_path(
+            &app,
+            &format!(
+                "door@bevy_transform::components::transform::Transform.{}",
+                field
+            ),
+        )
+    };
+    let translation = Tween::new(
+        Vec3::ZERO,
+        Vec3::new(2.0, 0.0, -1.0),
+        1.0,
+        Easing::QuadInOut,
+    );
+    let rotation = Tween::new(
+        Quat::IDENTITY,
+        Quat::from_rotation_y(1.5),
+        0.8,
+        Easing::CubicOut,
+    );
+    let clip = AnimationClip::builder()
+        .add_curve(path("translation"), translation.clone())
+        .add_curve(path("rotation"), rotation.clone())
+        .add_tween(
+            path("scale"),
+            Vec3::ONE,
+            Vec3::splat(2.0),
+            0.5,
+            Easing::Linear,
+        )
+        .build();
+    assert_eq!(clip.duration(), 1.0);
+    spawn_graph(&mut app, &hierarchy, &clip);
+
+    let mut time = 0.0;
+    for _ in 0..12 {
+        step(&mut app, DELTA);
+        time += DELTA;
+        let transform = *app.world.get::<Transform>(door).unwrap();
+        assert_close(transform.translation, translation.sample(time));
+        let expected = rotation.sample(time).normalize();
+        assert!(transform.rotation.dot(expected).abs() > 1.0 - 1e-5);
+        let scale = 1.0 + (time / 0.5).min(1.0);
+        assert_close(transform.scale, Vec3::splat(scale));
+    }
+}
+
+#[test]
+fn test_whole_transform_curves_match_field_curves() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["whole", "fields"]);
+    let transforms: Vec<_> = (0..=8)
+        .map(|idx| {
+            let idx = idx as f32;
+            Transform {
+                translation: Vec3::new(idx, 1.0, -idx),
+                rotation: Quat::from_rotation_y(0.4 * idx),
+                scale: Vec3::splat(1.0 + 0.1 * idx),
+            }
+        })
+        .collect();
+    let field_curve = |field: fn(&Transform) -> _| {
+        CurveFixed::from_keyframes(4.0, transforms.iter().map(field).collect::<Vec<Vec3>>())
+    };
+    let rotations = CurveFixed::from_keyframes(
+        4.0,
+        transforms
+            .iter()
+            .map(|transform| transform.rotation)
+            .collect(),
+    );
+    let field_path = |field: &str| {
+        property_path(
+            &app,
+            &format!(
+                "fields@bevy_transform::components::transform::Transform.{}",
+                field
+            ),
+        )
+    };
+    let clip = AnimationClip::builder()
+        .add_curve(
+            property_path(
+                &app,
+                "whole@bevy_transform::components::transform::Transform",
+            ),
+            CurveFixed::from_keyframes(4.0, transforms.clone()),
+        )
+        .add_curve(
+            field_path("translation"),
+            field_curve(|transform| transform.translation),
+        )
+        .add_curve(field_path("rotation"), rotations)
+        .add_curve(
+            field_path("scale"),
+            field_curve(|transform| transform.scale),
+        )
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+
+    let (whole, fields) = (hierarchy.entity("whole"), hierarchy.entity("fields"));
+    for _ in 0..12 {
+        step(&mut app, DELTA);
+        let whole = *app.world.get::<Transform>(whole).unwrap();
+        let fields = *app.world.get::<Transform>(fields).unwrap();
+        assert_close(whole.translation, fields.translation);
+        assert_close(whole.scale, fields.scale);
+        assert!(whole.rotation.abs_diff_eq(fields.rotation, 1e-5));
+        assert_ne!(whole, Transform::identity());
+    }
+}
+
+fn blend_whole_transform_and_translation(whole_first: bool, priority: i32) -> Transform {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body"]);
+    let whole = Transform {
+        translation: Vec3::splat(5.0),
+        rotation: Quat::from_rotation_y(0.5),
+        scale: Vec3::splat(2.0),
+    };
+    let (entity, access) = property_path(
+        &app,
+        "body@bevy_transform::components::transform::Transform",
+    )
+    .into_parts();
+    let whole_clip = AnimationClip::builder()
+        .add_curve(
+            PropertyPath::from_parts(entity, access.with_priority(priority)),
+            CurveFixed::from_constant(whole),
+        )
+        .build();
+    let translation_clip = AnimationClip::builder()
+        .add_curve(
+            translation_path(&app, "body"),
+            CurveFixed::from_constant(Vec3::X),
+        )
+        .build();
+    let clips = if whole_first {
+        [whole_clip, translation_clip]
+    } else {
+        [translation_clip, whole_clip]
+    };
+
+    let mut graph = AnimationGraph::new();
+    for clip in clips.iter() {
+        let node = graph.add_clip(clip).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+    }
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+    step(&mut app, DELTA);
+    *app.world
+        .get::<Transform>(hierarchy.entity("body"))
+        .unwrap()
+}
+
+#[test]
+fn test_field_tracks_refine_whole_component_tracks() {
+    for whole_first in [true, false] {
+        let transform = blend_whole_transform_and_translation(whole_first, 0);
+        assert_close(transform.translation, Vec3::X);
+        assert_close(transform.scale, Vec3::splat(2.0));
+        assert!(transform
+            .rotation
+            .abs_diff_eq(Quat::from_rotation_y(0.5), 1e-5));
+
+        // A higher priority applies the whole component last instead.
+        let transform = blend_whole_transform_and_translation(whole_first, 1);
+        assert_close(transform.translation, Vec3::splat(5.0));
+    }
+}
+
+#[test]
+fn test_bones_are_bound_to_the_hierarchy() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body/arm", "body/leg"]);
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body/arm"), translations(0.0))
+        .add_curve(translation_path(&app, "body/tail"), translations(0.0))
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+    step(&mut app, DELTA);
+
+    let arm = hierarchy.entity("body/arm");
+    let binding = app.world.get::<BoneBinding>(arm).unwrap();
+    assert_eq!(binding.graph(), hierarchy.root());
+    assert!(app
+        .world
+        .get::<BoneBinding>(hierarchy.entity("body"))
+        .is_none());
+    assert!(app
+        .world
+        .get::<BoneBinding>(hierarchy.entity("body/leg"))
+        .is_none());
+    let graph = app.world.get::<AnimationGraph>(hierarchy.root()).unwrap();
+    assert_eq!(graph.bound_entity(&"body/arm".parse().unwrap()), Some(arm));
+    assert_eq!(graph.bound_entity(&"body/tail".parse().unwrap()), None);
+}
+
+#[test]
+fn test_bones_spawned_later_are_bound() {
+    let mut app = test_app();
+    let mut hierarchy = TestHierarchy::spawn(&mut app.world, &["body"]);
+    let curve = translations(0.0);
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body/arm"), curve.clone())
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+    step(&mut app, DELTA);
+
+    let arm = hierarchy.spawn_child(&mut app.world, "body", "arm");
+    step(&mut app, DELTA);
+    let translation = app.world.get::<Transform>(arm).unwrap().translation;
+    assert_close(translation, curve.sample(DELTA + DELTA));
+}
+
+#[test]
+fn test_unchanged_graphs_are_not_reapplied() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body"]);
+    let body = hierarchy.entity("body");
+    let curve = translations(0.0);
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body"), curve.clone())
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+    step(&mut app, DELTA);
+
+    // Without advancing the graph, it isn't changed, evaluated, or applied.
+    let overridden = Transform::from_rotation(Quat::from_rotation_y(1.0));
+    *app.world.get_mut::<Transform>(body).unwrap() = overridden;
+    app.update();
+    assert_eq!(*app.world.get::<Transform>(body).unwrap(), overridden);
+
+    step(&mut app, DELTA);
+    let transform = app.world.get::<Transform>(body).unwrap();
+    assert_close(transform.translation, curve.sample(DELTA + DELTA));
+    // Only the animated field is overwritten.
+    assert_eq!(transform.rotation, overridden.rotation);
+}
+
+/// Counts the frames in which any graph was changed.
+#[derive(Default)]
+struct ChangedGraphFrames(usize);
+
+fn read_graphs_system(graphs: Query<&AnimationGraph>) {
+    let mut pose = PoseBuffer::new();
+    for graph in graphs.iter() {
+        for bone in graph.bones() {
+            let bone = graph.find_bone(bone.path()).unwrap();
+            assert!(graph.bound_entity(bone.path()).is_some());
+            assert!(graph.total_influence(bone.path()) > 0.0);
+        }
+        assert_eq!(graph.params().count(), 1);
+        assert_eq!(graph.get_param("speed"), Some(1.0));
+        let node = graph
+            .inputs(NodeId::ROOT)
+            .unwrap()
+            .next()
+            .unwrap()
+            .node_id();
+        assert_eq!(graph.clip_weight(node).unwrap(), 1.0);
+        assert_eq!(graph.input(NodeId::ROOT, node).unwrap().weight(), 1.0);
+        graph.sample_pose(&mut pose);
+    }
+}
+
+fn count_changed_graphs_system(
+    graphs: Query<(), Changed<AnimationGraph>>,
+    mut frames: ResMut<ChangedGraphFrames>,
+) {
+    if !graphs.is_empty() {
+        frames.0 += 1;
+    }
+}
+
+#[test]
+fn test_reading_graphs_does_not_change_them() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body"]);
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body"), translations(0.0))
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+    app.world
+        .get_mut::<AnimationGraph>(hierarchy.root())
+        .unwrap()
+        .set_param("speed", 1.0);
+    app.init_resource::<ChangedGraphFrames>()
+        .add_system(read_graphs_system.after(AnimationSystem::GraphEvaluation))
+        .add_system_to_stage(CoreStage::Last, count_changed_graphs_system);
+    step(&mut app, DELTA);
+    step(&mut app, DELTA);
+    let changed = app.world.get_resource::<ChangedGraphFrames>().unwrap().0;
+    assert_eq!(changed, 2);
+
+    // Only reading the graphs doesn't mark them as changed.
+    for _ in 0..3 {
+        app.update();
+    }
+    let frames = app.world.get_resource::<ChangedGraphFrames>().unwrap();
+    assert_eq!(frames.0, changed);
+}
+
+#[test]
+fn test_track_stats_find_constant_tracks() {
+    let mut app = test_app();
+    let bones = ["a", "b", "c", "d"];
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &bones);
+    let mut builder = AnimationClip::builder();
+    for (idx, bone) in bones.iter().enumerate() {
+        let path = translation_path(&app, bone);
+        builder = if idx % 2 == 0 {
+            builder.add_curve(path, translations(idx as f32))
+        } else {
+            builder.add_curve(path, CurveFixed::from_constant(Vec3::splat(idx as f32)))
+        };
+    }
+    spawn_graph(&mut app, &hierarchy, &builder.build());
+    let root = hierarchy.root();
+    app.world
+        .get_mut::<AnimationGraph>(root)
+        .unwrap()
+        .enable_track_stats(true);
+
+    for _ in 0..5 {
+        step(&mut app, DELTA);
+    }
+    let graph = app.world.get::<AnimationGraph>(root).unwrap();
+    let stats: Vec<_> = graph.track_stats().collect();
+    assert_eq!(stats.len(), bones.len());
+    for (path, stats) in stats {
+        assert_eq!(stats.samples, 5);
+        let idx = bones
+            .iter()
+            .position(|bone| path == translation_path(&app, bone))
+            .unwrap();
+        if idx % 2 == 0 {
+            assert!(stats.max_delta > 0.1, "{}: {:?}", path, stats);
+        } else {
+            assert_eq!(stats.max_delta, 0.0, "{}: {:?}", path, stats);
+            assert_eq!(stats.skipped, 4, "{}: {:?}", path, stats);
+        }
+    }
+
+    let mut graph = app.world.get_mut::<AnimationGraph>(root).unwrap();
+    graph.reset_track_stats();
+    assert_eq!(graph.track_stats().count(), 0);
+    graph.enable_track_stats(false);
+    step(&mut app, DELTA);
+    let graph = app.world.get::<AnimationGraph>(root).unwrap();
+    assert_eq!(graph.track_stats().count(), 0);
+}
+
+#[test]
+fn test_bones_bound_to_the_same_entity_are_all_applied() {
+    let mut app = test_app();
+    // "a/shared" and "b/shared" are the same entity, listed in the children of
+    // both of its parents.
+    let mut named = |name: &str, children: &[Entity]| {
+        let mut entity = app.world.spawn();
+        entity.insert_bundle((Transform::identity(), GlobalTransform::identity()));
+        entity.insert(Name::new(name.to_string()));
+        if !children.is_empty() {
+            entity.insert(Children::with(children));
+        }
+        entity.id()
+    };
+    let shared = named("shared", &[]);
+    let (a, b) = (named("a", &[shared]), named("b", &[shared]));
+    let root = app
+        .world
+        .spawn()
+        .insert_bundle((Transform::identity(), GlobalTransform::identity()))
+        .insert(Children::with(&[a, b]))
+        .id();
+    let scale = property_path(
+        &app,
+        "b/shared@bevy_transform::components::transform::Transform.scale",
+    );
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "a/shared"), translations(1.0))
+        .add_curve(scale.clone(), CurveFixed::from_constant(Vec3::splat(2.0)))
+        .build();
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(&clip).unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+    graph.enable_track_stats(true);
+    app.world.entity_mut(root).insert(graph);
+
+    step(&mut app, DELTA);
+    let transform = app.world.get::<Transform>(shared).unwrap();
+    assert_close(transform.translation, translations(1.0).sample(DELTA));
+    assert_close(transform.scale, Vec3::splat(2.0));
+    let diagnostics = app.world.get_resource::<AnimationDiagnostics>().unwrap();
+    assert_eq!(diagnostics.bones_applied, 2);
+    assert_eq!(diagnostics.tracks_sampled, 2);
+    let graph = app.world.get::<AnimationGraph>(root).unwrap();
+    let mut stats: Vec<_> = graph.track_stats().collect();
+    stats.sort_by_key(|(path, _)| path.to_string());
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0].0, translation_path(&app, "a/shared"));
+    assert_eq!(stats[1].0, scale);
+    assert!(stats.iter().all(|(_, stats)| stats.samples == 1));
+}
+
+#[test]
+fn test_diagnostics_count_the_applied_tracks() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body", "body/arm"]);
+    let rotation = property_path(
+        &app,
+        "body@bevy_transform::components::transform::Transform.rotation",
+    );
+    let rotations =
+        CurveFixed::from_keyframes(1.0, vec![Quat::IDENTITY, Quat::from_rotation_y(1.0)]);
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body"), translations(0.0))
+        .add_curve(rotation, rotations)
+        .add_curve(
+            translation_path(&app, "body/arm"),
+            CurveFixed::from_constant(Vec3::ONE),
+        )
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+
+    step(&mut app, DELTA);
+    let diagnostics = app.world.get_resource::<AnimationDiagnostics>().unwrap();
+    assert_eq!(diagnostics.graphs_evaluated, 1);
+    // The root and the clip.
+    assert_eq!(diagnostics.nodes_visited, 2);
+    assert_eq!(diagnostics.active_clips, 1);
+    assert_eq!(diagnostics.bones_applied, 2);
+    assert_eq!(diagnostics.tracks_sampled, 3);
+    assert_eq!(diagnostics.tracks_skipped, 0);
+
+    // The arm's translation is constant, so it's only written once.
+    step(&mut app, DELTA);
+    let diagnostics = app.world.get_resource::<AnimationDiagnostics>().unwrap();
+    assert_eq!(diagnostics.bones_applied, 2);
+    assert_eq!(diagnostics.tracks_sampled, 3);
+    assert_eq!(diagnostics.tracks_skipped, 1);
+
+    // The counters are reset every frame, so frames without changed graphs
+    // count nothing.
+    app.update();
+    let diagnostics = app.world.get_resource::<AnimationDiagnostics>().unwrap();
+    assert_eq!(diagnostics.graphs_evaluated, 0);
+    assert_eq!(diagnostics.bones_applied, 0);
+    assert_eq!(diagnostics.tracks_sampled, 0);
+}
+
+/// The pose of the "arm" entity, as seen by a system in `CoreStage::Update`.
+#[derive(Default)]
+struct ObservedPose {
+    local: Vec3,
+    global: Vec3,
+}
+
+fn observe_arm_system(
+    bones: Query<(&Name, &Transform, &GlobalTransform)>,
+    mut observed: ResMut<ObservedPose>,
+) {
+    for (name, transform, global) in bones.iter() {
+        if name.as_str() == "arm" {
+            observed.local = transform.translation;
+            observed.global = global.translation;
+        }
+    }
+}
+
+/// Runs the translation clip of `test_translation_clip_matches_curve_sampling`
+/// with `plugin`, and returns the pose observed in `CoreStage::Update` and the
+/// arm's curve time of every frame.
+fn observe_arm_poses(plugin: AnimationPlugin) -> Vec<(ObservedPose, f32)> {
+    let mut app = App::new();
+    app.add_plugin(CorePlugin)
+        .add_plugin(TransformPlugin)
+        .add_plugin(AssetPlugin)
+        .add_plugin(plugin)
+        .init_resource::<ObservedPose>()
+        .add_system(observe_arm_system);
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body/arm"]);
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body"), translations(1.0))
+        .add_curve(translation_path(&app, "body/arm"), translations(2.0))
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+
+    let mut time = 0.0;
+    (0..5)
+        .map(|_| {
+            step(&mut app, DELTA);
+            time += DELTA;
+            let observed = app.world.get_resource::<ObservedPose>().unwrap();
+            (
+                ObservedPose {
+                    local: observed.local,
+                    global: observed.global,
+                },
+                time,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_early_application_is_visible_in_update() {
+    let (body_curve, arm_curve) = (translations(1.0), translations(2.0));
+    for (observed, time) in observe_arm_poses(AnimationPlugin::early()) {
+        assert_close(observed.local, arm_curve.sample(time));
+        assert_close(
+            observed.global,
+            body_curve.sample(time) + arm_curve.sample(time),
+        );
+    }
+
+    // By default, graphs are applied at the end of the update stage, so
+    // systems in it see the pose of the previous frame.
+    for (observed, time) in observe_arm_poses(AnimationPlugin::default())
+        .into_iter()
+        .skip(1)
+    {
+        let previous = time - DELTA;
+        assert_close(observed.local, arm_curve.sample(previous));
+        assert_close(
+            observed.global,
+            body_curve.sample(previous) + arm_curve.sample(previous),
+        );
+    }
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct Health {
+    value: f32,
+}
+
+#[test]
+fn test_reflected_components_are_animated() {
+    let mut app = test_app();
+    app.register_type::<Health>();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body"]);
+    let body = hierarchy.entity("body");
+    app.world.entity_mut(body).insert(Health::default());
+    let curve = CurveFixed::from_keyframes(1.0, vec![0.0f32, 10.0, 20.0]);
+    let path = property_path(&app, "body@pipeline::Health.value");
+    let clip = AnimationClip::builder()
+        .add_curve(path, curve.clone())
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+
+    let mut time = 0.0;
+    for _ in 0..5 {
+        step(&mut app, 0.25);
+        time += 0.25;
+        let value = app.world.get::<Health>(body).unwrap().value;
+        assert!((value - curve.sample(time)).abs() < 1e-5);
+    }
+}
+
+#[derive(Reflect, Default)]
+struct Fog {
+    density: f32,
+}
+
+#[derive(Reflect, TypeUuid, Default)]
+#[uuid = "5f1c3b9e-8d2a-4e6f-a0b7-3c9d1e2f4a58"]
+struct Tint {
+    strength: f32,
+}
+
+#[test]
+fn test_resources_and_assets_are_animated() {
+    let mut app = test_app();
+    app.add_asset::<Tint>()
+        .insert_resource(Fog::default())
+        .register_animated_resource::<Fog>()
+        .register_animated_asset::<Tint>();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body"]);
+    let (root, body) = (hierarchy.root(), hierarchy.entity("body"));
+    let tint = app
+        .world
+        .get_resource_mut::<Assets<Tint>>()
+        .unwrap()
+        .add(Tint::default());
+    app.world.entity_mut(body).insert(tint.clone());
+
+    let fog_curve = CurveFixed::from_keyframes(1.0, vec![0.0f32, 1.0, 4.0]);
+    let tint_curve = CurveFixed::from_keyframes(1.0, vec![2.0f32, 0.0, 1.0]);
+    let fog_path = property_path(&app, "@res:pipeline::Fog.density");
+    let tint_path = property_path(
+        &app,
+        &format!(
+            "body@asset:{}#strength",
+            std::any::type_name::<Handle<Tint>>()
+        ),
+    );
+    assert_eq!(
+        tint_path.access(),
+        &AccessPath::of_asset::<Handle<Tint>>("", "strength").unwrap()
+    );
+    let clip = AnimationClip::builder()
+        .add_curve(fog_path, fog_curve.clone())
+        .add_curve(tint_path, tint_curve.clone())
+        .build();
+    {
+        let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+        assert!(clip.validate(&registry.read()).is_empty());
+    }
+    spawn_graph(&mut app, &hierarchy, &clip);
+
+    let mut time = 0.0;
+    for _ in 0..6 {
+        step(&mut app, 0.25);
+        time += 0.25;
+        let density = app.world.get_resource::<Fog>().unwrap().density;
+        assert!((density - fog_curve.sample(time)).abs() < 1e-5);
+        let tints = app.world.get_resource::<Assets<Tint>>().unwrap();
+        let strength = tints.get(&tint).unwrap().strength;
+        assert!((strength - tint_curve.sample(time)).abs() < 1e-5);
+    }
+
+    let registry = app.world.get_resource::<TypeRegistryArc>().unwrap();
+    let graph = app.world.get::<AnimationGraph>(root).unwrap();
+    assert!(graph
+        .validate_against(&registry.read(), &app.world, root)
+        .is_empty());
+}
+
+#[test]
+fn test_sampled_poses_match_applied_values() {
+    let mut app = test_app();
+    app.register_type::<Health>();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body/arm", "whole"]);
+    let (body, arm, whole) = (
+        hierarchy.entity("body"),
+        hierarchy.entity("body/arm"),
+        hierarchy.entity("whole"),
+    );
+    app.world.entity_mut(body).insert(Health::default());
+    let paths = [
+        translation_path(&app, "body"),
+        property_path(
+            &app,
+            "body/arm@bevy_transform::components::transform::Transform.rotation",
+        ),
+        property_path(&app, "body@pipeline::Health.value"),
+        property_path(
+            &app,
+            "whole@bevy_transform::components::transform::Transform",
+        ),
+    ];
+    let clip = |offset: f32| {
+        AnimationClip::builder()
+            .add_curve(paths[0].clone(), translations(offset))
+            .add_curve(
+                paths[1].clone(),
+                CurveFixed::from_keyframes(
+                    4.0,
+                    (0..=8)
+                        .map(|idx| Quat::from_rotation_y(offset + 0.2 * idx as f32))
+                        .collect(),
+                ),
+            )
+            .add_curve(
+                paths[2].clone(),
+                CurveFixed::from_keyframes(1.0, vec![offset, 10.0 * offset]),
+            )
+            .add_curve(
+                paths[3].clone(),
+                CurveFixed::from_keyframes(
+                    4.0,
+                    (0..=8)
+                        .map(|idx| Transform::from_xyz(offset, idx as f32, 0.0))
+                        .collect(),
+                ),
+            )
+            .build()
+    };
+    let mut graph = AnimationGraph::new();
+    for offset in [1.0, 3.0] {
+        let node = graph.add_clip(&clip(offset)).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+    }
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+
+    let mut pose = PoseBuffer::new();
+    for _ in 0..6 {
+        step(&mut app, DELTA);
+        let graph = app.world.get::<AnimationGraph>(hierarchy.root()).unwrap();
+        graph.sample_pose(&mut pose);
+        assert_eq!(pose.len(), paths.len());
+
+        let applied = app.world.get::<Transform>(body).unwrap().translation;
+        match pose.get(paths[0].entity(), paths[0].access()) {
+            Some(PoseValue::Vec3(value)) => assert_close(*value, applied),
+            _ => panic!("expected a sampled Vec3"),
+        }
+        let applied = app.world.get::<Transform>(arm).unwrap().rotation;
+        let sampled = pose.value::<Quat>(&paths[1]).unwrap();
+        assert!(sampled.abs_diff_eq(applied, 1e-5));
+        let applied = app.world.get::<Health>(body).unwrap().value;
+        assert!((pose.value::<f32>(&paths[2]).unwrap() - applied).abs() < 1e-5);
+        let applied = *app.world.get::<Transform>(whole).unwrap();
+        match pose.get(paths[3].entity(), paths[3].access()) {
+            Some(PoseValue::Transform(value)) => {
+                assert_close(value.translation, applied.translation);
+                assert_ne!(applied, Transform::identity());
+            }
+            _ => panic!("expected a sampled Transform"),
+        }
+    }
+}
+
+#[test]
+fn test_budget_spreads_bindings_over_frames() {
+    let mut app = test_app();
+    app.register_type::<Health>();
+    let names: Vec<String> = (0..10).map(|idx| format!("b{}", idx)).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    // The priority graph is only bound to some of the bones.
+    let (names, priority_names) = (&names[..], &names[..3]);
+    let hierarchy = TestHierarchy::spawn(&mut app.world, names);
+    let priority = TestHierarchy::spawn(&mut app.world, priority_names);
+    let curve = CurveFixed::from_keyframes(1.0, vec![0.0f32, 10.0]);
+    let mut builder = AnimationClip::builder();
+    for name in names {
+        let path = property_path(&app, &format!("{}@pipeline::Health.value", name));
+        builder = builder.add_curve(path, curve.clone());
+    }
+    let clip = builder.build();
+    for (hierarchy, names) in [(&hierarchy, names), (&priority, priority_names)] {
+        for name in names {
+            let entity = hierarchy.entity(name);
+            app.world.entity_mut(entity).insert(Health::default());
+        }
+        spawn_graph(&mut app, hierarchy, &clip);
+    }
+    app.world
+        .entity_mut(priority.root())
+        .insert(AnimationPriority);
+    // Only the first batch of bindings fits in the budget.
+    app.world.insert_resource(AnimationBudget {
+        max_micros_per_frame: 0,
+    });
+
+    let written = |app: &App, hierarchy: &TestHierarchy, names: &[&str], time: f32| {
+        names
+            .iter()
+            .map(|name| {
+                let value = app
+                    .world
+                    .get::<Health>(hierarchy.entity(name))
+                    .unwrap()
+                    .value;
+                (value - curve.sample(time)).abs() < 1e-5
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut written_once = vec![false; names.len()];
+    let mut time = 0.0;
+    for _ in 0..2 {
+        step(&mut app, DELTA);
+        time += DELTA;
+        let written_now = written(&app, &hierarchy, names, time);
+        assert_eq!(written_now.iter().filter(|written| **written).count(), 5);
+        for (once, now) in written_once.iter_mut().zip(written_now) {
+            *once |= now;
+        }
+        assert!(written(&app, &priority, priority_names, time)
+            .into_iter()
+            .all(|written| written));
+    }
+    // The skipped half was written in the second frame.
+    assert!(written_once.into_iter().all(|written| written));
+}
+
+#[test]
+fn test_budgeted_bindings_are_all_eventually_written() {
+    let mut app = test_app();
+    app.register_type::<Health>();
+    let names: Vec<String> = (0..64).map(|idx| format!("b{}", idx)).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &names);
+    let curve = CurveFixed::from_keyframes(1.0, vec![1.0f32, 10.0]);
+    let mut builder = AnimationClip::builder();
+    for name in names.iter() {
+        let path = property_path(&app, &format!("{}@pipeline::Health.value", name));
+        builder = builder.add_curve(path, curve.clone());
+    }
+    for name in names.iter() {
+        app.world
+            .entity_mut(hierarchy.entity(name))
+            .insert(Health { value: -1.0 });
+    }
+    spawn_graph(&mut app, &hierarchy, &builder.build());
+    app.world.insert_resource(AnimationBudget {
+        max_micros_per_frame: 50,
+    });
+
+    // At least one batch of 8 is written each frame, and the skipped
+    // bindings are written first on the next, so every binding is written
+    // within 8 frames however the budget falls.
+    for _ in 0..8 {
+        step(&mut app, DELTA);
+    }
+    for name in names.iter() {
+        let value = app
+            .world
+            .get::<Health>(hierarchy.entity(name))
+            .unwrap()
+            .value;
+        assert!(value > 0.0, "'{}' was never written", name);
+    }
+}
+
+/// A curve for a custom track that blends to the largest value of the clips
+/// animating it, ignoring their weights.
+#[derive(Clone)]
+struct MaxCurve(Arc<CurveFixed<f32>>);
+
+impl ClipCurve for MaxCurve {
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<f32>()
+    }
+    fn value_type_name(&self) -> &'static str {
+        std::any::type_name::<f32>()
+    }
+    fn duration(&self) -> f32 {
+        self.0.duration()
+    }
+    fn keyframe_count(&self) -> usize {
+        self.0.keyframe_count()
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn into_track(&self, clip_id: ClipId) -> Box<dyn Track> {
+        Box::new(MaxTrack {
+            curves: vec![(clip_id, self.0.clone())],
+            rest: None,
+        })
+    }
+    fn curve_ptr(&self) -> *const () {
+        Arc::as_ptr(&self.0) as *const ()
+    }
+    fn sample_reflect(&self, time: f32) -> Box<dyn Reflect> {
+        Box::new(self.0.sample(time))
+    }
+    fn clone_curve(&self) -> Box<dyn ClipCurve> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+struct MaxTrack {
+    curves: Vec<(ClipId, Arc<CurveFixed<f32>>)>,
+    rest: Option<f32>,
+}
+
+impl MaxTrack {
+    fn blend(&self, state: &GraphState) -> f32 {
+        self.curves
+            .iter()
+            .filter(|(clip_id, _)| state.clip_weight(*clip_id) != 0.0)
+            .map(|(clip_id, curve)| curve.sample(state.clip_sample_time(*clip_id)))
+            .chain(self.rest)
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    fn write(value: f32, output: &mut dyn Reflect) -> Result<bool, TrackError> {
+        let output = output
+            .downcast_mut::<f32>()
+            .ok_or_else(|| TrackError::incorrect_type::<f32>("another type"))?;
+        let written = *output != value;
+        *output = value;
+        Ok(written)
+    }
+}
+
+impl Track for MaxTrack {
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<f32>()
+    }
+    fn value_type_name(&self) -> &'static str {
+        std::any::type_name::<f32>()
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn clone_track(&self) -> Box<dyn Track> {
+        Box::new(self.clone())
+    }
+    fn sample_clip_boxed(&self, clip_id: ClipId, time: f32) -> Option<Box<dyn Reflect>> {
+        let (_, curve) = self.curves.iter().find(|(id, _)| *id == clip_id)?;
+        Some(Box::new(curve.sample(time)))
+    }
+    fn blend_boxed(&self, state: &GraphState) -> Box<dyn Reflect> {
+        Box::new(self.blend(state))
+    }
+    fn add_generic_curve(
+        &mut self,
+        clip_id: ClipId,
+        curve: &dyn ClipCurve,
+    ) -> Result<(), TrackError> {
+        let curve = curve
+            .as_any()
+            .downcast_ref::<MaxCurve>()
+            .ok_or_else(|| TrackError::incorrect_type::<MaxCurve>(curve.value_type_name()))?;
+        self.curves.retain(|(id, _)| *id != clip_id);
+        self.curves.push((clip_id, curve.0.clone()));
+        Ok(())
+    }
+    fn animates_clip(&self, clip_id: ClipId) -> bool {
+        self.curves.iter().any(|(id, _)| *id == clip_id)
+    }
+    fn remove_clip_curves(
+        &mut self,
+        access: &AccessPath,
+        clip_id: ClipId,
+        keep: &dyn Fn(&AccessPath) -> bool,
+    ) -> bool {
+        if !keep(access) {
+            self.curves.retain(|(id, _)| *id != clip_id);
+        }
+        self.curves.is_empty()
+    }
+    fn add_snapshot(&mut self, pose_id: ClipId, value: &dyn Reflect) -> Result<(), TrackError> {
+        let value = value
+            .downcast_ref::<f32>()
+            .ok_or_else(|| TrackError::incorrect_type::<f32>(value.type_name()))?;
+        self.curves.retain(|(id, _)| *id != pose_id);
+        self.curves
+            .push((pose_id, Arc::new(CurveFixed::from_constant(*value))));
+        Ok(())
+    }
+    fn set_rest_value(&mut self, value: &dyn Reflect) -> Result<(), TrackError> {
+        let value = value
+            .downcast_ref::<f32>()
+            .ok_or_else(|| TrackError::incorrect_type::<f32>(value.type_name()))?;
+        self.rest = Some(*value);
+        Ok(())
+    }
+    fn clear_rest_value(&mut self) {
+        self.rest = None;
+    }
+    fn blend_via_reflect(
+        &self,
+        state: &GraphState,
+        output: &mut dyn Reflect,
+        _resources: WorldResources<'_>,
+    ) -> Result<bool, TrackError> {
+        Self::write(self.blend(state), output)
+    }
+    fn write_blended(
+        &self,
+        value: &dyn Reflect,
+        output: &mut dyn Reflect,
+        _resources: WorldResources<'_>,
+    ) -> Result<bool, TrackError> {
+        let value = value
+            .downcast_ref::<f32>()
+            .ok_or_else(|| TrackError::incorrect_type::<f32>(value.type_name()))?;
+        Self::write(*value, output)
+    }
+}
+
+#[test]
+fn test_custom_tracks_are_blended_with_their_own_blend() {
+    let mut app = test_app();
+    app.register_type::<Health>();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body"]);
+    let body = hierarchy.entity("body");
+    app.world.entity_mut(body).insert(Health::default());
+    let path = property_path(&app, "body@pipeline::Health.value");
+    let rising = Arc::new(CurveFixed::from_keyframes(4.0, vec![0.0f32, 4.0, 8.0]));
+    let clips = [rising.clone(), Arc::new(CurveFixed::from_constant(3.0))].map(|curve| {
+        AnimationClip::builder()
+            .add_custom_curve(path.clone(), Box::new(MaxCurve(curve)))
+            .build()
+    });
+    let mut graph = AnimationGraph::new();
+    for node in graph.add_clips(&clips).unwrap() {
+        graph.add_input(NodeId::ROOT, node).unwrap().set_weight(0.5);
+    }
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+
+    let mut time = 0.0;
+    for _ in 0..4 {
+        step(&mut app, DELTA);
+        time += DELTA;
+        // A weighted blend would land halfway between the clips.
+        let value = app.world.get::<Health>(body).unwrap().value;
+        assert!((value - rising.sample(time).max(3.0)).abs() < 1e-5);
+    }
+
+    // Curves of other types can't be added to the custom track.
+    let typed = AnimationClip::builder()
+        .add_curve(path, CurveFixed::from_constant(1.0f32))
+        .build();
+    let mut graph = app
+        .world
+        .get_mut::<AnimationGraph>(hierarchy.root())
+        .unwrap();
+    assert!(graph.add_clip(&typed).is_err());
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct Blendshapes {
+    weights: Vec<f32>,
+}
+
+#[test]
+fn test_split_bones_match_unsplit_bones() {
+    const WEIGHTS: usize = 24;
+    let mut app = test_app();
+    app.register_type::<Blendshapes>();
+    let mut hierarchy = TestHierarchy::spawn(&mut app.world, &["face"]);
+    let mut split = TestHierarchy::spawn(&mut app.world, &["face"]);
+    let mut builder = AnimationClip::builder();
+    for idx in 0..WEIGHTS {
+        let path = format!("face@pipeline::Blendshapes.weights[{}]", idx);
+        let keyframes = (0..8).map(|frame| (frame * idx) as f32 * 0.1).collect();
+        builder = builder.add_curve(
+            property_path(&app, &path),
+            CurveFixed::from_keyframes(4.0, keyframes),
+        );
+    }
+    let clip = builder
+        .add_curve(translation_path(&app, "face"), translations(0.0))
+        .build();
+    for (hierarchy, threshold) in [(&mut hierarchy, None), (&mut split, Some(5))] {
+        app.world
+            .entity_mut(hierarchy.entity("face"))
+            .insert(Blendshapes {
+                weights: vec![0.0; WEIGHTS],
+            });
+        let mut graph = AnimationGraph::new();
+        let node = graph.add_clip(&clip).unwrap();
+        graph.add_input(NodeId::ROOT, node).unwrap();
+        graph.set_track_split_threshold(threshold);
+        app.world.entity_mut(hierarchy.root()).insert(graph);
+    }
+
+    let (face, split_face) = (hierarchy.entity("face"), split.entity("face"));
+    for _ in 0..6 {
+        step(&mut app, DELTA);
+        let weights = &app.world.get::<Blendshapes>(face).unwrap().weights;
+        let split_weights = &app.world.get::<Blendshapes>(split_face).unwrap().weights;
+        assert_eq!(weights, split_weights);
+        assert_ne!(weights[WEIGHTS - 1], 0.0);
+        assert_eq!(
+            app.world.get::<Transform>(face),
+            app.world.get::<Transform>(split_face)
+        );
+    }
+}
+
+#[test]
+fn test_morph_curves_blend_each_weight() {
+    let mut app = test_app();
+    app.register_type::<Blendshapes>();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["face"]);
+    let face = hierarchy.entity("face");
+    let initial = vec![0.1, 0.2, 0.3, 0.4, 0.5];
+    app.world.entity_mut(face).insert(Blendshapes {
+        weights: initial.clone(),
+    });
+    let path = property_path(&app, "face@pipeline::Blendshapes.weights");
+    let constant =
+        |value: f32| -> Arc<dyn Curve<f32>> { Arc::new(CurveFixed::from_constant(value)) };
+    let rising = Arc::new(CurveFixed::from_keyframes(4.0, vec![0.0f32, 0.5, 1.0]));
+    let clips = [
+        vec![
+            (3, rising.clone() as Arc<dyn Curve<f32>>),
+            (0, constant(1.0)),
+        ],
+        // Index 7 is out of range, and is skipped.
+        vec![(0, constant(0.0)), (3, constant(0.5)), (7, constant(1.0))],
+    ]
+    .map(|curves| {
+        AnimationClip::builder()
+            .add_morph_curves(path.clone(), curves)
+            .build()
+    });
+    let mut graph = AnimationGraph::new();
+    for node in graph.add_clips(&clips).unwrap() {
+        graph.add_input(NodeId::ROOT, node).unwrap().set_weight(0.5);
+    }
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+
+    let mut time = 0.0;
+    for _ in 0..4 {
+        step(&mut app, DELTA);
+        time += DELTA;
+        let weights = &app.world.get::<Blendshapes>(face).unwrap().weights;
+        assert_eq!(weights.len(), initial.len());
+        assert!((weights[0] - 0.5).abs() < 1e-5);
+        assert!((weights[3] - (0.5 * rising.sample(time) + 0.25)).abs() < 1e-5);
+        for idx in [1, 2, 4] {
+            assert_eq!(weights[idx], initial[idx]);
+        }
+    }
+}
+
+#[test]
+fn test_masked_bones_are_left_to_gameplay_code() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body/arm"]);
+    let (body, arm) = (hierarchy.entity("body"), hierarchy.entity("body/arm"));
+    let (body_curve, arm_curve) = (translations(1.0), translations(2.0));
+    let arm_path = translation_path(&app, "body/arm");
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body"), body_curve.clone())
+        .add_curve(arm_path.clone(), arm_curve.clone())
+        .build();
+    spawn_graph(&mut app, &hierarchy, &clip);
+    step(&mut app, DELTA);
+
+    let ik = Transform::from_xyz(0.0, -5.0, 0.0);
+    *app.world.get_mut::<Transform>(arm).unwrap() = ik;
+    let mut graph = app
+        .world
+        .get_mut::<AnimationGraph>(hierarchy.root())
+        .unwrap();
+    graph.disable_bone(arm_path.entity()).unwrap();
+
+    let mut time = DELTA;
+    for _ in 0..3 {
+        step(&mut app, DELTA);
+        time += DELTA;
+        let body_translation = app.world.get::<Transform>(body).unwrap().translation;
+        assert_close(body_translation, body_curve.sample(time));
+        assert_eq!(*app.world.get::<Transform>(arm).unwrap(), ik);
+        // The bone stays bound while masked.
+        assert!(app.world.get::<BoneBinding>(arm).is_some());
+    }
+
+    // Masking only the animated property has the same effect.
+    let mut graph = app
+        .world
+        .get_mut::<AnimationGraph>(hierarchy.root())
+        .unwrap();
+    let mask = WriteMask::Exclude(vec![arm_path.access().clone()]);
+    graph.set_bone_write_mask(arm_path.entity(), mask).unwrap();
+    step(&mut app, DELTA);
+    time += DELTA;
+    assert_eq!(*app.world.get::<Transform>(arm).unwrap(), ik);
+
+    let mut graph = app
+        .world
+        .get_mut::<AnimationGraph>(hierarchy.root())
+        .unwrap();
+    graph.enable_bone(arm_path.entity()).unwrap();
+    step(&mut app, DELTA);
+    time += DELTA;
+    let arm_translation = app.world.get::<Transform>(arm).unwrap().translation;
+    assert_close(arm_translation, arm_curve.sample(time));
+}
+
+/// The rotations of the "arm" and "arm/forearm" joints and the translation of
+/// "arm/forearm/hand" after reaching with an [`IkConstraint`] of `weight`.
+fn reach_with_arm(weight: f32, chain: [&str; 2]) -> (Quat, Quat, Vec3) {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["arm/forearm/hand"]);
+    // A straight chain of two bones, each 1 unit long.
+    let mut builder = AnimationClip::builder();
+    for (bone, offset) in [
+        ("arm", 0.0),
+        ("arm/forearm", 1.0),
+        ("arm/forearm/hand", 1.0),
+    ] {
+        builder = builder.add_curve(
+            translation_path(&app, bone),
+            CurveFixed::from_constant(Vec3::new(offset, 0.0, 0.0)),
+        );
+        let rotation = format!(
+            "{}@bevy_transform::components::transform::Transform.rotation",
+            bone
+        );
+        builder = builder.add_curve(
+            property_path(&app, &rotation),
+            CurveFixed::from_constant(Quat::IDENTITY),
+        );
+    }
+    spawn_graph(&mut app, &hierarchy, &builder.build());
+    let target = app
+        .world
+        .spawn()
+        .insert_bundle((
+            Transform::from_xyz(0.0, 1.5, 0.0),
+            GlobalTransform::identity(),
+        ))
+        .id();
+    app.world.spawn().insert(IkConstraint {
+        graph: hierarchy.root(),
+        chain: chain.map(|path| EntityPath::from_str(path).unwrap()),
+        target,
+        weight,
+    });
+    step(&mut app, DELTA);
+
+    let rotation = |path| {
+        app.world
+            .get::<Transform>(hierarchy.entity(path))
+            .unwrap()
+            .rotation
+    };
+    let hand = app
+        .world
+        .get::<GlobalTransform>(hierarchy.entity("arm/forearm/hand"))
+        .unwrap();
+    (rotation("arm"), rotation("arm/forearm"), hand.translation)
+}
+
+#[test]
+fn test_ik_constraints_reach_their_target() {
+    let chain = ["arm", "arm/forearm/hand"];
+    let (arm, forearm, hand) = reach_with_arm(1.0, chain);
+    assert_close(hand, Vec3::new(0.0, 1.5, 0.0));
+    assert!(!arm.abs_diff_eq(Quat::IDENTITY, 1e-3));
+    assert!(!forearm.abs_diff_eq(Quat::IDENTITY, 1e-3));
+
+    // Partial weights blend the solved rotations with the animated ones.
+    let (half_arm, half_forearm, half_hand) = reach_with_arm(0.5, chain);
+    assert!(half_arm.abs_diff_eq(Quat::IDENTITY.slerp(arm, 0.5), 1e-5));
+    assert!(half_forearm.abs_diff_eq(Quat::IDENTITY.slerp(forearm, 0.5), 1e-5));
+    assert!(half_hand.distance(Vec3::new(0.0, 1.5, 0.0)) > 1e-3);
+    assert!(half_hand.distance(Vec3::new(2.0, 0.0, 0.0)) > 1e-3);
+
+    // Chains that aren't bound by the graph are left alone.
+    let (arm, forearm, hand) = reach_with_arm(1.0, ["arm", "arm/forearm/finger"]);
+    assert_eq!(arm, Quat::IDENTITY);
+    assert_eq!(forearm, Quat::IDENTITY);
+    assert_close(hand, Vec3::new(2.0, 0.0, 0.0));
+}
+
+#[test]
+fn test_modified_clip_assets_are_reloaded() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body/arm", "body/leg"]);
+    let (body, arm, leg) = (
+        hierarchy.entity("body"),
+        hierarchy.entity("body/arm"),
+        hierarchy.entity("body/leg"),
+    );
+    let rotation_path = property_path(
+        &app,
+        "body@bevy_transform::components::transform::Transform.rotation",
+    );
+    let rotations =
+        CurveFixed::from_keyframes(1.0, vec![Quat::IDENTITY, Quat::from_rotation_x(1.0)]);
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body"), translations(1.0))
+        .add_curve(rotation_path, rotations.clone())
+        .add_curve(translation_path(&app, "body/arm"), translations(2.0))
+        .build();
+    let handle = app
+        .world
+        .get_resource_mut::<Assets<AnimationClip>>()
+        .unwrap()
+        .add(clip);
+    let mut graph = AnimationGraph::new();
+    let node = graph
+        .add_clip_asset(
+            &handle,
+            app.world.get_resource::<Assets<AnimationClip>>().unwrap(),
+        )
+        .unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap().set_weight(0.5);
+    assert_eq!(graph.clip_asset(node), Some(&handle));
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+    for _ in 0..3 {
+        step(&mut app, DELTA);
+    }
+
+    // The new version moves the body differently, stops rotating it, tries to
+    // change the type of the arm's translation, and animates the leg.
+    let (new_body_curve, leg_curve) = (translations(5.0), translations(3.0));
+    let new_clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body"), new_body_curve.clone())
+        .add_curve(translation_path(&app, "body/arm"), rotations)
+        .add_curve(translation_path(&app, "body/leg"), leg_curve.clone())
+        .build();
+    *app.world
+        .get_resource_mut::<Assets<AnimationClip>>()
+        .unwrap()
+        .get_mut(&handle)
+        .unwrap() = new_clip;
+    // The modification event is sent at the end of the frame, so the old
+    // curves are sampled once more.
+    step(&mut app, DELTA);
+    let old_translation = translations(1.0).sample(0.4) * 0.5;
+    let body_translation = app.world.get::<Transform>(body).unwrap().translation;
+    assert_close(body_translation, old_translation);
+
+    let overridden = Quat::from_rotation_y(1.0);
+    app.world.get_mut::<Transform>(body).unwrap().rotation = overridden;
+    step(&mut app, DELTA);
+    let graph = app.world.get::<AnimationGraph>(hierarchy.root()).unwrap();
+    assert!((graph.clip_time(node).unwrap() - 0.5).abs() < 1e-5);
+    let body_transform = app.world.get::<Transform>(body).unwrap();
+    assert_close(body_transform.translation, new_body_curve.sample(0.5) * 0.5);
+    assert_eq!(body_transform.rotation, overridden);
+    // The arm keeps its old curve, as the type change is rejected.
+    let arm_translation = app.world.get::<Transform>(arm).unwrap().translation;
+    assert_close(arm_translation, translations(2.0).sample(0.5) * 0.5);
+    // The new bone is bound and animated in the same frame.
+    assert!(app.world.get::<BoneBinding>(leg).is_some());
+    let leg_translation = app.world.get::<Transform>(leg).unwrap().translation;
+    assert_close(leg_translation, leg_curve.sample(0.5) * 0.5);
+}
+
+#[test]
+fn test_idle_lazy_clips_are_evicted_and_restored() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body"]);
+    let body = hierarchy.entity("body");
+    let path = translation_path(&app, "body");
+    let curve = translations(1.0);
+    let clip = AnimationClip::builder()
+        .add_curve(path.clone(), curve.clone())
+        .build();
+    let handle = app
+        .world
+        .get_resource_mut::<Assets<AnimationClip>>()
+        .unwrap()
+        .add(clip);
+    let clip_curve = |app: &App| {
+        let clips = app.world.get_resource::<Assets<AnimationClip>>().unwrap();
+        clips
+            .get(&handle)
+            .unwrap()
+            .get_curve::<Vec3>(&Hashed::new(path.clone()))
+            .unwrap()
+    };
+    let mut graph = AnimationGraph::new();
+    let node = graph
+        .add_lazy_clip_asset(
+            &handle,
+            app.world.get_resource::<Assets<AnimationClip>>().unwrap(),
+            0.5,
+        )
+        .unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+    let resident_bytes = graph.resident_curve_bytes();
+    assert!(resident_bytes > 0);
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+    fn lazy_graph(app: &mut App, root: Entity) -> Mut<'_, AnimationGraph> {
+        app.world.get_mut::<AnimationGraph>(root).unwrap()
+    }
+    let root = hierarchy.root();
+    let translation = |app: &App| app.world.get::<Transform>(body).unwrap().translation;
+
+    step(&mut app, DELTA);
+    assert_close(translation(&app), curve.sample(DELTA));
+    // Held by the clip, the graph's track, and here.
+    assert_eq!(Arc::strong_count(&clip_curve(&app)), 3);
+
+    lazy_graph(&mut app, root)
+        .input_mut(NodeId::ROOT, node)
+        .unwrap()
+        .set_weight(0.0);
+    for _ in 0..5 {
+        step(&mut app, DELTA);
+        assert!(!lazy_graph(&mut app, root).is_clip_evicted(node).unwrap());
+    }
+    step(&mut app, DELTA);
+    assert!(lazy_graph(&mut app, root).is_clip_evicted(node).unwrap());
+    assert_eq!(Arc::strong_count(&clip_curve(&app)), 2);
+    assert!(lazy_graph(&mut app, root).resident_curve_bytes() < resident_bytes);
+    // The bone is kept, so the body stays bound.
+    assert_eq!(
+        lazy_graph(&mut app, root).bound_entity(path.entity()),
+        Some(body)
+    );
+
+    // The clip contributes nothing while its curves are restored.
+    lazy_graph(&mut app, root)
+        .input_mut(NodeId::ROOT, node)
+        .unwrap()
+        .set_weight(1.0);
+    let held = translation(&app);
+    step(&mut app, DELTA);
+    assert_close(translation(&app), held);
+    step(&mut app, DELTA);
+    assert!(!lazy_graph(&mut app, root).is_clip_evicted(node).unwrap());
+    assert_eq!(Arc::strong_count(&clip_curve(&app)), 3);
+    assert_eq!(
+        lazy_graph(&mut app, root).resident_curve_bytes(),
+        resident_bytes
+    );
+    let time = lazy_graph(&mut app, root).clip_time(node).unwrap();
+    assert_close(translation(&app), curve.sample(time));
+}
+
+/// Checks that `value` lies on the segment from `from` to `to`, returning how
+/// far along it is.
+fn segment_progress(value: Vec3, from: Vec3, to: Vec3) -> f32 {
+    let span = to - from;
+    let t = (value - from).dot(span) / span.length_squared();
+    assert!(
+        (-1e-5..=1.0 + 1e-5).contains(&t),
+        "{} is not between {} and {}",
+        value,
+        from,
+        to
+    );
+    assert_close(value, from + span * t);
+    t
+}
+
+#[test]
+fn test_graph_transitions_crossfade_from_the_current_pose() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body/arm", "body/leg"]);
+    let (body, arm, leg) = (
+        hierarchy.entity("body"),
+        hierarchy.entity("body/arm"),
+        hierarchy.entity("body/leg"),
+    );
+    let constant = |value: Vec3| CurveFixed::from_keyframes(1.0, vec![value; 2]);
+    let (old_body, old_arm) = (Vec3::new(1.0, 2.0, 3.0), Vec3::new(0.0, -1.0, 0.0));
+    let (new_body, new_leg) = (Vec3::new(-4.0, 0.0, 2.0), Vec3::new(0.0, 5.0, 0.0));
+    let old_clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body"), constant(old_body))
+        .add_curve(translation_path(&app, "body/arm"), constant(old_arm))
+        .build();
+    spawn_graph(&mut app, &hierarchy, &old_clip);
+    // The leg isn't animated by the old graph.
+    let old_leg = Vec3::new(2.0, 2.0, 2.0);
+    app.world.get_mut::<Transform>(leg).unwrap().translation = old_leg;
+    step(&mut app, DELTA);
+
+    let new_clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body"), constant(new_body))
+        .add_curve(translation_path(&app, "body/leg"), constant(new_leg))
+        .build();
+    let mut new_graph = AnimationGraph::new();
+    let node = new_graph.add_clip(&new_clip).unwrap();
+    new_graph.add_input(NodeId::ROOT, node).unwrap();
+    app.world
+        .entity_mut(hierarchy.root())
+        .insert(AnimationGraphTransition::new(new_graph, 0.5));
+
+    // The new graph is bound and applied in the same frame, starting from the
+    // old pose.
+    step(&mut app, DELTA);
+    assert!(app.world.get::<BoneBinding>(leg).is_some());
+    assert!(app.world.get::<BoneBinding>(arm).is_none());
+    let body_translation = app.world.get::<Transform>(body).unwrap().translation;
+    assert_close(body_translation, old_body);
+    let leg_translation = app.world.get::<Transform>(leg).unwrap().translation;
+    assert_close(leg_translation, old_leg);
+
+    let mut progress = 0.0;
+    for _ in 0..5 {
+        step(&mut app, DELTA);
+        let body_translation = app.world.get::<Transform>(body).unwrap().translation;
+        let leg_translation = app.world.get::<Transform>(leg).unwrap().translation;
+        let body_progress = segment_progress(body_translation, old_body, new_body);
+        let leg_progress = segment_progress(leg_translation, old_leg, new_leg);
+        assert!((body_progress - leg_progress).abs() < 1e-4);
+        assert!(body_progress >= progress);
+        progress = body_progress;
+        // The arm is left where the old graph left it.
+        let arm_translation = app.world.get::<Transform>(arm).unwrap().translation;
+        assert_close(arm_translation, old_arm);
+    }
+
+    step(&mut app, DELTA);
+    let body_translation = app.world.get::<Transform>(body).unwrap().translation;
+    assert_close(body_translation, new_body);
+    let leg_translation = app.world.get::<Transform>(leg).unwrap().translation;
+    assert_close(leg_translation, new_leg);
+    assert!(app
+        .world
+        .get::<AnimationGraphTransition>(hierarchy.root())
+        .is_none());
+}
+
+#[test]
+fn test_parallel_application_writes_each_entity_once() {
+    // Scaled down so that it finishes in a reasonable time under miri. The
+    // application system reads the clock, so run it with
+    // `MIRIFLAGS=-Zmiri-disable-isolation cargo +nightly miri test --test pipeline`.
+    let (count, steps) = if cfg!(miri) { (12, 2) } else { (512, 8) };
+    let mut app = test_app();
+    app.register_type::<Health>();
+    app.world.insert_resource(ComputeTaskPool(
+        TaskPoolBuilder::new().num_threads(4).build(),
+    ));
+    let paths: Vec<_> = (0..count).map(|idx| format!("bone{}", idx)).collect();
+    let paths: Vec<_> = paths.iter().map(String::as_str).collect();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &paths);
+
+    // Every entity has two components animated by its own curves.
+    let mut builder = AnimationClip::builder();
+    for (idx, path) in paths.iter().enumerate() {
+        app.world
+            .entity_mut(hierarchy.entity(path))
+            .insert(Health::default());
+        builder = builder
+            .add_curve(translation_path(&app, path), translations(idx as f32))
+            .add_curve(
+                property_path(&app, &format!("{}@pipeline::Health.value", path)),
+                CurveFixed::from_keyframes(1.0, vec![idx as f32, -(idx as f32)]),
+            );
+    }
+    spawn_graph(&mut app, &hierarchy, &builder.build());
+
+    let mut time = 0.0;
+    for _ in 0..steps {
+        step(&mut app, DELTA);
+        time += DELTA;
+        for (idx, path) in paths.iter().enumerate() {
+            let entity = hierarchy.entity(path);
+            let translation = app.world.get::<Transform>(entity).unwrap().translation;
+            assert_close(translation, translations(idx as f32).sample(time));
+            let health = app.world.get::<Health>(entity).unwrap().value;
+            let expected = idx as f32 * (1.0 - 2.0 * time);
+            assert!((health - expected).abs() < 1e-4);
+        }
+    }
+}
+
+fn arm_graph(app: &App) -> AnimationGraph {
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path(app, "body/arm"), translations(1.0))
+        .build();
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(&clip).unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+    graph
+}
+
+/// A graph to spawn from a system, along with its root.
+struct PendingGraph(Option<(Entity, AnimationGraph)>);
+
+fn spawn_pending_graph_system(mut pending: ResMut<PendingGraph>, mut commands: Commands) {
+    if let Some((root, graph)) = pending.0.take() {
+        commands
+            .entity(root)
+            .insert_bundle(AnimationGraphBundle::new(graph));
+    }
+}
+
+#[test]
+fn test_auto_bound_graphs_are_applied_in_the_frame_they_are_spawned() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body/arm"]);
+    let arm = hierarchy.entity("body/arm");
+    let graph = arm_graph(&app);
+    app.insert_resource(PendingGraph(Some((hierarchy.root(), graph))))
+        .add_system(spawn_pending_graph_system);
+
+    // The graph is only added once the animation systems have already run.
+    app.update();
+    let expected = translations(1.0).sample(0.0);
+    assert_close(
+        app.world.get::<Transform>(arm).unwrap().translation,
+        expected,
+    );
+    let global = app.world.get::<GlobalTransform>(arm).unwrap().translation;
+    assert_close(global, expected);
+
+    step(&mut app, DELTA);
+    let expected = translations(1.0).sample(DELTA);
+    assert_close(
+        app.world.get::<Transform>(arm).unwrap().translation,
+        expected,
+    );
+}
+
+#[test]
+fn test_bound_graphs_are_applied_immediately() {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["body/arm"]);
+    let arm = hierarchy.entity("body/arm");
+    let mut graph = arm_graph(&app);
+    graph.bind(hierarchy.root(), &mut app.world);
+    let expected = translations(1.0).sample(0.0);
+    assert_close(
+        app.world.get::<Transform>(arm).unwrap().translation,
+        expected,
+    );
+    assert_eq!(
+        app.world.get::<BoneBinding>(arm).map(BoneBinding::graph),
+        Some(hierarchy.root())
+    );
+
+    // Graphs already in the world are bound in place by a command.
+    app.world.get_mut::<Transform>(arm).unwrap().translation = Vec3::ZERO;
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+    let mut queue = bevy_ecs::system::CommandQueue::default();
+    Commands::new(&mut queue, &app.world)
+        .entity(hierarchy.root())
+        .bind_animation_graph();
+    queue.apply(&mut app.world);
+    assert_close(
+        app.world.get::<Transform>(arm).unwrap().translation,
+        expected,
+    );
+}
+
+/// A chain of entities spawned one per frame beneath `parent`, like a scene
+/// being instantiated over several frames.
+struct PendingChain {
+    parent: Entity,
+    names: Vec<&'static str>,
+    unnamed: Option<(Entity, &'static str)>,
+}
+
+fn spawn_chain_system(mut chain: ResMut<PendingChain>, mut commands: Commands) {
+    // Names land the frame after their entity is parented.
+    if let Some((entity, name)) = chain.unnamed.take() {
+        commands.entity(entity).insert(Name::new(name));
+    }
+    if chain.names.is_empty() {
+        return;
+    }
+    let name = chain.names.remove(0);
+    let entity = commands
+        .spawn_bundle((Transform::identity(), GlobalTransform::identity()))
+        .id();
+    commands.entity(chain.parent).push_children(&[entity]);
+    chain.parent = entity;
+    chain.unnamed = Some((entity, name));
+}
+
+#[test]
+fn test_hierarchies_spawned_over_several_frames_are_bound() {
+    let mut app = test_app();
+    let paths = ["body", "body/arm", "body/arm/hand"];
+    let mut builder = AnimationClip::builder();
+    for (idx, path) in paths.iter().enumerate() {
+        builder = builder.add_curve(translation_path(&app, path), translations(idx as f32));
+    }
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(&builder.build()).unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+    let root = app
+        .world
+        .spawn()
+        .insert_bundle((Transform::identity(), GlobalTransform::identity()))
+        .insert(graph)
+        .id();
+    app.insert_resource(PendingChain {
+        parent: root,
+        names: vec!["body", "arm", "hand"],
+        unnamed: None,
+    })
+    .add_system(spawn_chain_system);
+
+    let mut time = 0.0;
+    for _ in 0..10 {
+        step(&mut app, DELTA);
+        time += DELTA;
+    }
+    let graph = app.world.get::<AnimationGraph>(root).unwrap();
+    for (idx, path) in paths.iter().enumerate() {
+        let entity = graph
+            .find_bone(&path.parse().unwrap())
+            .and_then(|bone| bone.entity())
+            .unwrap_or_else(|| panic!("'{}' was not bound", path));
+        assert_eq!(
+            app.world.get::<BoneBinding>(entity).map(BoneBinding::graph),
+            Some(root)
+        );
+        assert_close(
+            app.world.get::<Transform>(entity).unwrap().translation,
+            translations(idx as f32).sample(time),
+        );
+    }
+}
+
+#[test]
+fn test_clips_are_applied_to_detached_worlds() {
+    let mut world = World::new();
+    let hierarchy = TestHierarchy::spawn(&mut world, &["body/arm"]);
+    let mut registry = TypeRegistry::default();
+    registry.register::<Transform>();
+    let path = |entity: &str, field: &str| {
+        PropertyPath::from_parts(
+            EntityPath::from_str(entity).unwrap(),
+            AccessPath::of::<Transform>(field).unwrap(),
+        )
+    };
+    let translation = translations(1.0);
+    let rotation = CurveFixed::from_keyframes(
+        1.0,
+        vec![
+            Quat::IDENTITY,
+            Quat::from_rotation_y(1.0),
+            Quat::from_rotation_y(2.0),
+        ],
+    );
+    let clip = AnimationClip::builder()
+        .add_curve(path("body", "translation"), translation.clone())
+        .add_curve(path("body/arm", "rotation"), rotation.clone())
+        .add_curve(path("body/leg", "scale"), translations(2.0))
+        .build();
+
+    let (body, arm) = (hierarchy.entity("body"), hierarchy.entity("body/arm"));
+    for time in [0.0, 0.3, 1.25, 2.0] {
+        let errors = apply_clip_to_world(&mut world, hierarchy.root(), &clip, time, &registry);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, path("body/leg", "scale"));
+        assert_eq!(errors[0].kind, ClipValidationErrorKind::MissingEntity);
+        let body = world.get::<Transform>(body).unwrap();
+        let arm = world.get::<Transform>(arm).unwrap();
+        assert_eq!(body.translation, translation.sample(time));
+        assert_eq!(arm.rotation, rotation.sample(time));
+        assert_eq!(arm.translation, Vec3::ZERO);
+    }
+}
+
+#[test]
+fn test_applied_clips_match_the_pipeline() {
+    let mut app = test_app();
+    let animated = TestHierarchy::spawn(&mut app.world, &["body/arm"]);
+    let applied = TestHierarchy::spawn(&mut app.world, &["body/arm"]);
+    let clip = AnimationClip::builder()
+        .add_curve(translation_path(&app, "body"), translations(1.0))
+        .add_curve(translation_path(&app, "body/arm"), translations(2.0))
+        .build();
+    spawn_graph(&mut app, &animated, &clip);
+    let registry = app.world.get_resource::<TypeRegistryArc>().unwrap().clone();
+
+    let mut time = 0.0;
+    for _ in 0..8 {
+        step(&mut app, DELTA);
+        time += DELTA;
+        let errors = apply_clip_to_world(
+            &mut app.world,
+            applied.root(),
+            &clip,
+            time,
+            &registry.read(),
+        );
+        assert!(errors.is_empty());
+        for path in ["body", "body/arm"] {
+            assert_eq!(
+                app.world.get::<Transform>(animated.entity(path)),
+                app.world.get::<Transform>(applied.entity(path))
+            );
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Compares components animated through typed tracks against identical
+//! components animated through reflection.
+
+use bevy_app::{App, CoreStage};
+use bevy_ecs::prelude::*;
+use bevy_math::Vec3;
+use bevy_prototype_animation::{
+    animate_component,
+    curve::CurveFixed,
+    graph::{typed::TypedAnimationPlugin, AnimationGraph, NodeId, WriteMask},
+    path::{AccessPath, EntityPath, FieldPath, PropertyPath},
+    prelude::*,
+    test_utils::{property_path, step, test_app, TestHierarchy},
+};
+use bevy_reflect::Reflect;
+use std::any::TypeId;
+
+#[derive(Component, Reflect, Clone, Default, Debug, PartialEq)]
+#[reflect(Component)]
+struct TypedGlow {
+    intensity: f32,
+    offset: Vec3,
+}
+
+animate_component!(TypedGlow {
+    intensity: f32,
+    offset: Vec3
+});
+
+#[derive(Component, Reflect, Clone, Default, Debug, PartialEq)]
+#[reflect(Component)]
+struct ReflectedGlow {
+    intensity: f32,
+    offset: Vec3,
+}
+
+fn glow_app() -> (App, TestHierarchy) {
+    let mut app = test_app();
+    app.add_plugin(TypedAnimationPlugin::<TypedGlow>::default())
+        .register_type::<ReflectedGlow>();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["lamp"]);
+    app.world
+        .entity_mut(hierarchy.entity("lamp"))
+        .insert(TypedGlow::default())
+        .insert(ReflectedGlow::default());
+    (app, hierarchy)
+}
+
+fn glow_path(app: &App, component: &str, field: &str) -> PropertyPath {
+    property_path(app, &format!("lamp@typed::{}.{}", component, field))
+}
+
+/// Builds a clip animating both components with the same curves.
+fn glow_clip(app: &App, scale: f32) -> AnimationClip {
+    let intensity = CurveFixed::from_keyframes(2.0, vec![0.0, scale, 0.5 * scale, 2.0 * scale]);
+    let offset = CurveFixed::from_keyframes(
+        4.0,
+        (0..8)
+            .map(|idx| Vec3::new(idx as f32, scale, -(idx as f32) * scale))
+            .collect(),
+    );
+    let mut builder = AnimationClip::builder();
+    for component in ["TypedGlow", "ReflectedGlow"] {
+        builder = builder
+            .add_curve(glow_path(app, component, "intensity"), intensity.clone())
+            .add_curve(glow_path(app, component, "offset"), offset.clone());
+    }
+    builder.build()
+}
+
+fn assert_matching(app: &App, hierarchy: &TestHierarchy) {
+    let lamp = hierarchy.entity("lamp");
+    let typed = app.world.get::<TypedGlow>(lamp).unwrap();
+    let reflected = app.world.get::<ReflectedGlow>(lamp).unwrap();
+    assert_eq!(typed.intensity, reflected.intensity);
+    assert_eq!(typed.offset, reflected.offset);
+}
+
+#[test]
+fn test_typed_application_matches_reflection() {
+    let (mut app, hierarchy) = glow_app();
+    let mut graph = AnimationGraph::new();
+    for (scale, weight, additive) in [(1.0, 0.3, false), (3.0, 0.5, false), (0.5, 1.0, true)] {
+        let node = graph.add_clip(&glow_clip(&app, scale)).unwrap();
+        graph
+            .add_input(NodeId::ROOT, node)
+            .unwrap()
+            .set_weight(weight);
+        graph.set_additive(node, additive).unwrap();
+    }
+
+    // The typed fields are routed into a single track for the component.
+    let bone = graph.find_bone(&"lamp".parse().unwrap()).unwrap();
+    let properties: Vec<_> = bone.properties().map(|path| path.to_string()).collect();
+    assert_eq!(properties.len(), 3);
+    assert!(properties.iter().any(|path| path.ends_with("TypedGlow")));
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+
+    for _ in 0..12 {
+        step(&mut app, 0.15);
+        assert_matching(&app, &hierarchy);
+    }
+    let lamp = hierarchy.entity("lamp");
+    assert_ne!(
+        *app.world.get::<TypedGlow>(lamp).unwrap(),
+        TypedGlow::default()
+    );
+}
+
+#[test]
+fn test_typed_fields_are_masked_like_reflected_fields() {
+    let (mut app, hierarchy) = glow_app();
+    let lamp = hierarchy.entity("lamp");
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(&glow_clip(&app, 2.0)).unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+    step(&mut app, 0.1);
+
+    let entity: EntityPath = "lamp".parse().unwrap();
+    let mask = WriteMask::Exclude(vec![
+        glow_path(&app, "TypedGlow", "intensity").access().clone(),
+        glow_path(&app, "ReflectedGlow", "intensity")
+            .access()
+            .clone(),
+    ]);
+    let mut graph = app
+        .world
+        .get_mut::<AnimationGraph>(hierarchy.root())
+        .unwrap();
+    graph.set_bone_write_mask(&entity, mask).unwrap();
+    app.world.get_mut::<TypedGlow>(lamp).unwrap().intensity = -1.0;
+    app.world.get_mut::<ReflectedGlow>(lamp).unwrap().intensity = -1.0;
+    for _ in 0..3 {
+        step(&mut app, 0.1);
+        assert_matching(&app, &hierarchy);
+        assert_eq!(app.world.get::<TypedGlow>(lamp).unwrap().intensity, -1.0);
+    }
+
+    let mut graph = app
+        .world
+        .get_mut::<AnimationGraph>(hierarchy.root())
+        .unwrap();
+    graph.disable_bone(&entity).unwrap();
+    let frozen = app.world.get::<TypedGlow>(lamp).unwrap().clone();
+    step(&mut app, 0.1);
+    assert_eq!(*app.world.get::<TypedGlow>(lamp).unwrap(), frozen);
+
+    let mut graph = app
+        .world
+        .get_mut::<AnimationGraph>(hierarchy.root())
+        .unwrap();
+    graph.enable_bone(&entity).unwrap();
+    step(&mut app, 0.1);
+    assert_matching(&app, &hierarchy);
+    assert_ne!(app.world.get::<TypedGlow>(lamp).unwrap().intensity, -1.0);
+}
+
+#[derive(Default)]
+struct ChangedGlows(usize);
+
+fn count_changed_glows(changed: Query<(), Changed<TypedGlow>>, mut count: ResMut<ChangedGlows>) {
+    count.0 += changed.iter().count();
+}
+
+#[test]
+fn test_unchanged_typed_components_are_not_marked_changed() {
+    let (mut app, hierarchy) = glow_app();
+    app.init_resource::<ChangedGlows>()
+        .add_system_to_stage(CoreStage::PostUpdate, count_changed_glows);
+    let clip = AnimationClip::builder()
+        .add_curve(
+            glow_path(&app, "TypedGlow", "intensity"),
+            CurveFixed::from_constant(2.0f32),
+        )
+        .build();
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(&clip).unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+    step(&mut app, 0.1);
+    let lamp = hierarchy.entity("lamp");
+    assert_eq!(app.world.get::<TypedGlow>(lamp).unwrap().intensity, 2.0);
+
+    // The graph changes every frame, but the blended value doesn't.
+    let changed = app.world.get_resource::<ChangedGlows>().unwrap().0;
+    for _ in 0..3 {
+        step(&mut app, 0.1);
+    }
+    assert_eq!(app.world.get_resource::<ChangedGlows>().unwrap().0, changed);
+
+    app.world.get_mut::<TypedGlow>(lamp).unwrap().intensity = -1.0;
+    step(&mut app, 0.1);
+    assert_eq!(app.world.get::<TypedGlow>(lamp).unwrap().intensity, 2.0);
+    assert_eq!(
+        app.world.get_resource::<ChangedGlows>().unwrap().0,
+        changed + 1
+    );
+}
+
+#[test]
+fn test_typed_rest_values_match_reflection() {
+    let (mut app, hierarchy) = glow_app();
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(&glow_clip(&app, 1.0)).unwrap();
+    graph
+        .add_input(NodeId::ROOT, node)
+        .unwrap()
+        .set_weight(0.25);
+
+    // The typed track animates the whole component, so its rest value is set
+    // for the whole component at once.
+    let (intensity, offset) = (4.0f32, Vec3::new(1.0, 2.0, 3.0));
+    let typed = PropertyPath::from_parts(
+        "lamp".parse().unwrap(),
+        AccessPath::from_parts(
+            TypeId::of::<TypedGlow>(),
+            std::any::type_name::<TypedGlow>(),
+            FieldPath::root(),
+        ),
+    );
+    graph
+        .set_rest_value(&typed, &TypedGlow { intensity, offset })
+        .unwrap();
+    graph
+        .set_rest_value(&glow_path(&app, "ReflectedGlow", "intensity"), &intensity)
+        .unwrap();
+    graph
+        .set_rest_value(&glow_path(&app, "ReflectedGlow", "offset"), &offset)
+        .unwrap();
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+
+    for _ in 0..5 {
+        step(&mut app, 0.2);
+        assert_matching(&app, &hierarchy);
+    }
+}
+
+#[test]
+fn test_typed_components_are_registered_per_app() {
+    let (typed_app, _) = glow_app();
+    let mut app = test_app();
+    app.register_type::<TypedGlow>();
+
+    // Only paths parsed against the app with the plugin are routed.
+    for (app, typed) in [(&typed_app, true), (&app, false)] {
+        let mut graph = AnimationGraph::new();
+        let clip = AnimationClip::builder()
+            .add_curve(
+                glow_path(app, "TypedGlow", "intensity"),
+                CurveFixed::from_constant(1.0f32),
+            )
+            .build();
+        graph.add_clip(&clip).unwrap();
+        let bone = graph.find_bone(&"lamp".parse().unwrap()).unwrap();
+        let properties: Vec<_> = bone.properties().map(|path| path.to_string()).collect();
+        assert_eq!(properties.len(), 1);
+        assert_eq!(properties[0].ends_with("TypedGlow"), typed);
+    }
+}
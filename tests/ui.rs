@@ -0,0 +1,114 @@
+//! Animates UI node properties through the full pipeline in a headless app.
+
+use bevy_math::{Rect, Size};
+use bevy_prototype_animation::{
+    curve::{Curve, CurveFixed},
+    graph::{AnimationGraph, NodeId},
+    path::EntityPath,
+    prelude::*,
+    test_utils::{step, test_app, TestHierarchy},
+    ui::UiProperty,
+};
+use bevy_render::color::Color;
+use bevy_ui::{Style, UiColor, Val};
+
+const DELTA: f32 = 0.25;
+
+fn panel() -> EntityPath {
+    "panel".parse().unwrap()
+}
+
+fn spawn_panel(clip: &AnimationClip) -> (bevy_app::App, bevy_ecs::entity::Entity) {
+    let mut app = test_app();
+    let hierarchy = TestHierarchy::spawn(&mut app.world, &["panel"]);
+    let panel = hierarchy.entity("panel");
+    app.world
+        .entity_mut(panel)
+        .insert(Style::default())
+        .insert(UiColor(Color::rgba_linear(0.0, 0.0, 0.0, 0.0)));
+
+    let mut graph = AnimationGraph::new();
+    let node = graph.add_clip(clip).unwrap();
+    graph.add_input(NodeId::ROOT, node).unwrap();
+    app.world.entity_mut(hierarchy.root()).insert(graph);
+    (app, panel)
+}
+
+#[test]
+fn test_style_fields_follow_curves() {
+    let width = CurveFixed::from_keyframes(1.0, vec![Val::Px(0.0), Val::Px(200.0)]);
+    let margin = CurveFixed::from_keyframes(
+        1.0,
+        vec![Rect::all(Val::Percent(0.0)), Rect::all(Val::Percent(10.0))],
+    );
+    let color = CurveFixed::from_keyframes(
+        1.0,
+        vec![
+            UiColor(Color::rgba_linear(0.0, 0.0, 0.0, 0.0)),
+            UiColor(Color::rgba_linear(1.0, 1.0, 1.0, 1.0)),
+        ],
+    );
+    let clip = AnimationClip::builder()
+        .add_curve(UiProperty::width(panel()), width.clone())
+        .add_curve(UiProperty::margin(panel()), margin.clone())
+        .add_curve(UiProperty::color(panel()), color.clone())
+        .build();
+    let (mut app, panel) = spawn_panel(&clip);
+
+    let mut time = 0.0;
+    let mut widths = Vec::new();
+    for _ in 0..4 {
+        step(&mut app, DELTA);
+        time += DELTA;
+        let style = app.world.get::<Style>(panel).unwrap();
+        assert_eq!(style.size.width, width.sample(time));
+        assert_eq!(style.margin, margin.sample(time));
+        // Only the animated fields are written.
+        assert_eq!(style.size.height, Val::Auto);
+        let alpha = app
+            .world
+            .get::<UiColor>(panel)
+            .unwrap()
+            .0
+            .as_linear_rgba_f32()[3];
+        assert!((alpha - color.sample(time).0.as_linear_rgba_f32()[3]).abs() < 1e-5);
+        widths.push(style.size.width);
+    }
+    assert_eq!(
+        widths,
+        vec![
+            Val::Px(50.0),
+            Val::Px(100.0),
+            Val::Px(150.0),
+            Val::Px(200.0)
+        ]
+    );
+}
+
+#[test]
+fn test_mixed_units_are_stepped() {
+    let size = CurveFixed::from_keyframes(
+        1.0,
+        vec![
+            Size::new(Val::Px(0.0), Val::Auto),
+            Size::new(Val::Percent(100.0), Val::Percent(50.0)),
+        ],
+    );
+    let clip = AnimationClip::builder()
+        .add_curve(UiProperty::size(panel()), size)
+        .build();
+    let (mut app, panel) = spawn_panel(&clip);
+
+    // Values of different units are held until the next keyframe.
+    for _ in 0..3 {
+        step(&mut app, DELTA);
+        let style = app.world.get::<Style>(panel).unwrap();
+        assert_eq!(style.size, Size::new(Val::Px(0.0), Val::Auto));
+    }
+    step(&mut app, DELTA);
+    let style = app.world.get::<Style>(panel).unwrap();
+    assert_eq!(
+        style.size,
+        Size::new(Val::Percent(100.0), Val::Percent(50.0))
+    );
+}